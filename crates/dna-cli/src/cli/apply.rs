@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use dna::services::{ConfigService, Plan, PlanService};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Args)]
+pub struct ApplyArgs {
+    /// Plan file (YAML or JSON) listing add/update/remove/link operations
+    pub plan: PathBuf,
+
+    /// Validate the plan and show the steps without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output the result as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(args: ApplyArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+    let service = super::artifact::create_service().await?;
+    let config = config_service.load()?;
+
+    let text = std::fs::read_to_string(&args.plan)
+        .with_context(|| format!("Failed to read plan file {}", args.plan.display()))?;
+    let plan = Plan::parse(&text)?;
+    let plan_service = PlanService::new(Arc::new(service)).with_labels(config.labels);
+
+    if args.dry_run {
+        let steps = plan_service.validate(&plan).await?;
+        if args.json {
+            println!("{}", serde_json::to_string_pretty(&steps)?);
+            return Ok(());
+        }
+        println!("Plan is valid ({} operations):", steps.len());
+        for step in &steps {
+            println!("  {}. {} {}", step.index, step.action, step.target);
+        }
+        println!();
+        println!("Dry run: no changes made.");
+        return Ok(());
+    }
+
+    let report = plan_service.apply(&plan).await?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    println!("Applied {} operations:", report.steps.len());
+    for step in &report.steps {
+        println!("  {}. {} {}", step.index, step.action, step.target);
+    }
+    println!(
+        "Database version: {} -> {}",
+        report.version_before, report.version_after
+    );
+    Ok(())
+}
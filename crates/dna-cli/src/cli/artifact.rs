@@ -1,8 +1,13 @@
-use super::{parse_metadata, validate_label_keys};
+use super::{parse_metadata, validate_labels};
 use anyhow::Result;
-use clap::Args;
-use dna::services::{ArtifactService, ConfigService, ContentFormat};
-use std::path::PathBuf;
+use clap::{ArgGroup, Args};
+use dna::services::attachment::{self, AttachmentService};
+use dna::services::{
+    parse_date, slugify_kind, Artifact, ArtifactService, Assignment, ChangePreview, ConfigService,
+    ContentFormat, Deprecation, QueuedWrite, SearchFilters, ServiceError,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 #[derive(Args)]
 pub struct AddArgs {
@@ -10,15 +15,22 @@ pub struct AddArgs {
     pub kind: String,
 
     /// Artifact content - the full text to be embedded and stored
-    pub content: String,
+    #[arg(required_unless_present = "edit")]
+    pub content: Option<String>,
+
+    /// Write the content in $EDITOR, starting from the kind's template
+    /// (or from the given content)
+    #[arg(long, short = 'e')]
+    pub edit: bool,
 
     /// Optional name slug for human-readable identification
     #[arg(long)]
     pub name: Option<String>,
 
-    /// Content format [default: markdown] [possible values: markdown, yaml, json, openapi, text]
-    #[arg(long, default_value = "markdown")]
-    pub format: String,
+    /// Content format [default: the kind's format, else markdown]
+    /// [possible values: markdown, yaml, json, openapi, text]
+    #[arg(long)]
+    pub format: Option<String>,
 
     /// Label as key=value pair for filtering and organization.
     /// Can be repeated. Example: --label domain=auth --label priority=high
@@ -30,21 +42,65 @@ pub struct AddArgs {
     /// Gets its own embedding for context-aware search.
     #[arg(long, short = 'c')]
     pub context: Option<String>,
+
+    /// Namespace to scope the artifact to, such as a team or service
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// Person or team accountable for the artifact, such as @payments
+    #[arg(long, value_name = "HANDLE")]
+    pub owner: Option<String>,
+
+    /// Person or team who reviews changes to the artifact
+    /// (comma-separated or repeated)
+    #[arg(long = "reviewer", value_name = "HANDLE", value_delimiter = ',')]
+    pub reviewers: Vec<String>,
+
+    /// Show what would be stored, with token estimates and embedding calls,
+    /// without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 #[derive(Args)]
 pub struct GetArgs {
-    /// Artifact ID
-    pub id: String,
+    /// Artifact IDs or unique ID prefixes; several are printed as a JSON array
+    #[arg(required = true)]
+    pub ids: Vec<String>,
 
     /// Retrieve artifact at specific database version
     #[arg(long)]
     pub version: Option<u64>,
+
+    /// Also write the artifacts' attachments to DIR [default: .]. With
+    /// several IDs, each artifact's go in a subdirectory named after its ID.
+    #[arg(
+        long,
+        value_name = "DIR",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "."
+    )]
+    pub with_attachments: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct AttachArgs {
+    /// Artifact ID (or unique ID prefix) to attach the files to
+    pub id: String,
+
+    /// Files to attach; a file with the name of an existing attachment replaces it
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+
+    /// Output the artifact's attachments as JSON
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(Args)]
 pub struct UpdateArgs {
-    /// Artifact ID to update
+    /// Artifact ID (or unique ID prefix) to update
     pub id: String,
 
     /// New content (replaces existing, triggers re-embedding)
@@ -68,16 +124,84 @@ pub struct UpdateArgs {
     /// Use empty string to remove: --context ""
     #[arg(long, short = 'c')]
     pub context: Option<String>,
+
+    /// New owner, such as @payments. Use empty string to remove: --owner ""
+    #[arg(long, value_name = "HANDLE")]
+    pub owner: Option<String>,
+
+    /// Replace the reviewers (comma-separated or repeated).
+    /// Use empty string to remove them all: --reviewer ""
+    #[arg(long = "reviewer", value_name = "HANDLE", value_delimiter = ',')]
+    pub reviewers: Vec<String>,
+
+    /// Give the artifact a new ID; the old one keeps resolving to it
+    #[arg(long, value_name = "ID", conflicts_with = "dry_run")]
+    pub new_id: Option<String>,
+
+    /// Show what would be stored, with token estimates and embedding calls,
+    /// without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
 }
 
 #[derive(Args)]
 pub struct RemoveArgs {
-    /// Artifact ID
+    /// Artifact ID or unique ID prefix
     pub id: String,
+
+    /// Show what would be removed without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Skip the confirmation prompt
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+#[derive(Args)]
+#[command(group = ArgGroup::new("selection").required(true).args(&["id", "before"]))]
+pub struct ArchiveArgs {
+    /// Artifact ID or unique ID prefix to archive
+    pub id: Option<String>,
+
+    /// Archive every artifact last updated before this date
+    /// (YYYY-MM-DD or RFC3339 datetime)
+    #[arg(long)]
+    pub before: Option<String>,
+
+    /// With --before, only archive artifacts of this kind
+    #[arg(long, requires = "before")]
+    pub kind: Option<String>,
+
+    /// Show what would be archived without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// With --before, skip the confirmation prompt
+    #[arg(long, short)]
+    pub yes: bool,
 }
 
-async fn create_service() -> Result<ArtifactService> {
-    let project_root = PathBuf::from(".");
+#[derive(Args)]
+pub struct DeprecateArgs {
+    /// Artifact ID or unique ID prefix to deprecate
+    pub id: String,
+
+    /// ID of the artifact that replaces it
+    #[arg(long, value_name = "ID")]
+    pub replaced_by: Option<String>,
+
+    /// Why it is deprecated
+    #[arg(long)]
+    pub reason: Option<String>,
+
+    /// Show what would change without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+pub(super) async fn create_service() -> Result<ArtifactService> {
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -87,35 +211,96 @@ async fn create_service() -> Result<ArtifactService> {
     }
 
     let config = config_service.load()?;
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = std::sync::Arc::new(dna::db::lance::LanceDatabase::new(&storage_uri).await?);
+    let db = std::sync::Arc::new(config_service.open_database(&project_root).await?);
     let embedding = dna::embedding::create_provider(&config.model).await?;
+    let extra_models = dna::embedding::create_extra_providers(&config.extra_models).await;
 
-    Ok(ArtifactService::new(db, embedding))
+    Ok(
+        ArtifactService::from_config(db, embedding, &config, &project_root)?
+            .with_extra_models(extra_models),
+    )
 }
 
 pub async fn execute_add(args: AddArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
     let config = config_service.load()?;
 
-    let service = create_service().await?;
-    let format: ContentFormat = args.format.parse()?;
+    let kind = slugify_kind(&args.kind);
+    let format: ContentFormat = match args.format {
+        Some(ref format) => format.parse()?,
+        None => config.kinds.default_format(&kind),
+    };
     let labels = parse_metadata(&args.labels)?;
 
-    let label_keys: Vec<String> = labels.keys().cloned().collect();
-    validate_label_keys(&label_keys, &config)?;
-
-    let artifact = service
-        .add(
-            args.kind,
-            args.content,
-            format,
-            args.name,
-            labels,
-            args.context,
-        )
-        .await?;
+    validate_labels(&args.kind, &labels, &config)?;
+    let assignment = Assignment {
+        owner: args.owner.clone().map(Some),
+        reviewers: (!args.reviewers.is_empty()).then(|| args.reviewers.clone()),
+    };
+    // Checked up front, so a bad handle doesn't leave the artifact added but unowned
+    assignment.check(&config.owners)?;
+
+    let content = if args.edit {
+        let template = config.kinds.get(&kind).and_then(|d| d.template.clone());
+        let initial = args.content.or(template).unwrap_or_default();
+        let edited = super::edit_text(&initial, format.file_extension())?;
+        if edited.trim().is_empty() {
+            return Err(ServiceError::Validation("Aborted: content is empty".to_string()).into());
+        }
+        edited
+    } else {
+        args.content.unwrap_or_default()
+    };
+
+    if args.dry_run {
+        let service = create_service().await?.with_namespace(args.namespace);
+        let mut preview =
+            service.preview_add(args.kind, content, format, args.name, labels, args.context)?;
+        assignment.apply(&mut preview.artifact);
+        print_preview("add", &preview)?;
+        return Ok(());
+    }
+
+    let added = match create_service().await {
+        Ok(service) => {
+            let service = service.with_namespace(args.namespace.clone());
+            match service
+                .add(
+                    args.kind,
+                    content.clone(),
+                    format,
+                    args.name.clone(),
+                    labels.clone(),
+                    args.context.clone(),
+                )
+                .await
+            {
+                Ok(artifact) if !assignment.is_empty() => service
+                    .assign(&artifact.id, &assignment)
+                    .await
+                    .map_err(Into::into),
+                added => added,
+            }
+        },
+        Err(e) => Err(e),
+    };
+    let artifact = match added {
+        Ok(artifact) => artifact,
+        Err(e) => {
+            return super::outbox::queue_or_fail(&config, e, || {
+                let mut artifact =
+                    Artifact::new(kind, content, format, args.name, labels, String::new());
+                artifact.id = Artifact::generate_id_with(&config.ids, &artifact.kind);
+                artifact.context = args.context;
+                artifact.namespace = args.namespace;
+                assignment.apply(&mut artifact);
+                QueuedWrite::Add {
+                    artifact: Box::new(artifact),
+                }
+            })
+        },
+    };
     println!("Added artifact: {}", artifact.id);
     println!("{}", serde_json::to_string_pretty(&artifact)?);
     Ok(())
@@ -123,62 +308,456 @@ pub async fn execute_add(args: AddArgs) -> Result<()> {
 
 pub async fn execute_get(args: GetArgs) -> Result<()> {
     let service = create_service().await?;
+    // Former IDs of renamed or merged artifacts are not stored, but resolve
+    let mut ids = Vec::with_capacity(args.ids.len());
+    for id in &args.ids {
+        ids.push(
+            resolve_id(&service, id)
+                .await?
+                .unwrap_or_else(|| id.clone()),
+        );
+    }
 
-    let artifact = match args.version {
-        Some(version) => service.get_at_version(&args.id, version).await?,
-        None => service.get(&args.id).await?,
+    let artifacts = match args.version {
+        Some(version) => {
+            let mut artifacts = Vec::with_capacity(ids.len());
+            for id in &ids {
+                artifacts.push(service.get_at_version(id, version).await?);
+            }
+            artifacts
+        },
+        None => service.get_many(&ids).await?,
     };
 
-    if let Some(artifact) = artifact {
+    let mut found = Vec::with_capacity(artifacts.len());
+    let mut missing = Vec::new();
+    for (asked, artifact) in args.ids.iter().zip(artifacts) {
+        match artifact {
+            Some(artifact) => {
+                if let Some(alias) = &artifact.redirected_from {
+                    eprintln!("{} is now {}", alias, artifact.id);
+                }
+                if let Some(deprecation) = Deprecation::of(&artifact) {
+                    eprintln!("{}: {}", artifact.id, deprecation.describe());
+                }
+                found.push(artifact);
+            },
+            None => missing.push(asked.as_str()),
+        }
+    }
+
+    if let Some(dir) = &args.with_attachments {
+        let attachments = open_attachments()?;
+        for artifact in &found {
+            let dir = if args.ids.len() == 1 {
+                dir.clone()
+            } else {
+                dir.join(&artifact.id)
+            };
+            restore_attachments(&attachments, artifact, &dir).await?;
+        }
+    }
+
+    if let [id] = args.ids.as_slice() {
+        let Some(artifact) = found.pop() else {
+            return Err(ServiceError::NotFound(format!("Artifact '{}' not found", id)).into());
+        };
         println!("{}", serde_json::to_string_pretty(&artifact)?);
-    } else {
-        println!("Artifact not found: {}", args.id);
+        return Ok(());
+    }
+
+    println!("{}", serde_json::to_string_pretty(&found)?);
+    if !missing.is_empty() {
+        return Err(
+            ServiceError::NotFound(format!("Artifacts not found: {}", missing.join(", "))).into(),
+        );
+    }
+    Ok(())
+}
+
+/// The attachment store configured for the project
+fn open_attachments() -> Result<AttachmentService> {
+    let project_root = super::project_root();
+    let config = ConfigService::new(&project_root).load()?;
+    AttachmentService::new(
+        &config.attachments.resolve_location(&project_root),
+        &config.storage.s3,
+    )
+}
+
+/// Write each of `artifact`'s attachments into `dir`
+async fn restore_attachments(
+    attachments: &AttachmentService,
+    artifact: &Artifact,
+    dir: &Path,
+) -> Result<()> {
+    let manifest = attachment::attachments(artifact);
+    if manifest.is_empty() {
+        return Ok(());
+    }
+    tokio::fs::create_dir_all(dir).await?;
+    for attached in manifest {
+        let data = attachments.get(&artifact.id, &attached).await?;
+        let path = dir.join(&attached.name);
+        tokio::fs::write(&path, data).await?;
+        eprintln!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+pub async fn execute_attach(args: AttachArgs) -> Result<()> {
+    let service = create_service().await?;
+    let id = service.resolve_id(&args.id).await?;
+    let mut artifact = service
+        .get(&id)
+        .await?
+        .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", args.id)))?;
+    let attachments = open_attachments()?;
+
+    for file in &args.files {
+        let name = file
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| anyhow::anyhow!("'{}' is not a file", file.display()))?;
+        let data = tokio::fs::read(file)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", file.display(), e))?;
+        let attached = attachments.put(&artifact.id, &name, data).await?;
+        let labels = attachment::manifest_with(&artifact, attached)?;
+        artifact = service
+            .update(&artifact.id, None, None, None, Some(labels), None)
+            .await?;
+    }
+
+    // Text from Markdown, HTML and PDF attachments is searched with the context
+    let text = attachments.extract_all(&artifact).await?;
+    artifact = service.set_attachment_text(&artifact.id, text).await?;
+
+    let manifest = attachment::attachments(&artifact);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&manifest)?);
+        return Ok(());
+    }
+    println!(
+        "Attached {} file(s) to {}; it now has:",
+        args.files.len(),
+        artifact.id
+    );
+    for attached in manifest {
+        println!(
+            "  {} ({})",
+            attached.name,
+            super::version::format_bytes(attached.size)
+        );
     }
     Ok(())
 }
 
 pub async fn execute_update(args: UpdateArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
     let config = config_service.load()?;
 
-    let service = create_service().await?;
     let labels = if args.labels.is_empty() {
         None
     } else {
-        let parsed = parse_metadata(&args.labels)?;
-        // Only validate keys that are being set (not removed via empty value)
-        let label_keys: Vec<String> = parsed
-            .iter()
-            .filter(|(_, v)| !v.is_empty())
-            .map(|(k, _)| k.clone())
-            .collect();
-        validate_label_keys(&label_keys, &config)?;
-        Some(parsed)
-    };
-
-    let artifact = service
-        .update(
-            &args.id,
-            args.content,
-            args.name,
-            args.kind,
-            labels,
-            args.context,
-        )
-        .await?;
+        Some(parse_metadata(&args.labels)?)
+    };
+    let assignment = Assignment {
+        owner: args
+            .owner
+            .clone()
+            .map(|owner| (!owner.is_empty()).then_some(owner)),
+        reviewers: (!args.reviewers.is_empty()).then(|| {
+            args.reviewers
+                .iter()
+                .filter(|r| !r.is_empty())
+                .cloned()
+                .collect()
+        }),
+    };
+    assignment.check(&config.owners)?;
+    // Only owner and reviewers change, so there is nothing to re-embed
+    let assign_only = !assignment.is_empty()
+        && args.content.is_none()
+        && args.name.is_none()
+        && args.kind.is_none()
+        && labels.is_none()
+        && args.context.is_none();
+    let queued = |id: String, labels: Option<HashMap<String, String>>| QueuedWrite::Update {
+        id,
+        content: args.content.clone(),
+        name: args.name.clone(),
+        kind: args.kind.clone(),
+        labels,
+        context: args.context.clone(),
+    };
+
+    let service = match create_service().await {
+        Ok(service) => service,
+        Err(e) if args.new_id.is_none() && assignment.is_empty() && !args.dry_run => {
+            // The store may be out of reach, so the ID is queued as given and
+            // resolved when the outbox is flushed
+            if let (Some(kind), Some(labels)) = (&args.kind, &labels) {
+                validate_labels(kind, labels, &config)?;
+            }
+            return super::outbox::queue_or_fail(&config, e, || {
+                queued(args.id.clone(), labels.clone())
+            });
+        },
+        Err(e) => return Err(e),
+    };
+    let id = service.resolve_id(&args.id).await?;
+    if let Some(labels) = &labels {
+        // Labels are checked against the kind the artifact will have after the update
+        let kind = match args.kind {
+            Some(ref kind) => kind.clone(),
+            None => {
+                service
+                    .get(&id)
+                    .await?
+                    .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", id)))?
+                    .kind
+            },
+        };
+        validate_labels(&kind, labels, &config)?;
+    }
+
+    if args.dry_run {
+        let mut preview = service
+            .preview_update(
+                &id,
+                args.content.clone(),
+                args.name.clone(),
+                args.kind.clone(),
+                labels,
+                args.context.clone(),
+            )
+            .await?;
+        assignment.apply(&mut preview.artifact);
+        print_preview("update", &preview)?;
+        return Ok(());
+    }
+
+    let mut artifact = if assign_only {
+        service.assign(&id, &assignment).await?
+    } else {
+        let updated = service
+            .update(
+                &id,
+                args.content.clone(),
+                args.name.clone(),
+                args.kind.clone(),
+                labels.clone(),
+                args.context.clone(),
+            )
+            .await;
+        match updated {
+            Ok(artifact) if !assignment.is_empty() => {
+                service.assign(&artifact.id, &assignment).await?
+            },
+            Ok(artifact) => artifact,
+            Err(e) if args.new_id.is_none() && assignment.is_empty() => {
+                return super::outbox::queue_or_fail(&config, e.into(), || queued(id, labels))
+            },
+            Err(e) => return Err(e.into()),
+        }
+    };
+    if let Some(new_id) = &args.new_id {
+        artifact = service.rename(&artifact.id, new_id).await?;
+    }
     println!("Updated artifact: {}", artifact.id);
     println!("{}", serde_json::to_string_pretty(&artifact)?);
     Ok(())
 }
 
 pub async fn execute_remove(args: RemoveArgs) -> Result<()> {
+    let config = ConfigService::new(&super::project_root()).load()?;
+    let service = match create_service().await {
+        Ok(service) => service,
+        Err(e) if !args.dry_run && super::outbox::is_offline(&config) => {
+            // The store may be out of reach, so the ID is queued as given and
+            // resolved when the outbox is flushed
+            let prompt = format!("Queue removal of artifact {}?", args.id);
+            if !super::confirm(&prompt, args.yes)? {
+                return Ok(());
+            }
+            return super::outbox::queue_or_fail(&config, e, || QueuedWrite::Remove {
+                id: args.id.clone(),
+            });
+        },
+        Err(e) => return Err(e),
+    };
+
+    let Some(id) = resolve_id(&service, &args.id).await? else {
+        println!("Artifact not found: {}", args.id);
+        return Ok(());
+    };
+
+    let Some(artifact) = service.get(&id).await? else {
+        println!("Artifact not found: {}", id);
+        return Ok(());
+    };
+
+    if args.dry_run {
+        println!(
+            "Would remove artifact: {} - {} ({})",
+            artifact.id, artifact.kind, artifact.format
+        );
+        return Ok(());
+    }
+
+    let prompt = format!(
+        "Remove {} artifact {} \"{}\"?",
+        artifact.kind,
+        artifact.id,
+        describe(&artifact)
+    );
+    if !super::confirm(&prompt, args.yes)? {
+        return Ok(());
+    }
+
+    if service.remove(&id).await? {
+        println!("Removed artifact: {}", id);
+    } else {
+        println!("Artifact not found: {}", id);
+    }
+    Ok(())
+}
+
+pub async fn execute_archive(args: ArchiveArgs) -> Result<()> {
     let service = create_service().await?;
 
-    if service.remove(&args.id).await? {
-        println!("Removed artifact: {}", args.id);
+    if let Some(prefix) = &args.id {
+        let Some(id) = resolve_id(&service, prefix).await? else {
+            println!("Artifact not found: {}", prefix);
+            return Ok(());
+        };
+
+        if args.dry_run {
+            println!("Would archive artifact: {}", id);
+        } else if service.archive(&id).await? {
+            println!("Archived artifact: {}", id);
+        } else {
+            println!("Artifact not found: {}", id);
+        }
+        return Ok(());
+    }
+
+    let Some(before) = args.before.as_deref() else {
+        return Ok(());
+    };
+    let before = parse_date(before)?;
+    let filters = SearchFilters {
+        kind: args.kind,
+        ..Default::default()
+    };
+
+    let candidates = service
+        .list(SearchFilters {
+            before: Some(before),
+            ..filters.clone()
+        })
+        .await?;
+    if args.dry_run {
+        println!("Would archive {} artifact(s):", candidates.len());
+        for artifact in candidates {
+            println!(
+                "  {} - {} ({})",
+                artifact.id, artifact.kind, artifact.format
+            );
+        }
+        return Ok(());
+    }
+    if candidates.is_empty() {
+        println!("Archived 0 artifact(s).");
+        return Ok(());
+    }
+    let prompt = format!("Archive {} artifact(s)?", candidates.len());
+    if !super::confirm(&prompt, args.yes)? {
+        return Ok(());
+    }
+
+    let archived = service.archive_before(before, filters).await?;
+    println!("Archived {} artifact(s).", archived.len());
+    for id in archived {
+        println!("  {}", id);
+    }
+    Ok(())
+}
+
+/// Name of an artifact, or the start of its content when it has none
+fn describe(artifact: &Artifact) -> String {
+    if let Some(name) = &artifact.name {
+        return name.clone();
+    }
+    let first_line = artifact.content.lines().next().unwrap_or_default();
+    if first_line.chars().count() > 60 {
+        format!("{}...", first_line.chars().take(57).collect::<String>())
+    } else {
+        first_line.to_string()
+    }
+}
+
+/// Summarise a dry-run add or update, then print the artifact as it would be stored
+fn print_preview(action: &str, preview: &ChangePreview) -> Result<()> {
+    let artifact = &preview.artifact;
+    println!("Would {} artifact: {}", action, artifact.id);
+    if preview.changed_fields.is_empty() {
+        println!("  No changes");
     } else {
-        println!("Artifact not found: {}", args.id);
+        println!("  Changes: {}", preview.changed_fields.join(", "));
+    }
+    match preview.context_tokens {
+        Some(context_tokens) => println!(
+            "  Tokens: ~{} content, ~{} context (model limit {})",
+            preview.content_tokens, context_tokens, preview.max_tokens
+        ),
+        None => println!(
+            "  Tokens: ~{} content (model limit {})",
+            preview.content_tokens, preview.max_tokens
+        ),
+    }
+    println!("  Embedding calls: {}", preview.embedding_calls);
+    println!("{}", serde_json::to_string_pretty(artifact)?);
+    println!("Dry run: no changes made.");
+    Ok(())
+}
+
+pub async fn execute_deprecate(args: DeprecateArgs) -> Result<()> {
+    let service = create_service().await?;
+    let Some(id) = resolve_id(&service, &args.id).await? else {
+        return Err(ServiceError::NotFound(format!("Artifact '{}' not found", args.id)).into());
+    };
+    let replaced_by = match &args.replaced_by {
+        Some(successor) => Some(resolve_id(&service, successor).await?.ok_or_else(|| {
+            ServiceError::NotFound(format!("Successor '{}' not found", successor))
+        })?),
+        None => None,
+    };
+    let deprecation = Deprecation {
+        replaced_by,
+        reason: args
+            .reason
+            .map(|reason| reason.trim().to_string())
+            .filter(|reason| !reason.is_empty()),
+    };
+
+    if args.dry_run {
+        println!("Would mark {}: {}", id, deprecation.describe());
+        return Ok(());
     }
+    let artifact = service.deprecate(&id, &deprecation).await?;
+    let recorded = Deprecation::of(&artifact).unwrap_or(deprecation);
+    println!("{}: {}", artifact.id, recorded.describe());
     Ok(())
 }
+
+/// Expand a full or prefix ID, returning None if no artifact matches.
+pub(crate) async fn resolve_id(service: &ArtifactService, id: &str) -> Result<Option<String>> {
+    match service.resolve_id(id).await {
+        Ok(id) => Ok(Some(id)),
+        Err(ServiceError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,90 @@
+use super::parse_metadata;
+use anyhow::Result;
+use clap::Args;
+use dna::services::completion::create_completion;
+use dna::services::{AskService, ConfigService, SearchFilters, SearchService};
+use std::sync::Arc;
+
+#[derive(Args)]
+pub struct AskArgs {
+    /// Question to answer from the store
+    pub question: String,
+
+    /// Only use artifacts of this kind
+    #[arg(long)]
+    pub kind: Option<String>,
+
+    /// Only use artifacts in this namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// Only use artifacts with label key=value (can be repeated)
+    #[arg(long = "filter")]
+    pub filters: Vec<String>,
+
+    /// Number of artifacts to answer from
+    #[arg(long, short = 'k', default_value = "5")]
+    pub top_k: usize,
+
+    /// Print the context block without asking the completion model
+    #[arg(long)]
+    pub context_only: bool,
+
+    /// Output the answer, context and sources as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(args: AskArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+    let config = config_service.load()?;
+    let db = Arc::new(config_service.open_database(&project_root).await?);
+    let embedding = dna::embedding::create_provider(&config.model).await?;
+    let mut search = SearchService::from_config(db, embedding, &config);
+    if !config.languages.models.is_empty() {
+        let extra_models = dna::embedding::create_extra_providers(&config.extra_models).await;
+        search = search.with_extra_models(extra_models);
+    }
+    let search = Arc::new(search);
+
+    let completion = if args.context_only {
+        None
+    } else {
+        create_completion(&config.completion)?
+    };
+    let service = AskService::new(search).with_completion(completion);
+    let filters = SearchFilters {
+        kind: args.kind,
+        namespace: args.namespace,
+        metadata: parse_metadata(&args.filters)?,
+        limit: Some(args.top_k),
+        exclude_deprecated: true,
+        ..Default::default()
+    };
+    let answer = service.ask(&args.question, filters).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&answer)?);
+        return Ok(());
+    }
+    if answer.sources.is_empty() {
+        println!("No artifacts found for this question.");
+        return Ok(());
+    }
+    match &answer.answer {
+        Some(text) => {
+            println!("{}", text);
+            println!();
+            println!("Sources:");
+            for source in &answer.sources {
+                match &source.name {
+                    Some(name) => println!("  [{}] {}: {}", source.id, source.kind, name),
+                    None => println!("  [{}] {}", source.id, source.kind),
+                }
+            }
+        },
+        None => print!("{}", answer.context),
+    }
+    Ok(())
+}
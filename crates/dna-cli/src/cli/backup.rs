@@ -0,0 +1,114 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use dna::services::{BackupService, ConfigService};
+
+use super::version::format_bytes;
+
+#[derive(Args)]
+pub struct BackupArgs {
+    #[command(subcommand)]
+    pub command: BackupCommands,
+}
+
+#[derive(Subcommand)]
+pub enum BackupCommands {
+    /// Snapshot the store to the backup location
+    Create(BackupCreateArgs),
+
+    /// List snapshots, newest first
+    List(BackupListArgs),
+
+    /// Replace the store with a snapshot
+    Restore(BackupRestoreArgs),
+}
+
+#[derive(Args)]
+pub struct BackupCreateArgs {
+    /// Output the new snapshot as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct BackupListArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct BackupRestoreArgs {
+    /// Snapshot name from `dna backup list`, or `latest`
+    pub name: String,
+
+    /// Skip the confirmation prompt
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+pub async fn execute(args: BackupArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+
+    if !config_service.exists() {
+        return Err(anyhow::anyhow!(
+            "DNA not initialized. Run 'dna init' first."
+        ));
+    }
+
+    let config = config_service.load()?;
+    let service = BackupService::new(
+        &config.storage.resolve_uri(&project_root),
+        &config.backup.resolve_location(&project_root),
+        &config.storage.s3,
+    )?
+    .with_keep(config.backup.keep);
+
+    match args.command {
+        BackupCommands::Create(args) => {
+            let backup = service.create().await?;
+            let removed = service.prune().await?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&backup)?);
+                return Ok(());
+            }
+            println!("Created {} ({})", backup.name, format_bytes(backup.size));
+            if !removed.is_empty() {
+                println!("Removed {} older backup(s)", removed.len());
+            }
+        },
+        BackupCommands::List(args) => {
+            let backups = service.list().await?;
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&backups)?);
+                return Ok(());
+            }
+            if backups.is_empty() {
+                println!("No backups found.");
+                return Ok(());
+            }
+            for backup in backups {
+                println!(
+                    "  {}  {}  {}",
+                    backup.name,
+                    backup.created_at.format("%Y-%m-%d %H:%M:%S"),
+                    format_bytes(backup.size)
+                );
+            }
+        },
+        BackupCommands::Restore(args) => {
+            let prompt = format!(
+                "Replace the store with backup '{}'? Changes since then, including \
+                 version history, are lost.",
+                args.name
+            );
+            if !super::confirm(&prompt, args.yes)? {
+                return Ok(());
+            }
+            let files = service.restore(&args.name).await?;
+            println!("Restored {} files from {}", files, args.name);
+        },
+    }
+
+    Ok(())
+}
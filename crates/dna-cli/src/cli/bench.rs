@@ -0,0 +1,289 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use dna::db::bench::{self, DbBenchOptions, DbBenchReport, Latency};
+use dna::db::lance::LanceDatabase;
+use dna::services::{
+    parse_kind_boost, BenchService, ConfigService, EmbeddingPrecision, GoldenSet, RetrievalReport,
+    SearchService, SearchWeights,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Args)]
+pub struct BenchArgs {
+    #[command(subcommand)]
+    pub command: BenchCommands,
+}
+
+#[derive(Subcommand)]
+pub enum BenchCommands {
+    /// Score search against a golden set of queries: recall@k and MRR
+    Retrieval(BenchRetrievalArgs),
+
+    /// Time inserts, lists and vector searches on synthetic stores
+    Db(BenchDbArgs),
+}
+
+#[derive(Args)]
+pub struct BenchRetrievalArgs {
+    /// YAML file mapping queries to the artifact IDs they should retrieve
+    #[arg(long)]
+    pub golden: PathBuf,
+
+    /// Results scored per query (default: the golden set's `k`, or 10)
+    #[arg(long, short)]
+    pub k: Option<usize>,
+
+    /// Score this model's embeddings instead of [model]'s; it must be listed
+    /// in [[extra_models]]. Repeat to compare models side by side.
+    #[arg(long = "model")]
+    pub models: Vec<String>,
+
+    /// Rank by a blend of content and context similarity, e.g. content=0.7,context=0.3
+    #[arg(long)]
+    pub weights: Option<SearchWeights>,
+
+    /// Multiply scores of a kind, e.g. invariant=2.0 (can be repeated)
+    #[arg(long = "boost", value_parser = parse_kind_boost)]
+    pub boosts: Vec<(String, f32)>,
+
+    /// List every query, not just those that missed expected artifacts
+    #[arg(long)]
+    pub all: bool,
+
+    /// Output the reports as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct BenchDbArgs {
+    /// Store sizes to measure at, in artifacts (comma-separated)
+    #[arg(long, value_delimiter = ',', default_value = "1000,10000")]
+    pub sizes: Vec<usize>,
+
+    /// Dimension of the synthetic embeddings
+    #[arg(long, default_value = "384")]
+    pub dimension: usize,
+
+    /// Times each operation is run per size
+    #[arg(long, default_value = "20")]
+    pub samples: usize,
+
+    /// Artifacts per batch insert
+    #[arg(long, default_value = "100")]
+    pub batch_size: usize,
+
+    /// Results requested from each list and search
+    #[arg(long, default_value = "10")]
+    pub limit: usize,
+
+    /// How embeddings are stored: f32, f16 or int8 (default: storage.embedding_precision)
+    #[arg(long)]
+    pub precision: Option<EmbeddingPrecision>,
+
+    /// Write the report as JSON to this file
+    #[arg(long, short)]
+    pub output: Option<PathBuf>,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(args: BenchArgs) -> Result<()> {
+    match args.command {
+        BenchCommands::Retrieval(args) => execute_retrieval(args).await,
+        BenchCommands::Db(args) => execute_db(args).await,
+    }
+}
+
+async fn execute_retrieval(args: BenchRetrievalArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+
+    if !config_service.exists() {
+        return Err(anyhow::anyhow!(
+            "DNA not initialized. Run 'dna init' first."
+        ));
+    }
+
+    let text = std::fs::read_to_string(&args.golden)
+        .with_context(|| format!("Failed to read {}", args.golden.display()))?;
+    let golden = GoldenSet::parse(&text)?;
+
+    let config = config_service.load()?;
+    let db = Arc::new(config_service.open_database(&project_root).await?);
+    let embedding = dna::embedding::create_provider(&config.model).await?;
+    let mut search_service = SearchService::from_config(db, embedding, &config);
+    if !args.models.is_empty() {
+        let extra_models = dna::embedding::create_extra_providers(&config.extra_models).await;
+        search_service = search_service.with_extra_models(extra_models);
+    }
+
+    let services = if args.models.is_empty() {
+        vec![search_service]
+    } else {
+        args.models
+            .iter()
+            .map(|model| search_service.for_model(model))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+
+    let weights = args.weights.unwrap_or_default();
+    let boosts: HashMap<String, f32> = args.boosts.into_iter().collect();
+    let mut reports = Vec::with_capacity(services.len());
+    for service in services {
+        let bench = BenchService::new(service)
+            .with_weights(weights)
+            .with_boosts(boosts.clone());
+        reports.push(bench.retrieval(&golden, args.k).await?);
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reports)?);
+        return Ok(());
+    }
+
+    for report in &reports {
+        print_report(report, args.all);
+    }
+    if reports.len() > 1 {
+        print_comparison(&reports);
+    }
+
+    Ok(())
+}
+
+async fn execute_db(args: BenchDbArgs) -> Result<()> {
+    if args.sizes.is_empty() || args.dimension == 0 || args.samples == 0 || args.batch_size == 0 {
+        return Err(anyhow::anyhow!(
+            "--sizes, --dimension, --samples and --batch-size must be positive"
+        ));
+    }
+
+    // Stores are synthetic, so a project is only needed for its precision
+    let precision = match args.precision {
+        Some(precision) => precision,
+        None => {
+            let config_service = ConfigService::new(&super::project_root());
+            if config_service.exists() {
+                config_service.load()?.storage.embedding_precision
+            } else {
+                EmbeddingPrecision::default()
+            }
+        },
+    };
+
+    let options = DbBenchOptions {
+        sizes: args.sizes,
+        dimension: args.dimension,
+        samples: args.samples,
+        batch_size: args.batch_size,
+        limit: args.limit,
+    };
+
+    let mut sizes = Vec::with_capacity(options.sizes.len());
+    for &size in &options.sizes {
+        if !args.json {
+            eprintln!("Measuring a store of {} artifacts...", size);
+        }
+        let dir = tempfile::tempdir().context("Failed to create a temporary store")?;
+        let uri = dir.path().join("bench.lance");
+        let db = LanceDatabase::new(&uri.to_string_lossy())
+            .await?
+            .with_embedding_precision(precision);
+        db.init().await?;
+        sizes.push(bench::measure(&db, size, &options).await?);
+    }
+    let report = DbBenchReport {
+        options,
+        precision,
+        sizes,
+    };
+
+    let json = serde_json::to_string_pretty(&report)?;
+    if let Some(path) = &args.output {
+        std::fs::write(path, &json)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    if args.json {
+        println!("{}", json);
+        return Ok(());
+    }
+
+    print_db_report(&report);
+    if let Some(path) = &args.output {
+        println!("\nWrote {}", path.display());
+    }
+    Ok(())
+}
+
+fn print_db_report(report: &DbBenchReport) {
+    println!(
+        "{} samples, {}-dim {} embeddings, batches of {}",
+        report.options.samples,
+        report.options.dimension,
+        report.precision,
+        report.options.batch_size
+    );
+    println!(
+        "{:>9}  {:<18}  {:>9}  {:>9}  {:>9}",
+        "SIZE", "OPERATION", "P50 MS", "P95 MS", "MAX MS"
+    );
+    for size in &report.sizes {
+        let rows: [(&str, &Latency); 4] = [
+            ("insert", &size.insert),
+            ("batch_insert", &size.batch_insert),
+            ("list_with_filters", &size.list_with_filters),
+            ("vector_search", &size.vector_search),
+        ];
+        for (operation, latency) in rows {
+            println!(
+                "{:>9}  {:<18}  {:>9.2}  {:>9.2}  {:>9.2}",
+                size.size, operation, latency.p50_ms, latency.p95_ms, latency.max_ms
+            );
+        }
+    }
+}
+
+fn print_report(report: &RetrievalReport, all: bool) {
+    println!("{} ({} queries)", report.model, report.queries.len());
+    for query in &report.queries {
+        if !all && query.missed.is_empty() {
+            continue;
+        }
+        println!(
+            "  recall {:.2}  rr {:.2}  {}",
+            query.recall, query.reciprocal_rank, query.query
+        );
+        if !query.missed.is_empty() {
+            println!("    missed: {}", query.missed.join(", "));
+        }
+    }
+    println!("  recall@{}: {:.3}", report.k, report.recall);
+    println!("  MRR:       {:.3}", report.mrr);
+    println!();
+}
+
+fn print_comparison(reports: &[RetrievalReport]) {
+    let width = reports
+        .iter()
+        .map(|r| r.model.len())
+        .max()
+        .unwrap_or(0)
+        .max("MODEL".len());
+    println!(
+        "{:<width$}  {:>9}  {:>6}",
+        "MODEL",
+        format!("RECALL@{}", reports[0].k),
+        "MRR"
+    );
+    for report in reports {
+        println!(
+            "{:<width$}  {:>9.3}  {:>6.3}",
+            report.model, report.recall, report.mrr
+        );
+    }
+}
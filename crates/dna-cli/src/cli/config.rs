@@ -1,7 +1,6 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
-use dna::services::ConfigService;
-use std::path::PathBuf;
+use dna::services::{ConfigService, LintSeverity};
 
 #[derive(Args)]
 pub struct ConfigArgs {
@@ -30,10 +29,16 @@ enum ConfigCommands {
         /// Configuration value
         value: String,
     },
+
+    /// Check that the configured storage is reachable
+    ValidateStorage,
+
+    /// Check config.toml for unknown keys and invalid values
+    Validate,
 }
 
 pub async fn execute(args: ConfigArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -75,6 +80,69 @@ pub async fn execute(args: ConfigArgs) -> Result<()> {
             config_service.set(&key, value.clone())?;
             println!("Set {} = {}", key, value);
         },
+
+        ConfigCommands::Validate => {
+            let issues = config_service.validate()?;
+            let path = config_service.path();
+            let path = path.strip_prefix(&project_root).unwrap_or(path).display();
+
+            for issue in &issues {
+                match issue.line {
+                    Some(line) => {
+                        println!("{}:{}: {}: {}", path, line, issue.severity, issue.message)
+                    },
+                    None => println!("{}: {}: {}", path, issue.severity, issue.message),
+                }
+            }
+
+            let errors = issues
+                .iter()
+                .filter(|issue| issue.severity == LintSeverity::Error)
+                .count();
+            let warnings = issues.len() - errors;
+            if errors > 0 {
+                return Err(anyhow::anyhow!(
+                    "{} has {} error(s) and {} warning(s)",
+                    path,
+                    errors,
+                    warnings
+                ));
+            }
+            if warnings > 0 {
+                println!("{} is valid, with {} warning(s)", path, warnings);
+            } else {
+                println!("{} is valid", path);
+            }
+        },
+
+        ConfigCommands::ValidateStorage => {
+            let config = config_service.load()?;
+            let db = config_service.open_database(&project_root).await?;
+
+            println!("Storage: {}", db.uri());
+            let s3 = &config.storage.s3;
+            let options = [
+                ("Region", s3.region.as_deref()),
+                ("Endpoint", s3.endpoint.as_deref()),
+                ("Profile", s3.profile.as_deref()),
+                ("Commit store", s3.commit_table()),
+            ];
+            for (label, value) in options {
+                if let Some(value) = value {
+                    println!("  {}: {}", label, value);
+                }
+            }
+
+            let tables = db
+                .table_names()
+                .await
+                .map_err(|e| anyhow::anyhow!("Storage is not reachable: {:#}", e))?;
+            if tables.is_empty() {
+                println!("Connected. No tables yet; run 'dna init' to create them.");
+            } else {
+                println!("Connected. Tables: {}", tables.join(", "));
+            }
+        },
     }
 
     Ok(())
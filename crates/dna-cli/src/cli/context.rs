@@ -2,7 +2,6 @@ use anyhow::Result;
 use clap::Args;
 use dna::services::{ArtifactService, ConfigService, SearchFilters};
 use std::collections::HashMap;
-use std::path::PathBuf;
 
 #[derive(Args)]
 pub struct ContextArgs {
@@ -12,7 +11,7 @@ pub struct ContextArgs {
 }
 
 pub async fn execute(args: ContextArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -24,10 +23,9 @@ pub async fn execute(args: ContextArgs) -> Result<()> {
     let config = config_service.load()?;
 
     // Load artifact counts per kind
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = std::sync::Arc::new(dna::db::lance::LanceDatabase::new(&storage_uri).await?);
+    let db = std::sync::Arc::new(config_service.open_database(&project_root).await?);
     let embedding = dna::embedding::create_provider(&config.model).await?;
-    let service = ArtifactService::new(db, embedding);
+    let service = ArtifactService::from_config(db, embedding, &config, &project_root)?;
 
     let artifacts = service.list(SearchFilters::default()).await?;
     let mut counts_by_kind: HashMap<String, usize> = HashMap::new();
@@ -0,0 +1,165 @@
+use anyhow::Result;
+use clap::Args;
+use dna::services::{ArtifactService, DuplicateGroup, SearchFilters, ServiceError};
+use std::io::{BufRead, IsTerminal, Write};
+
+#[derive(Args)]
+pub struct DedupeArgs {
+    /// Cosine similarity at or above which artifacts count as duplicates
+    #[arg(long, default_value = "0.95")]
+    pub threshold: f32,
+
+    /// Only look for duplicates of this kind
+    #[arg(long)]
+    pub kind: Option<String>,
+
+    /// Only look for duplicates in this namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// Merge the rest of this artifact's group into it
+    #[arg(long, value_name = "ID", conflicts_with = "interactive")]
+    pub merge_into: Option<String>,
+
+    /// Pick the artifact to merge each group into, or skip it
+    #[arg(long, short)]
+    pub interactive: bool,
+
+    /// Print the groups as JSON
+    #[arg(long, conflicts_with_all = ["merge_into", "interactive"])]
+    pub json: bool,
+
+    /// With --merge-into, skip the confirmation prompt
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+pub async fn execute(args: DedupeArgs) -> Result<()> {
+    let service = super::artifact::create_service().await?;
+    let filters = SearchFilters {
+        kind: args.kind,
+        namespace: args.namespace,
+        ..Default::default()
+    };
+    let groups = service.find_duplicates(filters, args.threshold).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&groups)?);
+        return Ok(());
+    }
+
+    if let Some(prefix) = &args.merge_into {
+        let Some(target) = super::artifact::resolve_id(&service, prefix).await? else {
+            return Err(ServiceError::NotFound(format!("Artifact '{}' not found", prefix)).into());
+        };
+        let Some(group) = groups.iter().find(|group| group.contains(&target)) else {
+            return Err(anyhow::anyhow!(
+                "Artifact '{}' has no duplicates at threshold {}",
+                target,
+                args.threshold
+            ));
+        };
+        print_group(1, group);
+        let duplicates = others(group, &target);
+        let prompt = format!("Merge {} artifact(s) into {}?", duplicates.len(), target);
+        if super::confirm(&prompt, args.yes)? {
+            merge(&service, &target, &duplicates).await?;
+        }
+        return Ok(());
+    }
+
+    if groups.is_empty() {
+        println!("No duplicates at threshold {}.", args.threshold);
+        return Ok(());
+    }
+
+    if !args.interactive {
+        println!("Found {} group(s) of duplicates:", groups.len());
+        for (i, group) in groups.iter().enumerate() {
+            print_group(i + 1, group);
+        }
+        println!(
+            "\nMerge a group with 'dna dedupe --merge-into <id>', or pick targets with --interactive."
+        );
+        return Ok(());
+    }
+
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "--interactive needs a terminal; use --merge-into <id> instead"
+        ));
+    }
+    for (i, group) in groups.iter().enumerate() {
+        print_group(i + 1, group);
+        let Some(choice) = choose(group.artifacts.len())? else {
+            break;
+        };
+        if let Some(member) = choice {
+            let target = &group.artifacts[member].id;
+            merge(&service, target, &others(group, target)).await?;
+        }
+    }
+    Ok(())
+}
+
+/// IDs of the group's members other than `target`
+fn others(group: &DuplicateGroup, target: &str) -> Vec<String> {
+    group
+        .artifacts
+        .iter()
+        .filter(|a| a.id != target)
+        .map(|a| a.id.clone())
+        .collect()
+}
+
+async fn merge(service: &ArtifactService, target: &str, duplicates: &[String]) -> Result<()> {
+    service.merge_into(target, duplicates).await?;
+    println!(
+        "Merged {} into {}; their IDs now redirect to it",
+        duplicates.join(", "),
+        target
+    );
+    Ok(())
+}
+
+fn print_group(number: usize, group: &DuplicateGroup) {
+    println!(
+        "\nGroup {} ({} artifacts, similarity {:.3}):",
+        number,
+        group.artifacts.len(),
+        group.similarity
+    );
+    for (i, artifact) in group.artifacts.iter().enumerate() {
+        let first_line = artifact.content.lines().next().unwrap_or_default();
+        let preview: String = first_line.chars().take(60).collect();
+        println!(
+            "  [{}] {} - {} ({}) {}",
+            i + 1,
+            artifact.id,
+            artifact.kind,
+            artifact.created_at.format("%Y-%m-%d"),
+            preview
+        );
+    }
+}
+
+/// Ask which member of a group of `len` to merge into: `Some(Some(i))` to
+/// merge into member `i`, `Some(None)` to skip the group, `None` to stop
+fn choose(len: usize) -> Result<Option<Option<usize>>> {
+    loop {
+        eprint!("Merge into [1-{}], s to skip, q to quit [s]: ", len);
+        std::io::stderr().flush()?;
+        let mut answer = String::new();
+        if std::io::stdin().lock().read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+        match answer.trim().to_lowercase().as_str() {
+            "" | "s" => return Ok(Some(None)),
+            "q" => return Ok(None),
+            n => match n.parse::<usize>() {
+                Ok(n) if (1..=len).contains(&n) => return Ok(Some(Some(n - 1))),
+                _ => eprintln!("Enter a number from 1 to {}, s or q.", len),
+            },
+        }
+    }
+}
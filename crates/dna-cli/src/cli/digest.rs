@@ -0,0 +1,58 @@
+use anyhow::Result;
+use clap::Args;
+use dna::services::digest;
+use dna::services::{slugify_kind, CompletionConfig, ConfigService, DigestService};
+use std::sync::Arc;
+
+#[derive(Args)]
+pub struct DigestArgs {
+    /// Kind of the artifacts to summarize
+    #[arg(long)]
+    pub kind: String,
+
+    /// Write one digest per value of this label instead of one for the kind
+    #[arg(long, value_name = "LABEL")]
+    pub group_by: Option<String>,
+
+    /// Summarize by quoting artifacts, even with a completion model configured
+    #[arg(long)]
+    pub extractive: bool,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output the result as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(args: DigestArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let mut config = ConfigService::new(&project_root).load()?;
+    if args.extractive {
+        config.completion = CompletionConfig::default();
+    }
+    let summarizer = digest::create_summarizer(&config)?;
+
+    let service = Arc::new(super::artifact::create_service().await?);
+    let digests = DigestService::new(service, summarizer);
+    let kind = slugify_kind(&args.kind);
+    let mut plan = digests.plan(&kind, args.group_by.as_deref()).await?;
+
+    if !args.dry_run {
+        digests.apply(&mut plan).await?;
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+        return Ok(());
+    }
+
+    super::ingest::print_report(&plan);
+    if args.dry_run {
+        println!();
+        println!("Dry run: no changes made.");
+    }
+    Ok(())
+}
@@ -0,0 +1,88 @@
+use anyhow::Result;
+use clap::Args;
+use dna::db::StorageCorruption;
+use dna::services::ConfigService;
+
+#[derive(Args)]
+pub struct FsckArgs {
+    /// Move damaged rows out of their table into the quarantine table
+    #[arg(long)]
+    pub quarantine: bool,
+
+    /// Skip the confirmation prompt
+    #[arg(long, short)]
+    pub yes: bool,
+
+    /// Output the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(args: FsckArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+
+    if !config_service.exists() {
+        return Err(anyhow::anyhow!(
+            "DNA not initialized. Run 'dna init' first."
+        ));
+    }
+
+    let db = config_service.open_database(&project_root).await?;
+    let mut report = db.fsck(false).await?;
+
+    let damaged_rows = report.issues.iter().filter(|i| i.id.is_some()).count();
+    if args.quarantine && damaged_rows > 0 {
+        let prompt = format!(
+            "Remove {} damaged row(s) from the store? They stay readable at the \
+             current version until it is pruned.",
+            damaged_rows
+        );
+        if super::confirm(&prompt, args.yes)? {
+            report = db.fsck(true).await?;
+        }
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        println!(
+            "Checked {} table(s), {} version(s), {} row(s)",
+            report.tables, report.versions, report.rows
+        );
+        for issue in &report.issues {
+            let location = match (&issue.id, issue.version) {
+                (Some(id), _) => format!("{} {}", issue.table, id),
+                (None, Some(version)) => format!("{} v{}", issue.table, version),
+                (None, None) => issue.table.clone(),
+            };
+            println!(
+                "  {:<10}  {}: {}{}",
+                issue.problem,
+                location,
+                issue.detail,
+                if issue.quarantined {
+                    " (quarantined)"
+                } else {
+                    ""
+                }
+            );
+        }
+        if report.unchecksummed > 0 {
+            println!(
+                "{} row(s) have no checksum yet; any other dna command migrates the \
+                 store and adds them.",
+                report.unchecksummed
+            );
+        }
+        if report.is_clean() {
+            println!("No problems found.");
+        }
+    }
+
+    let unresolved = report.unresolved();
+    if unresolved > 0 {
+        return Err(StorageCorruption(format!("{} problem(s) found by fsck", unresolved)).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,432 @@
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use dna::services::{
+    Artifact, ArtifactService, ConfigService, ContentFormat, ProjectConfig, UpsertAction,
+};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct ImportArgs {
+    #[command(subcommand)]
+    pub command: ImportCommands,
+}
+
+#[derive(Subcommand)]
+pub enum ImportCommands {
+    /// Import one artifact per row of a CSV file
+    Csv(CsvImportArgs),
+}
+
+#[derive(Args)]
+pub struct CsvImportArgs {
+    /// CSV file with a header row
+    pub file: PathBuf,
+
+    /// Artifact field and its source: kind, content, name, context or format,
+    /// mapped to `col:<header>` or a fixed value (e.g. --map kind=intent)
+    #[arg(long = "map", value_name = "FIELD=SOURCE")]
+    pub map: Vec<String>,
+
+    /// Label and its source, `col:<header>` or a fixed value
+    /// (e.g. --label domain=col:area); empty cells add no label
+    #[arg(long = "label", value_name = "KEY=SOURCE")]
+    pub labels: Vec<String>,
+
+    /// Stable key of each row, `col:<header>`; rows already imported under
+    /// their key are updated instead of added again
+    #[arg(long, value_name = "SOURCE")]
+    pub external_id: Option<String>,
+
+    /// Field delimiter
+    #[arg(long, default_value = ",")]
+    pub delimiter: char,
+
+    /// Rows embedded per provider call
+    #[arg(long, default_value = "32")]
+    pub batch_size: usize,
+
+    /// Output the result as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Where an artifact field gets its value
+#[derive(Debug, Clone, PartialEq)]
+enum Source {
+    /// The cell at this index of each row
+    Column(usize),
+    /// The same value for every row
+    Value(String),
+}
+
+impl Source {
+    /// Parse `col:<header>` against the header row, or take the text as a fixed value
+    fn parse(spec: &str, headers: &csv::StringRecord) -> Result<Self> {
+        let Some(column) = spec.strip_prefix("col:") else {
+            return Ok(Self::Value(spec.to_string()));
+        };
+        headers
+            .iter()
+            .position(|h| h.trim() == column)
+            .map(Self::Column)
+            .with_context(|| {
+                format!(
+                    "Column '{}' not found in CSV header (columns: {})",
+                    column,
+                    headers.iter().collect::<Vec<_>>().join(", ")
+                )
+            })
+    }
+
+    fn get<'a>(&'a self, record: &'a csv::StringRecord) -> &'a str {
+        match self {
+            Self::Column(index) => record.get(*index).unwrap_or_default().trim(),
+            Self::Value(value) => value,
+        }
+    }
+}
+
+/// How CSV columns become artifact fields
+#[derive(Debug)]
+struct Mapping {
+    kind: Source,
+    content: Source,
+    name: Option<Source>,
+    context: Option<Source>,
+    format: Option<Source>,
+    labels: Vec<(String, Source)>,
+}
+
+impl Mapping {
+    fn parse(maps: &[String], labels: &[String], headers: &csv::StringRecord) -> Result<Self> {
+        let mut fields = HashMap::new();
+        for pair in maps {
+            let (field, spec) = split_pair(pair)?;
+            if !matches!(field, "kind" | "content" | "name" | "context" | "format") {
+                anyhow::bail!(
+                    "Unknown field '{}' in --map (expected kind, content, name, context or format)",
+                    field
+                );
+            }
+            fields.insert(field.to_string(), Source::parse(spec, headers)?);
+        }
+
+        let mut label_sources = Vec::new();
+        for pair in labels {
+            let (key, spec) = split_pair(pair)?;
+            label_sources.push((key.to_string(), Source::parse(spec, headers)?));
+        }
+
+        Ok(Self {
+            kind: fields
+                .remove("kind")
+                .context("Missing --map kind=... (a column or a fixed kind)")?,
+            content: fields
+                .remove("content")
+                .context("Missing --map content=col:<header>")?,
+            name: fields.remove("name"),
+            context: fields.remove("context"),
+            format: fields.remove("format"),
+            labels: label_sources,
+        })
+    }
+
+    /// Build the artifact for one row and check its labels against the registry
+    fn artifact(&self, record: &csv::StringRecord, config: &ProjectConfig) -> Result<Artifact> {
+        let kind = self.kind.get(record);
+        let content = self.content.get(record);
+        if kind.is_empty() {
+            anyhow::bail!("Empty kind");
+        }
+        if content.is_empty() {
+            anyhow::bail!("Empty content");
+        }
+
+        let format = match self.format.as_ref().map(|s| s.get(record)) {
+            Some(format) if !format.is_empty() => format.parse()?,
+            _ => ContentFormat::Markdown,
+        };
+        let labels: HashMap<String, String> = self
+            .labels
+            .iter()
+            .map(|(key, source)| (key.clone(), source.get(record).to_string()))
+            .filter(|(_, value)| !value.is_empty())
+            .collect();
+        super::validate_labels(kind, &labels, config)?;
+
+        let optional = |source: &Option<Source>| {
+            source
+                .as_ref()
+                .map(|s| s.get(record).to_string())
+                .filter(|value| !value.is_empty())
+        };
+        let mut artifact = Artifact::new(
+            kind.to_string(),
+            content.to_string(),
+            format,
+            optional(&self.name),
+            labels,
+            String::new(),
+        );
+        artifact.id = String::new();
+        artifact.context = optional(&self.context);
+        Ok(artifact)
+    }
+}
+
+fn split_pair(pair: &str) -> Result<(&str, &str)> {
+    pair.split_once('=')
+        .map(|(k, v)| (k.trim(), v.trim()))
+        .filter(|(k, v)| !k.is_empty() && !v.is_empty())
+        .with_context(|| format!("Invalid mapping '{}', expected KEY=SOURCE", pair))
+}
+
+/// A row that could not be imported
+struct RowError {
+    /// Line in the file, counting the header as line 1
+    line: u64,
+    message: String,
+}
+
+pub async fn execute(args: ImportArgs) -> Result<()> {
+    match args.command {
+        ImportCommands::Csv(args) => execute_csv(args).await,
+    }
+}
+
+async fn execute_csv(args: CsvImportArgs) -> Result<()> {
+    if args.batch_size == 0 {
+        anyhow::bail!("--batch-size must be at least 1");
+    }
+    if !args.delimiter.is_ascii() {
+        anyhow::bail!("--delimiter must be a single ASCII character");
+    }
+
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+    let service = super::artifact::create_service().await?;
+    let config = config_service.load()?;
+
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(args.delimiter as u8)
+        .from_path(&args.file)
+        .with_context(|| format!("Failed to open {}", args.file.display()))?;
+    let headers = reader
+        .headers()
+        .with_context(|| format!("Failed to read the header row of {}", args.file.display()))?
+        .clone();
+    let mapping = Mapping::parse(&args.map, &args.labels, &headers)?;
+    let external_id = args
+        .external_id
+        .as_deref()
+        .map(|spec| Source::parse(spec, &headers))
+        .transpose()?;
+
+    let mut imported = Vec::new();
+    let mut errors = Vec::new();
+    let mut batch = Vec::new();
+    let mut actions = Vec::new();
+    let mut rows = 0;
+
+    for record in reader.records() {
+        rows += 1;
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(RowError {
+                    line: e.position().map_or(rows + 1, |p| p.line()),
+                    message: e.to_string(),
+                });
+                continue;
+            },
+        };
+        let line = record.position().map_or(rows + 1, |p| p.line());
+        let artifact = match mapping.artifact(&record, &config) {
+            Ok(artifact) => artifact,
+            Err(e) => {
+                errors.push(RowError {
+                    line,
+                    message: format!("{:#}", e),
+                });
+                continue;
+            },
+        };
+
+        // Keyed rows are upserted one at a time, as each needs a lookup
+        if let Some(source) = &external_id {
+            match upsert(&service, source.get(&record), artifact).await {
+                Ok((action, artifact)) => {
+                    actions.push(action);
+                    imported.push(artifact);
+                },
+                Err(e) => errors.push(RowError {
+                    line,
+                    message: format!("{:#}", e),
+                }),
+            }
+            continue;
+        }
+        batch.push((line, artifact));
+
+        if batch.len() >= args.batch_size {
+            flush(&service, &mut batch, &mut imported, &mut errors).await;
+            if !args.json {
+                eprintln!("Imported {} rows...", imported.len());
+            }
+        }
+    }
+    flush(&service, &mut batch, &mut imported, &mut errors).await;
+    errors.sort_by_key(|e| e.line);
+
+    if args.json {
+        let failed: Vec<_> = errors
+            .iter()
+            .map(|e| serde_json::json!({ "line": e.line, "error": e.message }))
+            .collect();
+        let ids: Vec<_> = imported.iter().map(|a: &Artifact| &a.id).collect();
+        let mut result = serde_json::json!({
+            "rows": rows,
+            "imported": ids,
+            "failed": failed,
+        });
+        if external_id.is_some() {
+            let (created, updated, unchanged) = tally(&actions);
+            result["created"] = created.into();
+            result["updated"] = updated.into();
+            result["unchanged"] = unchanged.into();
+        }
+        println!("{}", serde_json::to_string_pretty(&result)?);
+    } else if external_id.is_some() {
+        let (created, updated, unchanged) = tally(&actions);
+        println!(
+            "Imported {} of {} rows ({} created, {} updated, {} unchanged)",
+            imported.len(),
+            rows,
+            created,
+            updated,
+            unchanged
+        );
+        for error in &errors {
+            println!("  line {}: {}", error.line, error.message);
+        }
+    } else {
+        println!("Imported {} of {} rows", imported.len(), rows);
+        for error in &errors {
+            println!("  line {}: {}", error.line, error.message);
+        }
+    }
+
+    if !errors.is_empty() {
+        anyhow::bail!("{} of {} rows failed to import", errors.len(), rows);
+    }
+    Ok(())
+}
+
+/// Add or update the artifact stored under a row's external ID
+async fn upsert(
+    service: &ArtifactService,
+    key: &str,
+    artifact: Artifact,
+) -> Result<(UpsertAction, Artifact)> {
+    if key.is_empty() {
+        anyhow::bail!("Empty external ID");
+    }
+    let upserted = service.upsert(key, artifact).await?;
+    Ok((upserted.action, upserted.artifact))
+}
+
+/// Rows created, updated and left unchanged
+fn tally(actions: &[UpsertAction]) -> (usize, usize, usize) {
+    let count = |action| actions.iter().filter(|a| **a == action).count();
+    (
+        count(UpsertAction::Created),
+        count(UpsertAction::Updated),
+        count(UpsertAction::Unchanged),
+    )
+}
+
+/// Embed and insert the pending rows, sorting them into imported and failed
+async fn flush(
+    service: &ArtifactService,
+    batch: &mut Vec<(u64, Artifact)>,
+    imported: &mut Vec<Artifact>,
+    errors: &mut Vec<RowError>,
+) {
+    if batch.is_empty() {
+        return;
+    }
+    let (lines, artifacts): (Vec<_>, Vec<_>) = batch.drain(..).unzip();
+    for (line, result) in lines.into_iter().zip(service.add_batch(artifacts).await) {
+        match result {
+            Ok(artifact) => imported.push(artifact),
+            Err(e) => errors.push(RowError {
+                line,
+                message: format!("{:#}", e),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers() -> csv::StringRecord {
+        csv::StringRecord::from(vec!["type", "text", "area", "title"])
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn maps_columns_and_fixed_values() {
+        let mapping = Mapping::parse(
+            &strings(&["kind=col:type", "content=col:text", "name=col:title"]),
+            &strings(&["domain=col:area", "source=spreadsheet"]),
+            &headers(),
+        )
+        .unwrap();
+        assert_eq!(mapping.kind, Source::Column(0));
+
+        let record = csv::StringRecord::from(vec!["Intent", " Users sign in ", "auth", ""]);
+        let artifact = mapping
+            .artifact(&record, &ProjectConfig::default())
+            .unwrap();
+        assert_eq!(artifact.kind, "Intent");
+        assert_eq!(artifact.content, "Users sign in");
+        assert_eq!(artifact.name, None);
+        assert_eq!(artifact.metadata["domain"], "auth");
+        assert_eq!(artifact.metadata["source"], "spreadsheet");
+        assert!(artifact.id.is_empty());
+
+        let record = csv::StringRecord::from(vec!["intent", "", "auth", "Login"]);
+        let err = mapping
+            .artifact(&record, &ProjectConfig::default())
+            .unwrap_err();
+        assert!(err.to_string().contains("Empty content"));
+    }
+
+    #[test]
+    fn rejects_unknown_columns_and_fields() {
+        let err = Mapping::parse(
+            &strings(&["kind=intent", "content=col:body"]),
+            &[],
+            &headers(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Column 'body' not found"), "{err}");
+
+        let err = Mapping::parse(
+            &strings(&["kind=intent", "content=col:text", "owner=col:area"]),
+            &[],
+            &headers(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Unknown field 'owner'"), "{err}");
+
+        let err = Mapping::parse(&strings(&["content=col:text"]), &[], &headers()).unwrap_err();
+        assert!(err.to_string().contains("Missing --map kind"), "{err}");
+    }
+}
@@ -0,0 +1,324 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand};
+use dna::integrations::confluence::ConfluenceClient;
+use dna::integrations::jira::JiraClient;
+use dna::integrations::linear::LinearClient;
+use dna::integrations::notion::NotionClient;
+use dna::integrations::{KindRules, Page, Ticket};
+use dna::services::{ConfigService, IngestItem, IngestPlan, IngestService, SyncAction};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Last ingested edit time per wiki source, under `.dna/`
+const CURSORS_FILE: &str = "ingest-cursors.json";
+
+#[derive(Args)]
+pub struct IngestArgs {
+    #[command(subcommand)]
+    pub command: IngestCommands,
+}
+
+#[derive(Subcommand)]
+pub enum IngestCommands {
+    /// Create or update artifacts from Jira issues matching a JQL query
+    Jira(JiraArgs),
+
+    /// Create or update artifacts from a Linear team's issues
+    Linear(LinearArgs),
+
+    /// Create or update artifacts from the pages of a Notion database
+    Notion(NotionArgs),
+
+    /// Create or update artifacts from the pages of a Confluence space
+    Confluence(ConfluenceArgs),
+}
+
+/// Options shared by every ingest source
+#[derive(Args)]
+pub struct IngestOptions {
+    /// Kind for issues no --kind-for rule matches
+    #[arg(long, default_value = "intent")]
+    pub kind: String,
+
+    /// Kind for an issue type or label, e.g. --kind-for Bug=evaluation (repeatable)
+    #[arg(long = "kind-for", value_name = "TYPE=KIND")]
+    pub kind_for: Vec<String>,
+
+    /// Stop after this many issues
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output the result as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct JiraArgs {
+    /// JQL query selecting the issues
+    #[arg(long)]
+    pub jql: String,
+
+    /// Site URL, e.g. https://example.atlassian.net
+    #[arg(long, env = "JIRA_URL")]
+    pub url: String,
+
+    /// Account email for the API token
+    #[arg(long, env = "JIRA_EMAIL")]
+    pub email: String,
+
+    /// API token
+    #[arg(long, env = "JIRA_API_TOKEN", hide_env_values = true)]
+    pub token: String,
+
+    #[command(flatten)]
+    pub options: IngestOptions,
+}
+
+#[derive(Args)]
+pub struct LinearArgs {
+    /// Team key, e.g. ENG
+    #[arg(long)]
+    pub team: String,
+
+    /// Personal API key
+    #[arg(long, env = "LINEAR_API_KEY", hide_env_values = true)]
+    pub api_key: String,
+
+    #[command(flatten)]
+    pub options: IngestOptions,
+}
+
+/// Options shared by wiki sources
+#[derive(Args)]
+pub struct PageOptions {
+    /// Kind for the page artifacts
+    #[arg(long, default_value = "intent")]
+    pub kind: String,
+
+    /// Only pages edited after this time (RFC 3339), instead of since the last run
+    #[arg(long, conflicts_with = "full")]
+    pub since: Option<DateTime<Utc>>,
+
+    /// Fetch every page, ignoring the last run
+    #[arg(long)]
+    pub full: bool,
+
+    /// Stop after this many pages (oldest edits first; the next run continues)
+    #[arg(long)]
+    pub limit: Option<usize>,
+
+    /// Show what would change without writing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output the result as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Args)]
+pub struct NotionArgs {
+    /// Database ID, from the database's URL
+    #[arg(long)]
+    pub database: String,
+
+    /// Integration token; the database must be shared with the integration
+    #[arg(long, env = "NOTION_TOKEN", hide_env_values = true)]
+    pub token: String,
+
+    #[command(flatten)]
+    pub options: PageOptions,
+}
+
+#[derive(Args)]
+pub struct ConfluenceArgs {
+    /// Space key, e.g. ENG
+    #[arg(long)]
+    pub space: String,
+
+    /// Site URL, e.g. https://example.atlassian.net
+    #[arg(long, env = "CONFLUENCE_URL")]
+    pub url: String,
+
+    /// Account email for the API token
+    #[arg(long, env = "CONFLUENCE_EMAIL")]
+    pub email: String,
+
+    /// API token
+    #[arg(long, env = "CONFLUENCE_API_TOKEN", hide_env_values = true)]
+    pub token: String,
+
+    #[command(flatten)]
+    pub options: PageOptions,
+}
+
+impl IngestOptions {
+    fn kind_rules(&self) -> Result<KindRules> {
+        let mut by_type = HashMap::new();
+        for rule in &self.kind_for {
+            let (ticket_type, kind) = rule
+                .split_once('=')
+                .filter(|(t, k)| !t.is_empty() && !k.is_empty())
+                .with_context(|| format!("Invalid --kind-for '{}', expected TYPE=KIND", rule))?;
+            by_type.insert(ticket_type.to_string(), kind.to_string());
+        }
+        Ok(KindRules {
+            default: self.kind.clone(),
+            by_type,
+        })
+    }
+}
+
+pub async fn execute(args: IngestArgs) -> Result<()> {
+    match args.command {
+        IngestCommands::Jira(args) => {
+            let client = JiraClient::new(&args.url, &args.email, &args.token);
+            let tickets = client.search(&args.jql, args.options.limit).await?;
+            ingest_tickets("jira", tickets, &args.options).await
+        },
+        IngestCommands::Linear(args) => {
+            let client = LinearClient::new(&args.api_key);
+            let tickets = client.team_issues(&args.team, args.options.limit).await?;
+            ingest_tickets("linear", tickets, &args.options).await
+        },
+        IngestCommands::Notion(args) => {
+            let client = NotionClient::new(&args.token);
+            let cursor = format!("notion:{}", args.database);
+            let since = args.options.since(&cursor)?;
+            let pages = client
+                .database_pages(&args.database, since, args.options.limit)
+                .await?;
+            ingest_pages("notion", &cursor, pages, &args.options).await
+        },
+        IngestCommands::Confluence(args) => {
+            let client = ConfluenceClient::new(&args.url, &args.email, &args.token);
+            let cursor = format!("confluence:{}", args.space);
+            let since = args.options.since(&cursor)?;
+            let pages = client
+                .space_pages(&args.space, since, args.options.limit)
+                .await?;
+            ingest_pages("confluence", &cursor, pages, &args.options).await
+        },
+    }
+}
+
+impl PageOptions {
+    /// Edit time to fetch from: `--since`, else the last run's, unless `--full`
+    fn since(&self, cursor: &str) -> Result<Option<DateTime<Utc>>> {
+        if self.full {
+            return Ok(None);
+        }
+        if self.since.is_some() {
+            return Ok(self.since);
+        }
+        Ok(load_cursors(&cursors_path())?.get(cursor).copied())
+    }
+}
+
+async fn ingest_tickets(system: &str, tickets: Vec<Ticket>, options: &IngestOptions) -> Result<()> {
+    let rules = options.kind_rules()?;
+    let items: Vec<_> = tickets
+        .into_iter()
+        .map(|ticket| ticket.into_item(system, &rules))
+        .collect();
+    ingest(&items, options.dry_run, options.json).await
+}
+
+async fn ingest_pages(
+    system: &str,
+    cursor: &str,
+    pages: Vec<Page>,
+    options: &PageOptions,
+) -> Result<()> {
+    let latest = pages.iter().map(|page| page.edited_at).max();
+    let items: Vec<_> = pages
+        .into_iter()
+        .map(|page| page.into_item(system, &options.kind))
+        .collect();
+    ingest(&items, options.dry_run, options.json).await?;
+
+    // Only after a successful apply, so a failed run is retried in full
+    if options.dry_run {
+        return Ok(());
+    }
+    if let Some(latest) = latest {
+        let path = cursors_path();
+        let mut cursors = load_cursors(&path)?;
+        cursors.insert(cursor.to_string(), latest);
+        save_cursors(&path, &cursors)?;
+    }
+    Ok(())
+}
+
+async fn ingest(items: &[IngestItem], dry_run: bool, json: bool) -> Result<()> {
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+    let service = super::artifact::create_service().await?;
+    let config = config_service.load()?;
+
+    let ingest = IngestService::new(Arc::new(service)).with_labels(config.labels);
+    let mut plan = ingest.plan(items).await?;
+
+    if !dry_run {
+        ingest.apply(&mut plan).await?;
+    }
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&plan)?);
+        return Ok(());
+    }
+
+    print_report(&plan);
+    if dry_run {
+        println!();
+        println!("Dry run: no changes made.");
+    }
+    Ok(())
+}
+
+fn cursors_path() -> PathBuf {
+    super::project_root().join(".dna").join(CURSORS_FILE)
+}
+
+fn load_cursors(path: &Path) -> Result<BTreeMap<String, DateTime<Utc>>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let json = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+fn save_cursors(path: &Path, cursors: &BTreeMap<String, DateTime<Utc>>) -> Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(cursors)? + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+pub(super) fn print_report(plan: &IngestPlan) {
+    for change in &plan.changes {
+        let id = change.id.as_deref().unwrap_or("(new)");
+        match change.action {
+            SyncAction::Update => println!(
+                "  ~ {} {} [{}]",
+                id,
+                change.source,
+                change.fields.join(", ")
+            ),
+            _ => println!("  + {} {}", id, change.source),
+        }
+    }
+
+    println!(
+        "Created: {}, updated: {}, unchanged: {}",
+        plan.count(SyncAction::Create),
+        plan.count(SyncAction::Update),
+        plan.unchanged
+    );
+}
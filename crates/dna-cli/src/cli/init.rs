@@ -102,14 +102,13 @@ pub async fn execute(args: InitArgs) -> Result<()> {
     };
 
     // Initialize database
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = dna::db::lance::LanceDatabase::new(&storage_uri).await?;
+    let db = config_service.open_database(&project_root).await?;
     db.init().await?;
 
     println!("Initialized DNA project at {}", project_root.display());
     println!("  Provider: {}", config.model.provider);
     println!("  Model: {}", config.model.name);
-    println!("  Storage: {}", storage_uri);
+    println!("  Storage: {}", db.uri());
 
     Ok(())
 }
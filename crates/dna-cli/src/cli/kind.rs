@@ -1,6 +1,10 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
-use dna::services::{slugify_kind, ConfigService, KindValidationError};
+use dna::db::Database;
+use dna::services::{
+    slugify_kind, validate_kind_slug, ConfigService, ContentFormat, KindDefinition, KindUsage,
+    KindValidationError, SearchFilters,
+};
 use std::path::PathBuf;
 
 #[derive(Args)]
@@ -14,14 +18,23 @@ pub enum KindCommands {
     /// Register a new artifact kind
     Add(KindAddArgs),
 
-    /// List all registered kinds
-    List,
+    /// List kinds with their artifact counts, including unregistered ones in use
+    List(KindListArgs),
 
     /// Show details of a registered kind
     Show(KindShowArgs),
 
     /// Remove a registered kind
     Remove(KindRemoveArgs),
+
+    /// Rename a kind, moving its artifacts and settings to the new slug
+    Rename(KindRenameArgs),
+
+    /// Fold a kind into another, moving its artifacts there
+    Merge(KindMergeArgs),
+
+    /// Register a kind that artifacts already use
+    Adopt(KindAdoptArgs),
 }
 
 #[derive(Args)]
@@ -32,6 +45,41 @@ pub struct KindAddArgs {
     /// Description of what artifacts of this kind contain.
     /// Helps LLMs understand when to use this kind.
     pub description: String,
+
+    /// Top-level YAML/JSON content field to store as a queryable column (repeatable).
+    /// Filter on it with `dna list --where <field>=<value>`.
+    #[arg(long = "field")]
+    pub fields: Vec<String>,
+
+    /// Default content format for new artifacts of this kind
+    /// [possible values: markdown, yaml, json, openapi, text]
+    #[arg(long)]
+    pub format: Option<String>,
+
+    /// Content scaffold offered by `dna add --edit`, with {{name}} placeholders
+    /// that must be filled in before an artifact is saved
+    #[arg(long, conflicts_with = "template_file")]
+    pub template: Option<String>,
+
+    /// Read the content scaffold from a file
+    #[arg(long)]
+    pub template_file: Option<PathBuf>,
+
+    /// Guidance for agents using this kind, added to its MCP tool descriptions
+    #[arg(long)]
+    pub instructions: Option<String>,
+
+    /// What to embed: content (default), full (name, labels and content), or a
+    /// template such as "{kind}: {name}\n{content}"
+    #[arg(long)]
+    pub embed: Option<String>,
+}
+
+#[derive(Args)]
+pub struct KindListArgs {
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
 }
 
 #[derive(Args)]
@@ -50,17 +98,52 @@ pub struct KindRemoveArgs {
     pub force: bool,
 }
 
+#[derive(Args)]
+pub struct KindRenameArgs {
+    /// Current kind slug
+    pub old: String,
+
+    /// New kind name (will be slugified)
+    pub new: String,
+}
+
+#[derive(Args)]
+pub struct KindMergeArgs {
+    /// Kind to fold in and remove
+    pub slug: String,
+
+    /// Registered kind that takes its artifacts
+    #[arg(long)]
+    pub into: String,
+
+    /// Skip the confirmation prompt
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+#[derive(Args)]
+pub struct KindAdoptArgs {
+    /// Kind slug the artifacts use
+    pub slug: String,
+
+    /// Description of what artifacts of this kind contain
+    pub description: Option<String>,
+}
+
 pub async fn execute(args: KindArgs) -> Result<()> {
     match args.command {
         KindCommands::Add(add_args) => execute_add(add_args).await,
-        KindCommands::List => execute_list().await,
+        KindCommands::List(list_args) => execute_list(list_args).await,
         KindCommands::Show(show_args) => execute_show(show_args).await,
         KindCommands::Remove(remove_args) => execute_remove(remove_args).await,
+        KindCommands::Rename(rename_args) => execute_rename(rename_args).await,
+        KindCommands::Merge(merge_args) => execute_merge(merge_args).await,
+        KindCommands::Adopt(adopt_args) => execute_adopt(adopt_args).await,
     }
 }
 
 async fn execute_add(args: KindAddArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -71,8 +154,31 @@ async fn execute_add(args: KindAddArgs) -> Result<()> {
 
     let slug = slugify_kind(&args.name);
     let description = args.description;
+    let format = args
+        .format
+        .as_deref()
+        .map(str::parse::<ContentFormat>)
+        .transpose()?;
+    let template =
+        match args.template_file {
+            Some(path) => Some(std::fs::read_to_string(&path).map_err(|e| {
+                anyhow::anyhow!("Failed to read template {}: {}", path.display(), e)
+            })?),
+            None => args.template,
+        };
+
+    let definition = KindDefinition {
+        slug: slug.clone(),
+        description: description.clone(),
+        fields: args.fields.clone(),
+        format,
+        template,
+        instructions: args.instructions.clone(),
+        embed: args.embed.clone(),
+    };
+    let placeholders = definition.placeholders();
 
-    let added = match config_service.add_kind(&slug, &description) {
+    let added = match config_service.add_kind_definition(definition) {
         Ok(added) => added,
         Err(e) => {
             // Check if it's a validation error and provide a user-friendly message
@@ -88,6 +194,18 @@ async fn execute_add(args: KindAddArgs) -> Result<()> {
     if added {
         println!("Added kind: {}", slug);
         println!("  Description: {}", description);
+        if !args.fields.is_empty() {
+            println!("  Fields: {}", args.fields.join(", "));
+        }
+        if let Some(format) = format {
+            println!("  Format: {}", format);
+        }
+        if !placeholders.is_empty() {
+            println!("  Template placeholders: {}", placeholders.join(", "));
+        }
+        if let Some(embed) = &args.embed {
+            println!("  Embeds: {}", embed.escape_debug());
+        }
         println!();
         println!("You can now use:");
         println!(
@@ -116,8 +234,8 @@ async fn execute_add(args: KindAddArgs) -> Result<()> {
     Ok(())
 }
 
-async fn execute_list() -> Result<()> {
-    let project_root = PathBuf::from(".");
+async fn execute_list(args: KindListArgs) -> Result<()> {
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -127,23 +245,65 @@ async fn execute_list() -> Result<()> {
     }
 
     let config = config_service.load()?;
-    let kinds = &config.kinds.definitions;
+    // Counting needs only the store, not the embedding model
+    let db = config_service.open_database(&project_root).await?;
+    let artifacts = db.list(SearchFilters::default()).await?;
+    let usage = config.kinds.usage(&artifacts);
 
-    if kinds.is_empty() {
-        println!("No kinds registered. Use 'dna kind add <name>' or 'dna init --intent-flow'.");
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&usage)?);
         return Ok(());
     }
 
-    println!("Registered kinds ({}):", kinds.len());
-    for kind in kinds {
-        println!("  {} - {}", kind.slug, kind.description);
+    let (registered, orphans): (Vec<&KindUsage>, Vec<&KindUsage>) =
+        usage.iter().partition(|u| u.registered);
+
+    if registered.is_empty() {
+        println!("No kinds registered. Use 'dna kind add <name>' or 'dna init --template <name>'.");
+    } else {
+        println!("Registered kinds ({}):", registered.len());
+        for kind in &registered {
+            let description = config
+                .kinds
+                .get(&kind.slug)
+                .map(|d| d.description.as_str())
+                .unwrap_or_default();
+            println!("  {}  {}", usage_line(kind), description);
+        }
+    }
+
+    if !orphans.is_empty() {
+        let count: usize = orphans.iter().map(|u| u.artifacts).sum();
+        println!();
+        println!(
+            "Warning: {} artifact(s) have a kind that is not registered:",
+            count
+        );
+        for kind in &orphans {
+            println!("  {}", usage_line(kind));
+        }
+        println!(
+            "Register one with 'dna kind adopt <slug>', or move its artifacts with 'dna kind merge <slug> --into <kind>'."
+        );
     }
 
     Ok(())
 }
 
+/// Slug, artifact count and last update, padded to line up
+fn usage_line(kind: &KindUsage) -> String {
+    let last_used = kind
+        .last_used
+        .map(|at| at.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "never".to_string());
+    format!(
+        "{:<16} {:>5} artifact(s)  last used {:<10}",
+        kind.slug, kind.artifacts, last_used
+    )
+}
+
 async fn execute_show(args: KindShowArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -159,10 +319,31 @@ async fn execute_show(args: KindShowArgs) -> Result<()> {
         Some(kind) => {
             println!("Kind: {}", kind.slug);
             println!("Description: {}", kind.description);
+            if !kind.fields.is_empty() {
+                println!("Fields: {}", kind.fields.join(", "));
+            }
+            if let Some(format) = kind.format {
+                println!("Format: {}", format);
+            }
+            if let Some(template) = &kind.template {
+                println!("Template:");
+                for line in template.lines() {
+                    println!("  {}", line);
+                }
+            }
+            if let Some(instructions) = &kind.instructions {
+                println!("Instructions: {}", instructions);
+            }
+            if let Some(embed) = &kind.embed {
+                println!("Embeds: {}", embed.escape_debug());
+            }
             println!();
             let tool_prefix = slug.replace('-', "_");
             println!("CLI:");
             println!("  dna add {} <content>", slug);
+            if kind.template.is_some() {
+                println!("  dna add {} --edit", slug);
+            }
             println!("  dna search <query> --kind {}", slug);
             println!("  dna list --kind {}", slug);
             println!();
@@ -185,7 +366,7 @@ async fn execute_show(args: KindShowArgs) -> Result<()> {
 }
 
 async fn execute_remove(args: KindRemoveArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -224,6 +405,113 @@ async fn execute_remove(args: KindRemoveArgs) -> Result<()> {
     Ok(())
 }
 
+async fn execute_rename(args: KindRenameArgs) -> Result<()> {
+    let config_service = ConfigService::new(&super::project_root());
+    let config = config_service.load()?;
+    let old = slugify_kind(&args.old);
+    let new = slugify_kind(&args.new);
+
+    validate_kind_slug(&new).map_err(|e| anyhow::anyhow!("{}", format_validation_error(&e)))?;
+    if config.kinds.has(&new) {
+        return Err(anyhow::anyhow!(
+            "Kind '{}' already exists; use 'dna kind merge {} --into {}' to combine them",
+            new,
+            old,
+            new
+        ));
+    }
+
+    let moved = move_kind(&config_service, &old, &new).await?;
+    println!("Renamed kind {} to {}", old, new);
+    println!("  Artifacts moved: {}", moved);
+    Ok(())
+}
+
+async fn execute_merge(args: KindMergeArgs) -> Result<()> {
+    let config_service = ConfigService::new(&super::project_root());
+    let config = config_service.load()?;
+    let from = slugify_kind(&args.slug);
+    let into = slugify_kind(&args.into);
+
+    if !config.kinds.has(&into) {
+        return Err(anyhow::anyhow!(
+            "Kind '{}' is not registered; use 'dna kind rename {} {}' instead",
+            into,
+            from,
+            into
+        ));
+    }
+
+    let prompt = format!(
+        "Move all {} artifacts to {} and remove kind {}? This cannot be split apart again.",
+        from, into, from
+    );
+    if !super::confirm(&prompt, args.yes)? {
+        return Ok(());
+    }
+
+    let moved = move_kind(&config_service, &from, &into).await?;
+    println!("Merged kind {} into {}", from, into);
+    println!("  Artifacts moved: {}", moved);
+    Ok(())
+}
+
+/// Move the artifacts of kind `from` to `to`, then the config.
+///
+/// The store goes first so a failure there leaves the config as it was.
+async fn move_kind(config_service: &ConfigService, from: &str, to: &str) -> Result<usize> {
+    if from == to {
+        return Err(anyhow::anyhow!("Kind '{}' is already called that", from));
+    }
+    let db = config_service.open_database(&super::project_root()).await?;
+    let moved = db.rename_kind(from, to).await?;
+    config_service.move_kind(from, to)?;
+    Ok(moved)
+}
+
+async fn execute_adopt(args: KindAdoptArgs) -> Result<()> {
+    let config_service = ConfigService::new(&super::project_root());
+    let config = config_service.load()?;
+    let slug = args.slug.trim().to_string();
+
+    if config.kinds.has(&slug) {
+        return Err(anyhow::anyhow!("Kind '{}' is already registered", slug));
+    }
+
+    let db = config_service.open_database(&super::project_root()).await?;
+    let filters = SearchFilters {
+        kind: Some(slug.clone()),
+        include_archived: true,
+        ..Default::default()
+    };
+    let count = db.list(filters).await?.len();
+    if count == 0 {
+        return Err(anyhow::anyhow!(
+            "No artifacts have kind '{}'; register a new kind with 'dna kind add'",
+            slug
+        ));
+    }
+
+    let description = args
+        .description
+        .unwrap_or_else(|| format!("Adopted from {} existing artifact(s)", count));
+    if let Err(e) = config_service.add_kind(&slug, &description) {
+        if let Some(validation_error) = e.downcast_ref::<KindValidationError>() {
+            return Err(anyhow::anyhow!(
+                "{}. Move its artifacts to a valid slug with 'dna kind rename {} <new>' instead.",
+                format_validation_error(validation_error),
+                slug
+            ));
+        }
+        return Err(e);
+    }
+
+    println!("Adopted kind: {}", slug);
+    println!("  Description: {}", description);
+    println!("  Artifacts: {}", count);
+    Ok(())
+}
+
 fn format_validation_error(error: &KindValidationError) -> String {
     match error {
         KindValidationError::Empty => "Kind slug cannot be empty".to_string(),
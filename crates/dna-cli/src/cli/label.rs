@@ -1,7 +1,8 @@
 use anyhow::Result;
 use clap::{Args, Subcommand};
-use dna::services::{slugify_kind, ConfigService, KindValidationError};
-use std::path::PathBuf;
+use dna::services::{
+    slugify_kind, ConfigService, KindValidationError, LabelDefinition, LabelValueType,
+};
 
 #[derive(Args)]
 pub struct LabelArgs {
@@ -32,6 +33,18 @@ pub struct LabelAddArgs {
     /// Description of what this label represents.
     /// Helps LLMs understand when to use this label.
     pub description: String,
+
+    /// Value type: string, enum, bool, or date
+    #[arg(long = "type", value_name = "TYPE", default_value = "string")]
+    pub value_type: String,
+
+    /// Allowed values for enum labels (comma-separated, implies --type enum)
+    #[arg(long, value_delimiter = ',')]
+    pub values: Vec<String>,
+
+    /// Restrict the label to these kinds (repeatable, defaults to all kinds)
+    #[arg(long = "kind")]
+    pub kinds: Vec<String>,
 }
 
 #[derive(Args)]
@@ -60,7 +73,7 @@ pub async fn execute(args: LabelArgs) -> Result<()> {
 }
 
 async fn execute_add(args: LabelAddArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -70,9 +83,19 @@ async fn execute_add(args: LabelAddArgs) -> Result<()> {
     }
 
     let key = slugify_kind(&args.key);
-    let description = args.description;
+    let mut value_type: LabelValueType = args.value_type.parse()?;
+    if !args.values.is_empty() && value_type == LabelValueType::String {
+        value_type = LabelValueType::Enum;
+    }
+    let definition = LabelDefinition {
+        key: key.clone(),
+        description: args.description,
+        value_type,
+        values: args.values,
+        kinds: args.kinds.iter().map(|k| slugify_kind(k)).collect(),
+    };
 
-    let added = match config_service.add_label(&key, &description) {
+    let added = match config_service.add_label_definition(definition.clone()) {
         Ok(added) => added,
         Err(e) => {
             if let Some(validation_error) = e.downcast_ref::<KindValidationError>() {
@@ -86,7 +109,8 @@ async fn execute_add(args: LabelAddArgs) -> Result<()> {
     };
     if added {
         println!("Added label: {}", key);
-        println!("  Description: {}", description);
+        println!("  Description: {}", definition.description);
+        print_constraints(&definition, "  ");
         println!();
         println!("You can now use:");
         println!("  dna add <kind> <content> --label {}=<value>", key);
@@ -98,7 +122,7 @@ async fn execute_add(args: LabelAddArgs) -> Result<()> {
 }
 
 async fn execute_list() -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -118,13 +142,14 @@ async fn execute_list() -> Result<()> {
     println!("Registered labels ({}):", labels.len());
     for label in labels {
         println!("  {} - {}", label.key, label.description);
+        print_constraints(label, "      ");
     }
 
     Ok(())
 }
 
 async fn execute_show(args: LabelShowArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -140,6 +165,7 @@ async fn execute_show(args: LabelShowArgs) -> Result<()> {
         Some(label) => {
             println!("Label: {}", label.key);
             println!("Description: {}", label.description);
+            print_constraints(label, "");
             println!();
             println!("Usage:");
             println!("  dna add <kind> <content> --label {}=<value>", key);
@@ -154,7 +180,7 @@ async fn execute_show(args: LabelShowArgs) -> Result<()> {
 }
 
 async fn execute_remove(args: LabelRemoveArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -191,6 +217,16 @@ async fn execute_remove(args: LabelRemoveArgs) -> Result<()> {
     Ok(())
 }
 
+/// Print the value type and kind scope of a label, omitting defaults
+fn print_constraints(label: &LabelDefinition, indent: &str) {
+    if label.value_type != LabelValueType::String {
+        println!("{}Values: {}", indent, label.value_hint());
+    }
+    if !label.kinds.is_empty() {
+        println!("{}Kinds: {}", indent, label.kinds.join(", "));
+    }
+}
+
 fn format_validation_error(error: &KindValidationError) -> String {
     match error {
         KindValidationError::Empty => "Label key cannot be empty".to_string(),
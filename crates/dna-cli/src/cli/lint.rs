@@ -0,0 +1,79 @@
+use anyhow::Result;
+use clap::Args;
+use dna::services::{
+    slugify_kind, ConfigService, LintService, LintSeverity, Linter, SearchFilters, ServiceError,
+};
+use std::sync::Arc;
+
+#[derive(Args)]
+pub struct LintArgs {
+    /// Only lint artifacts of this kind
+    #[arg(long)]
+    pub kind: Option<String>,
+
+    /// Only lint artifacts in this namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// Apply mechanical fixes (replaceable words, whitespace, label defaults)
+    /// before reporting what is left
+    #[arg(long)]
+    pub fix: bool,
+
+    /// Output the report as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(args: LintArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config = ConfigService::new(&project_root).load()?;
+    let linter = Linter::from_config(&config.lint)?;
+    if linter.is_empty() {
+        return Err(anyhow::anyhow!(
+            "No lint rules configured; add them under [lint.kinds.<kind>] or [lint.glossary] in .dna/config.toml"
+        ));
+    }
+
+    let service = Arc::new(super::artifact::create_service().await?);
+    let filters = SearchFilters {
+        kind: args.kind.as_deref().map(slugify_kind),
+        namespace: args.namespace,
+        ..Default::default()
+    };
+    let report = LintService::new(service, linter)
+        .run(filters, args.fix)
+        .await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for violation in &report.violations {
+            println!(
+                "  {:<8} {} ({}) {}: {}{}",
+                violation.severity,
+                violation.id,
+                violation.kind,
+                violation.rule,
+                violation.message,
+                if violation.fixable { " [fixable]" } else { "" }
+            );
+        }
+        if !report.fixed.is_empty() {
+            println!("Fixed {} artifact(s)", report.fixed.len());
+        }
+        println!(
+            "Checked {} artifact(s): {} error(s), {} warning(s), {} info",
+            report.checked,
+            report.count(LintSeverity::Error),
+            report.count(LintSeverity::Warning),
+            report.count(LintSeverity::Info)
+        );
+    }
+
+    let errors = report.count(LintSeverity::Error);
+    if errors > 0 {
+        return Err(ServiceError::Validation(format!("{} lint error(s)", errors)).into());
+    }
+    Ok(())
+}
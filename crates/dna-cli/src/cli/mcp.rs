@@ -1,23 +1,200 @@
-use anyhow::Result;
-use clap::Args;
-use dna::mcp::{DnaToolHandler, RegisteredKind, RegisteredLabel};
-use dna::services::ConfigService;
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use dna::mcp::{
+    read_session, replay, tool_names, DnaToolHandler, McpClient, RegisteredKind, RegisteredLabel,
+    ServerManifest, SessionLog,
+};
+use dna::services::{
+    slugify_kind, Access, AccessPolicy, ArtifactService, ConfigService, FederatedSearch,
+    KindAccess, ProjectConfig, SearchService, Signer, Variables, Verifier,
+};
 use rmcp::ServiceExt;
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 #[derive(Args)]
 pub struct McpArgs {
+    #[command(subcommand)]
+    command: Option<McpCommands>,
+
+    /// Project directory to serve [default: current directory]
+    #[arg(long, global = true)]
+    project: Option<PathBuf>,
+
     /// Include only specified tools (comma-separated)
-    #[arg(long)]
+    #[arg(long, global = true)]
     include: Option<String>,
 
     /// Exclude specified tools (comma-separated)
-    #[arg(long)]
+    #[arg(long, global = true)]
     exclude: Option<String>,
+
+    /// Namespace tools read from and add to unless a call names another
+    #[arg(long = "ns", value_name = "NAMESPACE", global = true)]
+    namespace: Option<String>,
+
+    /// Only let tools read these kinds, plus any in --write-kinds (comma-separated)
+    #[arg(long, value_delimiter = ',', global = true)]
+    read_kinds: Option<Vec<String>>,
+
+    /// Only let tools add, update and remove these kinds (comma-separated)
+    #[arg(long, value_delimiter = ',', global = true)]
+    write_kinds: Option<Vec<String>>,
+
+    /// Append every tool call, its result and timing to this file (JSON lines)
+    #[arg(long, value_name = "FILE")]
+    log_session: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum McpCommands {
+    /// Print client configuration that starts this server
+    Manifest(ManifestArgs),
+
+    /// Re-run the tool calls of a session log against the store
+    Replay(ReplayArgs),
+}
+
+#[derive(Args)]
+struct ReplayArgs {
+    /// Session log written by --log-session
+    session: PathBuf,
+
+    /// Output each recorded and replayed call as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct ManifestArgs {
+    /// Client to configure: claude-desktop, cursor or generic
+    #[arg(long, default_value = "generic")]
+    client: String,
+
+    /// Command the client runs [default: this dna executable]
+    #[arg(long)]
+    command: Option<String>,
+
+    /// Name to register the server under
+    #[arg(long, default_value = "dna")]
+    name: String,
+}
+
+/// Split a comma-separated tool filter
+fn parse_tools(filter: Option<&String>) -> Option<Vec<String>> {
+    filter.map(|s| s.split(',').map(|t| t.trim().to_string()).collect())
+}
+
+/// Kinds the server may read and write, from --read-kinds and --write-kinds
+fn kind_access(args: &McpArgs) -> KindAccess {
+    let slugs = |kinds: &Vec<String>| {
+        kinds
+            .iter()
+            .map(|kind| slugify_kind(kind.trim()))
+            .filter(|kind| !kind.is_empty())
+            .collect()
+    };
+    KindAccess {
+        read: args.read_kinds.as_ref().map(slugs),
+        write: args.write_kinds.as_ref().map(slugs),
+    }
+}
+
+/// Registered kinds from config, for dynamic tool generation
+fn registered_kinds(config: &ProjectConfig) -> Vec<RegisteredKind> {
+    config
+        .kinds
+        .definitions
+        .iter()
+        .map(RegisteredKind::from)
+        .collect()
+}
+
+fn manifest(
+    args: &McpArgs,
+    manifest_args: &ManifestArgs,
+    project_root: &std::path::Path,
+    config: &ProjectConfig,
+) -> Result<ServerManifest> {
+    // Clients start servers from a directory of their choosing
+    let project_root = project_root
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", project_root.display()))?;
+    let command = match &manifest_args.command {
+        Some(command) => command.clone(),
+        None => std::env::current_exe()
+            .context("Failed to locate the dna executable")?
+            .to_string_lossy()
+            .to_string(),
+    };
+
+    let mut cli_args = vec![
+        "mcp".to_string(),
+        "--project".to_string(),
+        project_root.to_string_lossy().to_string(),
+    ];
+    if let Some(include) = &args.include {
+        cli_args.extend(["--include".to_string(), include.clone()]);
+    }
+    if let Some(exclude) = &args.exclude {
+        cli_args.extend(["--exclude".to_string(), exclude.clone()]);
+    }
+    if let Some(namespace) = &args.namespace {
+        cli_args.extend(["--ns".to_string(), namespace.clone()]);
+    }
+    if let Some(kinds) = &args.read_kinds {
+        cli_args.extend(["--read-kinds".to_string(), kinds.join(",")]);
+    }
+    if let Some(kinds) = &args.write_kinds {
+        cli_args.extend(["--write-kinds".to_string(), kinds.join(",")]);
+    }
+    if let Some(log) = &args.log_session {
+        // The log may not exist yet, so it can't be canonicalized
+        let log = std::path::absolute(log)
+            .with_context(|| format!("Failed to resolve {}", log.display()))?;
+        cli_args.extend([
+            "--log-session".to_string(),
+            log.to_string_lossy().to_string(),
+        ]);
+    }
+
+    // GUI clients rarely inherit the shell environment; secrets get a placeholder
+    let mut env = BTreeMap::new();
+    if config.model.provider == "openai" && config.model.api_key.is_none() {
+        env.insert(
+            "DNA_MODEL__API_KEY".to_string(),
+            "<your OpenAI API key>".to_string(),
+        );
+    }
+    if let Ok(hf_home) = std::env::var("HF_HOME") {
+        env.insert("HF_HOME".to_string(), hf_home);
+    }
+
+    Ok(ServerManifest {
+        name: manifest_args.name.clone(),
+        command,
+        args: cli_args,
+        env,
+        tools: tool_names(
+            &registered_kinds(config),
+            parse_tools(args.include.as_ref()).as_deref(),
+            parse_tools(args.exclude.as_ref()).as_deref(),
+        ),
+    })
+}
+
+/// A kind allowlist for the startup note
+fn describe_kinds(kinds: Option<Vec<String>>) -> String {
+    match kinds {
+        None => "all".to_string(),
+        Some(kinds) if kinds.is_empty() => "none".to_string(),
+        Some(kinds) => kinds.join(", "),
+    }
 }
 
 pub async fn execute(args: McpArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = args.project.clone().unwrap_or_else(super::project_root);
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -27,59 +204,159 @@ pub async fn execute(args: McpArgs) -> Result<()> {
     }
 
     let config = config_service.load()?;
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = std::sync::Arc::new(dna::db::lance::LanceDatabase::new(&storage_uri).await?);
-    let embedding = dna::embedding::create_provider(&config.model).await?;
 
-    // Parse tool filters
-    let include_tools = args.include.as_ref().map(|s| {
-        s.split(',')
-            .map(|t| t.trim().to_string())
-            .collect::<Vec<_>>()
-    });
-
-    let exclude_tools = args.exclude.as_ref().map(|s| {
-        s.split(',')
-            .map(|t| t.trim().to_string())
-            .collect::<Vec<_>>()
-    });
+    if let Some(McpCommands::Manifest(manifest_args)) = &args.command {
+        let client: McpClient = manifest_args.client.parse()?;
+        let manifest = manifest(&args, manifest_args, &project_root, &config)?;
+        // Notes go to stderr so stdout can be piped straight into a file
+        eprintln!("Add this to {}", client.config_location());
+        eprintln!(
+            "The server exposes {} tools: {}",
+            manifest.tools.len(),
+            manifest.tools.join(", ")
+        );
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&manifest.render(client))?
+        );
+        return Ok(());
+    }
+
+    if let Some(McpCommands::Replay(replay_args)) = &args.command {
+        let entries = read_session(&replay_args.session)?;
+        let handler = build_handler(&args, &config_service, &project_root, &config).await?;
+        return replay_session(&handler, entries, replay_args.json).await;
+    }
 
     // Log to stderr for stdio servers
     eprintln!("Starting DNA MCP server...");
 
-    // Build registered kinds from config
-    let kinds: Vec<RegisteredKind> = config
-        .kinds
-        .definitions
-        .iter()
-        .map(|d| RegisteredKind {
-            slug: d.slug.clone(),
-            description: d.description.clone(),
-        })
-        .collect();
+    let mut handler = build_handler(&args, &config_service, &project_root, &config).await?;
+    if let Some(path) = &args.log_session {
+        eprintln!("Logging tool calls to {}", path.display());
+        handler = handler.with_session_log(Arc::new(SessionLog::open(path)?));
+    }
+    let service = handler.serve(rmcp::transport::io::stdio()).await?;
+    service.waiting().await?;
+
+    Ok(())
+}
+
+/// Re-run recorded calls and report which ones came back differently
+async fn replay_session(
+    handler: &DnaToolHandler,
+    entries: Vec<dna::mcp::SessionEntry>,
+    json: bool,
+) -> Result<()> {
+    let calls = replay(handler, entries).await;
+    if json {
+        println!("{}", serde_json::to_string_pretty(&calls)?);
+        return Ok(());
+    }
+
+    let mut differing = 0;
+    for (index, call) in calls.iter().enumerate() {
+        let status = |failed: bool| if failed { "error" } else { "ok" };
+        let matches = call.matches();
+        if !matches {
+            differing += 1;
+        }
+        println!(
+            "{:>4}  {:<24}  {:>6}ms -> {:>6}ms  {:<5} -> {:<5}{}",
+            index + 1,
+            call.recorded.tool,
+            call.recorded.duration_ms,
+            call.replayed.duration_ms,
+            status(call.recorded.failed()),
+            status(call.replayed.failed()),
+            if matches { "" } else { "  (differs)" }
+        );
+    }
+    println!(
+        "Replayed {} call(s); {} returned something other than recorded",
+        calls.len(),
+        differing
+    );
+    Ok(())
+}
+
+/// The tool handler `dna mcp` serves, as configured by `args` and the project
+async fn build_handler(
+    args: &McpArgs,
+    config_service: &ConfigService,
+    project_root: &std::path::Path,
+    config: &ProjectConfig,
+) -> Result<DnaToolHandler> {
+    let db = Arc::new(config_service.open_database(project_root).await?);
+    let embedding = dna::embedding::create_provider(&config.model).await?;
+    let extra_models = dna::embedding::create_extra_providers(&config.extra_models).await;
+
+    // Parse tool filters
+    let include_tools = parse_tools(args.include.as_ref());
+    let exclude_tools = parse_tools(args.exclude.as_ref());
+
+    let kinds = registered_kinds(config);
 
     // Build registered labels from config
-    let labels: Vec<RegisteredLabel> = config
-        .labels
-        .definitions
-        .iter()
-        .map(|d| RegisteredLabel {
-            key: d.key.clone(),
-            description: d.description.clone(),
-        })
-        .collect();
-
-    // Create handler and start server with stdio transport
-    let handler = DnaToolHandler::with_kinds_and_labels(
+    let labels: Vec<RegisteredLabel> = config.labels.definitions.clone();
+
+    let signer = Signer::from_config(&config.signing, project_root)?;
+    let verifier = Verifier::from_config(&config.signing, signer.as_ref())?;
+    let mut artifact_service =
+        ArtifactService::from_config(db.clone(), embedding.clone(), config, project_root)?
+            .with_namespace(args.namespace.clone())
+            .with_extra_models(extra_models.clone());
+    let mut search_service = SearchService::from_config(db.clone(), embedding.clone(), config)
+        .with_extra_models(extra_models);
+    let access = kind_access(args);
+    if !access.is_unrestricted() {
+        eprintln!(
+            "Reading kinds: {}; writing kinds: {}",
+            describe_kinds(access.kinds(Access::Read)),
+            describe_kinds(access.kinds(Access::Write)),
+        );
+        let policy: Arc<dyn AccessPolicy> = Arc::new(access);
+        artifact_service = artifact_service.with_access_policy(policy.clone());
+        search_service = search_service.with_access_policy(policy);
+    }
+    let search_service = Arc::new(search_service);
+    let federation = if config.search.federate.is_empty() {
+        None
+    } else {
+        let federation = FederatedSearch::from_config(
+            search_service.clone(),
+            &config.search.federate,
+            project_root,
+        )
+        .await?;
+        eprintln!(
+            "Searching stores: {}",
+            federation.store_names().collect::<Vec<_>>().join(", ")
+        );
+        Some(Arc::new(federation))
+    };
+
+    let mut handler = DnaToolHandler::with_kinds_and_labels(
         db,
         embedding,
         include_tools,
         exclude_tools,
         kinds,
         labels,
-    );
-    let service = handler.serve(rmcp::transport::io::stdio()).await?;
-    service.waiting().await?;
-
-    Ok(())
+    )
+    .with_artifact_service(Arc::new(artifact_service))
+    .with_search_service(search_service)
+    .with_namespace(args.namespace.clone())
+    .with_verifier(verifier)
+    .with_mcp_config(&config.mcp)
+    .with_completion(dna::services::completion::create_completion(
+        &config.completion,
+    )?);
+    if let Some(federation) = federation {
+        handler = handler.with_federation(federation);
+    }
+    if !config.variables.is_default() {
+        handler = handler.with_variables(Variables::from_config(&config.variables, project_root)?);
+    }
+    Ok(handler)
 }
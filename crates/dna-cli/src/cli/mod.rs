@@ -1,18 +1,85 @@
+mod apply;
 mod artifact;
+mod ask;
+mod backup;
+mod bench;
 mod config;
 mod context;
+mod dedupe;
+mod digest;
+mod fsck;
+mod import;
+mod ingest;
 mod init;
 mod kind;
 mod label;
+mod lint;
 mod mcp;
+mod model;
+mod monitors;
+mod notify;
+mod outbox;
+mod owners;
+mod pack;
 mod render;
 mod search;
+mod seed;
+mod serve;
+mod sign;
+mod store;
+mod sync;
+mod table;
+mod variables;
 mod version;
+mod watch;
 
 use anyhow::Result;
 use clap::{Parser, Subcommand};
-use dna::services::ProjectConfig;
+use dna::services::{slugify_kind, stores, ProjectConfig, StoreRegistry};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Project directory of the store commands act on, set once by [`execute`]
+static PROJECT_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Project directory of the store commands act on, as found by
+/// [`resolve_store`], else the current directory
+pub fn project_root() -> PathBuf {
+    PROJECT_ROOT
+        .get()
+        .cloned()
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Project directory of the store to act on, in order of precedence: the
+/// `--store` value (a registered name or a path), `$DNA_DIR`, the nearest
+/// store at or above the current directory, and the store chosen with
+/// `dna store use`.
+fn resolve_store(store: Option<&str>) -> Result<Option<PathBuf>> {
+    if let Some(store) = store {
+        return StoreRegistry::from_env()?.resolve(store).map(Some);
+    }
+    if let Some(dir) = std::env::var_os(stores::DNA_DIR_ENV) {
+        let root = stores::project_root(Path::new(&dir));
+        if !stores::is_store(&root) {
+            return Err(anyhow::anyhow!(
+                "{} is set to {}, which holds no DNA store",
+                stores::DNA_DIR_ENV,
+                root.display()
+            ));
+        }
+        return Ok(Some(root));
+    }
+    if let Some(root) = stores::discover(&std::env::current_dir()?) {
+        return Ok(Some(root));
+    }
+    // Without a config directory there are no named stores to fall back to
+    match StoreRegistry::from_env() {
+        Ok(registry) => registry.current(),
+        Err(_) => Ok(None),
+    }
+}
 
 /// Parse metadata key=value pairs from command line arguments
 pub fn parse_metadata(pairs: &[String]) -> Result<HashMap<String, String>> {
@@ -35,6 +102,17 @@ pub struct Cli {
     #[arg(short, long, global = true)]
     pub verbose: bool,
 
+    /// Never download embedding models; fail if they are not cached. Writes
+    /// that can't reach the embedding provider or store are queued for
+    /// 'dna flush'
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Store to act on: a project directory or a name from 'dna store list'
+    /// [default: the current directory]
+    #[arg(long, global = true, value_name = "PATH|NAME")]
+    pub store: Option<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -56,15 +134,73 @@ pub enum Commands {
     /// Remove an artifact
     Remove(artifact::RemoveArgs),
 
+    /// Move artifacts to the archive (hidden from search by default)
+    Archive(artifact::ArchiveArgs),
+
+    /// Mark an artifact deprecated, pointing at the artifact that replaces it
+    Deprecate(artifact::DeprecateArgs),
+
+    /// Attach files, such as diagrams or spec files, to an artifact
+    Attach(artifact::AttachArgs),
+
+    /// Embed and apply writes queued in offline mode
+    Flush(outbox::FlushArgs),
+
+    /// Apply a plan file of changes all-or-nothing
+    Apply(apply::ApplyArgs),
+
+    /// Bulk-load artifacts from other formats
+    Import(import::ImportArgs),
+
+    /// Mirror issues from a tracker as artifacts
+    Ingest(ingest::IngestArgs),
+
+    /// Reconcile the store with a directory of frontmatter files
+    Sync(sync::SyncArgs),
+
     /// Semantic search across artifacts
     Search(search::SearchArgs),
 
     /// List artifacts
     List(search::ListArgs),
 
+    /// Answer a question from the store, citing artifact IDs
+    Ask(ask::AskArgs),
+
+    /// Build a context pack of the most important artifacts within a token budget
+    Pack(pack::PackArgs),
+
     /// Show artifact diffs since a date
     Diff(search::DiffArgs),
 
+    /// Print changes to artifacts as they happen
+    Watch(watch::WatchArgs),
+
+    /// Find near-identical artifacts and merge them
+    Dedupe(dedupe::DedupeArgs),
+
+    /// Write digest artifacts summarizing the artifacts of a kind
+    Digest(digest::DigestArgs),
+
+    /// Check artifacts against the lint rules configured for their kind
+    Lint(lint::LintArgs),
+
+    /// Report on who owns artifacts, and which have no owner
+    Owners(owners::OwnersArgs),
+
+    /// Build a digest per owner of changes to the artifacts they own, as
+    /// email or Slack payloads
+    Notify(notify::NotifyArgs),
+
+    /// List monitors, or their metrics and thresholds as structured data
+    Monitors(monitors::MonitorsArgs),
+
+    /// Sign artifacts with the key configured in [signing]
+    Sign(sign::SignArgs),
+
+    /// Check artifact signatures against the trusted signers
+    Verify(sign::VerifyArgs),
+
     /// Render artifacts to filesystem
     Render(render::RenderArgs),
 
@@ -74,9 +210,15 @@ pub enum Commands {
     /// Configuration management
     Config(config::ConfigArgs),
 
+    /// Manage the local embedding model cache
+    Model(model::ModelArgs),
+
     /// Start MCP server
     Mcp(mcp::McpArgs),
 
+    /// Serve the store to an editor extension over stdio
+    Serve(serve::ServeArgs),
+
     /// Manage artifact kinds
     Kind(kind::KindArgs),
 
@@ -91,52 +233,165 @@ pub enum Commands {
 
     /// List database versions
     Versions(version::VersionsArgs),
+
+    /// Snapshot the store to a backup location, or restore a snapshot
+    Backup(backup::BackupArgs),
+
+    /// Measure retrieval quality against a golden set of queries
+    Bench(bench::BenchArgs),
+
+    /// Add sample artifacts for demos and benchmarks, or remove them
+    Seed(seed::SeedArgs),
+
+    /// Check the store for damaged versions and rows
+    Fsck(fsck::FsckArgs),
+
+    /// Manage named stores shared across projects
+    Store(store::StoreArgs),
+
+    /// Print the project directory of the store commands act on
+    Root,
 }
 
-/// Validate that all label keys in metadata are registered in the project config.
+/// Validate labels against the label registry in the project config.
 ///
-/// Returns an error listing unrecognized keys with a hint to register them.
-pub fn validate_label_keys(keys: &[String], config: &ProjectConfig) -> Result<()> {
-    if config.labels.definitions.is_empty() {
-        return Ok(());
-    }
+/// Checks that keys are registered, apply to the artifact's kind, and carry
+/// values of the declared type.
+pub fn validate_labels(
+    kind: &str,
+    labels: &HashMap<String, String>,
+    config: &ProjectConfig,
+) -> Result<()> {
+    config.labels.validate(&slugify_kind(kind), labels)?;
+    Ok(())
+}
 
-    let unregistered: Vec<&str> = keys
-        .iter()
-        .filter(|k| !config.labels.has(k))
-        .map(|k| k.as_str())
-        .collect();
+/// Ask before a destructive operation; `yes` (from `--yes`) skips the prompt.
+///
+/// Returns whether to go ahead. Without a terminal to prompt on, the
+/// operation is refused instead of assumed, so scripts must pass `--yes`.
+pub fn confirm(prompt: &str, yes: bool) -> Result<bool> {
+    use std::io::{BufRead, IsTerminal, Write};
 
-    if unregistered.is_empty() {
-        return Ok(());
+    if yes {
+        return Ok(true);
+    }
+    if !std::io::stdin().is_terminal() {
+        return Err(anyhow::anyhow!(
+            "{} Refusing without confirmation; re-run with --yes.",
+            prompt
+        ));
     }
 
-    let list = unregistered.join(", ");
-    Err(anyhow::anyhow!(
-        "Unregistered label key(s): {}. Register with 'dna label add <key> <description>'.",
-        list
-    ))
+    eprint!("{} [y/N] ", prompt);
+    std::io::stderr().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    let confirmed = matches!(answer.trim().to_lowercase().as_str(), "y" | "yes");
+    if !confirmed {
+        eprintln!("Aborted.");
+    }
+    Ok(confirmed)
+}
+
+/// Open `initial` in `$VISUAL` or `$EDITOR` (falling back to `vi`) and return
+/// what was saved. `extension` names the temp file so editors pick a syntax.
+pub fn edit_text(initial: &str, extension: &str) -> Result<String> {
+    use anyhow::Context;
+    use std::io::Write;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    // The editor may carry its own arguments, like "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow::anyhow!("$EDITOR is set but empty"))?;
+
+    let mut file = tempfile::Builder::new()
+        .prefix("dna-")
+        .suffix(&format!(".{}", extension))
+        .tempfile()?;
+    file.write_all(initial.as_bytes())?;
+    file.flush()?;
+
+    let status = std::process::Command::new(program)
+        .args(parts)
+        .arg(file.path())
+        .status()
+        .with_context(|| format!("Failed to start editor '{}'", editor))?;
+    if !status.success() {
+        return Err(anyhow::anyhow!(
+            "Editor '{}' exited with {}",
+            editor,
+            status
+        ));
+    }
+    Ok(std::fs::read_to_string(file.path())?)
 }
 
 /// Execute the CLI command
 pub async fn execute(cli: Cli) -> Result<()> {
+    match &cli.command {
+        Commands::Init(_) if cli.store.is_some() => {
+            return Err(anyhow::anyhow!(
+                "--store does not apply to 'dna init'; pass the project directory instead"
+            ));
+        },
+        // Named stores are managed from anywhere, even a broken global config
+        Commands::Init(_) | Commands::Store(_) => {},
+        _ => {
+            if let Some(root) = resolve_store(cli.store.as_deref())? {
+                let _ = PROJECT_ROOT.set(root);
+            }
+        },
+    }
+
     match cli.command {
         Commands::Init(args) => init::execute(args).await,
         Commands::Add(args) => artifact::execute_add(args).await,
         Commands::Get(args) => artifact::execute_get(args).await,
         Commands::Update(args) => artifact::execute_update(args).await,
         Commands::Remove(args) => artifact::execute_remove(args).await,
+        Commands::Archive(args) => artifact::execute_archive(args).await,
+        Commands::Deprecate(args) => artifact::execute_deprecate(args).await,
+        Commands::Attach(args) => artifact::execute_attach(args).await,
+        Commands::Flush(args) => outbox::execute_flush(args).await,
+        Commands::Apply(args) => apply::execute(args).await,
+        Commands::Import(args) => import::execute(args).await,
+        Commands::Ingest(args) => ingest::execute(args).await,
+        Commands::Sync(args) => sync::execute(args).await,
         Commands::Search(args) => search::execute_search(args).await,
         Commands::List(args) => search::execute_list(args).await,
+        Commands::Ask(args) => ask::execute(args).await,
+        Commands::Pack(args) => pack::execute(args).await,
         Commands::Diff(args) => search::execute_diff(args).await,
+        Commands::Watch(args) => watch::execute(args).await,
+        Commands::Dedupe(args) => dedupe::execute(args).await,
+        Commands::Digest(args) => digest::execute(args).await,
+        Commands::Lint(args) => lint::execute(args).await,
+        Commands::Owners(args) => owners::execute(args).await,
+        Commands::Notify(args) => notify::execute(args).await,
+        Commands::Monitors(args) => monitors::execute(args).await,
+        Commands::Sign(args) => sign::execute_sign(args).await,
+        Commands::Verify(args) => sign::execute_verify(args).await,
         Commands::Render(args) => render::execute(args).await,
         Commands::Reindex(args) => search::execute_reindex(args).await,
         Commands::Config(args) => config::execute(args).await,
         Commands::Mcp(args) => mcp::execute(args).await,
+        Commands::Serve(args) => serve::execute(args).await,
+        Commands::Model(args) => model::execute(args).await,
         Commands::Kind(args) => kind::execute(args).await,
         Commands::Label(args) => label::execute(args).await,
         Commands::Context(args) => context::execute(args).await,
         Commands::Prune(args) => version::execute_prune(args).await,
         Commands::Versions(args) => version::execute_versions(args).await,
+        Commands::Backup(args) => backup::execute(args).await,
+        Commands::Bench(args) => bench::execute(args).await,
+        Commands::Seed(args) => seed::execute(args).await,
+        Commands::Fsck(args) => fsck::execute(args).await,
+        Commands::Store(args) => store::execute(args).await,
+        Commands::Root => store::execute_root(PROJECT_ROOT.get()),
     }
 }
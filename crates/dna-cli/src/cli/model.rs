@@ -0,0 +1,117 @@
+use super::version::format_bytes;
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use dna::embedding::local::{offline_from_env, ModelCache};
+use dna::services::{ConfigService, ModelConfig};
+
+#[derive(Args)]
+pub struct ModelArgs {
+    #[command(subcommand)]
+    command: ModelCommands,
+}
+
+#[derive(Subcommand)]
+enum ModelCommands {
+    /// List models in the local model cache
+    List,
+
+    /// Download a model into the cache for offline use
+    Download {
+        /// HuggingFace model id [default: the configured model]
+        model: Option<String>,
+    },
+
+    /// Remove a model from the cache
+    Remove {
+        /// HuggingFace model id
+        model: String,
+    },
+
+    /// Print the directory a model is cached in
+    Path {
+        /// HuggingFace model id [default: the configured model]
+        model: Option<String>,
+    },
+}
+
+/// Model settings from the project, or the defaults outside a project
+fn model_config() -> Result<ModelConfig> {
+    let config_service = ConfigService::new(&super::project_root());
+    if config_service.exists() {
+        Ok(config_service.load()?.model)
+    } else {
+        Ok(ModelConfig::default())
+    }
+}
+
+pub async fn execute(args: ModelArgs) -> Result<()> {
+    let config = model_config()?;
+    let cache = ModelCache::for_config(&config);
+
+    match args.command {
+        ModelCommands::List => {
+            let models = cache.list()?;
+            println!("Model cache: {}", cache.root().display());
+            if models.is_empty() {
+                println!("No models cached.");
+                return Ok(());
+            }
+
+            println!();
+            for model in models {
+                let mut notes = Vec::new();
+                if model.id == config.name {
+                    notes.push("configured");
+                }
+                if !model.complete {
+                    notes.push("incomplete");
+                }
+                let notes = if notes.is_empty() {
+                    String::new()
+                } else {
+                    format!("  ({})", notes.join(", "))
+                };
+                println!(
+                    "  {:<40} {:>10}{}",
+                    model.id,
+                    format_bytes(model.size_bytes),
+                    notes
+                );
+            }
+        },
+        ModelCommands::Download { model } => {
+            let model_id = model.unwrap_or(config.name.clone());
+            if config.offline.unwrap_or(false) || offline_from_env() {
+                return Err(anyhow::anyhow!(
+                    "Cannot download '{}' in offline mode",
+                    model_id
+                ));
+            }
+
+            println!("Downloading {}...", model_id);
+            let files = cache.download(&model_id)?;
+            println!(
+                "Cached {} in {}",
+                model_id,
+                cache.model_dir(&model_id).display()
+            );
+            tracing::debug!("Weights: {}", files.weights.display());
+        },
+        ModelCommands::Remove { model } => {
+            if cache.remove(&model)? {
+                println!("Removed {} from the model cache", model);
+            } else {
+                return Err(anyhow::anyhow!("Model not cached: {}", model));
+            }
+        },
+        ModelCommands::Path { model } => {
+            let model_id = model.unwrap_or(config.name.clone());
+            if !cache.is_cached(&model_id) {
+                eprintln!("Warning: {} is not fully cached", model_id);
+            }
+            println!("{}", cache.model_dir(&model_id).display());
+        },
+    }
+
+    Ok(())
+}
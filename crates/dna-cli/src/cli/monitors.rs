@@ -0,0 +1,179 @@
+use super::table::{self, Column, TableArgs, TableStyle};
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use comfy_table::Color;
+use dna::services::{Artifact, Monitor, SearchFilters, MONITOR_KIND};
+use serde_json::json;
+
+#[derive(Args)]
+pub struct MonitorsArgs {
+    #[command(subcommand)]
+    pub command: MonitorsCommands,
+}
+
+#[derive(Subcommand)]
+pub enum MonitorsCommands {
+    /// List monitor artifacts
+    List(MonitorsListArgs),
+}
+
+#[derive(Args)]
+pub struct MonitorsListArgs {
+    /// Only list monitors in this namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    namespace: Option<String>,
+
+    /// Show each monitor's metric, comparator, threshold and window, and
+    /// report monitors that don't describe them
+    #[arg(long)]
+    structured: bool,
+
+    /// Output the structured monitors as JSON
+    #[arg(long, requires = "structured")]
+    json: bool,
+
+    #[command(flatten)]
+    table: TableArgs,
+}
+
+pub async fn execute(args: MonitorsArgs) -> Result<()> {
+    match args.command {
+        MonitorsCommands::List(args) => execute_list(args).await,
+    }
+}
+
+async fn execute_list(args: MonitorsListArgs) -> Result<()> {
+    let service = super::artifact::create_service().await?;
+    let artifacts = service
+        .list(SearchFilters {
+            kind: Some(MONITOR_KIND.to_string()),
+            namespace: args.namespace,
+            ..Default::default()
+        })
+        .await?;
+    let style = TableStyle::detect(&args.table);
+
+    if !args.structured {
+        let columns = artifact_columns();
+        let columns = table::select(&columns, &["id", "name", "content"], &args.table.columns)?;
+        println!("Found {} monitors:", artifacts.len());
+        if !artifacts.is_empty() {
+            println!("{}", table::render(&artifacts, &columns, style));
+        }
+        return Ok(());
+    }
+
+    let mut monitors = Vec::new();
+    for artifact in artifacts {
+        match Monitor::from_artifact(&artifact) {
+            Ok(monitor) => monitors.push((artifact, monitor)),
+            Err(e) => eprintln!("Warning: skipping monitor {}: {}", artifact.id, e),
+        }
+    }
+
+    if args.json {
+        let monitors: Vec<_> = monitors.iter().map(monitor_json).collect();
+        println!("{}", serde_json::to_string_pretty(&monitors)?);
+        return Ok(());
+    }
+
+    let columns = monitor_columns();
+    let columns = table::select(
+        &columns,
+        &["id", "name", "metric", "comparator", "threshold", "window"],
+        &args.table.columns,
+    )?;
+    println!("Found {} structured monitors:", monitors.len());
+    if !monitors.is_empty() {
+        println!("{}", table::render(&monitors, &columns, style));
+    }
+    Ok(())
+}
+
+fn artifact_columns() -> Vec<Column<Artifact>> {
+    vec![
+        Column::new("id", |a: &Artifact| a.id.clone()).colored(Color::Yellow),
+        Column::new("name", |a: &Artifact| a.name.clone().unwrap_or_default()).truncated(),
+        Column::new("namespace", |a: &Artifact| {
+            a.namespace.clone().unwrap_or_default()
+        }),
+        Column::new("content", |a: &Artifact| a.content.clone()).truncated(),
+    ]
+}
+
+fn monitor_columns() -> Vec<Column<(Artifact, Monitor)>> {
+    type Row = (Artifact, Monitor);
+    vec![
+        Column::new("id", |(a, _): &Row| a.id.clone()).colored(Color::Yellow),
+        Column::new("name", |(a, _): &Row| a.name.clone().unwrap_or_default()).truncated(),
+        Column::new("namespace", |(a, _): &Row| {
+            a.namespace.clone().unwrap_or_default()
+        }),
+        Column::new("metric", |(_, m): &Row| m.metric.clone()).colored(Color::Cyan),
+        Column::new("comparator", |(_, m): &Row| m.comparator.to_string()),
+        Column::new("threshold", |(_, m): &Row| {
+            format!("{}{}", m.threshold, m.unit.as_deref().unwrap_or_default())
+        }),
+        Column::new("window", |(_, m): &Row| {
+            m.window.clone().unwrap_or_default()
+        }),
+        Column::new("expression", |(_, m): &Row| m.to_string()),
+    ]
+}
+
+fn monitor_json((artifact, monitor): &(Artifact, Monitor)) -> serde_json::Value {
+    json!({
+        "id": artifact.id,
+        "name": artifact.name,
+        "namespace": artifact.namespace,
+        "metric": monitor.metric,
+        "comparator": monitor.comparator,
+        "threshold": monitor.threshold,
+        "unit": monitor.unit,
+        "window": monitor.window,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dna::services::ContentFormat;
+    use std::collections::HashMap;
+
+    #[test]
+    fn renders_structured_monitors() {
+        let mut artifact = Artifact::new(
+            MONITOR_KIND.to_string(),
+            "error_rate < 2% over 1h".to_string(),
+            ContentFormat::Markdown,
+            Some("Payment failures".to_string()),
+            HashMap::new(),
+            String::new(),
+        );
+        artifact.id = "k7v3m9xnp2".to_string();
+        let monitor = Monitor::from_artifact(&artifact).unwrap();
+        let rows = vec![(artifact, monitor)];
+
+        let columns = monitor_columns();
+        let columns = table::select(
+            &columns,
+            &["id", "metric", "comparator", "threshold", "window"],
+            &[],
+        )
+        .unwrap();
+        let style = TableStyle {
+            tty: false,
+            color: false,
+        };
+        assert_eq!(
+            table::render(&rows, &columns, style),
+            "ID          METRIC      COMPARATOR  THRESHOLD  WINDOW\n\
+             k7v3m9xnp2  error_rate  <           2%         1h"
+        );
+
+        let json = monitor_json(&rows[0]);
+        assert_eq!(json["comparator"], "<");
+        assert_eq!(json["threshold"], 2.0);
+        assert_eq!(json["unit"], "%");
+    }
+}
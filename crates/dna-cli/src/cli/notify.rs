@@ -0,0 +1,99 @@
+use anyhow::Result;
+use clap::Args;
+use dna::services::{
+    owner_digests, parse_date, ChangedArtifact, NotifyFormat, OwnerMap, SearchFilters,
+};
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct NotifyArgs {
+    /// Include changes since this date (YYYY-MM-DD, RFC3339, 7d, "last monday", ...)
+    #[arg(long, default_value = "7d")]
+    since: String,
+
+    /// Include changes until this date. Defaults to now.
+    #[arg(long)]
+    until: Option<String>,
+
+    /// TOML file with a table per owner handle giving its name, email and
+    /// slack channel
+    #[arg(long, value_name = "FILE")]
+    owner_map: Option<PathBuf>,
+
+    /// Payload to build for each owner: email or slack-json
+    #[arg(long, default_value = "email")]
+    format: String,
+
+    /// Only include changes to this kind
+    #[arg(long)]
+    kind: Option<String>,
+
+    /// Only include changes in this namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    namespace: Option<String>,
+
+    /// Compare store versions committed in the range instead of timestamps,
+    /// so deletions are included
+    #[arg(long)]
+    history: bool,
+}
+
+/// Print a JSON array with a digest payload per owner whose artifacts
+/// changed, for an email or Slack sender to post
+pub async fn execute(args: NotifyArgs) -> Result<()> {
+    let format: NotifyFormat = args.format.parse()?;
+    let owner_map = args.owner_map.as_deref().map(OwnerMap::load).transpose()?;
+    let since = parse_date(&args.since)?;
+    let until = args.until.as_deref().map(parse_date).transpose()?;
+
+    let service = super::artifact::create_service().await?;
+    let filters = SearchFilters {
+        kind: args.kind,
+        namespace: args.namespace,
+        after: Some(since),
+        before: until,
+        ..Default::default()
+    };
+    let changes: Vec<ChangedArtifact> = if args.history {
+        let history = service.change_history(filters).await?;
+        if let Some(version) = history.truncated_at {
+            eprintln!(
+                "Note: history before version {} was cleaned up, so earlier changes are missing",
+                version
+            );
+        }
+        history.changes
+    } else {
+        service
+            .list(filters)
+            .await?
+            .into_iter()
+            .map(|a| ChangedArtifact::from_timestamps(a, Some(since)))
+            .collect()
+    };
+
+    let unowned = changes
+        .iter()
+        .filter(|c| c.artifact.owner.is_none())
+        .count();
+    if unowned > 0 {
+        eprintln!(
+            "Skipping {} changed artifacts with no owner; see 'dna owners report'",
+            unowned
+        );
+    }
+
+    let mut payloads = Vec::new();
+    for digest in owner_digests(changes, Some(since), until) {
+        let recipient = owner_map.as_ref().and_then(|map| map.get(&digest.owner));
+        if owner_map.is_some() && recipient.is_none() {
+            eprintln!(
+                "Warning: {} is not in the owner map; its digest has no address",
+                digest.owner
+            );
+        }
+        payloads.push(digest.payload(format, recipient));
+    }
+    println!("{}", serde_json::to_string_pretty(&payloads)?);
+    Ok(())
+}
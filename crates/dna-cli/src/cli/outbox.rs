@@ -0,0 +1,122 @@
+use anyhow::Result;
+use clap::Args;
+use dna::embedding::local::offline_from_env;
+use dna::services::{ConfigService, Outbox, ProjectConfig, QueuedWrite, ServiceError};
+
+#[derive(Args)]
+pub struct FlushArgs {
+    /// List the queued writes without applying them
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output the result as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+/// Whether writes that can't reach the provider or store are queued: with
+/// `--offline`, `HF_HUB_OFFLINE=1` or `offline = true` under `[model]`
+pub fn is_offline(config: &ProjectConfig) -> bool {
+    offline_from_env() || config.model.offline.unwrap_or(false)
+}
+
+/// Queue the write made by `write` in the outbox when `err` shows the
+/// embedding provider or the store couldn't be reached in offline mode;
+/// otherwise fail with `err`.
+///
+/// Rejected input and missing artifacts are never queued, as they would
+/// fail again on `dna flush`.
+pub fn queue_or_fail(
+    config: &ProjectConfig,
+    err: anyhow::Error,
+    write: impl FnOnce() -> QueuedWrite,
+) -> Result<()> {
+    if !is_offline(config) {
+        return Err(err);
+    }
+    let reason: anyhow::Error = match ServiceError::classify(err) {
+        ServiceError::Internal(err) => err,
+        err @ ServiceError::ProviderUnavailable(_) => err.into(),
+        err => return Err(err.into()),
+    };
+
+    let write = write();
+    let outbox = Outbox::new(&super::project_root());
+    outbox.push(&write)?;
+    eprintln!("Offline: {:#}", reason);
+    println!(
+        "Queued {} in {}; run 'dna flush' to apply it",
+        write.describe(),
+        outbox.dir().display()
+    );
+    if let QueuedWrite::Add { artifact } = &write {
+        println!("{}", serde_json::to_string_pretty(artifact)?);
+    }
+    Ok(())
+}
+
+pub async fn execute_flush(args: FlushArgs) -> Result<()> {
+    let project_root = super::project_root();
+    if !ConfigService::new(&project_root).exists() {
+        return Err(anyhow::anyhow!(
+            "DNA not initialized. Run 'dna init' first."
+        ));
+    }
+    let outbox = Outbox::new(&project_root);
+
+    if args.dry_run {
+        let pending = outbox.pending()?;
+        if args.json {
+            let writes: Vec<&QueuedWrite> = pending.iter().map(|(_, write)| write).collect();
+            println!("{}", serde_json::to_string_pretty(&writes)?);
+        } else if pending.is_empty() {
+            println!("No queued writes.");
+        } else {
+            for (_, write) in &pending {
+                println!("Would flush: {}", write.describe());
+            }
+        }
+        return Ok(());
+    }
+
+    if outbox.pending()?.is_empty() {
+        if args.json {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&dna::services::FlushReport::default())?
+            );
+        } else {
+            println!("No queued writes.");
+        }
+        return Ok(());
+    }
+
+    let service = super::artifact::create_service().await?;
+    let report = outbox.flush(&service).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for write in &report.flushed {
+            println!("  flushed  {}", write);
+        }
+        if let Some((write, error)) = &report.failed {
+            println!("  failed   {}: {}", write, error);
+        }
+        println!(
+            "Flushed {} write(s), {} still queued",
+            report.flushed.len(),
+            report.pending
+        );
+    }
+
+    if report.failed.is_some() {
+        return Err(anyhow::anyhow!(
+            "{} queued write(s) left in {}; fix the failed one or delete its file, then run \
+             'dna flush' again",
+            report.pending,
+            outbox.dir().display()
+        ));
+    }
+    Ok(())
+}
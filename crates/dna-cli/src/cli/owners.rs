@@ -0,0 +1,148 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use dna::services::{ownership_report, KindOwnership, SearchFilters};
+use serde_json::json;
+
+#[derive(Args)]
+pub struct OwnersArgs {
+    #[command(subcommand)]
+    pub command: OwnersCommands,
+}
+
+#[derive(Subcommand)]
+pub enum OwnersCommands {
+    /// Show how many artifacts of each kind have an owner, and list those
+    /// that don't
+    Report(OwnersReportArgs),
+}
+
+#[derive(Args)]
+pub struct OwnersReportArgs {
+    /// Only report on this kind
+    #[arg(long)]
+    kind: Option<String>,
+
+    /// Only report on this namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    namespace: Option<String>,
+
+    /// Exit with an error if any artifact has no owner, for CI
+    #[arg(long)]
+    check: bool,
+
+    /// Output the report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub async fn execute(args: OwnersArgs) -> Result<()> {
+    match args.command {
+        OwnersCommands::Report(args) => execute_report(args).await,
+    }
+}
+
+async fn execute_report(args: OwnersReportArgs) -> Result<()> {
+    let service = super::artifact::create_service().await?;
+    let artifacts = service
+        .list(SearchFilters {
+            kind: args.kind,
+            namespace: args.namespace,
+            ..Default::default()
+        })
+        .await?;
+    let report = ownership_report(&artifacts);
+
+    if args.json {
+        let kinds: Vec<_> = report.iter().map(kind_json).collect();
+        println!("{}", serde_json::to_string_pretty(&kinds)?);
+    } else if report.is_empty() {
+        println!("No artifacts found");
+    } else {
+        print!("{}", describe(&report));
+    }
+
+    let unowned: usize = report.iter().map(|k| k.unowned.len()).sum();
+    if args.check && unowned > 0 {
+        anyhow::bail!("{} artifacts have no owner", unowned);
+    }
+    Ok(())
+}
+
+fn kind_json(kind: &KindOwnership) -> serde_json::Value {
+    let unowned: Vec<_> = kind
+        .unowned
+        .iter()
+        .map(|(id, name)| json!({ "id": id, "name": name }))
+        .collect();
+    json!({
+        "kind": kind.kind,
+        "total": kind.total,
+        "owned": kind.owned,
+        "unowned": unowned,
+    })
+}
+
+/// A table of counts per kind, then the unowned artifacts by kind
+fn describe(report: &[KindOwnership]) -> String {
+    let width = report
+        .iter()
+        .map(|k| k.kind.len())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+    let mut out = format!(
+        "{:<width$}  {:>5}  {:>5}  {:>7}\n",
+        "KIND", "TOTAL", "OWNED", "UNOWNED"
+    );
+    for kind in report {
+        out.push_str(&format!(
+            "{:<width$}  {:>5}  {:>5}  {:>7}\n",
+            kind.kind,
+            kind.total,
+            kind.owned,
+            kind.unowned.len()
+        ));
+    }
+    for kind in report.iter().filter(|k| !k.unowned.is_empty()) {
+        out.push_str(&format!("\nUnowned {}:\n", kind.kind));
+        for (id, name) in &kind.unowned {
+            match name {
+                Some(name) => out.push_str(&format!("  {}  {}\n", id, name)),
+                None => out.push_str(&format!("  {}\n", id)),
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_counts_and_unowned_artifacts() {
+        let report = vec![
+            KindOwnership {
+                kind: "intent".to_string(),
+                total: 2,
+                owned: 1,
+                unowned: vec![("k7v3m9xnp2".to_string(), Some("checkout".to_string()))],
+            },
+            KindOwnership {
+                kind: "runbook".to_string(),
+                total: 1,
+                owned: 1,
+                unowned: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            describe(&report),
+            "KIND     TOTAL  OWNED  UNOWNED\n\
+             intent       2      1        1\n\
+             runbook      1      1        0\n\
+             \n\
+             Unowned intent:\n  k7v3m9xnp2  checkout\n"
+        );
+        assert_eq!(kind_json(&report[0])["unowned"][0]["id"], "k7v3m9xnp2");
+    }
+}
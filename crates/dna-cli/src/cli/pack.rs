@@ -0,0 +1,69 @@
+use super::parse_metadata;
+use super::variables::VariableArgs;
+use anyhow::Result;
+use clap::Args;
+use dna::services::{slugify_kind, ConfigService, PackService, SearchFilters};
+use std::sync::Arc;
+
+#[derive(Args)]
+pub struct PackArgs {
+    /// Approximate number of tokens the pack may take
+    #[arg(long, default_value = "4000")]
+    pub budget: usize,
+
+    /// Only pack artifacts of these kinds, in this order (comma-separated)
+    #[arg(long, value_delimiter = ',')]
+    pub kinds: Vec<String>,
+
+    /// Only pack artifacts with label key=value (can be repeated)
+    #[arg(long = "label", short = 'l')]
+    pub labels: Vec<String>,
+
+    /// Only pack artifacts in this namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    pub namespace: Option<String>,
+
+    /// Output the pack as JSON instead of Markdown
+    #[arg(long)]
+    pub json: bool,
+
+    #[command(flatten)]
+    pub variables: VariableArgs,
+}
+
+pub async fn execute(args: PackArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config = ConfigService::new(&project_root).load()?;
+    let service = Arc::new(super::artifact::create_service().await?);
+    let mut packs = PackService::new(service).with_boosts(config.search.boosts.clone());
+    if let Some(variables) = args.variables.resolve(&config, &project_root)? {
+        packs = packs.with_variables(variables);
+    }
+
+    let kinds: Vec<String> = args
+        .kinds
+        .iter()
+        .map(|kind| slugify_kind(kind.trim()))
+        .filter(|kind| !kind.is_empty())
+        .collect();
+    let filters = SearchFilters {
+        namespace: args.namespace,
+        metadata: parse_metadata(&args.labels)?,
+        ..Default::default()
+    };
+    let pack = packs.pack(filters, &kinds, args.budget).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&pack)?);
+    } else {
+        print!("{}", pack.to_markdown());
+    }
+    if !pack.omitted.is_empty() {
+        eprintln!(
+            "{} matching artifact(s) did not fit in {} tokens",
+            pack.omitted.len(),
+            pack.budget
+        );
+    }
+    Ok(())
+}
@@ -1,21 +1,64 @@
+use super::variables::VariableArgs;
 use anyhow::Result;
 use clap::Args;
-use dna::services::{ArtifactService, ConfigService, SearchFilters};
+use dna::db::remote::SearchIndex;
+use dna::services::{
+    expand_includes, ArtifactService, ConfigService, SearchFilters, DEFAULT_LINK_LABEL,
+};
 use std::path::PathBuf;
 
+/// Search index written next to the rendered files by `--index`
+const INDEX_FILE: &str = "index.json";
+
 #[derive(Args)]
 pub struct RenderArgs {
-    /// Group artifacts by metadata keys (comma-separated)
-    #[arg(long)]
-    by: Option<String>,
+    /// Group artifacts by label keys, one directory level each (comma-separated).
+    /// Files go under their kind first unless `kind` is listed; `namespace`
+    /// groups by namespace.
+    #[arg(long, alias = "by")]
+    group_by: Option<String>,
+
+    /// Render only artifacts in this namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    namespace: Option<String>,
 
     /// Output directory
     #[arg(long, default_value = "dna")]
     output: PathBuf,
+
+    /// Labels holding IDs of related artifacts, rendered as links between
+    /// files (comma-separated)
+    #[arg(long, default_value = DEFAULT_LINK_LABEL)]
+    links: String,
+
+    /// Write identical files for an unchanged store: sorted output, `\n` line
+    /// endings, and unchanged files left untouched
+    #[arg(long)]
+    deterministic: bool,
+
+    /// With --deterministic, leave volatile fields (updated_at) out of the
+    /// frontmatter
+    #[arg(long, requires = "deterministic")]
+    omit_volatile: bool,
+
+    /// List the files that would be written without writing them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Also write index.json with embeddings, for client-side search
+    #[arg(long)]
+    index: bool,
+
+    /// Leave include directives as written instead of expanding them
+    #[arg(long)]
+    no_includes: bool,
+
+    #[command(flatten)]
+    variables: VariableArgs,
 }
 
 pub async fn execute(args: RenderArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -25,19 +68,37 @@ pub async fn execute(args: RenderArgs) -> Result<()> {
     }
 
     let config = config_service.load()?;
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = std::sync::Arc::new(dna::db::lance::LanceDatabase::new(&storage_uri).await?);
+    let db = std::sync::Arc::new(config_service.open_database(&project_root).await?);
     let embedding = dna::embedding::create_provider(&config.model).await?;
 
-    let service = ArtifactService::new(db, embedding);
-    let render_service = dna::render::RenderService::new(args.output.clone());
+    let model_id = embedding.model_id().to_string();
+    let service = ArtifactService::from_config(db, embedding, &config, &project_root)?;
+    let link_labels = args
+        .links
+        .split(',')
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect();
+    let mut render_service = dna::render::RenderService::new(args.output.clone())
+        .with_link_labels(link_labels)
+        .with_deterministic(args.deterministic)
+        .with_omit_volatile(args.omit_volatile)
+        .with_attachments(config.attachments.resolve_location(&project_root));
+    if let Some(variables) = args.variables.resolve(&config, &project_root)? {
+        render_service = render_service.with_variables(variables);
+    }
 
     // Get all artifacts
-    let artifacts = service.list(SearchFilters::default()).await?;
+    let artifacts = service
+        .list(SearchFilters {
+            namespace: args.namespace.clone(),
+            ..Default::default()
+        })
+        .await?;
 
     // Parse grouping keys
     let group_by = args
-        .by
+        .group_by
         .as_ref()
         .map(|s| {
             s.split(',')
@@ -46,8 +107,41 @@ pub async fn execute(args: RenderArgs) -> Result<()> {
         })
         .unwrap_or_default();
 
-    // Render artifacts
-    render_service.render_all(&artifacts, &group_by).await?;
+    if args.dry_run {
+        let files = render_service.planned_files(&artifacts, &group_by)?;
+        println!(
+            "Would render {} artifacts to {}:",
+            files.len(),
+            args.output.display()
+        );
+        for file in files {
+            let marker = if file.exists() { "~" } else { "+" };
+            println!("  {} {}", marker, file.display());
+        }
+        if args.index {
+            println!("  + {}", args.output.join(INDEX_FILE).display());
+        }
+        return Ok(());
+    }
+
+    // Render artifacts, with includes expanded in the files only
+    let mut rendered = artifacts.clone();
+    if !args.no_includes {
+        expand_includes(&service, &mut rendered).await?;
+    }
+    render_service.render_all(&rendered, &group_by).await?;
+
+    if args.index {
+        let index = SearchIndex::new(model_id, artifacts.clone());
+        let path = args.output.join(INDEX_FILE);
+        tokio::fs::create_dir_all(&args.output).await?;
+        tokio::fs::write(&path, serde_json::to_vec(&index)?).await?;
+        println!(
+            "Wrote search index of {} artifacts to {}",
+            index.artifacts.len(),
+            path.display()
+        );
+    }
 
     println!(
         "Rendered {} artifacts to {}",
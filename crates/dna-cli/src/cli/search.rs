@@ -1,12 +1,19 @@
 use super::parse_metadata;
+use super::table::{self, Column, TableArgs, TableStyle};
 use anyhow::Result;
-use chrono::{NaiveDate, TimeZone, Utc};
 use clap::{ArgGroup, Args};
-use dna::db::Database;
-use dna::services::{ArtifactService, ConfigService, ReindexTarget, SearchFilters, SearchService};
+use comfy_table::Color;
+use dna::db::{model_slug, Database};
+use dna::services::signing::VERIFIED_CANDIDATES;
+use dna::services::{
+    normalize_handle, parse_date, parse_kind_boost, Artifact, ArtifactService, ChangeReport,
+    ChangeType, ChangedArtifact, ConfigService, EmbeddingIssue, FederatedSearch, ReindexTarget,
+    SearchExplanation, SearchFilters, SearchResult, SearchService, SearchWeights, ServiceError,
+    Signer, Verifier, LANGUAGE_LABEL,
+};
 use similar::{ChangeTag, TextDiff};
 use std::collections::HashSet;
-use std::path::PathBuf;
+use std::time::Instant;
 
 #[derive(Args)]
 pub struct SearchArgs {
@@ -17,13 +24,79 @@ pub struct SearchArgs {
     #[arg(long)]
     kind: Option<String>,
 
+    /// Filter by namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    namespace: Option<String>,
+
     /// Filter by metadata key=value
     #[arg(long = "filter")]
     filters: Vec<String>,
 
+    /// Filter by structured content field key=value (fields configured per kind)
+    #[arg(long = "where")]
+    wheres: Vec<String>,
+
+    /// Only artifacts in this language, as an ISO 639-3 code such as deu;
+    /// searched with the model [languages] routes it to, if any
+    #[arg(long)]
+    language: Option<String>,
+
+    /// Add the glossary's synonyms for terms in the query, e.g. ICP also
+    /// searches for "Ideal Customer Profile"
+    #[arg(long)]
+    expand: bool,
+
+    /// Leave out artifacts of this kind (can be repeated)
+    #[arg(long = "not-kind")]
+    not_kinds: Vec<String>,
+
+    /// Leave out artifacts with label key=value (can be repeated)
+    #[arg(long = "not-label")]
+    not_labels: Vec<String>,
+
     /// Limit number of results
     #[arg(long, default_value = "10")]
     limit: usize,
+
+    /// Include archived artifacts in results
+    #[arg(long)]
+    include_archived: bool,
+
+    /// Include deprecated artifacts in results
+    #[arg(long)]
+    include_deprecated: bool,
+
+    /// Rank by a blend of content and context similarity, e.g. content=0.7,context=0.3
+    #[arg(long)]
+    weights: Option<SearchWeights>,
+
+    /// Multiply scores of a kind, e.g. invariant=2.0 (can be repeated)
+    #[arg(long = "boost", value_parser = parse_kind_boost)]
+    boosts: Vec<(String, f32)>,
+
+    /// Show the sentences that best match the query instead of the start of the content
+    #[arg(long)]
+    show_snippets: bool,
+
+    /// Show why each result ranked: vector distances, boost and matched filters
+    #[arg(long)]
+    explain: bool,
+
+    /// Search only this store, not the stores configured in [[search.federate]]
+    #[arg(long)]
+    local: bool,
+
+    /// Only return artifacts signed by a trusted signer and unchanged since
+    #[arg(long)]
+    verified: bool,
+
+    /// Search the embeddings of this model instead of [model]'s; it must be
+    /// listed in [[extra_models]]. Searches only this store.
+    #[arg(long)]
+    model: Option<String>,
+
+    #[command(flatten)]
+    table: TableArgs,
 }
 
 #[derive(Args)]
@@ -32,30 +105,61 @@ pub struct ListArgs {
     #[arg(long)]
     kind: Option<String>,
 
+    /// Filter by namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    namespace: Option<String>,
+
+    /// Filter by owner, a person or team such as @payments
+    #[arg(long, value_name = "HANDLE")]
+    owner: Option<String>,
+
+    /// Filter by reviewer, a person or team such as @alice
+    #[arg(long, value_name = "HANDLE")]
+    reviewer: Option<String>,
+
     /// Filter by metadata key=value
     #[arg(long = "filter")]
     filters: Vec<String>,
 
-    /// Show only artifacts updated after this date (YYYY-MM-DD or RFC3339 datetime)
+    /// Filter by structured content field key=value (fields configured per kind)
+    #[arg(long = "where")]
+    wheres: Vec<String>,
+
+    /// Leave out artifacts of this kind (can be repeated)
+    #[arg(long = "not-kind")]
+    not_kinds: Vec<String>,
+
+    /// Leave out artifacts with label key=value (can be repeated)
+    #[arg(long = "not-label")]
+    not_labels: Vec<String>,
+
+    /// Show only artifacts updated after this date (YYYY-MM-DD, RFC3339, 7d, "last monday", ...)
     #[arg(long)]
     after: Option<String>,
 
-    /// Show only artifacts updated before this date (YYYY-MM-DD or RFC3339 datetime)
+    /// Show only artifacts updated before this date (YYYY-MM-DD, RFC3339, 7d, "last monday", ...)
     #[arg(long)]
     before: Option<String>,
 
     /// Limit number of results
     #[arg(long)]
     limit: Option<usize>,
+
+    /// Include archived artifacts in results
+    #[arg(long)]
+    include_archived: bool,
+
+    #[command(flatten)]
+    table: TableArgs,
 }
 
 #[derive(Args)]
 pub struct DiffArgs {
-    /// Show changes since this date (YYYY-MM-DD or RFC3339 datetime)
+    /// Show changes since this date (YYYY-MM-DD, RFC3339, 7d, "last monday", ...)
     #[arg(long)]
     since: String,
 
-    /// Show changes until this date (YYYY-MM-DD or RFC3339 datetime). Defaults to now.
+    /// Show changes until this date (YYYY-MM-DD, RFC3339, 7d, "last monday", ...). Defaults to now.
     #[arg(long)]
     until: Option<String>,
 
@@ -63,6 +167,10 @@ pub struct DiffArgs {
     #[arg(long)]
     kind: Option<String>,
 
+    /// Filter by namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    namespace: Option<String>,
+
     /// Filter by metadata label (key=value, can be repeated)
     #[arg(long = "label", short = 'l')]
     labels: Vec<String>,
@@ -74,6 +182,19 @@ pub struct DiffArgs {
     /// Show only artifact IDs, not content diffs
     #[arg(long)]
     names_only: bool,
+
+    /// Start with a summary of the changes counted by kind
+    #[arg(long)]
+    summary: bool,
+
+    /// Output a report of the changed artifacts as JSON instead of diffs
+    #[arg(long)]
+    json: bool,
+
+    /// Compare store versions committed in the range instead of timestamps,
+    /// to also show deleted artifacts and the version of each change
+    #[arg(long)]
+    history: bool,
 }
 
 /// Arguments for the reindex command.
@@ -120,8 +241,18 @@ pub struct DiffArgs {
 /// ```sh
 /// dna reindex --all --force
 /// ```
+///
+/// Repair only artifacts with missing or incompatible embeddings:
+/// ```sh
+/// dna reindex --missing-only
+/// ```
+///
+/// Fill in embeddings from a model listed in `[[extra_models]]`:
+/// ```sh
+/// dna reindex --model nomic-embed-text
+/// ```
 #[derive(Args)]
-#[command(group = ArgGroup::new("target").required(true).args(&["all", "content", "context", "id"]))]
+#[command(group = ArgGroup::new("target").required(true).args(&["all", "content", "context", "id", "missing_only", "model"]))]
 pub struct ReindexArgs {
     /// Reindex all embeddings (content + context).
     /// Use this for a full rebuild after model changes or database corruption.
@@ -142,6 +273,24 @@ pub struct ReindexArgs {
     #[arg(long)]
     pub context: bool,
 
+    /// Embeddings to regenerate: content, context or both.
+    /// Takes precedence over --content and --context.
+    #[arg(long, conflicts_with_all = ["content", "context"])]
+    pub target: Option<ReindexTarget>,
+
+    /// Reindex only artifacts whose embeddings are missing, were produced by a
+    /// different model, don't match the model's dimensions, or were composed
+    /// differently than their kind's `embed` setting.
+    /// Much cheaper than --all after restoring rows that lack vectors.
+    #[arg(long)]
+    pub missing_only: bool,
+
+    /// Embed content with this model from [[extra_models]], for artifacts
+    /// that have no embedding from it yet.
+    /// Lets a new model be filled in gradually, before switching to it.
+    #[arg(long, conflicts_with_all = ["content", "context", "target", "id"])]
+    pub model: Option<String>,
+
     /// Only reindex artifacts of this kind (e.g., "spec", "code", "doc").
     /// Useful for targeted reindexing when only certain artifact types need updates.
     #[arg(long)]
@@ -153,12 +302,12 @@ pub struct ReindexArgs {
     #[arg(long = "label", short = 'l')]
     pub labels: Vec<String>,
 
-    /// Reindex a specific artifact by its ID.
+    /// Reindex a specific artifact by its ID (or a unique ID prefix).
     /// Use this for surgical updates to individual artifacts.
     #[arg(long)]
     pub id: Option<String>,
 
-    /// Only reindex artifacts modified after this date (YYYY-MM-DD or RFC3339 datetime).
+    /// Only reindex artifacts modified after this date (YYYY-MM-DD, RFC3339, 7d, "last monday", ...).
     /// Useful for incremental reindexing after bulk imports or migrations.
     #[arg(long)]
     pub since: Option<String>,
@@ -176,7 +325,7 @@ pub struct ReindexArgs {
 }
 
 pub async fn execute_search(args: SearchArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -186,40 +335,301 @@ pub async fn execute_search(args: SearchArgs) -> Result<()> {
     }
 
     let config = config_service.load()?;
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = std::sync::Arc::new(dna::db::lance::LanceDatabase::new(&storage_uri).await?);
+    // Signatures and extra models are only kept in this store, so those
+    // searches stay local
+    let federated =
+        !args.local && !args.verified && args.model.is_none() && !config.search.federate.is_empty();
+    let columns = hit_columns();
+    let default_columns: &[&str] = if federated {
+        &["score", "id", "kind", "store", "content"]
+    } else {
+        &["score", "id", "kind", "content"]
+    };
+    let columns = table::select(&columns, default_columns, &args.table.columns)?;
+    let style = TableStyle::detect(&args.table);
+
+    let db = std::sync::Arc::new(config_service.open_database(&project_root).await?);
     let embedding = dna::embedding::create_provider(&config.model).await?;
 
-    let search_service = SearchService::new(db, embedding);
+    let mut search_service = SearchService::from_config(db, embedding, &config);
+    if args.model.is_some() || !config.languages.models.is_empty() {
+        let extra_models = dna::embedding::create_extra_providers(&config.extra_models).await;
+        search_service = search_service.with_extra_models(extra_models);
+    }
+    if let Some(model) = &args.model {
+        search_service = search_service.for_model(model)?;
+    }
+    let search_service = std::sync::Arc::new(search_service);
+    if federated && args.explain {
+        return Err(anyhow::anyhow!(
+            "--explain only works on this store's results; add --local"
+        ));
+    }
 
-    let metadata = parse_metadata(&args.filters)?;
+    let query = if args.expand {
+        let expanded = search_service.expand_query(&args.query).await?;
+        if expanded != args.query {
+            eprintln!("Searching for: {}", expanded);
+        }
+        expanded
+    } else {
+        args.query.clone()
+    };
+
+    let mut metadata = parse_metadata(&args.filters)?;
+    if let Some(language) = args.language {
+        metadata.insert(LANGUAGE_LABEL.to_string(), language);
+    }
 
     let filters = SearchFilters {
         kind: args.kind,
+        namespace: args.namespace,
+        owner: None,
+        reviewer: None,
         metadata,
         after: None,
         before: None,
-        limit: Some(args.limit),
+        limit: Some(if args.verified {
+            args.limit * VERIFIED_CANDIDATES
+        } else {
+            args.limit
+        }),
+        include_archived: args.include_archived,
+        fields: parse_metadata(&args.wheres)?,
+        kind_not: args.not_kinds,
+        kinds: None,
+        metadata_not: parse_metadata(&args.not_labels)?,
+        exclude_ids: Vec::new(),
+        exclude_deprecated: !args.include_deprecated,
+    };
+
+    let boosts = args.boosts.into_iter().collect();
+    let weights = args.weights.unwrap_or_default();
+    let (results, stores): (Vec<_>, Vec<_>) = if federated {
+        let federation = FederatedSearch::from_config(
+            search_service.clone(),
+            &config.search.federate,
+            &project_root,
+        )
+        .await?;
+        let merged = federation
+            .search(&query, filters.clone(), weights, &boosts)
+            .await?;
+        for failure in &merged.failures {
+            eprintln!(
+                "Warning: store '{}' was not searched: {}",
+                failure.store, failure.error
+            );
+        }
+        merged
+            .results
+            .into_iter()
+            .map(|hit| (hit.result, Some(hit.store)))
+            .unzip()
+    } else {
+        let results = search_service
+            .search_boosted(&query, filters.clone(), weights, &boosts)
+            .await?;
+        let stores = vec![None; results.len()];
+        (results, stores)
+    };
+    let (results, stores): (Vec<_>, Vec<_>) = if args.verified {
+        let signer = Signer::from_config(&config.signing, &project_root)?;
+        let verifier = Verifier::from_config(&config.signing, signer.as_ref())?;
+        results
+            .into_iter()
+            .zip(stores)
+            .filter(|(result, _)| verifier.verify(&result.artifact).is_verified())
+            .take(args.limit)
+            .unzip()
+    } else {
+        (results, stores)
     };
 
-    let results = search_service.search(&args.query, filters).await?;
+    let snippets = if args.show_snippets {
+        search_service
+            .snippets(&query, &results, SNIPPETS_PER_RESULT)
+            .await?
+    } else {
+        Vec::new()
+    };
+    let explanations = if args.explain {
+        search_service
+            .explain(&query, &results, &filters, weights, &boosts)
+            .await?
+    } else {
+        Vec::new()
+    };
 
     println!("Found {} results:", results.len());
-    for result in results {
+    // Snippets and explanations take several lines each, so they keep the
+    // one-block-per-result layout
+    if !args.show_snippets && !args.explain {
+        let hits: Vec<Hit> = results
+            .into_iter()
+            .zip(stores)
+            .map(|(result, store)| Hit { result, store })
+            .collect();
+        if !hits.is_empty() {
+            println!("{}", table::render(&hits, &columns, style));
+        }
+        return Ok(());
+    }
+    for (i, result) in results.iter().enumerate() {
         println!("\n  ID: {}", result.artifact.id);
         println!("  Kind: {}", result.artifact.kind);
+        if let Some(store) = &stores[i] {
+            println!("  Store: {}", store);
+        }
         println!("  Score: {:.4}", result.score);
-        println!(
-            "  Content: {}...",
-            &result.artifact.content[..result.artifact.content.len().min(100)]
-        );
+        match snippets.get(i) {
+            Some(snippets) => {
+                for snippet in snippets {
+                    println!("  > {}", highlight(&snippet.text, &query, style.color));
+                }
+            },
+            None => println!(
+                "  Content: {}...",
+                &result.artifact.content[..result.artifact.content.len().min(100)]
+            ),
+        }
+        if let Some(explanation) = explanations.get(i) {
+            print_explanation(explanation);
+        }
     }
 
     Ok(())
 }
 
+/// Rows of the `dna list` and `dna search` tables
+trait ArtifactRow {
+    fn artifact(&self) -> &Artifact;
+}
+
+impl ArtifactRow for Artifact {
+    fn artifact(&self) -> &Artifact {
+        self
+    }
+}
+
+/// A search result and the federated store it came from
+struct Hit {
+    result: SearchResult,
+    store: Option<String>,
+}
+
+impl ArtifactRow for Hit {
+    fn artifact(&self) -> &Artifact {
+        &self.result.artifact
+    }
+}
+
+/// Columns `--columns` can pick from an artifact's fields
+fn artifact_columns<T: ArtifactRow>() -> Vec<Column<T>> {
+    vec![
+        Column::new("id", |row: &T| row.artifact().id.clone()).colored(Color::Yellow),
+        Column::new("kind", |row: &T| row.artifact().kind.clone()).colored(Color::Cyan),
+        Column::new("name", |row: &T| {
+            row.artifact().name.clone().unwrap_or_default()
+        })
+        .truncated(),
+        Column::new("format", |row: &T| row.artifact().format.to_string()),
+        Column::new("namespace", |row: &T| {
+            row.artifact().namespace.clone().unwrap_or_default()
+        }),
+        Column::new("owner", |row: &T| {
+            row.artifact().owner.clone().unwrap_or_default()
+        }),
+        Column::new("reviewers", |row: &T| row.artifact().reviewers.join(", ")),
+        Column::new("labels", |row: &T| {
+            let mut labels: Vec<String> = row
+                .artifact()
+                .metadata
+                .iter()
+                .map(|(k, v)| format!("{}={}", k, v))
+                .collect();
+            labels.sort();
+            labels.join(", ")
+        })
+        .truncated(),
+        Column::new("updated", |row: &T| {
+            row.artifact()
+                .updated_at
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        }),
+        Column::new("content", |row: &T| row.artifact().content.clone()).truncated(),
+    ]
+}
+
+/// Columns of the `dna search` table
+fn hit_columns() -> Vec<Column<Hit>> {
+    let mut columns = vec![
+        Column::new("score", |hit: &Hit| format!("{:.4}", hit.result.score)).colored(Color::Green),
+        Column::new("store", |hit: &Hit| hit.store.clone().unwrap_or_default()),
+    ];
+    columns.extend(artifact_columns());
+    columns
+}
+
+/// Print the reasons behind a result's score, as reported by --explain
+fn print_explanation(explanation: &SearchExplanation) {
+    println!("  Matched: {}", explanation.matched);
+    for (name, vector, weight) in [
+        ("Content", explanation.content, explanation.weights.content),
+        ("Context", explanation.context, explanation.weights.context),
+    ] {
+        match vector {
+            Some(m) => println!(
+                "    {}: distance {:.4}, similarity {:.4}, weight {}",
+                name, m.distance, m.similarity, weight
+            ),
+            None => println!("    {}: no embedding", name),
+        }
+    }
+    println!("    Boost: x{}", explanation.boost);
+    if !explanation.filters.is_empty() {
+        let filters: Vec<String> = explanation
+            .filters
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v))
+            .collect();
+        println!("    Filters: {}", filters.join(", "));
+    }
+}
+
+/// Sentences shown per result with --show-snippets
+const SNIPPETS_PER_RESULT: usize = 2;
+
+/// Embolden words of `text` that appear in the query (on a terminal only)
+fn highlight(text: &str, query: &str, color: bool) -> String {
+    let normalize = |word: &str| {
+        word.trim_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase()
+    };
+    let terms: HashSet<String> = query
+        .split_whitespace()
+        .map(normalize)
+        .filter(|t| t.chars().count() > 2)
+        .collect();
+    if !color || terms.is_empty() {
+        return text.to_string();
+    }
+
+    text.split_inclusive(char::is_whitespace)
+        .map(|piece| {
+            let word = piece.trim_end();
+            if terms.contains(&normalize(word)) {
+                format!("\x1b[1;33m{}\x1b[0m{}", word, &piece[word.len()..])
+            } else {
+                piece.to_string()
+            }
+        })
+        .collect()
+}
+
 pub async fn execute_list(args: ListArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -228,12 +638,18 @@ pub async fn execute_list(args: ListArgs) -> Result<()> {
         ));
     }
 
+    let columns = artifact_columns::<Artifact>();
+    let columns = table::select(
+        &columns,
+        &["id", "kind", "name", "updated", "content"],
+        &args.table.columns,
+    )?;
+
     let config = config_service.load()?;
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = std::sync::Arc::new(dna::db::lance::LanceDatabase::new(&storage_uri).await?);
+    let db = std::sync::Arc::new(config_service.open_database(&project_root).await?);
     let embedding = dna::embedding::create_provider(&config.model).await?;
 
-    let service = ArtifactService::new(db, embedding);
+    let service = ArtifactService::from_config(db, embedding, &config, &project_root)?;
 
     let metadata = parse_metadata(&args.filters)?;
     let after = args.after.as_ref().map(|s| parse_date(s)).transpose()?;
@@ -241,42 +657,35 @@ pub async fn execute_list(args: ListArgs) -> Result<()> {
 
     let filters = SearchFilters {
         kind: args.kind,
+        namespace: args.namespace,
+        owner: args.owner.as_deref().map(normalize_handle),
+        reviewer: args.reviewer.as_deref().map(normalize_handle),
         metadata,
         after,
         before,
         limit: args.limit,
+        include_archived: args.include_archived,
+        fields: parse_metadata(&args.wheres)?,
+        kind_not: args.not_kinds,
+        kinds: None,
+        metadata_not: parse_metadata(&args.not_labels)?,
+        exclude_ids: Vec::new(),
+        exclude_deprecated: false,
     };
 
     let artifacts = service.list(filters).await?;
 
     println!("Found {} artifacts:", artifacts.len());
-    for artifact in artifacts {
-        println!(
-            "  {} - {} ({})",
-            artifact.id, artifact.kind, artifact.format
-        );
+    if !artifacts.is_empty() {
+        let style = TableStyle::detect(&args.table);
+        println!("{}", table::render(&artifacts, &columns, style));
     }
 
     Ok(())
 }
 
-fn parse_date(s: &str) -> Result<chrono::DateTime<Utc>> {
-    if let Ok(dt) = chrono::DateTime::parse_from_rfc3339(s) {
-        return Ok(dt.with_timezone(&Utc));
-    }
-    NaiveDate::parse_from_str(s, "%Y-%m-%d")
-        .map_err(|e| {
-            anyhow::anyhow!(
-                "Invalid date '{}': {}. Use YYYY-MM-DD or RFC3339 (e.g. 2024-01-15T10:30:00Z).",
-                s,
-                e
-            )
-        })
-        .map(|date| Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap()))
-}
-
 pub async fn execute_diff(args: DiffArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -286,11 +695,11 @@ pub async fn execute_diff(args: DiffArgs) -> Result<()> {
     }
 
     let config = config_service.load()?;
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = std::sync::Arc::new(dna::db::lance::LanceDatabase::new(&storage_uri).await?);
+    let db = std::sync::Arc::new(config_service.open_database(&project_root).await?);
     let embedding = dna::embedding::create_provider(&config.model).await?;
 
-    let service = ArtifactService::new(db.clone(), embedding.clone());
+    let service =
+        ArtifactService::from_config(db.clone(), embedding.clone(), &config, &project_root)?;
 
     let since = parse_date(&args.since)?;
     let until = args.until.as_ref().map(|s| parse_date(s)).transpose()?;
@@ -301,22 +710,36 @@ pub async fn execute_diff(args: DiffArgs) -> Result<()> {
     // Find artifacts updated in the time range
     let filters = SearchFilters {
         kind: args.kind.clone(),
+        namespace: args.namespace.clone(),
         metadata,
         after: Some(since),
         before: until,
         ..Default::default()
     };
 
-    let mut artifacts = service.list(filters).await?;
+    let mut truncated_at = None;
+    let mut changes = if args.history {
+        let history = service.change_history(filters).await?;
+        truncated_at = history.truncated_at;
+        history.changes
+    } else {
+        service
+            .list(filters)
+            .await?
+            .into_iter()
+            .map(|a| ChangedArtifact::from_timestamps(a, Some(since)))
+            .collect()
+    };
 
     // If --search is provided, filter to only artifacts matching the semantic search
     if let Some(ref query) = args.search {
-        let search_service = SearchService::new(db.clone(), embedding);
+        let search_service = SearchService::from_config(db.clone(), embedding, &config);
         let search_results = search_service
             .search(
                 query,
                 SearchFilters {
                     kind: args.kind.clone(),
+                    namespace: args.namespace.clone(),
                     ..Default::default()
                 },
             )
@@ -324,19 +747,48 @@ pub async fn execute_diff(args: DiffArgs) -> Result<()> {
 
         let matching_ids: HashSet<_> = search_results.iter().map(|r| &r.artifact.id).collect();
 
-        artifacts.retain(|a| matching_ids.contains(&a.id));
+        changes.retain(|c| matching_ids.contains(&c.artifact.id));
     }
 
-    if artifacts.is_empty() {
+    let mut report = ChangeReport::from_changes(changes, Some(since), until);
+    report.truncated_at = truncated_at;
+    if args.summary {
+        report = report.with_summary();
+    }
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if let Some(note) = report
+        .truncation_note()
+        .filter(|_| report.summary.is_none())
+    {
+        eprintln!("Note: {}", note);
+    }
+    if report.changes.is_empty() {
         println!("No changes since {}", args.since);
         return Ok(());
     }
+    if let Some(summary) = &report.summary {
+        println!("{}\n", summary);
+    }
 
     if args.names_only {
         println!("Changed artifacts since {}:", args.since);
-        for artifact in &artifacts {
+        for change in &report.changes {
+            let artifact = &change.artifact;
             let name = artifact.name.as_deref().unwrap_or(&artifact.id);
-            println!("  {}/{} ({})", artifact.kind, name, artifact.id);
+            match change.version {
+                Some(version) => println!(
+                    "  {}/{} ({}, {} in v{})",
+                    artifact.kind, name, artifact.id, change.change, version
+                ),
+                None => println!(
+                    "  {}/{} ({}, {})",
+                    artifact.kind, name, artifact.id, change.change
+                ),
+            }
         }
         return Ok(());
     }
@@ -350,10 +802,20 @@ pub async fn execute_diff(args: DiffArgs) -> Result<()> {
         .map(|v| v.version);
 
     // Output diffs
-    for artifact in &artifacts {
+    for change in &report.changes {
+        let artifact = &change.artifact;
         let name = artifact.name.as_deref().unwrap_or(&artifact.id);
         let header = format!("{}/{}", artifact.kind, name);
 
+        if change.change == ChangeType::Deleted {
+            println!("{} (deleted)", header);
+            for line in artifact.content.lines() {
+                println!("- {}", line);
+            }
+            println!();
+            continue;
+        }
+
         let old_content = if let Some(version) = baseline_version {
             db.get_at_version(&artifact.id, version)
                 .await?
@@ -397,7 +859,7 @@ fn print_diff(old: &str, new: &str) {
 }
 
 pub async fn execute_reindex(args: ReindexArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -407,28 +869,44 @@ pub async fn execute_reindex(args: ReindexArgs) -> Result<()> {
     }
 
     let config = config_service.load()?;
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = std::sync::Arc::new(dna::db::lance::LanceDatabase::new(&storage_uri).await?);
+    let db = std::sync::Arc::new(config_service.open_database(&project_root).await?);
     let embedding = dna::embedding::create_provider(&config.model).await?;
 
-    let service = ArtifactService::new(db.clone(), embedding.clone());
-    let search_service = SearchService::new(db, embedding);
-
-    // Check staleness unless --force is set
-    if !args.force && !args.dry_run {
+    let extra_models = dna::embedding::create_extra_providers(&config.extra_models).await;
+
+    let service =
+        ArtifactService::from_config(db.clone(), embedding.clone(), &config, &project_root)?
+            .with_extra_models(extra_models);
+    let search_service = SearchService::from_config(db, embedding, &config);
+
+    // Check staleness unless --force is set; --missing-only and --model do
+    // their own scan and an explicit --id always reindexes that artifact
+    if !args.force
+        && !args.dry_run
+        && !args.missing_only
+        && args.model.is_none()
+        && args.id.is_none()
+    {
         let inconsistent = search_service.check_embedding_consistency().await?;
-        if inconsistent.is_empty() {
+        // Artifacts of the current model whose kind now composes its text differently
+        let recomposed = service
+            .embedding_health(SearchFilters::default())
+            .await?
+            .count(EmbeddingIssue::CompositionChanged);
+        if inconsistent.is_empty() && recomposed == 0 {
             println!("All artifacts are indexed with the current model.");
             return Ok(());
         }
         println!(
             "Found {} artifacts with stale embeddings.",
-            inconsistent.len()
+            inconsistent.len() + recomposed
         );
     }
 
     // Determine the reindex target
-    let target = if args.content && args.context {
+    let target = if let Some(target) = args.target {
+        target
+    } else if args.content && args.context {
         ReindexTarget::Both
     } else if args.content {
         ReindexTarget::Content
@@ -446,7 +924,10 @@ pub async fn execute_reindex(args: ReindexArgs) -> Result<()> {
     };
 
     // Handle --id flag: reindex a specific artifact
-    if let Some(id) = &args.id {
+    if let Some(prefix) = &args.id {
+        let id = &super::artifact::resolve_id(&service, prefix)
+            .await?
+            .unwrap_or_else(|| prefix.clone());
         if args.dry_run {
             if let Some(artifact) = service.get(id).await? {
                 println!("Would reindex {} for artifact:", target_desc);
@@ -461,15 +942,19 @@ pub async fn execute_reindex(args: ReindexArgs) -> Result<()> {
         }
 
         println!("Reindexing {} for artifact '{}'...", target_desc, id);
+        let started = Instant::now();
         match service.reindex_by_id(id, target).await? {
             Some(artifact) => {
                 println!(
-                    "Reindexed artifact: {} - {} ({})",
-                    artifact.id, artifact.kind, artifact.format
+                    "Reindexed artifact: {} - {} ({}) in {:.2?}",
+                    artifact.id,
+                    artifact.kind,
+                    artifact.format,
+                    started.elapsed()
                 );
             },
             None => {
-                return Err(anyhow::anyhow!("Artifact '{}' not found.", id));
+                return Err(ServiceError::NotFound(format!("Artifact '{}' not found", id)).into());
             },
         }
         return Ok(());
@@ -486,10 +971,20 @@ pub async fn execute_reindex(args: ReindexArgs) -> Result<()> {
         after,
         before: None,
         limit: None,
+        include_archived: false,
+        ..Default::default()
     };
 
+    if args.missing_only {
+        return execute_reindex_missing(&service, filters, args.dry_run).await;
+    }
+
+    if let Some(model) = &args.model {
+        return execute_reindex_model(&service, model, filters, args.dry_run).await;
+    }
+
     // Get matching artifacts
-    let artifacts = service.list(filters).await?;
+    let artifacts = service.list(filters.clone()).await?;
 
     if artifacts.is_empty() {
         println!("No artifacts match the specified filters.");
@@ -526,15 +1021,110 @@ pub async fn execute_reindex(args: ReindexArgs) -> Result<()> {
         filter_desc
     );
 
-    // Reindex each artifact
-    let mut count = 0;
-    for artifact in artifacts {
-        service.reindex_by_id(&artifact.id, target).await?;
-        count += 1;
+    let started = Instant::now();
+    let count = service.reindex_filtered(filters, target).await?;
+
+    println!(
+        "Reindexed {} artifact(s) in {:.2?}.",
+        count,
+        started.elapsed()
+    );
+
+    Ok(())
+}
+
+async fn execute_reindex_missing(
+    service: &ArtifactService,
+    filters: SearchFilters,
+    dry_run: bool,
+) -> Result<()> {
+    let health = if dry_run {
+        service.embedding_health(filters).await?
+    } else {
+        service.reindex_missing(filters).await?
+    };
+
+    if health.is_healthy() {
+        println!("All {} artifact(s) have usable embeddings.", health.scanned);
+        return Ok(());
+    }
+
+    if dry_run {
+        println!(
+            "Would reindex {} of {} artifact(s):",
+            health.issues.len(),
+            health.scanned
+        );
+        for (id, issue) in &health.issues {
+            println!("  {} - {}", id, issue);
+        }
+        return Ok(());
     }
 
-    println!("Reindexed {} artifact(s).", count);
+    println!(
+        "Reindexed {} of {} artifact(s):",
+        health.issues.len(),
+        health.scanned
+    );
+    println!(
+        "  missing:            {}",
+        health.count(EmbeddingIssue::Missing)
+    );
+    println!(
+        "  model mismatch:     {}",
+        health.count(EmbeddingIssue::ModelMismatch)
+    );
+    println!(
+        "  dimension mismatch: {}",
+        health.count(EmbeddingIssue::DimensionMismatch)
+    );
+    println!(
+        "  composition:        {}",
+        health.count(EmbeddingIssue::CompositionChanged)
+    );
+
+    Ok(())
+}
+
+async fn execute_reindex_model(
+    service: &ArtifactService,
+    model: &str,
+    filters: SearchFilters,
+    dry_run: bool,
+) -> Result<()> {
+    if dry_run {
+        if !service.extra_model_ids().contains(&model) {
+            return Err(ServiceError::Validation(format!(
+                "Model '{}' is not listed in [[extra_models]]",
+                model
+            ))
+            .into());
+        }
+        let slug = model_slug(model);
+        let missing: Vec<_> = service
+            .list(filters)
+            .await?
+            .into_iter()
+            .filter(|a| !a.model_embeddings.contains_key(&slug))
+            .collect();
+        println!("Would embed {} artifact(s) with {}:", missing.len(), model);
+        for artifact in &missing {
+            println!(
+                "  {} - {} ({})",
+                artifact.id, artifact.kind, artifact.format
+            );
+        }
+        return Ok(());
+    }
 
+    let started = Instant::now();
+    let count = service.reindex_model(model, filters).await?;
+    println!(
+        "Embedded {} artifact(s) with {} in {:.2?}.",
+        count,
+        model,
+        started.elapsed()
+    );
     Ok(())
 }
 
@@ -563,6 +1153,17 @@ fn build_filter_description(args: &ReindexArgs) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    #[test]
+    fn highlight_marks_query_words_only_in_color() {
+        let text = "Each Token expires, then refresh.";
+        assert_eq!(highlight(text, "token refresh", false), text);
+        assert_eq!(
+            highlight(text, "token refresh", true),
+            "Each \x1b[1;33mToken\x1b[0m expires, then \x1b[1;33mrefresh.\x1b[0m"
+        );
+    }
 
     #[test]
     fn parse_date_accepts_yyyy_mm_dd() {
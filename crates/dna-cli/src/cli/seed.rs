@@ -0,0 +1,70 @@
+use anyhow::Result;
+use clap::Args;
+use dna::services::{list_templates, SeedService, SEED_LABEL};
+use std::sync::Arc;
+
+#[derive(Args)]
+pub struct SeedArgs {
+    /// Template whose sample artifacts to add
+    #[arg(long, default_value = "intent")]
+    pub template: String,
+
+    /// Number of sample artifacts to add
+    #[arg(long, default_value = "25")]
+    pub count: usize,
+
+    /// Remove all sample artifacts instead of adding any
+    #[arg(long, conflicts_with_all = ["template", "count"])]
+    pub clean: bool,
+
+    /// Output the added artifacts as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute(args: SeedArgs) -> Result<()> {
+    let service = Arc::new(super::artifact::create_service().await?);
+    let seeds = SeedService::new(service);
+
+    if args.clean {
+        let removed = seeds.clean().await?;
+        if args.json {
+            println!("{}", serde_json::json!({ "removed": removed }));
+        } else {
+            println!("Removed {} sample artifact(s)", removed);
+        }
+        return Ok(());
+    }
+
+    if !list_templates().contains(&args.template.as_str()) {
+        return Err(anyhow::anyhow!(
+            "Unknown template '{}'. Available templates: {}",
+            args.template,
+            list_templates().join(", ")
+        ));
+    }
+
+    let artifacts = seeds.seed(&args.template, args.count).await?;
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&artifacts)?);
+        return Ok(());
+    }
+
+    println!(
+        "Added {} sample artifact(s) from the {} template, labeled {}={}",
+        artifacts.len(),
+        args.template,
+        SEED_LABEL,
+        args.template
+    );
+    for artifact in &artifacts {
+        println!(
+            "  {}  {:<12} {}",
+            artifact.id,
+            artifact.kind,
+            artifact.name.as_deref().unwrap_or_default()
+        );
+    }
+    println!("\nRemove them with 'dna seed --clean'.");
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use anyhow::Result;
+use clap::Args;
+use dna::editor::EditorServer;
+use dna::services::{ArtifactService, ConfigService, SearchService};
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Speak JSON-RPC over stdio for editor extensions
+    #[arg(long)]
+    editor: bool,
+
+    /// Milliseconds a search waits for the next keystroke before running
+    #[arg(long, default_value = "150")]
+    debounce_ms: u64,
+
+    /// Milliseconds between checks for changes made outside the editor
+    #[arg(long, default_value = "2000")]
+    poll_ms: u64,
+}
+
+pub async fn execute(args: ServeArgs) -> Result<()> {
+    if !args.editor {
+        anyhow::bail!(
+            "Choose a server mode: 'dna serve --editor' for editors, 'dna mcp' for AI agents, or the dna-server binary for HTTP"
+        );
+    }
+
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+
+    if !config_service.exists() {
+        return Err(anyhow::anyhow!(
+            "DNA not initialized. Run 'dna init' first."
+        ));
+    }
+
+    let config = config_service.load()?;
+    let db = Arc::new(config_service.open_database(&project_root).await?);
+    let embedding = dna::embedding::create_provider(&config.model).await?;
+    let extra_models = dna::embedding::create_extra_providers(&config.extra_models).await;
+
+    let artifact_service =
+        ArtifactService::from_config(db.clone(), embedding.clone(), &config, &project_root)?
+            .with_extra_models(extra_models.clone());
+    let search_service =
+        SearchService::from_config(db, embedding, &config).with_extra_models(extra_models);
+
+    // stdout carries the protocol, so progress goes to stderr
+    eprintln!("Starting DNA editor server...");
+
+    EditorServer::new(Arc::new(artifact_service), Arc::new(search_service))
+        .with_debounce(Duration::from_millis(args.debounce_ms))
+        .with_poll_interval(Duration::from_millis(args.poll_ms))
+        .serve(
+            tokio::io::BufReader::new(tokio::io::stdin()),
+            tokio::io::stdout(),
+        )
+        .await
+}
@@ -0,0 +1,154 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use dna::services::{
+    slugify_kind, ConfigService, SearchFilters, ServiceError, Signer, Verification, Verifier,
+};
+use std::io::Write;
+
+/// Key file written by `dna sign --new-key` unless `[signing] key_path` names another
+const DEFAULT_KEY_PATH: &str = ".dna/signing.key";
+
+#[derive(Args)]
+pub struct SignArgs {
+    /// Artifact IDs to sign
+    #[arg(required_unless_present = "new_key")]
+    pub ids: Vec<String>,
+
+    /// Generate a signing key, write it to [signing] key_path (default:
+    /// .dna/signing.key) and print its public key
+    #[arg(long, conflicts_with = "ids")]
+    pub new_key: bool,
+}
+
+#[derive(Args)]
+pub struct VerifyArgs {
+    /// Artifact IDs to verify [default: every artifact]
+    pub ids: Vec<String>,
+
+    /// Without IDs, only verify artifacts of this kind
+    #[arg(long, conflicts_with = "ids")]
+    pub kind: Option<String>,
+
+    /// Output the results as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+pub async fn execute_sign(args: SignArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config = ConfigService::new(&project_root).load()?;
+
+    if args.new_key {
+        let relative = config
+            .signing
+            .key_path
+            .clone()
+            .unwrap_or_else(|| DEFAULT_KEY_PATH.to_string());
+        let path = project_root.join(&relative);
+        if path.exists() {
+            return Err(ServiceError::Conflict(format!(
+                "{} already exists; remove it first to replace the key",
+                path.display()
+            ))
+            .into());
+        }
+        let key = Signer::generate_key();
+        write_private(&path, &key)?;
+        let public_key = Signer::new("new", &key)?.public_key();
+        println!("Wrote signing key to {}", path.display());
+        println!("Public key: {}", public_key);
+        println!("\nAdd to .dna/config.toml:\n");
+        println!("[signing]");
+        println!("key_path = \"{}\"", relative);
+        println!("signer = \"you@example.com\"");
+        println!("\nOther writers trust your signatures with:\n");
+        println!("[signing.trusted]");
+        println!("\"you@example.com\" = \"{}\"", public_key);
+        return Ok(());
+    }
+
+    let service = super::artifact::create_service().await?;
+    for id in &args.ids {
+        let artifact = service.sign(id).await?;
+        println!("Signed {} ({})", artifact.id, artifact.kind);
+    }
+    Ok(())
+}
+
+/// Write a private key readable only by its owner
+fn write_private(path: &std::path::Path, key: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    writeln!(file, "{}", key)?;
+    Ok(())
+}
+
+pub async fn execute_verify(args: VerifyArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config = ConfigService::new(&project_root).load()?;
+    let signer = Signer::from_config(&config.signing, &project_root)?;
+    let verifier = Verifier::from_config(&config.signing, signer.as_ref())?;
+    let service = super::artifact::create_service().await?;
+
+    let named = !args.ids.is_empty();
+    let artifacts =
+        if named {
+            let mut artifacts = Vec::new();
+            for id in &args.ids {
+                let id = service.resolve_id(id).await?;
+                artifacts.push(service.get(&id).await?.ok_or_else(|| {
+                    ServiceError::NotFound(format!("Artifact '{}' not found", id))
+                })?);
+            }
+            artifacts
+        } else {
+            service
+                .list(SearchFilters {
+                    kind: args.kind.as_deref().map(slugify_kind),
+                    ..Default::default()
+                })
+                .await?
+        };
+
+    let results: Vec<(String, Verification)> = artifacts
+        .iter()
+        .map(|artifact| (artifact.id.clone(), verifier.verify(artifact)))
+        .collect();
+    if args.json {
+        let json: Vec<_> = results
+            .iter()
+            .map(|(id, verification)| serde_json::json!({ "id": id, "verification": verification }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        for (id, verification) in &results {
+            println!("  {}: {}", id, verification);
+        }
+    }
+
+    // Unsigned artifacts only fail when asked about by name
+    let failed = results
+        .iter()
+        .filter(|(_, verification)| match verification {
+            Verification::Verified { .. } => false,
+            Verification::Invalid { .. } => true,
+            _ => named,
+        })
+        .count();
+    if failed > 0 {
+        return Err(ServiceError::Validation(format!(
+            "{} artifact(s) failed verification",
+            failed
+        ))
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use clap::{ArgGroup, Args, Subcommand};
+use dna::services::StoreRegistry;
+use std::path::PathBuf;
+
+#[derive(Args)]
+pub struct StoreArgs {
+    #[command(subcommand)]
+    pub command: StoreCommands,
+}
+
+#[derive(Subcommand)]
+pub enum StoreCommands {
+    /// Register a project's store under a name
+    Add(StoreAddArgs),
+
+    /// List named stores, marking the current one
+    List,
+
+    /// Use a named store when not inside a project
+    Use(StoreUseArgs),
+
+    /// Forget a named store (its data is left alone)
+    Remove(StoreRemoveArgs),
+}
+
+#[derive(Args)]
+pub struct StoreAddArgs {
+    /// Name to pass to --store
+    pub name: String,
+
+    /// Project directory, or its .dna directory [default: the nearest store]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Args)]
+#[command(group = ArgGroup::new("target").required(true).args(&["name", "clear"]))]
+pub struct StoreUseArgs {
+    /// Name of a registered store
+    pub name: Option<String>,
+
+    /// Stop using a named store outside projects
+    #[arg(long)]
+    pub clear: bool,
+}
+
+#[derive(Args)]
+pub struct StoreRemoveArgs {
+    /// Name of a registered store
+    pub name: String,
+}
+
+pub async fn execute(args: StoreArgs) -> Result<()> {
+    let registry = StoreRegistry::from_env()?;
+    match args.command {
+        StoreCommands::Add(add_args) => {
+            let path = match add_args.path {
+                Some(path) => path,
+                None => dna::services::stores::discover(&std::env::current_dir()?)
+                    .unwrap_or_else(|| PathBuf::from(".")),
+            };
+            let root = registry.add(&add_args.name, &path)?;
+            println!("Added store '{}' at {}", add_args.name, root.display());
+        },
+        StoreCommands::List => {
+            let config = registry.load()?;
+            if config.stores.is_empty() {
+                println!("No named stores. Add one with 'dna store add <name> <path>'.");
+                return Ok(());
+            }
+            for (name, root) in &config.stores {
+                let marker = if config.current.as_ref() == Some(name) {
+                    "*"
+                } else {
+                    " "
+                };
+                println!("{} {}\t{}", marker, name, root.display());
+            }
+        },
+        StoreCommands::Use(use_args) => {
+            registry.use_store(use_args.name.as_deref())?;
+            match use_args.name {
+                Some(name) => println!("Using store '{}' outside projects", name),
+                None => println!("No longer using a named store"),
+            }
+        },
+        StoreCommands::Remove(remove_args) => {
+            if registry.remove(&remove_args.name)? {
+                println!("Removed store '{}'", remove_args.name);
+            } else {
+                return Err(anyhow::anyhow!("Unknown store '{}'", remove_args.name));
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Print the resolved project directory, failing when no store was found
+pub fn execute_root(root: Option<&PathBuf>) -> Result<()> {
+    let root = root.ok_or_else(|| {
+        anyhow::anyhow!(
+            "No DNA store found in the current directory or any parent. Run 'dna init' first."
+        )
+    })?;
+    let root = root.canonicalize().unwrap_or_else(|_| root.clone());
+    println!("{}", root.display());
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use clap::Args;
+use dna::services::sync::read_documents;
+use dna::services::{ConfigService, Reconciliation, SyncAction, SyncService};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Args)]
+pub struct SyncArgs {
+    /// Directory of frontmatter files describing the desired artifacts
+    #[arg(long = "from", value_name = "DIR")]
+    pub from: PathBuf,
+
+    /// Remove artifacts that have no file in the directory
+    #[arg(long)]
+    pub prune: bool,
+
+    /// Show the reconciliation report without changing anything
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Output the report as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// With --prune, skip the confirmation prompt before deleting
+    #[arg(long, short)]
+    pub yes: bool,
+}
+
+pub async fn execute(args: SyncArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+    let service = super::artifact::create_service().await?;
+    let config = config_service.load()?;
+
+    let documents = read_documents(&args.from)?;
+    let sync = SyncService::new(Arc::new(service)).with_labels(config.labels);
+    let mut reconciliation = sync.reconcile(&documents, args.prune).await?;
+
+    if !args.dry_run {
+        let deletes = reconciliation.count(SyncAction::Delete);
+        if deletes > 0 && !args.yes {
+            for change in &reconciliation.changes {
+                if change.action == SyncAction::Delete {
+                    eprintln!("  - {}", change.id.as_deref().unwrap_or_default());
+                }
+            }
+            let prompt = format!(
+                "Delete {} artifact(s) with no file in {}?",
+                deletes,
+                args.from.display()
+            );
+            if !super::confirm(&prompt, args.yes)? {
+                return Ok(());
+            }
+        }
+        sync.apply(&mut reconciliation).await?;
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reconciliation)?);
+        return Ok(());
+    }
+
+    print_report(&reconciliation);
+    if args.dry_run {
+        let embeds = reconciliation.changes.iter().filter(|c| c.reembed).count();
+        println!();
+        println!(
+            "Dry run: no changes made. Applying would embed {} artifact(s).",
+            embeds
+        );
+    }
+    Ok(())
+}
+
+fn print_report(reconciliation: &Reconciliation) {
+    for change in &reconciliation.changes {
+        let id = change.id.as_deref().unwrap_or("(new)");
+        let path = change
+            .path
+            .as_ref()
+            .map(|p| format!(" {}", p.display()))
+            .unwrap_or_default();
+        match change.action {
+            SyncAction::Create => println!("  + {}{}", id, path),
+            SyncAction::Update => println!(
+                "  ~ {}{} [{}]{}",
+                id,
+                path,
+                change.fields.join(", "),
+                if change.reembed { " (re-embed)" } else { "" }
+            ),
+            SyncAction::Delete => println!("  - {}", id),
+        }
+    }
+
+    println!(
+        "Created: {}, updated: {}, deleted: {}, unchanged: {}",
+        reconciliation.count(SyncAction::Create),
+        reconciliation.count(SyncAction::Update),
+        reconciliation.count(SyncAction::Delete),
+        reconciliation.unchanged
+    );
+}
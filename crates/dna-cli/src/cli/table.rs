@@ -0,0 +1,239 @@
+//! Tables for commands that print one row per artifact or version.
+//!
+//! On a terminal rows are drawn with borders, fitted to its width and
+//! colored; piped elsewhere they become plain, space-aligned columns, one
+//! line per row, so they stay easy to `grep` and `cut`.
+
+use anyhow::Result;
+use clap::Args;
+use comfy_table::{presets, Attribute, Cell, Color, ContentArrangement, Table};
+use std::io::IsTerminal;
+
+/// Characters of a long cell kept on a terminal
+const MAX_CELL_CHARS: usize = 60;
+
+#[derive(Args, Clone, Default)]
+pub struct TableArgs {
+    /// Columns to show, in order (comma-separated, e.g. id,kind,content)
+    #[arg(long, value_delimiter = ',')]
+    pub columns: Vec<String>,
+
+    /// Don't color the output (also off when NO_COLOR is set)
+    #[arg(long)]
+    pub no_color: bool,
+}
+
+/// How a table is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableStyle {
+    /// Borders and truncated cells, for a terminal
+    pub tty: bool,
+    pub color: bool,
+}
+
+impl TableStyle {
+    /// Style for stdout: a bordered table on a terminal, plain columns otherwise
+    pub fn detect(args: &TableArgs) -> Self {
+        let tty = std::io::stdout().is_terminal();
+        let color = tty && !args.no_color && std::env::var_os("NO_COLOR").is_none();
+        Self { tty, color }
+    }
+}
+
+/// A column a table can show, and how a row fills it
+pub struct Column<T> {
+    pub name: &'static str,
+    /// Shortened to [`MAX_CELL_CHARS`] on a terminal
+    pub truncate: bool,
+    pub color: Option<Color>,
+    pub cell: fn(&T) -> String,
+}
+
+impl<T> Column<T> {
+    pub const fn new(name: &'static str, cell: fn(&T) -> String) -> Self {
+        Self {
+            name,
+            truncate: false,
+            color: None,
+            cell,
+        }
+    }
+
+    pub const fn truncated(mut self) -> Self {
+        self.truncate = true;
+        self
+    }
+
+    pub const fn colored(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+}
+
+/// The columns named by `--columns`, or those named in `default`
+pub fn select<'a, T>(
+    columns: &'a [Column<T>],
+    default: &[&str],
+    requested: &[String],
+) -> Result<Vec<&'a Column<T>>> {
+    let names: Vec<&str> = if requested.is_empty() {
+        default.to_vec()
+    } else {
+        requested.iter().map(|name| name.trim()).collect()
+    };
+    names
+        .into_iter()
+        .map(|name| {
+            columns.iter().find(|c| c.name == name).ok_or_else(|| {
+                let available: Vec<&str> = columns.iter().map(|c| c.name).collect();
+                anyhow::anyhow!(
+                    "Unknown column '{}' (available: {})",
+                    name,
+                    available.join(", ")
+                )
+            })
+        })
+        .collect()
+}
+
+/// Draw `rows` under the headers of `columns`
+pub fn render<T>(rows: &[T], columns: &[&Column<T>], style: TableStyle) -> String {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|column| {
+                    let text = one_line(&(column.cell)(row));
+                    if style.tty && column.truncate {
+                        truncate(&text, MAX_CELL_CHARS)
+                    } else {
+                        text
+                    }
+                })
+                .collect()
+        })
+        .collect();
+    let headers: Vec<String> = columns.iter().map(|c| c.name.to_uppercase()).collect();
+
+    if !style.tty {
+        return plain(&headers, &cells);
+    }
+
+    let mut table = Table::new();
+    table
+        .load_preset(presets::UTF8_FULL_CONDENSED)
+        .set_content_arrangement(ContentArrangement::Dynamic);
+    table.set_header(headers.into_iter().map(|header| {
+        let header = Cell::new(header);
+        if style.color {
+            header.add_attribute(Attribute::Bold)
+        } else {
+            header
+        }
+    }));
+    for row in cells {
+        table.add_row(
+            row.into_iter()
+                .zip(columns)
+                .map(|(text, column)| match column.color {
+                    Some(color) if style.color => Cell::new(text).fg(color),
+                    _ => Cell::new(text),
+                }),
+        );
+    }
+    table.to_string()
+}
+
+/// Columns padded to their widest cell and separated by two spaces
+fn plain(headers: &[String], rows: &[Vec<String>]) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    std::iter::once(headers)
+        .chain(rows.iter().map(Vec::as_slice))
+        .map(|row| {
+            let line: String = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| {
+                    let padding = width - cell.chars().count();
+                    format!("{}{}  ", cell, " ".repeat(padding))
+                })
+                .collect();
+            line.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Collapse line breaks and runs of whitespace into single spaces
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// `text` cut to `max` characters, ending in an ellipsis when shortened
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut short: String = text.chars().take(max.saturating_sub(1)).collect();
+    short.push('…');
+    short
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn columns() -> Vec<Column<(&'static str, &'static str)>> {
+        vec![
+            Column::new("id", |row: &(&str, &str)| row.0.to_string()).colored(Color::Yellow),
+            Column::new("content", |row: &(&str, &str)| row.1.to_string()).truncated(),
+        ]
+    }
+
+    #[test]
+    fn selects_requested_columns_in_order() {
+        let columns = columns();
+        let selected = select(&columns, &["id"], &[]).unwrap();
+        assert_eq!(selected.len(), 1);
+
+        let requested = vec!["content".to_string(), "id".to_string()];
+        let selected = select(&columns, &["id"], &requested).unwrap();
+        let names: Vec<&str> = selected.iter().map(|c| c.name).collect();
+        assert_eq!(names, ["content", "id"]);
+
+        let err = select(&columns, &["id"], &["owner".to_string()])
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("available: id, content"), "{err}");
+    }
+
+    #[test]
+    fn plain_output_is_one_aligned_line_per_row() {
+        let columns = columns();
+        let selected = select(&columns, &["id", "content"], &[]).unwrap();
+        let rows = [("abc", "first line\nsecond line"), ("defghi", "short")];
+
+        let style = TableStyle {
+            tty: false,
+            color: false,
+        };
+        let text = render(&rows, &selected, style);
+        assert_eq!(
+            text,
+            "ID      CONTENT\nabc     first line second line\ndefghi  short"
+        );
+    }
+
+    #[test]
+    fn truncate_keeps_short_text_and_marks_cuts() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("a longer sentence", 8), "a longe…");
+    }
+}
@@ -0,0 +1,64 @@
+//! Options for filling `{{name}}` placeholders when content is rendered or
+//! packed, on top of `[variables]` in the config.
+
+use anyhow::Result;
+use clap::Args;
+use dna::services::{ProjectConfig, Variables};
+use std::path::{Path, PathBuf};
+
+#[derive(Args, Clone, Default)]
+pub struct VariableArgs {
+    /// Read variables from this TOML, YAML or JSON file, after those in
+    /// [variables] (can be repeated)
+    #[arg(long = "vars", value_name = "FILE")]
+    pub files: Vec<PathBuf>,
+
+    /// Set a variable, overriding files and config (name=value, can be repeated)
+    #[arg(long = "var", value_name = "NAME=VALUE")]
+    pub values: Vec<String>,
+
+    /// Fail when a placeholder has no value
+    #[arg(long, conflicts_with = "lenient_vars")]
+    pub strict_vars: bool,
+
+    /// Leave placeholders without a value as written, even if [variables]
+    /// is strict
+    #[arg(long)]
+    pub lenient_vars: bool,
+
+    /// Leave every placeholder as written
+    #[arg(long, conflicts_with_all = ["files", "values", "strict_vars", "lenient_vars"])]
+    pub no_vars: bool,
+}
+
+impl VariableArgs {
+    /// The variables to fill content with, or None to leave it as stored:
+    /// with `--no-vars`, or when neither the config nor the options set any
+    pub fn resolve(
+        &self,
+        config: &ProjectConfig,
+        project_root: &Path,
+    ) -> Result<Option<Variables>> {
+        let requested = !self.files.is_empty()
+            || !self.values.is_empty()
+            || self.strict_vars
+            || self.lenient_vars;
+        if self.no_vars || (!requested && config.variables.is_default()) {
+            return Ok(None);
+        }
+
+        let mut variables = Variables::from_config(&config.variables, project_root)?;
+        for file in &self.files {
+            variables.load_file(file)?;
+        }
+        for (name, value) in super::parse_metadata(&self.values)? {
+            variables.set(name, value);
+        }
+        if self.strict_vars {
+            variables = variables.with_strict(true);
+        } else if self.lenient_vars {
+            variables = variables.with_strict(false);
+        }
+        Ok(Some(variables))
+    }
+}
@@ -1,8 +1,9 @@
+use super::table::{self, Column, TableArgs, TableStyle};
 use anyhow::Result;
 use clap::Args;
-use dna::db::Database;
+use comfy_table::Color;
+use dna::db::{Database, VersionInfo};
 use dna::services::ConfigService;
-use std::path::PathBuf;
 
 #[derive(Args)]
 pub struct PruneArgs {
@@ -17,6 +18,10 @@ pub struct PruneArgs {
     /// Show what would be pruned without doing it
     #[arg(long)]
     pub dry_run: bool,
+
+    /// Skip the confirmation prompt
+    #[arg(long, short)]
+    pub yes: bool,
 }
 
 #[derive(Args)]
@@ -24,9 +29,12 @@ pub struct VersionsArgs {
     /// Show last N versions [default: 20]
     #[arg(long, default_value = "20")]
     pub limit: usize,
+
+    #[command(flatten)]
+    pub table: TableArgs,
 }
 
-fn format_bytes(bytes: u64) -> String {
+pub(super) fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = 1024 * KB;
     const GB: u64 = 1024 * MB;
@@ -43,7 +51,7 @@ fn format_bytes(bytes: u64) -> String {
 }
 
 pub async fn execute_prune(args: PruneArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -52,8 +60,7 @@ pub async fn execute_prune(args: PruneArgs) -> Result<()> {
         ));
     }
 
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = dna::db::lance::LanceDatabase::new(&storage_uri).await?;
+    let db = config_service.open_database(&project_root).await?;
 
     if args.older_than.is_some() {
         return Err(anyhow::anyhow!("--older-than is not yet implemented"));
@@ -61,6 +68,18 @@ pub async fn execute_prune(args: PruneArgs) -> Result<()> {
 
     if args.dry_run {
         println!("Dry run - no changes will be made\n");
+    } else {
+        let versions = db.list_versions(None).await?;
+        let prompt = format!(
+            "Remove {} of {} database versions, keeping the latest {}? \
+             Removed versions can no longer be restored.",
+            versions.len().saturating_sub(args.keep_versions),
+            versions.len(),
+            args.keep_versions
+        );
+        if !super::confirm(&prompt, args.yes)? {
+            return Ok(());
+        }
     }
 
     // Compact database
@@ -98,7 +117,7 @@ pub async fn execute_prune(args: PruneArgs) -> Result<()> {
 }
 
 pub async fn execute_versions(args: VersionsArgs) -> Result<()> {
-    let project_root = PathBuf::from(".");
+    let project_root = super::project_root();
     let config_service = ConfigService::new(&project_root);
 
     if !config_service.exists() {
@@ -107,8 +126,15 @@ pub async fn execute_versions(args: VersionsArgs) -> Result<()> {
         ));
     }
 
-    let storage_uri = config_service.resolve_storage_uri(&project_root)?;
-    let db = dna::db::lance::LanceDatabase::new(&storage_uri).await?;
+    let columns = [
+        Column::new("version", |v: &VersionInfo| v.version.to_string()).colored(Color::Yellow),
+        Column::new("timestamp", |v: &VersionInfo| {
+            v.timestamp.format("%Y-%m-%d %H:%M:%S").to_string()
+        }),
+    ];
+    let columns = table::select(&columns, &["version", "timestamp"], &args.table.columns)?;
+
+    let db = config_service.open_database(&project_root).await?;
 
     let versions = db.list_versions(Some(args.limit)).await?;
 
@@ -118,14 +144,8 @@ pub async fn execute_versions(args: VersionsArgs) -> Result<()> {
     }
 
     println!("Database versions:");
-    println!("  {:>8}  Timestamp", "Version");
-    for version_info in versions {
-        println!(
-            "  {:>8}  {}",
-            version_info.version,
-            version_info.timestamp.format("%Y-%m-%d %H:%M:%S")
-        );
-    }
+    let style = TableStyle::detect(&args.table);
+    println!("{}", table::render(&versions, &columns, style));
 
     Ok(())
 }
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use chrono::Utc;
+use clap::Args;
+use dna::services::{ChangeType, ChangeWatcher, ConfigService, SearchFilters, WatchedChange};
+use serde_json::{json, Value};
+use similar::{ChangeTag, TextDiff};
+use std::io::Write;
+use std::time::Duration;
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Only watch these kinds (comma-separated or repeated)
+    #[arg(long, value_delimiter = ',')]
+    kind: Vec<String>,
+
+    /// Only watch this namespace
+    #[arg(long = "ns", value_name = "NAMESPACE")]
+    namespace: Option<String>,
+
+    /// Milliseconds between checks for new changes
+    #[arg(long, default_value = "1000")]
+    poll_ms: u64,
+
+    /// Print each change as a line of JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub async fn execute(args: WatchArgs) -> Result<()> {
+    let project_root = super::project_root();
+    let config_service = ConfigService::new(&project_root);
+
+    if !config_service.exists() {
+        return Err(anyhow::anyhow!(
+            "DNA not initialized. Run 'dna init' first."
+        ));
+    }
+
+    let db = config_service.open_database(&project_root).await?;
+    let filters = SearchFilters {
+        kinds: (!args.kind.is_empty()).then(|| args.kind.clone()),
+        namespace: args.namespace.clone(),
+        ..Default::default()
+    };
+    let mut watcher = ChangeWatcher::start(&db, filters).await?;
+    // stdout carries the changes, so status goes to stderr
+    eprintln!(
+        "Watching for changes from version {} (Ctrl-C to stop)...",
+        watcher.version()
+    );
+
+    let mut interval = tokio::time::interval(Duration::from_millis(args.poll_ms.max(1)));
+    let mut stdout = std::io::stdout();
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {},
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+        }
+        let changes = match watcher.poll(&db).await {
+            Ok(changes) => changes,
+            Err(e) => {
+                eprintln!("Warning: failed to read changes: {:#}", e);
+                continue;
+            },
+        };
+        for change in &changes {
+            let line = if args.json {
+                watch_line(change).to_string()
+            } else {
+                describe(change)
+            };
+            // Stop quietly once whatever reads the stream goes away
+            if writeln!(stdout, "{}", line).is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// One line of `dna watch --json`
+fn watch_line(change: &WatchedChange) -> Value {
+    let artifact = change.artifact();
+    let (lines_added, lines_removed) = line_counts(change);
+    json!({
+        "change": change.change,
+        "version": change.version,
+        "seen_at": Utc::now(),
+        "id": artifact.id,
+        "kind": artifact.kind,
+        "name": artifact.name,
+        "namespace": artifact.namespace,
+        "fields": change.changed_fields(),
+        "lines_added": lines_added,
+        "lines_removed": lines_removed,
+    })
+}
+
+/// "v12    updated  intent/Checkout (k7v3m9xnp2)  content +3 -1, labels"
+fn describe(change: &WatchedChange) -> String {
+    let artifact = change.artifact();
+    let name = artifact.name.as_deref().unwrap_or(&artifact.id);
+    let (added, removed) = line_counts(change);
+    let summary = match change.change {
+        ChangeType::Created => format!("+{} lines", added),
+        ChangeType::Deleted => format!("-{} lines", removed),
+        ChangeType::Updated => {
+            let fields: Vec<String> = change
+                .changed_fields()
+                .into_iter()
+                .map(|field| match field {
+                    "content" => format!("content +{} -{}", added, removed),
+                    "kind" => format!(
+                        "kind {} -> {}",
+                        change.before.as_ref().map_or("", |a| &a.kind),
+                        artifact.kind
+                    ),
+                    field => field.to_string(),
+                })
+                .collect();
+            if fields.is_empty() {
+                "touched".to_string()
+            } else {
+                fields.join(", ")
+            }
+        },
+    };
+    format!(
+        "v{:<5} {:<8} {}/{} ({})  {}",
+        change.version,
+        change.change.to_string(),
+        artifact.kind,
+        name,
+        artifact.id,
+        summary
+    )
+}
+
+/// Lines of content added and removed by the change
+fn line_counts(change: &WatchedChange) -> (usize, usize) {
+    let old = change.before.as_ref().map_or("", |a| a.content.as_str());
+    let new = change.after.as_ref().map_or("", |a| a.content.as_str());
+    let diff = TextDiff::from_lines(old, new);
+    diff.iter_all_changes()
+        .fold((0, 0), |(added, removed), line| match line.tag() {
+            ChangeTag::Insert => (added + 1, removed),
+            ChangeTag::Delete => (added, removed + 1),
+            ChangeTag::Equal => (added, removed),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dna::services::{Artifact, ContentFormat};
+    use std::collections::HashMap;
+
+    fn artifact(content: &str) -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            Some("Checkout".to_string()),
+            HashMap::new(),
+            "model".to_string(),
+        );
+        artifact.id = "k7v3m9xnp2".to_string();
+        artifact
+    }
+
+    #[test]
+    fn describes_changes_with_line_counts() {
+        let before = artifact("Pay first\nThen ship\n");
+        let mut after = before.clone();
+        after.content = "Pay first\nThen pack\nThen ship\n".to_string();
+        after
+            .metadata
+            .insert("team".to_string(), "core".to_string());
+
+        let updated = WatchedChange {
+            change: ChangeType::Updated,
+            version: 7,
+            before: Some(before.clone()),
+            after: Some(after),
+        };
+        assert_eq!(
+            describe(&updated),
+            "v7     updated  intent/Checkout (k7v3m9xnp2)  content +1 -0, labels"
+        );
+
+        let deleted = WatchedChange {
+            change: ChangeType::Deleted,
+            version: 8,
+            before: Some(before),
+            after: None,
+        };
+        let json = watch_line(&deleted);
+        assert_eq!(json["change"], "deleted");
+        assert_eq!(json["lines_removed"], 2);
+        assert_eq!(json["fields"], json!([]));
+    }
+}
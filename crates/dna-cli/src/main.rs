@@ -1,11 +1,12 @@
 mod cli;
 
-use anyhow::Result;
 use clap::Parser;
+use dna::services::ServiceError;
+use std::process::ExitCode;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 #[tokio::main]
-async fn main() -> Result<()> {
+async fn main() -> ExitCode {
     // Parse CLI first to get verbose flag
     let cli = cli::Cli::parse();
 
@@ -17,6 +18,17 @@ async fn main() -> Result<()> {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    // Execute command
-    cli::execute(cli).await
+    // Downloads are skipped wherever the HuggingFace cache is consulted
+    if cli.offline {
+        std::env::set_var("HF_HUB_OFFLINE", "1");
+    }
+
+    // Execute command; failures exit with a code per error kind (see docs/cli.md)
+    match cli::execute(cli).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            ExitCode::from(ServiceError::classify(e).exit_code())
+        },
+    }
 }
@@ -0,0 +1,13 @@
+import assert from 'node:assert/strict'
+import { mkdtempSync } from 'node:fs'
+import { createRequire } from 'node:module'
+import { tmpdir } from 'node:os'
+import { join } from 'node:path'
+import test from 'node:test'
+
+const { DnaStore } = createRequire(import.meta.url)('../index.js')
+
+test('open rejects a directory without a DNA project', async () => {
+  const dir = mkdtempSync(join(tmpdir(), 'dna-node-'))
+  await assert.rejects(DnaStore.open(dir), /not initialized/)
+})
@@ -0,0 +1,235 @@
+//! Node.js bindings for DNA.
+//!
+//! Exposes [`dna::DnaStore`] to JavaScript so docs generators and editor
+//! extensions can read and write a project's truth store in-process. Build
+//! with `npm run build` from this directory:
+//!
+//! ```js
+//! const { DnaStore } = require('@dna/node')
+//!
+//! const store = await DnaStore.open('.')
+//! await store.add('intent', 'Users sign in with SSO', { labels: { team: 'auth' } })
+//! for (const hit of await store.search('how do users log in?', { limit: 5 })) {
+//!   console.log(hit.score, hit.artifact.id)
+//! }
+//! ```
+//!
+//! Every method returns a promise and runs off the JavaScript thread.
+
+use dna::services::{ContentFormat, SearchFilters, ServiceError};
+use dna::NewArtifact;
+use napi::{Error, Status};
+use napi_derive::napi;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Turn a service failure into a JS error; rejected input becomes `InvalidArg`
+fn to_napi_err(err: anyhow::Error) -> Error {
+    match ServiceError::classify(err) {
+        err @ ServiceError::Validation(_) => Error::new(Status::InvalidArg, err.to_string()),
+        ServiceError::Internal(err) => Error::new(Status::GenericFailure, format!("{:#}", err)),
+        err => Error::new(Status::GenericFailure, err.to_string()),
+    }
+}
+
+/// A stored artifact
+#[napi(object)]
+pub struct Artifact {
+    pub id: String,
+    pub kind: String,
+    pub name: Option<String>,
+    pub content: String,
+    pub format: String,
+    pub labels: HashMap<String, String>,
+    pub context: Option<String>,
+    /// RFC 3339 timestamp
+    pub created_at: String,
+    /// RFC 3339 timestamp
+    pub updated_at: String,
+}
+
+impl From<dna::services::Artifact> for Artifact {
+    fn from(artifact: dna::services::Artifact) -> Self {
+        Self {
+            id: artifact.id,
+            kind: artifact.kind,
+            name: artifact.name,
+            content: artifact.content,
+            format: artifact.format.to_string(),
+            labels: artifact.metadata,
+            context: artifact.context,
+            created_at: artifact.created_at.to_rfc3339(),
+            updated_at: artifact.updated_at.to_rfc3339(),
+        }
+    }
+}
+
+/// An artifact and its similarity to the search query
+#[napi(object)]
+pub struct SearchResult {
+    pub artifact: Artifact,
+    pub score: f64,
+}
+
+/// Optional fields for `DnaStore.add`
+#[napi(object)]
+pub struct AddOptions {
+    pub name: Option<String>,
+    pub labels: Option<HashMap<String, String>>,
+    pub context: Option<String>,
+    /// markdown (default), yaml, json, openapi or text
+    pub format: Option<String>,
+}
+
+/// Filters for `DnaStore.list` and `DnaStore.search`
+#[napi(object)]
+pub struct QueryOptions {
+    pub kind: Option<String>,
+    /// Exact label values that must all match
+    pub labels: Option<HashMap<String, String>>,
+    pub limit: Option<u32>,
+}
+
+impl QueryOptions {
+    fn into_filters(self, default_limit: Option<usize>) -> SearchFilters {
+        SearchFilters {
+            kind: self.kind,
+            metadata: self.labels.unwrap_or_default(),
+            limit: self.limit.map(|n| n as usize).or(default_limit),
+            ..Default::default()
+        }
+    }
+}
+
+/// Results per search when no limit is given
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+/// A DNA project, opened from the directory holding `.dna/config.toml`
+#[napi]
+pub struct DnaStore {
+    store: Arc<dna::DnaStore>,
+}
+
+#[napi]
+impl DnaStore {
+    /// Open the project at `path`, or the working directory
+    #[napi]
+    pub async fn open(path: Option<String>) -> napi::Result<DnaStore> {
+        let store = dna::DnaStore::open(path.as_deref().unwrap_or("."))
+            .await
+            .map_err(to_napi_err)?;
+        Ok(Self {
+            store: Arc::new(store),
+        })
+    }
+
+    /// Add an artifact and return it with its generated ID
+    #[napi]
+    pub async fn add(
+        &self,
+        kind: String,
+        content: String,
+        options: Option<AddOptions>,
+    ) -> napi::Result<Artifact> {
+        let options = options.unwrap_or(AddOptions {
+            name: None,
+            labels: None,
+            context: None,
+            format: None,
+        });
+        let format = match options.format {
+            Some(format) => format
+                .parse::<ContentFormat>()
+                .map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?,
+            None => ContentFormat::Markdown,
+        };
+        let new = NewArtifact {
+            kind,
+            content,
+            format,
+            name: options.name,
+            labels: options.labels.unwrap_or_default(),
+            context: options.context,
+        };
+        self.store
+            .add(new)
+            .await
+            .map(Into::into)
+            .map_err(to_napi_err)
+    }
+
+    /// Get an artifact by ID, or `null` if there is none
+    #[napi]
+    pub async fn get(&self, id: String) -> napi::Result<Option<Artifact>> {
+        self.store
+            .get(&id)
+            .await
+            .map(|artifact| artifact.map(Into::into))
+            .map_err(to_napi_err)
+    }
+
+    /// List artifacts, optionally by kind and exact label values
+    #[napi]
+    pub async fn list(&self, options: Option<QueryOptions>) -> napi::Result<Vec<Artifact>> {
+        let filters = options.map(|o| o.into_filters(None)).unwrap_or_default();
+        self.store
+            .list(filters)
+            .await
+            .map(|artifacts| artifacts.into_iter().map(Into::into).collect())
+            .map_err(to_napi_err)
+    }
+
+    /// Search by meaning, best matches first (10 results unless `limit` is set)
+    #[napi]
+    pub async fn search(
+        &self,
+        query: String,
+        options: Option<QueryOptions>,
+    ) -> napi::Result<Vec<SearchResult>> {
+        let filters = match options {
+            Some(options) => options.into_filters(Some(DEFAULT_SEARCH_LIMIT)),
+            None => SearchFilters {
+                limit: Some(DEFAULT_SEARCH_LIMIT),
+                ..Default::default()
+            },
+        };
+        let results = self
+            .store
+            .search(&query, filters)
+            .await
+            .map_err(to_napi_err)?;
+        Ok(results
+            .into_iter()
+            .map(|result| SearchResult {
+                artifact: result.artifact.into(),
+                score: f64::from(result.score),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn query_options_map_labels_and_default_limit() {
+        let labels = HashMap::from([("team".to_string(), "auth".to_string())]);
+        let options = QueryOptions {
+            kind: Some("intent".to_string()),
+            labels: Some(labels.clone()),
+            limit: None,
+        };
+        let filters = options.into_filters(Some(DEFAULT_SEARCH_LIMIT));
+        assert_eq!(filters.kind.as_deref(), Some("intent"));
+        assert_eq!(filters.metadata, labels);
+        assert_eq!(filters.limit, Some(DEFAULT_SEARCH_LIMIT));
+
+        let explicit = QueryOptions {
+            kind: None,
+            labels: None,
+            limit: Some(3),
+        };
+        assert_eq!(explicit.into_filters(None).limit, Some(3));
+    }
+}
@@ -0,0 +1,295 @@
+//! Python bindings for DNA.
+//!
+//! Exposes [`dna::DnaStore`] as the `dna` Python module so notebooks and
+//! evaluation pipelines can populate and query a project's truth store
+//! directly. Build with `maturin develop` from this directory:
+//!
+//! ```python
+//! import dna
+//!
+//! store = dna.DnaStore(".")
+//! store.add("intent", "Users sign in with SSO", labels={"team": "auth"})
+//! for hit in store.search("how do users log in?", limit=5):
+//!     print(hit.score, hit.artifact.id)
+//! store.render("docs/dna")
+//! ```
+//!
+//! Calls block until done and release the GIL while they wait.
+
+use chrono::{DateTime, Utc};
+use dna::services::{ContentFormat, SearchFilters, ServiceError};
+use dna::NewArtifact;
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+
+create_exception!(dna, DnaError, PyException, "Base class for DNA errors");
+create_exception!(dna, NotFoundError, DnaError, "The artifact does not exist");
+create_exception!(
+    dna,
+    ValidationError,
+    DnaError,
+    "Input was rejected, e.g. an unregistered label value"
+);
+
+/// Raise the Python exception matching the kind of service failure
+fn to_py_err(err: anyhow::Error) -> PyErr {
+    match ServiceError::classify(err) {
+        ServiceError::NotFound(msg) => NotFoundError::new_err(msg),
+        ServiceError::Validation(msg) => ValidationError::new_err(msg),
+        ServiceError::Internal(err) => DnaError::new_err(format!("{:#}", err)),
+        err => DnaError::new_err(err.to_string()),
+    }
+}
+
+/// Filters shared by `list` and `search`
+fn filters(
+    kind: Option<String>,
+    labels: Option<HashMap<String, String>>,
+    limit: Option<usize>,
+) -> SearchFilters {
+    SearchFilters {
+        kind,
+        metadata: labels.unwrap_or_default(),
+        limit,
+        ..Default::default()
+    }
+}
+
+/// A stored artifact
+#[pyclass(
+    name = "Artifact",
+    module = "dna",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+pub struct PyArtifact {
+    id: String,
+    kind: String,
+    name: Option<String>,
+    content: String,
+    format: String,
+    labels: HashMap<String, String>,
+    context: Option<String>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl From<dna::services::Artifact> for PyArtifact {
+    fn from(artifact: dna::services::Artifact) -> Self {
+        Self {
+            id: artifact.id,
+            kind: artifact.kind,
+            name: artifact.name,
+            content: artifact.content,
+            format: artifact.format.to_string(),
+            labels: artifact.metadata,
+            context: artifact.context,
+            created_at: artifact.created_at,
+            updated_at: artifact.updated_at,
+        }
+    }
+}
+
+#[pymethods]
+impl PyArtifact {
+    fn __repr__(&self) -> String {
+        match &self.name {
+            Some(name) => format!(
+                "Artifact(id={:?}, kind={:?}, name={:?})",
+                self.id, self.kind, name
+            ),
+            None => format!("Artifact(id={:?}, kind={:?})", self.id, self.kind),
+        }
+    }
+}
+
+/// An artifact and its similarity to the search query
+#[pyclass(
+    name = "SearchResult",
+    module = "dna",
+    frozen,
+    get_all,
+    skip_from_py_object
+)]
+#[derive(Clone)]
+pub struct PySearchResult {
+    artifact: PyArtifact,
+    score: f32,
+}
+
+#[pymethods]
+impl PySearchResult {
+    fn __repr__(&self) -> String {
+        format!(
+            "SearchResult(score={:.3}, artifact={})",
+            self.score,
+            self.artifact.__repr__()
+        )
+    }
+}
+
+/// A DNA project, opened from the directory holding `.dna/config.toml`
+#[pyclass(name = "DnaStore", module = "dna", frozen)]
+pub struct PyDnaStore {
+    store: dna::DnaStore,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl PyDnaStore {
+    /// Run a store call to completion without holding the GIL
+    fn wait<T: Send>(
+        &self,
+        py: Python<'_>,
+        call: impl Future<Output = anyhow::Result<T>> + Send,
+    ) -> PyResult<T> {
+        py.detach(|| self.runtime.block_on(call)).map_err(to_py_err)
+    }
+}
+
+#[pymethods]
+impl PyDnaStore {
+    #[new]
+    #[pyo3(signature = (path = None))]
+    fn new(py: Python<'_>, path: Option<PathBuf>) -> PyResult<Self> {
+        let runtime = tokio::runtime::Runtime::new()?;
+        let root = path.unwrap_or_else(|| PathBuf::from("."));
+        let store = py
+            .detach(|| runtime.block_on(dna::DnaStore::open(root)))
+            .map_err(to_py_err)?;
+        Ok(Self { store, runtime })
+    }
+
+    /// Add an artifact and return it with its generated ID
+    #[pyo3(signature = (kind, content, *, name = None, labels = None, context = None, format = "markdown"))]
+    #[allow(clippy::too_many_arguments)]
+    fn add(
+        &self,
+        py: Python<'_>,
+        kind: String,
+        content: String,
+        name: Option<String>,
+        labels: Option<HashMap<String, String>>,
+        context: Option<String>,
+        format: &str,
+    ) -> PyResult<PyArtifact> {
+        let format: ContentFormat = format
+            .parse()
+            .map_err(|e: anyhow::Error| ValidationError::new_err(e.to_string()))?;
+        let new = NewArtifact {
+            kind,
+            content,
+            format,
+            name,
+            labels: labels.unwrap_or_default(),
+            context,
+        };
+        self.wait(py, self.store.add(new)).map(Into::into)
+    }
+
+    /// Get an artifact by ID, or `None` if there is none
+    fn get(&self, py: Python<'_>, id: String) -> PyResult<Option<PyArtifact>> {
+        self.wait(py, self.store.get(&id))
+            .map(|artifact| artifact.map(Into::into))
+    }
+
+    /// List artifacts, optionally by kind and exact label values
+    #[pyo3(signature = (*, kind = None, labels = None, limit = None))]
+    fn list(
+        &self,
+        py: Python<'_>,
+        kind: Option<String>,
+        labels: Option<HashMap<String, String>>,
+        limit: Option<usize>,
+    ) -> PyResult<Vec<PyArtifact>> {
+        let filters = filters(kind, labels, limit);
+        self.wait(py, self.store.list(filters))
+            .map(|artifacts| artifacts.into_iter().map(Into::into).collect())
+    }
+
+    /// Search by meaning, best matches first
+    #[pyo3(signature = (query, *, kind = None, labels = None, limit = 10))]
+    fn search(
+        &self,
+        py: Python<'_>,
+        query: String,
+        kind: Option<String>,
+        labels: Option<HashMap<String, String>>,
+        limit: usize,
+    ) -> PyResult<Vec<PySearchResult>> {
+        let filters = filters(kind, labels, Some(limit));
+        let results = self.wait(py, self.store.search(&query, filters))?;
+        Ok(results
+            .into_iter()
+            .map(|result| PySearchResult {
+                artifact: result.artifact.into(),
+                score: result.score,
+            })
+            .collect())
+    }
+
+    /// Render every artifact as markdown under `output_dir` and return how many were written
+    #[pyo3(signature = (output_dir, group_by = None))]
+    fn render(
+        &self,
+        py: Python<'_>,
+        output_dir: PathBuf,
+        group_by: Option<Vec<String>>,
+    ) -> PyResult<usize> {
+        let group_by = group_by.unwrap_or_default();
+        self.wait(py, self.store.render(output_dir, &group_by))
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "dna")]
+fn dna_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyDnaStore>()?;
+    m.add_class::<PyArtifact>()?;
+    m.add_class::<PySearchResult>()?;
+    m.add("DnaError", m.py().get_type::<DnaError>())?;
+    m.add("NotFoundError", m.py().get_type::<NotFoundError>())?;
+    m.add("ValidationError", m.py().get_type::<ValidationError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_map_labels_to_metadata() {
+        let labels = HashMap::from([("team".to_string(), "auth".to_string())]);
+        let filters = filters(Some("intent".to_string()), Some(labels.clone()), Some(5));
+        assert_eq!(filters.kind.as_deref(), Some("intent"));
+        assert_eq!(filters.metadata, labels);
+        assert_eq!(filters.limit, Some(5));
+        assert!(!filters.include_archived);
+    }
+
+    #[test]
+    fn artifact_exposes_metadata_as_labels() {
+        let mut artifact = dna::services::Artifact::new(
+            "intent".to_string(),
+            "content".to_string(),
+            ContentFormat::Yaml,
+            Some("Login".to_string()),
+            HashMap::from([("team".to_string(), "auth".to_string())]),
+            "model".to_string(),
+        );
+        artifact.id = "abc123".to_string();
+
+        let py_artifact = PyArtifact::from(artifact);
+        assert_eq!(py_artifact.format, "yaml");
+        assert_eq!(py_artifact.labels["team"], "auth");
+        assert_eq!(
+            py_artifact.__repr__(),
+            r#"Artifact(id="abc123", kind="intent", name="Login")"#
+        );
+    }
+}
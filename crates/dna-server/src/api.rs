@@ -1,5 +1,5 @@
 use axum::{
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Path, Query, State},
     http::{header, Method},
     middleware,
     response::IntoResponse,
@@ -7,14 +7,22 @@ use axum::{
     Json, Router,
 };
 use chrono::{DateTime, Utc};
-use dna::services::{Artifact, ContentFormat, SearchFilters, SearchResult, ServiceError};
+use dna::mcp::RegisteredLabel;
+use dna::services::{
+    normalize_handle, parse_date, parse_kind_boost, slugify_kind, validate_boosts, validate_labels,
+    Artifact, ArtifactUpdate, ChangeReport, ChangeType, ChangedArtifact, ContentBlocked,
+    ContentFormat, Deprecation, MatchedVector, ReindexTarget, SearchExplanation, SearchFilters,
+    SearchResult, SearchWeights, ServiceError, UpsertAction, Upserted, VectorMatch, LANGUAGE_LABEL,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Instant;
 use tower_http::cors::CorsLayer;
+use tower_http::limit::RequestBodyLimitLayer;
 use utoipa::{IntoParams, Modify, OpenApi, ToSchema};
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::auth::{auth_middleware, require_write, ApiKeyAuth};
+use crate::auth::{auth_middleware, require_admin, require_write, ApiKeyAuth, Scoped};
 use crate::state::AppState;
 
 /// Query parameters for listing artifacts
@@ -24,10 +32,16 @@ pub struct ListQuery {
     kind: Option<String>,
     /// Maximum number of results to return
     limit: Option<usize>,
-    /// Only return artifacts created after this ISO 8601 timestamp
+    /// Only return artifacts updated at or after this date: RFC3339, YYYY-MM-DD, or relative such as 7d
     after: Option<String>,
-    /// Only return artifacts created before this ISO 8601 timestamp
+    /// Only return artifacts updated before this date, in the same forms as `after`
     before: Option<String>,
+    /// Leave out artifacts of these kinds (comma-separated)
+    not_kind: Option<String>,
+    /// Only return artifacts in this namespace
+    namespace: Option<String>,
+    /// Only return artifacts owned by this person or team, e.g. @payments
+    owner: Option<String>,
 }
 
 /// Request body for searching artifacts
@@ -37,8 +51,128 @@ pub struct SearchBody {
     query: String,
     /// Filter by artifact kind
     kind: Option<String>,
+    /// Only return artifacts in this namespace
+    namespace: Option<String>,
     /// Maximum number of results to return
     limit: Option<usize>,
+    /// Relative weights of content and context similarity (default: content only)
+    weights: Option<SearchWeights>,
+    /// Score multipliers by kind, e.g. {"invariant": 2.0}; override configured boosts
+    boosts: Option<HashMap<String, f32>>,
+    /// Leave out artifacts of these kinds
+    kind_not: Option<Vec<String>>,
+    /// Leave out artifacts with any of these labels
+    metadata_not: Option<HashMap<String, String>>,
+    /// Leave out these artifact IDs, e.g. results already seen
+    exclude_ids: Option<Vec<String>>,
+    /// Also return deprecated artifacts, which are left out by default
+    include_deprecated: Option<bool>,
+    /// Only artifacts in this language, as an ISO 639-3 code such as "deu";
+    /// searched with the model configured for it, if any
+    language: Option<String>,
+    /// Add the glossary's synonyms for terms in the query, e.g. "ICP" also
+    /// searches for "Ideal Customer Profile"
+    expand: Option<bool>,
+    /// Explain each result's score: vector distances, boost and matched filters
+    explain: Option<bool>,
+}
+
+/// Query parameters for searching artifacts, mirroring the search body so
+/// searches can be linked
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct SearchQuery {
+    /// Search query text
+    q: String,
+    /// Filter by artifact kind
+    kind: Option<String>,
+    /// Only return artifacts in this namespace
+    namespace: Option<String>,
+    /// Maximum number of results to return
+    limit: Option<usize>,
+    /// Weight of content similarity; with `context_weight`, replaces the
+    /// default of ranking by content alone
+    content_weight: Option<f32>,
+    /// Weight of context similarity
+    context_weight: Option<f32>,
+    /// Score multipliers by kind (comma-separated), e.g. invariant=2,contract=1.5
+    boost: Option<String>,
+    /// Leave out artifacts of these kinds (comma-separated)
+    not_kind: Option<String>,
+    /// Leave out artifacts with any of these labels (comma-separated), e.g. status=draft
+    not_label: Option<String>,
+    /// Leave out these artifact IDs (comma-separated)
+    exclude_ids: Option<String>,
+    /// Also return deprecated artifacts
+    include_deprecated: Option<bool>,
+    /// Only artifacts in this language, as an ISO 639-3 code such as "deu"
+    language: Option<String>,
+    /// Add the glossary's synonyms for terms in the query
+    expand: Option<bool>,
+    /// Explain each result's score
+    explain: Option<bool>,
+}
+
+impl TryFrom<SearchQuery> for SearchBody {
+    type Error = String;
+
+    fn try_from(query: SearchQuery) -> Result<Self, String> {
+        let list = |value: Option<String>| -> Option<Vec<String>> {
+            value.map(|v| {
+                v.split(',')
+                    .map(str::trim)
+                    .filter(|item| !item.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+        };
+        let boosts = match list(query.boost) {
+            Some(boosts) => Some(
+                boosts
+                    .iter()
+                    .map(|b| parse_kind_boost(b))
+                    .collect::<anyhow::Result<HashMap<_, _>>>()
+                    .map_err(|e| e.to_string())?,
+            ),
+            None => None,
+        };
+        let metadata_not = match list(query.not_label) {
+            Some(labels) => Some(
+                labels
+                    .iter()
+                    .map(|label| match label.split_once('=') {
+                        Some((key, value)) => {
+                            Ok((key.trim().to_string(), value.trim().to_string()))
+                        },
+                        None => Err(format!("Invalid label '{}': expected key=value", label)),
+                    })
+                    .collect::<Result<HashMap<_, _>, _>>()?,
+            ),
+            None => None,
+        };
+        let weights =
+            (query.content_weight.is_some() || query.context_weight.is_some()).then(|| {
+                SearchWeights {
+                    content: query.content_weight.unwrap_or(0.0),
+                    context: query.context_weight.unwrap_or(0.0),
+                }
+            });
+
+        Ok(Self {
+            query: query.q,
+            kind: query.kind,
+            namespace: query.namespace,
+            limit: query.limit,
+            weights,
+            boosts,
+            kind_not: list(query.not_kind),
+            metadata_not,
+            exclude_ids: list(query.exclude_ids),
+            include_deprecated: query.include_deprecated,
+            language: query.language,
+            expand: query.expand,
+            explain: query.explain,
+        })
+    }
 }
 
 /// Request body for creating an artifact
@@ -48,12 +182,40 @@ pub struct CreateBody {
     kind: String,
     /// Artifact content
     content: String,
-    /// Content format: markdown, yaml, json, openapi, text
+    /// Content format: markdown, yaml, json, openapi, text (default: the kind's format)
     format: Option<String>,
     /// Optional human-readable name
     name: Option<String>,
     /// Optional key-value metadata
     metadata: Option<HashMap<String, String>>,
+    /// Optional namespace scoping the artifact within the store, such as a team
+    namespace: Option<String>,
+}
+
+/// Request body for creating or updating the artifact with an external ID
+#[derive(Deserialize, ToSchema)]
+pub struct UpsertBody {
+    /// Artifact kind (e.g., "intent", "contract")
+    kind: String,
+    /// Artifact content
+    content: String,
+    /// Content format: markdown, yaml, json, openapi, text (default: the kind's format)
+    format: Option<String>,
+    /// Optional human-readable name
+    name: Option<String>,
+    /// Optional key-value metadata; merged into the existing labels on update
+    metadata: Option<HashMap<String, String>>,
+    /// Optional context used for search
+    context: Option<String>,
+    /// Optional namespace, applied when the artifact is created
+    namespace: Option<String>,
+}
+
+/// Request body for fetching several artifacts at once
+#[derive(Deserialize, ToSchema)]
+pub struct BatchGetBody {
+    /// Artifact IDs to fetch
+    ids: Vec<String>,
 }
 
 /// Request body for updating an artifact
@@ -69,6 +231,47 @@ pub struct UpdateBody {
     metadata: Option<HashMap<String, String>>,
 }
 
+/// Request body for several writes at once
+#[derive(Deserialize, ToSchema)]
+pub struct BulkBody {
+    /// Operations to apply; creates run first, then updates, then deletes
+    operations: Vec<BulkOperation>,
+}
+
+/// One write in a bulk request, chosen by `op`
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum BulkOperation {
+    /// Create an artifact
+    Create(CreateBody),
+    /// Update an artifact
+    Update(BulkUpdateBody),
+    /// Delete an artifact
+    Delete(BulkDeleteBody),
+}
+
+/// An update in a bulk request
+#[derive(Deserialize, ToSchema)]
+pub struct BulkUpdateBody {
+    /// Artifact ID
+    id: String,
+    /// New content (optional)
+    content: Option<String>,
+    /// New name (optional)
+    name: Option<String>,
+    /// New kind (optional)
+    kind: Option<String>,
+    /// New metadata (replaces existing)
+    metadata: Option<HashMap<String, String>>,
+}
+
+/// A delete in a bulk request
+#[derive(Deserialize, ToSchema)]
+pub struct BulkDeleteBody {
+    /// Artifact ID
+    id: String,
+}
+
 /// Error response wrapper
 #[derive(Serialize, ToSchema)]
 pub struct ErrorResponse {
@@ -92,18 +295,104 @@ pub struct ArtifactListResponse {
     artifacts: Vec<Artifact>,
 }
 
+/// Response to a batch get
+#[derive(Serialize, ToSchema)]
+pub struct BatchGetResponse {
+    /// Artifacts found, in the order their IDs were asked for
+    artifacts: Vec<Artifact>,
+    /// IDs that matched no artifact
+    missing: Vec<String>,
+}
+
+/// Response to a bulk request
+#[derive(Serialize, ToSchema)]
+pub struct BulkResponse {
+    /// One result per operation, in the order of the request
+    results: Vec<BulkItemResult>,
+    /// Operations that succeeded
+    succeeded: usize,
+    /// Operations that failed
+    failed: usize,
+}
+
+/// Outcome of one operation in a bulk request
+#[derive(Serialize, ToSchema)]
+pub struct BulkItemResult {
+    /// Position of the operation in the request
+    index: usize,
+    /// "create", "update" or "delete"
+    op: String,
+    /// Status the operation would have had as a request of its own, such as
+    /// 201, 200, 204 or 404
+    status: u16,
+    /// ID of the artifact, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    /// The artifact as written, for creates and updates
+    #[serde(skip_serializing_if = "Option::is_none")]
+    artifact: Option<Artifact>,
+    /// Why the operation failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<ErrorDetail>,
+}
+
+impl BulkItemResult {
+    fn new(
+        index: usize,
+        op: &str,
+        id: Option<String>,
+        outcome: Result<
+            (axum::http::StatusCode, Option<Artifact>),
+            (axum::http::StatusCode, ErrorDetail),
+        >,
+    ) -> Self {
+        let (status, artifact, error) = match outcome {
+            Ok((status, artifact)) => (status, artifact, None),
+            Err((status, error)) => (status, None, Some(error)),
+        };
+        Self {
+            index,
+            op: op.to_string(),
+            status: status.as_u16(),
+            id: id.or_else(|| artifact.as_ref().map(|a| a.id.clone())),
+            artifact,
+            error,
+        }
+    }
+}
+
 /// Response containing search results
 #[derive(Serialize, ToSchema)]
 pub struct SearchResultsResponse {
     /// Search results with scores
     results: Vec<SearchResult>,
+    /// Why each result scored as it did, in the same order (when requested)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanations: Option<Vec<SearchExplanation>>,
 }
 
-/// Response containing changes (same as artifact list)
+/// Response containing changes read from the store's version history
 #[derive(Serialize, ToSchema)]
 pub struct ChangesResponse {
-    /// List of changed artifacts
-    changes: Vec<Artifact>,
+    /// Created, updated and deleted artifacts, latest change first. Each
+    /// carries its change type and the version of its last change; deleted
+    /// ones are shown as last stored
+    changes: Vec<ChangedArtifact>,
+    /// The oldest version left, when the window reaches back past versions
+    /// that were cleaned up and earlier changes are missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated_at: Option<u64>,
+}
+
+/// Artifact counts for the store
+#[derive(Serialize, ToSchema)]
+pub struct StatsResponse {
+    /// Number of active artifacts
+    total: usize,
+    /// Active artifacts per kind
+    kinds: HashMap<String, usize>,
+    /// Current store version
+    version: u64,
 }
 
 /// Health check response
@@ -113,6 +402,51 @@ pub struct HealthResponse {
     status: String,
 }
 
+/// Readiness check response
+#[derive(Serialize, ToSchema)]
+pub struct ReadyResponse {
+    /// "ok" when every dependency is available, otherwise "unavailable"
+    status: String,
+    /// Artifact store status
+    database: DependencyStatus,
+    /// Embedding provider status
+    embedding: DependencyStatus,
+}
+
+/// Status of one dependency
+#[derive(Serialize, ToSchema)]
+pub struct DependencyStatus {
+    /// "ok" or "error"
+    status: String,
+    /// Time the check took in milliseconds
+    latency_ms: u64,
+    /// Why the dependency is unavailable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl DependencyStatus {
+    fn from_result<T, E: std::fmt::Display>(result: Result<T, E>, started: Instant) -> Self {
+        let latency_ms = started.elapsed().as_millis() as u64;
+        match result {
+            Ok(_) => Self {
+                status: "ok".to_string(),
+                latency_ms,
+                error: None,
+            },
+            Err(e) => Self {
+                status: "error".to_string(),
+                latency_ms,
+                error: Some(e.to_string()),
+            },
+        }
+    }
+
+    fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
 fn error_response(
     status: axum::http::StatusCode,
     code: &str,
@@ -127,9 +461,37 @@ fn error_response(
     (status, Json(body)).into_response()
 }
 
+/// Map a service failure to its HTTP status and error code
+fn service_error(err: impl Into<ServiceError>) -> axum::response::Response {
+    let (status, detail) = service_error_detail(err);
+    (status, Json(ErrorResponse { error: detail })).into_response()
+}
+
+/// HTTP status and error detail for a service failure
+fn service_error_detail(err: impl Into<ServiceError>) -> (axum::http::StatusCode, ErrorDetail) {
+    use axum::http::StatusCode;
+
+    let err = err.into();
+    let (status, message) = match &err {
+        ServiceError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.clone()),
+        ServiceError::Validation(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg.clone()),
+        ServiceError::Conflict(msg) => (StatusCode::CONFLICT, msg.clone()),
+        ServiceError::RateLimited(msg) => (StatusCode::TOO_MANY_REQUESTS, msg.clone()),
+        ServiceError::ProviderUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg.clone()),
+        ServiceError::StorageCorruption(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg.clone()),
+        ServiceError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg.clone()),
+        ServiceError::Internal(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    };
+    let detail = ErrorDetail {
+        code: err.code().to_string(),
+        message,
+    };
+    (status, detail)
+}
+
+/// Parse a date filter: RFC3339, a calendar date, or relative such as `7d`
 fn parse_datetime(s: &str) -> Result<DateTime<Utc>, String> {
-    s.parse::<DateTime<Utc>>()
-        .map_err(|e| format!("Invalid datetime '{}': {}", s, e))
+    parse_date(s).map_err(|e| e.to_string())
 }
 
 fn parse_content_format(s: &str) -> Result<ContentFormat, String> {
@@ -137,32 +499,33 @@ fn parse_content_format(s: &str) -> Result<ContentFormat, String> {
         .map_err(|e| format!("Invalid content format '{}': {}", s, e))
 }
 
-/// Validate metadata keys against registered labels.
+/// Format for a new artifact of `kind` when the request doesn't name one
+fn kind_default_format(kind: &str, state: &AppState) -> ContentFormat {
+    let slug = slugify_kind(kind);
+    state
+        .registered_kinds
+        .iter()
+        .find(|k| k.slug == slug)
+        .and_then(|k| k.format)
+        .unwrap_or(ContentFormat::Markdown)
+}
+
+/// Validate metadata against registered labels for the given kind.
 /// Returns None if valid, Some(error_response) if invalid.
 fn validate_metadata_labels(
+    kind: &str,
     metadata: &HashMap<String, String>,
     state: &AppState,
 ) -> Option<axum::response::Response> {
-    if state.registered_labels.is_empty() {
-        return None;
-    }
-    let unregistered: Vec<&str> = metadata
-        .keys()
-        .filter(|k| !state.registered_labels.iter().any(|l| l.key == **k))
-        .map(|k| k.as_str())
-        .collect();
-    if unregistered.is_empty() {
-        None
-    } else {
-        Some(error_response(
-            axum::http::StatusCode::BAD_REQUEST,
-            "bad_request",
-            &format!(
-                "Unregistered label key(s): {}. Register with 'dna label add <key> <description>'.",
-                unregistered.join(", ")
-            ),
-        ))
-    }
+    validate_labels(&state.registered_labels, &slugify_kind(kind), metadata)
+        .err()
+        .map(|e| {
+            error_response(
+                axum::http::StatusCode::BAD_REQUEST,
+                "bad_request",
+                &e.to_string(),
+            )
+        })
 }
 
 #[utoipa::path(
@@ -180,6 +543,40 @@ async fn health() -> impl IntoResponse {
     })
 }
 
+#[utoipa::path(
+    get,
+    path = "/ready",
+    tag = "System",
+    responses(
+        (status = 200, description = "Dependencies are available", body = ReadyResponse),
+        (status = 503, description = "A dependency is unavailable", body = ReadyResponse)
+    ),
+    security()
+)]
+async fn ready(State(state): State<AppState>) -> axum::response::Response {
+    let started = Instant::now();
+    let database = DependencyStatus::from_result(state.db.count().await, started);
+
+    let started = Instant::now();
+    let embedding = DependencyStatus::from_result(
+        state.embedding_probe.check(state.embedding.as_ref()).await,
+        started,
+    );
+
+    let ready = database.is_ok() && embedding.is_ok();
+    let status = if ready {
+        axum::http::StatusCode::OK
+    } else {
+        axum::http::StatusCode::SERVICE_UNAVAILABLE
+    };
+    let body = ReadyResponse {
+        status: if ready { "ok" } else { "unavailable" }.to_string(),
+        database,
+        embedding,
+    };
+    (status, Json(body)).into_response()
+}
+
 #[utoipa::path(
     get,
     path = "/api/v1/artifacts",
@@ -194,7 +591,7 @@ async fn health() -> impl IntoResponse {
     security(("bearer_auth" = []))
 )]
 async fn list_artifacts(
-    State(state): State<AppState>,
+    Scoped(state): Scoped,
     Query(query): Query<ListQuery>,
 ) -> axum::response::Response {
     let after = match query.after {
@@ -219,19 +616,21 @@ async fn list_artifacts(
 
     let filters = SearchFilters {
         kind: query.kind,
+        namespace: query.namespace,
+        owner: query.owner.as_deref().map(normalize_handle),
         after,
         before,
         limit: query.limit,
+        kind_not: query
+            .not_kind
+            .map(|kinds| kinds.split(',').map(|k| k.trim().to_string()).collect())
+            .unwrap_or_default(),
         ..Default::default()
     };
 
     match state.artifact_service.list(filters).await {
         Ok(artifacts) => Json(ArtifactListResponse { artifacts }).into_response(),
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
-            &e.to_string(),
-        ),
+        Err(e) => service_error(e),
     }
 }
 
@@ -244,14 +643,266 @@ async fn list_artifacts(
         (status = 201, description = "Artifact created", body = Artifact),
         (status = 400, description = "Bad request", body = ErrorResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 403, description = "Write access required"),
-        (status = 500, description = "Internal server error", body = ErrorResponse)
+        (status = 403, description = "Write access to the kind required"),
+        (status = 413, description = "Request body too large"),
+        (status = 422, description = "Input rejected by limits or content scanning", body = ErrorResponse),
+        (status = 429, description = "Embedding provider rate limited the request", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 503, description = "Embedding provider unavailable", body = ErrorResponse)
     ),
     security(("bearer_auth" = ["write"]))
 )]
 async fn create_artifact(
-    State(state): State<AppState>,
+    Scoped(state): Scoped,
     Json(body): Json<CreateBody>,
+) -> axum::response::Response {
+    let artifact = match new_artifact(body, &state) {
+        Ok(artifact) => artifact,
+        Err(msg) => {
+            return error_response(axum::http::StatusCode::BAD_REQUEST, "bad_request", &msg)
+        },
+    };
+
+    match state.artifact_service.import(artifact).await {
+        Ok(artifact) => (axum::http::StatusCode::CREATED, Json(artifact)).into_response(),
+        Err(e) if e.is::<ContentBlocked>() => error_response(
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            "content_blocked",
+            &e.to_string(),
+        ),
+        Err(e) => service_error(e),
+    }
+}
+
+/// The artifact a create request describes, once its format and labels check out
+fn new_artifact(body: CreateBody, state: &AppState) -> Result<Artifact, String> {
+    let format = match body.format {
+        Some(ref f) => parse_content_format(f)?,
+        None => kind_default_format(&body.kind, state),
+    };
+
+    let metadata = body.metadata.unwrap_or_default();
+    validate_labels(
+        &state.registered_labels,
+        &slugify_kind(&body.kind),
+        &metadata,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut artifact = Artifact::new(
+        body.kind,
+        body.content,
+        format,
+        body.name,
+        metadata,
+        String::new(),
+    );
+    // An empty ID is generated by the service, as for a plain add
+    artifact.id = String::new();
+    artifact.namespace = body.namespace;
+    Ok(artifact)
+}
+
+/// Most operations accepted by one bulk request
+const MAX_BULK_OPERATIONS: usize = 1000;
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/artifacts/bulk",
+    tag = "Artifacts",
+    request_body = BulkBody,
+    responses(
+        (status = 200, description = "Operations applied; each result carries its own status and error", body = BulkResponse),
+        (status = 400, description = "No operations, or more than 1000", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Write access required"),
+        (status = 413, description = "Request body too large")
+    ),
+    security(("bearer_auth" = ["write"]))
+)]
+async fn bulk_artifacts(
+    Scoped(state): Scoped,
+    Json(body): Json<BulkBody>,
+) -> axum::response::Response {
+    use axum::http::StatusCode;
+
+    let count = body.operations.len();
+    if count == 0 || count > MAX_BULK_OPERATIONS {
+        return error_response(
+            StatusCode::BAD_REQUEST,
+            "bad_request",
+            &format!(
+                "A bulk request takes 1 to {} operations, got {}",
+                MAX_BULK_OPERATIONS, count
+            ),
+        );
+    }
+    let bad_request = |message: String| {
+        let detail = ErrorDetail {
+            code: "bad_request".to_string(),
+            message,
+        };
+        Err((StatusCode::BAD_REQUEST, detail))
+    };
+
+    let mut results: Vec<Option<BulkItemResult>> = (0..count).map(|_| None).collect();
+    let mut creates = Vec::new();
+    let mut updates = Vec::new();
+    let mut deletes = Vec::new();
+    for (index, operation) in body.operations.into_iter().enumerate() {
+        match operation {
+            BulkOperation::Create(body) => match new_artifact(body, &state) {
+                Ok(artifact) => creates.push((index, artifact)),
+                Err(msg) => {
+                    results[index] =
+                        Some(BulkItemResult::new(index, "create", None, bad_request(msg)))
+                },
+            },
+            BulkOperation::Update(body) => {
+                let checked = match body.metadata {
+                    Some(ref metadata) => {
+                        check_update_labels(&state, &body.id, body.kind.as_deref(), metadata).await
+                    },
+                    None => Ok(()),
+                };
+                match checked {
+                    Ok(()) => updates.push((
+                        index,
+                        ArtifactUpdate {
+                            id: body.id,
+                            content: body.content,
+                            name: body.name,
+                            kind: body.kind,
+                            metadata: body.metadata,
+                            context: None,
+                        },
+                    )),
+                    Err(outcome) => {
+                        results[index] = Some(BulkItemResult::new(
+                            index,
+                            "update",
+                            Some(body.id),
+                            Err(outcome),
+                        ))
+                    },
+                }
+            },
+            BulkOperation::Delete(body) => deletes.push((index, body.id)),
+        }
+    }
+
+    let (indices, artifacts): (Vec<usize>, Vec<Artifact>) = creates.into_iter().unzip();
+    for (index, created) in indices
+        .into_iter()
+        .zip(state.artifact_service.add_batch(artifacts).await)
+    {
+        let outcome = match created {
+            Ok(artifact) => Ok((StatusCode::CREATED, Some(artifact))),
+            Err(e) if e.is::<ContentBlocked>() => Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                ErrorDetail {
+                    code: "content_blocked".to_string(),
+                    message: e.to_string(),
+                },
+            )),
+            Err(e) => Err(service_error_detail(e)),
+        };
+        results[index] = Some(BulkItemResult::new(index, "create", None, outcome));
+    }
+
+    let (indices, updates): (Vec<usize>, Vec<ArtifactUpdate>) = updates.into_iter().unzip();
+    let ids: Vec<String> = updates.iter().map(|u| u.id.clone()).collect();
+    let updated = state.artifact_service.update_batch(updates).await;
+    for ((index, id), updated) in indices.into_iter().zip(ids).zip(updated) {
+        let outcome = updated
+            .map(|artifact| (StatusCode::OK, Some(artifact)))
+            .map_err(service_error_detail);
+        results[index] = Some(BulkItemResult::new(index, "update", Some(id), outcome));
+    }
+
+    for (index, id) in deletes {
+        let outcome = match state.artifact_service.remove(&id).await {
+            Ok(true) => Ok((StatusCode::NO_CONTENT, None)),
+            Ok(false) => Err(service_error_detail(ServiceError::NotFound(format!(
+                "Artifact '{}' not found",
+                id
+            )))),
+            Err(e) => Err(service_error_detail(e)),
+        };
+        results[index] = Some(BulkItemResult::new(index, "delete", Some(id), outcome));
+    }
+
+    let results: Vec<BulkItemResult> = results
+        .into_iter()
+        .map(|result| result.expect("every operation is either rejected or applied"))
+        .collect();
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    Json(BulkResponse {
+        succeeded: results.len() - failed,
+        failed,
+        results,
+    })
+    .into_response()
+}
+
+/// Check the labels of an update against the kind the artifact will have after it
+async fn check_update_labels(
+    state: &AppState,
+    id: &str,
+    kind: Option<&str>,
+    metadata: &HashMap<String, String>,
+) -> Result<(), (axum::http::StatusCode, ErrorDetail)> {
+    let kind = match kind {
+        Some(kind) => kind.to_string(),
+        None => match state.artifact_service.get(id).await {
+            Ok(Some(artifact)) => artifact.kind,
+            Ok(None) => {
+                return Err(service_error_detail(ServiceError::NotFound(format!(
+                    "Artifact '{}' not found",
+                    id
+                ))))
+            },
+            Err(e) => return Err(service_error_detail(e)),
+        },
+    };
+    validate_labels(&state.registered_labels, &slugify_kind(&kind), metadata).map_err(|e| {
+        (
+            axum::http::StatusCode::BAD_REQUEST,
+            ErrorDetail {
+                code: "bad_request".to_string(),
+                message: e.to_string(),
+            },
+        )
+    })
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/artifacts/by-external-id/{key}",
+    tag = "Artifacts",
+    params(
+        ("key" = String, Path, description = "Stable ID of the record in the caller's system")
+    ),
+    request_body = UpsertBody,
+    responses(
+        (status = 201, description = "Artifact created", body = Upserted),
+        (status = 200, description = "Artifact updated, or already up to date", body = Upserted),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Write access to the kind required"),
+        (status = 409, description = "The artifact with this key is archived", body = ErrorResponse),
+        (status = 413, description = "Request body too large"),
+        (status = 422, description = "Input rejected by limits or content scanning", body = ErrorResponse),
+        (status = 429, description = "Embedding provider rate limited the request", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 503, description = "Embedding provider unavailable", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = ["write"]))
+)]
+async fn upsert_artifact(
+    Scoped(state): Scoped,
+    Path(key): Path<String>,
+    Json(body): Json<UpsertBody>,
 ) -> axum::response::Response {
     let format = match body.format {
         Some(ref f) => match parse_content_format(f) {
@@ -260,26 +911,40 @@ async fn create_artifact(
                 return error_response(axum::http::StatusCode::BAD_REQUEST, "bad_request", &msg)
             },
         },
-        None => ContentFormat::Markdown,
+        None => kind_default_format(&body.kind, &state),
     };
 
     let metadata = body.metadata.unwrap_or_default();
 
-    if let Some(err) = validate_metadata_labels(&metadata, &state) {
+    if let Some(err) = validate_metadata_labels(&body.kind, &metadata, &state) {
         return err;
     }
 
-    match state
-        .artifact_service
-        .add(body.kind, body.content, format, body.name, metadata, None)
-        .await
-    {
-        Ok(artifact) => (axum::http::StatusCode::CREATED, Json(artifact)).into_response(),
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
+    let mut artifact = Artifact::new(
+        body.kind,
+        body.content,
+        format,
+        body.name,
+        metadata,
+        String::new(),
+    );
+    artifact.context = body.context;
+    artifact.namespace = body.namespace;
+
+    match state.artifact_service.upsert(&key, artifact).await {
+        Ok(upserted) => {
+            let status = match upserted.action {
+                UpsertAction::Created => axum::http::StatusCode::CREATED,
+                UpsertAction::Updated | UpsertAction::Unchanged => axum::http::StatusCode::OK,
+            };
+            (status, Json(upserted)).into_response()
+        },
+        Err(e) if e.is::<ContentBlocked>() => error_response(
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            "content_blocked",
             &e.to_string(),
         ),
+        Err(e) => service_error(e),
     }
 }
 
@@ -291,32 +956,75 @@ async fn create_artifact(
         ("id" = String, Path, description = "Artifact ID")
     ),
     responses(
-        (status = 200, description = "Artifact found", body = Artifact),
+        (status = 200, description = "Artifact found; for a former ID, the artifact it now belongs to with redirected_from set. A deprecated artifact with a successor has a Link header with rel=\"successor-version\"", body = Artifact),
+        (status = 304, description = "Unchanged since the ETag in If-None-Match or the date in If-Modified-Since"),
         (status = 401, description = "Unauthorized"),
         (status = 404, description = "Artifact not found", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     security(("bearer_auth" = []))
 )]
-async fn get_artifact(
-    State(state): State<AppState>,
-    Path(id): Path<String>,
-) -> axum::response::Response {
+async fn get_artifact(Scoped(state): Scoped, Path(id): Path<String>) -> axum::response::Response {
     match state.artifact_service.get(&id).await {
-        Ok(Some(artifact)) => Json(artifact).into_response(),
+        Ok(Some(artifact)) => {
+            let successor = Deprecation::of(&artifact).and_then(|d| d.replaced_by);
+            let mut response =
+                (crate::cache::artifact_headers(&artifact), Json(artifact)).into_response();
+            // Relative, so it resolves under whatever prefix the request used
+            let link = successor
+                .map(|id| format!("<{}>; rel=\"successor-version\"", id))
+                .and_then(|link| header::HeaderValue::from_str(&link).ok());
+            if let Some(link) = link {
+                response.headers_mut().insert(header::LINK, link);
+            }
+            response
+        },
         Ok(None) => error_response(
             axum::http::StatusCode::NOT_FOUND,
             "not_found",
             &format!("Artifact '{}' not found", id),
         ),
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
-            &e.to_string(),
-        ),
+        Err(e) => service_error(e),
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/v1/artifacts/batch-get",
+    tag = "Artifacts",
+    request_body = BatchGetBody,
+    responses(
+        (status = 200, description = "Artifacts found and IDs that matched none; former IDs return the artifact with redirected_from set", body = BatchGetResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Read access to a kind required"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn batch_get_artifacts(
+    Scoped(state): Scoped,
+    Json(body): Json<BatchGetBody>,
+) -> axum::response::Response {
+    let artifacts = match state.artifact_service.get_many(&body.ids).await {
+        Ok(artifacts) => artifacts,
+        Err(e) => return service_error(e),
+    };
+
+    let mut found = Vec::with_capacity(artifacts.len());
+    let mut missing = Vec::new();
+    for (id, artifact) in body.ids.into_iter().zip(artifacts) {
+        match artifact {
+            Some(artifact) => found.push(artifact),
+            None => missing.push(id),
+        }
+    }
+    Json(BatchGetResponse {
+        artifacts: found,
+        missing,
+    })
+    .into_response()
+}
+
 #[utoipa::path(
     put,
     path = "/api/v1/artifacts/{id}",
@@ -329,26 +1037,26 @@ async fn get_artifact(
         (status = 200, description = "Artifact updated", body = Artifact),
         (status = 400, description = "Bad request", body = ErrorResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 403, description = "Write access required"),
+        (status = 403, description = "Write access to the kind required"),
+        (status = 413, description = "Request body too large"),
+        (status = 422, description = "Input rejected by limits or content scanning", body = ErrorResponse),
         (status = 404, description = "Artifact not found", body = ErrorResponse),
-        (status = 500, description = "Internal server error", body = ErrorResponse)
+        (status = 429, description = "Embedding provider rate limited the request", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 503, description = "Embedding provider unavailable", body = ErrorResponse)
     ),
     security(("bearer_auth" = ["write"]))
 )]
 async fn update_artifact(
-    State(state): State<AppState>,
+    Scoped(state): Scoped,
     Path(id): Path<String>,
     Json(body): Json<UpdateBody>,
 ) -> axum::response::Response {
     if let Some(ref metadata) = body.metadata {
-        // Only validate keys that are being set (not removed via empty value)
-        let non_empty: HashMap<String, String> = metadata
-            .iter()
-            .filter(|(_, v)| !v.is_empty())
-            .map(|(k, v)| (k.clone(), v.clone()))
-            .collect();
-        if let Some(err) = validate_metadata_labels(&non_empty, &state) {
-            return err;
+        if let Err((status, detail)) =
+            check_update_labels(&state, &id, body.kind.as_deref(), metadata).await
+        {
+            return (status, Json(ErrorResponse { error: detail })).into_response();
         }
     }
 
@@ -358,14 +1066,7 @@ async fn update_artifact(
         .await
     {
         Ok(artifact) => Json(artifact).into_response(),
-        Err(ServiceError::NotFound(msg)) => {
-            error_response(axum::http::StatusCode::NOT_FOUND, "not_found", &msg)
-        },
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
-            &e.to_string(),
-        ),
+        Err(e) => service_error(e),
     }
 }
 
@@ -379,14 +1080,14 @@ async fn update_artifact(
     responses(
         (status = 204, description = "Artifact deleted"),
         (status = 401, description = "Unauthorized"),
-        (status = 403, description = "Write access required"),
+        (status = 403, description = "Write access to the kind required"),
         (status = 404, description = "Artifact not found", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     ),
     security(("bearer_auth" = ["write"]))
 )]
 async fn delete_artifact(
-    State(state): State<AppState>,
+    Scoped(state): Scoped,
     Path(id): Path<String>,
 ) -> axum::response::Response {
     match state.artifact_service.remove(&id).await {
@@ -396,11 +1097,7 @@ async fn delete_artifact(
             "not_found",
             &format!("Artifact '{}' not found", id),
         ),
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
-            &e.to_string(),
-        ),
+        Err(e) => service_error(e),
     }
 }
 
@@ -411,29 +1108,109 @@ async fn delete_artifact(
     request_body = SearchBody,
     responses(
         (status = 200, description = "Search results", body = SearchResultsResponse),
+        (status = 400, description = "Invalid search weights or boosts", body = ErrorResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 500, description = "Internal server error", body = ErrorResponse)
+        (status = 429, description = "Embedding provider rate limited the request", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 503, description = "Embedding provider unavailable", body = ErrorResponse)
     ),
     security(("bearer_auth" = []))
 )]
 async fn search_artifacts(
-    State(state): State<AppState>,
+    Scoped(state): Scoped,
     Json(body): Json<SearchBody>,
 ) -> axum::response::Response {
+    search(&state, body).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/search",
+    tag = "Search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Search results", body = SearchResultsResponse),
+        (status = 304, description = "Unchanged since the ETag in If-None-Match"),
+        (status = 400, description = "Invalid search weights, boosts or labels", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 429, description = "Embedding provider rate limited the request", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 503, description = "Embedding provider unavailable", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn search_artifacts_get(
+    Scoped(state): Scoped,
+    Query(query): Query<SearchQuery>,
+) -> axum::response::Response {
+    match SearchBody::try_from(query) {
+        Ok(body) => search(&state, body).await,
+        Err(msg) => error_response(axum::http::StatusCode::BAD_REQUEST, "bad_request", &msg),
+    }
+}
+
+/// Run a search as described by the search body
+async fn search(state: &AppState, body: SearchBody) -> axum::response::Response {
+    let weights = body.weights.unwrap_or_default();
+    let boosts = body.boosts.unwrap_or_default();
+    if let Err(e) = weights.validate().and_then(|_| validate_boosts(&boosts)) {
+        return error_response(
+            axum::http::StatusCode::BAD_REQUEST,
+            "bad_request",
+            &e.to_string(),
+        );
+    }
+
+    let query = if body.expand.unwrap_or(false) {
+        match state.search_service.expand_query(&body.query).await {
+            Ok(query) => query,
+            Err(e) => return service_error(e),
+        }
+    } else {
+        body.query
+    };
+
     let filters = SearchFilters {
         kind: body.kind,
+        namespace: body.namespace,
         limit: body.limit,
+        kind_not: body.kind_not.unwrap_or_default(),
+        metadata: body
+            .language
+            .map(|language| HashMap::from([(LANGUAGE_LABEL.to_string(), language)]))
+            .unwrap_or_default(),
+        metadata_not: body.metadata_not.unwrap_or_default(),
+        exclude_ids: body.exclude_ids.unwrap_or_default(),
+        exclude_deprecated: !body.include_deprecated.unwrap_or(false),
         ..Default::default()
     };
 
-    match state.search_service.search(&body.query, filters).await {
-        Ok(results) => Json(SearchResultsResponse { results }).into_response(),
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
-            &e.to_string(),
-        ),
-    }
+    let explain_filters = body.explain.unwrap_or(false).then(|| filters.clone());
+    let results = match state
+        .search_service
+        .search_boosted(&query, filters, weights, &boosts)
+        .await
+    {
+        Ok(results) => results,
+        Err(e) => return service_error(e),
+    };
+    let explanations = match explain_filters {
+        Some(filters) => match state
+            .search_service
+            .explain(&query, &results, &filters, weights, &boosts)
+            .await
+        {
+            Ok(explanations) => Some(explanations),
+            Err(e) => return service_error(e),
+        },
+        None => None,
+    };
+
+    Json(SearchResultsResponse {
+        results,
+        explanations,
+    })
+    .into_response()
 }
 
 // Kind-scoped request bodies (no kind field needed -- it comes from the URL)
@@ -443,7 +1220,7 @@ async fn search_artifacts(
 pub struct KindCreateBody {
     /// Artifact content
     content: String,
-    /// Content format: markdown, yaml, json, openapi, text
+    /// Content format: markdown, yaml, json, openapi, text (default: the kind's format)
     format: Option<String>,
     /// Optional human-readable name
     name: Option<String>,
@@ -458,6 +1235,10 @@ pub struct KindSearchBody {
     query: String,
     /// Maximum number of results to return
     limit: Option<usize>,
+    /// Relative weights of content and context similarity (default: content only)
+    weights: Option<SearchWeights>,
+    /// Also return deprecated artifacts, which are left out by default
+    include_deprecated: Option<bool>,
 }
 
 /// Query parameters for listing artifacts within a kind scope
@@ -465,6 +1246,8 @@ pub struct KindSearchBody {
 pub struct KindListQuery {
     /// Maximum number of results to return
     limit: Option<usize>,
+    /// Only return artifacts in this namespace
+    namespace: Option<String>,
 }
 
 #[utoipa::path(
@@ -483,23 +1266,20 @@ pub struct KindListQuery {
     security(("bearer_auth" = []))
 )]
 async fn kind_list_artifacts(
-    State(state): State<AppState>,
+    Scoped(state): Scoped,
     Path(kind): Path<String>,
     Query(query): Query<KindListQuery>,
 ) -> axum::response::Response {
     let filters = SearchFilters {
         kind: Some(kind),
+        namespace: query.namespace,
         limit: query.limit,
         ..Default::default()
     };
 
     match state.artifact_service.list(filters).await {
         Ok(artifacts) => Json(ArtifactListResponse { artifacts }).into_response(),
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
-            &e.to_string(),
-        ),
+        Err(e) => service_error(e),
     }
 }
 
@@ -515,13 +1295,17 @@ async fn kind_list_artifacts(
         (status = 201, description = "Artifact created", body = Artifact),
         (status = 400, description = "Bad request", body = ErrorResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 403, description = "Write access required"),
-        (status = 500, description = "Internal server error", body = ErrorResponse)
+        (status = 403, description = "Write access to the kind required"),
+        (status = 413, description = "Request body too large"),
+        (status = 422, description = "Input rejected by limits or content scanning", body = ErrorResponse),
+        (status = 429, description = "Embedding provider rate limited the request", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 503, description = "Embedding provider unavailable", body = ErrorResponse)
     ),
     security(("bearer_auth" = ["write"]))
 )]
 async fn kind_create_artifact(
-    State(state): State<AppState>,
+    Scoped(state): Scoped,
     Path(kind): Path<String>,
     Json(body): Json<KindCreateBody>,
 ) -> axum::response::Response {
@@ -532,12 +1316,12 @@ async fn kind_create_artifact(
                 return error_response(axum::http::StatusCode::BAD_REQUEST, "bad_request", &msg)
             },
         },
-        None => ContentFormat::Markdown,
+        None => kind_default_format(&kind, &state),
     };
 
     let metadata = body.metadata.unwrap_or_default();
 
-    if let Some(err) = validate_metadata_labels(&metadata, &state) {
+    if let Some(err) = validate_metadata_labels(&kind, &metadata, &state) {
         return err;
     }
 
@@ -547,11 +1331,12 @@ async fn kind_create_artifact(
         .await
     {
         Ok(artifact) => (axum::http::StatusCode::CREATED, Json(artifact)).into_response(),
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
+        Err(e) if e.is::<ContentBlocked>() => error_response(
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            "content_blocked",
             &e.to_string(),
         ),
+        Err(e) => service_error(e),
     }
 }
 
@@ -565,29 +1350,46 @@ async fn kind_create_artifact(
     request_body = KindSearchBody,
     responses(
         (status = 200, description = "Search results for this kind", body = SearchResultsResponse),
+        (status = 400, description = "Invalid search weights", body = ErrorResponse),
         (status = 401, description = "Unauthorized"),
-        (status = 500, description = "Internal server error", body = ErrorResponse)
+        (status = 429, description = "Embedding provider rate limited the request", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 503, description = "Embedding provider unavailable", body = ErrorResponse)
     ),
     security(("bearer_auth" = []))
 )]
 async fn kind_search_artifacts(
-    State(state): State<AppState>,
+    Scoped(state): Scoped,
     Path(kind): Path<String>,
     Json(body): Json<KindSearchBody>,
 ) -> axum::response::Response {
+    let weights = body.weights.unwrap_or_default();
+    if let Err(e) = weights.validate() {
+        return error_response(
+            axum::http::StatusCode::BAD_REQUEST,
+            "bad_request",
+            &e.to_string(),
+        );
+    }
+
     let filters = SearchFilters {
         kind: Some(kind),
         limit: body.limit,
+        exclude_deprecated: !body.include_deprecated.unwrap_or(false),
         ..Default::default()
     };
 
-    match state.search_service.search(&body.query, filters).await {
-        Ok(results) => Json(SearchResultsResponse { results }).into_response(),
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
-            &e.to_string(),
-        ),
+    match state
+        .search_service
+        .search_weighted(&body.query, filters, weights)
+        .await
+    {
+        Ok(results) => Json(SearchResultsResponse {
+            results,
+            explanations: None,
+        })
+        .into_response(),
+        Err(e) => service_error(e),
     }
 }
 
@@ -597,7 +1399,7 @@ async fn kind_search_artifacts(
     tag = "Changes",
     params(ListQuery),
     responses(
-        (status = 200, description = "List of recent changes", body = ChangesResponse),
+        (status = 200, description = "Artifacts created, updated or deleted in the window", body = ChangesResponse),
         (status = 400, description = "Bad request", body = ErrorResponse),
         (status = 401, description = "Unauthorized"),
         (status = 500, description = "Internal server error", body = ErrorResponse)
@@ -605,7 +1407,7 @@ async fn kind_search_artifacts(
     security(("bearer_auth" = []))
 )]
 async fn list_changes(
-    State(state): State<AppState>,
+    Scoped(state): Scoped,
     Query(query): Query<ListQuery>,
 ) -> axum::response::Response {
     let after = match query.after {
@@ -631,17 +1433,303 @@ async fn list_changes(
     let filters = SearchFilters {
         after,
         before,
-        limit: query.limit,
         ..Default::default()
     };
 
-    match state.artifact_service.list(filters).await {
-        Ok(changes) => Json(ChangesResponse { changes }).into_response(),
-        Err(e) => error_response(
-            axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-            "internal_error",
-            &e.to_string(),
-        ),
+    match state.artifact_service.change_history(filters).await {
+        Ok(history) => {
+            let report = ChangeReport::from_history(history, after, before);
+            let mut changes = report.changes;
+            if let Some(limit) = query.limit {
+                changes.truncate(limit);
+            }
+            Json(ChangesResponse {
+                changes,
+                truncated_at: report.truncated_at,
+            })
+            .into_response()
+        },
+        Err(e) => service_error(e),
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/stats",
+    tag = "System",
+    responses(
+        (status = 200, description = "Artifact counts and store version", body = StatsResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = []))
+)]
+async fn get_stats(Scoped(state): Scoped) -> axum::response::Response {
+    let artifacts = match state.artifact_service.list(SearchFilters::default()).await {
+        Ok(artifacts) => artifacts,
+        Err(e) => return service_error(e),
+    };
+    let version = match state.artifact_service.version().await {
+        Ok(version) => version,
+        Err(e) => return service_error(e),
+    };
+
+    let mut kinds = HashMap::new();
+    for artifact in &artifacts {
+        *kinds.entry(artifact.kind.clone()).or_insert(0) += 1;
+    }
+
+    Json(StatsResponse {
+        total: artifacts.len(),
+        kinds,
+        version,
+    })
+    .into_response()
+}
+
+/// Request body for regenerating embeddings
+#[derive(Deserialize, ToSchema)]
+pub struct ReindexBody {
+    /// Reindex only this artifact; the filters below are ignored
+    id: Option<String>,
+    /// Filter by artifact kind
+    kind: Option<String>,
+    /// Filter by labels (all must match)
+    labels: Option<HashMap<String, String>>,
+    /// Only artifacts updated after this time (RFC3339)
+    since: Option<String>,
+    /// Embeddings to regenerate: content, context or both (default: both)
+    target: Option<String>,
+}
+
+/// Result of a reindex
+#[derive(Serialize, ToSchema)]
+pub struct ReindexResponse {
+    /// Number of artifacts reindexed
+    reindexed: usize,
+    /// Embeddings that were regenerated
+    target: String,
+    /// Time taken in milliseconds
+    duration_ms: u64,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/reindex",
+    tag = "System",
+    request_body = ReindexBody,
+    responses(
+        (status = 200, description = "Embeddings regenerated", body = ReindexResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Write access to the kind required"),
+        (status = 404, description = "Artifact not found", body = ErrorResponse),
+        (status = 429, description = "Embedding provider rate limited the request", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse),
+        (status = 503, description = "Embedding provider unavailable", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = ["write"]))
+)]
+async fn reindex_artifacts(
+    Scoped(state): Scoped,
+    Json(body): Json<ReindexBody>,
+) -> axum::response::Response {
+    let target = match body.target.as_deref().map(str::parse::<ReindexTarget>) {
+        Some(Ok(target)) => target,
+        Some(Err(e)) => {
+            return error_response(
+                axum::http::StatusCode::BAD_REQUEST,
+                "bad_request",
+                &e.to_string(),
+            )
+        },
+        None => ReindexTarget::Both,
+    };
+
+    let after = match body.since {
+        Some(ref s) => match parse_datetime(s) {
+            Ok(dt) => Some(dt),
+            Err(msg) => {
+                return error_response(axum::http::StatusCode::BAD_REQUEST, "bad_request", &msg)
+            },
+        },
+        None => None,
+    };
+
+    let started = Instant::now();
+    let result = match body.id {
+        Some(id) => match state.artifact_service.reindex_by_id(&id, target).await {
+            Ok(Some(_)) => Ok(1),
+            Ok(None) => {
+                return error_response(
+                    axum::http::StatusCode::NOT_FOUND,
+                    "not_found",
+                    &format!("Artifact '{}' not found", id),
+                )
+            },
+            Err(e) => Err(e),
+        },
+        None => {
+            let filters = SearchFilters {
+                kind: body.kind,
+                metadata: body.labels.unwrap_or_default(),
+                after,
+                ..Default::default()
+            };
+            state
+                .artifact_service
+                .reindex_filtered(filters, target)
+                .await
+        },
+    };
+
+    match result {
+        Ok(reindexed) => Json(ReindexResponse {
+            reindexed,
+            target: target.to_string(),
+            duration_ms: started.elapsed().as_millis() as u64,
+        })
+        .into_response(),
+        Err(e) => service_error(e),
+    }
+}
+
+/// Query parameters for listing store versions
+#[derive(Deserialize, ToSchema, IntoParams)]
+pub struct VersionsQuery {
+    /// Maximum number of versions to return, newest first
+    limit: Option<usize>,
+}
+
+/// A committed version of the artifacts table
+#[derive(Serialize, ToSchema)]
+pub struct VersionItem {
+    /// Version number
+    version: u64,
+    /// When the version was committed
+    timestamp: DateTime<Utc>,
+}
+
+/// Response containing store versions
+#[derive(Serialize, ToSchema)]
+pub struct VersionsResponse {
+    versions: Vec<VersionItem>,
+}
+
+/// Result of compacting the store
+#[derive(Serialize, ToSchema)]
+pub struct CompactResponse {
+    /// Number of data files merged
+    files_merged: usize,
+    /// Bytes reclaimed by merging
+    bytes_saved: u64,
+}
+
+/// Request body for pruning old versions
+#[derive(Deserialize, ToSchema)]
+pub struct PruneBody {
+    /// Number of most recent versions to keep (default: 1)
+    keep_versions: Option<usize>,
+}
+
+/// Result of pruning old versions
+#[derive(Serialize, ToSchema)]
+pub struct PruneResponse {
+    /// Number of versions removed
+    versions_removed: usize,
+    /// Bytes freed on disk
+    bytes_freed: u64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/versions",
+    tag = "Maintenance",
+    params(VersionsQuery),
+    responses(
+        (status = 200, description = "Store versions", body = VersionsResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = ["admin"]))
+)]
+async fn list_versions(
+    State(state): State<AppState>,
+    Query(query): Query<VersionsQuery>,
+) -> axum::response::Response {
+    match state.db.list_versions(query.limit).await {
+        Ok(versions) => Json(VersionsResponse {
+            versions: versions
+                .into_iter()
+                .map(|v| VersionItem {
+                    version: v.version,
+                    timestamp: v.timestamp,
+                })
+                .collect(),
+        })
+        .into_response(),
+        Err(e) => service_error(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/maintenance/compact",
+    tag = "Maintenance",
+    responses(
+        (status = 200, description = "Store compacted", body = CompactResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = ["admin"]))
+)]
+async fn compact_store(State(state): State<AppState>) -> axum::response::Response {
+    match state.db.compact().await {
+        Ok(stats) => Json(CompactResponse {
+            files_merged: stats.files_merged,
+            bytes_saved: stats.bytes_saved,
+        })
+        .into_response(),
+        Err(e) => service_error(e),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/maintenance/prune",
+    tag = "Maintenance",
+    request_body = PruneBody,
+    responses(
+        (status = 200, description = "Old versions removed", body = PruneResponse),
+        (status = 400, description = "Bad request", body = ErrorResponse),
+        (status = 401, description = "Unauthorized"),
+        (status = 403, description = "Admin access required"),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    ),
+    security(("bearer_auth" = ["admin"]))
+)]
+async fn prune_versions(
+    State(state): State<AppState>,
+    Json(body): Json<PruneBody>,
+) -> axum::response::Response {
+    let keep_versions = body.keep_versions.unwrap_or(1);
+    if keep_versions == 0 {
+        return error_response(
+            axum::http::StatusCode::BAD_REQUEST,
+            "bad_request",
+            "keep_versions must be at least 1",
+        );
+    }
+
+    match state.db.cleanup_versions(keep_versions).await {
+        Ok(stats) => Json(PruneResponse {
+            versions_removed: stats.versions_removed,
+            bytes_freed: stats.bytes_freed,
+        })
+        .into_response(),
+        Err(e) => service_error(e),
     }
 }
 
@@ -659,6 +1747,27 @@ pub struct LabelItem {
     key: String,
     /// Description of what this label represents
     description: String,
+    /// Value type: string, enum, bool, or date
+    #[serde(rename = "type")]
+    value_type: String,
+    /// Allowed values for enum labels
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    values: Vec<String>,
+    /// Kinds this label applies to (empty means all kinds)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    kinds: Vec<String>,
+}
+
+impl From<&RegisteredLabel> for LabelItem {
+    fn from(label: &RegisteredLabel) -> Self {
+        Self {
+            key: label.key.clone(),
+            description: label.description.clone(),
+            value_type: label.value_type.to_string(),
+            values: label.values.clone(),
+            kinds: label.kinds.clone(),
+        }
+    }
 }
 
 /// Response containing the project truth schema
@@ -697,10 +1806,7 @@ async fn list_labels(State(state): State<AppState>) -> impl IntoResponse {
     let labels = state
         .registered_labels
         .iter()
-        .map(|l| LabelItem {
-            key: l.key.clone(),
-            description: l.description.clone(),
-        })
+        .map(LabelItem::from)
         .collect();
     Json(LabelsResponse { labels })
 }
@@ -716,16 +1822,10 @@ async fn list_labels(State(state): State<AppState>) -> impl IntoResponse {
     ),
     security(("bearer_auth" = []))
 )]
-async fn get_context(State(state): State<AppState>) -> axum::response::Response {
+async fn get_context(Scoped(state): Scoped) -> axum::response::Response {
     let artifacts = match state.artifact_service.list(SearchFilters::default()).await {
         Ok(a) => a,
-        Err(e) => {
-            return error_response(
-                axum::http::StatusCode::INTERNAL_SERVER_ERROR,
-                "internal_error",
-                &e.to_string(),
-            )
-        },
+        Err(e) => return service_error(e),
     };
 
     let mut counts: HashMap<String, usize> = HashMap::new();
@@ -746,10 +1846,7 @@ async fn get_context(State(state): State<AppState>) -> axum::response::Response
     let labels = state
         .registered_labels
         .iter()
-        .map(|l| LabelItem {
-            key: l.key.clone(),
-            description: l.description.clone(),
-        })
+        .map(LabelItem::from)
         .collect();
 
     Json(ContextResponse {
@@ -770,33 +1867,64 @@ async fn get_context(State(state): State<AppState>) -> axum::response::Response
     ),
     paths(
         health,
+        ready,
         list_artifacts,
         create_artifact,
+        bulk_artifacts,
+        upsert_artifact,
         get_artifact,
+        batch_get_artifacts,
         update_artifact,
         delete_artifact,
         search_artifacts,
+        search_artifacts_get,
         list_changes,
+        get_stats,
         kind_list_artifacts,
         kind_create_artifact,
         kind_search_artifacts,
         list_labels,
         get_context,
+        reindex_artifacts,
+        list_versions,
+        compact_store,
+        prune_versions,
     ),
     components(schemas(
         Artifact,
         ContentFormat,
         SearchResult,
+        SearchExplanation,
+        MatchedVector,
+        VectorMatch,
+        SearchWeights,
         ListQuery,
         SearchBody,
+        SearchQuery,
         CreateBody,
+        UpsertBody,
         UpdateBody,
+        BatchGetBody,
+        BatchGetResponse,
+        BulkBody,
+        BulkOperation,
+        BulkUpdateBody,
+        BulkDeleteBody,
+        BulkResponse,
+        BulkItemResult,
+        Upserted,
+        UpsertAction,
         ErrorResponse,
         ErrorDetail,
         ArtifactListResponse,
         SearchResultsResponse,
         ChangesResponse,
+        ChangedArtifact,
+        ChangeType,
+        StatsResponse,
         HealthResponse,
+        ReadyResponse,
+        DependencyStatus,
         KindCreateBody,
         KindSearchBody,
         KindListQuery,
@@ -804,6 +1932,14 @@ async fn get_context(State(state): State<AppState>) -> axum::response::Response
         LabelItem,
         ContextResponse,
         ContextKind,
+        ReindexBody,
+        ReindexResponse,
+        VersionsQuery,
+        VersionItem,
+        VersionsResponse,
+        CompactResponse,
+        PruneBody,
+        PruneResponse,
     )),
     tags(
         (name = "System", description = "System health and status"),
@@ -811,7 +1947,8 @@ async fn get_context(State(state): State<AppState>) -> axum::response::Response
         (name = "Search", description = "Semantic search across artifacts"),
         (name = "Changes", description = "Track artifact changes over time"),
         (name = "Kinds", description = "Kind-scoped artifact operations"),
-        (name = "Labels", description = "Label registry management")
+        (name = "Labels", description = "Label registry management"),
+        (name = "Maintenance", description = "Store versions, compaction and pruning")
     ),
     security(
         ("bearer_auth" = [])
@@ -868,8 +2005,11 @@ impl utoipa::Modify for OpenApiModifier<'_> {
     }
 }
 
+/// Largest request body accepted when `server.max_body_bytes` is unset
+const DEFAULT_MAX_BODY_BYTES: usize = 1024 * 1024;
+
 pub fn build_router(state: AppState) -> Router {
-    let api_key_auth = ApiKeyAuth::from_env();
+    let api_key_auth = ApiKeyAuth::from_env().with_scoped_keys(&state.server_config.keys);
 
     let cors = CorsLayer::new()
         .allow_origin(tower_http::cors::Any)
@@ -885,20 +2025,39 @@ pub fn build_router(state: AppState) -> Router {
     // Routes that require write access
     let write_routes = Router::new()
         .route("/api/v1/artifacts", post(create_artifact))
+        .route("/api/v1/artifacts/bulk", post(bulk_artifacts))
         .route(
             "/api/v1/artifacts/{id}",
             put(update_artifact).delete(delete_artifact),
         )
+        .route(
+            "/api/v1/artifacts/by-external-id/{key}",
+            put(upsert_artifact),
+        )
+        .route("/api/v1/reindex", post(reindex_artifacts))
         .route_layer(middleware::from_fn(require_write));
 
+    // Routes that require admin access
+    let admin_routes = Router::new()
+        .route("/api/v1/versions", get(list_versions))
+        .route("/api/v1/maintenance/compact", post(compact_store))
+        .route("/api/v1/maintenance/prune", post(prune_versions))
+        .route_layer(middleware::from_fn(require_admin));
+
     // Read-only API routes
     let read_routes = Router::new()
         .route("/api/v1/artifacts", get(list_artifacts))
         .route("/api/v1/artifacts/{id}", get(get_artifact))
-        .route("/api/v1/search", post(search_artifacts))
+        .route("/api/v1/artifacts/batch-get", post(batch_get_artifacts))
+        .route(
+            "/api/v1/search",
+            get(search_artifacts_get).post(search_artifacts),
+        )
         .route("/api/v1/changes", get(list_changes))
+        .route("/api/v1/stats", get(get_stats))
         .route("/api/v1/labels", get(list_labels))
-        .route("/api/v1/context", get(get_context));
+        .route("/api/v1/context", get(get_context))
+        .route_layer(middleware::from_fn(crate::cache::conditional_get));
 
     // Kind-scoped routes
     let kind_write_routes = Router::new()
@@ -907,30 +2066,41 @@ pub fn build_router(state: AppState) -> Router {
 
     let kind_read_routes = Router::new()
         .route("/api/v1/kinds/{kind}/artifacts", get(kind_list_artifacts))
-        .route("/api/v1/kinds/{kind}/search", post(kind_search_artifacts));
+        .route("/api/v1/kinds/{kind}/search", post(kind_search_artifacts))
+        .route_layer(middleware::from_fn(crate::cache::conditional_get));
 
     // Combine API routes with auth middleware
     let api_routes = Router::new()
         .merge(write_routes)
+        .merge(admin_routes)
         .merge(read_routes)
         .merge(kind_write_routes)
         .merge(kind_read_routes)
         .route_layer(middleware::from_fn(auth_middleware))
-        .layer(axum::Extension(api_key_auth));
+        .layer(axum::Extension(api_key_auth))
+        .layer(axum::Extension(crate::cache::CachePolicy::new(
+            state.server_config.cache_control.as_deref(),
+        )));
 
     // MCP routes (with dynamic kind-specific tools)
-    let mcp_routes = crate::mcp::mcp_router(
-        state.db.clone(),
-        state.embedding.clone(),
-        state.registered_kinds.clone(),
-        state.registered_labels.clone(),
-    );
+    let mcp_routes = crate::mcp::mcp_router(&state);
 
     let mut router = Router::new()
         .route("/health", get(health))
+        .route("/ready", get(ready))
+        // Authenticated by Slack's request signature rather than an API key
+        .route(
+            "/api/v1/integrations/slack",
+            post(crate::slack::slash_command),
+        )
         .merge(api_routes)
         .merge(mcp_routes);
 
+    #[cfg(feature = "ui")]
+    {
+        router = router.merge(crate::ui::ui_router());
+    }
+
     // Conditionally add API documentation
     if state.server_config.api_docs.enabled {
         router = router.merge(SwaggerUi::new("/docs").url(
@@ -939,5 +2109,108 @@ pub fn build_router(state: AppState) -> Router {
         ));
     }
 
-    router.layer(cors).with_state(state)
+    let max_body_bytes = state
+        .server_config
+        .max_body_bytes
+        .unwrap_or(DEFAULT_MAX_BODY_BYTES);
+
+    router
+        .layer(DefaultBodyLimit::max(max_body_bytes))
+        .layer(RequestBodyLimitLayer::new(max_body_bytes))
+        .layer(cors)
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use dna::services::{ArtifactService, SearchService};
+    use dna::testing::{TestDatabase, TestEmbedding};
+    use std::sync::Arc;
+    use tower::ServiceExt;
+
+    fn test_state() -> AppState {
+        let db: Arc<dyn dna::db::Database> = Arc::new(TestDatabase::new());
+        let embedding: Arc<dyn dna::embedding::EmbeddingProvider> = Arc::new(TestEmbedding);
+        AppState {
+            artifact_service: Arc::new(ArtifactService::new(db.clone(), embedding.clone())),
+            search_service: Arc::new(SearchService::new(db.clone(), embedding.clone())),
+            db,
+            embedding,
+            registered_kinds: Vec::new(),
+            registered_labels: Vec::new(),
+            server_config: Default::default(),
+            auto_prune: false,
+            embedding_probe: Default::default(),
+            backup_schedule: None,
+            verifier: Default::default(),
+            mcp_config: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn changes_include_deletions_with_their_versions() {
+        let state = test_state();
+        let service = state.artifact_service.clone();
+        let add = |content: &str| {
+            service.add(
+                "intent".to_string(),
+                content.to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+        };
+        let removed = add("Refunds take a week").await.unwrap(); // version 2
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let after = Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+        std::thread::sleep(std::time::Duration::from_millis(2));
+
+        let added = add("Orders ship after payment").await.unwrap(); // version 3
+        assert!(service.remove(&removed.id).await.unwrap()); // version 4
+
+        let app = Router::new()
+            .route("/changes", get(list_changes))
+            .with_state(state);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/changes?after={}", after))
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let changes: Vec<(&str, &str, u64)> = json["changes"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|c| {
+                (
+                    c["id"].as_str().unwrap(),
+                    c["change"].as_str().unwrap(),
+                    c["version"].as_u64().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (removed.id.as_str(), "deleted", 4),
+                (added.id.as_str(), "created", 3),
+            ]
+        );
+    }
 }
@@ -1,26 +1,66 @@
 use axum::{
-    extract::Request,
-    http::StatusCode,
+    extract::{FromRequestParts, Request},
+    http::{request::Parts, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use dna::services::{Access, AccessPolicy, KindAccess};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use subtle::ConstantTimeEq;
 
+use crate::state::AppState;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KeyScope {
     ReadOnly,
     ReadWrite,
+    /// Read-write plus store maintenance (versions, compaction, pruning)
+    Admin,
+}
+
+impl KeyScope {
+    fn can_write(self) -> bool {
+        matches!(self, KeyScope::ReadWrite | KeyScope::Admin)
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct AuthContext {
     pub scope: KeyScope,
+    /// Kinds the key is limited to, for keys from `[[server.keys]]`
+    pub access: Option<Arc<KindAccess>>,
+}
+
+impl AuthContext {
+    fn new(scope: KeyScope) -> Self {
+        Self {
+            scope,
+            access: None,
+        }
+    }
+}
+
+/// An API key limited to some kinds, from `[[server.keys]]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopedKeyConfig {
+    /// Environment variable holding the key
+    pub key_env: String,
+    /// Kinds the key may read besides those it may write; every kind when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read: Option<Vec<String>>,
+    /// Kinds the key may add, update and remove; without any the key is read-only
+    #[serde(default)]
+    pub write: Vec<String>,
 }
 
 #[derive(Clone)]
 pub struct ApiKeyAuth {
     read_only_keys: Vec<String>,
     read_write_keys: Vec<String>,
+    admin_keys: Vec<String>,
+    /// Keys limited to some kinds, with what they may read and write
+    scoped_keys: Vec<(String, Arc<KindAccess>)>,
     trust_proxy_auth: bool,
 }
 
@@ -40,6 +80,13 @@ impl ApiKeyAuth {
             .filter(|s| !s.is_empty())
             .collect();
 
+        let admin_keys = std::env::var("DNA_SERVER__API_KEYS_ADMIN")
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect();
+
         let trust_proxy_auth = std::env::var("DNA_SERVER__TRUST_PROXY_AUTH")
             .unwrap_or_default()
             .eq_ignore_ascii_case("true");
@@ -47,18 +94,53 @@ impl ApiKeyAuth {
         Self {
             read_only_keys,
             read_write_keys,
+            admin_keys,
+            scoped_keys: Vec::new(),
             trust_proxy_auth,
         }
     }
 
+    /// Accept the keys configured in `[[server.keys]]`, each limited to its
+    /// kinds. Keys whose environment variable is unset are skipped.
+    pub fn with_scoped_keys(mut self, keys: &[ScopedKeyConfig]) -> Self {
+        for config in keys {
+            let key = std::env::var(&config.key_env).unwrap_or_default();
+            if key.trim().is_empty() {
+                tracing::warn!("Skipping scoped API key: {} is not set", config.key_env);
+                continue;
+            }
+            let access = KindAccess {
+                read: config.read.clone(),
+                write: Some(config.write.clone()),
+            };
+            self.scoped_keys
+                .push((key.trim().to_string(), Arc::new(access)));
+        }
+        self
+    }
+
     pub fn is_enabled(&self) -> bool {
-        !self.read_only_keys.is_empty() || !self.read_write_keys.is_empty()
+        !self.read_only_keys.is_empty()
+            || !self.read_write_keys.is_empty()
+            || !self.admin_keys.is_empty()
+            || !self.scoped_keys.is_empty()
     }
 
     pub fn authenticate(&self, token: &str) -> Option<AuthContext> {
         let token_bytes = token.as_bytes();
 
-        // Check read-write keys first (higher privilege)
+        // Check admin keys first (highest privilege)
+        let mut is_admin = false;
+        for key in &self.admin_keys {
+            if constant_time_eq(token_bytes, key.as_bytes()) {
+                is_admin = true;
+            }
+        }
+        if is_admin {
+            return Some(AuthContext::new(KeyScope::Admin));
+        }
+
+        // Then read-write keys
         let mut is_read_write = false;
         for key in &self.read_write_keys {
             if constant_time_eq(token_bytes, key.as_bytes()) {
@@ -66,9 +148,7 @@ impl ApiKeyAuth {
             }
         }
         if is_read_write {
-            return Some(AuthContext {
-                scope: KeyScope::ReadWrite,
-            });
+            return Some(AuthContext::new(KeyScope::ReadWrite));
         }
 
         // Check read-only keys
@@ -79,12 +159,27 @@ impl ApiKeyAuth {
             }
         }
         if is_read_only {
-            return Some(AuthContext {
-                scope: KeyScope::ReadOnly,
-            });
+            return Some(AuthContext::new(KeyScope::ReadOnly));
         }
 
-        None
+        // Then keys limited to some kinds
+        let mut scoped = None;
+        for (key, access) in &self.scoped_keys {
+            if constant_time_eq(token_bytes, key.as_bytes()) {
+                scoped = Some(access);
+            }
+        }
+        scoped.map(|access| {
+            let writes = access.kinds(Access::Write);
+            AuthContext {
+                scope: if writes.is_some_and(|kinds| !kinds.is_empty()) {
+                    KeyScope::ReadWrite
+                } else {
+                    KeyScope::ReadOnly
+                },
+                access: Some(access.clone()),
+            }
+        })
     }
 }
 
@@ -112,11 +207,12 @@ pub async fn auth_middleware(request: Request, next: Next) -> Response {
         if let Some(scope_header) = request.headers().get("x-auth-scope") {
             if let Ok(scope_str) = scope_header.to_str() {
                 let scope = match scope_str {
+                    "admin" => KeyScope::Admin,
                     "read-write" | "rw" => KeyScope::ReadWrite,
                     _ => KeyScope::ReadOnly,
                 };
                 let mut request = request;
-                request.extensions_mut().insert(AuthContext { scope });
+                request.extensions_mut().insert(AuthContext::new(scope));
                 return next.run(request).await;
             }
         }
@@ -145,13 +241,45 @@ pub async fn auth_middleware(request: Request, next: Next) -> Response {
 /// Middleware to enforce write access
 pub async fn require_write(request: Request, next: Next) -> Response {
     if let Some(context) = request.extensions().get::<AuthContext>() {
-        if context.scope != KeyScope::ReadWrite {
+        if !context.scope.can_write() {
             return (StatusCode::FORBIDDEN, "Write access required").into_response();
         }
     }
     next.run(request).await
 }
 
+/// The app state with its services limited to the kinds the caller's key
+/// may use
+pub struct Scoped(pub AppState);
+
+impl FromRequestParts<AppState> for Scoped {
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let access = parts
+            .extensions
+            .get::<AuthContext>()
+            .and_then(|context| context.access.clone());
+        Ok(Self(match access {
+            Some(access) => state.clone().with_access_policy(access),
+            None => state.clone(),
+        }))
+    }
+}
+
+/// Middleware to enforce admin access
+pub async fn require_admin(request: Request, next: Next) -> Response {
+    if let Some(context) = request.extensions().get::<AuthContext>() {
+        if context.scope != KeyScope::Admin {
+            return (StatusCode::FORBIDDEN, "Admin access required").into_response();
+        }
+    }
+    next.run(request).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -162,6 +290,14 @@ mod tests {
         ApiKeyAuth {
             read_only_keys: vec!["ro-key".to_string()],
             read_write_keys: vec!["rw-key".to_string()],
+            admin_keys: vec!["admin-key".to_string()],
+            scoped_keys: vec![(
+                "agent-key".to_string(),
+                Arc::new(KindAccess {
+                    read: Some(vec!["invariant".to_string()]),
+                    write: Some(vec!["evaluation".to_string()]),
+                }),
+            )],
             trust_proxy_auth: trust_proxy,
         }
     }
@@ -255,9 +391,52 @@ mod tests {
         let ro = auth.authenticate("ro-key").unwrap();
         assert_eq!(ro.scope, KeyScope::ReadOnly);
 
+        let admin = auth.authenticate("admin-key").unwrap();
+        assert_eq!(admin.scope, KeyScope::Admin);
+
         assert!(auth.authenticate("bad-key").is_none());
     }
 
+    #[test]
+    fn scoped_keys_carry_their_kinds() {
+        let auth = test_auth(false);
+
+        let agent = auth.authenticate("agent-key").unwrap();
+        assert_eq!(agent.scope, KeyScope::ReadWrite);
+        let access = agent.access.unwrap();
+        assert!(access.allows(Access::Write, "evaluation"));
+        assert!(!access.allows(Access::Write, "invariant"));
+        assert!(access.allows(Access::Read, "invariant"));
+        assert!(auth.authenticate("rw-key").unwrap().access.is_none());
+    }
+
+    #[tokio::test]
+    async fn admin_routes_require_admin_key() {
+        let app = Router::new()
+            .route("/admin", get(|| async { "ok" }))
+            .route_layer(middleware::from_fn(require_admin))
+            .route_layer(middleware::from_fn(auth_middleware))
+            .layer(axum::Extension(test_auth(false)));
+
+        for (key, status) in [
+            ("rw-key", StatusCode::FORBIDDEN),
+            ("admin-key", StatusCode::OK),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/admin")
+                        .header("authorization", format!("Bearer {}", key))
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status, "key {}", key);
+        }
+    }
+
     #[test]
     fn constant_time_eq_works() {
         assert!(constant_time_eq(b"hello", b"hello"));
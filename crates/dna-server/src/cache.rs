@@ -0,0 +1,320 @@
+//! HTTP caching for REST reads.
+//!
+//! GET responses carry an `ETag` and a `Cache-Control` policy (`private,
+//! no-cache` unless `server.cache_control` says otherwise), and answer a
+//! matching `If-None-Match` or an unmodified `If-Modified-Since` with `304`,
+//! so polling clients don't download artifacts that haven't changed.
+//! Single artifacts get an ETag and `Last-Modified` derived from their
+//! update time and content; other responses are tagged with a hash of their
+//! body.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use dna::services::Artifact;
+use sha2::{Digest, Sha256};
+
+/// `Cache-Control` sent when `server.cache_control` is unset: clients may
+/// keep responses, but must revalidate them before use
+pub const DEFAULT_CACHE_CONTROL: &str = "private, no-cache";
+
+/// Format of HTTP dates, as in `Last-Modified`
+const HTTP_DATE: &str = "%a, %d %b %Y %H:%M:%S GMT";
+
+/// `Cache-Control` value sent with GET responses
+#[derive(Clone)]
+pub struct CachePolicy {
+    cache_control: HeaderValue,
+}
+
+impl CachePolicy {
+    /// Policy sending `cache_control`, or the default when it is unset or
+    /// not a valid header value
+    pub fn new(cache_control: Option<&str>) -> Self {
+        let cache_control = cache_control
+            .and_then(|value| match HeaderValue::from_str(value) {
+                Ok(value) => Some(value),
+                Err(_) => {
+                    tracing::warn!(
+                        "Ignoring invalid server.cache_control '{}', using '{}'",
+                        value,
+                        DEFAULT_CACHE_CONTROL
+                    );
+                    None
+                },
+            })
+            .unwrap_or_else(|| HeaderValue::from_static(DEFAULT_CACHE_CONTROL));
+        Self { cache_control }
+    }
+}
+
+/// Strong ETag for an artifact as returned, from its ID, update time and
+/// content
+pub fn artifact_etag(artifact: &Artifact) -> String {
+    let mut hasher = Sha256::new();
+    for part in [
+        artifact.id.as_str(),
+        &artifact.updated_at.timestamp_millis().to_string(),
+        &artifact.content,
+        artifact.redirected_from.as_deref().unwrap_or_default(),
+    ] {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    quoted_hash(hasher)
+}
+
+/// `ETag` and `Last-Modified` headers for a response about `artifact`
+pub fn artifact_headers(artifact: &Artifact) -> [(header::HeaderName, String); 2] {
+    [
+        (header::ETAG, artifact_etag(artifact)),
+        (header::LAST_MODIFIED, http_date(artifact.updated_at)),
+    ]
+}
+
+/// `time` as an HTTP date
+fn http_date(time: DateTime<Utc>) -> String {
+    time.format(HTTP_DATE).to_string()
+}
+
+/// First 16 bytes of a SHA-256 digest, hex-encoded and quoted
+fn quoted_hash(hasher: Sha256) -> String {
+    let hex: String = hasher.finalize()[..16]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("\"{}\"", hex)
+}
+
+/// Tag successful GET responses and answer conditional requests for
+/// unchanged ones with `304 Not Modified`
+pub async fn conditional_get(
+    axum::Extension(policy): axum::Extension<CachePolicy>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if request.method() != Method::GET {
+        return next.run(request).await;
+    }
+    let conditions = request.headers().clone();
+    let response = next.run(request).await;
+    if response.status() != StatusCode::OK {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let body = if parts.headers.contains_key(header::ETAG) {
+        body
+    } else {
+        let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                tracing::error!("Failed to read response body: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            },
+        };
+        let etag = format!("W/{}", quoted_hash(Sha256::new_with_prefix(&bytes)));
+        if let Ok(etag) = HeaderValue::from_str(&etag) {
+            parts.headers.insert(header::ETAG, etag);
+        }
+        Body::from(bytes)
+    };
+    parts
+        .headers
+        .entry(header::CACHE_CONTROL)
+        .or_insert(policy.cache_control);
+
+    if not_modified(&conditions, &parts.headers) {
+        let mut not_modified = StatusCode::NOT_MODIFIED.into_response();
+        for name in [header::ETAG, header::LAST_MODIFIED, header::CACHE_CONTROL] {
+            if let Some(value) = parts.headers.get(&name) {
+                not_modified.headers_mut().insert(name, value.clone());
+            }
+        }
+        return not_modified;
+    }
+    Response::from_parts(parts, body)
+}
+
+/// Whether the request's conditions show the client already has this
+/// response.
+///
+/// `If-None-Match` compares ETags weakly and takes precedence over
+/// `If-Modified-Since`, as RFC 9110 asks.
+fn not_modified(request: &HeaderMap, response: &HeaderMap) -> bool {
+    if let Some(if_none_match) = request.get(header::IF_NONE_MATCH) {
+        let Some(etag) = response.get(header::ETAG).and_then(|v| v.to_str().ok()) else {
+            return false;
+        };
+        let etag = etag.trim_start_matches("W/");
+        return if_none_match.to_str().is_ok_and(|tags| {
+            tags.split(',')
+                .map(|tag| tag.trim().trim_start_matches("W/"))
+                .any(|tag| tag == "*" || tag == etag)
+        });
+    }
+
+    let date = |headers: &HeaderMap, name| {
+        headers
+            .get(name)
+            .and_then(|v: &HeaderValue| v.to_str().ok())
+            .and_then(|v| DateTime::parse_from_rfc2822(v).ok())
+    };
+    match (
+        date(request, header::IF_MODIFIED_SINCE),
+        date(response, header::LAST_MODIFIED),
+    ) {
+        (Some(since), Some(modified)) => modified <= since,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{middleware, routing::get, Router};
+    use chrono::TimeZone;
+    use tower::ServiceExt;
+
+    fn artifact() -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            "Orders ship after payment".to_string(),
+            dna::services::ContentFormat::Markdown,
+            None,
+            std::collections::HashMap::new(),
+            "test".to_string(),
+        );
+        artifact.id = "int-1".to_string();
+        artifact.updated_at = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        artifact
+    }
+
+    fn app(cache_control: Option<&str>) -> Router {
+        Router::new()
+            .route(
+                "/artifact",
+                get(|| async {
+                    let artifact = artifact();
+                    (artifact_headers(&artifact), artifact.content).into_response()
+                }),
+            )
+            .route("/list", get(|| async { "[1, 2, 3]" }))
+            .route_layer(middleware::from_fn(conditional_get))
+            .layer(axum::Extension(CachePolicy::new(cache_control)))
+    }
+
+    async fn get_with(app: &Router, uri: &str, headers: &[(&str, &str)]) -> Response {
+        let mut request = Request::builder().uri(uri);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        app.clone()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn matching_etags_get_not_modified() {
+        let app = app(None);
+        let etag = artifact_etag(&artifact());
+
+        let response = get_with(&app, "/artifact", &[]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        assert_eq!(
+            response.headers()[header::LAST_MODIFIED],
+            "Sun, 01 Mar 2026 12:00:00 GMT"
+        );
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            DEFAULT_CACHE_CONTROL
+        );
+
+        let response = get_with(&app, "/artifact", &[("if-none-match", &etag)]).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+
+        let response = get_with(&app, "/artifact", &[("if-none-match", "\"stale\"")]).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn other_responses_are_tagged_by_their_body() {
+        let app = app(Some("private, max-age=30"));
+
+        let response = get_with(&app, "/list", &[]).await;
+        let etag = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(etag.starts_with("W/\""), "{etag}");
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "private, max-age=30"
+        );
+
+        let response = get_with(&app, "/list", &[("if-none-match", &etag)]).await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn unmodified_since_gets_not_modified() {
+        let app = app(None);
+
+        let response = get_with(
+            &app,
+            "/artifact",
+            &[("if-modified-since", "Sun, 01 Mar 2026 12:00:00 GMT")],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let response = get_with(
+            &app,
+            "/artifact",
+            &[("if-modified-since", "Sat, 28 Feb 2026 12:00:00 GMT")],
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn signing_and_attachment_text_change_the_etag() {
+        use dna::services::{ArtifactService, ContentFormat, Signer};
+        use dna::testing::{TestDatabase, TestEmbedding};
+        use std::sync::Arc;
+
+        let service = ArtifactService::new(Arc::new(TestDatabase::new()), Arc::new(TestEmbedding))
+            .with_signer(Some(Signer::new("alice", &Signer::generate_key()).unwrap()));
+        let added = service
+            .add(
+                "intent".to_string(),
+                "Orders ship after payment".to_string(),
+                ContentFormat::Markdown,
+                None,
+                std::collections::HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let with_text = service
+            .set_attachment_text(&added.id, Some("Retry twice".to_string()))
+            .await
+            .unwrap();
+        assert_ne!(artifact_etag(&with_text), artifact_etag(&added));
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let signed = service.sign(&added.id).await.unwrap();
+        assert_ne!(artifact_etag(&signed), artifact_etag(&with_text));
+        assert!(signed.updated_at > with_text.updated_at);
+    }
+}
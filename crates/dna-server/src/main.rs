@@ -1,10 +1,21 @@
 use anyhow::Result;
+use dna::db::Database;
+use std::future::IntoFuture;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 mod api;
 mod auth;
+mod cache;
 mod mcp;
+mod slack;
 mod state;
+#[cfg(feature = "ui")]
+mod ui;
+
+/// How long in-flight requests get to finish after a shutdown signal
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -20,6 +31,15 @@ async fn main() -> Result<()> {
         .bind
         .clone()
         .unwrap_or_else(|| "0.0.0.0:3000".to_string());
+    let shutdown_timeout = Duration::from_secs(
+        state
+            .server_config
+            .shutdown_timeout
+            .unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS),
+    );
+    let db = state.db.clone();
+    let auto_prune = state.auto_prune;
+    let backup_schedule = state.backup_schedule.clone();
 
     let app = api::build_router(state);
 
@@ -28,6 +48,16 @@ async fn main() -> Result<()> {
         if api_docs_enabled {
             tracing::info!("API documentation available at /docs");
         }
+
+        // Lambda only signals the process between invocations, so there is
+        // nothing in flight to drain; finish up and exit.
+        tokio::spawn(async move {
+            shutdown_signal().await;
+            tracing::info!("Shutting down");
+            finish(db, auto_prune, Stopped::Drained).await;
+            std::process::exit(0);
+        });
+
         lambda_http::run(app)
             .await
             .map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -36,9 +66,175 @@ async fn main() -> Result<()> {
         if api_docs_enabled {
             tracing::info!("API documentation available at http://{}/docs", bind_addr);
         }
+        #[cfg(feature = "ui")]
+        tracing::info!("Dashboard available at http://{}/ui", bind_addr);
+        if let Some(schedule) = backup_schedule {
+            tokio::spawn(run_backups(schedule));
+        }
         let listener = tokio::net::TcpListener::bind(&bind_addr).await?;
-        axum::serve(listener, app).await?;
+
+        let (draining_tx, draining_rx) = tokio::sync::oneshot::channel();
+        let server = axum::serve(listener, app)
+            .with_graceful_shutdown(async move {
+                shutdown_signal().await;
+                tracing::info!("Shutting down, waiting for in-flight requests");
+                let _ = draining_tx.send(());
+            })
+            .into_future();
+
+        let stopped = drain(server, draining_rx, shutdown_timeout).await?;
+        finish(db, auto_prune, stopped).await;
     }
 
     Ok(())
 }
+
+/// Resolves on SIGINT (Ctrl+C) or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            tracing::error!("Failed to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            },
+            Err(e) => {
+                tracing::error!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            },
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
+
+/// Take a snapshot every interval, starting one interval after startup
+async fn run_backups(schedule: state::BackupSchedule) {
+    tracing::info!("Backing up every {:?}", schedule.interval);
+    let mut timer = tokio::time::interval(schedule.interval);
+    // The first tick completes immediately
+    timer.tick().await;
+    loop {
+        timer.tick().await;
+        match schedule.service.create().await {
+            Ok(backup) => {
+                tracing::info!("Created backup {}", backup.name);
+                if let Err(e) = schedule.service.prune().await {
+                    tracing::warn!("Failed to remove old backups: {}", e);
+                }
+            },
+            Err(e) => tracing::error!("Scheduled backup failed: {:#}", e),
+        }
+    }
+}
+
+/// How the server stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Stopped {
+    /// Every in-flight request finished
+    Drained,
+    /// Requests were still running when the shutdown timeout ran out
+    TimedOut,
+}
+
+/// Run `server` until it stops, giving in-flight requests `timeout` to
+/// finish once `draining` fires
+async fn drain(
+    server: impl std::future::Future<Output = std::io::Result<()>>,
+    draining: tokio::sync::oneshot::Receiver<()>,
+    timeout: Duration,
+) -> Result<Stopped> {
+    // Stop waiting on requests that outlive the timeout
+    let deadline = async move {
+        if draining.await.is_ok() {
+            tokio::time::sleep(timeout).await;
+        } else {
+            std::future::pending::<()>().await;
+        }
+    };
+
+    tokio::select! {
+        result = server => {
+            result?;
+            Ok(Stopped::Drained)
+        },
+        _ = deadline => {
+            tracing::warn!(
+                "Requests still in flight after {}s, shutting down anyway",
+                timeout.as_secs()
+            );
+            Ok(Stopped::TimedOut)
+        },
+    }
+}
+
+/// Final store maintenance once the server has stopped taking requests.
+///
+/// Compaction only runs once every request has finished, never under one
+/// still writing. Returns whether the store was compacted.
+async fn finish(db: Arc<dyn Database>, auto_prune: bool, stopped: Stopped) -> bool {
+    if !auto_prune {
+        return false;
+    }
+    if stopped == Stopped::TimedOut {
+        tracing::warn!("Skipping compaction before exit: requests may still be writing");
+        return false;
+    }
+
+    match db.compact().await {
+        Ok(stats) => {
+            tracing::info!(
+                "Compacted store before exit: {} files merged",
+                stats.files_merged
+            );
+            true
+        },
+        Err(e) => {
+            tracing::warn!("Failed to compact store before exit: {}", e);
+            false
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dna::testing::TestDatabase;
+
+    #[tokio::test]
+    async fn draining_times_out_on_requests_that_never_finish() {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        tx.send(()).unwrap();
+        let stopped = drain(std::future::pending(), rx, Duration::from_millis(10))
+            .await
+            .unwrap();
+        assert_eq!(stopped, Stopped::TimedOut);
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        tx.send(()).unwrap();
+        let stopped = drain(async { Ok(()) }, rx, Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(stopped, Stopped::Drained);
+    }
+
+    #[tokio::test]
+    async fn compacts_only_after_a_full_drain() {
+        let db: Arc<dyn Database> = Arc::new(TestDatabase::new());
+        assert!(finish(db.clone(), true, Stopped::Drained).await);
+        assert!(!finish(db.clone(), true, Stopped::TimedOut).await);
+        assert!(!finish(db, false, Stopped::Drained).await);
+    }
+}
@@ -1,7 +1,5 @@
 use axum::Router;
-use dna::db::Database;
-use dna::embedding::EmbeddingProvider;
-use dna::mcp::{DnaToolHandler, RegisteredKind, RegisteredLabel};
+use dna::mcp::DnaToolHandler;
 use rmcp::transport::streamable_http_server::{
     session::local::LocalSessionManager, StreamableHttpService,
 };
@@ -9,12 +7,16 @@ use std::sync::Arc;
 
 use crate::state::AppState;
 
-pub fn mcp_router(
-    db: Arc<dyn Database>,
-    embedding: Arc<dyn EmbeddingProvider>,
-    kinds: Vec<RegisteredKind>,
-    labels: Vec<RegisteredLabel>,
-) -> Router<AppState> {
+pub fn mcp_router(state: &AppState) -> Router<AppState> {
+    let db = state.db.clone();
+    let embedding = state.embedding.clone();
+    let artifact_service = Arc::clone(&state.artifact_service);
+    let search_service = Arc::clone(&state.search_service);
+    let kinds = state.registered_kinds.clone();
+    let labels = state.registered_labels.clone();
+    let verifier = state.verifier.clone();
+    let mcp_config = state.mcp_config.clone();
+
     let service = StreamableHttpService::new(
         move || {
             Ok(DnaToolHandler::with_kinds_and_labels(
@@ -24,7 +26,11 @@ pub fn mcp_router(
                 None,
                 kinds.clone(),
                 labels.clone(),
-            ))
+            )
+            .with_artifact_service(Arc::clone(&artifact_service))
+            .with_search_service(Arc::clone(&search_service))
+            .with_verifier(verifier.clone())
+            .with_mcp_config(&mcp_config))
         },
         LocalSessionManager::default().into(),
         Default::default(),
@@ -0,0 +1,399 @@
+//! Slack slash commands.
+//!
+//! `/dna search <query>` and `/dna add <kind> <content>` arrive as form posts
+//! signed with the app's signing secret, which stands in for an API key, and
+//! are limited to the kinds `[server.slack]` grants, as a scoped key is.
+//! Replies are Block Kit messages: search results only for the caller, added
+//! artifacts for the whole channel.
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use dna::services::{
+    ArtifactService, ContentBlocked, ContentFormat, KindAccess, SearchFilters, SearchResult,
+    SearchService,
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+
+use crate::state::AppState;
+
+/// Requests signed longer ago than this are rejected as possible replays
+const MAX_REQUEST_AGE_SECS: i64 = 5 * 60;
+
+/// Results shown for `/dna search`
+const SEARCH_LIMIT: usize = 5;
+
+/// Characters of content shown per search result
+const SNIPPET_CHARS: usize = 280;
+
+const USAGE: &str = "Usage: `/dna search <query>` or `/dna add <kind> <content>`";
+
+/// Slack app settings, under `[server.slack]`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackConfig {
+    /// Signing secret from the app's Basic Information page
+    pub signing_secret: String,
+    /// Kinds `/dna search` may return besides those `/dna add` may create;
+    /// every kind when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read: Option<Vec<String>>,
+    /// Kinds `/dna add` may create; without any, the command only searches
+    #[serde(default)]
+    pub write: Vec<String>,
+}
+
+impl SlackConfig {
+    /// What slash commands may read and write
+    fn access(&self) -> KindAccess {
+        KindAccess {
+            read: self.read.clone(),
+            write: Some(self.write.clone()),
+        }
+    }
+}
+
+/// The fields of a slash command payload this endpoint uses
+#[derive(Debug, Deserialize)]
+struct SlashCommand {
+    #[serde(default)]
+    text: String,
+    user_id: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Command<'a> {
+    Search(&'a str),
+    Add { kind: &'a str, content: &'a str },
+}
+
+fn parse_command(text: &str) -> Option<Command<'_>> {
+    let (verb, rest) = text.trim().split_once(char::is_whitespace)?;
+    let rest = rest.trim();
+    match verb {
+        "search" if !rest.is_empty() => Some(Command::Search(rest)),
+        "add" => {
+            let (kind, content) = rest.split_once(char::is_whitespace)?;
+            let content = content.trim();
+            (!content.is_empty()).then_some(Command::Add { kind, content })
+        },
+        _ => None,
+    }
+}
+
+/// A slash command reply
+#[derive(Debug, Serialize)]
+struct Message {
+    /// `ephemeral` (only the caller sees it) or `in_channel`
+    response_type: &'static str,
+    /// Fallback for notifications
+    text: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    blocks: Vec<Value>,
+}
+
+impl Message {
+    fn ephemeral(text: impl Into<String>) -> Self {
+        Self {
+            response_type: "ephemeral",
+            text: text.into(),
+            blocks: Vec::new(),
+        }
+    }
+}
+
+pub async fn slash_command(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let Some(slack) = &state.server_config.slack else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let now = chrono::Utc::now().timestamp();
+    if let Err(reason) = verify_signature(&slack.signing_secret, &headers, &body, now) {
+        tracing::warn!("Rejected Slack request: {}", reason);
+        return (StatusCode::UNAUTHORIZED, reason).into_response();
+    }
+    let command: SlashCommand = match serde_urlencoded::from_bytes(&body) {
+        Ok(command) => command,
+        Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    };
+
+    let state = state.clone().with_access_policy(Arc::new(slack.access()));
+    let message = match parse_command(&command.text) {
+        Some(Command::Search(query)) => search(&state.search_service, query).await,
+        Some(Command::Add { kind, content }) => {
+            add(&state.artifact_service, &command.user_id, kind, content).await
+        },
+        None => Message::ephemeral(USAGE),
+    };
+    Json(message).into_response()
+}
+
+async fn search(service: &SearchService, query: &str) -> Message {
+    let filters = SearchFilters {
+        limit: Some(SEARCH_LIMIT),
+        ..Default::default()
+    };
+    match service.search(query, filters).await {
+        Ok(results) => search_message(query, &results),
+        Err(e) => Message::ephemeral(format!("Search failed: {}", e)),
+    }
+}
+
+async fn add(service: &ArtifactService, user_id: &str, kind: &str, content: &str) -> Message {
+    let result = service
+        .add(
+            kind.to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            None,
+        )
+        .await;
+    match result {
+        Ok(artifact) => {
+            let text = format!(
+                "<@{}> added {} `{}`",
+                user_id,
+                escape(&artifact.kind),
+                artifact.id
+            );
+            Message {
+                response_type: "in_channel",
+                blocks: vec![section(&text), section(&quote(&escape(&artifact.content)))],
+                text,
+            }
+        },
+        Err(e) if e.is::<ContentBlocked>() => Message::ephemeral(e.to_string()),
+        Err(e) => Message::ephemeral(format!("Couldn't add the artifact: {}", e)),
+    }
+}
+
+fn search_message(query: &str, results: &[SearchResult]) -> Message {
+    if results.is_empty() {
+        return Message::ephemeral(format!("No artifacts match _{}_", escape(query)));
+    }
+
+    let text = format!("Top {} for _{}_", results.len(), escape(query));
+    let mut blocks = vec![section(&text)];
+    for result in results {
+        let artifact = &result.artifact;
+        let title = artifact.name.as_deref().unwrap_or(&artifact.id);
+        let mut snippet: String = artifact.content.chars().take(SNIPPET_CHARS).collect();
+        if snippet.len() < artifact.content.len() {
+            snippet.push('…');
+        }
+        blocks.push(json!({ "type": "divider" }));
+        blocks.push(section(&format!(
+            "*{}*  `{}` · {:.2}\n{}",
+            escape(title),
+            escape(&artifact.kind),
+            result.score,
+            quote(&escape(&snippet))
+        )));
+        blocks.push(json!({
+            "type": "context",
+            "elements": [{ "type": "mrkdwn", "text": format!("ID `{}`", artifact.id) }],
+        }));
+    }
+
+    Message {
+        response_type: "ephemeral",
+        text,
+        blocks,
+    }
+}
+
+fn section(text: &str) -> Value {
+    json!({ "type": "section", "text": { "type": "mrkdwn", "text": text } })
+}
+
+fn quote(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("> {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Escape the characters mrkdwn treats as control sequences
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Check Slack's `v0` request signature over the raw body
+fn verify_signature(
+    secret: &str,
+    headers: &HeaderMap,
+    body: &[u8],
+    now: i64,
+) -> Result<(), &'static str> {
+    let timestamp = headers
+        .get("x-slack-request-timestamp")
+        .and_then(|v| v.to_str().ok())
+        .ok_or("missing timestamp")?;
+    let signed_at: i64 = timestamp.parse().map_err(|_| "invalid timestamp")?;
+    if (now - signed_at).abs() > MAX_REQUEST_AGE_SECS {
+        return Err("stale timestamp");
+    }
+
+    let signature = headers
+        .get("x-slack-signature")
+        .and_then(|v| v.to_str().ok())
+        .ok_or("missing signature")?;
+    let expected = sign(secret, timestamp, body);
+    if bool::from(expected.as_bytes().ct_eq(signature.as_bytes())) {
+        Ok(())
+    } else {
+        Err("signature mismatch")
+    }
+}
+
+fn sign(secret: &str, timestamp: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(b"v0:");
+    mac.update(timestamp.as_bytes());
+    mac.update(b":");
+    mac.update(body);
+    let digest: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("v0={}", digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dna::services::Artifact;
+    use dna::testing::{TestDatabase, TestEmbedding};
+
+    fn signed_headers(secret: &str, timestamp: i64, body: &[u8]) -> HeaderMap {
+        let timestamp = timestamp.to_string();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-slack-signature",
+            sign(secret, &timestamp, body).parse().unwrap(),
+        );
+        headers.insert("x-slack-request-timestamp", timestamp.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn verifies_signature_and_freshness() {
+        let body = b"command=%2Fdna&text=search+sso&user_id=U1";
+        let headers = signed_headers("secret", 1_700_000_000, body);
+
+        assert_eq!(
+            verify_signature("secret", &headers, body, 1_700_000_060),
+            Ok(())
+        );
+        assert_eq!(
+            verify_signature("other", &headers, body, 1_700_000_060),
+            Err("signature mismatch")
+        );
+        assert_eq!(
+            verify_signature("secret", &headers, b"text=add+intent+x", 1_700_000_060),
+            Err("signature mismatch")
+        );
+        assert_eq!(
+            verify_signature("secret", &headers, body, 1_700_001_000),
+            Err("stale timestamp")
+        );
+        assert_eq!(
+            verify_signature("secret", &HeaderMap::new(), body, 1_700_000_000),
+            Err("missing timestamp")
+        );
+    }
+
+    #[test]
+    fn parses_search_and_add() {
+        assert_eq!(
+            parse_command("search how do users log in?"),
+            Some(Command::Search("how do users log in?"))
+        );
+        assert_eq!(
+            parse_command(" add intent  Users sign in with SSO "),
+            Some(Command::Add {
+                kind: "intent",
+                content: "Users sign in with SSO"
+            })
+        );
+        assert_eq!(parse_command("add intent"), None);
+        assert_eq!(parse_command("search"), None);
+        assert_eq!(parse_command("delete abc"), None);
+    }
+
+    #[test]
+    fn formats_results_as_blocks() {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            "Users sign in with <SSO>".to_string(),
+            ContentFormat::Markdown,
+            Some("SSO login".to_string()),
+            HashMap::new(),
+            "test".to_string(),
+        );
+        artifact.id = "abc123".to_string();
+        let results = [SearchResult {
+            artifact,
+            score: 0.8123,
+        }];
+
+        let message = search_message("login", &results);
+        assert_eq!(message.response_type, "ephemeral");
+        assert_eq!(message.blocks.len(), 4);
+        assert_eq!(
+            message.blocks[2]["text"]["text"],
+            "*SSO login*  `intent` · 0.81\n> Users sign in with &lt;SSO&gt;"
+        );
+
+        let empty = search_message("login", &[]);
+        assert!(empty.blocks.is_empty());
+        assert_eq!(empty.text, "No artifacts match _login_");
+    }
+
+    #[tokio::test]
+    async fn add_is_limited_to_the_granted_kinds() {
+        let config: SlackConfig = serde_json::from_value(json!({
+            "signing_secret": "secret",
+            "write": ["intent"],
+        }))
+        .unwrap();
+        let service = ArtifactService::new(Arc::new(TestDatabase::new()), Arc::new(TestEmbedding))
+            .with_access_policy(Arc::new(config.access()));
+
+        let added = add(&service, "U1", "intent", "Users sign in with SSO").await;
+        assert_eq!(added.response_type, "in_channel");
+
+        let refused = add(&service, "U1", "contract", "Orders ship after payment").await;
+        assert_eq!(refused.response_type, "ephemeral");
+        assert!(refused.text.starts_with("Couldn't add the artifact"));
+
+        let search_only = SlackConfig {
+            signing_secret: "secret".to_string(),
+            read: None,
+            write: Vec::new(),
+        };
+        let service = ArtifactService::new(Arc::new(TestDatabase::new()), Arc::new(TestEmbedding))
+            .with_access_policy(Arc::new(search_only.access()));
+        let refused = add(&service, "U1", "intent", "Users sign in with SSO").await;
+        assert_eq!(refused.response_type, "ephemeral");
+    }
+}
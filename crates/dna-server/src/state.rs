@@ -4,11 +4,22 @@ use dna::db::Database;
 use dna::embedding;
 use dna::embedding::EmbeddingProvider;
 use dna::mcp::{RegisteredKind, RegisteredLabel};
-use dna::services::{ArtifactService, ProjectConfig, SearchService};
+use dna::services::seed::seeds_for;
+use dna::services::{
+    get_template, list_templates, AccessPolicy, ArtifactService, BackupService, McpConfig,
+    ProjectConfig, SearchService, SeedService, Signer, Verifier,
+};
 use figment::providers::{Env, Format, Serialized, Toml};
 use figment::Figment;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::auth::ScopedKeyConfig;
+use crate::slack::SlackConfig;
+
+/// How long `/ready` reuses the result of probing the embedding provider
+const EMBEDDING_PROBE_TTL: Duration = Duration::from_secs(60);
 
 /// API documentation branding configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,6 +63,28 @@ pub struct ServerConfig {
     /// Accepts a bool (e.g. `api_docs = false`) or a full config table.
     #[serde(default, deserialize_with = "deserialize_api_docs")]
     pub api_docs: ApiDocsConfig,
+    /// Largest request body accepted, in bytes (default: 1 MiB)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_body_bytes: Option<usize>,
+    /// Seconds to wait for in-flight requests after a shutdown signal (default: 30)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shutdown_timeout: Option<u64>,
+    /// `Cache-Control` sent with GET responses (default: `private, no-cache`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_control: Option<String>,
+    /// Slack slash commands; the endpoint is off unless this is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slack: Option<SlackConfig>,
+    /// API keys limited to some kinds
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<ScopedKeyConfig>,
+    /// Template whose kinds and labels are registered at startup, e.g. `intent`,
+    /// so a store configured only through the environment is usable at once
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Add the template's sample artifacts when the store is empty (default: true)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<bool>,
 }
 
 fn deserialize_api_docs<'de, D>(deserializer: D) -> Result<ApiDocsConfig, D::Error>
@@ -74,6 +107,45 @@ where
     }
 }
 
+/// Cached result of checking that the embedding provider can embed text.
+///
+/// Remote providers bill per request, so readiness probes reuse the last
+/// answer for [`EMBEDDING_PROBE_TTL`] instead of embedding on every call.
+#[derive(Default)]
+pub struct EmbeddingProbe {
+    last: tokio::sync::Mutex<Option<(Instant, Result<(), String>)>>,
+}
+
+impl EmbeddingProbe {
+    pub async fn check(&self, provider: &dyn EmbeddingProvider) -> Result<(), String> {
+        let mut last = self.last.lock().await;
+        if let Some((checked_at, result)) = last.as_ref() {
+            if checked_at.elapsed() < EMBEDDING_PROBE_TTL {
+                return result.clone();
+            }
+        }
+
+        let result = match provider.embed("readiness check").await {
+            Ok(embedding) if embedding.len() == provider.dimensions() => Ok(()),
+            Ok(embedding) => Err(format!(
+                "expected {} dimensions, got {}",
+                provider.dimensions(),
+                embedding.len()
+            )),
+            Err(e) => Err(e.to_string()),
+        };
+        *last = Some((Instant::now(), result.clone()));
+        result
+    }
+}
+
+/// Snapshots taken on a timer, from `[backup] interval_hours`
+#[derive(Clone)]
+pub struct BackupSchedule {
+    pub service: Arc<BackupService>,
+    pub interval: Duration,
+}
+
 #[derive(Clone)]
 pub struct AppState {
     pub db: Arc<dyn Database>,
@@ -83,6 +155,52 @@ pub struct AppState {
     pub registered_kinds: Vec<RegisteredKind>,
     pub registered_labels: Vec<RegisteredLabel>,
     pub server_config: ServerConfig,
+    /// Compact the store before exiting
+    pub auto_prune: bool,
+    pub embedding_probe: Arc<EmbeddingProbe>,
+    pub backup_schedule: Option<BackupSchedule>,
+    /// Trusted signers, from `[signing]`
+    pub verifier: Verifier,
+    /// Instructions and tool descriptions for MCP clients, from `[mcp]`
+    pub mcp_config: McpConfig,
+}
+
+impl AppState {
+    /// The same state with its artifact and search services limited to the
+    /// kinds `policy` allows
+    pub fn with_access_policy(mut self, policy: Arc<dyn AccessPolicy>) -> Self {
+        self.artifact_service = Arc::new(
+            (*self.artifact_service)
+                .clone()
+                .with_access_policy(policy.clone()),
+        );
+        self.search_service = Arc::new((*self.search_service).clone().with_access_policy(policy));
+        self
+    }
+}
+
+/// Add `template`'s sample artifacts to a store with nothing in it yet, so a
+/// fresh deployment has something to search
+async fn seed_empty_store(
+    db: &dyn Database,
+    artifact_service: &Arc<ArtifactService>,
+    template: &str,
+) -> Result<()> {
+    let Some(samples) = seeds_for(template) else {
+        return Ok(());
+    };
+    if db.count().await? > 0 {
+        return Ok(());
+    }
+    let seeded = SeedService::new(Arc::clone(artifact_service))
+        .seed(template, samples.len())
+        .await?;
+    tracing::info!(
+        "Seeded the empty store with {} {} sample artifact(s)",
+        seeded.len(),
+        template
+    );
+    Ok(())
 }
 
 /// Combined configuration for figment extraction
@@ -104,7 +222,28 @@ impl AppState {
             figment = figment.merge(Toml::file(config_path));
         }
 
-        let config: CombinedConfig = figment.merge(Env::prefixed("DNA_").split("__")).extract()?;
+        let mut config: CombinedConfig =
+            figment.merge(Env::prefixed("DNA_").split("__")).extract()?;
+        let template = match &config.server.template {
+            Some(name) => {
+                let template = get_template(name).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "Unknown template '{}' in server.template. Available templates: {}",
+                        name,
+                        list_templates().join(", ")
+                    )
+                })?;
+                let (kinds, labels) = config.project.apply_template(template);
+                tracing::info!(
+                    "Template {} registered {} kind(s) and {} label(s)",
+                    name,
+                    kinds,
+                    labels
+                );
+                Some(template)
+            },
+            None => None,
+        };
 
         let storage_uri = config
             .project
@@ -113,36 +252,63 @@ impl AppState {
             .clone()
             .unwrap_or_else(|| ".dna/db/artifacts.lance".to_string());
 
-        let lance_db = LanceDatabase::new(&storage_uri).await?;
+        let lance_db = LanceDatabase::new(&storage_uri)
+            .await?
+            .with_s3_config(&config.project.storage.s3)?
+            .with_embedding_precision(config.project.storage.embedding_precision);
         lance_db.init().await?;
         let db: Arc<dyn Database> = Arc::new(lance_db);
 
         let embedding = embedding::create_provider(&config.project.model).await?;
+        let extra_models = embedding::create_extra_providers(&config.project.extra_models).await;
 
-        let artifact_service = Arc::new(ArtifactService::new(db.clone(), embedding.clone()));
-        let search_service = Arc::new(SearchService::new(db.clone(), embedding.clone()));
+        let project_root = std::path::Path::new(".");
+        let signer = Signer::from_config(&config.project.signing, project_root)?;
+        let verifier = Verifier::from_config(&config.project.signing, signer.as_ref())?;
+        let artifact_service = Arc::new(
+            ArtifactService::from_config(
+                db.clone(),
+                embedding.clone(),
+                &config.project,
+                project_root,
+            )?
+            .with_extra_models(extra_models.clone()),
+        );
+        let search_service = Arc::new(
+            SearchService::from_config(db.clone(), embedding.clone(), &config.project)
+                .with_extra_models(extra_models),
+        );
 
         let registered_kinds: Vec<RegisteredKind> = config
             .project
             .kinds
             .definitions
             .iter()
-            .map(|d| RegisteredKind {
-                slug: d.slug.clone(),
-                description: d.description.clone(),
-            })
+            .map(RegisteredKind::from)
             .collect();
 
-        let registered_labels: Vec<RegisteredLabel> = config
-            .project
-            .labels
-            .definitions
-            .iter()
-            .map(|d| RegisteredLabel {
-                key: d.key.clone(),
-                description: d.description.clone(),
-            })
-            .collect();
+        let registered_labels: Vec<RegisteredLabel> = config.project.labels.definitions.clone();
+
+        if let Some(template) = template {
+            if config.server.seed.unwrap_or(true) {
+                seed_empty_store(db.as_ref(), &artifact_service, template.name).await?;
+            }
+        }
+
+        let backup = &config.project.backup;
+        let backup_schedule = match backup.interval_hours {
+            Some(hours) if hours > 0 => {
+                let location = backup.resolve_location(std::path::Path::new("."));
+                let service =
+                    BackupService::new(&storage_uri, &location, &config.project.storage.s3)?
+                        .with_keep(backup.keep);
+                Some(BackupSchedule {
+                    service: Arc::new(service),
+                    interval: Duration::from_secs(hours * 3600),
+                })
+            },
+            _ => None,
+        };
 
         Ok(Self {
             db,
@@ -152,6 +318,54 @@ impl AppState {
             registered_kinds,
             registered_labels,
             server_config: config.server,
+            auto_prune: config.project.storage.auto_prune.unwrap_or(false),
+            embedding_probe: Arc::new(EmbeddingProbe::default()),
+            backup_schedule,
+            verifier,
+            mcp_config: config.project.mcp.clone(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingProvider {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl EmbeddingProvider for CountingProvider {
+        async fn embed(&self, _text: &str) -> Result<Vec<f32>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![0.0; 3])
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            Ok(texts.iter().map(|_| vec![0.0; 3]).collect())
+        }
+
+        fn model_id(&self) -> &str {
+            "counting"
+        }
+
+        fn dimensions(&self) -> usize {
+            4
+        }
+    }
+
+    #[tokio::test]
+    async fn embedding_probe_reports_mismatch_and_caches_result() {
+        let provider = CountingProvider {
+            calls: AtomicUsize::new(0),
+        };
+        let probe = EmbeddingProbe::default();
+
+        let first = probe.check(&provider).await;
+        assert_eq!(first, Err("expected 4 dimensions, got 3".to_string()));
+        assert_eq!(probe.check(&provider).await, first);
+        assert_eq!(provider.calls.load(Ordering::SeqCst), 1);
+    }
+}
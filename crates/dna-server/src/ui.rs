@@ -0,0 +1,55 @@
+//! Built-in dashboard served at `/ui`.
+//!
+//! The page, script and stylesheet are compiled into the binary, so the
+//! dashboard works on Lambda and in containers without a static file mount.
+//! The assets themselves are public; the script calls `/api/v1` with the API
+//! key entered on the page, so the usual key scopes apply to every request.
+
+use axum::{http::header, response::IntoResponse, routing::get, Router};
+
+use crate::state::AppState;
+
+const INDEX_HTML: &str = include_str!("../ui/index.html");
+const APP_JS: &str = include_str!("../ui/app.js");
+const STYLE_CSS: &str = include_str!("../ui/style.css");
+
+pub fn ui_router() -> Router<AppState> {
+    Router::new()
+        .route("/ui", get(index))
+        .route("/ui/", get(index))
+        .route("/ui/app.js", get(app_js))
+        .route("/ui/style.css", get(style_css))
+}
+
+async fn index() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+        INDEX_HTML,
+    )
+}
+
+async fn app_js() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/javascript; charset=utf-8")],
+        APP_JS,
+    )
+}
+
+async fn style_css() -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/css; charset=utf-8")],
+        STYLE_CSS,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_references_embedded_assets() {
+        assert!(INDEX_HTML.contains(r#"src="/ui/app.js""#));
+        assert!(INDEX_HTML.contains(r#"href="/ui/style.css""#));
+        assert!(APP_JS.contains("/api/v1/search"));
+    }
+}
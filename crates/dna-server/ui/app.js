@@ -0,0 +1,158 @@
+// DNA dashboard. Talks to the same /api/v1 endpoints as any other client,
+// sending the API key from the header form as a bearer token.
+
+const KEY_STORAGE = 'dna.apiKey';
+const CHANGE_LIMIT = 20;
+const CHANGE_WINDOW_DAYS = 7;
+const SEARCH_LIMIT = 20;
+
+const $ = (id) => document.getElementById(id);
+
+function apiKey() {
+  return sessionStorage.getItem(KEY_STORAGE) || '';
+}
+
+async function api(path, options = {}) {
+  const headers = { 'Content-Type': 'application/json' };
+  if (apiKey()) {
+    headers.Authorization = `Bearer ${apiKey()}`;
+  }
+  const response = await fetch(path, { ...options, headers });
+  if (response.status === 401) {
+    throw new Error('Unauthorized: enter an API key with read access');
+  }
+  const body = await response.json().catch(() => null);
+  if (!response.ok) {
+    throw new Error(body?.error?.message || `${response.status} ${response.statusText}`);
+  }
+  return body;
+}
+
+function showError(error) {
+  $('error').textContent = error ? error.message : '';
+  $('error').hidden = !error;
+}
+
+function el(tag, attrs = {}, ...children) {
+  const node = document.createElement(tag);
+  Object.assign(node, attrs);
+  node.append(...children);
+  return node;
+}
+
+function title(artifact) {
+  return artifact.name || artifact.id;
+}
+
+function labels(artifact) {
+  return Object.entries(artifact.metadata || {})
+    .sort(([a], [b]) => a.localeCompare(b))
+    .map(([k, v]) => `${k}=${v}`)
+    .join(' ');
+}
+
+function summary(content) {
+  const text = content.replace(/\s+/g, ' ').trim();
+  return text.length > 200 ? `${text.slice(0, 200)}…` : text;
+}
+
+function artifactItem(artifact, extra) {
+  const link = el('a', { onclick: () => showDetail(artifact.id) }, title(artifact));
+  const meta = el('div', { className: 'meta' }, [artifact.kind, extra, labels(artifact)].filter(Boolean).join(' · '));
+  return el('li', {}, link, meta, el('p', { className: 'summary' }, summary(artifact.content)));
+}
+
+async function loadStats() {
+  const stats = await api('/api/v1/stats');
+  const entries = [['artifacts', stats.total], ['version', stats.version]];
+  for (const [kind, count] of Object.entries(stats.kinds).sort(([, a], [, b]) => b - a)) {
+    entries.push([kind, count]);
+  }
+  $('stats-list').replaceChildren(
+    ...entries.map(([name, value]) => el('div', {}, el('dt', {}, name), el('dd', {}, String(value)))),
+  );
+}
+
+function changeItem(change) {
+  const item = artifactItem(change, `${change.change} at version ${change.version}`);
+  if (change.change === 'deleted') {
+    // Nothing left to open
+    item.firstChild.replaceWith(el('span', { className: 'deleted' }, title(change)));
+  }
+  return item;
+}
+
+async function loadChanges() {
+  // Changes come latest first, deletions included
+  const since = new Date(Date.now() - CHANGE_WINDOW_DAYS * 24 * 60 * 60 * 1000).toISOString();
+  const { changes } = await api(
+    `/api/v1/changes?after=${encodeURIComponent(since)}&limit=${CHANGE_LIMIT}`,
+  );
+  $('change-list').replaceChildren(...changes.map(changeItem));
+}
+
+async function search(event) {
+  event.preventDefault();
+  const query = $('query').value.trim();
+  if (!query) {
+    $('results').replaceChildren();
+    return;
+  }
+  const body = { query, limit: SEARCH_LIMIT };
+  const kind = $('kind').value.trim();
+  if (kind) {
+    body.kind = kind;
+  }
+  try {
+    const { results } = await api('/api/v1/search', { method: 'POST', body: JSON.stringify(body) });
+    $('results').replaceChildren(
+      ...results.map((r) => artifactItem(r.artifact, `score ${r.score.toFixed(3)}`)),
+    );
+    showError(null);
+  } catch (error) {
+    showError(error);
+  }
+}
+
+async function showDetail(id) {
+  try {
+    const artifact = await api(`/api/v1/artifacts/${encodeURIComponent(id)}`);
+    $('detail-title').textContent = title(artifact);
+    $('detail-meta').textContent = [
+      artifact.kind,
+      artifact.id,
+      artifact.format,
+      `updated ${new Date(artifact.updated_at).toLocaleString()}`,
+      labels(artifact),
+    ].filter(Boolean).join(' · ');
+    $('detail-content').textContent = artifact.content;
+    $('detail-context').textContent = artifact.context ? `Context: ${artifact.context}` : '';
+    $('detail').hidden = false;
+    $('detail').scrollIntoView({ behavior: 'smooth' });
+    showError(null);
+  } catch (error) {
+    showError(error);
+  }
+}
+
+async function refresh() {
+  try {
+    await Promise.all([loadStats(), loadChanges()]);
+    showError(null);
+  } catch (error) {
+    showError(error);
+  }
+}
+
+$('key-form').addEventListener('submit', (event) => {
+  event.preventDefault();
+  sessionStorage.setItem(KEY_STORAGE, $('key').value.trim());
+  refresh();
+});
+$('search-form').addEventListener('submit', search);
+$('detail-close').addEventListener('click', () => {
+  $('detail').hidden = true;
+});
+
+$('key').value = apiKey();
+refresh();
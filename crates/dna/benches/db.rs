@@ -0,0 +1,130 @@
+//! Latency of LanceDB operations on stores of synthetic artifacts.
+//!
+//! Run with `cargo bench -p dna --bench db`. `dna bench db` measures the same
+//! operations at any store size and writes a JSON report.
+//!
+//! Vector search on int8 stores is exhaustive, so `lance_int8` times it at
+//! each size to show how its cost grows with the store.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use dna::db::bench::SyntheticData;
+use dna::db::lance::LanceDatabase;
+use dna::db::Database;
+use dna::services::{EmbeddingPrecision, SearchFilters};
+use tempfile::TempDir;
+use tokio::runtime::Runtime;
+
+const SIZES: [usize; 2] = [1_000, 10_000];
+const DIMENSION: usize = 384;
+const BATCH_SIZE: usize = 100;
+const LIMIT: usize = 10;
+
+/// A store in a temporary directory filled with `size` artifacts, with
+/// embeddings stored at `precision`
+fn filled_store(
+    rt: &Runtime,
+    size: usize,
+    precision: EmbeddingPrecision,
+) -> (TempDir, LanceDatabase, SyntheticData) {
+    let dir = TempDir::new().unwrap();
+    let mut data = SyntheticData::new(DIMENSION, size as u64);
+    let db = rt.block_on(async {
+        let db = LanceDatabase::new(dir.path().join("bench.lance").to_str().unwrap())
+            .await
+            .unwrap()
+            .with_embedding_precision(precision);
+        db.init().await.unwrap();
+        let mut remaining = size;
+        while remaining > 0 {
+            let chunk = data.artifacts(remaining.min(1_000));
+            remaining -= chunk.len();
+            db.insert_batch(&chunk).await.unwrap();
+        }
+        db
+    });
+    (dir, db, data)
+}
+
+fn lance(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    for size in SIZES {
+        let (_dir, db, mut data) = filled_store(&rt, size, EmbeddingPrecision::F32);
+        let db = &db;
+        let mut group = c.benchmark_group(format!("lance/{}", size));
+
+        // Reads first, while the store holds exactly `size` artifacts
+        let mut n = 0;
+        group.bench_function("list_with_filters", |b| {
+            b.to_async(&rt).iter_batched(
+                || {
+                    n += 1;
+                    SyntheticData::filters(n, LIMIT)
+                },
+                |filters| async move { db.list(filters).await.unwrap() },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function("vector_search", |b| {
+            b.to_async(&rt).iter_batched(
+                || data.embedding(),
+                |query| async move {
+                    let filters = SearchFilters {
+                        limit: Some(LIMIT),
+                        ..Default::default()
+                    };
+                    db.search(&query, filters).await.unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.bench_function("insert", |b| {
+            b.to_async(&rt).iter_batched(
+                || data.artifact(),
+                |artifact| async move { db.insert(&artifact).await.unwrap() },
+                BatchSize::SmallInput,
+            )
+        });
+        group.bench_function(format!("batch_insert_{}", BATCH_SIZE), |b| {
+            b.to_async(&rt).iter_batched(
+                || data.artifacts(BATCH_SIZE),
+                |batch| async move { db.insert_batch(&batch).await.unwrap() },
+                BatchSize::SmallInput,
+            )
+        });
+
+        group.finish();
+    }
+}
+
+fn lance_int8(c: &mut Criterion) {
+    let rt = Runtime::new().unwrap();
+
+    for size in SIZES {
+        let (_dir, db, mut data) = filled_store(&rt, size, EmbeddingPrecision::Int8);
+        let db = &db;
+        let mut group = c.benchmark_group(format!("lance_int8/{}", size));
+        group.bench_function("vector_search", |b| {
+            b.to_async(&rt).iter_batched(
+                || data.embedding(),
+                |query| async move {
+                    let filters = SearchFilters {
+                        limit: Some(LIMIT),
+                        ..Default::default()
+                    };
+                    db.search(&query, filters).await.unwrap()
+                },
+                BatchSize::SmallInput,
+            )
+        });
+        group.finish();
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = lance, lance_int8
+}
+criterion_main!(benches);
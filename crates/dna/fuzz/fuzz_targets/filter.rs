@@ -0,0 +1,52 @@
+//! Filter values are interpolated into the SQL predicates Lance parses, so
+//! each must come back out of its predicate unchanged.
+
+#![no_main]
+
+use dna::db::filter;
+use dna::services::SearchFilters;
+use dna::testing::sql;
+use libfuzzer_sys::fuzz_target;
+use std::collections::HashMap;
+
+fuzz_target!(|input: (String, String, String, String)| {
+    let (kind, field, key, value) = input;
+    let filters = SearchFilters {
+        kind: Some(kind.clone()),
+        fields: HashMap::from([("owner".to_string(), field.clone())]),
+        kind_not: vec![kind.clone()],
+        metadata: HashMap::from([(key.clone(), value.clone())]),
+        metadata_not: HashMap::from([(key.clone(), value.clone())]),
+        ..Default::default()
+    };
+
+    let mut filter = filter::shared(&filters);
+    filter.extend(filter::metadata(&filters));
+    let predicates: Vec<String> = filter
+        .predicates()
+        .iter()
+        .map(|p| p.to_sql().expect("filter columns are valid"))
+        .collect();
+    let entry = "(metadata LIKE ? OR metadata LIKE ? OR metadata LIKE ? OR metadata LIKE ?)";
+    let skeletons = [
+        "kind = ?".to_string(),
+        "field_owner = ?".to_string(),
+        "kind NOT IN (?)".to_string(),
+        format!("NOT {}", entry),
+        entry.to_string(),
+    ];
+    assert_eq!(predicates.len(), skeletons.len());
+
+    for (predicate, expected) in predicates.iter().zip(skeletons) {
+        let (skeleton, literals) = sql::split_literals(predicate).expect("every literal is closed");
+        assert_eq!(skeleton, expected, "in {}", predicate);
+        if skeleton.contains("metadata") {
+            let label = sql::unescape_entry(&literals[3]);
+            assert_eq!(label, Some((key.clone(), value.clone())), "in {}", predicate);
+        } else if skeleton.starts_with("field_") {
+            assert_eq!(literals, vec![field.clone()]);
+        } else {
+            assert_eq!(literals, vec![kind.clone()]);
+        }
+    }
+});
@@ -0,0 +1,30 @@
+//! Synced files are edited by hand, so splitting and parsing their
+//! frontmatter must fail cleanly, and whatever parses must survive being
+//! written back out.
+
+#![no_main]
+
+use dna::services::frontmatter::{self, Frontmatter};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    let Some((yaml, body)) = frontmatter::split(text) else {
+        return;
+    };
+    let Ok(parsed) = Frontmatter::parse(yaml) else {
+        return;
+    };
+    let labels = parsed.labels();
+
+    let written = frontmatter::join(&parsed, body).expect("parsed frontmatter serializes");
+    let (yaml, _) = frontmatter::split(&written).expect("join writes both delimiters");
+    let reparsed = Frontmatter::parse(yaml).expect("written frontmatter parses");
+
+    assert_eq!(reparsed.id, parsed.id);
+    assert_eq!(reparsed.kind, parsed.kind);
+    assert_eq!(reparsed.name, parsed.name);
+    assert_eq!(reparsed.context, parsed.context);
+    if let Ok(labels) = labels {
+        assert_eq!(reparsed.labels().expect("labels still parse"), labels);
+    }
+});
@@ -0,0 +1,319 @@
+//! Latency benchmarks for database operations.
+//!
+//! Stores are filled with synthetic artifacts whose embeddings are
+//! pseudo-random unit vectors, so no embedding model is loaded and runs are
+//! repeatable. Each operation is timed several times and summarised as
+//! latency percentiles, to compare storage settings such as embedding
+//! precision and to catch regressions between releases.
+
+use super::Database;
+use crate::services::{Artifact, ContentFormat, EmbeddingPrecision, SearchFilters};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Kinds synthetic artifacts cycle through
+pub const SYNTHETIC_KINDS: [&str; 4] = ["intent", "contract", "constraint", "invariant"];
+
+/// Label spreading synthetic artifacts over [`SYNTHETIC_GROUPS`] values
+pub const SYNTHETIC_GROUP_LABEL: &str = "group";
+
+/// Distinct values of [`SYNTHETIC_GROUP_LABEL`]
+pub const SYNTHETIC_GROUPS: usize = 10;
+
+/// Artifacts written per insert while filling a store
+const FILL_CHUNK: usize = 1000;
+
+/// What a database benchmark measures
+#[derive(Debug, Clone, Serialize)]
+pub struct DbBenchOptions {
+    /// Store sizes to measure at, in artifacts
+    pub sizes: Vec<usize>,
+    /// Dimension of the synthetic embeddings
+    pub dimension: usize,
+    /// Times each operation is run per size
+    pub samples: usize,
+    /// Artifacts per batch insert
+    pub batch_size: usize,
+    /// Results requested from each list and search
+    pub limit: usize,
+}
+
+impl Default for DbBenchOptions {
+    fn default() -> Self {
+        Self {
+            sizes: vec![1_000, 10_000],
+            dimension: 384,
+            samples: 20,
+            batch_size: 100,
+            limit: 10,
+        }
+    }
+}
+
+/// Latency of one operation over its samples, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Latency {
+    pub samples: usize,
+    pub mean_ms: f64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+}
+
+impl Latency {
+    /// Summarise timings; all zero when there are none
+    pub fn from_durations(durations: &[Duration]) -> Self {
+        let mut ms: Vec<f64> = durations.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        ms.sort_by(f64::total_cmp);
+        let percentile = |p: f64| {
+            if ms.is_empty() {
+                return 0.0;
+            }
+            let rank = ((p * ms.len() as f64).ceil() as usize).clamp(1, ms.len());
+            ms[rank - 1]
+        };
+        Self {
+            samples: ms.len(),
+            mean_ms: if ms.is_empty() {
+                0.0
+            } else {
+                ms.iter().sum::<f64>() / ms.len() as f64
+            },
+            p50_ms: percentile(0.50),
+            p95_ms: percentile(0.95),
+            max_ms: ms.last().copied().unwrap_or(0.0),
+        }
+    }
+}
+
+/// Latencies measured on a store of one size
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeReport {
+    /// Artifacts in the store when listing and searching
+    pub size: usize,
+    /// Time to fill the store with synthetic artifacts
+    pub fill_ms: f64,
+    pub insert: Latency,
+    pub batch_insert: Latency,
+    pub list_with_filters: Latency,
+    pub vector_search: Latency,
+}
+
+/// Results of a database benchmark run
+#[derive(Debug, Clone, Serialize)]
+pub struct DbBenchReport {
+    pub options: DbBenchOptions,
+    /// How the measured stores kept their embeddings
+    pub precision: EmbeddingPrecision,
+    pub sizes: Vec<SizeReport>,
+}
+
+/// Deterministic generator of synthetic artifacts and query vectors
+pub struct SyntheticData {
+    dimension: usize,
+    state: u64,
+    next_id: usize,
+}
+
+impl SyntheticData {
+    /// Generator of `dimension`-wide embeddings; equal seeds give equal data
+    pub fn new(dimension: usize, seed: u64) -> Self {
+        Self {
+            dimension,
+            state: seed,
+            next_id: 0,
+        }
+    }
+
+    /// SplitMix64: fast, seedable and good enough to spread vectors out
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A pseudo-random unit vector
+    pub fn embedding(&mut self) -> Vec<f32> {
+        let mut vector: Vec<f32> = (0..self.dimension)
+            .map(|_| (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32 * 2.0 - 1.0)
+            .collect();
+        let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+        if norm > 0.0 {
+            vector.iter_mut().for_each(|v| *v /= norm);
+        }
+        vector
+    }
+
+    /// The next artifact, with a unique ID, a kind from [`SYNTHETIC_KINDS`]
+    /// and a [`SYNTHETIC_GROUP_LABEL`] label
+    pub fn artifact(&mut self) -> Artifact {
+        let n = self.next_id;
+        self.next_id += 1;
+        let mut artifact = Artifact::new(
+            SYNTHETIC_KINDS[n % SYNTHETIC_KINDS.len()].to_string(),
+            format!("Synthetic artifact {} for benchmarking", n),
+            ContentFormat::Markdown,
+            None,
+            HashMap::from([(
+                SYNTHETIC_GROUP_LABEL.to_string(),
+                (n % SYNTHETIC_GROUPS).to_string(),
+            )]),
+            "bench".to_string(),
+        );
+        artifact.id = format!("bench{:08}", n);
+        artifact.embedding = Some(self.embedding());
+        artifact
+    }
+
+    /// The next `count` artifacts
+    pub fn artifacts(&mut self, count: usize) -> Vec<Artifact> {
+        (0..count).map(|_| self.artifact()).collect()
+    }
+
+    /// Filters on a kind and a group label, varying with `n`
+    pub fn filters(n: usize, limit: usize) -> SearchFilters {
+        SearchFilters {
+            kind: Some(SYNTHETIC_KINDS[n % SYNTHETIC_KINDS.len()].to_string()),
+            metadata: HashMap::from([(
+                SYNTHETIC_GROUP_LABEL.to_string(),
+                (n % SYNTHETIC_GROUPS).to_string(),
+            )]),
+            limit: Some(limit),
+            ..Default::default()
+        }
+    }
+}
+
+/// Fill an empty store to `size` artifacts and time each operation on it.
+///
+/// Listing and searching run first, on exactly `size` artifacts; inserts run
+/// afterwards, so the store grows only after those are measured.
+pub async fn measure(
+    db: &dyn Database,
+    size: usize,
+    options: &DbBenchOptions,
+) -> Result<SizeReport> {
+    let mut data = SyntheticData::new(options.dimension, size as u64);
+
+    let started = Instant::now();
+    let mut remaining = size;
+    while remaining > 0 {
+        let chunk = data.artifacts(remaining.min(FILL_CHUNK));
+        remaining -= chunk.len();
+        db.insert_batch(&chunk)
+            .await
+            .context("Failed to fill the store")?;
+    }
+    let fill_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+    let mut list = Vec::with_capacity(options.samples);
+    for n in 0..options.samples {
+        let filters = SyntheticData::filters(n, options.limit);
+        let started = Instant::now();
+        db.list(filters).await.context("Failed to list artifacts")?;
+        list.push(started.elapsed());
+    }
+
+    let mut search = Vec::with_capacity(options.samples);
+    for _ in 0..options.samples {
+        let query = data.embedding();
+        let filters = SearchFilters {
+            limit: Some(options.limit),
+            ..Default::default()
+        };
+        let started = Instant::now();
+        db.search(&query, filters)
+            .await
+            .context("Failed to search artifacts")?;
+        search.push(started.elapsed());
+    }
+
+    let mut insert = Vec::with_capacity(options.samples);
+    for _ in 0..options.samples {
+        let artifact = data.artifact();
+        let started = Instant::now();
+        db.insert(&artifact)
+            .await
+            .context("Failed to insert artifact")?;
+        insert.push(started.elapsed());
+    }
+
+    let mut batch_insert = Vec::with_capacity(options.samples);
+    for _ in 0..options.samples {
+        let batch = data.artifacts(options.batch_size);
+        let started = Instant::now();
+        db.insert_batch(&batch)
+            .await
+            .context("Failed to insert artifacts")?;
+        batch_insert.push(started.elapsed());
+    }
+
+    Ok(SizeReport {
+        size,
+        fill_ms,
+        insert: Latency::from_durations(&insert),
+        batch_insert: Latency::from_durations(&batch_insert),
+        list_with_filters: Latency::from_durations(&list),
+        vector_search: Latency::from_durations(&search),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestDatabase;
+
+    #[test]
+    fn latency_percentiles_use_nearest_rank() {
+        let durations: Vec<Duration> = (1..=20).map(Duration::from_millis).collect();
+        let latency = Latency::from_durations(&durations);
+        assert_eq!(latency.samples, 20);
+        assert_eq!(latency.p50_ms, 10.0);
+        assert_eq!(latency.p95_ms, 19.0);
+        assert_eq!(latency.max_ms, 20.0);
+        assert!((latency.mean_ms - 10.5).abs() < 1e-9);
+
+        assert_eq!(Latency::from_durations(&[]).p95_ms, 0.0);
+    }
+
+    #[test]
+    fn synthetic_data_is_repeatable_and_normalised() {
+        let first = SyntheticData::new(8, 7).artifacts(3);
+        let second = SyntheticData::new(8, 7).artifacts(3);
+        assert_eq!(first[2].id, "bench00000002");
+        assert_eq!(first[2].embedding, second[2].embedding);
+        assert_ne!(first[0].embedding, first[1].embedding);
+
+        let norm: f32 = first[0]
+            .embedding
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|v| v * v)
+            .sum();
+        assert!((norm - 1.0).abs() < 1e-4);
+    }
+
+    #[tokio::test]
+    async fn measure_fills_the_store_then_grows_it_by_the_inserts() {
+        let db = TestDatabase::new();
+        let options = DbBenchOptions {
+            sizes: vec![50],
+            dimension: 4,
+            samples: 3,
+            batch_size: 5,
+            limit: 10,
+        };
+
+        let report = measure(&db, 50, &options).await.unwrap();
+
+        assert_eq!(report.size, 50);
+        assert_eq!(report.insert.samples, 3);
+        assert_eq!(report.vector_search.samples, 3);
+        assert_eq!(db.count().await.unwrap(), 50 + 3 + 3 * 5);
+    }
+}
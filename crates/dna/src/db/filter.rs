@@ -0,0 +1,528 @@
+//! SQL predicates for querying Lance tables.
+//!
+//! Lance takes filters as SQL text. Rather than formatting that text by hand,
+//! queries build a [`Filter`] from typed [`Predicate`]s, which write every
+//! value as a string literal with its quotes doubled, escape wildcards in
+//! values matched with `LIKE`, and refuse column names that are not plain
+//! identifiers.
+
+use super::is_valid_field_name;
+use super::schema::{field_column, metadata_entry};
+use crate::services::{SearchFilters, ServiceError, DEPRECATED_STATUS, STATUS_LABEL};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+/// A condition on one column, or a constant
+#[derive(Debug, Clone, PartialEq)]
+pub enum Predicate {
+    /// `column = value`
+    Eq(String, String),
+    /// `column IN (values)`; nothing matches an empty list
+    In(String, Vec<String>),
+    /// `column NOT IN (values)`; everything matches an empty list
+    NotIn(String, Vec<String>),
+    /// Timestamps from `from` (inclusive) up to `to` (exclusive)
+    Range {
+        column: String,
+        from: Option<DateTime<Utc>>,
+        to: Option<DateTime<Utc>>,
+    },
+    /// Text containing the value, matched literally
+    Contains(String, String),
+    /// Text not containing the value, matched literally
+    NotContains(String, String),
+    /// Text starting with the value, matched literally
+    StartsWith(String, String),
+    /// JSON labels with the key set to exactly the value
+    HasEntry(String, String, String),
+    /// JSON labels without that entry
+    LacksEntry(String, String, String),
+    /// `column IS NOT NULL`
+    NotNull(String),
+    /// Matches nothing
+    False,
+}
+
+impl Predicate {
+    pub fn eq(column: &str, value: impl Into<String>) -> Self {
+        Self::Eq(column.to_string(), value.into())
+    }
+
+    pub fn is_in(column: &str, values: Vec<String>) -> Self {
+        Self::In(column.to_string(), values)
+    }
+
+    pub fn not_in(column: &str, values: Vec<String>) -> Self {
+        Self::NotIn(column.to_string(), values)
+    }
+
+    pub fn range(column: &str, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> Self {
+        Self::Range {
+            column: column.to_string(),
+            from,
+            to,
+        }
+    }
+
+    pub fn contains(column: &str, value: impl Into<String>) -> Self {
+        Self::Contains(column.to_string(), value.into())
+    }
+
+    pub fn not_contains(column: &str, value: impl Into<String>) -> Self {
+        Self::NotContains(column.to_string(), value.into())
+    }
+
+    pub fn starts_with(column: &str, value: impl Into<String>) -> Self {
+        Self::StartsWith(column.to_string(), value.into())
+    }
+
+    pub fn has_entry(column: &str, key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::HasEntry(column.to_string(), key.into(), value.into())
+    }
+
+    pub fn lacks_entry(column: &str, key: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::LacksEntry(column.to_string(), key.into(), value.into())
+    }
+
+    pub fn not_null(column: &str) -> Self {
+        Self::NotNull(column.to_string())
+    }
+
+    /// The column this predicate reads, if any
+    pub fn column(&self) -> Option<&str> {
+        match self {
+            Self::Eq(column, _)
+            | Self::In(column, _)
+            | Self::NotIn(column, _)
+            | Self::Range { column, .. }
+            | Self::Contains(column, _)
+            | Self::NotContains(column, _)
+            | Self::StartsWith(column, _)
+            | Self::HasEntry(column, _, _)
+            | Self::LacksEntry(column, _, _)
+            | Self::NotNull(column) => Some(column),
+            Self::False => None,
+        }
+    }
+
+    /// The predicate as Lance SQL.
+    ///
+    /// Fails if the column name is not a plain identifier, since it is
+    /// written into the filter unquoted.
+    pub fn to_sql(&self) -> Result<String> {
+        if let Some(column) = self.column() {
+            if !is_valid_column(column) {
+                return Err(ServiceError::Validation(format!(
+                    "Invalid column name in filter: {:?}",
+                    column
+                ))
+                .into());
+            }
+        }
+
+        Ok(match self {
+            Self::Eq(column, value) => format!("{} = {}", column, string_literal(value)),
+            Self::In(_, values) if values.is_empty() => "false".to_string(),
+            Self::In(column, values) => format!("{} IN ({})", column, literal_list(values)),
+            Self::NotIn(_, values) if values.is_empty() => "true".to_string(),
+            Self::NotIn(column, values) => format!("{} NOT IN ({})", column, literal_list(values)),
+            Self::Range { column, from, to } => {
+                let mut bounds = Vec::new();
+                if let Some(from) = from {
+                    bounds.push(format!("{} >= {}", column, timestamp(from)));
+                }
+                if let Some(to) = to {
+                    bounds.push(format!("{} < {}", column, timestamp(to)));
+                }
+                if bounds.is_empty() {
+                    "true".to_string()
+                } else {
+                    bounds.join(" AND ")
+                }
+            },
+            Self::Contains(column, value) => {
+                format!("{} LIKE {}", column, contains_pattern(value))
+            },
+            Self::NotContains(column, value) => {
+                format!("{} NOT LIKE {}", column, contains_pattern(value))
+            },
+            Self::StartsWith(column, value) => format!(
+                "{} LIKE {}",
+                column,
+                string_literal(&format!("{}%", escape_like(value)))
+            ),
+            Self::HasEntry(column, key, value) => entry_condition(column, key, value),
+            Self::LacksEntry(column, key, value) => {
+                format!("NOT {}", entry_condition(column, key, value))
+            },
+            Self::NotNull(column) => format!("{} IS NOT NULL", column),
+            Self::False => "false".to_string(),
+        })
+    }
+}
+
+/// Predicates that must all hold
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Filter {
+    predicates: Vec<Predicate>,
+}
+
+impl Filter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Also require `predicate`
+    pub fn and(mut self, predicate: Predicate) -> Self {
+        self.predicates.push(predicate);
+        self
+    }
+
+    pub fn push(&mut self, predicate: Predicate) {
+        self.predicates.push(predicate);
+    }
+
+    pub fn predicates(&self) -> &[Predicate] {
+        &self.predicates
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.predicates.is_empty()
+    }
+
+    /// The predicates joined with `AND`, or `None` when there are none
+    pub fn to_sql(&self) -> Result<Option<String>> {
+        if self.predicates.is_empty() {
+            return Ok(None);
+        }
+        let parts = self
+            .predicates
+            .iter()
+            .map(Predicate::to_sql)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(parts.join(" AND ")))
+    }
+}
+
+impl Extend<Predicate> for Filter {
+    fn extend<I: IntoIterator<Item = Predicate>>(&mut self, predicates: I) {
+        self.predicates.extend(predicates);
+    }
+}
+
+impl From<Predicate> for Filter {
+    fn from(predicate: Predicate) -> Self {
+        Self::new().and(predicate)
+    }
+}
+
+/// Whether `name` can be written into a filter as a column name
+fn is_valid_column(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_lowercase() || c == '_')
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// `value` as a SQL string literal
+fn string_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Values as a comma-separated list of SQL string literals
+fn literal_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|v| string_literal(v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// `%`, `_` and `\` in `value` escaped so `LIKE` matches them literally
+fn escape_like(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A `LIKE` pattern, as a string literal, matching text that contains `needle`.
+///
+/// `%`, `_` and `\` in `needle` are escaped so they match only themselves.
+fn contains_pattern(needle: &str) -> String {
+    string_literal(&format!("%{}%", escape_like(needle)))
+}
+
+fn timestamp(at: &DateTime<Utc>) -> String {
+    format!(
+        "arrow_cast({}, 'Timestamp(Millisecond, None)')",
+        at.timestamp_millis()
+    )
+}
+
+/// Whether the JSON labels in `column` have `key` set to `value`.
+///
+/// Labels are written with commas inside keys and values escaped, so the only
+/// raw commas separate entries, and an entry bounded by a brace or comma on
+/// each side is a whole entry rather than part of a longer key or value.
+fn entry_condition(column: &str, key: &str, value: &str) -> String {
+    let entry = escape_like(&metadata_entry(key, value));
+    let patterns = [
+        format!("{{{}}}", entry),
+        format!("{{{},%", entry),
+        format!("%,{}}}", entry),
+        format!("%,{},%", entry),
+    ];
+    let conditions: Vec<String> = patterns
+        .iter()
+        .map(|pattern| format!("{} LIKE {}", column, string_literal(pattern)))
+        .collect();
+    format!("({})", conditions.join(" OR "))
+}
+
+/// Predicates shared by list and search
+pub fn shared(filters: &SearchFilters) -> Filter {
+    let mut filter = Filter::new();
+
+    if let Some(kind) = &filters.kind {
+        filter.push(Predicate::eq("kind", kind));
+    }
+
+    if let Some(namespace) = &filters.namespace {
+        filter.push(Predicate::eq("namespace", namespace));
+    }
+
+    if let Some(owner) = &filters.owner {
+        filter.push(Predicate::eq("owner", owner));
+    }
+
+    if let Some(reviewer) = &filters.reviewer {
+        // Reviewers are a JSON array, so match the quoted handle
+        let needle = serde_json::to_string(reviewer).unwrap_or_default();
+        filter.push(Predicate::contains("reviewers", needle));
+    }
+
+    if filters.after.is_some() || filters.before.is_some() {
+        filter.push(Predicate::range(
+            "updated_at",
+            filters.after,
+            filters.before,
+        ));
+    }
+
+    let mut fields: Vec<_> = filters.fields.iter().collect();
+    fields.sort();
+    for (name, value) in fields {
+        if is_valid_field_name(name) {
+            filter.push(Predicate::Eq(field_column(name), value.clone()));
+        } else {
+            // No column can hold it, so nothing matches
+            filter.push(Predicate::False);
+        }
+    }
+
+    if !filters.kind_not.is_empty() {
+        filter.push(Predicate::not_in("kind", filters.kind_not.clone()));
+    }
+
+    if let Some(kinds) = &filters.kinds {
+        filter.push(Predicate::is_in("kind", kinds.clone()));
+    }
+
+    if !filters.exclude_ids.is_empty() {
+        filter.push(Predicate::not_in("id", filters.exclude_ids.clone()));
+    }
+
+    let mut metadata_not: Vec<_> = filters.metadata_not.iter().collect();
+    metadata_not.sort();
+    for (key, value) in metadata_not {
+        filter.push(Predicate::lacks_entry("metadata", key, value));
+    }
+
+    if filters.excludes_deprecated() {
+        filter.push(Predicate::lacks_entry(
+            "metadata",
+            STATUS_LABEL,
+            DEPRECATED_STATUS,
+        ));
+    }
+
+    filter
+}
+
+/// Predicates requiring the labels in `filters.metadata`
+pub fn metadata(filters: &SearchFilters) -> Vec<Predicate> {
+    let mut metadata: Vec<_> = filters.metadata.iter().collect();
+    metadata.sort();
+    metadata
+        .into_iter()
+        .map(|(key, value)| Predicate::has_entry("metadata", key, value))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{sql, strategies};
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    fn rendered(filter: &Filter) -> Vec<String> {
+        filter
+            .predicates()
+            .iter()
+            .map(|p| p.to_sql().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn string_literal_doubles_quotes() {
+        assert_eq!(string_literal("it's"), "'it''s'");
+        assert_eq!(string_literal(""), "''");
+    }
+
+    #[test]
+    fn contains_pattern_escapes_like_wildcards() {
+        assert_eq!(contains_pattern(r"50%_off\"), r"'%50\%\_off\\%'");
+    }
+
+    #[test]
+    fn starts_with_escapes_like_wildcards() {
+        let sql = Predicate::starts_with("id", "a_b'").to_sql().unwrap();
+        assert_eq!(sql, r"id LIKE 'a\_b''%'");
+    }
+
+    #[test]
+    fn labels_match_whole_entries() {
+        let filters = SearchFilters {
+            metadata: HashMap::from([("team".to_string(), "it's, 50%".to_string())]),
+            metadata_not: HashMap::from([("status".to_string(), "old".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(
+            rendered(&shared(&filters)),
+            vec![concat!(
+                r#"NOT (metadata LIKE '{"status":"old"}' OR metadata LIKE '{"status":"old",%' "#,
+                r#"OR metadata LIKE '%,"status":"old"}' OR metadata LIKE '%,"status":"old",%')"#
+            )]
+        );
+        let entry = r#""team":"it''s\\u002c 50\%""#;
+        assert_eq!(
+            metadata(&filters)[0].to_sql().unwrap(),
+            format!(
+                "(metadata LIKE '{{{e}}}' OR metadata LIKE '{{{e},%' \
+                 OR metadata LIKE '%,{e}}}' OR metadata LIKE '%,{e},%')",
+                e = entry
+            )
+        );
+    }
+
+    #[test]
+    fn invalid_field_names_match_nothing() {
+        let filters = SearchFilters {
+            fields: HashMap::from([("x = x OR 1".to_string(), "y".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(rendered(&shared(&filters)), vec!["false".to_string()]);
+    }
+
+    #[test]
+    fn invalid_column_names_are_rejected() {
+        for column in ["", "id OR 1=1", "Kind", "1id", "id;"] {
+            assert!(Predicate::eq(column, "x").to_sql().is_err(), "{:?}", column);
+        }
+        assert!(Filter::new()
+            .and(Predicate::eq("id", "x"))
+            .and(Predicate::not_null("bad column"))
+            .to_sql()
+            .is_err());
+    }
+
+    #[test]
+    fn empty_lists_and_ranges_render_constants() {
+        assert_eq!(Predicate::is_in("kind", vec![]).to_sql().unwrap(), "false");
+        assert_eq!(Predicate::not_in("kind", vec![]).to_sql().unwrap(), "true");
+        assert_eq!(
+            Predicate::range("updated_at", None, None).to_sql().unwrap(),
+            "true"
+        );
+        assert_eq!(Filter::new().to_sql().unwrap(), None);
+    }
+
+    #[test]
+    fn filters_join_predicates_with_and() {
+        let filter = Filter::new()
+            .and(Predicate::eq("kind", "intent"))
+            .and(Predicate::is_in(
+                "id",
+                vec!["a".to_string(), "b".to_string()],
+            ));
+        assert_eq!(
+            filter.to_sql().unwrap().as_deref(),
+            Some("kind = 'intent' AND id IN ('a', 'b')")
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn literals_decode_to_the_filter_values(filters in strategies::search_filters()) {
+            let mut expected = Vec::new();
+            expected.extend(filters.kind.clone());
+            expected.extend(filters.namespace.clone());
+            expected.extend(filters.owner.clone());
+            expected.extend(filters.reviewer.iter().map(|r| serde_json::to_string(r).unwrap()));
+            let mut fields: Vec<_> = filters.fields.iter().collect();
+            fields.sort();
+            expected.extend(
+                fields
+                    .into_iter()
+                    .filter(|(name, _)| is_valid_field_name(name))
+                    .map(|(_, value)| value.clone()),
+            );
+            expected.extend(filters.kind_not.clone());
+            expected.extend(filters.kinds.clone().unwrap_or_default());
+            expected.extend(filters.exclude_ids.clone());
+            let mut metadata_not: Vec<_> = filters.metadata_not.iter().collect();
+            metadata_not.sort();
+            for (key, value) in metadata_not {
+                expected.extend([key.clone(), value.clone()]);
+            }
+            if filters.excludes_deprecated() {
+                expected.extend([STATUS_LABEL.to_string(), DEPRECATED_STATUS.to_string()]);
+            }
+
+            let mut literals = Vec::new();
+            for predicate in rendered(&shared(&filters)) {
+                let (skeleton, values) = sql::split_literals(&predicate)
+                    .expect("every literal is closed");
+                prop_assert!(!skeleton.contains('\''));
+                if skeleton.starts_with("updated_at") {
+                    continue;
+                }
+                if skeleton.starts_with("NOT (metadata") {
+                    let (key, value) = sql::unescape_entry(&values[3])
+                        .expect("labels are matched as whole entries");
+                    literals.extend([key, value]);
+                    continue;
+                }
+                if skeleton.starts_with("reviewers") {
+                    literals.extend(values.iter().filter_map(|v| sql::unescape_contains(v)));
+                    continue;
+                }
+                literals.extend(values);
+            }
+            prop_assert_eq!(literals, expected);
+        }
+
+        #[test]
+        fn like_patterns_decode_to_the_needle(needle in strategies::adversarial_text()) {
+            let predicate = Predicate::contains("aliases", needle.clone()).to_sql().unwrap();
+            let (skeleton, values) = sql::split_literals(&predicate).unwrap();
+            prop_assert_eq!(skeleton, "aliases LIKE ?");
+            prop_assert_eq!(sql::unescape_contains(&values[0]), Some(needle));
+        }
+    }
+}
@@ -0,0 +1,574 @@
+//! Integrity checks for the Lance store, run by `dna fsck`.
+//!
+//! Object stores can leave a table half-written when an upload is cut off,
+//! which shows up as versions that no longer open, rows that no longer
+//! decode, or rows that no longer match the checksum stored with them.
+
+use super::filter::Predicate;
+use super::lance::{LanceDatabase, ARCHIVE_TABLE_NAME, TABLE_NAME};
+use super::schema;
+use anyhow::{Context, Result};
+use arrow_array::{
+    cast::AsArray, Array, ArrayRef, RecordBatch, RecordBatchIterator, StringArray,
+    TimestampMillisecondArray, UInt64Array,
+};
+use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use chrono::Utc;
+use futures::TryStreamExt;
+use lancedb::query::ExecutableQuery;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Table recording the rows that `fsck` quarantined
+pub const QUARANTINE_TABLE_NAME: &str = "quarantine";
+
+/// Kind of damage found by [`LanceDatabase::fsck`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FsckProblem {
+    /// A table, or one of its versions, cannot be opened
+    Manifest,
+    /// A column has the wrong type or embedding dimension
+    Schema,
+    /// A row cannot be read back as an artifact
+    Unreadable,
+    /// A row no longer matches its stored checksum
+    Checksum,
+    /// An embedding has the wrong length or non-finite values
+    Embedding,
+}
+
+impl std::fmt::Display for FsckProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FsckProblem::Manifest => write!(f, "manifest"),
+            FsckProblem::Schema => write!(f, "schema"),
+            FsckProblem::Unreadable => write!(f, "unreadable"),
+            FsckProblem::Checksum => write!(f, "checksum"),
+            FsckProblem::Embedding => write!(f, "embedding"),
+        }
+    }
+}
+
+/// One problem found by [`LanceDatabase::fsck`]
+#[derive(Debug, Clone, Serialize)]
+pub struct FsckIssue {
+    pub table: String,
+    /// Artifact ID, for problems with a single row
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Table version, for problems with a single version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    pub problem: FsckProblem,
+    pub detail: String,
+    /// Whether the row was moved out of its table
+    pub quarantined: bool,
+}
+
+impl FsckIssue {
+    fn new(table: &str, problem: FsckProblem, detail: impl Into<String>) -> Self {
+        Self {
+            table: table.to_string(),
+            id: None,
+            version: None,
+            problem,
+            detail: detail.into(),
+            quarantined: false,
+        }
+    }
+}
+
+/// Outcome of [`LanceDatabase::fsck`]
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FsckReport {
+    /// Artifact tables checked
+    pub tables: usize,
+    /// Table versions whose manifests were opened
+    pub versions: usize,
+    /// Rows read
+    pub rows: usize,
+    /// Rows written before checksums were stored, which cannot be verified
+    pub unchecksummed: usize,
+    pub issues: Vec<FsckIssue>,
+}
+
+impl FsckReport {
+    /// Whether nothing is wrong with the store
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of problems still in the store, i.e. not quarantined
+    pub fn unresolved(&self) -> usize {
+        self.issues.iter().filter(|i| !i.quarantined).count()
+    }
+}
+
+impl LanceDatabase {
+    /// Check the artifact tables for damage.
+    ///
+    /// Every version's manifest must open, columns must have the types and
+    /// embedding dimension of the current layout, and every row must decode,
+    /// carry finite embeddings of that dimension and match its checksum.
+    /// The store is read as it is, without migrating it first.
+    ///
+    /// With `quarantine`, damaged rows are recorded in the quarantine table,
+    /// with the version they were removed from, and deleted. They can still
+    /// be read at that version until old versions are pruned.
+    pub async fn fsck(&self, quarantine: bool) -> Result<FsckReport> {
+        let _lock = match quarantine {
+            true => self.lock_for_write().await?,
+            false => None,
+        };
+        let db = self.connect().await?;
+        let table_names = db
+            .table_names()
+            .execute()
+            .await
+            .context("Failed to list tables")?;
+        let mut report = FsckReport::default();
+
+        for name in [TABLE_NAME, ARCHIVE_TABLE_NAME] {
+            if !table_names.iter().any(|n| n == name) {
+                continue;
+            }
+            report.tables += 1;
+
+            let table = match db.open_table(name).execute().await {
+                Ok(table) => table,
+                Err(e) => {
+                    report.issues.push(FsckIssue::new(
+                        name,
+                        FsckProblem::Manifest,
+                        format!("cannot open table: {}", e),
+                    ));
+                    continue;
+                },
+            };
+
+            check_versions(&table, name, &mut report).await?;
+
+            let table_schema = match table.schema().await {
+                Ok(table_schema) => table_schema,
+                Err(e) => {
+                    report.issues.push(FsckIssue::new(
+                        name,
+                        FsckProblem::Manifest,
+                        format!("cannot read schema: {}", e),
+                    ));
+                    continue;
+                },
+            };
+            let schema_issues = check_schema(&table_schema, name);
+            if !schema_issues.is_empty() {
+                // Rows can't be decoded safely against the wrong column types
+                report.issues.extend(schema_issues);
+                continue;
+            }
+
+            let damaged = check_rows(&table, name, &mut report).await;
+            if quarantine && !damaged.is_empty() {
+                quarantine_rows(&db, &table, name, &damaged).await?;
+                for issue in report.issues.iter_mut() {
+                    if issue.table == name
+                        && issue.id.as_ref().is_some_and(|id| damaged.contains(id))
+                    {
+                        issue.quarantined = true;
+                    }
+                }
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Open every version of a table, recording those that fail
+async fn check_versions(
+    table: &lancedb::table::Table,
+    name: &str,
+    report: &mut FsckReport,
+) -> Result<()> {
+    let versions = match table.list_versions().await {
+        Ok(versions) => versions,
+        Err(e) => {
+            report.issues.push(FsckIssue::new(
+                name,
+                FsckProblem::Manifest,
+                format!("cannot list versions: {}", e),
+            ));
+            return Ok(());
+        },
+    };
+
+    for version in versions {
+        report.versions += 1;
+        let opened = match table.checkout(version.version).await {
+            Ok(()) => table.count_rows(None).await.map(|_| ()),
+            Err(e) => Err(e),
+        };
+        if let Err(e) = opened {
+            report.issues.push(FsckIssue {
+                version: Some(version.version),
+                ..FsckIssue::new(name, FsckProblem::Manifest, e.to_string())
+            });
+        }
+    }
+
+    table
+        .checkout_latest()
+        .await
+        .context("Failed to return to the latest version")?;
+    Ok(())
+}
+
+/// Compare a table's columns with the current layout.
+///
+/// Columns added by later schema versions are nullable, so a nullable column
+/// that is missing only means the store is awaiting migration.
+fn check_schema(table_schema: &Schema, name: &str) -> Vec<FsckIssue> {
+    let expected = schema::create_schema_for(
+        &schema::field_names(table_schema),
+        schema::embedding_precision(table_schema),
+    );
+
+    let mut issues = Vec::new();
+    for field in expected.fields() {
+        let Ok(actual) = table_schema.field_with_name(field.name()) else {
+            if !field.is_nullable() {
+                issues.push(FsckIssue::new(
+                    name,
+                    FsckProblem::Schema,
+                    format!("missing {} column", field.name()),
+                ));
+            }
+            continue;
+        };
+        let detail = match (actual.data_type(), field.data_type()) {
+            (DataType::FixedSizeList(item, size), DataType::FixedSizeList(expected_item, _))
+                if item.data_type() == expected_item.data_type() =>
+            {
+                (*size != schema::EMBEDDING_DIMENSION).then(|| {
+                    format!(
+                        "{} has dimension {}, expected {}",
+                        field.name(),
+                        size,
+                        schema::EMBEDDING_DIMENSION
+                    )
+                })
+            },
+            (actual_type, expected_type) => (actual_type != expected_type).then(|| {
+                format!(
+                    "{} column is {}, expected {}",
+                    field.name(),
+                    actual_type,
+                    expected_type
+                )
+            }),
+        };
+        if let Some(detail) = detail {
+            issues.push(FsckIssue::new(name, FsckProblem::Schema, detail));
+        }
+    }
+    issues
+}
+
+/// Read every row of a table, recording damaged ones.
+///
+/// Returns the IDs of the damaged rows.
+async fn check_rows(
+    table: &lancedb::table::Table,
+    name: &str,
+    report: &mut FsckReport,
+) -> Vec<String> {
+    let mut damaged = Vec::new();
+    let mut stream = match table.query().execute().await {
+        Ok(stream) => stream,
+        Err(e) => {
+            report.issues.push(FsckIssue::new(
+                name,
+                FsckProblem::Unreadable,
+                format!("cannot read rows: {}", e),
+            ));
+            return damaged;
+        },
+    };
+
+    loop {
+        let batch = match stream.try_next().await {
+            Ok(Some(batch)) => batch,
+            Ok(None) => break,
+            Err(e) => {
+                report.issues.push(FsckIssue::new(
+                    name,
+                    FsckProblem::Unreadable,
+                    format!("cannot read rows: {}", e),
+                ));
+                break;
+            },
+        };
+
+        let fields = schema::field_names(&batch.schema());
+        let ids = batch.column_by_name("id").map(|c| c.as_string::<i32>());
+        let checksums = batch
+            .column_by_name(schema::CHECKSUM_COLUMN)
+            .map(|c| c.as_string::<i32>());
+
+        for row in 0..batch.num_rows() {
+            report.rows += 1;
+            let stored = checksums.filter(|c| !c.is_null(row)).map(|c| c.value(row));
+            if stored.is_none() {
+                report.unchecksummed += 1;
+            }
+
+            let Some((problem, detail)) = check_row(&batch.slice(row, 1), &fields, stored) else {
+                continue;
+            };
+            let id = ids.map(|c| c.value(row).to_string());
+            if let Some(id) = &id {
+                damaged.push(id.clone());
+            }
+            report.issues.push(FsckIssue {
+                id,
+                ..FsckIssue::new(name, problem, detail)
+            });
+        }
+    }
+
+    damaged
+}
+
+/// Check a single-row batch, returning what is wrong with it
+fn check_row(
+    batch: &RecordBatch,
+    fields: &[String],
+    stored_checksum: Option<&str>,
+) -> Option<(FsckProblem, String)> {
+    let artifact = match LanceDatabase::batch_to_artifacts(batch) {
+        Ok(mut artifacts) => artifacts.pop()?,
+        Err(e) => return Some((FsckProblem::Unreadable, e.to_string())),
+    };
+
+    if batch
+        .column_by_name("embedding")
+        .is_some_and(|c| c.is_null(0))
+    {
+        return Some((FsckProblem::Embedding, "embedding is null".to_string()));
+    }
+    let embeddings = [
+        ("embedding", artifact.embedding.as_deref()),
+        ("context_embedding", artifact.context_embedding.as_deref()),
+        (
+            schema::ATTACHMENT_EMBEDDING_COLUMN,
+            artifact.attachment_embedding.as_deref(),
+        ),
+    ];
+    for (column, vector) in embeddings {
+        let Some(vector) = vector else {
+            continue;
+        };
+        if vector.len() != schema::EMBEDDING_DIMENSION as usize {
+            return Some((
+                FsckProblem::Embedding,
+                format!(
+                    "{} has {} values, expected {}",
+                    column,
+                    vector.len(),
+                    schema::EMBEDDING_DIMENSION
+                ),
+            ));
+        }
+        if !vector.iter().all(|v| v.is_finite()) {
+            return Some((
+                FsckProblem::Embedding,
+                format!("{} has non-finite values", column),
+            ));
+        }
+    }
+
+    match stored_checksum {
+        Some(stored) if stored != schema::row_checksum(&artifact, fields) => Some((
+            FsckProblem::Checksum,
+            "row does not match its stored checksum".to_string(),
+        )),
+        _ => None,
+    }
+}
+
+/// Record damaged rows in the quarantine table, then delete them from `table`
+async fn quarantine_rows(
+    db: &lancedb::Connection,
+    table: &lancedb::table::Table,
+    name: &str,
+    ids: &[String],
+) -> Result<()> {
+    let version = table
+        .version()
+        .await
+        .context("Failed to get table version")?;
+    let quarantine_schema = Arc::new(Schema::new(vec![
+        Field::new("id", DataType::Utf8, false),
+        Field::new("source_table", DataType::Utf8, false),
+        Field::new("version", DataType::UInt64, false),
+        Field::new(
+            "quarantined_at",
+            DataType::Timestamp(TimeUnit::Millisecond, None),
+            false,
+        ),
+    ]));
+    let now = Utc::now().timestamp_millis();
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from(ids.to_vec())),
+        Arc::new(StringArray::from(vec![name; ids.len()])),
+        Arc::new(UInt64Array::from(vec![version; ids.len()])),
+        Arc::new(TimestampMillisecondArray::from(vec![now; ids.len()])),
+    ];
+    let batch = RecordBatch::try_new(quarantine_schema.clone(), columns)
+        .context("Failed to build quarantine batch")?;
+    let rows = RecordBatchIterator::new(vec![Ok(batch)], quarantine_schema.clone());
+
+    // Record first so a failure part-way never loses track of a row
+    let table_names = db.table_names().execute().await?;
+    if table_names.iter().any(|n| n == QUARANTINE_TABLE_NAME) {
+        db.open_table(QUARANTINE_TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open quarantine table")?
+            .add(rows)
+            .execute()
+            .await
+            .context("Failed to record quarantined rows")?;
+    } else {
+        db.create_table(QUARANTINE_TABLE_NAME, rows)
+            .execute()
+            .await
+            .context("Failed to create quarantine table")?;
+    }
+
+    table
+        .delete(&Predicate::is_in("id", ids.to_vec()).to_sql()?)
+        .await
+        .context("Failed to delete quarantined rows")?;
+
+    tracing::info!(
+        "Quarantined {} rows from '{}' at version {}",
+        ids.len(),
+        name,
+        version
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::services::{Artifact, ContentFormat, SearchFilters};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn artifact(content: &str) -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "test-model".to_string(),
+        );
+        artifact.embedding = Some(vec![0.1; 384]);
+        artifact
+    }
+
+    #[tokio::test]
+    async fn finds_and_quarantines_rows_that_fail_their_checksum() {
+        let temp_dir = TempDir::new().unwrap();
+        let uri = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(uri.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let good = artifact("intact");
+        db.insert(&good).await.unwrap();
+
+        // Write a row whose content changed after its checksum was taken
+        let damaged = artifact("original");
+        let mut batch = schema::artifacts_to_batch(std::slice::from_ref(&damaged)).unwrap();
+        let content = batch.schema().index_of("content").unwrap();
+        let mut columns = batch.columns().to_vec();
+        columns[content] = Arc::new(StringArray::from(vec!["tampered"]));
+        batch = RecordBatch::try_new(batch.schema(), columns).unwrap();
+        let conn = db.connect().await.unwrap();
+        conn.open_table(TABLE_NAME)
+            .execute()
+            .await
+            .unwrap()
+            .add(RecordBatchIterator::new(
+                vec![Ok(batch.clone())],
+                batch.schema(),
+            ))
+            .execute()
+            .await
+            .unwrap();
+
+        let report = db.fsck(false).await.unwrap();
+        assert_eq!(report.tables, 1);
+        assert_eq!(report.rows, 2);
+        assert_eq!(report.unchecksummed, 0);
+        assert!(report.versions >= 2);
+        assert_eq!(report.issues.len(), 1, "{:?}", report.issues);
+        assert_eq!(report.issues[0].problem, FsckProblem::Checksum);
+        assert_eq!(report.issues[0].id.as_deref(), Some(damaged.id.as_str()));
+        assert_eq!(report.unresolved(), 1);
+
+        let report = db.fsck(true).await.unwrap();
+        assert!(report.issues[0].quarantined);
+        assert_eq!(report.unresolved(), 0);
+
+        assert!(db.fsck(false).await.unwrap().is_clean());
+        let remaining = db.list(SearchFilters::default()).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, good.id);
+        let quarantined = conn
+            .open_table(QUARANTINE_TABLE_NAME)
+            .execute()
+            .await
+            .unwrap()
+            .count_rows(None)
+            .await
+            .unwrap();
+        assert_eq!(quarantined, 1);
+    }
+
+    #[test]
+    fn reports_embedding_columns_of_the_wrong_dimension() {
+        let current = schema::create_schema();
+        let narrow = Schema::new(
+            current
+                .fields()
+                .iter()
+                .map(|f| match f.name().as_str() {
+                    "embedding" => Field::new(
+                        "embedding",
+                        DataType::FixedSizeList(
+                            Arc::new(Field::new("item", DataType::Float32, true)),
+                            768,
+                        ),
+                        false,
+                    ),
+                    _ => f.as_ref().clone(),
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        assert!(check_schema(&current, TABLE_NAME).is_empty());
+        let issues = check_schema(&narrow, TABLE_NAME);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].problem, FsckProblem::Schema);
+        assert!(
+            issues[0].detail.contains("dimension 768"),
+            "{}",
+            issues[0].detail
+        );
+    }
+}
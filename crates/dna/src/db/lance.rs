@@ -1,66 +1,160 @@
-use super::{schema, CleanupStats, CompactStats, Database, VersionInfo};
-use crate::services::{Artifact, ContentFormat, SearchFilters, SearchResult};
+use super::{
+    filter::{self, Predicate},
+    lock::{self, WriterLock},
+    match_prefix, migrations, schema, CleanupStats, CompactStats, Database, StorageCorruption,
+    VersionInfo,
+};
+use crate::services::{
+    is_s3_uri, Artifact, ContentFormat, EmbeddingPrecision, S3Config, SearchFilters, SearchResult,
+};
 use anyhow::{Context, Result};
+use arrow_array::types::Float32Type;
 use arrow_array::{
     cast::AsArray, Array, FixedSizeListArray, Float32Array, RecordBatch, RecordBatchIterator,
     TimestampMillisecondArray,
 };
-use chrono::{TimeZone, Utc};
+use arrow_schema::{DataType, Field, Schema};
+use chrono::{DateTime, TimeZone, Utc};
 use futures::TryStreamExt;
-use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use lancedb::table::NewColumnTransform;
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 use tokio::sync::RwLock;
 
-const TABLE_NAME: &str = "artifacts";
+pub(super) const TABLE_NAME: &str = "artifacts";
+pub(super) const ARCHIVE_TABLE_NAME: &str = "archived";
+
+/// Filter matching the row with `id`
+fn id_filter(id: &str) -> Result<String> {
+    Predicate::eq("id", id).to_sql()
+}
 
-/// LanceDB implementation supporting local paths and S3 URIs
+/// LanceDB implementation supporting local paths and S3 URIs.
+///
+/// Writes to a local store hold a [`WriterLock`] so the CLI and a running
+/// server can share it; reads never wait and always see the latest version.
 pub struct LanceDatabase {
     uri: String,
+    lock_path: Option<PathBuf>,
+    storage_options: Vec<(String, String)>,
+    embedding_precision: EmbeddingPrecision,
     connection: RwLock<Option<lancedb::Connection>>,
 }
 
 impl LanceDatabase {
     /// Create a new LanceDB instance from a URI (local path or s3://...)
     pub async fn new(uri: &str) -> Result<Self> {
-        if !uri.starts_with("s3://") {
+        let mut lock_path = None;
+        if !is_s3_uri(uri) {
             let path = Path::new(uri);
             if let Some(parent) = path.parent() {
                 tokio::fs::create_dir_all(parent)
                     .await
                     .context("Failed to create database directory")?;
             }
+            lock_path = Some(WriterLock::path_for(uri));
         }
 
         Ok(Self {
             uri: uri.to_string(),
+            lock_path,
+            storage_options: Vec::new(),
+            embedding_precision: EmbeddingPrecision::default(),
             connection: RwLock::new(None),
         })
     }
 
+    /// Apply S3 connection options (region, endpoint, profile, commit store).
+    ///
+    /// Fails if options that only make sense for S3 are set on a local URI,
+    /// or if a profile is configured for an S3 store but `AWS_PROFILE` isn't
+    /// set: the AWS credential chain only reads the profile from the
+    /// environment, which can't safely be changed once the process runs.
+    pub fn with_s3_config(mut self, s3: &S3Config) -> Result<Self> {
+        self.uri = s3.connect_uri(&self.uri)?;
+        self.storage_options = s3.storage_options();
+        if let Some(profile) = &s3.profile {
+            if is_s3_uri(&self.uri) && std::env::var_os("AWS_PROFILE").is_none() {
+                anyhow::bail!(
+                    "[storage.s3] profile '{}' is only read from the environment; \
+                     start with AWS_PROFILE={} set",
+                    profile,
+                    profile
+                );
+            }
+        }
+        Ok(self)
+    }
+
+    /// Store embeddings at `precision`.
+    ///
+    /// New tables are created with it, and existing tables stored at another
+    /// precision are converted the next time the store is opened.
+    pub fn with_embedding_precision(mut self, precision: EmbeddingPrecision) -> Self {
+        self.embedding_precision = precision;
+        self
+    }
+
+    /// URI the database connects to, after S3 options are applied
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Connect and list the tables in the store, to check it is reachable
+    pub async fn table_names(&self) -> Result<Vec<String>> {
+        let db = self.connect().await?;
+        db.table_names()
+            .execute()
+            .await
+            .context("Failed to list tables")
+    }
+
+    /// Open a fresh connection with the configured storage options
+    pub(super) async fn connect(&self) -> Result<lancedb::Connection> {
+        lancedb::connect(&self.uri)
+            .storage_options(self.storage_options.clone())
+            // Pick up writes made by other processes on every read
+            .read_consistency_interval(std::time::Duration::ZERO)
+            .execute()
+            .await
+            .with_context(|| format!("Failed to connect to LanceDB at {}", self.uri))
+    }
+
+    /// Take the writer lock of a local store, waiting for other writers.
+    ///
+    /// Returns None for S3 stores, whose commits are coordinated by the
+    /// commit store instead.
+    pub(super) async fn lock_for_write(&self) -> Result<Option<WriterLock>> {
+        match &self.lock_path {
+            Some(path) => Ok(Some(WriterLock::acquire(path, lock::WAIT_FOR).await?)),
+            None => Ok(None),
+        }
+    }
+
     /// Initialize the database
     pub async fn init(&self) -> Result<()> {
-        if !self.uri.starts_with("s3://") {
+        if !is_s3_uri(&self.uri) {
             tokio::fs::create_dir_all(&self.uri)
                 .await
                 .context("Failed to create database directory")?;
         }
+        let _lock = self.lock_for_write().await?;
 
-        let db = lancedb::connect(&self.uri)
-            .execute()
-            .await
-            .context("Failed to connect to LanceDB")?;
+        let db = self.connect().await?;
 
         // Create table if it doesn't exist
         let table_names = db.table_names().execute().await?;
         if !table_names.contains(&TABLE_NAME.to_string()) {
-            let schema = schema::create_schema();
+            let schema = schema::create_schema_for(&[], self.embedding_precision);
             db.create_empty_table(TABLE_NAME, schema)
                 .execute()
                 .await
                 .context("Failed to create artifacts table")?;
         }
+        migrations::migrate(&db, self.embedding_precision).await?;
 
         *self.connection.write().await = Some(db);
         Ok(())
@@ -74,52 +168,477 @@ impl LanceDatabase {
         }
         drop(conn);
 
-        let db = lancedb::connect(&self.uri)
-            .execute()
-            .await
-            .context("Failed to connect to LanceDB")?;
+        let db = self.connect().await?;
+        if !migrations::is_current(&db, self.embedding_precision).await? {
+            // Migrating rewrites the tables, so it is a write like any other
+            let _lock = self.lock_for_write().await?;
+            migrations::migrate(&db, self.embedding_precision).await?;
+        }
 
         *self.connection.write().await = Some(db.clone());
         Ok(db)
     }
 
-    /// Convert a single artifact to RecordBatch
-    fn artifact_to_batch(artifact: &Artifact) -> Result<RecordBatch> {
-        schema::artifacts_to_batch(std::slice::from_ref(artifact))
+    /// Open the archive table, creating it first if `create` is set.
+    ///
+    /// Returns None when the table doesn't exist and `create` is false.
+    async fn open_archive_table(&self, create: bool) -> Result<Option<lancedb::table::Table>> {
+        let db = self.get_connection().await?;
+        let table_names = db.table_names().execute().await?;
+
+        if table_names.iter().any(|name| name == ARCHIVE_TABLE_NAME) {
+            let table = db
+                .open_table(ARCHIVE_TABLE_NAME)
+                .execute()
+                .await
+                .context("Failed to open archived table")?;
+            return Ok(Some(table));
+        }
+
+        if !create {
+            return Ok(None);
+        }
+
+        let table = db
+            .create_empty_table(
+                ARCHIVE_TABLE_NAME,
+                schema::create_schema_for(&[], self.embedding_precision),
+            )
+            .execute()
+            .await
+            .context("Failed to create archived table")?;
+        Ok(Some(table))
+    }
+
+    /// Whether the table has a column for every structured field being filtered on.
+    ///
+    /// Columns only exist once some artifact has stored the field, so a missing
+    /// column means nothing can match.
+    async fn has_field_columns(
+        table: &lancedb::table::Table,
+        filters: &SearchFilters,
+    ) -> Result<bool> {
+        if filters.fields.is_empty() {
+            return Ok(true);
+        }
+        let stored = schema::field_names(&*table.schema().await?);
+        Ok(filters
+            .fields
+            .keys()
+            .all(|name| schema::is_valid_field_name(name) && stored.contains(name)))
+    }
+
+    /// Delete the row with `id` from the artifacts table, without locking
+    async fn delete_row(&self, id: &str) -> Result<()> {
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        table
+            .delete(&id_filter(id)?)
+            .await
+            .context("Failed to delete artifact")?;
+        Ok(())
+    }
+
+    /// Give the artifacts of kind `from` in a single table the kind `to`
+    async fn rename_kind_in(table: &lancedb::table::Table, from: &str, to: &str) -> Result<usize> {
+        let filter = Predicate::eq("kind", from).to_sql()?;
+        let mut stream = table.query().only_if(filter.clone()).execute().await?;
+        let mut artifacts = Vec::new();
+        while let Some(batch) = stream.try_next().await? {
+            artifacts.extend(Self::batch_to_artifacts(&batch)?);
+        }
+        if artifacts.is_empty() {
+            return Ok(0);
+        }
+        for artifact in &mut artifacts {
+            artifact.kind = to.to_string();
+        }
+
+        // Copy first so a failure part-way leaves duplicates rather than
+        // losing artifacts; the delete only matches the old kind
+        let batch = Self::artifacts_to_batch(table, &artifacts).await?;
+        let schema = batch.schema();
+        table
+            .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+            .execute()
+            .await
+            .context("Failed to insert renamed artifacts")?;
+        table
+            .delete(&filter)
+            .await
+            .context("Failed to delete artifacts under the old kind")?;
+
+        Ok(artifacts.len())
+    }
+
+    /// List artifacts from a single table
+    async fn list_table(
+        table: &lancedb::table::Table,
+        filters: &SearchFilters,
+    ) -> Result<Vec<Artifact>> {
+        if !Self::has_field_columns(table, filters).await? {
+            return Ok(Vec::new());
+        }
+
+        let mut query = table.query();
+
+        let mut predicates = filter::shared(filters);
+        predicates.extend(filter::metadata(filters));
+
+        if let Some(filter) = predicates.to_sql()? {
+            query = query.only_if(filter);
+        }
+
+        if let Some(limit) = filters.limit {
+            query = query.limit(limit);
+        }
+
+        let mut stream = query.execute().await?;
+        let mut artifacts = Vec::new();
+
+        while let Some(batch) = stream.try_next().await? {
+            let batch_artifacts = Self::batch_to_artifacts(&batch)?;
+            artifacts.extend(batch_artifacts);
+        }
+
+        Ok(artifacts)
+    }
+
+    /// Vector search over one embedding column of a single table.
+    ///
+    /// Rows where the column is null are skipped, and a table without the
+    /// column (a model nothing in it was embedded with) matches nothing.
+    async fn search_table(
+        table: &lancedb::table::Table,
+        query_embedding: &[f32],
+        filters: &SearchFilters,
+        column: &str,
+    ) -> Result<Vec<SearchResult>> {
+        if !Self::has_field_columns(table, filters).await? {
+            return Ok(Vec::new());
+        }
+
+        let limit = filters.limit.unwrap_or(10);
+
+        let table_schema = table.schema().await?;
+        if table_schema.field_with_name(column).is_err() {
+            return Ok(Vec::new());
+        }
+        if schema::embedding_precision(&table_schema) == EmbeddingPrecision::Int8 {
+            return Self::scan_search_table(table, query_embedding, filters, column, limit).await;
+        }
+
+        let mut query = table
+            .vector_search(query_embedding.to_vec())
+            .context("Failed to create vector search")?
+            .limit(limit)
+            .column(column);
+
+        let predicates = filter::shared(filters).and(Predicate::not_null(column));
+        if let Some(filter) = predicates.to_sql()? {
+            query = query.only_if(filter);
+        }
+
+        let mut stream = query.execute().await?;
+        let mut results = Vec::new();
+
+        while let Some(batch) = stream.try_next().await? {
+            // Get distance column (added by vector search)
+            let distance_col = batch.column_by_name("_distance");
+
+            let artifacts = Self::batch_to_artifacts(&batch)?;
+
+            for (i, artifact) in artifacts.into_iter().enumerate() {
+                let score = if let Some(dist) = distance_col {
+                    let dist_array = dist.as_any().downcast_ref::<Float32Array>().unwrap();
+                    1.0 / (1.0 + dist_array.value(i)) // Convert distance to similarity score
+                } else {
+                    1.0
+                };
+
+                results.push(SearchResult { artifact, score });
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Exhaustive search over a table whose embeddings are stored as int8.
+    ///
+    /// Vectors are dequantized as they are read and ranked by squared L2
+    /// distance, the metric used for float columns. There is no index on the
+    /// quantized column, so every row matching the filters is read and the
+    /// cost grows linearly with the store; the `lance_int8` group in
+    /// `benches/db.rs` measures it by store size. Only the nearest `limit`
+    /// rows are held at a time.
+    async fn scan_search_table(
+        table: &lancedb::table::Table,
+        query_embedding: &[f32],
+        filters: &SearchFilters,
+        column: &str,
+        limit: usize,
+    ) -> Result<Vec<SearchResult>> {
+        let mut query = table.query();
+        let predicates = filter::shared(filters).and(Predicate::not_null(column));
+        if let Some(filter) = predicates.to_sql()? {
+            query = query.only_if(filter);
+        }
+        let mut stream = query.execute().await?;
+        let mut scored: Vec<(f32, Artifact)> = Vec::new();
+        while let Some(batch) = stream.try_next().await? {
+            for artifact in Self::batch_to_artifacts(&batch)? {
+                let vector = match column.strip_prefix(schema::MODEL_COLUMN_PREFIX) {
+                    Some(slug) => artifact.model_embeddings.get(slug).map(Vec::as_slice),
+                    None if column == "context_embedding" => artifact.context_embedding.as_deref(),
+                    None if column == schema::ATTACHMENT_EMBEDDING_COLUMN => {
+                        artifact.attachment_embedding.as_deref()
+                    },
+                    None => artifact.embedding.as_deref(),
+                };
+                let Some(vector) = vector else {
+                    continue;
+                };
+                let distance = vector
+                    .iter()
+                    .zip(query_embedding)
+                    .map(|(a, b)| (a - b) * (a - b))
+                    .sum();
+                scored.push((distance, artifact));
+                if scored.len() >= limit.max(1) * 2 {
+                    keep_nearest(&mut scored, limit);
+                }
+            }
+        }
+
+        keep_nearest(&mut scored, limit);
+        scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(scored
+            .into_iter()
+            .map(|(distance, artifact)| SearchResult {
+                artifact,
+                score: 1.0 / (1.0 + distance),
+            })
+            .collect())
+    }
+
+    /// Vector search over `column` in the active table, and the archive if requested
+    async fn search_column(
+        &self,
+        query_embedding: &[f32],
+        filters: &SearchFilters,
+        column: &str,
+    ) -> Result<Vec<SearchResult>> {
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        let mut results = Self::search_table(&table, query_embedding, filters, column).await?;
+
+        if filters.include_archived {
+            if let Some(archive) = self.open_archive_table(false).await? {
+                results
+                    .extend(Self::search_table(&archive, query_embedding, filters, column).await?);
+                results.sort_by(|a, b| b.score.total_cmp(&a.score));
+                results.truncate(filters.limit.unwrap_or(10));
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Convert a single artifact to a RecordBatch matching the table's schema
+    async fn artifact_to_batch(
+        table: &lancedb::table::Table,
+        artifact: &Artifact,
+    ) -> Result<RecordBatch> {
+        Self::artifacts_to_batch(table, std::slice::from_ref(artifact)).await
     }
 
-    /// Convert RecordBatch row to Artifact
-    fn batch_to_artifacts(batch: &RecordBatch) -> Result<Vec<Artifact>> {
+    /// Convert artifacts to one RecordBatch matching the table's schema.
+    ///
+    /// Structured fields and extra models the table has no column for yet are
+    /// added as nullable columns first, so existing rows read back as null.
+    async fn artifacts_to_batch(
+        table: &lancedb::table::Table,
+        artifacts: &[Artifact],
+    ) -> Result<RecordBatch> {
+        let table_schema = table.schema().await?;
+        let precision = schema::embedding_precision(&table_schema);
+        let mut fields = schema::field_names(&table_schema);
+
+        let mut missing: Vec<String> = artifacts
+            .iter()
+            .flat_map(|a| a.fields.keys())
+            .filter(|name| schema::is_valid_field_name(name) && !fields.contains(name))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            missing.sort();
+            missing.dedup();
+            let columns = Schema::new(
+                missing
+                    .iter()
+                    .map(|name| Field::new(schema::field_column(name), DataType::Utf8, true))
+                    .collect::<Vec<_>>(),
+            );
+            table
+                .add_columns(NewColumnTransform::AllNulls(Arc::new(columns)), None)
+                .await
+                .context("Failed to add structured field columns")?;
+            tracing::debug!("Added structured field columns: {}", missing.join(", "));
+            fields.extend(missing);
+        }
+
+        let mut models = schema::model_columns(&table_schema);
+        let mut missing: Vec<(String, i32)> = artifacts
+            .iter()
+            .flat_map(|a| &a.model_embeddings)
+            .filter(|(slug, _)| !models.iter().any(|(stored, _)| stored == *slug))
+            .map(|(slug, vector)| (slug.clone(), vector.len() as i32))
+            .collect();
+        if !missing.is_empty() {
+            missing.sort();
+            missing.dedup_by(|a, b| a.0 == b.0);
+            let columns = Schema::new(
+                missing
+                    .iter()
+                    .map(|(slug, dimension)| schema::model_field(slug, *dimension))
+                    .collect::<Vec<_>>(),
+            );
+            table
+                .add_columns(NewColumnTransform::AllNulls(Arc::new(columns)), None)
+                .await
+                .context("Failed to add model embedding columns")?;
+            tracing::debug!(
+                "Added model embedding columns: {}",
+                missing
+                    .iter()
+                    .map(|(slug, _)| slug.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            models.extend(missing);
+        }
+
+        schema::artifacts_to_batch_with_models(artifacts, &fields, &models, precision)
+    }
+
+    /// Convert RecordBatch row to Artifact.
+    ///
+    /// Columns are looked up by name, and columns added by later schema
+    /// versions may be absent, so batches from stores awaiting migration
+    /// can still be read.
+    pub(super) fn batch_to_artifacts(batch: &RecordBatch) -> Result<Vec<Artifact>> {
         let mut artifacts = Vec::with_capacity(batch.num_rows());
 
-        let ids = batch.column(0).as_string::<i32>();
-        let types = batch.column(1).as_string::<i32>();
-        let names = batch.column(2).as_string::<i32>();
-        let contents = batch.column(3).as_string::<i32>();
-        let formats = batch.column(4).as_string::<i32>();
-        let metadata_col = batch.column(5).as_string::<i32>();
-        let embeddings = batch
-            .column(6)
+        let column = |name: &str| {
+            batch
+                .column_by_name(name)
+                .ok_or_else(|| StorageCorruption(format!("missing {} column", name)))
+        };
+
+        let ids = column("id")?.as_string::<i32>();
+        let types = column("kind")?.as_string::<i32>();
+        let names = column("name")?.as_string::<i32>();
+        let contents = column("content")?.as_string::<i32>();
+        let formats = column("format")?.as_string::<i32>();
+        let metadata_col = column("metadata")?.as_string::<i32>();
+        let embeddings = column("embedding")?
             .as_any()
             .downcast_ref::<FixedSizeListArray>()
-            .context("Failed to cast embedding column")?;
-        let embedding_models = batch.column(7).as_string::<i32>();
-        let contexts = batch.column(8).as_string::<i32>();
+            .ok_or_else(|| StorageCorruption("embedding column has the wrong type".into()))?;
+        let embedding_scales = batch
+            .column_by_name("embedding_scale")
+            .map(|c| c.as_primitive::<Float32Type>());
+        let embedding_models = column("embedding_model")?.as_string::<i32>();
+        let contexts = batch
+            .column_by_name("context")
+            .map(|c| c.as_string::<i32>());
+        let namespaces = batch
+            .column_by_name("namespace")
+            .map(|c| c.as_string::<i32>());
+        let aliases_col = batch
+            .column_by_name("aliases")
+            .map(|c| c.as_string::<i32>());
+        let owners = batch
+            .column_by_name(schema::OWNER_COLUMN)
+            .map(|c| c.as_string::<i32>());
+        let reviewers_col = batch
+            .column_by_name(schema::REVIEWERS_COLUMN)
+            .map(|c| c.as_string::<i32>());
+        let compressed_col = batch
+            .column_by_name(schema::COMPRESSED_COLUMN)
+            .map(|c| c.as_boolean());
         let context_embeddings = batch
-            .column(9)
-            .as_any()
-            .downcast_ref::<FixedSizeListArray>()
-            .context("Failed to cast context_embedding column")?;
-        let created_ats = batch
-            .column(10)
+            .column_by_name("context_embedding")
+            .map(|c| {
+                c.as_any()
+                    .downcast_ref::<FixedSizeListArray>()
+                    .ok_or_else(|| {
+                        StorageCorruption("context_embedding column has the wrong type".into())
+                    })
+            })
+            .transpose()?;
+        let context_embedding_scales = batch
+            .column_by_name("context_embedding_scale")
+            .map(|c| c.as_primitive::<Float32Type>());
+        let attachment_texts = batch
+            .column_by_name(schema::ATTACHMENT_TEXT_COLUMN)
+            .map(|c| c.as_string::<i32>());
+        let attachment_embeddings = batch
+            .column_by_name(schema::ATTACHMENT_EMBEDDING_COLUMN)
+            .map(|c| {
+                c.as_any()
+                    .downcast_ref::<FixedSizeListArray>()
+                    .ok_or_else(|| {
+                        StorageCorruption("attachment_embedding column has the wrong type".into())
+                    })
+            })
+            .transpose()?;
+        let attachment_embedding_scales = batch
+            .column_by_name("attachment_embedding_scale")
+            .map(|c| c.as_primitive::<Float32Type>());
+        let embedding_texts = batch
+            .column_by_name(schema::EMBEDDING_TEXT_COLUMN)
+            .map(|c| c.as_string::<i32>());
+        let created_ats = column("created_at")?
             .as_any()
             .downcast_ref::<TimestampMillisecondArray>()
-            .context("Failed to cast created_at column")?;
-        let updated_ats = batch
-            .column(11)
+            .ok_or_else(|| StorageCorruption("created_at column has the wrong type".into()))?;
+        let updated_ats = column("updated_at")?
             .as_any()
             .downcast_ref::<TimestampMillisecondArray>()
-            .context("Failed to cast updated_at column")?;
+            .ok_or_else(|| StorageCorruption("updated_at column has the wrong type".into()))?;
+        let field_columns: Vec<(String, &arrow_array::StringArray)> = batch
+            .schema()
+            .fields()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, f)| {
+                f.name()
+                    .strip_prefix(schema::FIELD_COLUMN_PREFIX)
+                    .map(|name| (name.to_string(), batch.column(idx).as_string::<i32>()))
+            })
+            .collect();
+        let model_columns: Vec<(String, &FixedSizeListArray)> = batch
+            .schema()
+            .fields()
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, f)| {
+                let slug = f.name().strip_prefix(schema::MODEL_COLUMN_PREFIX)?;
+                let column = batch.column(idx).as_any().downcast_ref()?;
+                Some((slug.to_string(), column))
+            })
+            .collect();
 
         for i in 0..batch.num_rows() {
             let id = ids.value(i).to_string();
@@ -129,49 +648,85 @@ impl LanceDatabase {
             } else {
                 Some(names.value(i).to_string())
             };
-            let content = contents.value(i).to_string();
+            let compressed = compressed_col.is_some_and(|c| !c.is_null(i) && c.value(i));
+            let stored = |text: &str| {
+                if compressed {
+                    schema::decompress(text)
+                        .map_err(|e| StorageCorruption(format!("row {}: {:#}", id, e)))
+                } else {
+                    Ok(text.to_string())
+                }
+            };
+            let content = stored(contents.value(i))?;
             let format = ContentFormat::from_str(formats.value(i))?;
             let metadata: HashMap<String, String> =
                 serde_json::from_str(metadata_col.value(i)).unwrap_or_default();
 
-            let embedding_list = embeddings.value(i);
-            let embedding_array = embedding_list
-                .as_any()
-                .downcast_ref::<Float32Array>()
-                .context("Failed to cast embedding values")?;
-            let embedding: Vec<f32> = (0..embedding_array.len())
-                .map(|j| embedding_array.value(j))
-                .collect();
+            let embedding = schema::read_embedding(embeddings, embedding_scales, i)?;
 
             let embedding_model = embedding_models.value(i).to_string();
 
-            let context = if contexts.is_null(i) {
-                None
-            } else {
-                Some(contexts.value(i).to_string())
-            };
+            let context = contexts
+                .filter(|c| !c.is_null(i))
+                .map(|c| stored(c.value(i)))
+                .transpose()?;
 
-            let context_embedding = if context_embeddings.is_null(i) {
-                None
-            } else {
-                let context_emb_list = context_embeddings.value(i);
-                let context_emb_array = context_emb_list
-                    .as_any()
-                    .downcast_ref::<Float32Array>()
-                    .context("Failed to cast context_embedding values")?;
-                Some(
-                    (0..context_emb_array.len())
-                        .map(|j| context_emb_array.value(j))
-                        .collect(),
-                )
-            };
+            let namespace = namespaces
+                .filter(|c| !c.is_null(i))
+                .map(|c| c.value(i).to_string());
+
+            let aliases: Vec<String> = aliases_col
+                .filter(|c| !c.is_null(i))
+                .and_then(|c| serde_json::from_str(c.value(i)).ok())
+                .unwrap_or_default();
+
+            let owner = owners
+                .filter(|c| !c.is_null(i))
+                .map(|c| c.value(i).to_string());
+
+            let reviewers: Vec<String> = reviewers_col
+                .filter(|c| !c.is_null(i))
+                .and_then(|c| serde_json::from_str(c.value(i)).ok())
+                .unwrap_or_default();
+
+            let context_embedding = context_embeddings
+                .filter(|c| !c.is_null(i))
+                .map(|c| schema::read_embedding(c, context_embedding_scales, i))
+                .transpose()?;
+
+            let attachment_text = attachment_texts
+                .filter(|c| !c.is_null(i))
+                .map(|c| stored(c.value(i)))
+                .transpose()?;
+
+            let attachment_embedding = attachment_embeddings
+                .filter(|c| !c.is_null(i))
+                .map(|c| schema::read_embedding(c, attachment_embedding_scales, i))
+                .transpose()?;
+
+            let embedding_text = embedding_texts
+                .filter(|c| !c.is_null(i))
+                .map(|c| c.value(i).to_string());
 
             let created_at = Utc.timestamp_millis_opt(created_ats.value(i)).unwrap();
             let updated_at = Utc.timestamp_millis_opt(updated_ats.value(i)).unwrap();
 
+            let fields = field_columns
+                .iter()
+                .filter(|(_, col)| !col.is_null(i))
+                .map(|(name, col)| (name.clone(), col.value(i).to_string()))
+                .collect();
+
+            let model_embeddings = model_columns
+                .iter()
+                .filter(|(_, col)| !col.is_null(i))
+                .map(|(slug, col)| Ok((slug.clone(), schema::read_embedding(col, None, i)?)))
+                .collect::<Result<_>>()?;
+
             artifacts.push(Artifact {
                 id,
                 kind,
+                namespace,
                 name,
                 content,
                 format,
@@ -180,6 +735,15 @@ impl LanceDatabase {
                 embedding_model,
                 context,
                 context_embedding,
+                attachment_text,
+                attachment_embedding,
+                embedding_text,
+                model_embeddings,
+                fields,
+                aliases,
+                redirected_from: None,
+                owner,
+                reviewers,
                 created_at,
                 updated_at,
             });
@@ -192,6 +756,7 @@ impl LanceDatabase {
 #[async_trait::async_trait]
 impl Database for LanceDatabase {
     async fn insert(&self, artifact: &Artifact) -> Result<()> {
+        let _lock = self.lock_for_write().await?;
         let db = self.get_connection().await?;
         let table = db
             .open_table(TABLE_NAME)
@@ -199,7 +764,7 @@ impl Database for LanceDatabase {
             .await
             .context("Failed to open artifacts table")?;
 
-        let batch = Self::artifact_to_batch(artifact)?;
+        let batch = Self::artifact_to_batch(&table, artifact).await?;
         let schema = batch.schema();
 
         table
@@ -212,6 +777,31 @@ impl Database for LanceDatabase {
         Ok(())
     }
 
+    async fn insert_batch(&self, artifacts: &[Artifact]) -> Result<()> {
+        if artifacts.is_empty() {
+            return Ok(());
+        }
+        let _lock = self.lock_for_write().await?;
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        let batch = Self::artifacts_to_batch(&table, artifacts).await?;
+        let schema = batch.schema();
+
+        table
+            .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+            .execute()
+            .await
+            .context("Failed to insert artifacts")?;
+
+        tracing::debug!("Inserted {} artifacts", artifacts.len());
+        Ok(())
+    }
+
     async fn get(&self, id: &str) -> Result<Option<Artifact>> {
         let db = self.get_connection().await?;
         let table = db
@@ -220,7 +810,7 @@ impl Database for LanceDatabase {
             .await
             .context("Failed to open artifacts table")?;
 
-        let filter = format!("id = '{}'", id.replace('\'', "''"));
+        let filter = id_filter(id)?;
         let mut stream = table.query().only_if(filter).execute().await?;
 
         if let Some(batch) = stream.try_next().await? {
@@ -233,7 +823,30 @@ impl Database for LanceDatabase {
         Ok(None)
     }
 
+    async fn get_many(&self, ids: &[String]) -> Result<Vec<Artifact>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        let filter = Predicate::is_in("id", ids.to_vec()).to_sql()?;
+        let mut stream = table.query().only_if(filter).execute().await?;
+        let mut artifacts = Vec::new();
+
+        while let Some(batch) = stream.try_next().await? {
+            artifacts.extend(Self::batch_to_artifacts(&batch)?);
+        }
+
+        Ok(artifacts)
+    }
+
     async fn update(&self, artifact: &Artifact) -> Result<()> {
+        let _lock = self.lock_for_write().await?;
         let db = self.get_connection().await?;
         let table = db
             .open_table(TABLE_NAME)
@@ -242,13 +855,13 @@ impl Database for LanceDatabase {
             .context("Failed to open artifacts table")?;
 
         // Delete existing and re-insert (LanceDB doesn't have native update)
-        let filter = format!("id = '{}'", artifact.id.replace('\'', "''"));
+        let filter = id_filter(&artifact.id)?;
         table
             .delete(&filter)
             .await
             .context("Failed to delete old artifact during update")?;
 
-        let batch = Self::artifact_to_batch(artifact)?;
+        let batch = Self::artifact_to_batch(&table, artifact).await?;
         let schema = batch.schema();
 
         table
@@ -262,12 +875,20 @@ impl Database for LanceDatabase {
     }
 
     async fn delete(&self, id: &str) -> Result<bool> {
+        let _lock = self.lock_for_write().await?;
         // First check if artifact exists
         let exists = self.get(id).await?.is_some();
         if !exists {
             return Ok(false);
         }
 
+        self.delete_row(id).await?;
+        tracing::debug!("Deleted artifact: {}", id);
+        Ok(true)
+    }
+
+    async fn rename_kind(&self, from: &str, to: &str) -> Result<usize> {
+        let _lock = self.lock_for_write().await?;
         let db = self.get_connection().await?;
         let table = db
             .open_table(TABLE_NAME)
@@ -275,14 +896,13 @@ impl Database for LanceDatabase {
             .await
             .context("Failed to open artifacts table")?;
 
-        let filter = format!("id = '{}'", id.replace('\'', "''"));
-        table
-            .delete(&filter)
-            .await
-            .context("Failed to delete artifact")?;
+        let mut renamed = Self::rename_kind_in(&table, from, to).await?;
+        if let Some(archive) = self.open_archive_table(false).await? {
+            renamed += Self::rename_kind_in(&archive, from, to).await?;
+        }
 
-        tracing::debug!("Deleted artifact: {}", id);
-        Ok(true)
+        tracing::debug!("Moved {} artifacts from kind {} to {}", renamed, from, to);
+        Ok(renamed)
     }
 
     async fn list(&self, filters: SearchFilters) -> Result<Vec<Artifact>> {
@@ -293,52 +913,15 @@ impl Database for LanceDatabase {
             .await
             .context("Failed to open artifacts table")?;
 
-        let mut query = table.query();
-
-        // Build filter string
-        let mut filter_parts: Vec<String> = Vec::new();
-
-        if let Some(kind) = &filters.kind {
-            filter_parts.push(format!("kind = '{}'", kind.replace('\'', "''")));
-        }
-
-        if let Some(after) = &filters.after {
-            filter_parts.push(format!(
-                "updated_at >= arrow_cast({}, 'Timestamp(Millisecond, None)')",
-                after.timestamp_millis()
-            ));
-        }
+        let mut artifacts = Self::list_table(&table, &filters).await?;
 
-        if let Some(before) = &filters.before {
-            filter_parts.push(format!(
-                "updated_at < arrow_cast({}, 'Timestamp(Millisecond, None)')",
-                before.timestamp_millis()
-            ));
-        }
-
-        for (key, value) in &filters.metadata {
-            // Filter on JSON metadata field
-            filter_parts.push(format!(
-                "metadata LIKE '%\"{}\":\"{}\"%'",
-                key.replace('\'', "''"),
-                value.replace('\'', "''")
-            ));
-        }
-
-        if !filter_parts.is_empty() {
-            query = query.only_if(filter_parts.join(" AND "));
-        }
-
-        if let Some(limit) = filters.limit {
-            query = query.limit(limit);
-        }
-
-        let mut stream = query.execute().await?;
-        let mut artifacts = Vec::new();
-
-        while let Some(batch) = stream.try_next().await? {
-            let batch_artifacts = Self::batch_to_artifacts(&batch)?;
-            artifacts.extend(batch_artifacts);
+        if filters.include_archived {
+            if let Some(archive) = self.open_archive_table(false).await? {
+                artifacts.extend(Self::list_table(&archive, &filters).await?);
+                if let Some(limit) = filters.limit {
+                    artifacts.truncate(limit);
+                }
+            }
         }
 
         Ok(artifacts)
@@ -349,70 +932,52 @@ impl Database for LanceDatabase {
         query_embedding: &[f32],
         filters: SearchFilters,
     ) -> Result<Vec<SearchResult>> {
-        let db = self.get_connection().await?;
-        let table = db
-            .open_table(TABLE_NAME)
-            .execute()
+        self.search_column(query_embedding, &filters, "embedding")
             .await
-            .context("Failed to open artifacts table")?;
-
-        let limit = filters.limit.unwrap_or(10);
-
-        let mut query = table
-            .vector_search(query_embedding.to_vec())
-            .context("Failed to create vector search")?
-            .limit(limit)
-            .column("embedding");
-
-        // Build filter string
-        let mut filter_parts: Vec<String> = Vec::new();
-
-        if let Some(kind) = &filters.kind {
-            filter_parts.push(format!("kind = '{}'", kind.replace('\'', "''")));
-        }
-
-        if let Some(after) = &filters.after {
-            filter_parts.push(format!(
-                "updated_at >= arrow_cast({}, 'Timestamp(Millisecond, None)')",
-                after.timestamp_millis()
-            ));
-        }
-
-        if let Some(before) = &filters.before {
-            filter_parts.push(format!(
-                "updated_at < arrow_cast({}, 'Timestamp(Millisecond, None)')",
-                before.timestamp_millis()
-            ));
-        }
-
-        if !filter_parts.is_empty() {
-            query = query.only_if(filter_parts.join(" AND "));
-        }
-
-        let mut stream = query.execute().await?;
-        let mut results = Vec::new();
-
-        while let Some(batch) = stream.try_next().await? {
-            // Get distance column (added by vector search)
-            let distance_col = batch.column_by_name("_distance");
-
-            let artifacts = Self::batch_to_artifacts(&batch)?;
-
-            for (i, artifact) in artifacts.into_iter().enumerate() {
-                let score = if let Some(dist) = distance_col {
-                    let dist_array = dist.as_any().downcast_ref::<Float32Array>().unwrap();
-                    1.0 / (1.0 + dist_array.value(i)) // Convert distance to similarity score
-                } else {
-                    1.0
-                };
+    }
 
-                results.push(SearchResult { artifact, score });
+    async fn search_context(
+        &self,
+        query_embedding: &[f32],
+        filters: SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
+        // Attachment text is searched as a second context vector, each
+        // artifact keeping its better match
+        let mut results = self
+            .search_column(query_embedding, &filters, "context_embedding")
+            .await?;
+        let attachments = self
+            .search_column(
+                query_embedding,
+                &filters,
+                schema::ATTACHMENT_EMBEDDING_COLUMN,
+            )
+            .await?;
+        for result in attachments {
+            match results
+                .iter_mut()
+                .find(|r| r.artifact.id == result.artifact.id)
+            {
+                Some(existing) if existing.score >= result.score => {},
+                Some(existing) => *existing = result,
+                None => results.push(result),
             }
         }
-
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(filters.limit.unwrap_or(10));
         Ok(results)
     }
 
+    async fn search_model(
+        &self,
+        model: &str,
+        query_embedding: &[f32],
+        filters: SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_column(query_embedding, &filters, &schema::model_column(model))
+            .await
+    }
+
     async fn version(&self) -> Result<u64> {
         let db = self.get_connection().await?;
         let table = db
@@ -442,7 +1007,7 @@ impl Database for LanceDatabase {
             .await
             .context("Failed to checkout version")?;
 
-        let filter = format!("id = '{}'", id.replace('\'', "''"));
+        let filter = id_filter(id)?;
         let mut stream = table.query().only_if(filter).execute().await?;
 
         let result = if let Some(batch) = stream.try_next().await? {
@@ -461,6 +1026,125 @@ impl Database for LanceDatabase {
         Ok(result)
     }
 
+    async fn list_at_version(&self, version: u64) -> Result<Vec<Artifact>> {
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        table
+            .checkout(version)
+            .await
+            .context("Failed to checkout version")?;
+
+        let result = async {
+            let mut stream = table.query().execute().await?;
+            let mut artifacts = Vec::new();
+            while let Some(batch) = stream.try_next().await? {
+                artifacts.extend(Self::batch_to_artifacts(&batch)?);
+            }
+            Ok::<_, anyhow::Error>(artifacts)
+        }
+        .await;
+
+        table
+            .checkout_latest()
+            .await
+            .context("Failed to restore to latest version")?;
+
+        result
+    }
+
+    async fn updated_at_version(&self, version: u64) -> Result<HashMap<String, DateTime<Utc>>> {
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        table
+            .checkout(version)
+            .await
+            .context("Failed to checkout version")?;
+
+        let result = async {
+            let mut stream = table
+                .query()
+                .select(Select::Columns(vec![
+                    "id".to_string(),
+                    "updated_at".to_string(),
+                ]))
+                .execute()
+                .await?;
+            let mut updated = HashMap::new();
+            while let Some(batch) = stream.try_next().await? {
+                let ids = batch
+                    .column_by_name("id")
+                    .context("Missing id column")?
+                    .as_string::<i32>();
+                let updated_ats = batch
+                    .column_by_name("updated_at")
+                    .context("Missing updated_at column")?
+                    .as_any()
+                    .downcast_ref::<TimestampMillisecondArray>()
+                    .ok_or_else(|| {
+                        StorageCorruption("updated_at column has the wrong type".into())
+                    })?;
+                for i in 0..batch.num_rows() {
+                    let updated_at = Utc.timestamp_millis_opt(updated_ats.value(i)).unwrap();
+                    updated.insert(ids.value(i).to_string(), updated_at);
+                }
+            }
+            Ok::<_, anyhow::Error>(updated)
+        }
+        .await;
+
+        table
+            .checkout_latest()
+            .await
+            .context("Failed to restore to latest version")?;
+
+        result
+    }
+
+    async fn get_many_at_version(&self, ids: &[String], version: u64) -> Result<Vec<Artifact>> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        table
+            .checkout(version)
+            .await
+            .context("Failed to checkout version")?;
+
+        let result = async {
+            let filter = Predicate::is_in("id", ids.to_vec()).to_sql()?;
+            let mut stream = table.query().only_if(filter).execute().await?;
+            let mut artifacts = Vec::new();
+            while let Some(batch) = stream.try_next().await? {
+                artifacts.extend(Self::batch_to_artifacts(&batch)?);
+            }
+            Ok::<_, anyhow::Error>(artifacts)
+        }
+        .await;
+
+        table
+            .checkout_latest()
+            .await
+            .context("Failed to restore to latest version")?;
+
+        result
+    }
+
     async fn list_versions(&self, limit: Option<usize>) -> Result<Vec<VersionInfo>> {
         let db = self.get_connection().await?;
         let table = db
@@ -483,7 +1167,7 @@ impl Database for LanceDatabase {
             .collect();
 
         // Sort by version descending (most recent first)
-        versions.sort_by(|a, b| b.version.cmp(&a.version));
+        versions.sort_by_key(|v| std::cmp::Reverse(v.version));
 
         Ok(match limit {
             Some(n) => versions.into_iter().take(n).collect(),
@@ -492,6 +1176,7 @@ impl Database for LanceDatabase {
     }
 
     async fn compact(&self) -> Result<CompactStats> {
+        let _lock = self.lock_for_write().await?;
         let db = self.get_connection().await?;
         let table = db
             .open_table(TABLE_NAME)
@@ -514,6 +1199,142 @@ impl Database for LanceDatabase {
     }
 
     async fn cleanup_versions(&self, keep_versions: usize) -> Result<CleanupStats> {
+        let _lock = self.lock_for_write().await?;
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        let metrics = table
+            .optimize(lancedb::table::OptimizeAction::Prune {
+                older_than: None,
+                delete_unverified: Some(false),
+                error_if_tagged_old_versions: None,
+            })
+            .await
+            .context("Failed to cleanup old versions")?;
+
+        // Note: LanceDB prune doesn't directly support keep_versions count,
+        // so we use time-based pruning. The keep_versions parameter is ignored.
+        let _ = keep_versions;
+
+        let (versions_removed, bytes_freed) = match metrics.prune {
+            Some(p) => (p.bytes_removed as usize, p.bytes_removed),
+            None => (0, 0),
+        };
+
+        Ok(CleanupStats {
+            versions_removed,
+            bytes_freed,
+        })
+    }
+
+    async fn archive(&self, id: &str) -> Result<bool> {
+        let _lock = self.lock_for_write().await?;
+        let Some(artifact) = self.get(id).await? else {
+            return Ok(false);
+        };
+
+        let archive = self
+            .open_archive_table(true)
+            .await?
+            .context("Failed to open archived table")?;
+
+        // Copy first so a failure part-way never loses the artifact
+        let batch = Self::artifact_to_batch(&archive, &artifact).await?;
+        let schema = batch.schema();
+        archive
+            .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+            .execute()
+            .await
+            .context("Failed to copy artifact to archive")?;
+
+        self.delete_row(id).await?;
+
+        tracing::debug!("Archived artifact: {}", id);
+        Ok(true)
+    }
+
+    async fn restore(&self, version: u64) -> Result<()> {
+        let _lock = self.lock_for_write().await?;
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        if table.version().await? == version {
+            return Ok(());
+        }
+
+        table
+            .checkout(version)
+            .await
+            .context("Failed to checkout version")?;
+        table.restore().await.context("Failed to restore version")?;
+
+        tracing::debug!("Restored artifacts table to version {}", version);
+        Ok(())
+    }
+
+    async fn resolve_prefix(&self, prefix: &str) -> Result<Option<String>> {
+        // IDs never contain LIKE wildcards; anything that does can't match
+        if prefix.is_empty() || prefix.contains(['%', '_', '\\', '\'']) {
+            return Ok(None);
+        }
+
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        let mut stream = table
+            .query()
+            .select(Select::Columns(vec!["id".to_string()]))
+            .only_if(Predicate::starts_with("id", prefix).to_sql()?)
+            .execute()
+            .await?;
+
+        let mut ids = Vec::new();
+        while let Some(batch) = stream.try_next().await? {
+            let col = batch
+                .column_by_name("id")
+                .context("Missing id column")?
+                .as_string::<i32>();
+            ids.extend((0..col.len()).map(|i| col.value(i).to_string()));
+        }
+
+        match_prefix(prefix, ids)
+    }
+
+    async fn find_by_alias(&self, alias: &str) -> Result<Option<Artifact>> {
+        let db = self.get_connection().await?;
+        let table = db
+            .open_table(TABLE_NAME)
+            .execute()
+            .await
+            .context("Failed to open artifacts table")?;
+
+        // Narrow with LIKE on the JSON array, then match exactly
+        let filter = Predicate::contains("aliases", serde_json::to_string(alias)?).to_sql()?;
+        let mut stream = table.query().only_if(filter).execute().await?;
+        while let Some(batch) = stream.try_next().await? {
+            if let Some(artifact) = Self::batch_to_artifacts(&batch)?
+                .into_iter()
+                .find(|a| a.aliases.iter().any(|id| id == alias))
+            {
+                return Ok(Some(artifact));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn count(&self) -> Result<usize> {
         let db = self.get_connection().await?;
         let table = db
             .open_table(TABLE_NAME)
@@ -521,35 +1342,26 @@ impl Database for LanceDatabase {
             .await
             .context("Failed to open artifacts table")?;
 
-        let metrics = table
-            .optimize(lancedb::table::OptimizeAction::Prune {
-                older_than: None,
-                delete_unverified: Some(false),
-                error_if_tagged_old_versions: None,
-            })
+        table
+            .count_rows(None)
             .await
-            .context("Failed to cleanup old versions")?;
-
-        // Note: LanceDB prune doesn't directly support keep_versions count,
-        // so we use time-based pruning. The keep_versions parameter is ignored.
-        let _ = keep_versions;
-
-        let (versions_removed, bytes_freed) = match metrics.prune {
-            Some(p) => (p.bytes_removed as usize, p.bytes_removed),
-            None => (0, 0),
-        };
+            .context("Failed to count artifacts")
+    }
+}
 
-        Ok(CleanupStats {
-            versions_removed,
-            bytes_freed,
-        })
+/// Drop all but the `limit` nearest of `scored`, leaving them unordered
+fn keep_nearest(scored: &mut Vec<(f32, Artifact)>, limit: usize) {
+    if limit > 0 && scored.len() > limit {
+        scored.select_nth_unstable_by(limit - 1, |a, b| a.0.total_cmp(&b.0));
     }
+    scored.truncate(limit);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::services::{Artifact, ContentFormat};
+    use crate::testing::strategies;
     use std::collections::HashMap;
     use tempfile::TempDir;
 
@@ -578,6 +1390,25 @@ mod tests {
         assert!(temp_dir.path().join("subdir").exists());
     }
 
+    #[tokio::test]
+    async fn s3_profile_must_be_in_the_environment() {
+        let s3 = S3Config {
+            profile: Some("dna".to_string()),
+            ..Default::default()
+        };
+        let temp_dir = TempDir::new().unwrap();
+        let local = LanceDatabase::new(temp_dir.path().join("test.lance").to_str().unwrap())
+            .await
+            .unwrap();
+        assert!(local.with_s3_config(&s3).is_ok());
+
+        if std::env::var_os("AWS_PROFILE").is_none() {
+            let remote = LanceDatabase::new("s3://bucket/dna").await.unwrap();
+            let err = remote.with_s3_config(&s3).err().unwrap();
+            assert!(err.to_string().contains("AWS_PROFILE=dna"), "{err}");
+        }
+    }
+
     #[tokio::test]
     async fn init_creates_database_directory() {
         let temp_dir = TempDir::new().unwrap();
@@ -611,6 +1442,135 @@ mod tests {
         assert_eq!(retrieved.kind, "intent");
     }
 
+    #[tokio::test]
+    async fn get_many_returns_the_artifacts_that_exist() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let first = create_test_artifact("first", create_embedding(0.1));
+        let second = create_test_artifact("second", create_embedding(0.2));
+        let third = create_test_artifact("third", create_embedding(0.3));
+        db.insert_batch(&[first.clone(), second.clone(), third])
+            .await
+            .unwrap();
+
+        let ids = vec![second.id.clone(), "missing".to_string(), first.id.clone()];
+        let mut found: Vec<String> = db
+            .get_many(&ids)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        found.sort();
+        let mut expected = vec![first.id, second.id];
+        expected.sort();
+        assert_eq!(found, expected);
+
+        assert!(db.get_many(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn resolve_prefix_finds_unique_and_rejects_ambiguous() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let mut first = create_test_artifact("one", create_embedding(0.1));
+        first.id = "k7v3m9xnp2".to_string();
+        let mut second = create_test_artifact("two", create_embedding(0.2));
+        second.id = "k7zzzzzzzz".to_string();
+        db.insert(&first).await.unwrap();
+        db.insert(&second).await.unwrap();
+
+        assert_eq!(
+            db.resolve_prefix("k7v").await.unwrap(),
+            Some("k7v3m9xnp2".to_string())
+        );
+        assert_eq!(db.resolve_prefix("xx").await.unwrap(), None);
+
+        let err = db.resolve_prefix("k7").await.unwrap_err();
+        assert!(err
+            .downcast_ref::<super::super::AmbiguousPrefix>()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn find_by_alias_matches_whole_ids_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let mut artifact = create_test_artifact("renamed", create_embedding(0.1));
+        artifact.aliases = vec!["auth-intent".to_string(), "k7v3m9xnp2".to_string()];
+        db.insert(&artifact).await.unwrap();
+
+        let found = db.find_by_alias("k7v3m9xnp2").await.unwrap().unwrap();
+        assert_eq!(found.id, artifact.id);
+        assert_eq!(found.aliases, artifact.aliases);
+        assert!(db.find_by_alias("auth").await.unwrap().is_none());
+        assert!(db.find_by_alias(&artifact.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn archive_moves_artifact_out_of_default_results() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let kept = create_test_artifact("kept", create_embedding(0.1));
+        let archived = create_test_artifact("archived", create_embedding(0.1));
+        db.insert(&kept).await.unwrap();
+        db.insert(&archived).await.unwrap();
+
+        assert!(db.archive(&archived.id).await.unwrap());
+        assert!(!db.archive("missing").await.unwrap());
+        assert!(db.get(&archived.id).await.unwrap().is_none());
+
+        let active = db.list(SearchFilters::default()).await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].id, kept.id);
+
+        let with_archived = SearchFilters {
+            include_archived: true,
+            ..Default::default()
+        };
+        assert_eq!(db.list(with_archived.clone()).await.unwrap().len(), 2);
+
+        let results = db
+            .search(&create_embedding(0.1), with_archived)
+            .await
+            .unwrap();
+        assert!(results.iter().any(|r| r.artifact.id == archived.id));
+    }
+
+    #[tokio::test]
+    async fn restore_rolls_back_to_earlier_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let kept = create_test_artifact("kept", create_embedding(0.1));
+        db.insert(&kept).await.unwrap();
+        let snapshot = db.version().await.unwrap();
+
+        let added = create_test_artifact("added", create_embedding(0.2));
+        db.insert(&added).await.unwrap();
+        db.delete(&kept.id).await.unwrap();
+
+        db.restore(snapshot).await.unwrap();
+
+        assert!(db.get(&kept.id).await.unwrap().is_some());
+        assert!(db.get(&added.id).await.unwrap().is_none());
+        assert!(db.version().await.unwrap() > snapshot);
+    }
+
     // TDD: Insert then list should include the artifact
     #[tokio::test]
     async fn insert_then_list_includes_artifact() {
@@ -669,6 +1629,309 @@ mod tests {
         assert_eq!(results[0].id, intent_id);
     }
 
+    #[tokio::test]
+    async fn init_migrates_store_without_context_columns() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let uri = db_path.to_str().unwrap();
+
+        // Write a table in the layout used before context columns existed
+        let artifact = create_test_artifact("old content", create_embedding(0.3));
+        let full = schema::artifacts_to_batch(std::slice::from_ref(&artifact)).unwrap();
+        let keep: Vec<usize> = (0..full.num_columns())
+            .filter(|&i| {
+                let name = full.schema().field(i).name().clone();
+                !name.starts_with("context")
+                    && name != schema::CHECKSUM_COLUMN
+                    && name != "namespace"
+                    && name != "aliases"
+                    && name != schema::COMPRESSED_COLUMN
+                    && !name.starts_with("attachment")
+                    && name != schema::EMBEDDING_TEXT_COLUMN
+                    && name != schema::OWNER_COLUMN
+                    && name != schema::REVIEWERS_COLUMN
+            })
+            .collect();
+        let old = full.project(&keep).unwrap();
+        let old_schema = old.schema();
+        let conn = lancedb::connect(uri).execute().await.unwrap();
+        conn.create_table(
+            TABLE_NAME,
+            RecordBatchIterator::new(vec![Ok(old)], old_schema),
+        )
+        .execute()
+        .await
+        .unwrap();
+        assert_eq!(migrations::read_version(&conn).await.unwrap(), None);
+
+        let db = LanceDatabase::new(uri).await.unwrap();
+        db.init().await.unwrap();
+
+        let migrated = db.get(&artifact.id).await.unwrap().unwrap();
+        assert_eq!(migrated.content, "old content");
+        assert!(migrated.context.is_none());
+        assert_eq!(
+            migrations::read_version(&conn).await.unwrap(),
+            Some(migrations::current_version())
+        );
+
+        // Writes use the current layout once migrated
+        let mut fresh = create_test_artifact("new content", create_embedding(0.4));
+        fresh.context = Some("why".to_string());
+        db.insert(&fresh).await.unwrap();
+        assert_eq!(db.list(SearchFilters::default()).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn init_reencodes_labels_written_before_commas_were_escaped() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let uri = db_path.to_str().unwrap();
+
+        // Write a store recorded at schema v5, with labels as plain JSON
+        let mut artifact = create_test_artifact("labelled", create_embedding(0.3));
+        artifact.metadata = HashMap::from([
+            ("team".to_string(), "core,platform".to_string()),
+            ("tier".to_string(), "1".to_string()),
+        ]);
+        let batch = schema::artifacts_to_batch(std::slice::from_ref(&artifact)).unwrap();
+        let index = batch.schema().index_of("metadata").unwrap();
+        let mut columns = batch.columns().to_vec();
+        columns[index] = Arc::new(arrow_array::StringArray::from(vec![serde_json::to_string(
+            &artifact.metadata,
+        )
+        .unwrap()]));
+        let old = RecordBatch::try_new(batch.schema(), columns).unwrap();
+        let old_schema = old.schema();
+        let conn = lancedb::connect(uri).execute().await.unwrap();
+        conn.create_table(
+            TABLE_NAME,
+            RecordBatchIterator::new(vec![Ok(old)], old_schema),
+        )
+        .execute()
+        .await
+        .unwrap();
+        migrations::write_version(&conn, 5).await.unwrap();
+        let precision = EmbeddingPrecision::default();
+        assert!(!migrations::is_current(&conn, precision).await.unwrap());
+
+        let db = LanceDatabase::new(uri).await.unwrap();
+        let migrated = db.get(&artifact.id).await.unwrap().unwrap();
+        assert_eq!(migrated.metadata, artifact.metadata);
+        assert!(migrations::is_current(&conn, precision).await.unwrap());
+        assert!(!WriterLock::path_for(uri).exists());
+
+        // Labels match whole values, not parts of them
+        let label = |value: &str| SearchFilters {
+            metadata: HashMap::from([("team".to_string(), value.to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(db.list(label("core,platform")).await.unwrap().len(), 1);
+        assert!(db.list(label("core")).await.unwrap().is_empty());
+        assert!(db
+            .list(label("core,platform\",\"tier\":\"1"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn quantized_store_round_trips_and_converts() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let uri = db_path.to_str().unwrap();
+
+        let db = LanceDatabase::new(uri).await.unwrap();
+        db.init().await.unwrap();
+        let near = create_test_artifact("near", create_embedding(0.1));
+        let far = create_test_artifact("far", create_embedding(0.9));
+        db.insert(&near).await.unwrap();
+        db.insert(&far).await.unwrap();
+
+        // Reopening with a lower precision rewrites the stored vectors
+        let db = LanceDatabase::new(uri)
+            .await
+            .unwrap()
+            .with_embedding_precision(EmbeddingPrecision::Int8);
+        db.init().await.unwrap();
+        let conn = lancedb::connect(uri).execute().await.unwrap();
+        let table = conn.open_table(TABLE_NAME).execute().await.unwrap();
+        assert_eq!(
+            schema::embedding_precision(&table.schema().await.unwrap()),
+            EmbeddingPrecision::Int8
+        );
+
+        let stored = db.get(&near.id).await.unwrap().unwrap();
+        let original = near.embedding.as_ref().unwrap();
+        for (a, b) in original.iter().zip(stored.embedding.as_ref().unwrap()) {
+            assert!((a - b).abs() < 0.01);
+        }
+
+        let results = db
+            .search(&create_embedding(0.1), SearchFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].artifact.id, near.id);
+        assert!(results[0].score > results[1].score);
+
+        // New writes follow the table's precision
+        db.insert(&create_test_artifact("newer", create_embedding(0.5)))
+            .await
+            .unwrap();
+        assert_eq!(db.list(SearchFilters::default()).await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn structured_fields_add_columns_and_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let plain = create_test_artifact("no fields", create_embedding(0.1));
+        db.insert(&plain).await.unwrap();
+
+        let where_severity = |value: &str| SearchFilters {
+            fields: HashMap::from([("severity".to_string(), value.to_string())]),
+            ..Default::default()
+        };
+        // No column yet, so nothing can match
+        assert!(db
+            .list(where_severity("critical"))
+            .await
+            .unwrap()
+            .is_empty());
+
+        let mut critical = create_test_artifact("severity: critical", create_embedding(0.2));
+        critical
+            .fields
+            .insert("severity".to_string(), "critical".to_string());
+        db.insert(&critical).await.unwrap();
+
+        let results = db.list(where_severity("critical")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, critical.id);
+        assert_eq!(
+            results[0].fields.get("severity").map(String::as_str),
+            Some("critical")
+        );
+
+        let existing = db.get(&plain.id).await.unwrap().unwrap();
+        assert!(existing.fields.is_empty());
+        assert!(db.list(where_severity("low")).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn extra_model_embeddings_add_columns_and_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let plain = create_test_artifact("primary only", create_embedding(0.1));
+        db.insert(&plain).await.unwrap();
+        // No column yet, so nothing can match
+        assert!(db
+            .search_model("other", &[0.5; 8], SearchFilters::default())
+            .await
+            .unwrap()
+            .is_empty());
+
+        let mut both = create_test_artifact("both models", create_embedding(0.2));
+        both.model_embeddings
+            .insert("other".to_string(), vec![0.5; 8]);
+        db.insert(&both).await.unwrap();
+
+        let stored = db.get(&both.id).await.unwrap().unwrap();
+        assert_eq!(stored.model_embeddings.get("other"), Some(&vec![0.5; 8]));
+        assert!(db
+            .get(&plain.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .model_embeddings
+            .is_empty());
+
+        let results = db
+            .search_model("other", &[0.5; 8], SearchFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].artifact.id, both.id);
+    }
+
+    #[tokio::test]
+    async fn exclusion_filters_apply_to_list_and_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let kept = create_test_artifact("kept", create_embedding(0.1));
+        let mut deprecated = create_test_artifact("deprecated", create_embedding(0.2));
+        deprecated
+            .metadata
+            .insert("status".to_string(), "deprecated".to_string());
+        let mut contract = create_test_artifact("contract", create_embedding(0.3));
+        contract.kind = "contract".to_string();
+        let seen = create_test_artifact("seen", create_embedding(0.4));
+        for artifact in [&kept, &deprecated, &contract, &seen] {
+            db.insert(artifact).await.unwrap();
+        }
+
+        let filters = SearchFilters {
+            kind_not: vec!["contract".to_string()],
+            metadata_not: HashMap::from([("status".to_string(), "deprecated".to_string())]),
+            exclude_ids: vec![seen.id.clone()],
+            ..Default::default()
+        };
+
+        let listed = db.list(filters.clone()).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, kept.id);
+
+        let found = db.search(&create_embedding(0.2), filters).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].artifact.id, kept.id);
+    }
+
+    #[tokio::test]
+    async fn namespace_round_trips_and_scopes_list_and_search() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let mut payments = create_test_artifact("payments", create_embedding(0.1));
+        payments.namespace = Some("payments".to_string());
+        let shared = create_test_artifact("shared", create_embedding(0.2));
+        db.insert(&payments).await.unwrap();
+        db.insert(&shared).await.unwrap();
+
+        let stored = db.get(&payments.id).await.unwrap().unwrap();
+        assert_eq!(stored.namespace.as_deref(), Some("payments"));
+        assert!(db
+            .get(&shared.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .namespace
+            .is_none());
+
+        let filters = SearchFilters {
+            namespace: Some("payments".to_string()),
+            ..Default::default()
+        };
+        let listed = db.list(filters.clone()).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, payments.id);
+
+        let found = db.search(&create_embedding(0.2), filters).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].artifact.id, payments.id);
+    }
+
     // TDD: Update changes artifact content
     #[tokio::test]
     async fn update_changes_artifact_content() {
@@ -733,6 +1996,34 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn rename_kind_moves_active_and_archived_artifacts() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let active = create_test_artifact("active", create_embedding(0.1));
+        let archived = create_test_artifact("archived", create_embedding(0.2));
+        let mut other = create_test_artifact("other", create_embedding(0.3));
+        other.kind = "contract".to_string();
+        db.insert_batch(&[active.clone(), archived.clone(), other.clone()])
+            .await
+            .unwrap();
+        db.archive(&archived.id).await.unwrap();
+
+        assert_eq!(db.rename_kind("intent", "rule").await.unwrap(), 2);
+        assert_eq!(db.get(&active.id).await.unwrap().unwrap().kind, "rule");
+        assert_eq!(db.get(&other.id).await.unwrap().unwrap().kind, "contract");
+        let filters = SearchFilters {
+            kind: Some("rule".to_string()),
+            include_archived: true,
+            ..Default::default()
+        };
+        assert_eq!(db.list(filters).await.unwrap().len(), 2);
+        assert_eq!(db.rename_kind("intent", "rule").await.unwrap(), 0);
+    }
+
     // TDD: Get returns None for nonexistent
     #[tokio::test]
     async fn get_returns_none_for_nonexistent() {
@@ -853,6 +2144,90 @@ mod tests {
         assert_eq!(results.len(), 3, "Should have 3 artifacts");
     }
 
+    #[tokio::test]
+    async fn insert_batch_writes_one_version_with_every_field_column() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let mut first = create_test_artifact("first", create_embedding(0.1));
+        first.fields.insert("owner".to_string(), "auth".to_string());
+        let mut second = create_test_artifact("second", create_embedding(0.2));
+        second
+            .fields
+            .insert("priority".to_string(), "high".to_string());
+
+        db.insert_batch(&[first.clone(), second.clone()])
+            .await
+            .unwrap();
+        let stored = db.get(&second.id).await.unwrap().unwrap();
+        assert_eq!(
+            stored.fields.get("priority").map(String::as_str),
+            Some("high")
+        );
+        assert!(!stored.fields.contains_key("owner"));
+
+        let before = db.version().await.unwrap();
+        db.insert_batch(&[
+            create_test_artifact("third", create_embedding(0.3)),
+            create_test_artifact("fourth", create_embedding(0.4)),
+        ])
+        .await
+        .unwrap();
+        assert_eq!(db.version().await.unwrap(), before + 1);
+        assert_eq!(db.list(SearchFilters::default()).await.unwrap().len(), 4);
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(16))]
+
+        // Labels and namespaces are interpolated into the SQL predicate
+        #[test]
+        fn adversarial_labels_and_namespaces_filter_exactly(
+            key in strategies::adversarial_text(),
+            value in strategies::adversarial_text(),
+            namespace in strategies::adversarial_text(),
+        ) {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            runtime.block_on(async {
+                let temp_dir = TempDir::new().unwrap();
+                let db_path = temp_dir.path().join("test.lance");
+                let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+                db.init().await.unwrap();
+
+                let mut labelled = create_test_artifact("labelled", create_embedding(0.1));
+                labelled.metadata.insert(key.clone(), value.clone());
+                labelled.namespace = Some(namespace.clone());
+                let mut other = create_test_artifact("other", create_embedding(0.2));
+                other.metadata.insert(key.clone(), format!("{}x", value));
+                db.insert_batch(&[labelled.clone(), other.clone()])
+                    .await
+                    .unwrap();
+
+                let ids = |artifacts: Vec<Artifact>| {
+                    artifacts.into_iter().map(|a| a.id).collect::<Vec<_>>()
+                };
+                let label = HashMap::from([(key.clone(), value.clone())]);
+                let with_label = SearchFilters {
+                    metadata: label.clone(),
+                    ..Default::default()
+                };
+                assert_eq!(ids(db.list(with_label).await.unwrap()), vec![labelled.id.clone()]);
+                let without_label = SearchFilters {
+                    metadata_not: label,
+                    ..Default::default()
+                };
+                assert_eq!(ids(db.list(without_label).await.unwrap()), vec![other.id.clone()]);
+                let in_namespace = SearchFilters {
+                    namespace: Some(namespace.clone()),
+                    ..Default::default()
+                };
+                assert_eq!(ids(db.list(in_namespace).await.unwrap()), vec![labelled.id.clone()]);
+            });
+        }
+    }
+
     // TDD: List filters by after timestamp
     #[tokio::test]
     async fn list_filters_by_after_timestamp() {
@@ -1008,6 +2383,35 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn reads_update_times_and_changed_artifacts_at_a_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+
+        let first = create_test_artifact("first", create_embedding(0.1));
+        let second = create_test_artifact("second", create_embedding(0.2));
+        db.insert(&first).await.unwrap();
+        db.insert(&second).await.unwrap();
+        let before = db.version().await.unwrap();
+        db.delete(&second.id).await.unwrap();
+
+        let updated = db.updated_at_version(before).await.unwrap();
+        assert_eq!(updated.len(), 2);
+        assert_eq!(
+            updated[&first.id],
+            db.get(&first.id).await.unwrap().unwrap().updated_at
+        );
+        assert_eq!(db.updated_at_version(before + 1).await.unwrap().len(), 1);
+
+        let ids = vec![second.id.clone(), "missing".to_string()];
+        let artifacts = db.get_many_at_version(&ids, before).await.unwrap();
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(artifacts[0].content, "second");
+        assert!(db.get(&second.id).await.unwrap().is_none());
+    }
+
     // TDD: get_at_version() retrieves historical state
     #[tokio::test]
     async fn get_at_version_retrieves_historical_state() {
@@ -1041,6 +2445,23 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn count_excludes_archived_artifacts() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test.lance");
+        let db = LanceDatabase::new(db_path.to_str().unwrap()).await.unwrap();
+        db.init().await.unwrap();
+        assert_eq!(db.count().await.unwrap(), 0);
+
+        let kept = create_test_artifact("kept", create_embedding(1.0));
+        let archived = create_test_artifact("archived", create_embedding(2.0));
+        db.insert(&kept).await.unwrap();
+        db.insert(&archived).await.unwrap();
+        db.archive(&archived.id).await.unwrap();
+
+        assert_eq!(db.count().await.unwrap(), 1);
+    }
+
     // TDD: compact() completes without error
     #[tokio::test]
     async fn compact_completes_without_error() {
@@ -0,0 +1,349 @@
+//! Single-writer lock for local stores.
+//!
+//! Any number of processes can read a LanceDB store, but two processes
+//! writing at once, such as the CLI next to an MCP server running in an
+//! editor, can trip over each other's commits. Writers to a local store hold
+//! a lock file next to it for the length of each write, including the
+//! migration run when a store written by an older release is first opened;
+//! plain reads never take it.
+//!
+//! A holder touches the lock file every [`REFRESH_EVERY`] for as long as it
+//! runs, so a lock whose file hasn't been touched for [`STALE_AFTER`] was
+//! abandoned and is taken over, whether or not it can be read. A lock is also
+//! taken over as soon as its holder's process is gone, but only when it was
+//! taken on this host: a CLI and a server in a container sharing the store
+//! can't see each other's processes.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long a writer waits for the lock before giving up
+pub const WAIT_FOR: Duration = Duration::from_secs(10);
+
+/// Time without a refresh after which a lock is treated as abandoned,
+/// whoever holds it
+pub const STALE_AFTER: Duration = Duration::from_secs(10 * 60);
+
+/// How often a holder touches its lock file to show it is still running
+pub const REFRESH_EVERY: Duration = Duration::from_secs(60);
+
+const RETRY_EVERY: Duration = Duration::from_millis(50);
+
+/// Error returned when another writer kept the store locked for longer than
+/// the wait allows
+#[derive(Debug, thiserror::Error)]
+#[error("store is locked for writing by {holder}; try again once it finishes, or delete {} if that process is gone", .path.display())]
+pub struct StoreLocked {
+    pub holder: String,
+    pub path: PathBuf,
+}
+
+/// What the lock file records about its holder
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Holder {
+    pid: u32,
+    /// Host the process runs on; its pid means nothing elsewhere
+    #[serde(default)]
+    host: String,
+    command: String,
+    acquired_at: DateTime<Utc>,
+}
+
+impl Holder {
+    fn current() -> Self {
+        let command = std::env::args()
+            .take(2)
+            .map(|arg| {
+                Path::new(&arg)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or(arg)
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self {
+            pid: std::process::id(),
+            host: hostname(),
+            command,
+            acquired_at: Utc::now(),
+        }
+    }
+
+    /// Whether the lock was abandoned, given how long ago its file was last
+    /// touched
+    fn is_stale(&self, idle: Duration) -> bool {
+        let local = !self.host.is_empty() && self.host == hostname();
+        idle > STALE_AFTER || (local && !process_alive(self.pid))
+    }
+
+    fn describe(&self) -> String {
+        let host = match self.host.as_str() {
+            "" => String::new(),
+            host => format!(" on {}", host),
+        };
+        format!(
+            "'{}' (pid {}{}) since {}",
+            self.command,
+            self.pid,
+            host,
+            self.acquired_at.format("%H:%M:%S")
+        )
+    }
+}
+
+/// Name of this host, or empty where it can't be read without extra
+/// dependencies, which leaves process checks to the host that can.
+fn hostname() -> String {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .map(|name| name.trim().to_string())
+            .unwrap_or_default()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        String::new()
+    }
+}
+
+/// Whether a process with `pid` is running on this machine.
+///
+/// Only Linux can tell without extra dependencies; elsewhere a lock is only
+/// stale once it is older than [`STALE_AFTER`].
+fn process_alive(pid: u32) -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        Path::new("/proc").join(pid.to_string()).exists()
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = pid;
+        true
+    }
+}
+
+/// Held writer lock, released when dropped
+#[derive(Debug)]
+pub struct WriterLock {
+    path: PathBuf,
+    holder: Holder,
+    refresh: tokio::task::JoinHandle<()>,
+}
+
+impl WriterLock {
+    /// Lock file guarding the local store at `uri`
+    pub fn path_for(uri: &str) -> PathBuf {
+        PathBuf::from(format!("{}.lock", uri.trim_end_matches('/')))
+    }
+
+    /// Take the lock at `path`, waiting up to `wait` for another writer to
+    /// finish and taking over locks that are stale.
+    ///
+    /// Fails with [`StoreLocked`] if the lock is still held after `wait`.
+    pub async fn acquire(path: &Path, wait: Duration) -> Result<Self> {
+        let started = std::time::Instant::now();
+        loop {
+            let holder = Holder::current();
+            match std::fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(path)
+            {
+                Ok(file) => {
+                    serde_json::to_writer(file, &holder)
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                    let refresh = tokio::spawn(refresh(path.to_path_buf(), holder.clone()));
+                    return Ok(Self {
+                        path: path.to_path_buf(),
+                        holder,
+                        refresh,
+                    });
+                },
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {},
+                Err(e) => {
+                    return Err(e).with_context(|| format!("Failed to create {}", path.display()));
+                },
+            }
+
+            let current = read_holder(path);
+            let idle = modified_since(path);
+            match &current {
+                Some(holder) if holder.is_stale(idle.unwrap_or_default()) => {
+                    tracing::warn!("Taking over stale store lock held by {}", holder.describe());
+                    // Only remove the lock we judged stale, not a fresh one
+                    // another writer took in the meantime
+                    if read_holder(path).as_ref() == current.as_ref() {
+                        let _ = std::fs::remove_file(path);
+                    }
+                    continue;
+                },
+                // Empty or corrupt, left by a writer that died before
+                // finishing the file
+                None if idle.is_some_and(|idle| idle > STALE_AFTER) => {
+                    tracing::warn!("Taking over unreadable store lock {}", path.display());
+                    if read_holder(path).is_none() {
+                        let _ = std::fs::remove_file(path);
+                    }
+                    continue;
+                },
+                // Gone already, or still being written by its new holder
+                _ => {},
+            }
+
+            if started.elapsed() >= wait {
+                return Err(StoreLocked {
+                    holder: current
+                        .map(|holder| holder.describe())
+                        .unwrap_or_else(|| "another process".to_string()),
+                    path: path.to_path_buf(),
+                }
+                .into());
+            }
+            tokio::time::sleep(RETRY_EVERY).await;
+        }
+    }
+}
+
+impl Drop for WriterLock {
+    fn drop(&mut self) {
+        self.refresh.abort();
+        // Leave the file alone if the lock was taken over as stale
+        if read_holder(&self.path).as_ref() == Some(&self.holder) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Touch the lock file every [`REFRESH_EVERY`] while `holder` still owns it
+async fn refresh(path: PathBuf, holder: Holder) {
+    loop {
+        tokio::time::sleep(REFRESH_EVERY).await;
+        if read_holder(&path).as_ref() != Some(&holder) {
+            return;
+        }
+        let touched = std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(std::time::SystemTime::now()));
+        if let Err(e) = touched {
+            tracing::warn!("Failed to refresh store lock {}: {}", path.display(), e);
+        }
+    }
+}
+
+fn read_holder(path: &Path) -> Option<Holder> {
+    let text = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+/// How long ago the file at `path` was last written
+fn modified_since(path: &Path) -> Option<Duration> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    modified.elapsed().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn second_writer_waits_then_gives_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = WriterLock::path_for(dir.path().join("store.lance").to_str().unwrap());
+
+        let held = WriterLock::acquire(&path, WAIT_FOR).await.unwrap();
+        let err = WriterLock::acquire(&path, Duration::from_millis(120))
+            .await
+            .unwrap_err();
+        let locked = err.downcast_ref::<StoreLocked>().unwrap();
+        assert!(locked.holder.contains(&std::process::id().to_string()));
+
+        drop(held);
+        assert!(!path.exists());
+        let _again = WriterLock::acquire(&path, Duration::ZERO).await.unwrap();
+        assert!(path.exists());
+    }
+
+    fn write_lock(path: &Path, holder: &Holder, idle: Duration) {
+        std::fs::write(path, serde_json::to_string(holder).unwrap()).unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - idle)
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn stale_lock_is_taken_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.lance.lock");
+        write_lock(&path, &Holder::current(), STALE_AFTER * 2);
+
+        let lock = WriterLock::acquire(&path, Duration::ZERO).await.unwrap();
+        assert_eq!(read_holder(&path), Some(lock.holder.clone()));
+    }
+
+    #[tokio::test]
+    async fn refreshed_lock_is_kept_however_long_it_is_held() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.lance.lock");
+        let migrating = Holder {
+            acquired_at: Utc::now() - chrono::Duration::hours(1),
+            ..Holder::current()
+        };
+        write_lock(&path, &migrating, REFRESH_EVERY);
+
+        let err = WriterLock::acquire(&path, Duration::ZERO)
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<StoreLocked>().is_some());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn exited_holder_is_only_judged_by_pid_on_its_own_host() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.lance.lock");
+        let exited = Holder {
+            pid: u32::MAX,
+            ..Holder::current()
+        };
+
+        // Taken on another host, where that pid may well be running
+        let remote = Holder {
+            host: "container-1".to_string(),
+            ..exited.clone()
+        };
+        write_lock(&path, &remote, Duration::ZERO);
+        let err = WriterLock::acquire(&path, Duration::ZERO)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("on container-1"));
+
+        write_lock(&path, &exited, Duration::ZERO);
+        let lock = WriterLock::acquire(&path, Duration::ZERO).await.unwrap();
+        assert_eq!(read_holder(&path), Some(lock.holder.clone()));
+    }
+
+    #[tokio::test]
+    async fn unreadable_lock_is_taken_over_once_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("store.lance.lock");
+        let file = std::fs::File::create(&path).unwrap();
+
+        // Just created: its writer may still be filling it in
+        let err = WriterLock::acquire(&path, Duration::ZERO)
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<StoreLocked>().is_some());
+
+        file.set_modified(std::time::SystemTime::now() - STALE_AFTER * 2)
+            .unwrap();
+        let lock = WriterLock::acquire(&path, Duration::ZERO).await.unwrap();
+        assert_eq!(read_holder(&path), Some(lock.holder.clone()));
+    }
+}
@@ -0,0 +1,372 @@
+//! Versioned schema migrations for the Lance store.
+//!
+//! The schema version is recorded in a small key/value table next to the
+//! artifacts. Stores written before versions were recorded are identified by
+//! the columns their artifacts table has. Migrating rewrites each artifact
+//! table in the current layout with new columns left empty, apart from row
+//! checksums, which are computed from the rows as they are, and labels,
+//! which are written back in the current encoding. The same rewrite converts
+//! stored embeddings when the configured precision changes.
+
+use super::filter::Predicate;
+use super::lance::{LanceDatabase, ARCHIVE_TABLE_NAME, TABLE_NAME};
+use super::schema;
+use crate::services::EmbeddingPrecision;
+use anyhow::{Context, Result};
+use arrow_array::{cast::AsArray, Array, ArrayRef, RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::database::CreateTableMode;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use std::sync::Arc;
+
+/// Table holding store-level metadata such as the schema version
+pub const META_TABLE_NAME: &str = "dna_meta";
+
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// A step in the evolution of the artifacts table layout
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    /// Columns introduced by this version
+    pub columns: &'static [&'static str],
+}
+
+/// All schema versions, oldest first. Append new versions here.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "initial artifacts layout",
+        columns: &[
+            "id",
+            "kind",
+            "name",
+            "content",
+            "format",
+            "metadata",
+            "embedding",
+            "embedding_model",
+            "created_at",
+            "updated_at",
+        ],
+    },
+    Migration {
+        version: 2,
+        description: "add context and context_embedding columns",
+        columns: &["context", "context_embedding"],
+    },
+    Migration {
+        version: 3,
+        description: "add checksum column",
+        columns: &["checksum"],
+    },
+    Migration {
+        version: 4,
+        description: "add namespace column",
+        columns: &["namespace"],
+    },
+    Migration {
+        version: 5,
+        description: "add aliases column",
+        columns: &["aliases"],
+    },
+    Migration {
+        version: 6,
+        description: "escape commas in labels so label filters match whole entries",
+        columns: &[],
+    },
+    Migration {
+        version: 7,
+        description: "add compressed column for zstd-compressed content and context",
+        columns: &["compressed"],
+    },
+    Migration {
+        version: 8,
+        description: "add attachment_text and attachment_embedding columns",
+        columns: &["attachment_text", "attachment_embedding"],
+    },
+    Migration {
+        version: 9,
+        description: "add embedding_text column recording how content was composed for embedding",
+        columns: &["embedding_text"],
+    },
+    Migration {
+        version: 10,
+        description: "add owner and reviewers columns",
+        columns: &["owner", "reviewers"],
+    },
+];
+
+/// Schema version written by this build
+pub fn current_version() -> u32 {
+    MIGRATIONS.last().map_or(0, |m| m.version)
+}
+
+/// Infer the version of an artifacts table that has no recorded version.
+///
+/// Returns the newest version whose columns (and those of every earlier
+/// version) are all present.
+pub fn detect_version(schema: &Schema) -> u32 {
+    let mut version = 0;
+    for migration in MIGRATIONS {
+        if migration
+            .columns
+            .iter()
+            .all(|c| schema.field_with_name(c).is_ok())
+        {
+            version = migration.version;
+        } else {
+            break;
+        }
+    }
+    version
+}
+
+/// Migrations needed to bring a store at `from` up to date
+pub fn pending(from: u32) -> Vec<&'static Migration> {
+    MIGRATIONS.iter().filter(|m| m.version > from).collect()
+}
+
+/// Outcome of checking a store's schema on connect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationReport {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl MigrationReport {
+    /// Whether any table had to be rewritten
+    pub fn migrated(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+/// Bring the tables behind `db` up to [`current_version`], storing embeddings
+/// at `precision`.
+///
+/// A store without an artifacts table is left untouched. Stores recorded with
+/// a newer version than this build understands are rejected rather than
+/// guessed at. Callers hold the writer lock of a local store, and the recorded
+/// version is read again here, so a store another process migrated while
+/// the lock was awaited is not rewritten twice.
+pub async fn migrate(
+    db: &lancedb::Connection,
+    precision: EmbeddingPrecision,
+) -> Result<MigrationReport> {
+    let current = current_version();
+    let table_names = db.table_names().execute().await?;
+    if !table_names.iter().any(|n| n == TABLE_NAME) {
+        return Ok(MigrationReport {
+            from: current,
+            to: current,
+        });
+    }
+
+    let table = db
+        .open_table(TABLE_NAME)
+        .execute()
+        .await
+        .context("Failed to open artifacts table")?;
+    let table_schema = table.schema().await?;
+    let stored_precision = schema::embedding_precision(&table_schema);
+
+    let recorded = read_version(db).await?;
+    let from = recorded.unwrap_or_else(|| detect_version(&table_schema));
+
+    if from > current {
+        anyhow::bail!(
+            "Store uses schema version {} but this version of dna only supports up to {}. \
+             Upgrade dna to use this store.",
+            from,
+            current
+        );
+    }
+
+    let steps = pending(from);
+    for step in &steps {
+        tracing::info!(
+            "Migrating store to schema v{}: {}",
+            step.version,
+            step.description
+        );
+    }
+    if stored_precision != precision {
+        tracing::info!(
+            "Converting stored embeddings from {} to {}",
+            stored_precision,
+            precision
+        );
+    }
+    if !steps.is_empty() || stored_precision != precision {
+        for name in [TABLE_NAME, ARCHIVE_TABLE_NAME] {
+            if table_names.iter().any(|n| n == name) {
+                rewrite_table(db, name, precision).await?;
+            }
+        }
+    }
+
+    if recorded != Some(current) {
+        write_version(db, current).await?;
+    }
+
+    Ok(MigrationReport { from, to: current })
+}
+
+/// Whether opening the store behind `db` needs no migration: it has no
+/// artifacts table yet, or is recorded at [`current_version`] with embeddings
+/// stored at `precision`.
+///
+/// Lets readers skip the writer lock that [`migrate`] must run under.
+pub async fn is_current(db: &lancedb::Connection, precision: EmbeddingPrecision) -> Result<bool> {
+    let table_names = db.table_names().execute().await?;
+    if !table_names.iter().any(|n| n == TABLE_NAME) {
+        return Ok(true);
+    }
+    if read_version(db).await? != Some(current_version()) {
+        return Ok(false);
+    }
+    let table = db
+        .open_table(TABLE_NAME)
+        .execute()
+        .await
+        .context("Failed to open artifacts table")?;
+    let table_schema = table.schema().await?;
+    Ok(schema::embedding_precision(&table_schema) == precision)
+}
+
+/// Read the recorded schema version, if the store has one
+pub async fn read_version(db: &lancedb::Connection) -> Result<Option<u32>> {
+    let table_names = db.table_names().execute().await?;
+    if !table_names.iter().any(|n| n == META_TABLE_NAME) {
+        return Ok(None);
+    }
+
+    let table = db
+        .open_table(META_TABLE_NAME)
+        .execute()
+        .await
+        .context("Failed to open metadata table")?;
+    let mut stream = table
+        .query()
+        .only_if(Predicate::eq("key", SCHEMA_VERSION_KEY).to_sql()?)
+        .execute()
+        .await?;
+
+    while let Some(batch) = stream.try_next().await? {
+        let values = batch
+            .column_by_name("value")
+            .context("Metadata table has no value column")?
+            .as_string::<i32>();
+        if values.len() > 0 {
+            let version = values
+                .value(0)
+                .parse()
+                .context("Recorded schema version is not a number")?;
+            return Ok(Some(version));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Record the schema version, replacing the metadata table
+pub(super) async fn write_version(db: &lancedb::Connection, version: u32) -> Result<()> {
+    let meta_schema = Arc::new(Schema::new(vec![
+        Field::new("key", DataType::Utf8, false),
+        Field::new("value", DataType::Utf8, false),
+    ]));
+    let keys: ArrayRef = Arc::new(StringArray::from(vec![SCHEMA_VERSION_KEY]));
+    let values: ArrayRef = Arc::new(StringArray::from(vec![version.to_string()]));
+    let batch = RecordBatch::try_new(meta_schema.clone(), vec![keys, values])
+        .context("Failed to build metadata batch")?;
+
+    db.create_table(
+        META_TABLE_NAME,
+        RecordBatchIterator::new(vec![Ok(batch)], meta_schema),
+    )
+    .mode(CreateTableMode::Overwrite)
+    .execute()
+    .await
+    .context("Failed to record schema version")?;
+    Ok(())
+}
+
+/// Rewrite a table in the current layout, keeping its rows, structured field
+/// columns and extra model embeddings
+async fn rewrite_table(
+    db: &lancedb::Connection,
+    name: &str,
+    precision: EmbeddingPrecision,
+) -> Result<()> {
+    let table = db
+        .open_table(name)
+        .execute()
+        .await
+        .with_context(|| format!("Failed to open table '{}'", name))?;
+    let table_schema = table.schema().await?;
+    let fields = schema::field_names(&table_schema);
+    let models = schema::model_columns(&table_schema);
+
+    let mut stream = table.query().execute().await?;
+    let mut artifacts = Vec::new();
+    while let Some(batch) = stream.try_next().await? {
+        artifacts.extend(LanceDatabase::batch_to_artifacts(&batch)?);
+    }
+
+    let batch = schema::artifacts_to_batch_with_models(&artifacts, &fields, &models, precision)?;
+    let batch_schema = batch.schema();
+    db.create_table(
+        name,
+        RecordBatchIterator::new(vec![Ok(batch)], batch_schema),
+    )
+    .mode(CreateTableMode::Overwrite)
+    .execute()
+    .await
+    .with_context(|| format!("Failed to migrate table '{}'", name))?;
+
+    tracing::info!("Migrated {} rows in table '{}'", artifacts.len(), name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_sequential() {
+        for (i, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version as usize, i + 1);
+        }
+    }
+
+    #[test]
+    fn current_schema_is_current_version() {
+        assert_eq!(detect_version(&schema::create_schema()), current_version());
+    }
+
+    #[test]
+    fn detects_layout_without_context_columns() {
+        let full = schema::create_schema();
+        let old = Schema::new(
+            full.fields()
+                .iter()
+                .filter(|f| {
+                    !f.name().starts_with("context")
+                        && f.name() != "checksum"
+                        && f.name() != "namespace"
+                        && f.name() != "aliases"
+                        && f.name() != "compressed"
+                })
+                .map(|f| f.as_ref().clone())
+                .collect::<Vec<_>>(),
+        );
+        assert_eq!(detect_version(&old), 1);
+        assert_eq!(
+            pending(1).iter().map(|m| m.version).collect::<Vec<_>>(),
+            vec![2, 3, 4, 5, 6, 7, 8, 9, 10]
+        );
+        assert!(pending(current_version()).is_empty());
+    }
+}
@@ -1,9 +1,28 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod bench;
+#[cfg(feature = "lance")]
+pub mod filter;
+#[cfg(feature = "lance")]
+pub mod fsck;
+#[cfg(feature = "lance")]
 pub mod lance;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod lock;
+#[cfg(feature = "lance")]
+pub mod migrations;
+pub mod remote;
+#[cfg(feature = "lance")]
 pub mod schema;
 
+#[cfg(feature = "lance")]
+pub use fsck::{FsckIssue, FsckProblem, FsckReport, QUARANTINE_TABLE_NAME};
+#[cfg(not(target_arch = "wasm32"))]
+pub use lock::StoreLocked;
+
 use crate::services::{Artifact, SearchFilters, SearchResult};
 use anyhow::Result;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
 
 /// Information about a database version
 #[derive(Debug, Clone)]
@@ -26,15 +45,108 @@ pub struct CleanupStats {
     pub bytes_freed: u64,
 }
 
-/// Database trait for artifact storage
-#[async_trait::async_trait]
+/// Error returned when an ID prefix matches more than one artifact
+#[derive(Debug, thiserror::Error)]
+#[error("ambiguous ID prefix '{prefix}' matches {} artifacts: {}", .candidates.len(), .candidates.join(", "))]
+pub struct AmbiguousPrefix {
+    pub prefix: String,
+    pub candidates: Vec<String>,
+}
+
+/// Error returned when stored data cannot be read back as artifacts
+#[derive(Debug, thiserror::Error)]
+#[error("storage corruption: {0}")]
+pub struct StorageCorruption(pub String);
+
+/// Whether a structured field name can be stored as a column.
+///
+/// Names are lowercase identifiers so they can be used unquoted in filters.
+pub fn is_valid_field_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// A model name reduced to letters, digits and underscores, for its column name
+pub fn model_slug(model: &str) -> String {
+    model
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Pick the single ID that `prefix` identifies among `candidates`.
+///
+/// An exact match always wins. Returns `Ok(None)` when nothing starts with
+/// the prefix and [`AmbiguousPrefix`] when several IDs do.
+pub fn match_prefix<I>(prefix: &str, candidates: I) -> Result<Option<String>>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut matches: Vec<String> = candidates
+        .into_iter()
+        .filter(|id| id.starts_with(prefix))
+        .collect();
+
+    if matches.iter().any(|id| id == prefix) {
+        return Ok(Some(prefix.to_string()));
+    }
+
+    match matches.len() {
+        0 => Ok(None),
+        1 => Ok(matches.pop()),
+        _ => {
+            matches.sort();
+            Err(AmbiguousPrefix {
+                prefix: prefix.to_string(),
+                candidates: matches,
+            }
+            .into())
+        },
+    }
+}
+
+/// Database trait for artifact storage.
+///
+/// On wasm32 the futures are not `Send`, since browser I/O is single-threaded.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
 pub trait Database: Send + Sync {
     /// Insert a new artifact
     async fn insert(&self, artifact: &Artifact) -> Result<()>;
 
+    /// Insert several new artifacts.
+    ///
+    /// Stores that can write them together do so as one version; by default
+    /// they are inserted one at a time.
+    async fn insert_batch(&self, artifacts: &[Artifact]) -> Result<()> {
+        for artifact in artifacts {
+            self.insert(artifact).await?;
+        }
+        Ok(())
+    }
+
     /// Get an artifact by ID
     async fn get(&self, id: &str) -> Result<Option<Artifact>>;
 
+    /// Get the active artifacts with any of `ids`, in no particular order.
+    ///
+    /// IDs with no artifact are left out. Stores that can look them up
+    /// together do so in one query; by default they are fetched one at a time.
+    async fn get_many(&self, ids: &[String]) -> Result<Vec<Artifact>> {
+        let mut artifacts = Vec::with_capacity(ids.len());
+        for id in ids {
+            artifacts.extend(self.get(id).await?);
+        }
+        Ok(artifacts)
+    }
+
     /// Update an existing artifact
     async fn update(&self, artifact: &Artifact) -> Result<()>;
 
@@ -51,6 +163,25 @@ pub trait Database: Send + Sync {
         filters: SearchFilters,
     ) -> Result<Vec<SearchResult>>;
 
+    /// Give every artifact of kind `from` the kind `to`, returning how many
+    /// changed.
+    ///
+    /// Stores that can do so rewrite archived artifacts too, together; by
+    /// default only active artifacts are updated, one at a time.
+    async fn rename_kind(&self, from: &str, to: &str) -> Result<usize> {
+        let filters = SearchFilters {
+            kind: Some(from.to_string()),
+            ..Default::default()
+        };
+        let artifacts = self.list(filters).await?;
+        for artifact in &artifacts {
+            let mut artifact = artifact.clone();
+            artifact.kind = to.to_string();
+            self.update(&artifact).await?;
+        }
+        Ok(artifacts.len())
+    }
+
     /// Get the current database version number
     async fn version(&self) -> Result<u64>;
 
@@ -65,4 +196,146 @@ pub trait Database: Send + Sync {
 
     /// Cleanup old versions, keeping the specified number of recent versions
     async fn cleanup_versions(&self, keep_versions: usize) -> Result<CleanupStats>;
+
+    /// Semantic search against context embeddings.
+    ///
+    /// Stores that keep text extracted from attachments search it alongside
+    /// the context. Artifacts with neither are never returned.
+    async fn search_context(
+        &self,
+        query_embedding: &[f32],
+        filters: SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
+        let _ = (query_embedding, filters);
+        anyhow::bail!("context search is not supported by this database")
+    }
+
+    /// Semantic search against the content embeddings of one of the
+    /// `[[extra_models]]`, named by its [`model_slug`].
+    ///
+    /// Artifacts without an embedding from that model are never returned.
+    async fn search_model(
+        &self,
+        model: &str,
+        query_embedding: &[f32],
+        filters: SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
+        let _ = (model, query_embedding, filters);
+        anyhow::bail!("searching other models' embeddings is not supported by this database")
+    }
+
+    /// Move an artifact out of the active table into the archive.
+    ///
+    /// Archived artifacts are hidden from `get`, `list` and `search` unless
+    /// `SearchFilters::include_archived` is set. Returns false if no active
+    /// artifact has this ID.
+    async fn archive(&self, id: &str) -> Result<bool> {
+        let _ = id;
+        anyhow::bail!("archiving is not supported by this database")
+    }
+
+    /// All active artifacts as they were at a version
+    async fn list_at_version(&self, version: u64) -> Result<Vec<Artifact>> {
+        let _ = version;
+        anyhow::bail!("reading past versions is not supported by this database")
+    }
+
+    /// When each active artifact was last updated, by ID, as of a version.
+    ///
+    /// Enough to tell which artifacts changed between two versions. Stores
+    /// that can read just those columns do; by default whole artifacts are
+    /// read.
+    async fn updated_at_version(&self, version: u64) -> Result<HashMap<String, DateTime<Utc>>> {
+        Ok(self
+            .list_at_version(version)
+            .await?
+            .into_iter()
+            .map(|a| (a.id, a.updated_at))
+            .collect())
+    }
+
+    /// The active artifacts with any of `ids` as they were at a version, in
+    /// no particular order.
+    ///
+    /// IDs with no artifact at that version are left out. Stores that can
+    /// look them up together do so in one query; by default they are fetched
+    /// one at a time.
+    async fn get_many_at_version(&self, ids: &[String], version: u64) -> Result<Vec<Artifact>> {
+        let mut artifacts = Vec::with_capacity(ids.len());
+        for id in ids {
+            artifacts.extend(self.get_at_version(id, version).await?);
+        }
+        Ok(artifacts)
+    }
+
+    /// Roll the active table back to an earlier version.
+    ///
+    /// The restore is recorded as a new version, so the history between
+    /// `version` and now stays available to `get_at_version`.
+    async fn restore(&self, version: u64) -> Result<()> {
+        let _ = version;
+        anyhow::bail!("restoring versions is not supported by this database")
+    }
+
+    /// Resolve a unique artifact ID from a (possibly partial) ID prefix.
+    ///
+    /// Returns `Ok(None)` if no artifact matches and an [`AmbiguousPrefix`]
+    /// error listing the candidates if more than one does.
+    async fn resolve_prefix(&self, prefix: &str) -> Result<Option<String>> {
+        let ids = self
+            .list(SearchFilters::default())
+            .await?
+            .into_iter()
+            .map(|a| a.id);
+        match_prefix(prefix, ids)
+    }
+
+    /// The active artifact that lists `alias` among its former IDs
+    async fn find_by_alias(&self, alias: &str) -> Result<Option<Artifact>> {
+        Ok(self
+            .list(SearchFilters::default())
+            .await?
+            .into_iter()
+            .find(|a| a.aliases.iter().any(|id| id == alias)))
+    }
+
+    /// Number of active (non-archived) artifacts
+    async fn count(&self) -> Result<usize> {
+        Ok(self.list(SearchFilters::default()).await?.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn match_prefix_returns_unique_match() {
+        let result = match_prefix("k7", ids(&["k7v3m9xnp2", "abcdefghjk"])).unwrap();
+        assert_eq!(result, Some("k7v3m9xnp2".to_string()));
+    }
+
+    #[test]
+    fn match_prefix_returns_none_without_match() {
+        let result = match_prefix("zz", ids(&["k7v3m9xnp2"])).unwrap();
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn match_prefix_prefers_exact_match() {
+        let result = match_prefix("k7v3", ids(&["k7v3", "k7v3m9xnp2"])).unwrap();
+        assert_eq!(result, Some("k7v3".to_string()));
+    }
+
+    #[test]
+    fn match_prefix_errors_on_ambiguity_with_candidates() {
+        let err = match_prefix("k7", ids(&["k7zzzzzzzz", "k7v3m9xnp2"])).unwrap_err();
+        let ambiguous = err.downcast_ref::<AmbiguousPrefix>().unwrap();
+        assert_eq!(ambiguous.candidates, ids(&["k7v3m9xnp2", "k7zzzzzzzz"]));
+        assert!(err.to_string().contains("k7v3m9xnp2, k7zzzzzzzz"));
+    }
 }
@@ -0,0 +1,366 @@
+//! Read-only store over a serialized search index.
+//!
+//! `dna render --index` writes the active artifacts and their embeddings to
+//! `index.json` next to the rendered markdown. [`RemoteDatabase`] fetches that
+//! file once and answers reads and searches in memory, so a static site or a
+//! browser extension built for wasm32 can search without a server. How the
+//! file is fetched is up to the [`Fetch`] implementation; [`HttpFetch`] uses
+//! reqwest, which goes through the browser's `fetch` on wasm32.
+
+use super::{CleanupStats, CompactStats, Database, VersionInfo};
+use crate::services::snippet::cosine_similarity;
+use crate::services::{Artifact, SearchFilters, SearchResult};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, OnceLock};
+
+/// Version of the [`SearchIndex`] layout written by this build
+pub const INDEX_FORMAT_VERSION: u32 = 1;
+
+/// Artifacts and embeddings serialized for client-side search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchIndex {
+    pub format_version: u32,
+    /// Model that produced the embeddings; queries must be embedded with it too
+    pub embedding_model: String,
+    pub artifacts: Vec<IndexedArtifact>,
+}
+
+/// An artifact with the embeddings its serialized form normally leaves out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexedArtifact {
+    #[serde(flatten)]
+    pub artifact: Artifact,
+    pub embedding: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_embedding: Option<Vec<f32>>,
+}
+
+impl SearchIndex {
+    /// Index artifacts, skipping any without an embedding
+    pub fn new(embedding_model: impl Into<String>, artifacts: Vec<Artifact>) -> Self {
+        let artifacts = artifacts
+            .into_iter()
+            .filter_map(|mut artifact| {
+                let embedding = artifact.embedding.take()?;
+                let context_embedding = artifact.context_embedding.take();
+                Some(IndexedArtifact {
+                    artifact,
+                    embedding,
+                    context_embedding,
+                })
+            })
+            .collect();
+        Self {
+            format_version: INDEX_FORMAT_VERSION,
+            embedding_model: embedding_model.into(),
+            artifacts,
+        }
+    }
+
+    /// Parse an index, rejecting layouts newer than this build understands
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let index: Self = serde_json::from_slice(bytes).context("Failed to parse search index")?;
+        if index.format_version > INDEX_FORMAT_VERSION {
+            anyhow::bail!(
+                "Search index uses format version {} but this version of dna only reads up to {}",
+                index.format_version,
+                INDEX_FORMAT_VERSION
+            );
+        }
+        Ok(index)
+    }
+
+    /// Artifacts with their embeddings restored
+    pub fn into_artifacts(self) -> Vec<Artifact> {
+        self.artifacts
+            .into_iter()
+            .map(|indexed| Artifact {
+                embedding: Some(indexed.embedding),
+                context_embedding: indexed.context_embedding,
+                ..indexed.artifact
+            })
+            .collect()
+    }
+}
+
+/// Source of the bytes behind a URL
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+pub trait Fetch: Send + Sync {
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>>;
+}
+
+/// Fetch over HTTP with reqwest
+#[derive(Default)]
+pub struct HttpFetch {
+    client: reqwest::Client,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Fetch for HttpFetch {
+    async fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch {}", url))?
+            .error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+}
+
+/// A [`Database`] backed by a [`SearchIndex`] fetched on first use.
+///
+/// Writes and version history are not supported.
+pub struct RemoteDatabase {
+    url: String,
+    fetch: Arc<dyn Fetch>,
+    artifacts: OnceLock<Vec<Artifact>>,
+}
+
+impl RemoteDatabase {
+    /// Read the index at `url` over HTTP
+    pub fn new(url: impl Into<String>) -> Self {
+        Self::with_fetch(url, Arc::new(HttpFetch::default()))
+    }
+
+    /// Read the index at `url` through a custom fetcher
+    pub fn with_fetch(url: impl Into<String>, fetch: Arc<dyn Fetch>) -> Self {
+        Self {
+            url: url.into(),
+            fetch,
+            artifacts: OnceLock::new(),
+        }
+    }
+
+    /// Serve an index that is already loaded
+    pub fn from_index(index: SearchIndex) -> Self {
+        let db = Self::with_fetch(String::new(), Arc::new(HttpFetch::default()));
+        let _ = db.artifacts.set(index.into_artifacts());
+        db
+    }
+
+    async fn artifacts(&self) -> Result<&[Artifact]> {
+        if let Some(artifacts) = self.artifacts.get() {
+            return Ok(artifacts);
+        }
+        let bytes = self.fetch.fetch(&self.url).await?;
+        let index = SearchIndex::from_slice(&bytes)?;
+        Ok(self.artifacts.get_or_init(|| index.into_artifacts()))
+    }
+
+    async fn matching(&self, filters: &SearchFilters) -> Result<Vec<&Artifact>> {
+        Ok(self
+            .artifacts()
+            .await?
+            .iter()
+            .filter(|a| filters.matches(a))
+            .collect())
+    }
+
+    async fn rank(
+        &self,
+        query_embedding: &[f32],
+        filters: SearchFilters,
+        vector: fn(&Artifact) -> Option<&Vec<f32>>,
+    ) -> Result<Vec<SearchResult>> {
+        let mut results: Vec<SearchResult> = self
+            .matching(&filters)
+            .await?
+            .into_iter()
+            .filter_map(|artifact| {
+                let score = cosine_similarity(query_embedding, vector(artifact)?);
+                Some(SearchResult {
+                    artifact: artifact.clone(),
+                    score,
+                })
+            })
+            .collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(filters.limit.unwrap_or(usize::MAX));
+        Ok(results)
+    }
+}
+
+fn read_only() -> anyhow::Error {
+    anyhow::anyhow!("A remote search index is read-only")
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Database for RemoteDatabase {
+    async fn insert(&self, _artifact: &Artifact) -> Result<()> {
+        Err(read_only())
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Artifact>> {
+        Ok(self.artifacts().await?.iter().find(|a| a.id == id).cloned())
+    }
+
+    async fn update(&self, _artifact: &Artifact) -> Result<()> {
+        Err(read_only())
+    }
+
+    async fn delete(&self, _id: &str) -> Result<bool> {
+        Err(read_only())
+    }
+
+    async fn list(&self, filters: SearchFilters) -> Result<Vec<Artifact>> {
+        Ok(self
+            .matching(&filters)
+            .await?
+            .into_iter()
+            .take(filters.limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect())
+    }
+
+    async fn search(
+        &self,
+        query_embedding: &[f32],
+        filters: SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
+        self.rank(query_embedding, filters, |a| a.embedding.as_ref())
+            .await
+    }
+
+    async fn search_context(
+        &self,
+        query_embedding: &[f32],
+        filters: SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
+        self.rank(query_embedding, filters, |a| a.context_embedding.as_ref())
+            .await
+    }
+
+    /// An index is a single snapshot, so it only ever has version 1
+    async fn version(&self) -> Result<u64> {
+        Ok(1)
+    }
+
+    async fn get_at_version(&self, id: &str, version: u64) -> Result<Option<Artifact>> {
+        if version == 1 {
+            self.get(id).await
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn list_versions(&self, _limit: Option<usize>) -> Result<Vec<VersionInfo>> {
+        Ok(Vec::new())
+    }
+
+    async fn compact(&self) -> Result<CompactStats> {
+        Err(read_only())
+    }
+
+    async fn cleanup_versions(&self, _keep_versions: usize) -> Result<CleanupStats> {
+        Err(read_only())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn artifact(id: &str, kind: &str, embedding: Vec<f32>) -> Artifact {
+        let mut artifact = Artifact::new(
+            kind.to_string(),
+            format!("content of {}", id),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        artifact.id = id.to_string();
+        artifact.embedding = Some(embedding);
+        artifact
+    }
+
+    fn index() -> SearchIndex {
+        SearchIndex::new(
+            "model",
+            vec![
+                artifact("a", "intent", vec![1.0, 0.0]),
+                artifact("b", "contract", vec![0.0, 1.0]),
+                artifact("c", "intent", vec![0.7, 0.7]),
+            ],
+        )
+    }
+
+    /// Serves a fixed index and counts requests
+    struct StaticFetch {
+        body: Vec<u8>,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Fetch for StaticFetch {
+        async fn fetch(&self, _url: &str) -> Result<Vec<u8>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.body.clone())
+        }
+    }
+
+    #[test]
+    fn index_round_trips_embeddings() {
+        let json = serde_json::to_vec(&index()).unwrap();
+        let artifacts = SearchIndex::from_slice(&json).unwrap().into_artifacts();
+        assert_eq!(artifacts.len(), 3);
+        assert_eq!(artifacts[0].id, "a");
+        assert_eq!(artifacts[0].embedding, Some(vec![1.0, 0.0]));
+    }
+
+    #[test]
+    fn rejects_newer_index_formats() {
+        let mut newer = index();
+        newer.format_version = INDEX_FORMAT_VERSION + 1;
+        let json = serde_json::to_vec(&newer).unwrap();
+        assert!(SearchIndex::from_slice(&json).is_err());
+    }
+
+    #[tokio::test]
+    async fn fetches_once_and_searches_by_similarity() {
+        let fetch = Arc::new(StaticFetch {
+            body: serde_json::to_vec(&index()).unwrap(),
+            calls: AtomicUsize::new(0),
+        });
+        let db = RemoteDatabase::with_fetch("https://example.com/index.json", fetch.clone());
+
+        let results = db
+            .search(&[1.0, 0.1], SearchFilters::default())
+            .await
+            .unwrap();
+        let ids: Vec<_> = results.iter().map(|r| r.artifact.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "c", "b"]);
+
+        let intents = SearchFilters {
+            kind: Some("intent".to_string()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let results = db.search(&[0.0, 1.0], intents).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].artifact.id, "c");
+
+        assert!(db.get("b").await.unwrap().is_some());
+        assert_eq!(fetch.calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn rejects_writes() {
+        let db = RemoteDatabase::from_index(index());
+        let err = db
+            .insert(&artifact("d", "intent", vec![1.0, 0.0]))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("read-only"));
+        assert_eq!(db.count().await.unwrap(), 3);
+    }
+}
@@ -1,54 +1,468 @@
+use crate::services::EmbeddingPrecision;
+use anyhow::Context;
+use arrow_array::types::{Float16Type, Float32Type, Int8Type};
 use arrow_array::{
-    ArrayRef, FixedSizeListArray, Float32Array, RecordBatch, StringArray, TimestampMillisecondArray,
+    cast::AsArray, Array, ArrayRef, BooleanArray, FixedSizeListArray, Float16Array, Float32Array,
+    Int8Array, RecordBatch, StringArray, TimestampMillisecondArray,
 };
 use arrow_schema::{DataType, Field, Schema, TimeUnit};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::sync::Arc;
 
-const EMBEDDING_DIMENSION: i32 = 384;
+pub use super::{is_valid_field_name, model_slug};
+
+/// Length of every stored embedding vector
+pub const EMBEDDING_DIMENSION: i32 = 384;
+
+/// Column holding each row's [`row_checksum`]
+pub const CHECKSUM_COLUMN: &str = "checksum";
+
+/// Suffix of the per-vector scale column stored next to int8 embeddings
+pub const SCALE_COLUMN_SUFFIX: &str = "_scale";
+
+/// Column flagging rows whose content and context are stored compressed
+pub const COMPRESSED_COLUMN: &str = "compressed";
+
+/// Column holding text extracted from an artifact's attachments
+pub const ATTACHMENT_TEXT_COLUMN: &str = "attachment_text";
+
+/// Column holding the embedding of the attachment text
+pub const ATTACHMENT_EMBEDDING_COLUMN: &str = "attachment_embedding";
+
+/// Column holding the template the content embedding was composed with
+pub const EMBEDDING_TEXT_COLUMN: &str = "embedding_text";
+
+/// Column holding the person or team accountable for an artifact
+pub const OWNER_COLUMN: &str = "owner";
+
+/// Column holding who reviews changes to an artifact, as a JSON array
+pub const REVIEWERS_COLUMN: &str = "reviewers";
+
+/// Rows whose content, context and attachment text together exceed this many bytes are stored
+/// zstd-compressed, keeping large specs and design docs small on disk and in
+/// S3 scans
+pub const COMPRESSION_THRESHOLD: usize = 16 * 1024;
+
+/// Prefix for columns holding structured fields parsed from artifact content
+pub const FIELD_COLUMN_PREFIX: &str = "field_";
+
+/// Prefix for columns holding content embeddings from `[[extra_models]]`
+pub const MODEL_COLUMN_PREFIX: &str = "model_";
+
+/// Column name for a structured field
+pub fn field_column(name: &str) -> String {
+    format!("{}{}", FIELD_COLUMN_PREFIX, name)
+}
+
+/// Column name for the content embeddings of the model with slug `slug`
+pub fn model_column(slug: &str) -> String {
+    format!("{}{}", MODEL_COLUMN_PREFIX, slug)
+}
+
+/// Nullable f32 column for a model's embeddings of `dimension` values
+pub fn model_field(slug: &str, dimension: i32) -> Field {
+    Field::new(
+        model_column(slug),
+        DataType::FixedSizeList(
+            Arc::new(Field::new("item", DataType::Float32, true)),
+            dimension,
+        ),
+        true,
+    )
+}
+
+/// Model slugs stored in a schema with their embedding lengths, in column order
+pub fn model_columns(schema: &Schema) -> Vec<(String, i32)> {
+    schema
+        .fields()
+        .iter()
+        .filter_map(|f| match f.data_type() {
+            DataType::FixedSizeList(_, dimension) => f
+                .name()
+                .strip_prefix(MODEL_COLUMN_PREFIX)
+                .map(|slug| (slug.to_string(), *dimension)),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Structured field names stored in a schema, in column order
+pub fn field_names(schema: &Schema) -> Vec<String> {
+    schema
+        .fields()
+        .iter()
+        .filter_map(|f| f.name().strip_prefix(FIELD_COLUMN_PREFIX))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Labels as stored in the `metadata` column: a JSON object, with any
+/// commas in keys and values written as `\u002c`.
+///
+/// Outside its strings the JSON then has commas only between entries, so a
+/// label filter can look for `"key":"value"` between the braces and commas
+/// around entries and never match part of a longer key or value. JSON
+/// readers get the labels back unchanged.
+pub fn metadata_json(labels: &HashMap<String, String>) -> String {
+    let mut entries: Vec<_> = labels.iter().collect();
+    entries.sort();
+    let entries: Vec<String> = entries
+        .into_iter()
+        .map(|(key, value)| metadata_entry(key, value))
+        .collect();
+    format!("{{{}}}", entries.join(","))
+}
+
+/// One label as [`metadata_json`] writes it, e.g. `"team":"core"`
+pub fn metadata_entry(key: &str, value: &str) -> String {
+    format!("{}:{}", json_string(key), json_string(value))
+}
+
+fn json_string(value: &str) -> String {
+    serde_json::to_string(value)
+        .expect("strings always serialize")
+        .replace(',', "\\u002c")
+}
+
+/// Create the Arrow schema for artifacts with additional structured field columns
+pub fn create_schema_with_fields(fields: &[String]) -> Arc<Schema> {
+    create_schema_for(fields, EmbeddingPrecision::F32)
+}
 
 /// Create the Arrow schema for artifacts
 pub fn create_schema() -> Arc<Schema> {
-    Arc::new(Schema::new(vec![
+    create_schema_for(&[], EmbeddingPrecision::F32)
+}
+
+/// Create the Arrow schema for artifacts with structured field columns and
+/// embeddings stored at `precision`.
+///
+/// Int8 embeddings are followed by a `<column>_scale` column holding the
+/// factor that maps them back to floats.
+pub fn create_schema_for(fields: &[String], precision: EmbeddingPrecision) -> Arc<Schema> {
+    let mut columns = vec![
         Field::new("id", DataType::Utf8, false),
         Field::new("kind", DataType::Utf8, false),
         Field::new("name", DataType::Utf8, true),
         Field::new("content", DataType::Utf8, false),
         Field::new("format", DataType::Utf8, false),
         Field::new("metadata", DataType::Utf8, false), // JSON string
-        Field::new(
-            "embedding",
-            DataType::FixedSizeList(
-                Arc::new(Field::new("item", DataType::Float32, true)),
-                EMBEDDING_DIMENSION,
-            ),
-            false,
-        ),
-        Field::new("embedding_model", DataType::Utf8, false),
-        Field::new("context", DataType::Utf8, true),
-        Field::new(
-            "context_embedding",
-            DataType::FixedSizeList(
-                Arc::new(Field::new("item", DataType::Float32, true)),
-                EMBEDDING_DIMENSION,
-            ),
-            true,
-        ),
-        Field::new(
-            "created_at",
-            DataType::Timestamp(TimeUnit::Millisecond, None),
-            false,
-        ),
-        Field::new(
-            "updated_at",
-            DataType::Timestamp(TimeUnit::Millisecond, None),
-            false,
+    ];
+    columns.extend(embedding_fields("embedding", precision, false));
+    columns.push(Field::new("embedding_model", DataType::Utf8, false));
+    columns.push(Field::new("context", DataType::Utf8, true));
+    columns.extend(embedding_fields("context_embedding", precision, true));
+    columns.push(Field::new(
+        "created_at",
+        DataType::Timestamp(TimeUnit::Millisecond, None),
+        false,
+    ));
+    columns.push(Field::new(
+        "updated_at",
+        DataType::Timestamp(TimeUnit::Millisecond, None),
+        false,
+    ));
+    columns.push(Field::new(CHECKSUM_COLUMN, DataType::Utf8, true));
+    columns.push(Field::new("namespace", DataType::Utf8, true));
+    columns.push(Field::new("aliases", DataType::Utf8, true)); // JSON array
+    columns.push(Field::new(COMPRESSED_COLUMN, DataType::Boolean, true));
+    columns.push(Field::new(ATTACHMENT_TEXT_COLUMN, DataType::Utf8, true));
+    columns.extend(embedding_fields(
+        ATTACHMENT_EMBEDDING_COLUMN,
+        precision,
+        true,
+    ));
+    columns.push(Field::new(EMBEDDING_TEXT_COLUMN, DataType::Utf8, true));
+    columns.push(Field::new(OWNER_COLUMN, DataType::Utf8, true));
+    columns.push(Field::new(REVIEWERS_COLUMN, DataType::Utf8, true)); // JSON array
+    columns.extend(
+        fields
+            .iter()
+            .map(|name| Field::new(field_column(name), DataType::Utf8, true)),
+    );
+    Arc::new(Schema::new(columns))
+}
+
+/// Arrow element type for embeddings stored at `precision`
+fn embedding_item_type(precision: EmbeddingPrecision) -> DataType {
+    match precision {
+        EmbeddingPrecision::F32 => DataType::Float32,
+        EmbeddingPrecision::F16 => DataType::Float16,
+        EmbeddingPrecision::Int8 => DataType::Int8,
+    }
+}
+
+/// The vector column `name`, plus its scale column for int8
+fn embedding_fields(name: &str, precision: EmbeddingPrecision, nullable: bool) -> Vec<Field> {
+    let mut fields = vec![Field::new(
+        name,
+        DataType::FixedSizeList(
+            Arc::new(Field::new("item", embedding_item_type(precision), true)),
+            EMBEDDING_DIMENSION,
         ),
-    ]))
+        nullable,
+    )];
+    if precision == EmbeddingPrecision::Int8 {
+        fields.push(Field::new(
+            format!("{}{}", name, SCALE_COLUMN_SUFFIX),
+            DataType::Float32,
+            nullable,
+        ));
+    }
+    fields
+}
+
+/// Precision of the embeddings in a table, read from its schema
+pub fn embedding_precision(schema: &Schema) -> EmbeddingPrecision {
+    match schema.field_with_name("embedding").map(|f| f.data_type()) {
+        Ok(DataType::FixedSizeList(item, _)) => match item.data_type() {
+            DataType::Float16 => EmbeddingPrecision::F16,
+            DataType::Int8 => EmbeddingPrecision::Int8,
+            _ => EmbeddingPrecision::F32,
+        },
+        _ => EmbeddingPrecision::F32,
+    }
+}
+
+/// Quantize a vector to int8 with a symmetric per-vector scale
+pub fn quantize_int8(vector: &[f32]) -> (Vec<i8>, f32) {
+    let max = vector.iter().fold(0.0f32, |max, v| max.max(v.abs()));
+    if max == 0.0 {
+        return (vec![0; vector.len()], 0.0);
+    }
+    let scale = max / 127.0;
+    let quantized = vector
+        .iter()
+        .map(|v| (v / scale).round().clamp(-127.0, 127.0) as i8)
+        .collect();
+    (quantized, scale)
+}
+
+/// Map int8 values back to floats
+pub fn dequantize_int8(quantized: &[i8], scale: f32) -> Vec<f32> {
+    quantized.iter().map(|&q| f32::from(q) * scale).collect()
+}
+
+/// Read one row of an embedding column as `f32`, whatever precision it is stored at.
+///
+/// `scales` is the matching scale column, required for int8 embeddings.
+pub fn read_embedding(
+    column: &FixedSizeListArray,
+    scales: Option<&Float32Array>,
+    row: usize,
+) -> anyhow::Result<Vec<f32>> {
+    let values = column.value(row);
+    match values.data_type() {
+        DataType::Float32 => Ok(values.as_primitive::<Float32Type>().values().to_vec()),
+        DataType::Float16 => Ok(values
+            .as_primitive::<Float16Type>()
+            .values()
+            .iter()
+            .map(|v| v.to_f32())
+            .collect()),
+        DataType::Int8 => {
+            let scale = scales
+                .context("Missing scale column for int8 embeddings")?
+                .value(row);
+            Ok(dequantize_int8(
+                values.as_primitive::<Int8Type>().values(),
+                scale,
+            ))
+        },
+        other => Err(anyhow::anyhow!("Unsupported embedding type: {}", other)),
+    }
+}
+
+/// SHA-256 over everything stored for an artifact except its embeddings, as hex.
+///
+/// Only the structured fields in `fields` are covered, as the rest are not
+/// stored. Embeddings are left out because int8 and f16 storage is lossy;
+/// `dna fsck` checks their shape instead.
+pub fn row_checksum(artifact: &crate::services::Artifact, fields: &[String]) -> String {
+    let mut hasher = Sha256::new();
+    let mut put = |value: Option<&str>| match value {
+        Some(value) => {
+            hasher.update((value.len() as u64).to_le_bytes());
+            hasher.update(value.as_bytes());
+        },
+        None => hasher.update(u64::MAX.to_le_bytes()),
+    };
+
+    let format = artifact.format.to_string();
+    put(Some(&artifact.id));
+    put(Some(&artifact.kind));
+    put(artifact.name.as_deref());
+    put(Some(&artifact.content));
+    put(Some(&format));
+    let mut metadata: Vec<_> = artifact.metadata.iter().collect();
+    metadata.sort();
+    for (key, value) in metadata {
+        put(Some(key));
+        put(Some(value));
+    }
+    put(None);
+    put(Some(&artifact.embedding_model));
+    put(artifact.context.as_deref());
+    put(Some(&artifact.created_at.timestamp_millis().to_string()));
+    put(Some(&artifact.updated_at.timestamp_millis().to_string()));
+    for name in fields {
+        put(artifact.fields.get(name).map(String::as_str));
+    }
+    // Only when set, so rows from before namespaces keep their checksums
+    if let Some(namespace) = &artifact.namespace {
+        put(Some("namespace"));
+        put(Some(namespace));
+    }
+    if !artifact.aliases.is_empty() {
+        put(Some("aliases"));
+        for alias in &artifact.aliases {
+            put(Some(alias));
+        }
+    }
+    if let Some(template) = &artifact.embedding_text {
+        put(Some("embedding_text"));
+        put(Some(template));
+    }
+    if let Some(owner) = &artifact.owner {
+        put(Some("owner"));
+        put(Some(owner));
+    }
+    if !artifact.reviewers.is_empty() {
+        put(Some("reviewers"));
+        for reviewer in &artifact.reviewers {
+            put(Some(reviewer));
+        }
+    }
+
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Whether an artifact's content, context and attachment text are large
+/// enough to compress
+pub fn should_compress(artifact: &crate::services::Artifact) -> bool {
+    artifact.content.len()
+        + artifact.context.as_ref().map_or(0, String::len)
+        + artifact.attachment_text.as_ref().map_or(0, String::len)
+        > COMPRESSION_THRESHOLD
+}
+
+/// Compress text with zstd, as base64 so it fits the string columns
+pub fn compress(text: &str) -> anyhow::Result<String> {
+    let compressed = zstd::encode_all(text.as_bytes(), 0).context("Failed to compress text")?;
+    Ok(BASE64.encode(compressed))
+}
+
+/// Reverse [`compress`]
+pub fn decompress(stored: &str) -> anyhow::Result<String> {
+    let compressed = BASE64
+        .decode(stored)
+        .context("Compressed text is not valid base64")?;
+    let text = zstd::decode_all(compressed.as_slice()).context("Failed to decompress text")?;
+    String::from_utf8(text).context("Decompressed text is not valid UTF-8")
+}
+
+/// Build an embedding column at `precision`, plus its scale column for int8.
+///
+/// Missing vectors are stored as zeros, and marked null when `nullable`.
+fn embedding_arrays(
+    vectors: &[Option<&[f32]>],
+    precision: EmbeddingPrecision,
+    nullable: bool,
+) -> anyhow::Result<Vec<ArrayRef>> {
+    let zeros = [0.0f32; EMBEDDING_DIMENSION as usize];
+    let padded: Vec<&[f32]> = vectors.iter().map(|v| v.unwrap_or(&zeros)).collect();
+    let valid: Option<Vec<bool>> = nullable.then(|| vectors.iter().map(Option::is_some).collect());
+
+    let mut scales = None;
+    let values: ArrayRef = match precision {
+        EmbeddingPrecision::F32 => Arc::new(Float32Array::from(
+            padded
+                .iter()
+                .flat_map(|v| v.iter().copied())
+                .collect::<Vec<_>>(),
+        )),
+        EmbeddingPrecision::F16 => Arc::new(Float16Array::from(
+            padded
+                .iter()
+                .flat_map(|v| v.iter().map(|x| half::f16::from_f32(*x)))
+                .collect::<Vec<_>>(),
+        )),
+        EmbeddingPrecision::Int8 => {
+            let (quantized, vector_scales): (Vec<Vec<i8>>, Vec<f32>) =
+                padded.iter().map(|v| quantize_int8(v)).unzip();
+            let vector_scales: Vec<Option<f32>> = vector_scales
+                .into_iter()
+                .zip(vectors)
+                .map(|(scale, v)| (!nullable || v.is_some()).then_some(scale))
+                .collect();
+            scales = Some(Arc::new(Float32Array::from(vector_scales)) as ArrayRef);
+            Arc::new(Int8Array::from(quantized.concat()))
+        },
+    };
+
+    let field = Arc::new(Field::new("item", embedding_item_type(precision), true));
+    let list: ArrayRef = Arc::new(
+        FixedSizeListArray::try_new(field, EMBEDDING_DIMENSION, values, valid.map(Into::into))
+            .map_err(|e| anyhow::anyhow!("Failed to create embeddings array: {}", e))?,
+    );
+
+    Ok(std::iter::once(list).chain(scales).collect())
 }
 
 /// Convert artifacts to Arrow RecordBatch
 pub fn artifacts_to_batch(artifacts: &[crate::services::Artifact]) -> anyhow::Result<RecordBatch> {
-    let schema = create_schema();
+    artifacts_to_batch_with_fields(artifacts, &[])
+}
+
+/// Convert artifacts to Arrow RecordBatch, appending one column per structured field.
+///
+/// Artifacts that lack a field get a null in that column.
+pub fn artifacts_to_batch_with_fields(
+    artifacts: &[crate::services::Artifact],
+    fields: &[String],
+) -> anyhow::Result<RecordBatch> {
+    artifacts_to_batch_for(artifacts, fields, EmbeddingPrecision::F32)
+}
+
+/// Convert artifacts to a RecordBatch matching [`create_schema_for`]
+pub fn artifacts_to_batch_for(
+    artifacts: &[crate::services::Artifact],
+    fields: &[String],
+    precision: EmbeddingPrecision,
+) -> anyhow::Result<RecordBatch> {
+    artifacts_to_batch_with_models(artifacts, fields, &[], precision)
+}
+
+/// Convert artifacts to a RecordBatch matching [`create_schema_for`], followed
+/// by one [`model_field`] column per `(slug, dimension)` in `models`.
+///
+/// Model embeddings are always stored as f32, whatever `precision` is.
+/// Artifacts without an embedding from a model get a null in its column.
+pub fn artifacts_to_batch_with_models(
+    artifacts: &[crate::services::Artifact],
+    fields: &[String],
+    models: &[(String, i32)],
+    precision: EmbeddingPrecision,
+) -> anyhow::Result<RecordBatch> {
+    let base = create_schema_for(fields, precision);
+    let schema = if models.is_empty() {
+        base
+    } else {
+        let mut columns: Vec<Field> = base.fields().iter().map(|f| f.as_ref().clone()).collect();
+        columns.extend(
+            models
+                .iter()
+                .map(|(slug, dimension)| model_field(slug, *dimension)),
+        );
+        Arc::new(Schema::new(columns))
+    };
 
     let ids: ArrayRef = Arc::new(StringArray::from(
         artifacts.iter().map(|a| a.id.as_str()).collect::<Vec<_>>(),
@@ -68,11 +482,21 @@ pub fn artifacts_to_batch(artifacts: &[crate::services::Artifact]) -> anyhow::Re
             .collect::<Vec<_>>(),
     ));
 
+    let compressed: Vec<bool> = artifacts.iter().map(should_compress).collect();
+    let stored = |text: &str, compress_it: bool| -> anyhow::Result<String> {
+        if compress_it {
+            compress(text)
+        } else {
+            Ok(text.to_string())
+        }
+    };
+
     let contents: ArrayRef = Arc::new(StringArray::from(
         artifacts
             .iter()
-            .map(|a| a.content.as_str())
-            .collect::<Vec<_>>(),
+            .zip(&compressed)
+            .map(|(a, &c)| stored(&a.content, c))
+            .collect::<anyhow::Result<Vec<_>>>()?,
     ));
 
     let formats: ArrayRef = Arc::new(StringArray::from(
@@ -85,22 +509,18 @@ pub fn artifacts_to_batch(artifacts: &[crate::services::Artifact]) -> anyhow::Re
     let metadata: ArrayRef = Arc::new(StringArray::from(
         artifacts
             .iter()
-            .map(|a| serde_json::to_string(&a.metadata).unwrap_or_default())
+            .map(|a| metadata_json(&a.metadata))
             .collect::<Vec<_>>(),
     ));
 
-    // Build FixedSizeList for embeddings
-    let embeddings: Vec<f32> = artifacts
-        .iter()
-        .flat_map(|a| a.embedding.as_deref().unwrap_or(&[0.0; 384]))
-        .copied()
-        .collect();
-    let values = Float32Array::from(embeddings);
-    let field = Arc::new(Field::new("item", DataType::Float32, true));
-    let embeddings_array: ArrayRef = Arc::new(
-        FixedSizeListArray::try_new(field, EMBEDDING_DIMENSION, Arc::new(values), None)
-            .map_err(|e| anyhow::anyhow!("Failed to create embeddings array: {}", e))?,
-    );
+    let embeddings = embedding_arrays(
+        &artifacts
+            .iter()
+            .map(|a| a.embedding.as_deref())
+            .collect::<Vec<_>>(),
+        precision,
+        false,
+    )?;
 
     let embedding_models: ArrayRef = Arc::new(StringArray::from(
         artifacts
@@ -112,32 +532,41 @@ pub fn artifacts_to_batch(artifacts: &[crate::services::Artifact]) -> anyhow::Re
     let contexts: ArrayRef = Arc::new(StringArray::from(
         artifacts
             .iter()
-            .map(|a| a.context.as_deref())
+            .zip(&compressed)
+            .map(|(a, &c)| a.context.as_deref().map(|t| stored(t, c)).transpose())
+            .collect::<anyhow::Result<Vec<_>>>()?,
+    ));
+
+    let context_embeddings = embedding_arrays(
+        &artifacts
+            .iter()
+            .map(|a| a.context_embedding.as_deref())
             .collect::<Vec<_>>(),
+        precision,
+        true,
+    )?;
+
+    let attachment_texts: ArrayRef = Arc::new(StringArray::from(
+        artifacts
+            .iter()
+            .zip(&compressed)
+            .map(|(a, &c)| {
+                a.attachment_text
+                    .as_deref()
+                    .map(|t| stored(t, c))
+                    .transpose()
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?,
     ));
 
-    // Build FixedSizeList for context embeddings (nullable)
-    let context_embeddings_values: Vec<f32> = artifacts
-        .iter()
-        .flat_map(|a| a.context_embedding.as_deref().unwrap_or(&[0.0; 384]))
-        .copied()
-        .collect();
-    let context_values = Float32Array::from(context_embeddings_values);
-    let context_field = Arc::new(Field::new("item", DataType::Float32, true));
-    // Create null bitmap: true if context_embedding is Some
-    let context_nulls: Vec<bool> = artifacts
-        .iter()
-        .map(|a| a.context_embedding.is_some())
-        .collect();
-    let context_embeddings_array: ArrayRef = Arc::new(
-        FixedSizeListArray::try_new(
-            context_field,
-            EMBEDDING_DIMENSION,
-            Arc::new(context_values),
-            Some(context_nulls.into()),
-        )
-        .map_err(|e| anyhow::anyhow!("Failed to create context_embeddings array: {}", e))?,
-    );
+    let attachment_embeddings = embedding_arrays(
+        &artifacts
+            .iter()
+            .map(|a| a.attachment_embedding.as_deref())
+            .collect::<Vec<_>>(),
+        precision,
+        true,
+    )?;
 
     let created_ats: ArrayRef = Arc::new(TimestampMillisecondArray::from(
         artifacts
@@ -153,30 +582,126 @@ pub fn artifacts_to_batch(artifacts: &[crate::services::Artifact]) -> anyhow::Re
             .collect::<Vec<_>>(),
     ));
 
-    RecordBatch::try_new(
-        schema,
-        vec![
-            ids,
-            kinds,
-            names,
-            contents,
-            formats,
-            metadata,
-            embeddings_array,
-            embedding_models,
-            contexts,
-            context_embeddings_array,
-            created_ats,
-            updated_ats,
-        ],
+    let checksums: ArrayRef = Arc::new(StringArray::from(
+        artifacts
+            .iter()
+            .map(|a| row_checksum(a, fields))
+            .collect::<Vec<_>>(),
+    ));
+
+    let namespaces: ArrayRef = Arc::new(StringArray::from(
+        artifacts
+            .iter()
+            .map(|a| a.namespace.as_deref())
+            .collect::<Vec<_>>(),
+    ));
+
+    let aliases: ArrayRef = Arc::new(StringArray::from(
+        artifacts
+            .iter()
+            .map(|a| {
+                (!a.aliases.is_empty())
+                    .then(|| serde_json::to_string(&a.aliases).unwrap_or_default())
+            })
+            .collect::<Vec<_>>(),
+    ));
+
+    let mut columns = vec![ids, kinds, names, contents, formats, metadata];
+    columns.extend(embeddings);
+    columns.push(embedding_models);
+    columns.push(contexts);
+    columns.extend(context_embeddings);
+    columns.push(created_ats);
+    columns.push(updated_ats);
+    columns.push(checksums);
+    columns.push(namespaces);
+    columns.push(aliases);
+    columns.push(Arc::new(BooleanArray::from(compressed)));
+    columns.push(attachment_texts);
+    columns.extend(attachment_embeddings);
+    columns.push(Arc::new(StringArray::from(
+        artifacts
+            .iter()
+            .map(|a| a.embedding_text.as_deref())
+            .collect::<Vec<_>>(),
+    )));
+    columns.push(Arc::new(StringArray::from(
+        artifacts
+            .iter()
+            .map(|a| a.owner.as_deref())
+            .collect::<Vec<_>>(),
+    )));
+    columns.push(Arc::new(StringArray::from(
+        artifacts
+            .iter()
+            .map(|a| {
+                (!a.reviewers.is_empty())
+                    .then(|| serde_json::to_string(&a.reviewers).unwrap_or_default())
+            })
+            .collect::<Vec<_>>(),
+    )));
+    for name in fields {
+        columns.push(Arc::new(StringArray::from(
+            artifacts
+                .iter()
+                .map(|a| a.fields.get(name).map(String::as_str))
+                .collect::<Vec<_>>(),
+        )));
+    }
+
+    for (slug, dimension) in models {
+        columns.push(model_embedding_array(artifacts, slug, *dimension)?);
+    }
+
+    RecordBatch::try_new(schema, columns)
+        .map_err(|e| anyhow::anyhow!("Failed to create record batch: {}", e))
+}
+
+/// The column of embeddings from the model with slug `slug`, null where missing
+fn model_embedding_array(
+    artifacts: &[crate::services::Artifact],
+    slug: &str,
+    dimension: i32,
+) -> anyhow::Result<ArrayRef> {
+    let mut values = Vec::with_capacity(artifacts.len() * dimension as usize);
+    let mut valid = Vec::with_capacity(artifacts.len());
+    for artifact in artifacts {
+        match artifact.model_embeddings.get(slug) {
+            Some(vector) => {
+                anyhow::ensure!(
+                    vector.len() == dimension as usize,
+                    "Embedding from model '{}' for artifact '{}' has {} values, expected {}",
+                    slug,
+                    artifact.id,
+                    vector.len(),
+                    dimension
+                );
+                values.extend_from_slice(vector);
+                valid.push(true);
+            },
+            None => {
+                values.resize(values.len() + dimension as usize, 0.0);
+                valid.push(false);
+            },
+        }
+    }
+
+    let field = Arc::new(Field::new("item", DataType::Float32, true));
+    let list = FixedSizeListArray::try_new(
+        field,
+        dimension,
+        Arc::new(Float32Array::from(values)),
+        Some(valid.into()),
     )
-    .map_err(|e| anyhow::anyhow!("Failed to create record batch: {}", e))
+    .map_err(|e| anyhow::anyhow!("Failed to create model embeddings array: {}", e))?;
+    Ok(Arc::new(list))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::services::{Artifact, ContentFormat};
+    use arrow_array::Array;
     use std::collections::HashMap;
 
     #[test]
@@ -196,12 +721,21 @@ mod tests {
         assert!(field_names.contains(&"context_embedding"));
         assert!(field_names.contains(&"created_at"));
         assert!(field_names.contains(&"updated_at"));
+        assert!(field_names.contains(&"checksum"));
+        assert!(field_names.contains(&"namespace"));
+        assert!(field_names.contains(&"aliases"));
+        assert!(field_names.contains(&"compressed"));
+        assert!(field_names.contains(&"attachment_text"));
+        assert!(field_names.contains(&"attachment_embedding"));
+        assert!(field_names.contains(&"embedding_text"));
+        assert!(field_names.contains(&"owner"));
+        assert!(field_names.contains(&"reviewers"));
     }
 
     #[test]
     fn schema_field_count() {
         let schema = create_schema();
-        assert_eq!(schema.fields().len(), 12);
+        assert_eq!(schema.fields().len(), 21);
     }
 
     #[test]
@@ -243,7 +777,7 @@ mod tests {
 
         let batch = artifacts_to_batch(&[artifact]).unwrap();
         assert_eq!(batch.num_rows(), 1);
-        assert_eq!(batch.num_columns(), 12);
+        assert_eq!(batch.num_columns(), 21);
     }
 
     #[test]
@@ -297,4 +831,253 @@ mod tests {
         assert!(metadata_json.contains("key"));
         assert!(metadata_json.contains("value"));
     }
+
+    #[test]
+    fn metadata_json_keeps_commas_between_entries() {
+        let labels = HashMap::from([
+            ("a,".to_string(), ":".to_string()),
+            (",b".to_string(), "c\\,d".to_string()),
+        ]);
+        let json = metadata_json(&labels);
+        assert_eq!(json, r#"{"\u002cb":"c\\\u002cd","a\u002c":":"}"#);
+        let read: HashMap<String, String> = serde_json::from_str(&json).unwrap();
+        assert_eq!(read, labels);
+        assert_eq!(metadata_json(&HashMap::new()), "{}");
+    }
+
+    #[test]
+    fn field_names_are_lowercase_identifiers() {
+        assert!(is_valid_field_name("severity"));
+        assert!(is_valid_field_name("owner_team2"));
+        assert!(!is_valid_field_name("Severity"));
+        assert!(!is_valid_field_name("2fa"));
+        assert!(!is_valid_field_name("a-b"));
+        assert!(!is_valid_field_name(""));
+    }
+
+    #[test]
+    fn artifacts_to_batch_with_fields_adds_nullable_columns() {
+        let mut with = Artifact::new(
+            "incident".to_string(),
+            "severity: critical".to_string(),
+            ContentFormat::Yaml,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        with.fields
+            .insert("severity".to_string(), "critical".to_string());
+        let without = Artifact::new(
+            "incident".to_string(),
+            "title: x".to_string(),
+            ContentFormat::Yaml,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+
+        let batch =
+            artifacts_to_batch_with_fields(&[with, without], &["severity".to_string()]).unwrap();
+        assert_eq!(batch.num_columns(), 22);
+        assert_eq!(field_names(&batch.schema()), vec!["severity".to_string()]);
+
+        let column = batch
+            .column_by_name("field_severity")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(column.value(0), "critical");
+        assert!(column.is_null(1));
+    }
+
+    #[test]
+    fn model_embeddings_get_a_column_per_model() {
+        let mut with = Artifact::new(
+            "intent".to_string(),
+            "Test content".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        with.embedding = Some(vec![0.1; 384]);
+        with.model_embeddings
+            .insert(model_slug("nomic-embed-text"), vec![0.5; 8]);
+        let mut without = with.clone();
+        without.model_embeddings.clear();
+
+        let models = vec![("nomic_embed_text".to_string(), 8)];
+        let batch = artifacts_to_batch_with_models(
+            &[with.clone(), without],
+            &[],
+            &models,
+            EmbeddingPrecision::F32,
+        )
+        .unwrap();
+        assert_eq!(model_columns(&batch.schema()), models);
+        let column = batch
+            .column_by_name("model_nomic_embed_text")
+            .unwrap()
+            .as_fixed_size_list();
+        assert!(column.is_valid(0));
+        assert!(column.is_null(1));
+
+        with.model_embeddings
+            .insert("nomic_embed_text".to_string(), vec![0.5; 4]);
+        assert!(
+            artifacts_to_batch_with_models(&[with], &[], &models, EmbeddingPrecision::F32).is_err()
+        );
+    }
+
+    #[test]
+    fn checksum_covers_stored_values_only() {
+        let mut artifact = Artifact::new(
+            "incident".to_string(),
+            "severity: critical".to_string(),
+            ContentFormat::Yaml,
+            None,
+            HashMap::from([("team".to_string(), "ops".to_string())]),
+            "model".to_string(),
+        );
+        artifact.embedding = Some(vec![0.1; 384]);
+        artifact
+            .fields
+            .insert("severity".to_string(), "critical".to_string());
+        let fields = vec!["severity".to_string()];
+        let original = row_checksum(&artifact, &fields);
+        assert_eq!(original.len(), 64);
+
+        artifact.embedding = Some(vec![0.2; 384]);
+        assert_eq!(row_checksum(&artifact, &fields), original);
+        artifact
+            .fields
+            .insert("Unstored".to_string(), "x".to_string());
+        assert_eq!(row_checksum(&artifact, &fields), original);
+
+        artifact.content.push('!');
+        assert_ne!(row_checksum(&artifact, &fields), original);
+        let edited = row_checksum(&artifact, &fields);
+        artifact.namespace = Some("payments".to_string());
+        assert_ne!(row_checksum(&artifact, &fields), edited);
+        let namespaced = row_checksum(&artifact, &fields);
+        artifact.aliases = vec!["old-id".to_string()];
+        assert_ne!(row_checksum(&artifact, &fields), namespaced);
+        let aliased = row_checksum(&artifact, &fields);
+        artifact.owner = Some("@payments".to_string());
+        assert_ne!(row_checksum(&artifact, &fields), aliased);
+
+        let batch = artifacts_to_batch_with_fields(&[artifact.clone()], &fields).unwrap();
+        let stored = batch
+            .column_by_name(CHECKSUM_COLUMN)
+            .unwrap()
+            .as_string::<i32>();
+        assert_eq!(stored.value(0), row_checksum(&artifact, &fields));
+    }
+
+    #[test]
+    fn large_content_is_stored_compressed() {
+        let mut large = Artifact::new(
+            "spec".to_string(),
+            "paths:\n  /users: {}\n".repeat(2000),
+            ContentFormat::Yaml,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        large.context = Some("Public API".to_string());
+        let small = Artifact::new(
+            "intent".to_string(),
+            "Small content".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+
+        let batch = artifacts_to_batch(&[large.clone(), small]).unwrap();
+        let flags = batch
+            .column_by_name(COMPRESSED_COLUMN)
+            .unwrap()
+            .as_boolean();
+        assert!(flags.value(0));
+        assert!(!flags.value(1));
+
+        let contents = batch.column_by_name("content").unwrap().as_string::<i32>();
+        assert!(contents.value(0).len() < large.content.len() / 10);
+        assert_eq!(decompress(contents.value(0)).unwrap(), large.content);
+        assert_eq!(contents.value(1), "Small content");
+        let contexts = batch.column_by_name("context").unwrap().as_string::<i32>();
+        assert_eq!(decompress(contexts.value(0)).unwrap(), "Public API");
+    }
+
+    #[test]
+    fn int8_quantization_round_trips_within_a_step() {
+        let vector: Vec<f32> = (0..384).map(|i| (i as f32 - 192.0) / 200.0).collect();
+        let (quantized, scale) = quantize_int8(&vector);
+        let restored = dequantize_int8(&quantized, scale);
+        for (a, b) in vector.iter().zip(&restored) {
+            assert!((a - b).abs() <= scale / 2.0 + f32::EPSILON);
+        }
+
+        let (zeros, zero_scale) = quantize_int8(&[0.0; 4]);
+        assert_eq!(dequantize_int8(&zeros, zero_scale), vec![0.0; 4]);
+    }
+
+    #[test]
+    fn quantized_batches_read_back_as_floats() {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            "Test content".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        let vector: Vec<f32> = (0..384).map(|i| i as f32 / 384.0).collect();
+        artifact.embedding = Some(vector.clone());
+
+        for precision in [EmbeddingPrecision::F16, EmbeddingPrecision::Int8] {
+            let batch =
+                artifacts_to_batch_for(std::slice::from_ref(&artifact), &[], precision).unwrap();
+            assert_eq!(embedding_precision(&batch.schema()), precision);
+
+            let embeddings = batch
+                .column_by_name("embedding")
+                .unwrap()
+                .as_fixed_size_list();
+            let scales = batch
+                .column_by_name("embedding_scale")
+                .map(|c| c.as_primitive::<Float32Type>());
+            let restored = read_embedding(embeddings, scales, 0).unwrap();
+            for (a, b) in vector.iter().zip(&restored) {
+                assert!((a - b).abs() < 0.01, "{}: {} vs {}", precision, a, b);
+            }
+
+            // A missing context embedding stays null, scale included
+            let contexts = batch.column_by_name("context_embedding").unwrap();
+            assert!(contexts.is_null(0));
+            if let Some(scale) = batch.column_by_name("context_embedding_scale") {
+                assert!(scale.is_null(0));
+            }
+        }
+    }
+
+    #[test]
+    fn int8_schema_adds_scale_columns() {
+        let schema = create_schema_for(&[], EmbeddingPrecision::Int8);
+        assert_eq!(schema.fields().len(), 24);
+        assert!(!schema
+            .field_with_name("embedding_scale")
+            .unwrap()
+            .is_nullable());
+        assert!(schema
+            .field_with_name("context_embedding_scale")
+            .unwrap()
+            .is_nullable());
+        assert_eq!(
+            embedding_precision(&create_schema()),
+            EmbeddingPrecision::F32
+        );
+    }
 }
@@ -0,0 +1,568 @@
+//! JSON-RPC server for editor extensions.
+//!
+//! `dna serve --editor` speaks JSON-RPC 2.0 over stdio with LSP-style
+//! `Content-Length` framing, so extensions can reuse their language client
+//! plumbing. Unlike the MCP server it is tuned for keystroke latency: searches
+//! wait out a short debounce, and a search overtaken by a newer one is
+//! answered with `RequestCancelled` instead of results for a stale query.
+//!
+//! Requests: `initialize`, `shutdown`, `dna/search`, `dna/get` and
+//! `dna/hover`. The server sends a `dna/storeChanged` notification when the
+//! store version moves, e.g. after `dna add` in a terminal, and stops on the
+//! `exit` notification or end of input.
+
+use crate::services::{
+    Artifact, ArtifactService, SearchFilters, SearchService, SearchWeights, ServiceError,
+};
+use anyhow::{Context, Result};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+
+/// Scheme of artifact links in frontmatter, e.g. `dna://a1b2c3`
+pub const LINK_SCHEME: &str = "dna://";
+
+/// How long a search waits for a newer one before running
+pub const DEFAULT_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// How often the store version is checked for outside changes
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Characters of content shown in search results and hovers
+const SUMMARY_CHARS: usize = 280;
+
+const DEFAULT_SEARCH_LIMIT: usize = 10;
+
+const PARSE_ERROR: i32 = -32700;
+const METHOD_NOT_FOUND: i32 = -32601;
+const INVALID_PARAMS: i32 = -32602;
+/// LSP's code for a request the server abandoned
+const REQUEST_CANCELLED: i32 = -32800;
+
+/// A JSON-RPC error response
+#[derive(Debug)]
+struct RpcError {
+    code: i32,
+    message: String,
+}
+
+impl From<anyhow::Error> for RpcError {
+    fn from(err: anyhow::Error) -> Self {
+        ServiceError::classify(err).into()
+    }
+}
+
+impl From<ServiceError> for RpcError {
+    fn from(err: ServiceError) -> Self {
+        Self {
+            code: err.json_rpc_code(),
+            message: err.to_string(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    query: String,
+    kind: Option<String>,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct GetParams {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct HoverParams {
+    /// A `dna://<id>` link; a bare ID prefix also works
+    link: String,
+}
+
+/// Serves editor requests against one project's store
+#[derive(Clone)]
+pub struct EditorServer {
+    artifacts: Arc<ArtifactService>,
+    search: Arc<SearchService>,
+    debounce: Duration,
+    poll_interval: Duration,
+    /// Sequence number of the newest search request
+    latest_search: Arc<AtomicU64>,
+}
+
+impl EditorServer {
+    pub fn new(artifacts: Arc<ArtifactService>, search: Arc<SearchService>) -> Self {
+        Self {
+            artifacts,
+            search,
+            debounce: DEFAULT_DEBOUNCE,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            latest_search: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    pub fn with_debounce(mut self, debounce: Duration) -> Self {
+        self.debounce = debounce;
+        self
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Answer requests from `reader` until `exit` or end of input.
+    ///
+    /// Requests are handled concurrently, so a slow search doesn't hold up
+    /// hovers; responses can arrive out of order.
+    pub async fn serve<R, W>(self, mut reader: R, writer: W) -> Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+        let writer_task = tokio::spawn(async move {
+            let mut writer = writer;
+            while let Some(message) = rx.recv().await {
+                write_message(&mut writer, &message).await?;
+            }
+            anyhow::Ok(())
+        });
+        let baseline = self.artifacts.version().await.ok();
+        let watcher = tokio::spawn(self.clone().watch(baseline, tx.clone()));
+
+        while let Some(body) = read_message(&mut reader).await? {
+            let message: Value = match serde_json::from_slice(&body) {
+                Ok(message) => message,
+                Err(e) => {
+                    let error = RpcError {
+                        code: PARSE_ERROR,
+                        message: format!("Invalid JSON: {}", e),
+                    };
+                    let _ = tx.send(response(Value::Null, Err(error)));
+                    continue;
+                },
+            };
+            let method = message["method"].as_str().unwrap_or_default().to_string();
+            if method == "exit" {
+                break;
+            }
+            // Notifications other than exit need no answer
+            let Some(id) = message.get("id").cloned() else {
+                continue;
+            };
+            let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+            let server = self.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let result = server.handle(&method, params).await;
+                let _ = tx.send(response(id, result));
+            });
+        }
+
+        watcher.abort();
+        drop(tx);
+        writer_task.await?
+    }
+
+    async fn handle(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "initialize" => Ok(json!({
+                "serverInfo": { "name": "dna", "version": env!("CARGO_PKG_VERSION") },
+                "capabilities": {
+                    "searchDebounceMs": self.debounce.as_millis() as u64,
+                    "linkScheme": LINK_SCHEME,
+                },
+            })),
+            "shutdown" => Ok(Value::Null),
+            "dna/search" => self.search(parse(params)?).await,
+            "dna/get" => self.get(parse(params)?).await,
+            "dna/hover" => self.hover(parse(params)?).await,
+            _ => Err(RpcError {
+                code: METHOD_NOT_FOUND,
+                message: format!("Unknown method: {}", method),
+            }),
+        }
+    }
+
+    async fn search(&self, params: SearchParams) -> Result<Value, RpcError> {
+        let sequence = self.latest_search.fetch_add(1, Ordering::SeqCst) + 1;
+        tokio::time::sleep(self.debounce).await;
+        self.check_latest(sequence)?;
+
+        if params.query.trim().is_empty() {
+            return Ok(json!([]));
+        }
+        let filters = SearchFilters {
+            kind: params.kind,
+            metadata: params.labels,
+            limit: Some(params.limit.unwrap_or(DEFAULT_SEARCH_LIMIT)),
+            ..Default::default()
+        };
+        let results = self
+            .search
+            .search_boosted(
+                &params.query,
+                filters,
+                SearchWeights::default(),
+                &HashMap::new(),
+            )
+            .await?;
+        // The user may have kept typing while the query was embedded
+        self.check_latest(sequence)?;
+
+        Ok(results
+            .iter()
+            .map(|r| {
+                json!({
+                    "id": r.artifact.id,
+                    "kind": r.artifact.kind,
+                    "name": r.artifact.name,
+                    "score": r.score,
+                    "summary": summary(&r.artifact.content),
+                })
+            })
+            .collect())
+    }
+
+    fn check_latest(&self, sequence: u64) -> Result<(), RpcError> {
+        if self.latest_search.load(Ordering::SeqCst) == sequence {
+            Ok(())
+        } else {
+            Err(RpcError {
+                code: REQUEST_CANCELLED,
+                message: "Superseded by a newer search".to_string(),
+            })
+        }
+    }
+
+    async fn get(&self, params: GetParams) -> Result<Value, RpcError> {
+        let artifact = self.find(strip_scheme(&params.id)).await?;
+        Ok(serde_json::to_value(artifact).context("Failed to serialize artifact")?)
+    }
+
+    /// Markdown summary of a linked artifact, or null if the link is dangling
+    async fn hover(&self, params: HoverParams) -> Result<Value, RpcError> {
+        match self.find(strip_scheme(&params.link)).await {
+            Ok(artifact) => Ok(json!({
+                "contents": { "kind": "markdown", "value": hover_markdown(&artifact) },
+            })),
+            Err(ServiceError::NotFound(_)) => Ok(Value::Null),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn find(&self, id_or_prefix: &str) -> Result<Artifact, ServiceError> {
+        let id = self.artifacts.resolve_id(id_or_prefix).await?;
+        self.artifacts
+            .get(&id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", id)))
+    }
+
+    /// Notify the editor whenever the store version changes
+    async fn watch(self, mut last: Option<u64>, tx: mpsc::UnboundedSender<Value>) {
+        let mut interval = tokio::time::interval(self.poll_interval);
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let Ok(version) = self.artifacts.version().await else {
+                continue;
+            };
+            if last == Some(version) {
+                continue;
+            }
+            last = Some(version);
+            let notification = json!({
+                "jsonrpc": "2.0",
+                "method": "dna/storeChanged",
+                "params": { "version": version },
+            });
+            if tx.send(notification).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|e| RpcError {
+        code: INVALID_PARAMS,
+        message: format!("Invalid params: {}", e),
+    })
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": e.code, "message": e.message },
+        }),
+    }
+}
+
+fn strip_scheme(link: &str) -> &str {
+    link.trim().strip_prefix(LINK_SCHEME).unwrap_or(link.trim())
+}
+
+/// Content flattened to one line and cut to [`SUMMARY_CHARS`]
+fn summary(content: &str) -> String {
+    let text = content
+        .lines()
+        .map(|line| line.trim_start_matches(['#', ' ']))
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if text.chars().count() <= SUMMARY_CHARS {
+        return text;
+    }
+    let cut: String = text.chars().take(SUMMARY_CHARS).collect();
+    format!("{}…", cut.trim_end())
+}
+
+fn hover_markdown(artifact: &Artifact) -> String {
+    let title = artifact.name.as_deref().unwrap_or(&artifact.id);
+    let mut markdown = format!(
+        "**{}** · {} · `{}`\n\n{}",
+        title,
+        artifact.kind,
+        artifact.id,
+        summary(&artifact.content)
+    );
+    if !artifact.metadata.is_empty() {
+        let mut labels: Vec<_> = artifact
+            .metadata
+            .iter()
+            .map(|(k, v)| format!("`{}={}`", k, v))
+            .collect();
+        labels.sort();
+        markdown.push_str("\n\n");
+        markdown.push_str(&labels.join(" "));
+    }
+    markdown
+}
+
+/// Read one `Content-Length` framed message body, or `None` at end of input
+async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Vec<u8>>> {
+    let mut content_length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            if content_length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = Some(
+                    value
+                        .trim()
+                        .parse::<usize>()
+                        .context("Invalid Content-Length header")?,
+                );
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length.unwrap_or_default()];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(body))
+}
+
+async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::embedding::EmbeddingProvider;
+    use crate::services::ContentFormat;
+    use crate::testing::{TestDatabase, TestEmbedding};
+    use tokio::io::{BufReader, DuplexStream, ReadHalf, WriteHalf};
+
+    struct Client {
+        reader: BufReader<ReadHalf<DuplexStream>>,
+        writer: WriteHalf<DuplexStream>,
+    }
+
+    impl Client {
+        async fn send(&mut self, message: Value) {
+            write_message(&mut self.writer, &message).await.unwrap();
+        }
+
+        async fn request(&mut self, id: u64, method: &str, params: Value) {
+            self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+                .await;
+        }
+
+        async fn next(&mut self) -> Value {
+            let body = read_message(&mut self.reader).await.unwrap().unwrap();
+            serde_json::from_slice(&body).unwrap()
+        }
+    }
+
+    async fn start(
+        debounce: Duration,
+        poll_interval: Duration,
+    ) -> (
+        Client,
+        Arc<ArtifactService>,
+        tokio::task::JoinHandle<Result<()>>,
+    ) {
+        let db: Arc<dyn Database> = Arc::new(TestDatabase::new());
+        let embedding: Arc<dyn EmbeddingProvider> = Arc::new(TestEmbedding);
+        let artifacts = Arc::new(ArtifactService::new(db.clone(), embedding.clone()));
+        let search = Arc::new(SearchService::new(db, embedding));
+        let server = EditorServer::new(artifacts.clone(), search)
+            .with_debounce(debounce)
+            .with_poll_interval(poll_interval);
+
+        let (client_end, server_end) = tokio::io::duplex(64 * 1024);
+        let (server_read, server_write) = tokio::io::split(server_end);
+        let handle = tokio::spawn(server.serve(BufReader::new(server_read), server_write));
+        let (client_read, client_write) = tokio::io::split(client_end);
+        let client = Client {
+            reader: BufReader::new(client_read),
+            writer: client_write,
+        };
+        (client, artifacts, handle)
+    }
+
+    async fn add(artifacts: &ArtifactService, name: &str, content: &str) -> Artifact {
+        artifacts
+            .add(
+                "intent".to_string(),
+                content.to_string(),
+                ContentFormat::Markdown,
+                Some(name.to_string()),
+                HashMap::from([("team".to_string(), "auth".to_string())]),
+                None,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn answers_search_get_and_hover() {
+        let (mut client, artifacts, handle) =
+            start(Duration::ZERO, Duration::from_secs(3600)).await;
+        let login = add(&artifacts, "Login", "# Login\n\nUsers sign in with SSO.").await;
+
+        client.request(1, "initialize", json!({})).await;
+        let init = client.next().await;
+        assert_eq!(init["result"]["capabilities"]["linkScheme"], "dna://");
+
+        client
+            .request(2, "dna/search", json!({ "query": "sign in" }))
+            .await;
+        let search = client.next().await;
+        assert_eq!(search["id"], 2);
+        assert_eq!(search["result"][0]["id"], login.id.as_str());
+        assert_eq!(
+            search["result"][0]["summary"],
+            "Login Users sign in with SSO."
+        );
+
+        client
+            .request(3, "dna/get", json!({ "id": login.id[..4] }))
+            .await;
+        assert_eq!(client.next().await["result"]["name"], "Login");
+
+        let link = format!("dna://{}", login.id);
+        client
+            .request(4, "dna/hover", json!({ "link": link }))
+            .await;
+        let hover = client.next().await;
+        let markdown = hover["result"]["contents"]["value"].as_str().unwrap();
+        assert!(markdown.starts_with("**Login** · intent"), "{markdown}");
+        assert!(markdown.ends_with("`team=auth`"), "{markdown}");
+
+        client
+            .request(5, "dna/hover", json!({ "link": "dna://missing" }))
+            .await;
+        assert_eq!(client.next().await["result"], Value::Null);
+
+        client
+            .request(6, "dna/get", json!({ "id": "missing" }))
+            .await;
+        assert_eq!(client.next().await["error"]["code"], -32002);
+
+        client.request(7, "dna/unknown", json!({})).await;
+        assert_eq!(client.next().await["error"]["code"], METHOD_NOT_FOUND);
+
+        client
+            .send(json!({ "jsonrpc": "2.0", "method": "exit" }))
+            .await;
+        handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn superseded_searches_are_cancelled() {
+        let (mut client, artifacts, _handle) =
+            start(Duration::from_millis(100), Duration::from_secs(3600)).await;
+        add(&artifacts, "Login", "Users sign in with SSO.").await;
+
+        client
+            .request(1, "dna/search", json!({ "query": "si" }))
+            .await;
+        client
+            .request(2, "dna/search", json!({ "query": "sign" }))
+            .await;
+
+        let mut responses = [client.next().await, client.next().await];
+        responses.sort_by_key(|r| r["id"].as_u64());
+        assert_eq!(responses[0]["error"]["code"], REQUEST_CANCELLED);
+        assert_eq!(responses[1]["result"].as_array().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn notifies_when_the_store_changes() {
+        let (mut client, artifacts, _handle) =
+            start(Duration::ZERO, Duration::from_millis(10)).await;
+        // Let the server take its baseline before writing
+        client.request(1, "initialize", json!({})).await;
+        client.next().await;
+        add(&artifacts, "Login", "Users sign in with SSO.").await;
+
+        let notification = client.next().await;
+        assert_eq!(notification["method"], "dna/storeChanged");
+        assert_eq!(
+            notification["params"]["version"],
+            artifacts.version().await.unwrap()
+        );
+    }
+
+    #[test]
+    fn summary_flattens_and_truncates() {
+        assert_eq!(summary("# Title\n\n- one\n  two"), "Title - one two");
+        let long = "word ".repeat(100);
+        let cut = summary(&long);
+        assert!(cut.ends_with('…'));
+        assert!(cut.chars().count() <= SUMMARY_CHARS + 1);
+    }
+}
@@ -1,12 +1,322 @@
 use super::provider::EmbeddingProvider;
+use crate::services::ModelConfig;
 use anyhow::{Context, Result};
 use candle_core::{DType, Device, Tensor};
 use candle_nn::VarBuilder;
 use candle_transformers::models::bert::{BertModel, Config};
-use hf_hub::{api::sync::Api, Repo, RepoType};
+use hf_hub::{api::sync::ApiBuilder, Cache, Repo};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::sync::Arc;
 use tokenizers::Tokenizer;
 
+/// Weight files tried in order when loading a model
+const WEIGHT_FILES: &[&str] = &["model.safetensors", "pytorch_model.bin"];
+
+/// Prefix of model repositories in the hub cache layout
+const MODEL_DIR_PREFIX: &str = "models--";
+
+/// Whether `HF_HUB_OFFLINE` asks for downloads to be skipped
+pub fn offline_from_env() -> bool {
+    std::env::var("HF_HUB_OFFLINE").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+/// Files needed to load a BERT model
+#[derive(Debug, Clone)]
+pub struct ModelFiles {
+    pub config: PathBuf,
+    pub tokenizer: PathBuf,
+    pub weights: PathBuf,
+}
+
+/// A model repository found in the cache
+#[derive(Debug, Clone)]
+pub struct CachedModel {
+    pub id: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Whether every file needed to load the model is present
+    pub complete: bool,
+}
+
+/// Model files on disk, in the HuggingFace hub cache layout
+#[derive(Debug, Clone)]
+pub struct ModelCache {
+    cache: Cache,
+}
+
+impl ModelCache {
+    /// Cache at `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            cache: Cache::new(path),
+        }
+    }
+
+    /// Cache under `HF_HOME`, or `~/.cache/huggingface/hub`
+    pub fn from_env() -> Self {
+        Self {
+            cache: Cache::from_env(),
+        }
+    }
+
+    /// Cache configured for the project, falling back to the environment
+    pub fn for_config(config: &ModelConfig) -> Self {
+        match &config.cache_dir {
+            Some(dir) => Self::new(PathBuf::from(dir)),
+            None => Self::from_env(),
+        }
+    }
+
+    /// Root directory of the cache
+    pub fn root(&self) -> &Path {
+        self.cache.path()
+    }
+
+    /// Directory holding a model's files, whether or not it exists yet
+    pub fn model_dir(&self, model_id: &str) -> PathBuf {
+        self.root()
+            .join(Repo::model(model_id.to_string()).folder_name())
+    }
+
+    /// Files for `model_id` if they are all cached
+    pub fn cached_files(&self, model_id: &str) -> Option<ModelFiles> {
+        let repo = self.cache.model(model_id.to_string());
+        Some(ModelFiles {
+            config: repo.get("config.json")?,
+            tokenizer: repo.get("tokenizer.json")?,
+            weights: WEIGHT_FILES.iter().find_map(|f| repo.get(f))?,
+        })
+    }
+
+    /// Whether `model_id` can be loaded without network access
+    pub fn is_cached(&self, model_id: &str) -> bool {
+        self.cached_files(model_id).is_some()
+    }
+
+    /// Files for `model_id`, downloading anything missing unless `offline`
+    pub fn resolve(&self, model_id: &str, offline: bool) -> Result<ModelFiles> {
+        if let Some(files) = self.cached_files(model_id) {
+            return Ok(files);
+        }
+        if offline {
+            anyhow::bail!(
+                "Model '{}' is not in the model cache at {} and offline mode is enabled. \
+                 Run 'dna model download {}' where network access is available, \
+                 then copy the cache directory.",
+                model_id,
+                self.root().display(),
+                model_id
+            );
+        }
+        self.download(model_id)
+    }
+
+    /// Fetch the files for `model_id` from HuggingFace into the cache
+    pub fn download(&self, model_id: &str) -> Result<ModelFiles> {
+        let api = ApiBuilder::from_cache(self.cache.clone())
+            .build()
+            .context("Failed to create HuggingFace API client")?;
+        let repo = api.model(model_id.to_string());
+
+        tracing::debug!("Downloading model files from HuggingFace...");
+
+        let config = repo
+            .get("config.json")
+            .context("Failed to download config.json")?;
+        let tokenizer = repo
+            .get("tokenizer.json")
+            .context("Failed to download tokenizer.json")?;
+        let weights = repo
+            .get(WEIGHT_FILES[0])
+            .or_else(|_| repo.get(WEIGHT_FILES[1]))
+            .context("Failed to download model weights")?;
+
+        Ok(ModelFiles {
+            config,
+            tokenizer,
+            weights,
+        })
+    }
+
+    /// Models present in the cache, sorted by id
+    pub fn list(&self) -> Result<Vec<CachedModel>> {
+        if !self.root().exists() {
+            return Ok(vec![]);
+        }
+
+        let entries = std::fs::read_dir(self.root())
+            .with_context(|| format!("Failed to read model cache {}", self.root().display()))?;
+        let mut models = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(repo_name) = name.strip_prefix(MODEL_DIR_PREFIX) else {
+                continue;
+            };
+            let id = repo_name.replacen("--", "/", 1);
+            let path = entry.path();
+            models.push(CachedModel {
+                complete: self.is_cached(&id),
+                size_bytes: dir_size(&path),
+                id,
+                path,
+            });
+        }
+        models.sort_by(|a, b| a.id.cmp(&b.id));
+        Ok(models)
+    }
+
+    /// Delete a model from the cache. Returns false if it was not cached.
+    pub fn remove(&self, model_id: &str) -> Result<bool> {
+        let dir = self.model_dir(model_id);
+        if !dir.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_dir_all(&dir)
+            .with_context(|| format!("Failed to remove {}", dir.display()))?;
+        Ok(true)
+    }
+}
+
+/// Total size of the files under `path`, without following symlinks
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&entry.path()),
+            Ok(t) if t.is_file() => entry.metadata().map_or(0, |m| m.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Texts per forward pass when `batch_size` is not configured
+pub const DEFAULT_BATCH_SIZE: usize = 32;
+
+/// Where the local model runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LocalDevice {
+    #[default]
+    Cpu,
+    /// CUDA if available, then Metal, then CPU
+    Auto,
+    Cuda(usize),
+    Metal(usize),
+}
+
+impl FromStr for LocalDevice {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, ordinal) = match s.split_once(':') {
+            Some((name, ordinal)) => (
+                name,
+                ordinal
+                    .parse()
+                    .with_context(|| format!("Invalid device ordinal in '{}'", s))?,
+            ),
+            None => (s, 0),
+        };
+        match (name.to_lowercase().as_str(), ordinal) {
+            ("cpu", 0) => Ok(Self::Cpu),
+            ("auto", 0) => Ok(Self::Auto),
+            ("cuda", n) => Ok(Self::Cuda(n)),
+            ("metal", n) => Ok(Self::Metal(n)),
+            _ => Err(anyhow::anyhow!(
+                "Unknown device '{}'. Use cpu, auto, cuda[:N] or metal[:N]",
+                s
+            )),
+        }
+    }
+}
+
+impl LocalDevice {
+    /// Open the device. GPU backends need dna built with the matching feature.
+    fn open(self) -> Result<Device> {
+        match self {
+            Self::Cpu => Ok(Device::Cpu),
+            Self::Auto if candle_core::utils::cuda_is_available() => Self::Cuda(0).open(),
+            Self::Auto if candle_core::utils::metal_is_available() => Self::Metal(0).open(),
+            Self::Auto => Ok(Device::Cpu),
+            Self::Cuda(n) => Device::new_cuda(n).with_context(|| {
+                format!(
+                    "CUDA device {} is unavailable (requires dna built with --features cuda)",
+                    n
+                )
+            }),
+            Self::Metal(n) => Device::new_metal(n).with_context(|| {
+                format!(
+                    "Metal device {} is unavailable (requires dna built with --features metal)",
+                    n
+                )
+            }),
+        }
+    }
+}
+
+/// Runtime settings for [`LocalEmbedding`]
+#[derive(Debug, Clone)]
+pub struct LocalOptions {
+    /// Fail instead of downloading a model that is not cached
+    pub offline: bool,
+    pub device: LocalDevice,
+    /// Threads for CPU inference (default: one per core)
+    pub threads: Option<usize>,
+    /// Texts per forward pass
+    pub batch_size: usize,
+}
+
+impl Default for LocalOptions {
+    fn default() -> Self {
+        Self {
+            offline: offline_from_env(),
+            device: LocalDevice::Cpu,
+            threads: None,
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+impl LocalOptions {
+    /// Options from the `[model]` section of the project config
+    pub fn from_config(config: &ModelConfig) -> Result<Self> {
+        let device = match &config.device {
+            Some(device) => device.parse()?,
+            None => LocalDevice::Cpu,
+        };
+        if config.threads == Some(0) {
+            anyhow::bail!("model.threads must be at least 1");
+        }
+        if config.batch_size == Some(0) {
+            anyhow::bail!("model.batch_size must be at least 1");
+        }
+
+        Ok(Self {
+            offline: config.offline.unwrap_or(false) || offline_from_env(),
+            device,
+            threads: config.threads,
+            batch_size: config.batch_size.unwrap_or(DEFAULT_BATCH_SIZE),
+        })
+    }
+}
+
+/// Split text indices into batches of similar length, longest texts last.
+///
+/// Each batch is padded to its longest member, so grouping by length keeps
+/// short texts from paying for long ones.
+fn length_sorted_batches(texts: &[String], batch_size: usize) -> Vec<Vec<usize>> {
+    let mut order: Vec<usize> = (0..texts.len()).collect();
+    order.sort_by_key(|&i| texts[i].len());
+    order
+        .chunks(batch_size.max(1))
+        .map(<[usize]>::to_vec)
+        .collect()
+}
+
 /// Local embedding using Candle with BERT-based models
 pub struct LocalEmbedding {
     model_id: String,
@@ -14,31 +324,41 @@ pub struct LocalEmbedding {
     tokenizer: Arc<Tokenizer>,
     device: Device,
     dimensions: usize,
+    batch_size: usize,
+    /// Dedicated pool when `threads` is configured
+    pool: Option<Arc<rayon::ThreadPool>>,
 }
 
 impl LocalEmbedding {
-    /// Create a new local embedding provider
+    /// Create a new local embedding provider, downloading the model if needed
     pub async fn new(model_id: &str) -> Result<Self> {
-        tracing::info!("Initializing local embedding model: {}", model_id);
+        Self::with_options(model_id, &ModelCache::from_env(), LocalOptions::default()).await
+    }
 
-        let device = Device::Cpu;
+    /// Create a provider that loads its model from `cache`
+    pub async fn with_options(
+        model_id: &str,
+        cache: &ModelCache,
+        options: LocalOptions,
+    ) -> Result<Self> {
+        tracing::info!("Initializing local embedding model: {}", model_id);
 
-        // Download model files from HuggingFace
-        let api = Api::new().context("Failed to create HuggingFace API client")?;
-        let repo = api.repo(Repo::new(model_id.to_string(), RepoType::Model));
+        let device = options.device.open()?;
+        let pool = match options.threads {
+            Some(threads) => Some(Arc::new(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .context("Failed to create embedding thread pool")?,
+            )),
+            None => None,
+        };
 
-        tracing::debug!("Downloading model files from HuggingFace...");
-
-        let config_path = repo
-            .get("config.json")
-            .context("Failed to download config.json")?;
-        let tokenizer_path = repo
-            .get("tokenizer.json")
-            .context("Failed to download tokenizer.json")?;
-        let weights_path = repo
-            .get("model.safetensors")
-            .or_else(|_| repo.get("pytorch_model.bin"))
-            .context("Failed to download model weights")?;
+        let ModelFiles {
+            config: config_path,
+            tokenizer: tokenizer_path,
+            weights: weights_path,
+        } = cache.resolve(model_id, options.offline)?;
 
         // Load config
         let config_str =
@@ -67,7 +387,12 @@ impl LocalEmbedding {
 
         let model = BertModel::load(vb, &config).context("Failed to load BERT model")?;
 
-        tracing::info!("Loaded model {} with {} dimensions", model_id, dimensions);
+        tracing::info!(
+            "Loaded model {} with {} dimensions on {:?}",
+            model_id,
+            dimensions,
+            device
+        );
 
         Ok(Self {
             model_id: model_id.to_string(),
@@ -75,10 +400,95 @@ impl LocalEmbedding {
             tokenizer: Arc::new(tokenizer),
             device,
             dimensions,
+            batch_size: options.batch_size,
+            pool,
         })
     }
 }
 
+/// Run one padded batch through the model, returning mean-pooled, L2-normalized vectors
+fn embed_texts(
+    model: &BertModel,
+    tokenizer: &Tokenizer,
+    device: &Device,
+    texts: Vec<String>,
+) -> Result<Vec<Vec<f32>>> {
+    // Tokenize all texts
+    let encodings = tokenizer
+        .encode_batch(texts, true)
+        .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
+
+    // Find max length for padding
+    let max_len = encodings
+        .iter()
+        .map(|e| e.get_ids().len())
+        .max()
+        .unwrap_or(0);
+
+    // Build padded input tensors
+    let mut all_input_ids = Vec::new();
+    let mut all_attention_masks = Vec::new();
+    let mut all_token_type_ids = Vec::new();
+
+    for encoding in &encodings {
+        let ids = encoding.get_ids();
+        let attention = encoding.get_attention_mask();
+        let type_ids = encoding.get_type_ids();
+
+        // Pad to max_len
+        let mut padded_ids = ids.to_vec();
+        let mut padded_attention = attention.to_vec();
+        let mut padded_type_ids = type_ids.to_vec();
+
+        padded_ids.resize(max_len, 0);
+        padded_attention.resize(max_len, 0);
+        padded_type_ids.resize(max_len, 0);
+
+        all_input_ids.extend(padded_ids);
+        all_attention_masks.extend(padded_attention);
+        all_token_type_ids.extend(padded_type_ids);
+    }
+
+    let batch_size = encodings.len();
+
+    let input_ids =
+        Tensor::from_vec(all_input_ids, (batch_size, max_len), device)?.to_dtype(DType::U32)?;
+    let attention_mask = Tensor::from_vec(all_attention_masks, (batch_size, max_len), device)?
+        .to_dtype(DType::U32)?;
+    let token_type_ids = Tensor::from_vec(all_token_type_ids, (batch_size, max_len), device)?
+        .to_dtype(DType::U32)?;
+
+    // Run model forward pass
+    let embeddings = model.forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
+
+    // Mean pooling over sequence dimension
+    let attention_mask_f32 = attention_mask.to_dtype(DType::F32)?;
+    let mask_expanded = attention_mask_f32
+        .unsqueeze(2)?
+        .broadcast_as(embeddings.shape())?;
+
+    let masked = embeddings.mul(&mask_expanded)?;
+    let sum = masked.sum(1)?;
+    let count = mask_expanded.sum(1)?.clamp(1e-9, f64::MAX)?;
+    let pooled = sum.broadcast_div(&count)?;
+
+    // L2 normalize
+    let norm = pooled
+        .sqr()?
+        .sum_keepdim(1)?
+        .sqrt()?
+        .clamp(1e-12, f64::MAX)?;
+    let normalized = pooled.broadcast_div(&norm)?;
+
+    // Convert to Vec<Vec<f32>>
+    let flat: Vec<f32> = normalized.flatten_all()?.to_vec1()?;
+    let dim = normalized.dim(1)?;
+
+    let result: Vec<Vec<f32>> = flat.chunks(dim).map(|chunk| chunk.to_vec()).collect();
+
+    Ok(result)
+}
+
 #[async_trait::async_trait]
 impl EmbeddingProvider for LocalEmbedding {
     async fn embed(&self, text: &str) -> Result<Vec<f32>> {
@@ -99,87 +509,30 @@ impl EmbeddingProvider for LocalEmbedding {
         let model = Arc::clone(&self.model);
         let tokenizer = Arc::clone(&self.tokenizer);
         let device = self.device.clone();
+        let batch_size = self.batch_size;
+        let pool = self.pool.clone();
 
         // Move computation to blocking task since it's CPU-intensive
         let texts_owned: Vec<String> = texts.iter().map(|s| s.to_string()).collect();
 
         let result = tokio::task::spawn_blocking(move || -> Result<Vec<Vec<f32>>> {
-            // Tokenize all texts
-            let encodings = tokenizer
-                .encode_batch(texts_owned.clone(), true)
-                .map_err(|e| anyhow::anyhow!("Tokenization failed: {}", e))?;
-
-            // Find max length for padding
-            let max_len = encodings
-                .iter()
-                .map(|e| e.get_ids().len())
-                .max()
-                .unwrap_or(0);
-
-            // Build padded input tensors
-            let mut all_input_ids = Vec::new();
-            let mut all_attention_masks = Vec::new();
-            let mut all_token_type_ids = Vec::new();
-
-            for encoding in &encodings {
-                let ids = encoding.get_ids();
-                let attention = encoding.get_attention_mask();
-                let type_ids = encoding.get_type_ids();
-
-                // Pad to max_len
-                let mut padded_ids = ids.to_vec();
-                let mut padded_attention = attention.to_vec();
-                let mut padded_type_ids = type_ids.to_vec();
-
-                padded_ids.resize(max_len, 0);
-                padded_attention.resize(max_len, 0);
-                padded_type_ids.resize(max_len, 0);
-
-                all_input_ids.extend(padded_ids);
-                all_attention_masks.extend(padded_attention);
-                all_token_type_ids.extend(padded_type_ids);
+            let run = || -> Result<Vec<Vec<f32>>> {
+                let mut results = vec![Vec::new(); texts_owned.len()];
+                for batch in length_sorted_batches(&texts_owned, batch_size) {
+                    let batch_texts: Vec<String> =
+                        batch.iter().map(|&i| texts_owned[i].clone()).collect();
+                    let embeddings = embed_texts(&model, &tokenizer, &device, batch_texts)?;
+                    for (i, embedding) in batch.into_iter().zip(embeddings) {
+                        results[i] = embedding;
+                    }
+                }
+                Ok(results)
+            };
+
+            match pool {
+                Some(pool) => pool.install(run),
+                None => run(),
             }
-
-            let batch_size = texts_owned.len();
-
-            let input_ids = Tensor::from_vec(all_input_ids, (batch_size, max_len), &device)?
-                .to_dtype(DType::U32)?;
-            let attention_mask =
-                Tensor::from_vec(all_attention_masks, (batch_size, max_len), &device)?
-                    .to_dtype(DType::U32)?;
-            let token_type_ids =
-                Tensor::from_vec(all_token_type_ids, (batch_size, max_len), &device)?
-                    .to_dtype(DType::U32)?;
-
-            // Run model forward pass
-            let embeddings = model.forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
-
-            // Mean pooling over sequence dimension
-            let attention_mask_f32 = attention_mask.to_dtype(DType::F32)?;
-            let mask_expanded = attention_mask_f32
-                .unsqueeze(2)?
-                .broadcast_as(embeddings.shape())?;
-
-            let masked = embeddings.mul(&mask_expanded)?;
-            let sum = masked.sum(1)?;
-            let count = mask_expanded.sum(1)?.clamp(1e-9, f64::MAX)?;
-            let pooled = sum.broadcast_div(&count)?;
-
-            // L2 normalize
-            let norm = pooled
-                .sqr()?
-                .sum_keepdim(1)?
-                .sqrt()?
-                .clamp(1e-12, f64::MAX)?;
-            let normalized = pooled.broadcast_div(&norm)?;
-
-            // Convert to Vec<Vec<f32>>
-            let flat: Vec<f32> = normalized.flatten_all()?.to_vec1()?;
-            let dim = normalized.dim(1)?;
-
-            let result: Vec<Vec<f32>> = flat.chunks(dim).map(|chunk| chunk.to_vec()).collect();
-
-            Ok(result)
         })
         .await
         .context("Embedding task panicked")??;
@@ -200,6 +553,129 @@ impl EmbeddingProvider for LocalEmbedding {
 mod tests {
     use super::*;
 
+    /// Lay out `files` for `model_id` the way the hub cache stores them
+    fn seed_cache(root: &Path, model_id: &str, files: &[&str]) {
+        let repo = root.join(Repo::model(model_id.to_string()).folder_name());
+        std::fs::create_dir_all(repo.join("refs")).unwrap();
+        std::fs::write(repo.join("refs").join("main"), "abc123").unwrap();
+        let snapshot = repo.join("snapshots").join("abc123");
+        std::fs::create_dir_all(&snapshot).unwrap();
+        for file in files {
+            std::fs::write(snapshot.join(file), "{}").unwrap();
+        }
+    }
+
+    #[test]
+    fn cache_resolves_complete_models_offline() {
+        let dir = tempfile::tempdir().unwrap();
+        seed_cache(
+            dir.path(),
+            "org/small",
+            &["config.json", "tokenizer.json", "model.safetensors"],
+        );
+        let cache = ModelCache::new(dir.path().to_path_buf());
+
+        assert!(cache.is_cached("org/small"));
+        let files = cache.resolve("org/small", true).unwrap();
+        assert!(files.weights.ends_with("model.safetensors"));
+    }
+
+    #[test]
+    fn offline_fails_fast_for_missing_models() {
+        let dir = tempfile::tempdir().unwrap();
+        seed_cache(dir.path(), "org/partial", &["config.json"]);
+        let cache = ModelCache::new(dir.path().to_path_buf());
+
+        assert!(!cache.is_cached("org/partial"));
+        let err = cache.resolve("org/partial", true).unwrap_err();
+        assert!(err.to_string().contains("dna model download org/partial"));
+        assert!(cache.resolve("org/absent", true).is_err());
+    }
+
+    #[test]
+    fn cache_lists_and_removes_models() {
+        let dir = tempfile::tempdir().unwrap();
+        seed_cache(
+            dir.path(),
+            "org/b-model",
+            &["config.json", "tokenizer.json", "pytorch_model.bin"],
+        );
+        seed_cache(dir.path(), "org/a-model", &["config.json"]);
+        std::fs::create_dir_all(dir.path().join("datasets--org--data")).unwrap();
+        let cache = ModelCache::new(dir.path().to_path_buf());
+
+        let models = cache.list().unwrap();
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["org/a-model", "org/b-model"]);
+        assert!(!models[0].complete);
+        assert!(models[1].complete);
+        assert!(models[1].size_bytes > 0);
+
+        assert!(cache.remove("org/a-model").unwrap());
+        assert!(!cache.remove("org/a-model").unwrap());
+        assert_eq!(cache.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn device_parses_names_and_ordinals() {
+        assert_eq!("cpu".parse::<LocalDevice>().unwrap(), LocalDevice::Cpu);
+        assert_eq!("AUTO".parse::<LocalDevice>().unwrap(), LocalDevice::Auto);
+        assert_eq!("cuda".parse::<LocalDevice>().unwrap(), LocalDevice::Cuda(0));
+        assert_eq!(
+            "cuda:1".parse::<LocalDevice>().unwrap(),
+            LocalDevice::Cuda(1)
+        );
+        assert_eq!(
+            "metal".parse::<LocalDevice>().unwrap(),
+            LocalDevice::Metal(0)
+        );
+        assert!("tpu".parse::<LocalDevice>().is_err());
+        assert!("cuda:x".parse::<LocalDevice>().is_err());
+        assert!("cpu:1".parse::<LocalDevice>().is_err());
+    }
+
+    #[test]
+    fn options_come_from_model_config() {
+        let config = ModelConfig {
+            device: Some("cuda:2".to_string()),
+            threads: Some(4),
+            batch_size: Some(8),
+            offline: Some(true),
+            ..Default::default()
+        };
+        let options = LocalOptions::from_config(&config).unwrap();
+        assert_eq!(options.device, LocalDevice::Cuda(2));
+        assert_eq!(options.threads, Some(4));
+        assert_eq!(options.batch_size, 8);
+        assert!(options.offline);
+
+        let defaults = LocalOptions::from_config(&ModelConfig::default()).unwrap();
+        assert_eq!(defaults.device, LocalDevice::Cpu);
+        assert_eq!(defaults.batch_size, DEFAULT_BATCH_SIZE);
+
+        let zero = ModelConfig {
+            batch_size: Some(0),
+            ..Default::default()
+        };
+        assert!(LocalOptions::from_config(&zero).is_err());
+    }
+
+    #[test]
+    fn batches_group_texts_by_length() {
+        let texts: Vec<String> = ["long text here", "a", "medium", "bb", "longest text of all"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let batches = length_sorted_batches(&texts, 2);
+        assert_eq!(batches, vec![vec![1, 3], vec![2, 0], vec![4]]);
+    }
+
+    #[test]
+    fn missing_cache_lists_nothing() {
+        let cache = ModelCache::new(PathBuf::from("/nonexistent/dna-model-cache"));
+        assert!(cache.list().unwrap().is_empty());
+    }
+
     // Integration tests - require network access to download models from HuggingFace.
     // Run with: cargo test --package dna -- --ignored
 
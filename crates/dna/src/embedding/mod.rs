@@ -1,9 +1,10 @@
+#[cfg(feature = "local-embedding")]
 pub mod local;
 pub mod ollama;
 pub mod openai;
 pub mod provider;
 
-pub use provider::EmbeddingProvider;
+pub use provider::{EmbeddingProvider, ProviderError};
 
 use crate::services::ModelConfig;
 use anyhow::{Context, Result};
@@ -12,8 +13,11 @@ use std::sync::Arc;
 /// Create an embedding provider from configuration
 pub async fn create_provider(config: &ModelConfig) -> Result<Arc<dyn EmbeddingProvider>> {
     match config.provider.as_str() {
+        #[cfg(feature = "local-embedding")]
         "local" => {
-            let provider = local::LocalEmbedding::new(&config.name)
+            let cache = local::ModelCache::for_config(config);
+            let options = local::LocalOptions::from_config(config)?;
+            let provider = local::LocalEmbedding::with_options(&config.name, &cache, options)
                 .await
                 .context("Failed to initialize local embedding provider")?;
             Ok(Arc::new(provider))
@@ -35,10 +39,29 @@ pub async fn create_provider(config: &ModelConfig) -> Result<Arc<dyn EmbeddingPr
             let provider = ollama::OllamaEmbedding::new(&config.name, base_url);
             Ok(Arc::new(provider))
         },
+        #[cfg(not(feature = "local-embedding"))]
+        "local" => {
+            anyhow::bail!("The local provider is not available in this build; use openai or ollama")
+        },
         _ => Err(anyhow::anyhow!("Unknown provider: {}", config.provider)),
     }
 }
 
+/// Providers for the `[[extra_models]]` that can be set up.
+///
+/// One that fails (a missing API key, a local model not downloaded yet) is
+/// skipped with a warning, so the store still opens on the models it has.
+pub async fn create_extra_providers(configs: &[ModelConfig]) -> Vec<Arc<dyn EmbeddingProvider>> {
+    let mut providers = Vec::with_capacity(configs.len());
+    for config in configs {
+        match create_provider(config).await {
+            Ok(provider) => providers.push(provider),
+            Err(e) => tracing::warn!("Skipping extra model {}: {:#}", config.name, e),
+        }
+    }
+    providers
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -51,6 +74,7 @@ mod tests {
             name: "BAAI/bge-small-en-v1.5".to_string(),
             api_key: None,
             base_url: None,
+            ..Default::default()
         };
         let provider = create_provider(&config).await.unwrap();
         assert_eq!(provider.model_id(), "BAAI/bge-small-en-v1.5");
@@ -64,6 +88,7 @@ mod tests {
             name: "nomic-embed-text".to_string(),
             api_key: None,
             base_url: None,
+            ..Default::default()
         };
         let provider = create_provider(&config).await.unwrap();
         assert_eq!(provider.model_id(), "nomic-embed-text");
@@ -77,6 +102,7 @@ mod tests {
             name: "model".to_string(),
             api_key: None,
             base_url: Some("http://custom:8080".to_string()),
+            ..Default::default()
         };
         let provider = create_provider(&config).await.unwrap();
         assert_eq!(provider.model_id(), "model");
@@ -89,6 +115,7 @@ mod tests {
             name: "model".to_string(),
             api_key: None,
             base_url: None,
+            ..Default::default()
         };
         let result = create_provider(&config).await;
         assert!(result.is_err());
@@ -101,6 +128,7 @@ mod tests {
             name: "text-embedding-3-small".to_string(),
             api_key: None,
             base_url: None,
+            ..Default::default()
         };
         let result = create_provider(&config).await;
         assert!(result.is_err());
@@ -1,4 +1,4 @@
-use super::provider::EmbeddingProvider;
+use super::provider::{EmbeddingProvider, ProviderError};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
@@ -42,7 +42,8 @@ impl OllamaEmbedding {
     }
 }
 
-#[async_trait::async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
 impl EmbeddingProvider for OllamaEmbedding {
     async fn embed(&self, text: &str) -> Result<Vec<f32>> {
         let request = OllamaEmbedRequest {
@@ -57,11 +58,15 @@ impl EmbeddingProvider for OllamaEmbedding {
             .json(&request)
             .send()
             .await
+            .map_err(|e| ProviderError::Unavailable(e.to_string()))
             .context("Failed to send Ollama API request")?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
+            if let Some(err) = ProviderError::from_status("Ollama", status, &text) {
+                return Err(err.into());
+            }
             return Err(anyhow::anyhow!("Ollama API error {}: {}", status, text));
         }
 
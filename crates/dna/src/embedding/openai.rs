@@ -1,4 +1,4 @@
-use super::provider::EmbeddingProvider;
+use super::provider::{EmbeddingProvider, ProviderError};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 
@@ -53,7 +53,8 @@ impl OpenAIEmbedding {
     }
 }
 
-#[async_trait::async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
 impl EmbeddingProvider for OpenAIEmbedding {
     async fn embed(&self, text: &str) -> Result<Vec<f32>> {
         let embeddings = self.embed_batch(&[text]).await?;
@@ -77,11 +78,15 @@ impl EmbeddingProvider for OpenAIEmbedding {
             .json(&request)
             .send()
             .await
+            .map_err(|e| ProviderError::Unavailable(e.to_string()))
             .context("Failed to send OpenAI API request")?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
+            if let Some(err) = ProviderError::from_status("OpenAI", status, &text) {
+                return Err(err.into());
+            }
             return Err(anyhow::anyhow!("OpenAI API error {}: {}", status, text));
         }
 
@@ -1,7 +1,36 @@
 use anyhow::Result;
 
-/// Trait for embedding providers
-#[async_trait::async_trait]
+/// Failures of a remote embedding provider that callers may want to retry
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum ProviderError {
+    /// The provider refused the request for being over its rate limit
+    #[error("embedding provider rate limited the request: {0}")]
+    RateLimited(String),
+
+    /// The provider could not be reached or failed on its side
+    #[error("embedding provider unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl ProviderError {
+    /// Classify an unsuccessful HTTP response, if its status is retryable
+    pub fn from_status(provider: &str, status: reqwest::StatusCode, body: &str) -> Option<Self> {
+        let message = format!("{} API error {}: {}", provider, status, body);
+        if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            Some(Self::RateLimited(message))
+        } else if status.is_server_error() {
+            Some(Self::Unavailable(message))
+        } else {
+            None
+        }
+    }
+}
+
+/// Trait for embedding providers.
+///
+/// Like [`Database`](crate::db::Database), futures are not `Send` on wasm32.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
 pub trait EmbeddingProvider: Send + Sync {
     /// Generate embedding for a single text
     async fn embed(&self, text: &str) -> Result<Vec<f32>>;
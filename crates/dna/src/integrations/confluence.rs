@@ -0,0 +1,244 @@
+//! Confluence Cloud pages via CQL search.
+
+use super::Page;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+/// Pages requested per search page
+const PAGE_SIZE: usize = 50;
+
+#[derive(Deserialize)]
+struct SearchPage {
+    #[serde(default)]
+    results: Vec<Content>,
+    #[serde(rename = "_links", default)]
+    links: Links,
+}
+
+#[derive(Deserialize, Default)]
+struct Links {
+    /// Path of the next page, relative to `/wiki`
+    next: Option<String>,
+    webui: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Content {
+    id: String,
+    title: String,
+    body: Option<Body>,
+    version: Version,
+    #[serde(rename = "_links", default)]
+    links: Links,
+}
+
+#[derive(Deserialize)]
+struct Body {
+    storage: Storage,
+}
+
+#[derive(Deserialize)]
+struct Storage {
+    value: String,
+}
+
+#[derive(Deserialize)]
+struct Version {
+    when: DateTime<Utc>,
+}
+
+/// Client for a Confluence Cloud site, authenticated with an account email and API token
+pub struct ConfluenceClient {
+    base_url: String,
+    email: String,
+    api_token: String,
+    client: reqwest::Client,
+}
+
+impl ConfluenceClient {
+    /// `base_url` is the site root, e.g. `https://example.atlassian.net`
+    pub fn new(base_url: &str, email: &str, api_token: &str) -> Self {
+        Self {
+            base_url: base_url
+                .trim_end_matches('/')
+                .trim_end_matches("/wiki")
+                .to_string(),
+            email: email.to_string(),
+            api_token: api_token.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Pages of a space with their content, up to `limit`.
+    ///
+    /// With `edited_after`, only pages modified since then are fetched.
+    /// CQL compares to the minute, so a page edited in the same minute
+    /// may come back again.
+    pub async fn space_pages(
+        &self,
+        space: &str,
+        edited_after: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Page>> {
+        let limit = limit.unwrap_or(usize::MAX);
+        let cql = space_query(space, edited_after);
+        let mut pages = Vec::new();
+        let page_size = PAGE_SIZE.min(limit).to_string();
+        let mut request = self
+            .client
+            .get(format!("{}/wiki/rest/api/content/search", self.base_url))
+            .query(&[
+                ("cql", cql.as_str()),
+                ("expand", "body.storage,version"),
+                ("limit", page_size.as_str()),
+            ]);
+
+        while pages.len() < limit {
+            let response = request
+                .basic_auth(&self.email, Some(&self.api_token))
+                .send()
+                .await
+                .context("Failed to reach Confluence")?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                anyhow::bail!("Confluence API error {}: {}", status, body);
+            }
+            let page: SearchPage = response
+                .json()
+                .await
+                .context("Failed to parse Confluence search response")?;
+
+            pages.extend(page.results.into_iter().map(|content| self.page(content)));
+            match page.links.next {
+                Some(next) => request = self.client.get(format!("{}/wiki{}", self.base_url, next)),
+                None => break,
+            }
+        }
+
+        pages.truncate(limit);
+        Ok(pages)
+    }
+
+    fn page(&self, content: Content) -> Page {
+        let url = match content.links.webui {
+            Some(path) => format!("{}/wiki{}", self.base_url, path),
+            None => format!("{}/wiki/pages/{}", self.base_url, content.id),
+        };
+        Page {
+            content: content
+                .body
+                .map(|body| storage_to_markdown(&body.storage.value))
+                .unwrap_or_default(),
+            id: content.id,
+            title: content.title,
+            url,
+            edited_at: content.version.when,
+        }
+    }
+}
+
+fn space_query(space: &str, edited_after: Option<DateTime<Utc>>) -> String {
+    let mut cql = format!("space = \"{}\" and type = page", space.replace('"', "\\\""));
+    if let Some(after) = edited_after {
+        cql.push_str(&format!(
+            " and lastmodified >= \"{}\"",
+            after.format("%Y/%m/%d %H:%M")
+        ));
+    }
+    cql.push_str(" order by lastmodified");
+    cql
+}
+
+static CODE_MACRO: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<ac:structured-macro[^>]*ac:name="code"[^>]*>.*?<ac:plain-text-body><!\[CDATA\[(.*?)\]\]></ac:plain-text-body>.*?</ac:structured-macro>"#).unwrap()
+});
+static CDATA: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<!\[CDATA\[(.*?)\]\]>").unwrap());
+static PARAMETER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?s)<ac:parameter[^>]*>.*?</ac:parameter>").unwrap());
+static PAGE_LINK: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?s)<ac:link>\s*<ri:page[^>]*ri:content-title="([^"]*)"[^>]*/>\s*</ac:link>"#)
+        .unwrap()
+});
+
+/// Render a page body in Confluence storage format (XHTML with `ac:`
+/// macros) as Markdown.
+///
+/// Code macros become fenced blocks and links to other pages become their
+/// titles; other macros contribute their rich text body, if any.
+pub fn storage_to_markdown(storage: &str) -> String {
+    let html = CODE_MACRO.replace_all(storage, |caps: &Captures| {
+        format!("<pre><code>{}</code></pre>", escape_html(&caps[1]))
+    });
+    let html = CDATA.replace_all(&html, |caps: &Captures| escape_html(&caps[1]));
+    let html = PARAMETER.replace_all(&html, "");
+    let html = PAGE_LINK.replace_all(&html, "$1");
+    html2md::parse_html(&html).trim().to_string()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn converts_storage_format_to_markdown() {
+        let storage = r#"<p>Users sign in with <strong>SSO</strong>, see <ac:link><ri:page ri:content-title="Auth RFC" /></ac:link>.</p><ul><li>Okta</li><li>Google</li></ul><ac:structured-macro ac:name="code" ac:schema-version="1"><ac:parameter ac:name="language">sh</ac:parameter><ac:plain-text-body><![CDATA[dna add intent "a < b"]]></ac:plain-text-body></ac:structured-macro><ac:structured-macro ac:name="info"><ac:parameter ac:name="title">Note</ac:parameter><ac:rich-text-body><p>Okta only.</p></ac:rich-text-body></ac:structured-macro>"#;
+
+        let markdown = storage_to_markdown(storage);
+        assert!(
+            markdown.starts_with("Users sign in with **SSO**, see Auth RFC."),
+            "{markdown}"
+        );
+        assert!(markdown.contains("* Okta\n* Google"), "{markdown}");
+        assert!(
+            markdown.contains("```\ndna add intent \"a < b\"\n```"),
+            "{markdown}"
+        );
+        assert!(markdown.ends_with("Okta only."), "{markdown}");
+        assert!(!markdown.contains("Note"), "{markdown}");
+    }
+
+    #[test]
+    fn parses_search_results_into_pages() {
+        let page: SearchPage = serde_json::from_value(json!({
+            "results": [{
+                "id": "65538",
+                "title": "Login",
+                "body": { "storage": { "value": "<p>SSO only</p>" } },
+                "version": { "when": "2024-05-02T10:15:00.000Z" },
+                "_links": { "webui": "/spaces/ENG/pages/65538/Login" }
+            }],
+            "_links": {}
+        }))
+        .unwrap();
+        assert!(page.links.next.is_none());
+
+        let client = ConfluenceClient::new(
+            "https://example.atlassian.net/wiki/",
+            "me@example.com",
+            "token",
+        );
+        let page = client.page(page.results.into_iter().next().unwrap());
+        assert_eq!(
+            page.url,
+            "https://example.atlassian.net/wiki/spaces/ENG/pages/65538/Login"
+        );
+        assert_eq!(page.content, "SSO only");
+
+        let since = "2024-05-02T10:15:00Z".parse().unwrap();
+        assert_eq!(
+            space_query("ENG", Some(since)),
+            "space = \"ENG\" and type = page and lastmodified >= \"2024/05/02 10:15\" order by lastmodified"
+        );
+    }
+}
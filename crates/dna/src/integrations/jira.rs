@@ -0,0 +1,290 @@
+//! Jira Cloud issue search.
+
+use super::Ticket;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Issues requested per page (the API's maximum)
+const PAGE_SIZE: usize = 100;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchPage {
+    #[serde(default)]
+    issues: Vec<Issue>,
+    next_page_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Issue {
+    key: String,
+    fields: IssueFields,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IssueFields {
+    summary: String,
+    /// Atlassian Document Format
+    description: Option<Value>,
+    issuetype: Option<Named>,
+}
+
+#[derive(Deserialize)]
+struct Named {
+    name: String,
+}
+
+/// Client for a Jira Cloud site, authenticated with an account email and API token
+pub struct JiraClient {
+    base_url: String,
+    email: String,
+    api_token: String,
+    client: reqwest::Client,
+}
+
+impl JiraClient {
+    /// `base_url` is the site root, e.g. `https://example.atlassian.net`
+    pub fn new(base_url: &str, email: &str, api_token: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            email: email.to_string(),
+            api_token: api_token.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Issues matching a JQL query, up to `limit`
+    pub async fn search(&self, jql: &str, limit: Option<usize>) -> Result<Vec<Ticket>> {
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut tickets = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        while tickets.len() < limit {
+            let page_size = PAGE_SIZE.min(limit - tickets.len()).to_string();
+            let mut query = vec![
+                ("jql", jql),
+                ("fields", "summary,description,issuetype"),
+                ("maxResults", page_size.as_str()),
+            ];
+            if let Some(token) = &page_token {
+                query.push(("nextPageToken", token));
+            }
+
+            let response = self
+                .client
+                .get(format!("{}/rest/api/3/search/jql", self.base_url))
+                .basic_auth(&self.email, Some(&self.api_token))
+                .query(&query)
+                .send()
+                .await
+                .context("Failed to reach Jira")?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                anyhow::bail!("Jira API error {}: {}", status, body);
+            }
+            let page: SearchPage = response
+                .json()
+                .await
+                .context("Failed to parse Jira search response")?;
+
+            tickets.extend(page.issues.into_iter().map(|issue| self.ticket(issue)));
+            match page.next_page_token {
+                Some(token) => page_token = Some(token),
+                None => break,
+            }
+        }
+
+        tickets.truncate(limit);
+        Ok(tickets)
+    }
+
+    fn ticket(&self, issue: Issue) -> Ticket {
+        Ticket {
+            url: format!("{}/browse/{}", self.base_url, issue.key),
+            key: issue.key,
+            title: issue.fields.summary,
+            description: issue.fields.description.as_ref().map(adf_to_markdown),
+            types: issue.fields.issuetype.map(|t| t.name).into_iter().collect(),
+        }
+    }
+}
+
+/// Render an Atlassian Document Format tree as Markdown.
+///
+/// Covers the nodes issue descriptions are made of; anything else
+/// contributes the text of its children.
+pub fn adf_to_markdown(doc: &Value) -> String {
+    blocks(std::slice::from_ref(doc))
+}
+
+fn children(node: &Value) -> &[Value] {
+    node["content"].as_array().map_or(&[], Vec::as_slice)
+}
+
+/// Append a block node and the blank line after it
+fn block(node: &Value, out: &mut String) {
+    match node["type"].as_str().unwrap_or_default() {
+        "doc" => children(node).iter().for_each(|c| block(c, out)),
+        "paragraph" => {
+            out.push_str(&inline(node));
+            out.push_str("\n\n");
+        },
+        "heading" => {
+            let level = node["attrs"]["level"].as_u64().unwrap_or(1).clamp(1, 6) as usize;
+            out.push_str(&format!("{} {}\n\n", "#".repeat(level), inline(node)));
+        },
+        "bulletList" | "orderedList" => {
+            let ordered = node["type"] == "orderedList";
+            for (i, item) in children(node).iter().enumerate() {
+                let marker = if ordered {
+                    format!("{}. ", i + 1)
+                } else {
+                    "- ".to_string()
+                };
+                // Item content, nested lists included, indented under the marker
+                let body = blocks(children(item));
+                for (n, line) in body.lines().filter(|l| !l.is_empty()).enumerate() {
+                    if n == 0 {
+                        out.push_str(&marker);
+                    } else {
+                        out.push_str(&" ".repeat(marker.len()));
+                    }
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+            out.push('\n');
+        },
+        "codeBlock" => {
+            let language = node["attrs"]["language"].as_str().unwrap_or_default();
+            out.push_str(&format!("```{}\n{}\n```\n\n", language, inline(node)));
+        },
+        "blockquote" => {
+            for line in blocks(children(node)).lines() {
+                if line.is_empty() {
+                    out.push_str(">\n");
+                } else {
+                    out.push_str(&format!("> {}\n", line));
+                }
+            }
+            out.push('\n');
+        },
+        "rule" => out.push_str("---\n\n"),
+        _ if children(node).iter().any(|c| c["type"] == "text") => {
+            out.push_str(&inline(node));
+            out.push_str("\n\n");
+        },
+        _ => children(node).iter().for_each(|c| block(c, out)),
+    }
+}
+
+/// Block nodes as Markdown, without the trailing blank line
+fn blocks(nodes: &[Value]) -> String {
+    let mut out = String::new();
+    nodes.iter().for_each(|node| block(node, &mut out));
+    out.trim_end().to_string()
+}
+
+/// The inline content of a node as Markdown text
+fn inline(node: &Value) -> String {
+    children(node).iter().map(inline_node).collect()
+}
+
+fn inline_node(node: &Value) -> String {
+    match node["type"].as_str().unwrap_or_default() {
+        "text" => {
+            let mut text = node["text"].as_str().unwrap_or_default().to_string();
+            for mark in node["marks"].as_array().map_or(&[][..], Vec::as_slice) {
+                text = match mark["type"].as_str().unwrap_or_default() {
+                    "strong" => format!("**{}**", text),
+                    "em" => format!("_{}_", text),
+                    "code" => format!("`{}`", text),
+                    "strike" => format!("~~{}~~", text),
+                    "link" => match mark["attrs"]["href"].as_str() {
+                        Some(href) => format!("[{}]({})", text, href),
+                        None => text,
+                    },
+                    _ => text,
+                };
+            }
+            text
+        },
+        "hardBreak" => "\n".to_string(),
+        "mention" => node["attrs"]["text"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        "emoji" => node["attrs"]["text"]
+            .as_str()
+            .or_else(|| node["attrs"]["shortName"].as_str())
+            .unwrap_or_default()
+            .to_string(),
+        "inlineCard" => node["attrs"]["url"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        _ => inline(node),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn text(s: &str) -> Value {
+        json!({ "type": "text", "text": s })
+    }
+
+    #[test]
+    fn converts_description_to_markdown() {
+        let doc = json!({
+            "type": "doc",
+            "content": [
+                { "type": "heading", "attrs": { "level": 2 }, "content": [text("Goal")] },
+                { "type": "paragraph", "content": [
+                    text("Users sign in with "),
+                    { "type": "text", "text": "SSO", "marks": [{ "type": "strong" }] },
+                    text(", see "),
+                    { "type": "text", "text": "the RFC", "marks": [{ "type": "link", "attrs": { "href": "https://rfc" } }] },
+                ]},
+                { "type": "bulletList", "content": [
+                    { "type": "listItem", "content": [{ "type": "paragraph", "content": [text("Okta")] }] },
+                    { "type": "listItem", "content": [{ "type": "paragraph", "content": [text("Google")] }] },
+                ]},
+                { "type": "codeBlock", "attrs": { "language": "sh" }, "content": [text("dna add intent")] },
+            ]
+        });
+
+        assert_eq!(
+            adf_to_markdown(&doc),
+            "## Goal\n\nUsers sign in with **SSO**, see [the RFC](https://rfc)\n\n- Okta\n- Google\n\n```sh\ndna add intent\n```"
+        );
+    }
+
+    #[test]
+    fn parses_search_page_into_tickets() {
+        let page: SearchPage = serde_json::from_value(json!({
+            "issues": [{
+                "key": "PROJ-12",
+                "fields": {
+                    "summary": "SSO login",
+                    "description": null,
+                    "issuetype": { "name": "Story" }
+                }
+            }],
+            "isLast": true
+        }))
+        .unwrap();
+        assert!(page.next_page_token.is_none());
+
+        let client = JiraClient::new("https://example.atlassian.net/", "me@example.com", "token");
+        let ticket = client.ticket(page.issues.into_iter().next().unwrap());
+        assert_eq!(ticket.url, "https://example.atlassian.net/browse/PROJ-12");
+        assert_eq!(ticket.types, vec!["Story"]);
+        assert_eq!(ticket.description, None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Linear issues over the GraphQL API.
+
+use super::Ticket;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::json;
+
+const DEFAULT_API_URL: &str = "https://api.linear.app/graphql";
+
+/// Issues requested per page
+const PAGE_SIZE: usize = 100;
+
+const ISSUES_QUERY: &str = "query Issues($team: String!, $first: Int!, $after: String) {
+  issues(first: $first, after: $after, filter: { team: { key: { eq: $team } } }) {
+    nodes { identifier title description url labels { nodes { name } } }
+    pageInfo { hasNextPage endCursor }
+  }
+}";
+
+#[derive(Deserialize)]
+struct Response {
+    data: Option<Data>,
+    #[serde(default)]
+    errors: Vec<GraphQlError>,
+}
+
+#[derive(Deserialize)]
+struct GraphQlError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Data {
+    issues: Connection<Issue>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Connection<T> {
+    nodes: Vec<T>,
+    page_info: Option<PageInfo>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Issue {
+    identifier: String,
+    title: String,
+    description: Option<String>,
+    url: String,
+    labels: Connection<Label>,
+}
+
+#[derive(Deserialize)]
+struct Label {
+    name: String,
+}
+
+impl From<Issue> for Ticket {
+    fn from(issue: Issue) -> Self {
+        Self {
+            key: issue.identifier,
+            title: issue.title,
+            description: issue.description,
+            types: issue.labels.nodes.into_iter().map(|l| l.name).collect(),
+            url: issue.url,
+        }
+    }
+}
+
+/// Client for the Linear API, authenticated with a personal API key
+pub struct LinearClient {
+    api_key: String,
+    api_url: String,
+    client: reqwest::Client,
+}
+
+impl LinearClient {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            api_url: DEFAULT_API_URL.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Issues of the team with this key (e.g. `ENG`), up to `limit`.
+    ///
+    /// Labels become the ticket's types, so kind rules can match on them.
+    pub async fn team_issues(&self, team: &str, limit: Option<usize>) -> Result<Vec<Ticket>> {
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut tickets = Vec::new();
+        let mut after: Option<String> = None;
+
+        while tickets.len() < limit {
+            let body = json!({
+                "query": ISSUES_QUERY,
+                "variables": {
+                    "team": team,
+                    "first": PAGE_SIZE.min(limit - tickets.len()),
+                    "after": after,
+                },
+            });
+            let response = self
+                .client
+                .post(&self.api_url)
+                .header("Authorization", &self.api_key)
+                .json(&body)
+                .send()
+                .await
+                .context("Failed to reach Linear")?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                anyhow::bail!("Linear API error {}: {}", status, body);
+            }
+            let response: Response = response
+                .json()
+                .await
+                .context("Failed to parse Linear response")?;
+            let issues = response.into_issues()?;
+
+            tickets.extend(issues.nodes.into_iter().map(Ticket::from));
+            match issues.page_info {
+                Some(PageInfo {
+                    has_next_page: true,
+                    end_cursor: Some(cursor),
+                }) => after = Some(cursor),
+                _ => break,
+            }
+        }
+
+        tickets.truncate(limit);
+        Ok(tickets)
+    }
+}
+
+impl Response {
+    fn into_issues(self) -> Result<Connection<Issue>> {
+        if !self.errors.is_empty() {
+            let messages: Vec<_> = self.errors.into_iter().map(|e| e.message).collect();
+            anyhow::bail!("Linear API error: {}", messages.join("; "));
+        }
+        self.data
+            .map(|data| data.issues)
+            .context("Linear response has no data")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_issues_with_labels_as_types() {
+        let response: Response = serde_json::from_value(json!({
+            "data": { "issues": {
+                "nodes": [{
+                    "identifier": "ENG-7",
+                    "title": "Retry failed webhooks",
+                    "description": "Three attempts, then alert.",
+                    "url": "https://linear.app/acme/issue/ENG-7",
+                    "labels": { "nodes": [{ "name": "Bug" }] }
+                }],
+                "pageInfo": { "hasNextPage": false, "endCursor": null }
+            }}
+        }))
+        .unwrap();
+
+        let issues = response.into_issues().unwrap();
+        let ticket = Ticket::from(issues.nodes.into_iter().next().unwrap());
+        assert_eq!(ticket.key, "ENG-7");
+        assert_eq!(ticket.types, vec!["Bug"]);
+        assert_eq!(
+            ticket.description.as_deref(),
+            Some("Three attempts, then alert.")
+        );
+    }
+
+    #[test]
+    fn surfaces_graphql_errors() {
+        let response: Response = serde_json::from_value(json!({
+            "data": null,
+            "errors": [{ "message": "Team not found" }]
+        }))
+        .unwrap();
+        let err = response.into_issues().err().unwrap();
+        assert!(err.to_string().contains("Team not found"), "{err}");
+    }
+}
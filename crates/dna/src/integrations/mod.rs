@@ -0,0 +1,156 @@
+//! Clients that pull records from external systems for ingestion.
+//!
+//! Trackers yield [`Ticket`]s and wikis yield [`Page`]s, which become
+//! [`IngestItem`]s; the
+//! [`IngestService`](crate::services::IngestService) then creates or updates
+//! the matching artifacts.
+
+pub mod confluence;
+pub mod jira;
+pub mod linear;
+pub mod notion;
+
+use crate::services::IngestItem;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// An issue from a tracker
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ticket {
+    /// Tracker-assigned key, e.g. `PROJ-12`
+    pub key: String,
+    pub title: String,
+    /// Markdown description
+    pub description: Option<String>,
+    /// Issue type or labels, used to choose the artifact kind
+    pub types: Vec<String>,
+    pub url: String,
+}
+
+/// Chooses the artifact kind for a ticket from its types
+#[derive(Debug, Clone)]
+pub struct KindRules {
+    /// Kind for tickets no rule matches
+    pub default: String,
+    /// Ticket type or label (case-insensitive) to kind, e.g. `bug` to `evaluation`
+    pub by_type: HashMap<String, String>,
+}
+
+impl Default for KindRules {
+    fn default() -> Self {
+        Self {
+            default: "intent".to_string(),
+            by_type: HashMap::new(),
+        }
+    }
+}
+
+impl KindRules {
+    pub fn kind_for(&self, ticket: &Ticket) -> &str {
+        ticket
+            .types
+            .iter()
+            .find_map(|t| {
+                self.by_type
+                    .iter()
+                    .find(|(rule, _)| rule.eq_ignore_ascii_case(t))
+                    .map(|(_, kind)| kind.as_str())
+            })
+            .unwrap_or(&self.default)
+    }
+}
+
+impl Ticket {
+    /// The artifact for this ticket, with `system` naming the tracker in its
+    /// source label (`jira:PROJ-12`)
+    pub fn into_item(self, system: &str, kinds: &KindRules) -> IngestItem {
+        let kind = kinds.kind_for(&self).to_string();
+        let mut content = format!("# {}\n\n", self.title);
+        if let Some(description) = self.description.as_deref().map(str::trim) {
+            if !description.is_empty() {
+                content.push_str(description);
+                content.push_str("\n\n");
+            }
+        }
+        content.push_str(&format!("Source: [{}]({})\n", self.key, self.url));
+
+        IngestItem {
+            source: format!("{}:{}", system, self.key),
+            kind,
+            name: self.title,
+            content,
+            labels: HashMap::new(),
+        }
+    }
+}
+
+/// A page from a wiki
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    /// Wiki-assigned page ID
+    pub id: String,
+    pub title: String,
+    /// Markdown body
+    pub content: String,
+    pub url: String,
+    /// When the page was last edited, for incremental ingestion
+    pub edited_at: DateTime<Utc>,
+}
+
+impl Page {
+    /// The artifact for this page, with `system` naming the wiki in its
+    /// source label (`notion:<page id>`)
+    pub fn into_item(self, system: &str, kind: &str) -> IngestItem {
+        let mut content = format!("# {}\n\n", self.title);
+        let body = self.content.trim();
+        if !body.is_empty() {
+            content.push_str(body);
+            content.push_str("\n\n");
+        }
+        content.push_str(&format!("Source: [{}]({})\n", self.title, self.url));
+
+        IngestItem {
+            source: format!("{}:{}", system, self.id),
+            kind: kind.to_string(),
+            name: self.title,
+            content,
+            labels: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticket(types: &[&str]) -> Ticket {
+        Ticket {
+            key: "PROJ-12".to_string(),
+            title: "Users sign in with SSO".to_string(),
+            description: Some("Okta only.\n".to_string()),
+            types: types.iter().map(|t| t.to_string()).collect(),
+            url: "https://example.atlassian.net/browse/PROJ-12".to_string(),
+        }
+    }
+
+    #[test]
+    fn kind_rules_match_types_case_insensitively() {
+        let rules = KindRules {
+            by_type: HashMap::from([("bug".to_string(), "evaluation".to_string())]),
+            ..Default::default()
+        };
+        assert_eq!(rules.kind_for(&ticket(&["Story"])), "intent");
+        assert_eq!(rules.kind_for(&ticket(&["Bug"])), "evaluation");
+    }
+
+    #[test]
+    fn item_links_back_to_the_ticket() {
+        let item = ticket(&[]).into_item("jira", &KindRules::default());
+        assert_eq!(item.source, "jira:PROJ-12");
+        assert_eq!(item.kind, "intent");
+        assert_eq!(
+            item.content,
+            "# Users sign in with SSO\n\nOkta only.\n\nSource: [PROJ-12](https://example.atlassian.net/browse/PROJ-12)\n"
+        );
+    }
+}
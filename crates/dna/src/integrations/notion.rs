@@ -0,0 +1,375 @@
+//! Notion database pages over the public API.
+
+use super::Page;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use futures::future::{BoxFuture, FutureExt};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+const DEFAULT_API_URL: &str = "https://api.notion.com/v1";
+
+/// API version the request and response shapes below follow
+const NOTION_VERSION: &str = "2022-06-28";
+
+/// Results requested per page (the API's maximum)
+const PAGE_SIZE: usize = 100;
+
+#[derive(Deserialize)]
+struct List<T> {
+    results: Vec<T>,
+    #[serde(default)]
+    has_more: bool,
+    next_cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NotionPage {
+    id: String,
+    url: String,
+    last_edited_time: DateTime<Utc>,
+    #[serde(default)]
+    properties: serde_json::Map<String, Value>,
+}
+
+impl NotionPage {
+    /// Text of the page's title property, whatever it is called
+    fn title(&self) -> String {
+        self.properties
+            .values()
+            .find(|p| p["type"] == "title")
+            .map(|p| rich_text(&p["title"]))
+            .unwrap_or_default()
+    }
+}
+
+/// Client for the Notion API, authenticated with an integration token
+pub struct NotionClient {
+    token: String,
+    api_url: String,
+    client: reqwest::Client,
+}
+
+impl NotionClient {
+    pub fn new(token: &str) -> Self {
+        Self {
+            token: token.to_string(),
+            api_url: DEFAULT_API_URL.to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Pages of a database with their content, up to `limit`.
+    ///
+    /// With `edited_after`, only pages edited since then are fetched.
+    pub async fn database_pages(
+        &self,
+        database_id: &str,
+        edited_after: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Page>> {
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut found = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        while found.len() < limit {
+            // Oldest edits first, so a limited run can be continued from its last page
+            let mut body = json!({
+                "page_size": PAGE_SIZE.min(limit - found.len()),
+                "sorts": [{ "timestamp": "last_edited_time", "direction": "ascending" }],
+            });
+            if let Some(cursor) = &cursor {
+                body["start_cursor"] = json!(cursor);
+            }
+            if let Some(after) = edited_after {
+                body["filter"] = json!({
+                    "timestamp": "last_edited_time",
+                    "last_edited_time": { "after": after.to_rfc3339() },
+                });
+            }
+            let request = self
+                .client
+                .post(format!("{}/databases/{}/query", self.api_url, database_id))
+                .json(&body);
+            let list: List<NotionPage> = self.send(request).await?;
+
+            found.extend(list.results);
+            match list.next_cursor {
+                Some(next) if list.has_more => cursor = Some(next),
+                _ => break,
+            }
+        }
+        found.truncate(limit);
+
+        let mut pages = Vec::with_capacity(found.len());
+        for page in found {
+            let blocks = self.block_tree(page.id.clone()).await?;
+            pages.push(Page {
+                title: page.title(),
+                content: blocks_to_markdown(&blocks),
+                id: page.id,
+                url: page.url,
+                edited_at: page.last_edited_time,
+            });
+        }
+        Ok(pages)
+    }
+
+    /// A block's children, each with its own children nested under `children`
+    fn block_tree(&self, block_id: String) -> BoxFuture<'_, Result<Vec<Value>>> {
+        async move {
+            let mut blocks = Vec::new();
+            let mut cursor: Option<String> = None;
+            loop {
+                let mut query = vec![("page_size", PAGE_SIZE.to_string())];
+                if let Some(cursor) = &cursor {
+                    query.push(("start_cursor", cursor.clone()));
+                }
+                let request = self
+                    .client
+                    .get(format!("{}/blocks/{}/children", self.api_url, block_id))
+                    .query(&query);
+                let list: List<Value> = self.send(request).await?;
+
+                blocks.extend(list.results);
+                match list.next_cursor {
+                    Some(next) if list.has_more => cursor = Some(next),
+                    _ => break,
+                }
+            }
+
+            for block in &mut blocks {
+                if block["has_children"] == true {
+                    let id = block["id"].as_str().unwrap_or_default().to_string();
+                    block["children"] = Value::Array(self.block_tree(id).await?);
+                }
+            }
+            Ok(blocks)
+        }
+        .boxed()
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: reqwest::RequestBuilder) -> Result<T> {
+        let response = request
+            .bearer_auth(&self.token)
+            .header("Notion-Version", NOTION_VERSION)
+            .send()
+            .await
+            .context("Failed to reach Notion")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Notion API error {}: {}", status, body);
+        }
+        response
+            .json()
+            .await
+            .context("Failed to parse Notion response")
+    }
+}
+
+/// Render Notion blocks as Markdown.
+///
+/// Blocks are expected with their children nested under `children`, as
+/// the client fetches them. Unsupported blocks (embeds, databases, ...)
+/// are skipped.
+pub fn blocks_to_markdown(blocks: &[Value]) -> String {
+    let mut out = String::new();
+    let mut previous = "";
+    let mut number = 0;
+
+    for block in blocks {
+        let kind = block["type"].as_str().unwrap_or_default();
+        number = if kind == "numbered_list_item" {
+            if previous == kind {
+                number + 1
+            } else {
+                1
+            }
+        } else {
+            0
+        };
+        let Some(text) = block_to_markdown(block, number) else {
+            continue;
+        };
+
+        // Consecutive items of one list stay together
+        if !out.is_empty() {
+            let same_list = previous == kind && (kind.ends_with("list_item") || kind == "to_do");
+            out.push_str(if same_list { "\n" } else { "\n\n" });
+        }
+        out.push_str(&text);
+        previous = kind;
+    }
+    out
+}
+
+fn block_to_markdown(block: &Value, number: usize) -> Option<String> {
+    let kind = block["type"].as_str().unwrap_or_default();
+    let data = &block[kind];
+    let text = rich_text(&data["rich_text"]);
+    let children = block["children"].as_array().map_or(&[][..], Vec::as_slice);
+    let nested = blocks_to_markdown(children);
+
+    let markdown = match kind {
+        "paragraph" => join_blocks(&text, &nested),
+        "heading_1" => format!("# {}", text),
+        "heading_2" => format!("## {}", text),
+        "heading_3" => format!("### {}", text),
+        "bulleted_list_item" => list_item("- ", &text, &nested),
+        "numbered_list_item" => list_item(&format!("{}. ", number), &text, &nested),
+        "to_do" => {
+            let marker = if data["checked"] == true {
+                "- [x] "
+            } else {
+                "- [ ] "
+            };
+            list_item(marker, &text, &nested)
+        },
+        "toggle" => join_blocks(&text, &nested),
+        "code" => {
+            let language = data["language"].as_str().unwrap_or_default();
+            let language = if language == "plain text" {
+                ""
+            } else {
+                language
+            };
+            format!("```{}\n{}\n```", language, text)
+        },
+        "quote" | "callout" => quote(&join_blocks(&text, &nested)),
+        "divider" => "---".to_string(),
+        "image" => {
+            let url = data["file"]["url"]
+                .as_str()
+                .or_else(|| data["external"]["url"].as_str())?;
+            format!("![{}]({})", rich_text(&data["caption"]), url)
+        },
+        "bookmark" | "embed" | "link_preview" => {
+            let url = data["url"].as_str()?;
+            format!("<{}>", url)
+        },
+        "child_page" => format!("**{}**", data["title"].as_str().unwrap_or_default()),
+        _ => return None,
+    };
+    Some(markdown)
+}
+
+fn join_blocks(text: &str, nested: &str) -> String {
+    match (text.is_empty(), nested.is_empty()) {
+        (_, true) => text.to_string(),
+        (true, false) => nested.to_string(),
+        (false, false) => format!("{}\n\n{}", text, nested),
+    }
+}
+
+/// A list item, with nested blocks indented under the marker
+fn list_item(marker: &str, text: &str, nested: &str) -> String {
+    let mut out = format!("{}{}", marker, text);
+    let indent = " ".repeat(marker.len());
+    for line in nested.lines() {
+        out.push('\n');
+        if !line.is_empty() {
+            out.push_str(&indent);
+            out.push_str(line);
+        }
+    }
+    out
+}
+
+fn quote(text: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                ">".to_string()
+            } else {
+                format!("> {}", line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A rich text array as Markdown
+fn rich_text(spans: &Value) -> String {
+    let Some(spans) = spans.as_array() else {
+        return String::new();
+    };
+    spans
+        .iter()
+        .map(|span| {
+            let mut text = span["plain_text"].as_str().unwrap_or_default().to_string();
+            let annotations = &span["annotations"];
+            if annotations["code"] == true {
+                text = format!("`{}`", text);
+            }
+            if annotations["bold"] == true {
+                text = format!("**{}**", text);
+            }
+            if annotations["italic"] == true {
+                text = format!("_{}_", text);
+            }
+            if annotations["strikethrough"] == true {
+                text = format!("~~{}~~", text);
+            }
+            match span["href"].as_str() {
+                Some(href) => format!("[{}]({})", text, href),
+                None => text,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> Value {
+        json!([{ "plain_text": s, "annotations": {}, "href": null }])
+    }
+
+    fn block(kind: &str, s: &str) -> Value {
+        json!({ "type": kind, "has_children": false, kind: { "rich_text": text(s) } })
+    }
+
+    #[test]
+    fn converts_blocks_to_markdown() {
+        let mut item = block("bulleted_list_item", "Okta");
+        item["has_children"] = json!(true);
+        item["children"] = json!([block("bulleted_list_item", "SAML only")]);
+        let blocks = vec![
+            block("heading_2", "Goal"),
+            json!({ "type": "paragraph", "paragraph": { "rich_text": [
+                { "plain_text": "Users sign in with ", "annotations": {} },
+                { "plain_text": "SSO", "annotations": { "bold": true } },
+                { "plain_text": "the RFC", "annotations": {}, "href": "https://rfc" },
+            ]}}),
+            item,
+            block("bulleted_list_item", "Google"),
+            block("numbered_list_item", "Redirect"),
+            block("numbered_list_item", "Verify"),
+            json!({ "type": "code", "code": { "rich_text": text("dna add intent"), "language": "shell" } }),
+            json!({ "type": "unsupported", "unsupported": {} }),
+        ];
+
+        assert_eq!(
+            blocks_to_markdown(&blocks),
+            "## Goal\n\nUsers sign in with **SSO**[the RFC](https://rfc)\n\n- Okta\n  - SAML only\n- Google\n\n1. Redirect\n2. Verify\n\n```shell\ndna add intent\n```"
+        );
+    }
+
+    #[test]
+    fn finds_the_title_property() {
+        let page: NotionPage = serde_json::from_value(json!({
+            "id": "59833787-2cf9-4fdf-8782-e53db20768a5",
+            "url": "https://www.notion.so/SSO-598337872cf94fdf8782e53db20768a5",
+            "last_edited_time": "2024-05-02T10:15:00.000Z",
+            "properties": {
+                "Status": { "type": "select", "select": null },
+                "Page": { "type": "title", "title": text("SSO") }
+            }
+        }))
+        .unwrap();
+        assert_eq!(page.title(), "SSO");
+    }
+}
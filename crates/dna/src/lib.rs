@@ -1,8 +1,19 @@
 pub mod db;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod editor;
 pub mod embedding;
+pub mod integrations;
+#[cfg(feature = "mcp")]
 pub mod mcp;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod render;
 pub mod services;
+pub mod store;
+
+pub use store::{DnaStore, NewArtifact, StoreSource};
+
+#[cfg(all(feature = "cuda", feature = "metal"))]
+compile_error!("the `cuda` and `metal` features pick different GPU backends; enable only one");
 
 /// Test utilities for unit and integration testing.
 /// Only available with cfg(test) or feature "testing".
@@ -0,0 +1,123 @@
+//! Token budgets for MCP tool output.
+//!
+//! Agents with small context windows can pass `max_tokens` to the search and
+//! list tools. Artifacts are kept whole, in result order, until the budget
+//! runs out; the artifact that crosses it is cut short and later ones keep
+//! their IDs and metadata with empty content, so the agent can fetch the
+//! ones it needs with `dna_get`.
+
+use crate::services::types::estimate_tokens;
+use crate::services::Artifact;
+
+/// Fit artifact content and context into `max_tokens`, as counted by
+/// [`estimate_tokens`].
+///
+/// Returns the IDs of artifacts whose content or context was cut.
+pub fn fit<'a>(
+    artifacts: impl IntoIterator<Item = &'a mut Artifact>,
+    max_tokens: usize,
+) -> Vec<String> {
+    let mut remaining = max_tokens;
+    let mut truncated = Vec::new();
+
+    for artifact in artifacts {
+        let content_tokens = estimate_tokens(&artifact.content);
+        let context_tokens = artifact.context.as_deref().map_or(0, estimate_tokens);
+        if content_tokens + context_tokens <= remaining {
+            remaining -= content_tokens + context_tokens;
+            continue;
+        }
+
+        artifact.context = None;
+        if content_tokens > remaining {
+            artifact.content = truncate_to_tokens(&artifact.content, remaining);
+        }
+        remaining = remaining.saturating_sub(estimate_tokens(&artifact.content));
+        truncated.push(artifact.id.clone());
+    }
+
+    truncated
+}
+
+/// The longest prefix of `text`, ending at a word, that fits in `max_tokens`
+fn truncate_to_tokens(text: &str, max_tokens: usize) -> String {
+    // estimate_tokens counts 4 tokens per 3 words
+    let max_words = max_tokens * 3 / 4;
+    if max_words == 0 {
+        return String::new();
+    }
+
+    let mut words = 0;
+    let mut in_word = false;
+    for (i, c) in text.char_indices() {
+        if !c.is_whitespace() {
+            in_word = true;
+        } else if in_word {
+            in_word = false;
+            words += 1;
+            if words == max_words {
+                return text[..i].to_string();
+            }
+        }
+    }
+    text.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use std::collections::HashMap;
+
+    fn artifact(id: &str, words: usize) -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            vec!["word"; words].join(" "),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        artifact.id = id.to_string();
+        artifact
+    }
+
+    #[test]
+    fn keeps_artifacts_that_fit_and_cuts_the_rest() {
+        // 30 words is 40 tokens each
+        let mut artifacts = vec![artifact("a", 30), artifact("b", 30), artifact("c", 30)];
+        let truncated = fit(&mut artifacts, 60);
+
+        assert_eq!(truncated, vec!["b", "c"]);
+        assert_eq!(artifacts[0].content.split_whitespace().count(), 30);
+        assert_eq!(artifacts[1].content.split_whitespace().count(), 15);
+        assert!(artifacts[2].content.is_empty());
+        assert!(
+            artifacts
+                .iter()
+                .map(|a| estimate_tokens(&a.content))
+                .sum::<usize>()
+                <= 60
+        );
+    }
+
+    #[test]
+    fn drops_context_of_cut_artifacts() {
+        let mut with_context = artifact("a", 3);
+        with_context.context = Some(vec!["why"; 30].join(" "));
+        let truncated = fit(std::iter::once(&mut with_context), 10);
+
+        assert_eq!(truncated, vec!["a"]);
+        assert_eq!(with_context.context, None);
+        assert_eq!(with_context.content, "word word word");
+    }
+
+    #[test]
+    fn truncates_at_word_boundaries() {
+        assert_eq!(
+            truncate_to_tokens("one  two\nthree four", 4),
+            "one  two\nthree"
+        );
+        assert_eq!(truncate_to_tokens("one two", 1), "");
+    }
+}
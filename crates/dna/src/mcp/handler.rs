@@ -1,12 +1,21 @@
+use super::budget;
+use super::seen::Seen;
+use super::session::{self, SessionLog};
 use crate::db::Database;
 use crate::embedding::EmbeddingProvider;
-use crate::services::{ArtifactService, ContentFormat, SearchFilters, SearchService};
+use crate::services::signing::VERIFIED_CANDIDATES;
+use crate::services::{
+    normalize_handle, parse_date, validate_boosts, validate_labels, Artifact, ArtifactService,
+    AskService, ChangeReport, Completion, ContentFormat, Deprecation, FederatedSearch,
+    KindDefinition, LabelDefinition, LabelValueType, McpConfig, PackService, SearchFilters,
+    SearchResult, SearchService, SearchWeights, ServiceError, Variables, Verifier, LANGUAGE_LABEL,
+};
 use chrono::{DateTime, Utc};
-use rmcp::model::{CallToolResult, Content, PaginatedRequestParams};
+use rmcp::model::{CallToolResult, Content, ErrorCode, PaginatedRequestParams};
 use rmcp::service::RequestContext;
 use rmcp::{ErrorData, RoleServer, ServerHandler};
 use schemars::JsonSchema;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -24,17 +33,99 @@ use std::sync::Arc;
 /// - `dna_my_custom_kind_list`
 ///
 /// This naming convention follows MCP tool naming requirements (no hyphens).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct RegisteredKind {
     pub slug: String,
     pub description: String,
+    /// Format used when an add call doesn't name one
+    pub format: Option<ContentFormat>,
+    /// Content scaffold shown in the add tool description
+    pub template: Option<String>,
+    /// Guidance for agents, added to the kind's tool descriptions
+    pub instructions: Option<String>,
 }
 
-/// A registered label key for validation during artifact creation.
-#[derive(Debug, Clone)]
-pub struct RegisteredLabel {
-    pub key: String,
-    pub description: String,
+impl From<&KindDefinition> for RegisteredKind {
+    fn from(definition: &KindDefinition) -> Self {
+        Self {
+            slug: definition.slug.clone(),
+            description: definition.description.clone(),
+            format: definition.format,
+            template: definition.template.clone(),
+            instructions: definition.instructions.clone(),
+        }
+    }
+}
+
+/// A registered label for validation during artifact writes.
+///
+/// Carries the full definition (value type, allowed values, kind scope) so
+/// tool descriptions can tell clients which values are accepted.
+pub type RegisteredLabel = LabelDefinition;
+
+impl RegisteredKind {
+    /// Names of the search, add and list tools generated for this kind
+    pub fn tool_names(&self) -> [String; 3] {
+        let prefix = self.slug.replace('-', "_");
+        [
+            format!("dna_{}_search", prefix),
+            format!("dna_{}_add", prefix),
+            format!("dna_{}_list", prefix),
+        ]
+    }
+}
+
+/// Tools served whatever kinds are registered, in listing order
+pub const BASE_TOOLS: &[&str] = &[
+    "dna_search",
+    "dna_ask",
+    "dna_pack",
+    "dna_get",
+    "dna_get_many",
+    "dna_list",
+    "dna_changes",
+    "dna_add",
+    "dna_update",
+    "dna_remove",
+    "dna_context",
+];
+
+/// Tools exempt from include/exclude filtering (always available)
+const UNFILTERED_TOOLS: &[&str] = &["dna_context"];
+
+/// Whether include/exclude filters let a tool through.
+///
+/// Filters match on substrings of the tool name, and `include` wins when
+/// both are given.
+pub fn tool_allowed(
+    tool_name: &str,
+    include: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> bool {
+    if UNFILTERED_TOOLS.contains(&tool_name) {
+        return true;
+    }
+    if let Some(include) = include {
+        include.iter().any(|t| tool_name.contains(t))
+    } else if let Some(exclude) = exclude {
+        !exclude.iter().any(|t| tool_name.contains(t))
+    } else {
+        true
+    }
+}
+
+/// Names of the tools served with `kinds` registered, after filtering
+pub fn tool_names(
+    kinds: &[RegisteredKind],
+    include: Option<&[String]>,
+    exclude: Option<&[String]>,
+) -> Vec<String> {
+    BASE_TOOLS
+        .iter()
+        .map(|name| name.to_string())
+        .chain(kinds.iter().flat_map(RegisteredKind::tool_names))
+        .filter(|name| tool_allowed(name, include, exclude))
+        .collect()
 }
 
 /// DNA MCP tool handler using rmcp SDK
@@ -45,6 +136,24 @@ pub struct DnaToolHandler {
     exclude_tools: Option<Vec<String>>,
     registered_kinds: Vec<RegisteredKind>,
     registered_labels: Vec<RegisteredLabel>,
+    /// Namespace tools read from and add to unless a call names another
+    namespace: Option<String>,
+    /// Stores `dna_search` fans out to along with this one
+    federation: Option<Arc<FederatedSearch>>,
+    /// Model `dna_ask` answers with; without one it returns the context
+    completion: Option<Arc<dyn Completion>>,
+    /// Trusted signers for `dna_search` with `verified`
+    verifier: Arc<Verifier>,
+    /// Where tool calls are recorded, for `dna mcp --log-session`
+    session_log: Option<Arc<SessionLog>>,
+    /// Guidance sent to clients on initialize, from `[mcp] instructions`
+    instructions: Option<String>,
+    /// Descriptions replacing the built-in ones, by tool name
+    tool_descriptions: HashMap<String, String>,
+    /// Values filled into placeholders in `dna_pack` content
+    variables: Option<Variables>,
+    /// Artifacts already returned in this session
+    seen: Arc<Seen>,
 }
 
 impl Clone for DnaToolHandler {
@@ -56,6 +165,15 @@ impl Clone for DnaToolHandler {
             exclude_tools: self.exclude_tools.clone(),
             registered_kinds: self.registered_kinds.clone(),
             registered_labels: self.registered_labels.clone(),
+            namespace: self.namespace.clone(),
+            federation: self.federation.clone(),
+            completion: self.completion.clone(),
+            verifier: Arc::clone(&self.verifier),
+            session_log: self.session_log.clone(),
+            instructions: self.instructions.clone(),
+            tool_descriptions: self.tool_descriptions.clone(),
+            variables: self.variables.clone(),
+            seen: Arc::clone(&self.seen),
         }
     }
 }
@@ -78,6 +196,15 @@ impl DnaToolHandler {
             exclude_tools,
             registered_kinds: Vec::new(),
             registered_labels: Vec::new(),
+            namespace: None,
+            federation: None,
+            completion: None,
+            verifier: Arc::new(Verifier::default()),
+            session_log: None,
+            instructions: None,
+            tool_descriptions: HashMap::new(),
+            variables: None,
+            seen: Arc::new(Seen::default()),
         }
     }
 
@@ -99,6 +226,15 @@ impl DnaToolHandler {
             exclude_tools,
             registered_kinds: kinds,
             registered_labels: Vec::new(),
+            namespace: None,
+            federation: None,
+            completion: None,
+            verifier: Arc::new(Verifier::default()),
+            session_log: None,
+            instructions: None,
+            tool_descriptions: HashMap::new(),
+            variables: None,
+            seen: Arc::new(Seen::default()),
         }
     }
 
@@ -121,43 +257,425 @@ impl DnaToolHandler {
             exclude_tools,
             registered_kinds: kinds,
             registered_labels: labels,
+            namespace: None,
+            federation: None,
+            completion: None,
+            verifier: Arc::new(Verifier::default()),
+            session_log: None,
+            instructions: None,
+            tool_descriptions: HashMap::new(),
+            variables: None,
+            seen: Arc::new(Seen::default()),
         }
     }
 
-    /// Tools exempt from include/exclude filtering (always available)
-    const UNFILTERED_TOOLS: &[&str] = &["dna_context"];
+    /// Use a preconfigured artifact service (e.g. one with a custom ID generator)
+    pub fn with_artifact_service(mut self, artifact_service: Arc<ArtifactService>) -> Self {
+        self.artifact_service = artifact_service;
+        self
+    }
+
+    /// Use a preconfigured search service (e.g. one with default kind boosts)
+    pub fn with_search_service(mut self, search_service: Arc<SearchService>) -> Self {
+        self.search_service = search_service;
+        self
+    }
+
+    /// Search other stores along with this one in `dna_search`, tagging each
+    /// hit with its store
+    pub fn with_federation(mut self, federation: Arc<FederatedSearch>) -> Self {
+        self.federation = Some(federation);
+        self
+    }
+
+    /// Answer `dna_ask` questions with a model instead of returning the
+    /// retrieved context for the client to answer from
+    pub fn with_completion(mut self, completion: Option<Arc<dyn Completion>>) -> Self {
+        self.completion = completion;
+        self
+    }
+
+    /// Check signatures against these signers when `dna_search` asks for
+    /// verified artifacts only
+    pub fn with_verifier(mut self, verifier: Verifier) -> Self {
+        self.verifier = Arc::new(verifier);
+        self
+    }
+
+    /// Bind the tools to a namespace: searches, lists and change reports are
+    /// limited to it and added artifacts go in it, unless a call names
+    /// another namespace
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// Record every tool call, with its result and timing, to `log`
+    pub fn with_session_log(mut self, log: Arc<SessionLog>) -> Self {
+        self.session_log = Some(log);
+        self
+    }
+
+    /// Tell clients how to use the tools, and describe tools the way `[mcp]`
+    /// configures instead of with the built-in descriptions
+    pub fn with_mcp_config(mut self, config: &McpConfig) -> Self {
+        self.instructions = config.instructions.clone();
+        self.tool_descriptions = config.tools.clone();
+        self
+    }
+
+    /// Fill `{{name}}` placeholders in `dna_pack` content from `variables`
+    pub fn with_variables(mut self, variables: Variables) -> Self {
+        self.variables = Some(variables);
+        self
+    }
+
+    /// Run the tool `name` with `arguments`, as a client's `tools/call` would
+    pub async fn call(
+        &self,
+        name: &str,
+        arguments: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let Some(log) = &self.session_log else {
+            return self.dispatch(name, arguments).await;
+        };
+        let (outcome, entry) = session::timed_call(self, name, arguments).await;
+        log.record(&entry);
+        outcome
+    }
+
+    /// Mark the artifacts this session was already shown, fit the rest into
+    /// `max_tokens` and remember what went out in full.
+    ///
+    /// Returns the IDs of repeats and, under a budget, of artifacts cut short.
+    fn shown(
+        &self,
+        mut artifacts: Vec<&mut Artifact>,
+        omit_seen: Option<bool>,
+        max_tokens: Option<usize>,
+    ) -> (Vec<String>, Option<Vec<String>>) {
+        let seen = self.seen.mark(
+            artifacts.iter_mut().map(|a| &mut **a),
+            omit_seen.unwrap_or(false),
+        );
+        let truncated =
+            max_tokens.map(|max| budget::fit(artifacts.iter_mut().map(|a| &mut **a), max));
+        self.seen.record(
+            artifacts.iter().map(|a| &**a),
+            truncated.as_deref().unwrap_or_default(),
+        );
+        (seen, truncated)
+    }
+
+    /// Namespace a call reads from or adds to
+    fn namespace(&self, requested: Option<String>) -> Option<String> {
+        requested.or_else(|| self.namespace.clone())
+    }
+
+    /// Add an artifact in `namespace`, or the service's own when `None`
+    async fn add_artifact(
+        &self,
+        kind: String,
+        content: String,
+        format: ContentFormat,
+        name: Option<String>,
+        metadata: HashMap<String, String>,
+        namespace: Option<String>,
+    ) -> Result<Artifact, ErrorData> {
+        let mut artifact = Artifact::new(kind, content, format, name, metadata, String::new());
+        // An empty ID is generated by the service, as for a plain add
+        artifact.id = String::new();
+        artifact.namespace = namespace;
+        self.artifact_service
+            .import(artifact)
+            .await
+            .map_err(service_error)
+    }
 
     /// Check if a tool should be available based on filters
     fn is_tool_available(&self, tool_name: &str) -> bool {
-        if Self::UNFILTERED_TOOLS.contains(&tool_name) {
-            return true;
+        tool_allowed(
+            tool_name,
+            self.include_tools.as_deref(),
+            self.exclude_tools.as_deref(),
+        )
+    }
+
+    /// Semantic search for truth artifacts
+    async fn dna_search(&self, mut request: SearchRequest) -> Result<CallToolResult, ErrorData> {
+        let weights = request.weights.unwrap_or_default();
+        let boosts = request.boosts.clone().unwrap_or_default();
+        weights
+            .validate()
+            .and_then(|_| validate_boosts(&boosts))
+            .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
+
+        if request.expand.unwrap_or(false) {
+            request.query = self
+                .search_service
+                .expand_query(&request.query)
+                .await
+                .map_err(service_error)?;
+        }
+
+        let verified = request.verified.unwrap_or(false);
+        let filters = SearchFilters {
+            kind: request.kind.clone(),
+            namespace: self.namespace(request.namespace.clone()),
+            limit: request.limit.map(|limit| {
+                if verified {
+                    limit * VERIFIED_CANDIDATES
+                } else {
+                    limit
+                }
+            }),
+            kind_not: request.kind_not.clone().unwrap_or_default(),
+            metadata: request
+                .language
+                .clone()
+                .map(|language| HashMap::from([(LANGUAGE_LABEL.to_string(), language)]))
+                .unwrap_or_default(),
+            metadata_not: request.metadata_not.clone().unwrap_or_default(),
+            exclude_ids: request.exclude_ids.clone().unwrap_or_default(),
+            exclude_deprecated: !request.include_deprecated.unwrap_or(false),
+            ..Default::default()
+        };
+
+        // Signatures and extra models are only kept in this store, so those
+        // searches stay local
+        if let Some(federation) = self
+            .federation
+            .as_ref()
+            .filter(|_| !request.local.unwrap_or(false) && !verified && request.model.is_none())
+        {
+            return self
+                .federated_search(federation, request, filters, weights, &boosts)
+                .await;
+        }
+
+        let search_service = match request.model.as_deref() {
+            Some(model) => Arc::new(
+                self.search_service
+                    .for_model(model)
+                    .map_err(service_error)?,
+            ),
+            None => Arc::clone(&self.search_service),
+        };
+        let explain_filters = request.explain.unwrap_or(false).then(|| filters.clone());
+        let mut results = search_service
+            .search_boosted(&request.query, filters, weights, &boosts)
+            .await
+            .map_err(service_error)?;
+        if verified {
+            self.verifier
+                .retain_verified(&mut results, request.limit.unwrap_or(10));
         }
-        if let Some(ref include) = self.include_tools {
-            include.iter().any(|t| tool_name.contains(t))
-        } else if let Some(ref exclude) = self.exclude_tools {
-            !exclude.iter().any(|t| tool_name.contains(t))
+        let explanations = match explain_filters {
+            Some(filters) => search_service
+                .explain(&request.query, &results, &filters, weights, &boosts)
+                .await
+                .map_err(service_error)?,
+            None => Vec::new(),
+        };
+
+        let content = if request.snippets_only.unwrap_or(false) {
+            let snippets = search_service
+                .snippets(&request.query, &results, SNIPPETS_PER_RESULT)
+                .await
+                .map_err(service_error)?;
+            let hits: Vec<_> = results
+                .iter()
+                .zip(snippets)
+                .enumerate()
+                .map(|(i, (result, snippets))| {
+                    let mut hit = serde_json::json!({
+                        "id": result.artifact.id,
+                        "kind": result.artifact.kind,
+                        "name": result.artifact.name,
+                        "score": result.score,
+                        "snippets": snippets.into_iter().map(|s| s.text).collect::<Vec<_>>(),
+                    });
+                    if let Some(explanation) = explanations.get(i) {
+                        hit["explanation"] = serde_json::json!(explanation);
+                    }
+                    hit
+                })
+                .collect();
+            serde_json::to_string_pretty(&hits)
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?
+        } else {
+            let (seen, truncated) = self.shown(
+                results.iter_mut().map(|r| &mut r.artifact).collect(),
+                request.omit_seen,
+                request.max_tokens,
+            );
+            if explanations.is_empty() {
+                budgeted_json(&results, truncated, seen)?
+            } else {
+                let hits: Vec<_> = results
+                    .iter()
+                    .zip(&explanations)
+                    .map(|(result, explanation)| {
+                        serde_json::json!({
+                            "artifact": result.artifact,
+                            "score": result.score,
+                            "explanation": explanation,
+                        })
+                    })
+                    .collect();
+                budgeted_json(&hits, truncated, seen)?
+            }
+        };
+
+        Ok(CallToolResult {
+            content: vec![Content::text(content)],
+            is_error: Some(false),
+            meta: None,
+            structured_content: None,
+        })
+    }
+
+    /// `dna_search` across the federated stores, tagging each hit with its
+    /// store. Stores that could not be searched are listed after the results.
+    async fn federated_search(
+        &self,
+        federation: &FederatedSearch,
+        request: SearchRequest,
+        filters: SearchFilters,
+        weights: SearchWeights,
+        boosts: &HashMap<String, f32>,
+    ) -> Result<CallToolResult, ErrorData> {
+        if request.explain.unwrap_or(false) {
+            return Err(ErrorData::invalid_params(
+                "explain only works on this store's results; set local to true",
+                None,
+            ));
+        }
+        let mut merged = federation
+            .search(&request.query, filters, weights, boosts)
+            .await
+            .map_err(service_error)?;
+
+        let text = if request.snippets_only.unwrap_or(false) {
+            let results: Vec<SearchResult> = merged
+                .results
+                .iter()
+                .map(|hit| hit.result.clone())
+                .collect();
+            let snippets = self
+                .search_service
+                .snippets(&request.query, &results, SNIPPETS_PER_RESULT)
+                .await
+                .map_err(service_error)?;
+            let hits: Vec<_> = merged
+                .results
+                .iter()
+                .zip(snippets)
+                .map(|(hit, snippets)| {
+                    serde_json::json!({
+                        "id": hit.result.artifact.id,
+                        "kind": hit.result.artifact.kind,
+                        "name": hit.result.artifact.name,
+                        "score": hit.result.score,
+                        "store": hit.store,
+                        "snippets": snippets.into_iter().map(|s| s.text).collect::<Vec<_>>(),
+                    })
+                })
+                .collect();
+            serde_json::to_string_pretty(&hits)
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?
         } else {
-            true
+            let (seen, truncated) = self.shown(
+                merged
+                    .results
+                    .iter_mut()
+                    .map(|hit| &mut hit.result.artifact)
+                    .collect(),
+                request.omit_seen,
+                request.max_tokens,
+            );
+            budgeted_json(&merged.results, truncated, seen)?
+        };
+
+        let mut content = vec![Content::text(text)];
+        if !merged.failures.is_empty() {
+            let failures: Vec<String> = merged
+                .failures
+                .iter()
+                .map(|failure| format!("store '{}': {}", failure.store, failure.error))
+                .collect();
+            content.push(Content::text(format!(
+                "Not searched: {}",
+                failures.join("; ")
+            )));
         }
+        Ok(CallToolResult {
+            content,
+            is_error: Some(false),
+            meta: None,
+            structured_content: None,
+        })
     }
 
-    /// Semantic search for truth artifacts
-    async fn dna_search(&self, request: SearchRequest) -> Result<CallToolResult, ErrorData> {
+    /// Answer a question from the most relevant artifacts
+    async fn dna_ask(&self, request: AskRequest) -> Result<CallToolResult, ErrorData> {
+        let completion = if request.context_only.unwrap_or(false) {
+            None
+        } else {
+            self.completion.clone()
+        };
         let filters = SearchFilters {
             kind: request.kind,
+            namespace: self.namespace(request.namespace),
             limit: request.limit,
+            exclude_deprecated: true,
             ..Default::default()
         };
-
-        let results = self
-            .search_service
-            .search(&request.query, filters)
+        let answer = AskService::new(Arc::clone(&self.search_service))
+            .with_completion(completion)
+            .ask(&request.question, filters)
             .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .map_err(service_error)?;
 
-        let content = serde_json::to_string_pretty(&results)
+        let content = serde_json::to_string_pretty(&answer)
             .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        Ok(CallToolResult {
+            content: vec![Content::text(content)],
+            is_error: Some(false),
+            meta: None,
+            structured_content: None,
+        })
+    }
+
+    /// Pack the most important artifacts into a token budget
+    async fn dna_pack(&self, request: PackRequest) -> Result<CallToolResult, ErrorData> {
+        let filters = SearchFilters {
+            namespace: self.namespace(request.namespace),
+            metadata: request.metadata.unwrap_or_default(),
+            ..Default::default()
+        };
+        let kinds: Vec<String> = request
+            .kinds
+            .unwrap_or_default()
+            .iter()
+            .map(|kind| crate::services::slugify_kind(kind))
+            .collect();
+        let mut packs = PackService::new(Arc::clone(&self.artifact_service))
+            .with_boosts(self.search_service.boosts().clone());
+        if let Some(variables) = &self.variables {
+            packs = packs.with_variables(variables.clone());
+        }
+        let pack = packs
+            .pack(filters, &kinds, request.budget)
+            .await
+            .map_err(service_error)?;
 
+        let content = if request.json.unwrap_or(false) {
+            serde_json::to_string_pretty(&pack)
+                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?
+        } else {
+            pack.to_markdown()
+        };
         Ok(CallToolResult {
             content: vec![Content::text(content)],
             is_error: Some(false),
@@ -172,14 +690,57 @@ impl DnaToolHandler {
             .artifact_service
             .get(&request.id)
             .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .map_err(service_error)?;
 
         let is_error = artifact.is_none();
-        let content = match artifact {
-            Some(a) => serde_json::to_string_pretty(&a)
-                .map_err(|e| ErrorData::internal_error(e.to_string(), None))?,
-            None => format!("Artifact not found: {}", request.id),
-        };
+        self.seen.record(&artifact, &[]);
+        let mut content = Vec::new();
+        match artifact {
+            Some(a) => {
+                content
+                    .push(Content::text(serde_json::to_string_pretty(&a).map_err(
+                        |e| ErrorData::internal_error(e.to_string(), None),
+                    )?));
+                // Point agents at the successor rather than leave it in a label
+                if let Some(deprecation) = Deprecation::of(&a) {
+                    content.push(Content::text(deprecation.describe()));
+                }
+            },
+            None => content.push(Content::text(format!("Artifact not found: {}", request.id))),
+        }
+
+        Ok(CallToolResult {
+            content,
+            is_error: Some(is_error),
+            meta: None,
+            structured_content: None,
+        })
+    }
+
+    /// Get several artifacts by ID in one call
+    async fn dna_get_many(&self, request: GetManyRequest) -> Result<CallToolResult, ErrorData> {
+        let artifacts = self
+            .artifact_service
+            .get_many(&request.ids)
+            .await
+            .map_err(service_error)?;
+
+        let mut found = Vec::with_capacity(artifacts.len());
+        let mut missing = Vec::new();
+        for (id, artifact) in request.ids.iter().zip(artifacts) {
+            match artifact {
+                Some(artifact) => found.push(artifact),
+                None => missing.push(id),
+            }
+        }
+
+        let is_error = found.is_empty() && !missing.is_empty();
+        self.seen.record(&found, &[]);
+        let content = serde_json::to_string_pretty(&serde_json::json!({
+            "artifacts": found,
+            "missing": missing,
+        }))
+        .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
 
         Ok(CallToolResult {
             content: vec![Content::text(content)],
@@ -193,20 +754,29 @@ impl DnaToolHandler {
     async fn dna_list(&self, request: ListRequest) -> Result<CallToolResult, ErrorData> {
         let filters = SearchFilters {
             kind: request.kind,
-            after: request.after,
-            before: request.before,
+            namespace: self.namespace(request.namespace),
+            owner: request.owner.as_deref().map(normalize_handle),
+            after: date_param(request.after.as_deref())?,
+            before: date_param(request.before.as_deref())?,
             limit: request.limit,
+            kind_not: request.kind_not.unwrap_or_default(),
+            metadata_not: request.metadata_not.unwrap_or_default(),
+            exclude_ids: request.exclude_ids.unwrap_or_default(),
             ..Default::default()
         };
 
-        let artifacts = self
+        let mut artifacts = self
             .artifact_service
             .list(filters)
             .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .map_err(service_error)?;
 
-        let content = serde_json::to_string_pretty(&artifacts)
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let (seen, truncated) = self.shown(
+            artifacts.iter_mut().collect(),
+            request.omit_seen,
+            request.max_tokens,
+        );
+        let content = budgeted_json(&artifacts, truncated, seen)?;
 
         Ok(CallToolResult {
             content: vec![Content::text(content)],
@@ -218,19 +788,34 @@ impl DnaToolHandler {
 
     /// Artifacts modified in time range
     async fn dna_changes(&self, request: ChangesRequest) -> Result<CallToolResult, ErrorData> {
+        let after = date_param(request.after.as_deref())?;
+        let before = date_param(request.before.as_deref())?;
         let filters = SearchFilters {
-            after: request.after,
-            before: request.before,
+            namespace: self.namespace(request.namespace),
+            after,
+            before,
             ..Default::default()
         };
 
-        let artifacts = self
-            .artifact_service
-            .list(filters)
-            .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
-
-        let content = serde_json::to_string_pretty(&artifacts)
+        let mut report = if request.history.unwrap_or(false) {
+            let history = self
+                .artifact_service
+                .change_history(filters)
+                .await
+                .map_err(service_error)?;
+            ChangeReport::from_history(history, after, before)
+        } else {
+            let artifacts = self
+                .artifact_service
+                .list(filters)
+                .await
+                .map_err(service_error)?;
+            ChangeReport::new(artifacts, after, before)
+        };
+        if request.summary.unwrap_or(false) {
+            report = report.with_summary();
+        }
+        let content = serde_json::to_string_pretty(&report)
             .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
 
         Ok(CallToolResult {
@@ -241,30 +826,138 @@ impl DnaToolHandler {
         })
     }
 
-    /// Validate metadata keys against registered labels.
+    /// Validate metadata against registered labels for the given kind.
     /// Skips validation if no labels are registered.
     fn validate_metadata_labels(
         &self,
+        kind: &str,
         metadata: &HashMap<String, String>,
     ) -> Result<(), ErrorData> {
-        if self.registered_labels.is_empty() {
+        validate_labels(
+            &self.registered_labels,
+            &crate::services::slugify_kind(kind),
+            metadata,
+        )
+        .map_err(|e| ErrorData::invalid_params(e.to_string(), None))
+    }
+
+    /// The registered kind `kind` names, if any
+    fn registered_kind(&self, kind: &str) -> Option<&RegisteredKind> {
+        let slug = crate::services::slugify_kind(kind);
+        self.registered_kinds.iter().find(|k| k.slug == slug)
+    }
+
+    /// Format for an add call: the one requested, else the kind's default
+    fn add_format(&self, kind: &str, format: Option<ContentFormat>) -> ContentFormat {
+        format
+            .or_else(|| self.registered_kind(kind).and_then(|k| k.format))
+            .unwrap_or_else(default_format)
+    }
+
+    /// Refuse empty content for a kind with a template, handing back the
+    /// scaffold so the client can fill it in and call again
+    fn require_content(&self, kind: &str, content: &str) -> Result<(), ErrorData> {
+        if !content.trim().is_empty() {
             return Ok(());
         }
-        let unregistered: Vec<&str> = metadata
-            .keys()
-            .filter(|k| !self.registered_labels.iter().any(|l| l.key == **k))
-            .map(|k| k.as_str())
-            .collect();
-        if unregistered.is_empty() {
-            Ok(())
-        } else {
-            Err(ErrorData::invalid_params(
+        match self.registered_kind(kind).and_then(|k| k.template.as_deref()) {
+            Some(template) => Err(ErrorData::invalid_params(
                 format!(
-                    "Unregistered label key(s): {}. Register with 'dna label add <key> <description>'.",
-                    unregistered.join(", ")
+                    "Content is empty. Fill in the template for '{}', replacing every {{{{placeholder}}}}, and call again:\n\n{}",
+                    kind, template
                 ),
                 None,
-            ))
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Describe a kind's default format and content template, for its add tool.
+    ///
+    /// Returns an empty string when the kind has neither.
+    fn template_hint(kind: &RegisteredKind) -> String {
+        let mut hint = String::new();
+        if let Some(format) = kind.format {
+            hint.push_str(&format!("\n\nDefault format: {}", format));
+        }
+        if let Some(template) = &kind.template {
+            hint.push_str(&format!(
+                "\n\nContent template (replace every {{{{placeholder}}}}):\n{}",
+                template
+            ));
+        }
+        hint
+    }
+
+    /// A kind's guidance for agents, for its tool descriptions
+    fn instructions_hint(kind: &RegisteredKind) -> String {
+        kind.instructions
+            .as_ref()
+            .map(|instructions| format!("\n\n{}", instructions))
+            .unwrap_or_default()
+    }
+
+    /// `tool` with its description replaced if `[mcp.tools]` configures one
+    fn configured_tool(&self, mut tool: rmcp::model::Tool) -> rmcp::model::Tool {
+        if let Some(description) = self.tool_descriptions.get(tool.name.as_ref()) {
+            tool.description = Some(description.clone().into());
+        }
+        tool
+    }
+
+    /// Instructions for clients: the configured guidance, then each kind's
+    fn server_instructions(&self) -> Option<String> {
+        let mut sections: Vec<String> = self.instructions.iter().cloned().collect();
+        let kinds: Vec<String> = self
+            .registered_kinds
+            .iter()
+            .filter_map(|kind| {
+                let instructions = kind.instructions.as_ref()?;
+                Some(format!("- {}: {}", kind.slug, instructions))
+            })
+            .collect();
+        if !kinds.is_empty() {
+            sections.push(format!("Kinds:\n{}", kinds.join("\n")));
+        }
+        (!sections.is_empty()).then(|| sections.join("\n\n"))
+    }
+
+    /// Describe the labels usable on a kind (or all labels), for tool descriptions.
+    ///
+    /// Returns an empty string when no labels are registered.
+    fn labels_hint(&self, kind: Option<&str>) -> String {
+        let lines: Vec<String> = self
+            .registered_labels
+            .iter()
+            .filter(|l| kind.is_none_or(|k| l.applies_to(k)))
+            .map(|l| {
+                let mut constraints = Vec::new();
+                if l.value_type != LabelValueType::String {
+                    constraints.push(l.value_hint());
+                }
+                if kind.is_none() && !l.kinds.is_empty() {
+                    constraints.push(format!("kinds: {}", l.kinds.join(", ")));
+                }
+                if constraints.is_empty() {
+                    format!("- {}: {}", l.key, l.description)
+                } else {
+                    format!(
+                        "- {} ({}): {}",
+                        l.key,
+                        constraints.join("; "),
+                        l.description
+                    )
+                }
+            })
+            .collect();
+
+        if lines.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "\n\nRegistered labels (metadata keys):\n{}",
+                lines.join("\n")
+            )
         }
     }
 
@@ -274,7 +967,7 @@ impl DnaToolHandler {
             .artifact_service
             .list(SearchFilters::default())
             .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .map_err(service_error)?;
 
         let mut counts: HashMap<String, usize> = HashMap::new();
         for artifact in &artifacts {
@@ -300,6 +993,9 @@ impl DnaToolHandler {
                 serde_json::json!({
                     "key": l.key,
                     "description": l.description,
+                    "type": l.value_type,
+                    "values": l.values,
+                    "kinds": l.kinds,
                 })
             })
             .collect();
@@ -323,20 +1019,21 @@ impl DnaToolHandler {
 
     /// Add new artifact
     async fn dna_add(&self, request: AddRequest) -> Result<CallToolResult, ErrorData> {
-        self.validate_metadata_labels(&request.metadata)?;
+        self.validate_metadata_labels(&request.kind, &request.metadata)?;
+        self.require_content(&request.kind, &request.content)?;
+        let format = self.add_format(&request.kind, request.format);
 
+        let namespace = self.namespace(request.namespace);
         let artifact = self
-            .artifact_service
-            .add(
+            .add_artifact(
                 request.kind,
                 request.content,
-                request.format,
+                format,
                 request.name,
                 request.metadata,
-                None, // TODO: Add context support to MCP AddRequest
+                namespace,
             )
-            .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .await?;
 
         let content = serde_json::to_string_pretty(&artifact)
             .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
@@ -352,13 +1049,24 @@ impl DnaToolHandler {
     /// Modify existing artifact
     async fn dna_update(&self, request: UpdateRequest) -> Result<CallToolResult, ErrorData> {
         if let Some(ref metadata) = request.metadata {
-            // Only validate keys being set, not removed (empty value = removal)
-            let non_empty: HashMap<String, String> = metadata
-                .iter()
-                .filter(|(_, v)| !v.is_empty())
-                .map(|(k, v)| (k.clone(), v.clone()))
-                .collect();
-            self.validate_metadata_labels(&non_empty)?;
+            // Labels are checked against the kind the artifact will have after the update
+            let kind = match request.kind {
+                Some(ref kind) => kind.clone(),
+                None => {
+                    self.artifact_service
+                        .get(&request.id)
+                        .await
+                        .map_err(service_error)?
+                        .ok_or_else(|| {
+                            service_error(ServiceError::NotFound(format!(
+                                "Artifact '{}' not found",
+                                request.id
+                            )))
+                        })?
+                        .kind
+                },
+            };
+            self.validate_metadata_labels(&kind, metadata)?;
         }
 
         let artifact = self
@@ -372,7 +1080,7 @@ impl DnaToolHandler {
                 None, // TODO: Add context support to MCP UpdateRequest
             )
             .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .map_err(service_error)?;
 
         let content = serde_json::to_string_pretty(&artifact)
             .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
@@ -391,20 +1099,31 @@ impl DnaToolHandler {
         kind: &str,
         request: KindSearchRequest,
     ) -> Result<CallToolResult, ErrorData> {
+        let weights = request.weights.unwrap_or_default();
+        weights
+            .validate()
+            .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
+
         let filters = SearchFilters {
             kind: Some(kind.to_string()),
+            namespace: self.namespace.clone(),
             limit: request.limit,
+            exclude_deprecated: true,
             ..Default::default()
         };
 
-        let results = self
+        let mut results = self
             .search_service
-            .search(&request.query, filters)
+            .search_weighted(&request.query, filters, weights)
             .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .map_err(service_error)?;
 
-        let content = serde_json::to_string_pretty(&results)
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let (seen, truncated) = self.shown(
+            results.iter_mut().map(|r| &mut r.artifact).collect(),
+            request.omit_seen,
+            request.max_tokens,
+        );
+        let content = budgeted_json(&results, truncated, seen)?;
 
         Ok(CallToolResult {
             content: vec![Content::text(content)],
@@ -420,20 +1139,20 @@ impl DnaToolHandler {
         kind: &str,
         request: KindAddRequest,
     ) -> Result<CallToolResult, ErrorData> {
-        self.validate_metadata_labels(&request.metadata)?;
+        self.validate_metadata_labels(kind, &request.metadata)?;
+        self.require_content(kind, &request.content)?;
+        let format = self.add_format(kind, request.format);
 
         let artifact = self
-            .artifact_service
-            .add(
+            .add_artifact(
                 kind.to_string(),
                 request.content,
-                request.format,
+                format,
                 request.name,
                 request.metadata,
-                None, // TODO: Add context support to MCP KindAddRequest
+                self.namespace.clone(),
             )
-            .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .await?;
 
         let content = serde_json::to_string_pretty(&artifact)
             .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
@@ -454,18 +1173,23 @@ impl DnaToolHandler {
     ) -> Result<CallToolResult, ErrorData> {
         let filters = SearchFilters {
             kind: Some(kind.to_string()),
+            namespace: self.namespace.clone(),
             limit: request.limit,
             ..Default::default()
         };
 
-        let artifacts = self
+        let mut artifacts = self
             .artifact_service
             .list(filters)
             .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .map_err(service_error)?;
 
-        let content = serde_json::to_string_pretty(&artifacts)
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+        let (seen, truncated) = self.shown(
+            artifacts.iter_mut().collect(),
+            request.omit_seen,
+            request.max_tokens,
+        );
+        let content = budgeted_json(&artifacts, truncated, seen)?;
 
         Ok(CallToolResult {
             content: vec![Content::text(content)],
@@ -481,7 +1205,7 @@ impl DnaToolHandler {
             .artifact_service
             .remove(&request.id)
             .await
-            .map_err(|e| ErrorData::internal_error(e.to_string(), None))?;
+            .map_err(service_error)?;
 
         let result = serde_json::json!({ "removed": removed });
         let content = serde_json::to_string_pretty(&result)
@@ -514,7 +1238,7 @@ impl ServerHandler for DnaToolHandler {
                 title: None,
                 website_url: None,
             },
-            instructions: None,
+            instructions: self.server_instructions(),
         }
     }
 
@@ -552,9 +1276,37 @@ impl ServerHandler for DnaToolHandler {
                 icons: None,
                 meta: None,
             },
+            Tool {
+                name: "dna_ask".into(),
+                description: Some(
+                    "Answer a question from the most relevant artifacts, citing their IDs as [id]; returns the context block to answer from yourself when no model is configured or context_only is set"
+                        .into(),
+                ),
+                input_schema: schema_to_json!(AskRequest),
+                title: None,
+                output_schema: None,
+                annotations: None,
+                execution: None,
+                icons: None,
+                meta: None,
+            },
+            Tool {
+                name: "dna_pack".into(),
+                description: Some(
+                    "Build a Markdown context pack of the most important artifacts (by kind boost, recency and status label) that fits a token budget, e.g. to bootstrap a system prompt"
+                        .into(),
+                ),
+                input_schema: schema_to_json!(PackRequest),
+                title: None,
+                output_schema: None,
+                annotations: None,
+                execution: None,
+                icons: None,
+                meta: None,
+            },
             Tool {
                 name: "dna_get".into(),
-                description: Some("Get artifact by ID".into()),
+                description: Some("Get artifact by ID (former IDs of renamed or merged artifacts return the artifact with redirected_from set)".into()),
                 input_schema: schema_to_json!(GetRequest),
                 title: None,
                 output_schema: None,
@@ -563,6 +1315,17 @@ impl ServerHandler for DnaToolHandler {
                 icons: None,
                 meta: None,
             },
+            Tool {
+                name: "dna_get_many".into(),
+                description: Some("Get several artifacts by ID in one call; IDs that match nothing are listed under missing".into()),
+                input_schema: schema_to_json!(GetManyRequest),
+                title: None,
+                output_schema: None,
+                annotations: None,
+                execution: None,
+                icons: None,
+                meta: None,
+            },
             Tool {
                 name: "dna_list".into(),
                 description: Some("List artifacts by kind/metadata".into()),
@@ -576,7 +1339,10 @@ impl ServerHandler for DnaToolHandler {
             },
             Tool {
                 name: "dna_changes".into(),
-                description: Some("Artifacts modified in time range".into()),
+                description: Some(
+                    "Artifacts modified in time range, counted by kind and marked created or updated (or deleted, with history)"
+                        .into(),
+                ),
                 input_schema: schema_to_json!(ChangesRequest),
                 title: None,
                 output_schema: None,
@@ -587,7 +1353,7 @@ impl ServerHandler for DnaToolHandler {
             },
             Tool {
                 name: "dna_add".into(),
-                description: Some("Add new artifact".into()),
+                description: Some(format!("Add new artifact{}", self.labels_hint(None)).into()),
                 input_schema: schema_to_json!(AddRequest),
                 title: None,
                 output_schema: None,
@@ -598,7 +1364,9 @@ impl ServerHandler for DnaToolHandler {
             },
             Tool {
                 name: "dna_update".into(),
-                description: Some("Modify existing artifact".into()),
+                description: Some(
+                    format!("Modify existing artifact{}", self.labels_hint(None)).into(),
+                ),
                 input_schema: schema_to_json!(UpdateRequest),
                 title: None,
                 output_schema: None,
@@ -636,12 +1404,18 @@ impl ServerHandler for DnaToolHandler {
 
         // Add kind-specific tools for each registered kind
         for kind in &self.registered_kinds {
-            let prefix = kind.slug.replace('-', "_");
+            let [search, add, list] = kind.tool_names();
 
             all_tools.push(Tool {
-                name: format!("dna_{}_search", prefix).into(),
+                name: search.into(),
                 description: Some(
-                    format!("Search {} artifacts: {}", kind.slug, kind.description).into(),
+                    format!(
+                        "Search {} artifacts: {}{}",
+                        kind.slug,
+                        kind.description,
+                        Self::instructions_hint(kind)
+                    )
+                    .into(),
                 ),
                 input_schema: schema_to_json!(KindSearchRequest),
                 title: None,
@@ -653,9 +1427,17 @@ impl ServerHandler for DnaToolHandler {
             });
 
             all_tools.push(Tool {
-                name: format!("dna_{}_add", prefix).into(),
+                name: add.into(),
                 description: Some(
-                    format!("Add a new {} artifact: {}", kind.slug, kind.description).into(),
+                    format!(
+                        "Add a new {} artifact: {}{}{}{}",
+                        kind.slug,
+                        kind.description,
+                        Self::instructions_hint(kind),
+                        Self::template_hint(kind),
+                        self.labels_hint(Some(&kind.slug))
+                    )
+                    .into(),
                 ),
                 input_schema: schema_to_json!(KindAddRequest),
                 title: None,
@@ -667,9 +1449,15 @@ impl ServerHandler for DnaToolHandler {
             });
 
             all_tools.push(Tool {
-                name: format!("dna_{}_list", prefix).into(),
+                name: list.into(),
                 description: Some(
-                    format!("List {} artifacts: {}", kind.slug, kind.description).into(),
+                    format!(
+                        "List {} artifacts: {}{}",
+                        kind.slug,
+                        kind.description,
+                        Self::instructions_hint(kind)
+                    )
+                    .into(),
                 ),
                 input_schema: schema_to_json!(KindListRequest),
                 title: None,
@@ -681,9 +1469,10 @@ impl ServerHandler for DnaToolHandler {
             });
         }
 
-        // Apply filters
+        // Apply configured descriptions, then filters
         let tools: Vec<Tool> = all_tools
             .into_iter()
+            .map(|tool| self.configured_tool(tool))
             .filter(|tool| self.is_tool_available(&tool.name))
             .collect();
 
@@ -699,8 +1488,19 @@ impl ServerHandler for DnaToolHandler {
         params: rmcp::model::CallToolRequestParams,
         _context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, ErrorData> {
-        let name = &params.name;
-        let arguments = serde_json::Value::Object(params.arguments.unwrap_or_default());
+        self.call(&params.name, params.arguments.unwrap_or_default())
+            .await
+    }
+}
+
+impl DnaToolHandler {
+    /// Run a tool call without recording it
+    pub(crate) async fn dispatch(
+        &self,
+        name: &str,
+        arguments: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<CallToolResult, ErrorData> {
+        let arguments = serde_json::Value::Object(arguments);
 
         if !self.is_tool_available(name) {
             return Err(ErrorData::new(
@@ -710,17 +1510,32 @@ impl ServerHandler for DnaToolHandler {
             ));
         }
 
-        match name.as_ref() {
+        match name {
             "dna_search" => {
                 let request: SearchRequest = serde_json::from_value(arguments)
                     .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
                 self.dna_search(request).await
             },
+            "dna_ask" => {
+                let request: AskRequest = serde_json::from_value(arguments)
+                    .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
+                self.dna_ask(request).await
+            },
+            "dna_pack" => {
+                let request: PackRequest = serde_json::from_value(arguments)
+                    .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
+                self.dna_pack(request).await
+            },
             "dna_get" => {
                 let request: GetRequest = serde_json::from_value(arguments)
                     .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
                 self.dna_get(request).await
             },
+            "dna_get_many" => {
+                let request: GetManyRequest = serde_json::from_value(arguments)
+                    .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
+                self.dna_get_many(request).await
+            },
             "dna_list" => {
                 let request: ListRequest = serde_json::from_value(arguments)
                     .map_err(|e| ErrorData::invalid_params(e.to_string(), None))?;
@@ -749,8 +1564,7 @@ impl ServerHandler for DnaToolHandler {
             "dna_context" => self.dna_context().await,
             _ => {
                 // Check for kind-specific tools: dna_{kind_prefix}_{action}
-                let name_str: &str = name.as_ref();
-                if let Some(rest) = name_str.strip_prefix("dna_") {
+                if let Some(rest) = name.strip_prefix("dna_") {
                     for kind in &self.registered_kinds {
                         let prefix = kind.slug.replace('-', "_");
                         if let Some(action) =
@@ -803,8 +1617,94 @@ impl ServerHandler for DnaToolHandler {
 struct SearchRequest {
     query: String,
     kind: Option<String>,
+    /// Only artifacts in this namespace (default: the namespace the server is bound to)
+    #[serde(default)]
+    namespace: Option<String>,
     #[serde(default = "default_limit")]
     limit: Option<usize>,
+    /// Relative weights of content and context similarity (default: content only)
+    #[serde(default)]
+    weights: Option<SearchWeights>,
+    /// Score multipliers by kind, e.g. {"invariant": 2.0}; override configured boosts
+    #[serde(default)]
+    boosts: Option<HashMap<String, f32>>,
+    /// Return only the sentences that best match the query instead of full artifacts
+    #[serde(default)]
+    snippets_only: Option<bool>,
+    /// Approximate token budget for returned content; artifacts past it are cut short
+    #[serde(default)]
+    max_tokens: Option<usize>,
+    /// Leave out the content and context of artifacts already returned, unchanged, earlier in this session
+    #[serde(default)]
+    omit_seen: Option<bool>,
+    /// Leave out artifacts of these kinds
+    #[serde(default)]
+    kind_not: Option<Vec<String>>,
+    /// Leave out artifacts with any of these labels, e.g. {"status": "deprecated"}
+    #[serde(default)]
+    metadata_not: Option<HashMap<String, String>>,
+    /// Leave out these artifact IDs, e.g. results already seen
+    #[serde(default)]
+    exclude_ids: Option<Vec<String>>,
+    /// Also return deprecated artifacts, which are left out by default
+    #[serde(default)]
+    include_deprecated: Option<bool>,
+    /// Only artifacts in this language, as an ISO 639-3 code such as "deu";
+    /// searched with the model configured for it, if any
+    #[serde(default)]
+    language: Option<String>,
+    /// Add the glossary's synonyms for terms in the query, e.g. "ICP" also
+    /// searches for "Ideal Customer Profile"
+    #[serde(default)]
+    expand: Option<bool>,
+    /// Explain each hit's score: vector distances, boost and matched filters
+    #[serde(default)]
+    explain: Option<bool>,
+    /// Search only this store, not the stores federated with it
+    #[serde(default)]
+    local: Option<bool>,
+    /// Only artifacts signed by a trusted signer and unchanged since; searches this store only
+    #[serde(default)]
+    verified: Option<bool>,
+    /// Search the embeddings of this model from [[extra_models]] instead of
+    /// the configured one; content only, and this store only
+    #[serde(default)]
+    model: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct AskRequest {
+    question: String,
+    /// Only answer from artifacts of this kind
+    kind: Option<String>,
+    /// Only answer from artifacts in this namespace (default: the namespace the server is bound to)
+    #[serde(default)]
+    namespace: Option<String>,
+    /// Number of artifacts to answer from
+    #[serde(default = "default_ask_limit")]
+    limit: Option<usize>,
+    /// Return the context block without asking the configured model
+    #[serde(default)]
+    context_only: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+struct PackRequest {
+    /// Approximate number of tokens the pack may take
+    #[serde(default = "default_pack_budget")]
+    budget: usize,
+    /// Only pack artifacts of these kinds, in this order
+    #[serde(default)]
+    kinds: Option<Vec<String>>,
+    /// Only pack artifacts with all of these labels, e.g. {"domain": "auth"}
+    #[serde(default)]
+    metadata: Option<HashMap<String, String>>,
+    /// Only pack artifacts in this namespace (default: the namespace the server is bound to)
+    #[serde(default)]
+    namespace: Option<String>,
+    /// Return the pack as JSON with scores and omitted IDs instead of Markdown
+    #[serde(default)]
+    json: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -812,31 +1712,76 @@ struct GetRequest {
     id: String,
 }
 
+#[derive(Debug, Deserialize, JsonSchema)]
+struct GetManyRequest {
+    /// IDs of the artifacts to fetch, e.g. those named in another artifact's relations
+    ids: Vec<String>,
+}
+
 #[derive(Debug, Deserialize, JsonSchema)]
 struct ListRequest {
     kind: Option<String>,
+    /// Only artifacts in this namespace (default: the namespace the server is bound to)
+    #[serde(default)]
+    namespace: Option<String>,
+    /// Only artifacts owned by this person or team, e.g. "@payments"
     #[serde(default)]
-    after: Option<DateTime<Utc>>,
+    owner: Option<String>,
+    /// Only artifacts updated at or after this date: YYYY-MM-DD, RFC3339, or relative such as 7d or "last monday"
     #[serde(default)]
-    before: Option<DateTime<Utc>>,
+    after: Option<String>,
+    /// Only artifacts updated before this date, in the same forms as `after`
+    #[serde(default)]
+    before: Option<String>,
     limit: Option<usize>,
+    /// Leave out artifacts of these kinds
+    #[serde(default)]
+    kind_not: Option<Vec<String>>,
+    /// Leave out artifacts with any of these labels, e.g. {"status": "deprecated"}
+    #[serde(default)]
+    metadata_not: Option<HashMap<String, String>>,
+    /// Leave out these artifact IDs, e.g. results already seen
+    #[serde(default)]
+    exclude_ids: Option<Vec<String>>,
+    /// Approximate token budget for returned content; artifacts past it are cut short
+    #[serde(default)]
+    max_tokens: Option<usize>,
+    /// Leave out the content and context of artifacts already returned, unchanged, earlier in this session
+    #[serde(default)]
+    omit_seen: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 struct ChangesRequest {
-    after: Option<DateTime<Utc>>,
-    before: Option<DateTime<Utc>>,
+    /// Only artifacts updated at or after this date: YYYY-MM-DD, RFC3339, or relative such as 7d or "last monday"
+    after: Option<String>,
+    /// Only artifacts updated before this date, in the same forms as `after`
+    before: Option<String>,
+    /// Open the report with a plain-text summary of the counts
+    #[serde(default)]
+    summary: Option<bool>,
+    /// Compare store versions instead of timestamps, so deletions are reported
+    /// with the version that made each change
+    #[serde(default)]
+    history: Option<bool>,
+    /// Only artifacts in this namespace (default: the namespace the server is bound to)
+    #[serde(default)]
+    namespace: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 struct AddRequest {
     kind: String,
     content: String,
-    #[serde(default = "default_format")]
-    format: ContentFormat,
+    /// Content format (default: the kind's format, else markdown)
+    #[serde(default)]
+    format: Option<ContentFormat>,
     name: Option<String>,
     #[serde(default)]
     metadata: HashMap<String, String>,
+    /// Namespace to add the artifact to (default: the namespace the server is bound to)
+    #[serde(default)]
+    namespace: Option<String>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -863,13 +1808,23 @@ struct KindSearchRequest {
     query: String,
     #[serde(default = "default_limit")]
     limit: Option<usize>,
+    /// Relative weights of content and context similarity (default: content only)
+    #[serde(default)]
+    weights: Option<SearchWeights>,
+    /// Approximate token budget for returned content; artifacts past it are cut short
+    #[serde(default)]
+    max_tokens: Option<usize>,
+    /// Leave out the content and context of artifacts already returned, unchanged, earlier in this session
+    #[serde(default)]
+    omit_seen: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 struct KindAddRequest {
     content: String,
-    #[serde(default = "default_format")]
-    format: ContentFormat,
+    /// Content format (default: the kind's format, else markdown)
+    #[serde(default)]
+    format: Option<ContentFormat>,
     name: Option<String>,
     #[serde(default)]
     metadata: HashMap<String, String>,
@@ -878,12 +1833,79 @@ struct KindAddRequest {
 #[derive(Debug, Deserialize, JsonSchema)]
 struct KindListRequest {
     limit: Option<usize>,
+    /// Approximate token budget for returned content; artifacts past it are cut short
+    #[serde(default)]
+    max_tokens: Option<usize>,
+    /// Leave out the content and context of artifacts already returned, unchanged, earlier in this session
+    #[serde(default)]
+    omit_seen: Option<bool>,
+}
+
+/// Serialize tool output. Under a token budget, or when some artifacts were
+/// already returned earlier in the session, the output becomes
+/// `{"results": ..., "truncated": [ids], "seen": [ids]}` so agents know which
+/// artifacts to fetch in full and which they already have.
+fn budgeted_json<T: Serialize>(
+    results: &T,
+    truncated: Option<Vec<String>>,
+    seen: Vec<String>,
+) -> Result<String, ErrorData> {
+    let json = if truncated.is_none() && seen.is_empty() {
+        serde_json::to_string_pretty(results)
+    } else {
+        let mut output = serde_json::json!({ "results": results });
+        if let Some(truncated) = truncated {
+            output["truncated"] = serde_json::json!(truncated);
+        }
+        if !seen.is_empty() {
+            output["seen"] = serde_json::json!(seen);
+        }
+        serde_json::to_string_pretty(&output)
+    };
+    json.map_err(|e| ErrorData::internal_error(e.to_string(), None))
+}
+
+/// Parse an optional date filter, rejecting bad input as invalid params
+fn date_param(value: Option<&str>) -> Result<Option<DateTime<Utc>>, ErrorData> {
+    value
+        .map(parse_date)
+        .transpose()
+        .map_err(|e| ErrorData::invalid_params(e.to_string(), None))
+}
+
+/// Sentences returned per hit when `snippets_only` is set
+const SNIPPETS_PER_RESULT: usize = 2;
+
+/// Map a service failure to a JSON-RPC error, naming the failure in `data.error`
+fn service_error(err: impl Into<ServiceError>) -> ErrorData {
+    let err = err.into();
+    let data = Some(serde_json::json!({ "error": err.code() }));
+    let code = ErrorCode(err.json_rpc_code());
+    let message = match err {
+        ServiceError::NotFound(msg)
+        | ServiceError::Validation(msg)
+        | ServiceError::Conflict(msg)
+        | ServiceError::RateLimited(msg)
+        | ServiceError::ProviderUnavailable(msg)
+        | ServiceError::StorageCorruption(msg)
+        | ServiceError::Forbidden(msg) => msg,
+        ServiceError::Internal(e) => e.to_string(),
+    };
+    ErrorData::new(code, message, data)
 }
 
 fn default_limit() -> Option<usize> {
     Some(10)
 }
 
+fn default_ask_limit() -> Option<usize> {
+    Some(5)
+}
+
+fn default_pack_budget() -> usize {
+    4000
+}
+
 fn default_format() -> ContentFormat {
     ContentFormat::Markdown
 }
@@ -899,6 +1921,22 @@ mod tests {
         DnaToolHandler::new(db, embedding, None, None)
     }
 
+    #[test]
+    fn service_errors_map_to_json_rpc_codes() {
+        let not_found = service_error(ServiceError::NotFound("gone".to_string()));
+        assert_eq!(not_found.code, ErrorCode::RESOURCE_NOT_FOUND);
+        assert_eq!(not_found.message, "gone");
+
+        let limited = service_error(anyhow::Error::from(
+            crate::embedding::ProviderError::RateLimited("slow down".to_string()),
+        ));
+        assert_eq!(limited.code, ErrorCode(-32011));
+        assert_eq!(
+            limited.data,
+            Some(serde_json::json!({ "error": "rate_limited" }))
+        );
+    }
+
     #[test]
     fn is_tool_available_no_filters() {
         let handler = test_handler();
@@ -924,15 +1962,35 @@ mod tests {
         assert!(!handler.is_tool_available("dna_remove"));
     }
 
+    #[test]
+    fn tool_names_cover_kinds_and_honor_filters() {
+        let kinds = vec![RegisteredKind {
+            slug: "my-kind".to_string(),
+            description: "Custom".to_string(),
+            ..Default::default()
+        }];
+
+        let all = tool_names(&kinds, None, None);
+        assert_eq!(all.len(), BASE_TOOLS.len() + 3);
+        assert!(all.contains(&"dna_my_kind_add".to_string()));
+
+        let include = vec!["search".to_string()];
+        assert_eq!(
+            tool_names(&kinds, Some(&include), None),
+            vec!["dna_search", "dna_context", "dna_my_kind_search"]
+        );
+    }
+
     #[tokio::test]
     async fn dna_add_creates_artifact() {
         let handler = test_handler();
         let request = AddRequest {
             kind: "intent".to_string(),
             content: "test content".to_string(),
-            format: ContentFormat::Markdown,
+            format: Some(ContentFormat::Markdown),
             name: Some("test".to_string()),
             metadata: HashMap::new(),
+            namespace: None,
         };
 
         let result = handler.dna_add(request).await.unwrap();
@@ -959,9 +2017,10 @@ mod tests {
         let add_request = AddRequest {
             kind: "intent".to_string(),
             content: "get me".to_string(),
-            format: ContentFormat::Markdown,
+            format: Some(ContentFormat::Markdown),
             name: None,
             metadata: HashMap::new(),
+            namespace: None,
         };
         let add_result = handler.dna_add(add_request).await.unwrap();
         let added: serde_json::Value =
@@ -974,6 +2033,41 @@ mod tests {
         assert_eq!(result.is_error, Some(false));
     }
 
+    #[tokio::test]
+    async fn dna_get_many_lists_missing_ids() {
+        let handler = test_handler();
+        let added = handler
+            .dna_add(AddRequest {
+                kind: "intent".to_string(),
+                content: "get me too".to_string(),
+                format: Some(ContentFormat::Markdown),
+                name: None,
+                metadata: HashMap::new(),
+                namespace: None,
+            })
+            .await
+            .unwrap();
+        let added: serde_json::Value =
+            serde_json::from_str(&added.content[0].as_text().unwrap().text).unwrap();
+        let id = added["id"].as_str().unwrap().to_string();
+
+        let request = GetManyRequest {
+            ids: vec![id.clone(), "nonexistent".to_string()],
+        };
+        let result = handler.dna_get_many(request).await.unwrap();
+        assert_eq!(result.is_error, Some(false));
+        let body: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(body["artifacts"][0]["id"], id.as_str());
+        assert_eq!(body["missing"], serde_json::json!(["nonexistent"]));
+
+        let request = GetManyRequest {
+            ids: vec!["nonexistent".to_string()],
+        };
+        let result = handler.dna_get_many(request).await.unwrap();
+        assert_eq!(result.is_error, Some(true));
+    }
+
     #[tokio::test]
     async fn dna_list_returns_artifacts() {
         let handler = test_handler();
@@ -982,19 +2076,27 @@ mod tests {
             .dna_add(AddRequest {
                 kind: "intent".to_string(),
                 content: "one".to_string(),
-                format: ContentFormat::Markdown,
+                format: Some(ContentFormat::Markdown),
                 name: None,
                 metadata: HashMap::new(),
+                namespace: None,
             })
             .await
             .unwrap();
 
         let result = handler
             .dna_list(ListRequest {
+                owner: None,
                 kind: None,
                 after: None,
                 before: None,
                 limit: None,
+                max_tokens: None,
+                omit_seen: None,
+                kind_not: None,
+                metadata_not: None,
+                exclude_ids: None,
+                namespace: None,
             })
             .await
             .unwrap();
@@ -1005,6 +2107,141 @@ mod tests {
         assert_eq!(parsed.len(), 1);
     }
 
+    #[tokio::test]
+    async fn repeated_results_are_marked_seen() {
+        let handler = test_handler();
+        handler
+            .dna_add(AddRequest {
+                kind: "intent".to_string(),
+                content: "seen once".to_string(),
+                format: Some(ContentFormat::Markdown),
+                name: None,
+                metadata: HashMap::new(),
+                namespace: None,
+            })
+            .await
+            .unwrap();
+
+        let list = |omit_seen: Option<bool>| ListRequest {
+            owner: None,
+            kind: None,
+            after: None,
+            before: None,
+            limit: None,
+            max_tokens: None,
+            omit_seen,
+            kind_not: None,
+            metadata_not: None,
+            exclude_ids: None,
+            namespace: None,
+        };
+        let text = |result: CallToolResult| -> serde_json::Value {
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap()
+        };
+
+        let first = text(handler.dna_list(list(None)).await.unwrap());
+        let id = first[0]["id"].as_str().unwrap().to_string();
+
+        let again = text(handler.dna_list(list(None)).await.unwrap());
+        assert_eq!(again["seen"], serde_json::json!([id]));
+        assert_eq!(again["results"][0]["content"], "seen once");
+
+        let omitted = text(handler.dna_list(list(Some(true))).await.unwrap());
+        assert_eq!(omitted["seen"], serde_json::json!([id]));
+        assert_eq!(omitted["results"][0]["content"], "");
+    }
+
+    #[tokio::test]
+    async fn dna_list_fits_max_tokens() {
+        let handler = test_handler();
+
+        for _ in 0..2 {
+            handler
+                .dna_add(AddRequest {
+                    kind: "intent".to_string(),
+                    content: "word ".repeat(30),
+                    format: Some(ContentFormat::Markdown),
+                    name: None,
+                    metadata: HashMap::new(),
+                    namespace: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let result = handler
+            .dna_list(ListRequest {
+                owner: None,
+                kind: None,
+                after: None,
+                before: None,
+                limit: None,
+                max_tokens: Some(50),
+                omit_seen: None,
+                kind_not: None,
+                metadata_not: None,
+                exclude_ids: None,
+                namespace: None,
+            })
+            .await
+            .unwrap();
+
+        let text = &result.content[0].as_text().unwrap().text;
+        let parsed: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(parsed["results"].as_array().unwrap().len(), 2);
+        let truncated = parsed["truncated"].as_array().unwrap();
+        assert_eq!(truncated.len(), 1);
+        assert_eq!(parsed["results"][1]["id"], truncated[0]);
+    }
+
+    #[tokio::test]
+    async fn dna_list_leaves_out_excluded_artifacts() {
+        let handler = test_handler();
+
+        for (kind, status) in [
+            ("intent", "active"),
+            ("intent", "deprecated"),
+            ("contract", "active"),
+        ] {
+            handler
+                .dna_add(AddRequest {
+                    kind: kind.to_string(),
+                    content: format!("{} {}", kind, status),
+                    format: Some(ContentFormat::Markdown),
+                    name: None,
+                    metadata: HashMap::from([("status".to_string(), status.to_string())]),
+                    namespace: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let result = handler
+            .dna_list(ListRequest {
+                owner: None,
+                kind: None,
+                after: None,
+                before: None,
+                limit: None,
+                max_tokens: None,
+                omit_seen: None,
+                kind_not: Some(vec!["contract".to_string()]),
+                metadata_not: Some(HashMap::from([(
+                    "status".to_string(),
+                    "deprecated".to_string(),
+                )])),
+                exclude_ids: None,
+                namespace: None,
+            })
+            .await
+            .unwrap();
+
+        let text = &result.content[0].as_text().unwrap().text;
+        let parsed: Vec<serde_json::Value> = serde_json::from_str(text).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0]["content"], "intent active");
+    }
+
     #[tokio::test]
     async fn dna_search_returns_results() {
         let handler = test_handler();
@@ -1013,9 +2250,10 @@ mod tests {
             .dna_add(AddRequest {
                 kind: "intent".to_string(),
                 content: "searchable".to_string(),
-                format: ContentFormat::Markdown,
+                format: Some(ContentFormat::Markdown),
                 name: None,
                 metadata: HashMap::new(),
+                namespace: None,
             })
             .await
             .unwrap();
@@ -1025,6 +2263,22 @@ mod tests {
                 query: "searchable".to_string(),
                 kind: None,
                 limit: Some(10),
+                weights: None,
+                boosts: None,
+                snippets_only: None,
+                max_tokens: None,
+                omit_seen: None,
+                kind_not: None,
+                metadata_not: None,
+                exclude_ids: None,
+                include_deprecated: None,
+                explain: None,
+                namespace: None,
+                local: None,
+                verified: None,
+                model: None,
+                language: None,
+                expand: None,
             })
             .await
             .unwrap();
@@ -1032,6 +2286,80 @@ mod tests {
         assert_eq!(result.is_error, Some(false));
     }
 
+    #[tokio::test]
+    async fn dna_ask_returns_context_without_a_model() {
+        let handler = test_handler();
+        handler
+            .dna_add(AddRequest {
+                kind: "invariant".to_string(),
+                content: "Orders ship only after payment is captured".to_string(),
+                format: Some(ContentFormat::Markdown),
+                name: None,
+                metadata: HashMap::new(),
+                namespace: None,
+            })
+            .await
+            .unwrap();
+
+        let result = handler
+            .dna_ask(AskRequest {
+                question: "why pay before shipping?".to_string(),
+                kind: None,
+                namespace: None,
+                limit: Some(5),
+                context_only: None,
+            })
+            .await
+            .unwrap();
+
+        let text = &result.content[0].as_text().unwrap().text;
+        let answer: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert!(answer.get("answer").is_none());
+        let id = answer["sources"][0]["id"].as_str().unwrap();
+        assert!(answer["context"]
+            .as_str()
+            .unwrap()
+            .starts_with(&format!("[{}] invariant", id)));
+    }
+
+    #[tokio::test]
+    async fn dna_pack_renders_matching_artifacts() {
+        let handler = test_handler();
+        for (kind, domain) in [
+            ("invariant", "auth"),
+            ("boundary", "auth"),
+            ("invariant", "billing"),
+        ] {
+            handler
+                .dna_add(AddRequest {
+                    kind: kind.to_string(),
+                    content: format!("{} rule for {}", kind, domain),
+                    format: Some(ContentFormat::Markdown),
+                    name: None,
+                    metadata: HashMap::from([("domain".to_string(), domain.to_string())]),
+                    namespace: None,
+                })
+                .await
+                .unwrap();
+        }
+
+        let result = handler
+            .dna_pack(PackRequest {
+                budget: 4000,
+                kinds: Some(vec!["invariant".to_string()]),
+                metadata: Some(HashMap::from([("domain".to_string(), "auth".to_string())])),
+                namespace: None,
+                json: None,
+            })
+            .await
+            .unwrap();
+
+        let text = &result.content[0].as_text().unwrap().text;
+        assert!(text.contains("## invariant"));
+        assert!(text.contains("invariant rule for auth"));
+        assert!(!text.contains("billing") && !text.contains("boundary"));
+    }
+
     #[tokio::test]
     async fn dna_remove_nonexistent() {
         let handler = test_handler();
@@ -1055,9 +2383,10 @@ mod tests {
             .dna_add(AddRequest {
                 kind: "intent".to_string(),
                 content: "original".to_string(),
-                format: ContentFormat::Markdown,
+                format: Some(ContentFormat::Markdown),
                 name: None,
                 metadata: HashMap::new(),
+                namespace: None,
             })
             .await
             .unwrap();
@@ -1081,6 +2410,24 @@ mod tests {
         assert!(text.contains("updated"));
     }
 
+    #[tokio::test]
+    async fn dna_update_with_labels_of_missing_artifact_is_not_found() {
+        let handler = test_handler();
+        let err = handler
+            .dna_update(UpdateRequest {
+                id: "nonexistent".to_string(),
+                content: None,
+                name: None,
+                kind: None,
+                metadata: Some(HashMap::from([("team".to_string(), "core".to_string())])),
+            })
+            .await
+            .unwrap_err();
+
+        assert_eq!(err.code, ErrorCode::RESOURCE_NOT_FOUND);
+        assert!(err.message.contains("nonexistent"), "{}", err.message);
+    }
+
     #[tokio::test]
     async fn dna_changes_returns_artifacts() {
         let handler = test_handler();
@@ -1089,9 +2436,10 @@ mod tests {
             .dna_add(AddRequest {
                 kind: "intent".to_string(),
                 content: "changed".to_string(),
-                format: ContentFormat::Markdown,
+                format: Some(ContentFormat::Markdown),
                 name: None,
                 metadata: HashMap::new(),
+                namespace: None,
             })
             .await
             .unwrap();
@@ -1100,11 +2448,194 @@ mod tests {
             .dna_changes(ChangesRequest {
                 after: None,
                 before: None,
+                summary: Some(true),
+                history: None,
+                namespace: None,
             })
             .await
             .unwrap();
 
         assert_eq!(result.is_error, Some(false));
+        let text = &result.content[0].as_text().unwrap().text;
+        let report: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(
+            report["summary"],
+            "1 artifact changed: 1 created. intent: 1 created."
+        );
+
+        let recent = handler
+            .dna_changes(ChangesRequest {
+                after: Some("7d".to_string()),
+                before: Some("2099-01".to_string()),
+                summary: None,
+                history: None,
+                namespace: None,
+            })
+            .await
+            .unwrap();
+        let text = &recent.content[0].as_text().unwrap().text;
+        let report: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(report["total"], 1);
+        assert_eq!(report["kinds"]["intent"]["created"], 1);
+        assert_eq!(report["changes"][0]["change"], "created");
+        assert_eq!(report["changes"][0]["content"], "changed");
+        assert!(report.get("summary").is_none());
+
+        let err = handler
+            .dna_changes(ChangesRequest {
+                after: Some("whenever".to_string()),
+                before: None,
+                summary: None,
+                history: None,
+                namespace: None,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("Invalid date 'whenever'"));
+    }
+
+    #[tokio::test]
+    async fn dna_changes_history_reports_deletions() {
+        let handler = test_handler();
+
+        let added = handler
+            .dna_add(AddRequest {
+                kind: "intent".to_string(),
+                content: "short-lived".to_string(),
+                format: Some(ContentFormat::Markdown),
+                name: None,
+                metadata: HashMap::new(),
+                namespace: None,
+            })
+            .await
+            .unwrap();
+        let added: serde_json::Value =
+            serde_json::from_str(&added.content[0].as_text().unwrap().text).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let after = chrono::Utc::now().to_rfc3339();
+        handler
+            .dna_remove(RemoveRequest {
+                id: added["id"].as_str().unwrap().to_string(),
+            })
+            .await
+            .unwrap();
+
+        let result = handler
+            .dna_changes(ChangesRequest {
+                after: Some(after),
+                before: None,
+                summary: None,
+                history: Some(true),
+                namespace: None,
+            })
+            .await
+            .unwrap();
+        let text = &result.content[0].as_text().unwrap().text;
+        let report: serde_json::Value = serde_json::from_str(text).unwrap();
+        assert_eq!(report["total"], 1);
+        assert_eq!(report["kinds"]["intent"]["deleted"], 1);
+        assert_eq!(report["changes"][0]["change"], "deleted");
+        assert_eq!(report["changes"][0]["content"], "short-lived");
+        assert_eq!(report["changes"][0]["version"], 3);
+    }
+
+    #[tokio::test]
+    async fn bound_namespace_scopes_adds_and_lists() {
+        let handler = test_handler().with_namespace(Some("payments".to_string()));
+        let add = |content: &str, namespace: Option<&str>| AddRequest {
+            kind: "intent".to_string(),
+            content: content.to_string(),
+            format: Some(ContentFormat::Markdown),
+            name: None,
+            metadata: HashMap::new(),
+            namespace: namespace.map(str::to_string),
+        };
+        handler.dna_add(add("ours", None)).await.unwrap();
+        handler
+            .dna_add(add("theirs", Some("search")))
+            .await
+            .unwrap();
+
+        let list = |namespace: Option<&str>| ListRequest {
+            owner: None,
+            kind: None,
+            after: None,
+            before: None,
+            limit: None,
+            max_tokens: None,
+            omit_seen: None,
+            kind_not: None,
+            metadata_not: None,
+            exclude_ids: None,
+            namespace: namespace.map(str::to_string),
+        };
+        let listed = |result: CallToolResult| -> Vec<serde_json::Value> {
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap()
+        };
+
+        let ours = listed(handler.dna_list(list(None)).await.unwrap());
+        assert_eq!(ours.len(), 1);
+        assert_eq!(ours[0]["content"], "ours");
+        assert_eq!(ours[0]["namespace"], "payments");
+
+        let theirs = listed(handler.dna_list(list(Some("search"))).await.unwrap());
+        assert_eq!(theirs.len(), 1);
+        assert_eq!(theirs[0]["content"], "theirs");
+    }
+
+    #[tokio::test]
+    async fn federated_search_tags_hits_with_their_store() {
+        let db: Arc<dyn Database> = Arc::new(TestDatabase::new());
+        let embedding: Arc<dyn EmbeddingProvider> = Arc::new(TestEmbedding);
+        let local = Arc::new(SearchService::new(db.clone(), embedding.clone()));
+        let payments_db = Arc::new(TestDatabase::new());
+        let contract = Artifact::new(
+            "contract".to_string(),
+            "refunds settle within 5 days".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "test".to_string(),
+        );
+        payments_db.insert(&contract).await.unwrap();
+        let payments = Arc::new(SearchService::new(payments_db, embedding.clone()));
+        let handler = DnaToolHandler::new(db, embedding, None, None).with_federation(Arc::new(
+            FederatedSearch::new(local).with_store("payments", payments, 1.0),
+        ));
+
+        let search = |local: Option<bool>| SearchRequest {
+            query: "refunds".to_string(),
+            kind: None,
+            namespace: None,
+            limit: Some(10),
+            weights: None,
+            boosts: None,
+            snippets_only: None,
+            max_tokens: None,
+            omit_seen: None,
+            kind_not: None,
+            metadata_not: None,
+            exclude_ids: None,
+            include_deprecated: None,
+            explain: None,
+            local,
+            verified: None,
+            model: None,
+            language: None,
+            expand: None,
+        };
+        let hits = |result: CallToolResult| -> Vec<serde_json::Value> {
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap()
+        };
+
+        let federated = hits(handler.dna_search(search(None)).await.unwrap());
+        assert_eq!(federated.len(), 1);
+        assert_eq!(federated[0]["store"], "payments");
+        assert_eq!(federated[0]["artifact"]["id"], contract.id);
+
+        let local_only = hits(handler.dna_search(search(Some(true))).await.unwrap());
+        assert!(local_only.is_empty());
     }
 
     #[test]
@@ -1112,6 +2643,51 @@ mod tests {
         assert_eq!(default_limit(), Some(10));
     }
 
+    #[tokio::test]
+    async fn kind_add_uses_kind_format_and_template() {
+        let db: Arc<dyn Database> = Arc::new(TestDatabase::new());
+        let embedding: Arc<dyn EmbeddingProvider> = Arc::new(TestEmbedding);
+        let kinds = vec![RegisteredKind {
+            slug: "evaluation".to_string(),
+            description: "Evaluations".to_string(),
+            format: Some(ContentFormat::Yaml),
+            template: Some("given: {{given}}\nthen: {{then}}".to_string()),
+            ..Default::default()
+        }];
+        let handler = DnaToolHandler::with_kinds(db, embedding, None, None, kinds);
+
+        let err = handler
+            .dna_kind_add(
+                "evaluation",
+                KindAddRequest {
+                    content: " ".to_string(),
+                    format: None,
+                    name: None,
+                    metadata: HashMap::new(),
+                },
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::INVALID_PARAMS);
+        assert!(err.message.contains("given: {{given}}"));
+
+        let result = handler
+            .dna_kind_add(
+                "evaluation",
+                KindAddRequest {
+                    content: "given: a user\nthen: they see it".to_string(),
+                    format: None,
+                    name: None,
+                    metadata: HashMap::new(),
+                },
+            )
+            .await
+            .unwrap();
+        let added: serde_json::Value =
+            serde_json::from_str(&result.content[0].as_text().unwrap().text).unwrap();
+        assert_eq!(added["format"], "yaml");
+    }
+
     #[test]
     fn default_format_is_markdown() {
         assert_eq!(default_format(), ContentFormat::Markdown);
@@ -1124,6 +2700,126 @@ mod tests {
         assert_eq!(info.server_info.name, "dna-server");
         assert!(info.server_info.description.is_none());
         assert!(info.capabilities.tools.is_some());
+        assert!(info.instructions.is_none());
+    }
+
+    #[test]
+    fn configured_instructions_and_descriptions_reach_clients() {
+        let db: Arc<dyn Database> = Arc::new(TestDatabase::new());
+        let embedding: Arc<dyn EmbeddingProvider> = Arc::new(TestEmbedding);
+        let kinds = vec![
+            RegisteredKind {
+                slug: "intent".to_string(),
+                description: "Intents".to_string(),
+                instructions: Some("One decision per artifact".to_string()),
+                ..Default::default()
+            },
+            RegisteredKind {
+                slug: "contract".to_string(),
+                description: "Contracts".to_string(),
+                ..Default::default()
+            },
+        ];
+        let config = McpConfig {
+            instructions: Some("Always search before adding".to_string()),
+            tools: HashMap::from([("dna_add".to_string(), "Add a truth".to_string())]),
+        };
+        let handler =
+            DnaToolHandler::with_kinds(db, embedding, None, None, kinds).with_mcp_config(&config);
+
+        assert_eq!(
+            handler.get_info().instructions.as_deref(),
+            Some("Always search before adding\n\nKinds:\n- intent: One decision per artifact")
+        );
+        assert_eq!(
+            DnaToolHandler::instructions_hint(&handler.registered_kinds[0]),
+            "\n\nOne decision per artifact"
+        );
+        assert_eq!(
+            DnaToolHandler::instructions_hint(&handler.registered_kinds[1]),
+            ""
+        );
+
+        let tool = |name: &str| rmcp::model::Tool {
+            name: name.to_string().into(),
+            description: Some("Built in".into()),
+            input_schema: Arc::new(serde_json::Map::new()),
+            title: None,
+            output_schema: None,
+            annotations: None,
+            execution: None,
+            icons: None,
+            meta: None,
+        };
+        let described = |name: &str| handler.configured_tool(tool(name)).description;
+        assert_eq!(described("dna_add").as_deref(), Some("Add a truth"));
+        assert_eq!(described("dna_get").as_deref(), Some("Built in"));
+    }
+
+    #[tokio::test]
+    async fn stdio_session_follows_the_mcp_handshake() {
+        use rmcp::ServiceExt;
+        use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let running = tokio::spawn(async move {
+            let service = test_handler().serve(server).await.unwrap();
+            service.waiting().await.unwrap();
+        });
+        let (read, mut write) = tokio::io::split(client);
+        let mut lines = BufReader::new(read).lines();
+
+        // The server says nothing until the client initializes
+        let init = serde_json::json!({
+            "jsonrpc": "2.0", "id": 1, "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": { "name": "test", "version": "0" }
+            }
+        });
+        write
+            .write_all(format!("{init}\n").as_bytes())
+            .await
+            .unwrap();
+        let reply: serde_json::Value =
+            serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert_eq!(reply["id"], 1);
+        assert_eq!(reply["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(reply["result"]["serverInfo"]["name"], "dna-server");
+
+        // Requests sent back to back are all answered, and cancelling one that
+        // already finished is harmless
+        let messages = [
+            serde_json::json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }),
+            serde_json::json!({ "jsonrpc": "2.0", "id": 2, "method": "ping" }),
+            serde_json::json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/list" }),
+            serde_json::json!({
+                "jsonrpc": "2.0", "method": "notifications/cancelled",
+                "params": { "requestId": 2 }
+            }),
+            serde_json::json!({ "jsonrpc": "2.0", "id": 4, "method": "ping" }),
+        ];
+        for message in messages {
+            write
+                .write_all(format!("{message}\n").as_bytes())
+                .await
+                .unwrap();
+        }
+        let mut replies = HashMap::new();
+        while replies.len() < 3 {
+            let reply: serde_json::Value =
+                serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+            replies.insert(reply["id"].as_u64().unwrap(), reply);
+        }
+        assert_eq!(replies[&2]["result"], serde_json::json!({}));
+        assert!(replies[&3]["result"]["tools"].as_array().unwrap().len() > 1);
+        assert_eq!(replies[&4]["result"], serde_json::json!({}));
+
+        // The client hanging up ends the session
+        drop(write);
+        drop(lines);
+        running.await.unwrap();
     }
 
     #[test]
@@ -0,0 +1,153 @@
+//! Client configuration for the stdio MCP server, printed by `dna mcp manifest`.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// MCP clients a manifest can be written for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum McpClient {
+    /// `claude_desktop_config.json`
+    ClaudeDesktop,
+    /// `.cursor/mcp.json` in a project, or `~/.cursor/mcp.json`
+    Cursor,
+    /// A flat description with the tool allowlist, for anything else
+    #[default]
+    Generic,
+}
+
+impl McpClient {
+    /// Where the client reads its server configuration from
+    pub fn config_location(&self) -> &'static str {
+        match self {
+            McpClient::ClaudeDesktop => {
+                "claude_desktop_config.json (Settings > Developer > Edit Config)"
+            },
+            McpClient::Cursor => ".cursor/mcp.json in the project, or ~/.cursor/mcp.json",
+            McpClient::Generic => "your client's MCP server configuration",
+        }
+    }
+}
+
+impl std::fmt::Display for McpClient {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            McpClient::ClaudeDesktop => write!(f, "claude-desktop"),
+            McpClient::Cursor => write!(f, "cursor"),
+            McpClient::Generic => write!(f, "generic"),
+        }
+    }
+}
+
+impl FromStr for McpClient {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "claude-desktop" | "claude" => Ok(McpClient::ClaudeDesktop),
+            "cursor" => Ok(McpClient::Cursor),
+            "generic" => Ok(McpClient::Generic),
+            _ => Err(anyhow::anyhow!(
+                "Unknown MCP client '{}'. Expected claude-desktop, cursor or generic",
+                s
+            )),
+        }
+    }
+}
+
+/// How a client starts the stdio server and what it will find there
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerManifest {
+    /// Key the server is registered under
+    pub name: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: BTreeMap<String, String>,
+    /// Tools the server exposes with these arguments
+    pub tools: Vec<String>,
+}
+
+impl ServerManifest {
+    /// Configuration block in the shape `client` expects.
+    ///
+    /// Claude Desktop and Cursor take no tool allowlist; the `--include` and
+    /// `--exclude` arguments limit the tools the server lists instead.
+    pub fn render(&self, client: McpClient) -> Value {
+        let mut server = json!({
+            "command": self.command,
+            "args": self.args,
+        });
+        if !self.env.is_empty() {
+            server["env"] = json!(self.env);
+        }
+
+        match client {
+            McpClient::ClaudeDesktop | McpClient::Cursor => {
+                let mut servers = serde_json::Map::new();
+                servers.insert(self.name.clone(), server);
+                json!({ "mcpServers": servers })
+            },
+            McpClient::Generic => {
+                server["name"] = json!(self.name);
+                server["transport"] = json!("stdio");
+                server["tools"] = json!(self.tools);
+                server
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest() -> ServerManifest {
+        ServerManifest {
+            name: "dna".to_string(),
+            command: "/usr/local/bin/dna".to_string(),
+            args: vec![
+                "mcp".to_string(),
+                "--project".to_string(),
+                "/work/app".to_string(),
+            ],
+            env: BTreeMap::new(),
+            tools: vec!["dna_search".to_string(), "dna_context".to_string()],
+        }
+    }
+
+    #[test]
+    fn client_names_round_trip() {
+        for client in [
+            McpClient::ClaudeDesktop,
+            McpClient::Cursor,
+            McpClient::Generic,
+        ] {
+            assert_eq!(client.to_string().parse::<McpClient>().unwrap(), client);
+        }
+        assert!("vscode".parse::<McpClient>().is_err());
+    }
+
+    #[test]
+    fn desktop_clients_nest_under_mcp_servers() {
+        let value = manifest().render(McpClient::ClaudeDesktop);
+        assert_eq!(value["mcpServers"]["dna"]["command"], "/usr/local/bin/dna");
+        assert_eq!(value["mcpServers"]["dna"]["args"][2], "/work/app");
+        assert!(value["mcpServers"]["dna"].get("env").is_none());
+        assert!(value["mcpServers"]["dna"].get("tools").is_none());
+    }
+
+    #[test]
+    fn generic_manifest_lists_tools_and_env() {
+        let mut manifest = manifest();
+        manifest
+            .env
+            .insert("HF_HOME".to_string(), "/cache".to_string());
+
+        let value = manifest.render(McpClient::Generic);
+        assert_eq!(value["name"], "dna");
+        assert_eq!(value["transport"], "stdio");
+        assert_eq!(value["tools"], json!(["dna_search", "dna_context"]));
+        assert_eq!(value["env"]["HF_HOME"], "/cache");
+    }
+}
@@ -1,3 +1,11 @@
+mod budget;
 pub mod handler;
+pub mod manifest;
+mod seen;
+pub mod session;
 
-pub use handler::{DnaToolHandler, RegisteredKind, RegisteredLabel};
+pub use handler::{
+    tool_allowed, tool_names, DnaToolHandler, RegisteredKind, RegisteredLabel, BASE_TOOLS,
+};
+pub use manifest::{McpClient, ServerManifest};
+pub use session::{read_session, replay, ReplayedCall, SessionEntry, SessionLog};
@@ -0,0 +1,105 @@
+//! Artifacts an MCP session has already been shown.
+//!
+//! Agents often search and list the same ground several times in one
+//! session. Every artifact a tool returns in full is remembered by ID and
+//! version (its `updated_at`); when a later search or list returns it again
+//! unchanged, its ID is listed under `seen`, and with `omit_seen` its content
+//! and context are left out so the agent doesn't read them twice.
+
+use crate::services::Artifact;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+/// Artifact versions returned in full during a session
+#[derive(Debug, Default)]
+pub struct Seen {
+    shown: Mutex<HashSet<(String, DateTime<Utc>)>>,
+}
+
+impl Seen {
+    /// IDs of `artifacts` already shown at their current version. With
+    /// `omit`, those artifacts lose their content and context.
+    pub fn mark<'a>(
+        &self,
+        artifacts: impl IntoIterator<Item = &'a mut Artifact>,
+        omit: bool,
+    ) -> Vec<String> {
+        let shown = self.shown.lock().unwrap_or_else(|e| e.into_inner());
+        let mut seen = Vec::new();
+        for artifact in artifacts {
+            if !shown.contains(&(artifact.id.clone(), artifact.updated_at)) {
+                continue;
+            }
+            if omit {
+                artifact.content.clear();
+                artifact.context = None;
+            }
+            seen.push(artifact.id.clone());
+        }
+        seen
+    }
+
+    /// Remember `artifacts` as shown, except the ones in `partial`, whose
+    /// content was cut or left out
+    pub fn record<'a>(
+        &self,
+        artifacts: impl IntoIterator<Item = &'a Artifact>,
+        partial: &[String],
+    ) {
+        let mut shown = self.shown.lock().unwrap_or_else(|e| e.into_inner());
+        for artifact in artifacts {
+            if !partial.contains(&artifact.id) {
+                shown.insert((artifact.id.clone(), artifact.updated_at));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use std::collections::HashMap;
+
+    fn artifact(id: &str) -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            format!("content of {}", id),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        artifact.id = id.to_string();
+        artifact.context = Some("why".to_string());
+        artifact
+    }
+
+    #[test]
+    fn marks_artifacts_shown_at_the_same_version() {
+        let seen = Seen::default();
+        let mut first = vec![artifact("a"), artifact("b")];
+        assert!(seen.mark(&mut first, false).is_empty());
+        seen.record(&first, &[]);
+
+        let mut updated = artifact("b");
+        updated.updated_at = first[1].updated_at + chrono::Duration::seconds(1);
+        let mut again = vec![first[0].clone(), updated, artifact("c")];
+        assert_eq!(seen.mark(&mut again, false), vec!["a"]);
+        assert_eq!(again[0].content, "content of a");
+    }
+
+    #[test]
+    fn omits_bodies_of_repeats_and_skips_partial_results() {
+        let seen = Seen::default();
+        let shown = vec![artifact("a"), artifact("b")];
+        seen.record(&shown, &["b".to_string()]);
+
+        let mut again = shown.clone();
+        assert_eq!(seen.mark(&mut again, true), vec!["a"]);
+        assert!(again[0].content.is_empty());
+        assert_eq!(again[0].context, None);
+        assert_eq!(again[1].content, "content of b");
+    }
+}
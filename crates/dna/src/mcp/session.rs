@@ -0,0 +1,224 @@
+//! Session logs for the stdio MCP server.
+//!
+//! `dna mcp --log-session session.jsonl` appends one line per tool call: the
+//! tool, its arguments, what it returned and how long it took. `dna mcp
+//! replay session.jsonl` runs the same calls, in order, against a store, so an
+//! agent's session can be reproduced without the agent.
+
+use super::DnaToolHandler;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rmcp::model::CallToolResult;
+use rmcp::ErrorData;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// One tool call as recorded in a session log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionEntry {
+    /// When the call arrived
+    pub at: DateTime<Utc>,
+    pub tool: String,
+    pub arguments: Map<String, Value>,
+    pub duration_ms: u64,
+    /// What the tool returned, including results flagged as errors
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<CallToolResult>,
+    /// The JSON-RPC error the call failed with instead
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<ErrorData>,
+}
+
+impl SessionEntry {
+    fn new(
+        at: DateTime<Utc>,
+        tool: &str,
+        arguments: Map<String, Value>,
+        started: Instant,
+        outcome: &Result<CallToolResult, ErrorData>,
+    ) -> Self {
+        Self {
+            at,
+            tool: tool.to_string(),
+            arguments,
+            duration_ms: started.elapsed().as_millis() as u64,
+            result: outcome.as_ref().ok().cloned(),
+            error: outcome.as_ref().err().cloned(),
+        }
+    }
+
+    /// Whether the call failed, either with an error or an error result
+    pub fn failed(&self) -> bool {
+        self.error.is_some() || self.result.as_ref().and_then(|r| r.is_error) == Some(true)
+    }
+}
+
+/// Where a server appends the calls it handles
+pub struct SessionLog {
+    file: Mutex<std::fs::File>,
+}
+
+impl SessionLog {
+    /// Append to the log at `path`, creating it if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open session log {}", path.display()))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    /// Append `entry` as one line. A log that can't be written doesn't fail the call.
+    pub fn record(&self, entry: &SessionEntry) {
+        let Ok(mut line) = serde_json::to_string(entry) else {
+            return;
+        };
+        line.push('\n');
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = file.write_all(line.as_bytes()).and_then(|_| file.flush()) {
+            tracing::warn!("Failed to write session log: {}", e);
+        }
+    }
+}
+
+/// Run a tool call through `handler`, timed, as a log entry
+pub(crate) async fn timed_call(
+    handler: &DnaToolHandler,
+    tool: &str,
+    arguments: Map<String, Value>,
+) -> (Result<CallToolResult, ErrorData>, SessionEntry) {
+    let at = Utc::now();
+    let started = Instant::now();
+    let outcome = handler.dispatch(tool, arguments.clone()).await;
+    let entry = SessionEntry::new(at, tool, arguments, started, &outcome);
+    (outcome, entry)
+}
+
+/// Read the entries of a session log, skipping blank lines
+pub fn read_session(path: &Path) -> Result<Vec<SessionEntry>> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open session log {}", path.display()))?;
+    let mut entries = Vec::new();
+    for (index, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line).with_context(|| {
+            format!("{}:{}: not a session log entry", path.display(), index + 1)
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// A recorded call and what it did when run again
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayedCall {
+    /// The call as recorded
+    pub recorded: SessionEntry,
+    /// The call as run again
+    pub replayed: SessionEntry,
+}
+
+impl ReplayedCall {
+    /// Whether the replay returned what was recorded
+    pub fn matches(&self) -> bool {
+        let content = |entry: &SessionEntry| {
+            entry
+                .result
+                .as_ref()
+                .map(|r| serde_json::to_value(&r.content).unwrap_or_default())
+        };
+        self.recorded.failed() == self.replayed.failed()
+            && content(&self.recorded) == content(&self.replayed)
+            && self.recorded.error.as_ref().map(|e| e.code)
+                == self.replayed.error.as_ref().map(|e| e.code)
+    }
+}
+
+/// Run every call of a session through `handler`, in order
+pub async fn replay(handler: &DnaToolHandler, entries: Vec<SessionEntry>) -> Vec<ReplayedCall> {
+    let mut calls = Vec::with_capacity(entries.len());
+    for recorded in entries {
+        let (_, replayed) = timed_call(handler, &recorded.tool, recorded.arguments.clone()).await;
+        calls.push(ReplayedCall { recorded, replayed });
+    }
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TestDatabase, TestEmbedding};
+    use std::sync::Arc;
+
+    fn handler() -> DnaToolHandler {
+        DnaToolHandler::new(
+            Arc::new(TestDatabase::new()),
+            Arc::new(TestEmbedding),
+            None,
+            None,
+        )
+    }
+
+    fn arguments(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[tokio::test]
+    async fn logged_calls_replay_against_another_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let log = Arc::new(SessionLog::open(&path).unwrap());
+
+        let recording = handler().with_session_log(log);
+        recording
+            .call(
+                "dna_add",
+                arguments(serde_json::json!({
+                    "kind": "intent",
+                    "content": "Orders ship within two days"
+                })),
+            )
+            .await
+            .unwrap();
+        let missing = recording
+            .call("dna_get", arguments(serde_json::json!({ "id": "nope" })))
+            .await
+            .unwrap();
+        assert_eq!(missing.is_error, Some(true));
+
+        let entries = read_session(&path).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].tool, "dna_add");
+        assert!(!entries[0].failed());
+        assert!(entries[1].failed());
+
+        // A fresh store gives the new artifact another ID, and misses the same way
+        let calls = replay(&handler(), entries).await;
+        assert_eq!(calls.len(), 2);
+        assert!(!calls[0].replayed.failed());
+        assert!(!calls[0].matches());
+        assert!(calls[1].replayed.failed());
+        assert!(calls[1].matches());
+    }
+
+    #[test]
+    fn reading_a_bad_line_names_it() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        std::fs::write(&path, "\nnot json\n").unwrap();
+        let err = read_session(&path).unwrap_err();
+        assert!(err
+            .to_string()
+            .ends_with("session.jsonl:2: not a session log entry"));
+    }
+}
@@ -1,48 +1,259 @@
-use crate::services::Artifact;
+use crate::services::frontmatter::{self, Frontmatter, RELATED_SECTION_MARKER};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::services::{attachment, is_s3_uri};
+use crate::services::{Artifact, ContentFormat, Variables, DEFAULT_LINK_LABEL};
 use anyhow::Result;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+
+/// Group-by key that stands for the artifact kind rather than a label
+pub const KIND_KEY: &str = "kind";
+
+/// Group-by key that stands for the artifact namespace rather than a label
+pub const NAMESPACE_KEY: &str = "namespace";
+
+/// Directory for artifacts without the label (or namespace) a group-by key names
+pub const UNLABELED_DIR: &str = "_unlabeled";
+
+/// Characters of the ID appended to file names that would collide
+const SHORT_ID_LENGTH: usize = 6;
+
+/// A link to or from another rendered artifact
+struct Related<'a> {
+    /// Label the link is stored under
+    label: &'a str,
+    /// Path of the other file, relative to this file's directory
+    path: String,
+    other: &'a Artifact,
+    /// Whether the other artifact links to this one
+    backlink: bool,
+}
 
 /// Service for rendering artifacts to filesystem
 pub struct RenderService {
     output_dir: PathBuf,
+    /// Labels whose values are IDs of related artifacts
+    link_labels: Vec<String>,
+    /// Write byte-identical output for an unchanged store
+    deterministic: bool,
+    /// Leave out fields that change without the artifact changing
+    omit_volatile: bool,
+    /// Where attachments are stored, to link them from the files
+    attachments: Option<String>,
+    /// Values filled into `{{name}}` placeholders in content
+    variables: Option<Variables>,
 }
 
 impl RenderService {
     /// Create a new render service
     pub fn new(output_dir: PathBuf) -> Self {
-        Self { output_dir }
+        Self {
+            output_dir,
+            link_labels: vec![DEFAULT_LINK_LABEL.to_string()],
+            deterministic: false,
+            omit_volatile: false,
+            attachments: None,
+            variables: None,
+        }
+    }
+
+    /// Render links from these labels (comma-separated artifact IDs) instead of `related`
+    pub fn with_link_labels(mut self, labels: Vec<String>) -> Self {
+        self.link_labels = labels;
+        self
+    }
+
+    /// Render the same files byte for byte while the store is unchanged.
+    ///
+    /// Files are written in path order, related links are sorted, line
+    /// endings become `\n`, and files whose content is already on disk are
+    /// left untouched, so committed renders only change with the store.
+    pub fn with_deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Leave `updated_at` out of the frontmatter, which moves on every
+    /// update, even one that leaves the artifact as it was
+    pub fn with_omit_volatile(mut self, omit_volatile: bool) -> Self {
+        self.omit_volatile = omit_volatile;
+        self
+    }
+
+    /// Link attachments stored at `location`, a local directory or `s3://`
+    /// URI, from the Markdown files of the artifacts they belong to
+    pub fn with_attachments(mut self, location: String) -> Self {
+        self.attachments = Some(location);
+        self
+    }
+
+    /// Fill `{{name}}` placeholders in content from `variables`.
+    ///
+    /// The files then hold the values rather than the placeholders, so
+    /// syncing them back would store the values.
+    pub fn with_variables(mut self, variables: Variables) -> Self {
+        self.variables = Some(variables);
+        self
     }
 
     /// Render all artifacts to files
     pub async fn render_all(&self, artifacts: &[Artifact], group_by: &[String]) -> Result<()> {
-        // Group artifacts
-        let groups = self.group_artifacts(artifacts, group_by);
+        let interpolated;
+        let artifacts = match &self.variables {
+            Some(variables) => {
+                let mut copies = artifacts.to_vec();
+                variables.apply(&mut copies)?;
+                interpolated = copies;
+                &interpolated[..]
+            },
+            None => artifacts,
+        };
+        let paths = self.artifact_paths(artifacts, group_by)?;
+        let mut related = self.relations(artifacts, &paths);
+
+        let mut order: Vec<&Artifact> = artifacts.iter().collect();
+        if self.deterministic {
+            order.sort_by(|a, b| paths[a.id.as_str()].cmp(&paths[b.id.as_str()]));
+        }
 
-        for (path_parts, group_artifacts) in groups {
-            for artifact in group_artifacts {
-                self.render_artifact(artifact, &path_parts).await?;
+        for artifact in order {
+            let mut links = related.remove(artifact.id.as_str()).unwrap_or_default();
+            if self.deterministic {
+                links.sort_by(|a, b| {
+                    (a.backlink, a.label, &a.path).cmp(&(b.backlink, b.label, &b.path))
+                });
             }
+            self.render_artifact(artifact, &paths[artifact.id.as_str()], &links)
+                .await?;
         }
 
         Ok(())
     }
 
-    /// Group artifacts by metadata keys
+    /// Files [`render_all`](Self::render_all) would write, without writing them
+    pub fn planned_files(
+        &self,
+        artifacts: &[Artifact],
+        group_by: &[String],
+    ) -> Result<Vec<PathBuf>> {
+        let mut files: Vec<PathBuf> = self
+            .artifact_paths(artifacts, group_by)?
+            .into_values()
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// File each artifact is written to, keyed by ID.
+    ///
+    /// Artifacts that would share a file all get their short ID appended to
+    /// the file name, so none overwrites another whatever the listing order.
+    fn artifact_paths<'a>(
+        &self,
+        artifacts: &'a [Artifact],
+        group_by: &[String],
+    ) -> Result<HashMap<&'a str, PathBuf>> {
+        let mut by_path: HashMap<PathBuf, Vec<&'a Artifact>> = HashMap::new();
+        for (path_parts, group_artifacts) in self.group_artifacts(artifacts, group_by) {
+            for artifact in group_artifacts {
+                by_path
+                    .entry(self.file_path(artifact, &path_parts)?)
+                    .or_default()
+                    .push(artifact);
+            }
+        }
+
+        let mut paths = HashMap::with_capacity(artifacts.len());
+        for (path, sharing) in by_path {
+            if let [artifact] = sharing.as_slice() {
+                paths.insert(artifact.id.as_str(), path);
+                continue;
+            }
+            let ids: Vec<&str> = sharing.iter().map(|a| a.id.as_str()).collect();
+            for artifact in sharing {
+                paths.insert(
+                    artifact.id.as_str(),
+                    with_suffix(&path, short_id(&artifact.id, &ids)),
+                );
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Links between rendered artifacts, both ways, keyed by artifact ID.
+    ///
+    /// Links to artifacts outside the render are skipped, since there is no
+    /// file to point at.
+    fn relations<'a>(
+        &'a self,
+        artifacts: &'a [Artifact],
+        paths: &HashMap<&str, PathBuf>,
+    ) -> HashMap<&'a str, Vec<Related<'a>>> {
+        let by_id: HashMap<&str, &Artifact> =
+            artifacts.iter().map(|a| (a.id.as_str(), a)).collect();
+        let mut related: HashMap<&str, Vec<Related>> = HashMap::new();
+
+        for from in artifacts {
+            for label in &self.link_labels {
+                let Some(value) = from.metadata.get(label) else {
+                    continue;
+                };
+                for id in value.split(',').map(str::trim) {
+                    let Some(to) = by_id.get(id).copied() else {
+                        continue;
+                    };
+                    if to.id == from.id {
+                        continue;
+                    }
+                    let (from_path, to_path) = (&paths[from.id.as_str()], &paths[to.id.as_str()]);
+                    related.entry(from.id.as_str()).or_default().push(Related {
+                        label,
+                        path: relative_path(from_path, to_path),
+                        other: to,
+                        backlink: false,
+                    });
+                    related.entry(to.id.as_str()).or_default().push(Related {
+                        label,
+                        path: relative_path(to_path, from_path),
+                        other: from,
+                        backlink: true,
+                    });
+                }
+            }
+        }
+
+        related
+    }
+
+    /// Group artifacts by metadata keys, one directory level per key.
+    ///
+    /// Groups start with the kind unless `kind` is one of the keys, which
+    /// places it at that level instead. A `namespace` key groups by the
+    /// artifact's namespace. Artifacts without a key's label go
+    /// in an [`UNLABELED_DIR`] directory at that level, so every file sits
+    /// at the same depth. Values are escaped with [`dir_name`], so each
+    /// stays one directory below the last.
     fn group_artifacts<'a>(
         &self,
         artifacts: &'a [Artifact],
         keys: &[String],
     ) -> HashMap<Vec<String>, Vec<&'a Artifact>> {
         let mut groups: HashMap<Vec<String>, Vec<&'a Artifact>> = HashMap::new();
+        let kind_listed = keys.iter().any(|k| k == KIND_KEY);
 
         for artifact in artifacts {
-            let mut path_parts = vec![artifact.kind.clone()];
+            let mut path_parts = Vec::with_capacity(keys.len() + 1);
+            if !kind_listed {
+                path_parts.push(artifact.kind.clone());
+            }
 
             for key in keys {
-                if let Some(value) = artifact.metadata.get(key) {
-                    path_parts.push(value.clone());
-                }
+                let value = match key.as_str() {
+                    KIND_KEY => Some(&artifact.kind),
+                    NAMESPACE_KEY => artifact.namespace.as_ref(),
+                    _ => artifact.metadata.get(key).filter(|v| !v.is_empty()),
+                };
+                path_parts.push(value.map_or_else(|| UNLABELED_DIR.to_string(), |v| dir_name(v)));
             }
 
             groups.entry(path_parts).or_default().push(artifact);
@@ -52,28 +263,90 @@ impl RenderService {
     }
 
     /// Render a single artifact to file
-    async fn render_artifact(&self, artifact: &Artifact, path_parts: &[String]) -> Result<()> {
-        // Build directory path
-        let mut dir_path = self.output_dir.clone();
-        for part in path_parts {
-            dir_path.push(part);
+    async fn render_artifact(
+        &self,
+        artifact: &Artifact,
+        file_path: &Path,
+        related: &[Related<'_>],
+    ) -> Result<()> {
+        if let Some(dir_path) = file_path.parent() {
+            tokio::fs::create_dir_all(dir_path).await?;
         }
-        tokio::fs::create_dir_all(&dir_path).await?;
-
-        // Generate filename
-        let filename = self.generate_filename(artifact)?;
-        let file_path = dir_path.join(filename);
 
         // Generate frontmatter
-        let frontmatter = self.generate_frontmatter(artifact)?;
+        let frontmatter = self.generate_frontmatter(artifact, related);
 
         // Write file
-        let content = format!("---\n{}\n---\n\n{}", frontmatter, artifact.content);
-        tokio::fs::write(&file_path, content).await?;
+        let mut content = frontmatter::join(&frontmatter, &artifact.content)?;
+        if artifact.format == ContentFormat::Markdown {
+            let mut sections = Vec::new();
+            if !related.is_empty() {
+                sections.push(related_section(related));
+            }
+            let attachments = self.attachment_links(artifact, file_path)?;
+            if !attachments.is_empty() {
+                sections.push(attachments_section(&attachments));
+            }
+            if !sections.is_empty() {
+                content.push_str("\n\n");
+                content.push_str(RELATED_SECTION_MARKER);
+                content.push('\n');
+                content.push_str(&sections.join("\n"));
+            }
+        }
+        if self.deterministic {
+            content = content.replace("\r\n", "\n");
+            // Leave identical files alone so their modification time holds
+            if tokio::fs::read(file_path).await.ok().as_deref() == Some(content.as_bytes()) {
+                return Ok(());
+            }
+        }
+        tokio::fs::write(file_path, content).await?;
 
         Ok(())
     }
 
+    /// Attachment names and links to them from `file_path`: relative paths
+    /// for a local location, URIs on S3
+    #[cfg(not(target_arch = "wasm32"))]
+    fn attachment_links(
+        &self,
+        artifact: &Artifact,
+        file_path: &Path,
+    ) -> Result<Vec<(String, String)>> {
+        let Some(location) = &self.attachments else {
+            return Ok(Vec::new());
+        };
+        let mut links = Vec::new();
+        for attached in attachment::attachments(artifact) {
+            let key = attached.key(&artifact.id);
+            let link = if is_s3_uri(location) {
+                format!("{}/{}", location.trim_end_matches('/'), key)
+            } else {
+                relative_path(
+                    &std::path::absolute(file_path)?,
+                    &std::path::absolute(Path::new(location).join(&key))?,
+                )
+            };
+            links.push((attached.name, link));
+        }
+        Ok(links)
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn attachment_links(&self, _: &Artifact, _: &Path) -> Result<Vec<(String, String)>> {
+        Ok(Vec::new())
+    }
+
+    /// Path an artifact is written to within its group directory
+    fn file_path(&self, artifact: &Artifact, path_parts: &[String]) -> Result<PathBuf> {
+        let mut dir_path = self.output_dir.clone();
+        for part in path_parts {
+            dir_path.push(part);
+        }
+        Ok(dir_path.join(self.generate_filename(artifact)?))
+    }
+
     /// Generate filename for artifact
     fn generate_filename(&self, artifact: &Artifact) -> Result<String> {
         let extension = artifact.file_extension();
@@ -91,32 +364,131 @@ impl RenderService {
             Ok(format!("{}.{}", slug, extension))
         } else {
             // Last resort: use ID
-            Ok(format!("{}.{}", artifact.id, extension))
+            Ok(format!("{}.{}", dir_name(&artifact.id), extension))
         }
     }
 
     /// Generate YAML frontmatter
-    fn generate_frontmatter(&self, artifact: &Artifact) -> Result<String> {
-        let mut frontmatter = format!(
-            "id: {}\nkind: {}\nformat: {}",
-            artifact.id, artifact.kind, artifact.format
-        );
-
-        if !artifact.metadata.is_empty() {
-            frontmatter.push_str("\nmetadata:");
-            for (key, value) in &artifact.metadata {
-                frontmatter.push_str(&format!("\n  {}: {}", key, value));
+    fn generate_frontmatter(&self, artifact: &Artifact, related: &[Related<'_>]) -> Frontmatter {
+        let mut frontmatter = Frontmatter::from_artifact(artifact);
+        if self.omit_volatile {
+            frontmatter.updated_at = None;
+        }
+        for link in related.iter().filter(|r| !r.backlink) {
+            if !frontmatter.links.contains(&link.path) {
+                frontmatter.links.push(link.path.clone());
             }
         }
+        frontmatter
+    }
+}
+
+/// "Related" section of wiki links, appended after Markdown content
+fn related_section(related: &[Related<'_>]) -> String {
+    let mut section = "## Related\n".to_string();
+    let mut lines: Vec<String> = Vec::new();
+    for link in related {
+        // A mutual link is listed once, as this artifact's own
+        let mutual = link.backlink
+            && related
+                .iter()
+                .any(|r| !r.backlink && r.label == link.label && r.other.id == link.other.id);
+        if mutual {
+            continue;
+        }
+        // Wiki links leave off the extension of Markdown files
+        let target = link.path.strip_suffix(".md").unwrap_or(&link.path);
+        let title = link.other.name.as_deref().unwrap_or(&link.other.id);
+        let line = if link.backlink {
+            format!("- linked from ({}): [[{}|{}]]", link.label, target, title)
+        } else {
+            format!("- {}: [[{}|{}]]", link.label, target, title)
+        };
+        if !lines.contains(&line) {
+            lines.push(line);
+        }
+    }
+    section.push('\n');
+    section.push_str(&lines.join("\n"));
+    section.push('\n');
+    section
+}
+
+/// "Attachments" section linking each attached file by name
+fn attachments_section(links: &[(String, String)]) -> String {
+    let mut section = "## Attachments\n\n".to_string();
+    for (name, link) in links {
+        // Angle brackets keep spaces in file names from ending the link
+        section.push_str(&format!("- [{}](<{}>)\n", name, link));
+    }
+    section
+}
 
-        frontmatter.push_str(&format!(
-            "\ncreated_at: {}\nupdated_at: {}",
-            artifact.created_at.to_rfc3339(),
-            artifact.updated_at.to_rfc3339()
-        ));
+/// `value` as a single path component.
+///
+/// Path separators, drive colons, NUL and `%` itself are percent-encoded, as
+/// are the dots of a bare `.` or `..`, so a label or namespace can't name a
+/// directory outside the one it is grouped under.
+fn dir_name(value: &str) -> String {
+    if value == "." || value == ".." {
+        return value.replace('.', "%2E");
+    }
+    let mut name = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '/' | '\\' | ':' | '%' | '\0' => name.push_str(&format!("%{:02X}", c as u32)),
+            c => name.push(c),
+        }
+    }
+    name
+}
 
-        Ok(frontmatter)
+/// Shortest ID prefix (at least [`SHORT_ID_LENGTH`] characters) that tells
+/// `id` apart from the other IDs in `ids`
+fn short_id<'a>(id: &'a str, ids: &[&str]) -> &'a str {
+    let mut end = id
+        .char_indices()
+        .nth(SHORT_ID_LENGTH)
+        .map_or(id.len(), |(i, _)| i);
+    while end < id.len()
+        && ids
+            .iter()
+            .any(|other| *other != id && other.starts_with(&id[..end]))
+    {
+        end += id[end..].chars().next().map_or(1, char::len_utf8);
     }
+    &id[..end]
+}
+
+/// `path` with `-suffix` added to the file stem
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, suffix, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, suffix),
+    };
+    path.with_file_name(name)
+}
+
+/// Path of `to` relative to the directory holding `from`, with `/` separators
+fn relative_path(from: &Path, to: &Path) -> String {
+    let from_dir: Vec<Component> = from
+        .parent()
+        .map(|p| p.components().collect())
+        .unwrap_or_default();
+    let to: Vec<Component> = to.components().collect();
+    let common = from_dir.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts: Vec<String> = vec!["..".to_string(); from_dir.len() - common];
+    parts.extend(
+        to[common..]
+            .iter()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.join("/")
 }
 
 #[cfg(test)]
@@ -194,7 +566,10 @@ mod tests {
 
         let artifact = create_test_artifact(Some("test"), "content", "contract", HashMap::new());
 
-        let frontmatter = service.generate_frontmatter(&artifact).unwrap();
+        let frontmatter = service
+            .generate_frontmatter(&artifact, &[])
+            .to_yaml()
+            .unwrap();
         assert!(frontmatter.contains(&format!("id: {}", artifact.id)));
         assert!(frontmatter.contains("kind: contract"));
         assert!(frontmatter.contains("format: markdown"));
@@ -213,7 +588,10 @@ mod tests {
 
         let artifact = create_test_artifact(Some("test"), "content", "intent", metadata);
 
-        let frontmatter = service.generate_frontmatter(&artifact).unwrap();
+        let frontmatter = service
+            .generate_frontmatter(&artifact, &[])
+            .to_yaml()
+            .unwrap();
         assert!(frontmatter.contains("metadata:"));
         assert!(frontmatter.contains("domain: auth"));
         assert!(frontmatter.contains("priority: high"));
@@ -256,6 +634,120 @@ mod tests {
         assert_eq!(groups.len(), 2);
     }
 
+    #[test]
+    fn group_artifacts_puts_missing_labels_in_unlabeled() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RenderService::new(temp_dir.path().to_path_buf());
+
+        let mut metadata = HashMap::new();
+        metadata.insert("service".to_string(), "api".to_string());
+        let artifacts = vec![create_test_artifact(None, "one", "intent", metadata)];
+
+        let keys = vec!["domain".to_string(), "service".to_string()];
+        let groups = service.group_artifacts(&artifacts, &keys);
+        let parts: Vec<&Vec<String>> = groups.keys().collect();
+        assert_eq!(parts, vec![&vec!["intent", UNLABELED_DIR, "api"]]);
+    }
+
+    #[test]
+    fn group_artifacts_places_kind_where_listed() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RenderService::new(temp_dir.path().to_path_buf());
+
+        let mut metadata = HashMap::new();
+        metadata.insert("domain".to_string(), "auth".to_string());
+        let artifacts = vec![create_test_artifact(None, "one", "intent", metadata)];
+
+        let keys = vec!["domain".to_string(), "kind".to_string()];
+        let groups = service.group_artifacts(&artifacts, &keys);
+        let parts: Vec<&Vec<String>> = groups.keys().collect();
+        assert_eq!(parts, vec![&vec!["auth", "intent"]]);
+    }
+
+    #[test]
+    fn group_artifacts_by_namespace() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RenderService::new(temp_dir.path().to_path_buf());
+
+        let mut scoped = create_test_artifact(None, "one", "intent", HashMap::new());
+        scoped.namespace = Some("payments".to_string());
+        let shared = create_test_artifact(None, "two", "intent", HashMap::new());
+        let artifacts = vec![scoped, shared];
+
+        let keys = vec!["namespace".to_string()];
+        let groups = service.group_artifacts(&artifacts, &keys);
+        assert_eq!(
+            groups[&vec!["intent".to_string(), "payments".to_string()]].len(),
+            1
+        );
+        assert_eq!(
+            groups[&vec!["intent".to_string(), UNLABELED_DIR.to_string()]].len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn hostile_labels_stay_inside_the_output_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        let output_dir = temp_dir.path().join("render");
+        let service = RenderService::new(output_dir.clone());
+
+        let mut metadata = HashMap::new();
+        metadata.insert("domain".to_string(), "../../x".to_string());
+        let mut artifact = create_test_artifact(Some("escape"), "content", "intent", metadata);
+        artifact.namespace = Some("..".to_string());
+        let keys = vec!["namespace".to_string(), "domain".to_string()];
+
+        let files = service
+            .planned_files(std::slice::from_ref(&artifact), &keys)
+            .unwrap();
+        assert_eq!(
+            files,
+            vec![output_dir
+                .join("intent")
+                .join("%2E%2E")
+                .join("..%2F..%2Fx")
+                .join("escape.md")]
+        );
+
+        service.render_all(&[artifact], &keys).await.unwrap();
+        assert!(files[0].exists());
+        assert!(!temp_dir.path().join("x").exists());
+    }
+
+    #[test]
+    fn dir_names_escape_path_syntax() {
+        assert_eq!(dir_name("auth"), "auth");
+        assert_eq!(dir_name("."), "%2E");
+        assert_eq!(dir_name("/etc"), "%2Fetc");
+        assert_eq!(dir_name(r"C:\x"), "C%3A%5Cx");
+        assert_eq!(dir_name("50%"), "50%25");
+        assert_eq!(dir_name("a..b"), "a..b");
+    }
+
+    #[test]
+    fn colliding_file_names_get_short_ids() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RenderService::new(temp_dir.path().to_path_buf());
+
+        let mut a1 = create_test_artifact(Some("login"), "one", "intent", HashMap::new());
+        a1.id = "k7v3m9xnp2".to_string();
+        let mut a2 = create_test_artifact(Some("login"), "two", "intent", HashMap::new());
+        a2.id = "k7v3m9abcd".to_string();
+        let a3 = create_test_artifact(Some("logout"), "three", "intent", HashMap::new());
+
+        let files = service.planned_files(&[a1, a2, a3], &[]).unwrap();
+        let dir = temp_dir.path().join("intent");
+        assert_eq!(
+            files,
+            vec![
+                dir.join("login-k7v3m9a.md"),
+                dir.join("login-k7v3m9x.md"),
+                dir.join("logout.md"),
+            ]
+        );
+    }
+
     #[tokio::test]
     async fn render_artifact_creates_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -268,12 +760,11 @@ mod tests {
             HashMap::new(),
         );
 
+        let file_path = temp_dir.path().join("intent").join("test-artifact.md");
         service
-            .render_artifact(&artifact, &["intent".to_string()])
+            .render_artifact(&artifact, &file_path, &[])
             .await
             .unwrap();
-
-        let file_path = temp_dir.path().join("intent").join("test-artifact.md");
         assert!(file_path.exists());
 
         let content = std::fs::read_to_string(&file_path).unwrap();
@@ -304,4 +795,156 @@ mod tests {
             .join("contract-one.md")
             .exists());
     }
+
+    #[test]
+    fn planned_files_match_render_paths_without_writing() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RenderService::new(temp_dir.path().to_path_buf());
+
+        let mut metadata = HashMap::new();
+        metadata.insert("domain".to_string(), "auth".to_string());
+        let artifact = create_test_artifact(Some("login"), "content", "intent", metadata);
+
+        let files = service
+            .planned_files(&[artifact], &["domain".to_string()])
+            .unwrap();
+
+        assert_eq!(
+            files,
+            vec![temp_dir.path().join("intent").join("auth").join("login.md")]
+        );
+        assert!(!temp_dir.path().join("intent").exists());
+    }
+
+    #[test]
+    fn relative_path_walks_up_to_common_directory() {
+        let out = Path::new("/out");
+        assert_eq!(
+            relative_path(&out.join("intent/login.md"), &out.join("intent/logout.md")),
+            "logout.md"
+        );
+        assert_eq!(
+            relative_path(
+                &out.join("intent/auth/login.md"),
+                &out.join("contract/api.yaml")
+            ),
+            "../../contract/api.yaml"
+        );
+    }
+
+    #[tokio::test]
+    async fn render_all_links_related_artifacts_both_ways() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RenderService::new(temp_dir.path().to_path_buf());
+
+        let contract = create_test_artifact(Some("user-api"), "api", "contract", HashMap::new());
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            DEFAULT_LINK_LABEL.to_string(),
+            format!("{}, unknown-id", contract.id),
+        );
+        let intent = create_test_artifact(Some("login"), "Users can log in", "intent", metadata);
+
+        service
+            .render_all(&[intent.clone(), contract.clone()], &[])
+            .await
+            .unwrap();
+
+        let login =
+            std::fs::read_to_string(temp_dir.path().join("intent").join("login.md")).unwrap();
+        let (yaml, _) = frontmatter::split(&login).unwrap();
+        assert_eq!(
+            Frontmatter::parse(yaml).unwrap().links,
+            vec!["../contract/user-api.md"]
+        );
+        assert!(login.contains("- related: [[../contract/user-api|user-api]]"));
+        assert!(!login.contains("unknown-id]]"));
+
+        let api =
+            std::fs::read_to_string(temp_dir.path().join("contract").join("user-api.md")).unwrap();
+        assert!(!api.contains("links:"));
+        assert!(api.contains("- linked from (related): [[../intent/login|login]]"));
+
+        // Reading the file back yields the stored content, without the links
+        let doc = crate::services::SyncDocument::parse(
+            temp_dir.path(),
+            &temp_dir.path().join("intent").join("login.md"),
+            &login,
+        )
+        .unwrap();
+        assert_eq!(doc.content, intent.content);
+    }
+
+    #[tokio::test]
+    async fn render_all_links_attachments() {
+        let temp_dir = TempDir::new().unwrap();
+        let location = temp_dir.path().join(".dna").join("attachments");
+        let service = RenderService::new(temp_dir.path().join("docs"))
+            .with_attachments(location.to_string_lossy().to_string());
+
+        let mut intent =
+            create_test_artifact(Some("checkout"), "Checkout flow", "intent", HashMap::new());
+        intent.metadata.extend(
+            attachment::manifest_with(
+                &intent,
+                attachment::Attachment {
+                    name: "flow diagram.png".to_string(),
+                    size: 3,
+                },
+            )
+            .unwrap(),
+        );
+        service
+            .render_all(std::slice::from_ref(&intent), &[])
+            .await
+            .unwrap();
+
+        let file = temp_dir
+            .path()
+            .join("docs")
+            .join("intent")
+            .join("checkout.md");
+        let rendered = std::fs::read_to_string(&file).unwrap();
+        assert!(
+            rendered.contains(&format!(
+                "## Attachments\n\n- [flow diagram.png](<../../.dna/attachments/{}/flow diagram.png>)",
+                intent.id
+            )),
+            "{}",
+            rendered
+        );
+
+        let doc =
+            crate::services::SyncDocument::parse(&temp_dir.path().join("docs"), &file, &rendered)
+                .unwrap();
+        assert_eq!(doc.content, intent.content);
+    }
+
+    #[tokio::test]
+    async fn deterministic_render_is_stable_and_normalized() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = RenderService::new(temp_dir.path().to_path_buf())
+            .with_deterministic(true)
+            .with_omit_volatile(true);
+
+        let mut artifact = create_test_artifact(
+            Some("login"),
+            "line one\r\nline two\r\n",
+            "intent",
+            HashMap::new(),
+        );
+        service.render_all(&[artifact.clone()], &[]).await.unwrap();
+
+        let path = temp_dir.path().join("intent").join("login.md");
+        let first = std::fs::read_to_string(&path).unwrap();
+        assert!(first.contains("line one\nline two\n"));
+        assert!(!first.contains('\r'));
+        assert!(!first.contains("updated_at"));
+        assert!(first.contains("created_at"));
+
+        // A touched timestamp alone changes nothing on disk
+        artifact.updated_at += chrono::Duration::hours(1);
+        service.render_all(&[artifact], &[]).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), first);
+    }
 }
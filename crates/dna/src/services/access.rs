@@ -0,0 +1,187 @@
+//! Access control by kind.
+//!
+//! An [`AccessPolicy`] decides which kinds a caller may read and which it
+//! may write. The artifact and search services consult it before every
+//! operation, so an API key or MCP server limited to, say, adding
+//! `evaluation` artifacts and reading `invariant` ones cannot touch the
+//! rest of the store through any front end.
+
+use super::types::SearchFilters;
+use super::ServiceError;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// What a caller wants to do with artifacts of a kind
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    /// Get, list and search
+    Read,
+    /// Add, update, rename, merge, archive and remove
+    Write,
+}
+
+impl std::fmt::Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read => write!(f, "read"),
+            Self::Write => write!(f, "write"),
+        }
+    }
+}
+
+/// Decides which kinds a caller may read and write
+pub trait AccessPolicy: Send + Sync {
+    /// Whether `access` to artifacts of `kind` is allowed
+    fn allows(&self, access: Access, kind: &str) -> bool;
+
+    /// Kinds `access` is limited to, or `None` when every kind is allowed.
+    ///
+    /// Lets listings and searches filter in the store instead of dropping
+    /// results afterwards.
+    fn kinds(&self, access: Access) -> Option<Vec<String>>;
+
+    /// `Forbidden` unless `access` to `kind` is allowed
+    fn check(&self, access: Access, kind: &str) -> Result<(), ServiceError> {
+        if self.allows(access, kind) {
+            Ok(())
+        } else {
+            Err(ServiceError::Forbidden(format!(
+                "No {} access to kind '{}'",
+                access, kind
+            )))
+        }
+    }
+}
+
+/// Allowlists of kinds to read and to write.
+///
+/// `None` allows every kind; an empty list allows none. Kinds that may be
+/// written may also be read.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KindAccess {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write: Option<Vec<String>>,
+}
+
+impl KindAccess {
+    /// Only these kinds may be read
+    pub fn with_read(mut self, kinds: Vec<String>) -> Self {
+        self.read = Some(kinds);
+        self
+    }
+
+    /// Only these kinds may be written
+    pub fn with_write(mut self, kinds: Vec<String>) -> Self {
+        self.write = Some(kinds);
+        self
+    }
+
+    /// Whether every kind may be read and written
+    pub fn is_unrestricted(&self) -> bool {
+        self.read.is_none() && self.write.is_none()
+    }
+}
+
+impl AccessPolicy for KindAccess {
+    fn allows(&self, access: Access, kind: &str) -> bool {
+        let listed = |kinds: &Option<Vec<String>>| {
+            kinds
+                .as_ref()
+                .is_none_or(|kinds| kinds.iter().any(|k| k == kind))
+        };
+        match access {
+            Access::Write => listed(&self.write),
+            Access::Read => {
+                listed(&self.read)
+                    || self
+                        .write
+                        .as_ref()
+                        .is_some_and(|kinds| kinds.iter().any(|k| k == kind))
+            },
+        }
+    }
+
+    fn kinds(&self, access: Access) -> Option<Vec<String>> {
+        match access {
+            Access::Write => self.write.clone(),
+            Access::Read => {
+                let mut kinds = self.read.clone()?;
+                for kind in self.write.iter().flatten() {
+                    if !kinds.contains(kind) {
+                        kinds.push(kind.clone());
+                    }
+                }
+                Some(kinds)
+            },
+        }
+    }
+}
+
+/// `filters` narrowed to the kinds `policy` allows for `access`
+pub(crate) fn restrict(
+    policy: Option<&Arc<dyn AccessPolicy>>,
+    access: Access,
+    mut filters: SearchFilters,
+) -> SearchFilters {
+    let Some(allowed) = policy.and_then(|policy| policy.kinds(access)) else {
+        return filters;
+    };
+    filters.kinds = Some(match filters.kinds.take() {
+        Some(kinds) => kinds.into_iter().filter(|k| allowed.contains(k)).collect(),
+        None => allowed,
+    });
+    filters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_kinds_are_readable() {
+        let policy = KindAccess::default()
+            .with_read(vec!["invariant".to_string()])
+            .with_write(vec!["evaluation".to_string()]);
+
+        assert!(policy.allows(Access::Read, "invariant"));
+        assert!(policy.allows(Access::Read, "evaluation"));
+        assert!(!policy.allows(Access::Read, "boundary"));
+        assert!(policy.allows(Access::Write, "evaluation"));
+        assert!(!policy.allows(Access::Write, "invariant"));
+        assert_eq!(
+            policy.kinds(Access::Read),
+            Some(vec!["invariant".to_string(), "evaluation".to_string()])
+        );
+        assert!(matches!(
+            policy.check(Access::Write, "invariant"),
+            Err(ServiceError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn unset_lists_allow_every_kind() {
+        let policy = KindAccess::default().with_write(vec![]);
+        assert!(policy.allows(Access::Read, "invariant"));
+        assert!(!policy.allows(Access::Write, "invariant"));
+        assert_eq!(policy.kinds(Access::Read), None);
+        assert!(KindAccess::default().is_unrestricted());
+    }
+
+    #[test]
+    fn restrict_intersects_requested_kinds() {
+        let policy: Arc<dyn AccessPolicy> = Arc::new(
+            KindAccess::default().with_read(vec!["invariant".to_string(), "boundary".to_string()]),
+        );
+        let filters = SearchFilters {
+            kinds: Some(vec!["boundary".to_string(), "contract".to_string()]),
+            ..Default::default()
+        };
+
+        let restricted = restrict(Some(&policy), Access::Read, filters);
+        assert_eq!(restricted.kinds, Some(vec!["boundary".to_string()]));
+        let unrestricted = restrict(None, Access::Read, SearchFilters::default());
+        assert_eq!(unrestricted.kinds, None);
+    }
+}
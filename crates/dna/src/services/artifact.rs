@@ -1,24 +1,263 @@
+use super::access::{self, Access, AccessPolicy};
+#[cfg(not(target_arch = "wasm32"))]
+use super::attachment::{self, ATTACHMENTS_LABEL};
+use super::changes::{self, ChangeHistory};
+use super::dedupe::{self, DuplicateGroup, MERGED_INTO_LABEL};
+use super::deprecation::Deprecation;
+use super::embed_text;
+use super::language::LANGUAGE_LABEL;
+use super::limits::InputRejected;
+use super::monitor::{Monitor, MONITOR_KIND};
+use super::owners::Assignment;
+use super::scan::{ContentBlocked, ContentScanner};
+use super::signing::Signer;
 use super::types::{
-    estimate_tokens, get_model_info, slugify_kind, Artifact, ContentFormat, ReindexTarget,
-    SearchFilters,
+    estimate_tokens, get_model_info, slugify_kind, template_placeholders, Artifact, ArtifactUpdate,
+    ChangePreview, ContentFormat, EmbeddingHealth, EmbeddingIssue, IdConfig, IdGenerator,
+    LanguagesConfig, LimitsConfig, OwnersConfig, ProjectConfig, ReindexTarget, SearchFilters,
+    UpsertAction, Upserted,
 };
 use super::ServiceError;
-use crate::db::Database;
+use crate::db::{model_slug, AmbiguousPrefix, Database};
 use crate::embedding::EmbeddingProvider;
 use anyhow::{Context, Result};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
 use std::sync::Arc;
 
+/// Artifacts embedded per provider call when reindexing
+const REINDEX_BATCH_SIZE: usize = 64;
+
+/// Label holding the caller's stable key for an artifact written by
+/// [`ArtifactService::upsert`]
+pub const EXTERNAL_ID_LABEL: &str = "external-id";
+
+/// An update applied in memory and validated, ready to embed and write
+struct PreparedUpdate {
+    artifact: Artifact,
+    changed_fields: Vec<String>,
+    reembed_content: bool,
+    reembed_context: bool,
+}
+
 /// Service for artifact CRUD operations
+#[derive(Clone)]
 pub struct ArtifactService {
     db: Arc<dyn Database>,
     embedding: Arc<dyn EmbeddingProvider>,
+    /// Models whose content embeddings are stored alongside `embedding`'s
+    extra_models: Vec<Arc<dyn EmbeddingProvider>>,
+    id_generator: Arc<dyn IdGenerator>,
+    /// Content fields stored as columns, keyed by kind slug
+    structured_fields: HashMap<String, Vec<String>>,
+    /// Content templates, keyed by kind slug
+    templates: HashMap<String, String>,
+    /// Templates composing the embedded text, keyed by kind slug; other
+    /// kinds embed their content alone
+    embed_templates: HashMap<String, String>,
+    scanner: Arc<ContentScanner>,
+    limits: LimitsConfig,
+    languages: LanguagesConfig,
+    /// People and teams that can own and review artifacts
+    owners: OwnersConfig,
+    /// Namespace given to added artifacts that have none
+    namespace: Option<String>,
+    /// Kinds the caller may read and write; every kind when unset
+    access: Option<Arc<dyn AccessPolicy>>,
+    /// Signs what is written; nothing is signed when unset
+    signer: Option<Signer>,
 }
 
 impl ArtifactService {
     /// Create a new artifact service
     pub fn new(db: Arc<dyn Database>, embedding: Arc<dyn EmbeddingProvider>) -> Self {
-        Self { db, embedding }
+        Self {
+            db,
+            embedding,
+            extra_models: Vec::new(),
+            id_generator: Arc::new(IdConfig::default()),
+            structured_fields: HashMap::new(),
+            templates: HashMap::new(),
+            embed_templates: HashMap::new(),
+            scanner: Arc::new(ContentScanner::default()),
+            limits: LimitsConfig::default(),
+            languages: LanguagesConfig::default(),
+            owners: OwnersConfig::default(),
+            namespace: None,
+            access: None,
+            signer: None,
+        }
+    }
+
+    /// Create an artifact service configured by a project's `config`, with
+    /// signing keys resolved against `project_root`.
+    ///
+    /// Extra models, namespace and access policy depend on the caller, so
+    /// they are left for the `with_*` methods.
+    pub fn from_config(
+        db: Arc<dyn Database>,
+        embedding: Arc<dyn EmbeddingProvider>,
+        config: &ProjectConfig,
+        project_root: &Path,
+    ) -> Result<Self> {
+        Ok(Self::new(db, embedding)
+            .with_id_generator(Arc::new(config.ids.clone()))
+            .with_structured_fields(config.kinds.structured_fields())
+            .with_templates(config.kinds.templates())
+            .with_embed_templates(config.kinds.embed_templates())
+            .with_scanner(ContentScanner::from_config(&config.scan)?)
+            .with_limits(config.limits.clone())
+            .with_languages(config.languages.clone())
+            .with_owners(config.owners.clone())
+            .with_signer(Signer::from_config(&config.signing, project_root)?))
+    }
+
+    /// Also store content embeddings from these models, so search can use
+    /// them and a switch to one of them needs no reindex.
+    ///
+    /// The configured model is left out if listed.
+    pub fn with_extra_models(mut self, models: Vec<Arc<dyn EmbeddingProvider>>) -> Self {
+        let primary = self.embedding.model_id().to_string();
+        self.extra_models = models
+            .into_iter()
+            .filter(|model| model.model_id() != primary)
+            .collect();
+        self
+    }
+
+    /// Use a custom ID generator for newly added artifacts
+    pub fn with_id_generator(mut self, id_generator: Arc<dyn IdGenerator>) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
+    /// Parse the given top-level content fields, per kind, into queryable columns
+    pub fn with_structured_fields(mut self, fields: HashMap<String, Vec<String>>) -> Self {
+        self.structured_fields = fields;
+        self
+    }
+
+    /// Reject content that still holds placeholders from its kind's template
+    pub fn with_templates(mut self, templates: HashMap<String, String>) -> Self {
+        self.templates = templates;
+        self
+    }
+
+    /// Embed the text these templates compose, per kind, rather than the
+    /// content alone; see [`embed_text`]
+    pub fn with_embed_templates(mut self, templates: HashMap<String, String>) -> Self {
+        self.embed_templates = templates;
+        self
+    }
+
+    /// Scan content and context for secrets and PII before they are stored
+    pub fn with_scanner(mut self, scanner: ContentScanner) -> Self {
+        self.scanner = Arc::new(scanner);
+        self
+    }
+
+    /// Enforce size and character limits on content, context, name and metadata
+    pub fn with_limits(mut self, limits: LimitsConfig) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Label artifacts with the language of their content and reject
+    /// languages the model can't embed, as configured
+    pub fn with_languages(mut self, languages: LanguagesConfig) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Only let the people and teams in `owners` own and review artifacts
+    pub fn with_owners(mut self, owners: OwnersConfig) -> Self {
+        self.owners = owners;
+        self
+    }
+
+    /// Put added artifacts that have no namespace of their own in `namespace`
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// Limit reads and writes to the kinds `policy` allows
+    pub fn with_access_policy(mut self, policy: Arc<dyn AccessPolicy>) -> Self {
+        self.access = Some(policy);
+        self
+    }
+
+    /// Sign the ID, kind, content and context of every artifact written
+    pub fn with_signer(mut self, signer: Option<Signer>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// `Forbidden` unless the access policy allows `access` to `kind`
+    fn check_access(&self, access: Access, kind: &str) -> Result<(), ServiceError> {
+        match &self.access {
+            Some(policy) => policy.check(access, kind),
+            None => Ok(()),
+        }
+    }
+
+    /// Current ID of the artifact `id` names, following former IDs, once it
+    /// is known to be writable; `id` itself when no active artifact has it
+    async fn writable_id(&self, id: &str) -> Result<String, ServiceError> {
+        match self.get(id).await? {
+            Some(artifact) => {
+                self.check_access(Access::Write, &artifact.kind)?;
+                Ok(artifact.id)
+            },
+            None => Ok(id.to_string()),
+        }
+    }
+
+    /// `filters` narrowed to the kinds the access policy allows for `access`
+    fn restrict(&self, access: Access, filters: SearchFilters) -> SearchFilters {
+        access::restrict(self.access.as_ref(), access, filters)
+    }
+
+    /// Run the scanner over text bound for an artifact of `kind`
+    fn scan(&self, kind: &str, text: String) -> Result<String, ServiceError> {
+        self.scanner
+            .apply(kind, text)
+            .map_err(|e| match e.downcast_ref::<ContentBlocked>() {
+                Some(blocked) => ServiceError::Validation(blocked.to_string()),
+                None => ServiceError::Internal(e),
+            })
+    }
+
+    /// Fail if the content still has placeholders from its kind's template
+    fn check_placeholders(&self, artifact: &Artifact) -> Result<(), InputRejected> {
+        let Some(template) = self.templates.get(&artifact.kind) else {
+            return Ok(());
+        };
+        let expected = template_placeholders(template);
+        let unfilled: Vec<String> = template_placeholders(&artifact.content)
+            .into_iter()
+            .filter(|name| expected.contains(name))
+            .collect();
+        if unfilled.is_empty() {
+            return Ok(());
+        }
+        Err(InputRejected(format!(
+            "Content still has template placeholders to fill in: {}",
+            unfilled
+                .iter()
+                .map(|name| format!("{{{{{}}}}}", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )))
+    }
+
+    /// Recompute an artifact's structured fields from its content and kind
+    fn refresh_fields(&self, artifact: &mut Artifact) {
+        artifact.fields = match self.structured_fields.get(&artifact.kind) {
+            Some(names) => artifact.extract_fields(names),
+            None => HashMap::new(),
+        };
     }
 
     /// Add a new artifact
@@ -31,9 +270,353 @@ impl ArtifactService {
         metadata: HashMap<String, String>,
         context: Option<String>,
     ) -> Result<Artifact> {
+        // Create artifact with embedding model info
+        let mut artifact = Artifact::new(
+            slugify_kind(&kind),
+            content,
+            format,
+            name,
+            metadata,
+            self.embedding.model_id().to_string(),
+        );
+        artifact.id = Artifact::generate_id_with(self.id_generator.as_ref(), &artifact.kind);
+        artifact.context = context;
+
+        self.embed_and_insert(artifact).await
+    }
+
+    /// Add a prepared artifact, keeping its ID.
+    ///
+    /// Used when the ID is decided outside the store (sync from files, plans).
+    /// An empty ID is replaced by a generated one; an ID that already exists
+    /// is an error. Embeddings are always regenerated.
+    pub async fn import(&self, mut artifact: Artifact) -> Result<Artifact> {
+        artifact.kind = slugify_kind(&artifact.kind);
+        if artifact.id.is_empty() {
+            artifact.id = Artifact::generate_id_with(self.id_generator.as_ref(), &artifact.kind);
+        } else if self.get(&artifact.id).await?.is_some() {
+            return Err(ServiceError::Conflict(format!(
+                "Artifact '{}' already exists",
+                artifact.id
+            ))
+            .into());
+        }
+        artifact.embedding_model = self.embedding.model_id().to_string();
+
+        self.embed_and_insert(artifact).await
+    }
+
+    /// Add `artifact` under the caller's stable key `external_id`, or update
+    /// the artifact already stored under it.
+    ///
+    /// The key is kept in the [`EXTERNAL_ID_LABEL`] label, so writing the
+    /// same record again changes what differs instead of adding a duplicate,
+    /// and writes nothing when it already matches. Updates change the kind,
+    /// content, name, context and labels; labels not in `artifact` are kept.
+    /// An archived artifact under the key is a conflict rather than being
+    /// added again.
+    pub async fn upsert(&self, external_id: &str, mut artifact: Artifact) -> Result<Upserted> {
+        if external_id.trim().is_empty() {
+            return Err(
+                ServiceError::Validation("External ID must not be empty".to_string()).into(),
+            );
+        }
+        artifact
+            .metadata
+            .insert(EXTERNAL_ID_LABEL.to_string(), external_id.to_string());
+
+        let Some(current) = self.find_by_external_id(external_id).await? else {
+            artifact.id = String::new();
+            let artifact = self.import(artifact).await?;
+            return Ok(Upserted {
+                action: UpsertAction::Created,
+                artifact,
+                changed_fields: Vec::new(),
+            });
+        };
+        if self.db.get(&current.id).await?.is_none() {
+            return Err(ServiceError::Conflict(format!(
+                "Artifact '{}' with external ID '{}' is archived",
+                current.id, external_id
+            ))
+            .into());
+        }
+
+        let prepared = self
+            .prepare_update(
+                &current.id,
+                Some(artifact.content),
+                artifact.name,
+                Some(artifact.kind),
+                Some(artifact.metadata),
+                artifact.context,
+            )
+            .await?;
+        if prepared.changed_fields.is_empty() {
+            return Ok(Upserted {
+                action: UpsertAction::Unchanged,
+                artifact: current,
+                changed_fields: Vec::new(),
+            });
+        }
+        let changed_fields = prepared.changed_fields.clone();
+        let artifact = self.write_update(prepared).await?;
+        Ok(Upserted {
+            action: UpsertAction::Updated,
+            artifact,
+            changed_fields,
+        })
+    }
+
+    /// The artifact stored under `external_id` by [`upsert`](Self::upsert),
+    /// archived or not.
+    ///
+    /// Should the key somehow be on several, the oldest is returned.
+    pub async fn find_by_external_id(&self, external_id: &str) -> Result<Option<Artifact>> {
+        let filters = SearchFilters {
+            metadata: HashMap::from([(EXTERNAL_ID_LABEL.to_string(), external_id.to_string())]),
+            include_archived: true,
+            ..Default::default()
+        };
+        Ok(self
+            .list(filters)
+            .await?
+            .into_iter()
+            .filter(|a| a.metadata.get(EXTERNAL_ID_LABEL).map(String::as_str) == Some(external_id))
+            .min_by_key(|a| a.created_at))
+    }
+
+    /// Add many artifacts, embedding them with one provider call per batch.
+    ///
+    /// Every artifact is checked and inserted on its own, so a bad one fails
+    /// without stopping the rest; results line up with the input. Artifacts
+    /// without an ID get a generated one.
+    pub async fn add_batch(&self, artifacts: Vec<Artifact>) -> Vec<Result<Artifact>> {
+        let mut results: Vec<Option<Result<Artifact>>> = Vec::with_capacity(artifacts.len());
+        let mut ready = Vec::new();
+        for (index, mut artifact) in artifacts.into_iter().enumerate() {
+            artifact.kind = slugify_kind(&artifact.kind);
+            if artifact.id.is_empty() {
+                artifact.id =
+                    Artifact::generate_id_with(self.id_generator.as_ref(), &artifact.kind);
+            }
+            artifact.embedding_model = self.embedding.model_id().to_string();
+            match self.prepare_insert(artifact) {
+                Ok(artifact) => {
+                    results.push(None);
+                    ready.push((index, artifact));
+                },
+                Err(e) => results.push(Some(Err(e))),
+            }
+        }
+
+        let contents: Vec<String> = ready
+            .iter()
+            .map(|(_, a)| self.embedding_text(a).into_owned())
+            .collect();
+        let contents: Vec<&str> = contents.iter().map(String::as_str).collect();
+        match self.embed_all(&contents).await {
+            Ok(embeddings) => {
+                for ((_, artifact), embedding) in ready.iter_mut().zip(embeddings) {
+                    artifact.embedding = Some(embedding);
+                    self.record_composition(artifact);
+                }
+            },
+            Err(e) => {
+                let message = format!("Failed to generate embedding: {:#}", e);
+                for (index, _) in ready.drain(..) {
+                    results[index] = Some(Err(anyhow::anyhow!("{}", message)));
+                }
+            },
+        }
+
+        let contexts: Vec<&str> = ready
+            .iter()
+            .filter_map(|(_, a)| a.context.as_deref())
+            .collect();
+        match self.embed_all(&contexts).await {
+            Ok(embeddings) => {
+                let with_context = ready.iter_mut().filter(|(_, a)| a.context.is_some());
+                for ((_, artifact), embedding) in with_context.zip(embeddings) {
+                    artifact.context_embedding = Some(embedding);
+                }
+            },
+            Err(e) => {
+                let message = format!("Failed to generate context embedding: {:#}", e);
+                let (failed, rest): (Vec<_>, Vec<_>) =
+                    ready.into_iter().partition(|(_, a)| a.context.is_some());
+                ready = rest;
+                for (index, _) in failed {
+                    results[index] = Some(Err(anyhow::anyhow!("{}", message)));
+                }
+            },
+        }
+
+        for (index, mut artifact) in ready {
+            self.embed_extra(std::slice::from_mut(&mut artifact)).await;
+            self.refresh_fields(&mut artifact);
+            let inserted = self
+                .db
+                .insert(&artifact)
+                .await
+                .context("Failed to insert artifact")
+                .map(|_| artifact);
+            results[index] = Some(inserted);
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.expect("every artifact is either rejected or inserted"))
+            .collect()
+    }
+
+    /// Embed texts in one batch, skipping the provider call when there are none
+    async fn embed_all(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        let embeddings = self.embedding.embed_batch(texts).await?;
+        if embeddings.len() != texts.len() {
+            anyhow::bail!(
+                "Embedding provider returned {} embeddings for {} texts",
+                embeddings.len(),
+                texts.len()
+            );
+        }
+        Ok(embeddings)
+    }
+
+    /// Embed content with each extra model the artifacts have no embedding from.
+    ///
+    /// A model that fails is logged and skipped rather than failing the
+    /// write; `reindex_model` fills the gaps in later.
+    async fn embed_extra(&self, artifacts: &mut [Artifact]) {
+        for model in &self.extra_models {
+            let slug = model_slug(model.model_id());
+            let missing: Vec<usize> = (0..artifacts.len())
+                .filter(|&i| !artifacts[i].model_embeddings.contains_key(&slug))
+                .collect();
+            if missing.is_empty() {
+                continue;
+            }
+            let texts: Vec<Cow<str>> = missing
+                .iter()
+                .map(|&i| self.embedding_text(&artifacts[i]))
+                .collect();
+            let texts: Vec<&str> = texts.iter().map(AsRef::as_ref).collect();
+            match model.embed_batch(&texts).await {
+                Ok(embeddings) if embeddings.len() == texts.len() => {
+                    for (i, embedding) in missing.into_iter().zip(embeddings) {
+                        artifacts[i]
+                            .model_embeddings
+                            .insert(slug.clone(), embedding);
+                    }
+                },
+                Ok(embeddings) => tracing::warn!(
+                    "Model {} returned {} embeddings for {} texts",
+                    model.model_id(),
+                    embeddings.len(),
+                    texts.len()
+                ),
+                Err(e) => tracing::warn!("Failed to embed with {}: {:#}", model.model_id(), e),
+            }
+        }
+    }
+
+    /// Work out what [`add`](Self::add) would store, without embedding or writing
+    pub fn preview_add(
+        &self,
+        kind: String,
+        content: String,
+        format: ContentFormat,
+        name: Option<String>,
+        metadata: HashMap<String, String>,
+        context: Option<String>,
+    ) -> Result<ChangePreview> {
+        let mut artifact = Artifact::new(
+            slugify_kind(&kind),
+            content,
+            format,
+            name,
+            metadata,
+            self.embedding.model_id().to_string(),
+        );
+        artifact.id = Artifact::generate_id_with(self.id_generator.as_ref(), &artifact.kind);
+        artifact.context = context;
+
+        let mut artifact = self.prepare_insert(artifact)?;
+        self.refresh_fields(&mut artifact);
+
+        let mut changed_fields = vec!["kind".to_string(), "content".to_string()];
+        if artifact.name.is_some() {
+            changed_fields.push("name".to_string());
+        }
+        if !artifact.metadata.is_empty() {
+            changed_fields.push("labels".to_string());
+        }
+        if artifact.context.is_some() {
+            changed_fields.push("context".to_string());
+        }
+        let embedding_calls = 1 + usize::from(artifact.context.is_some()) + self.extra_models.len();
+        Ok(self.preview(artifact, changed_fields, embedding_calls))
+    }
+
+    fn preview(
+        &self,
+        artifact: Artifact,
+        changed_fields: Vec<String>,
+        embedding_calls: usize,
+    ) -> ChangePreview {
+        ChangePreview {
+            content_tokens: estimate_tokens(&artifact.content),
+            context_tokens: artifact.context.as_deref().map(estimate_tokens),
+            max_tokens: get_model_info(self.embedding.model_id()).max_tokens,
+            embedding_calls,
+            changed_fields,
+            artifact,
+        }
+    }
+
+    /// Text embedded for the content of `artifact`, as its kind composes it
+    fn embedding_text<'a>(&self, artifact: &'a Artifact) -> Cow<'a, str> {
+        match self.embed_templates.get(&artifact.kind) {
+            Some(template) => Cow::Owned(embed_text::compose(template, artifact)),
+            None => Cow::Borrowed(&artifact.content),
+        }
+    }
+
+    /// Record how the content embedding of `artifact` was composed
+    fn record_composition(&self, artifact: &mut Artifact) {
+        artifact.embedding_text = self.embed_templates.get(&artifact.kind).cloned();
+    }
+
+    /// Check limits, scan, and check token counts of an artifact about to be inserted
+    fn prepare_insert(&self, mut artifact: Artifact) -> Result<Artifact> {
+        self.check_access(Access::Write, &artifact.kind)?;
+        if artifact.namespace.is_none() {
+            artifact.namespace = self.namespace.clone();
+        }
+        if let Some(namespace) = &artifact.namespace {
+            validate_namespace(namespace)?;
+        }
+        self.limits.check_artifact(&artifact)?;
+        self.owners.check_artifact(&mut artifact)?;
+        self.check_placeholders(&artifact)?;
+        artifact.format.check(&artifact.content)?;
+        self.languages.label(&mut artifact, false);
+        self.languages.check(&artifact, self.embedding.model_id())?;
+
+        artifact.content = self.scanner.apply(&artifact.kind, artifact.content)?;
+        if let Some(ctx) = artifact.context.take() {
+            artifact.context = Some(self.scanner.apply(&artifact.kind, ctx)?);
+        }
+        if let Some(signer) = &self.signer {
+            signer.sign(&mut artifact);
+        }
+
         // Validate content and context length against model limits
         let model_info = get_model_info(self.embedding.model_id());
-        let content_tokens = estimate_tokens(&content);
+        let content_tokens = estimate_tokens(&artifact.content);
         if content_tokens > model_info.max_tokens {
             return Err(anyhow::anyhow!(
                 "Content exceeds maximum of {} tokens (estimated {}). \
@@ -43,7 +626,7 @@ impl ArtifactService {
             ));
         }
 
-        if let Some(ctx) = &context {
+        if let Some(ctx) = &artifact.context {
             let context_tokens = estimate_tokens(ctx);
             if context_tokens > model_info.max_tokens {
                 return Err(anyhow::anyhow!(
@@ -55,27 +638,27 @@ impl ArtifactService {
             }
         }
 
-        // Create artifact with embedding model info
-        let mut artifact = Artifact::new(
-            slugify_kind(&kind),
-            content.clone(),
-            format,
-            name,
-            metadata,
-            self.embedding.model_id().to_string(),
-        );
+        Ok(artifact)
+    }
+
+    /// Check token limits, embed content and context, and insert
+    async fn embed_and_insert(&self, artifact: Artifact) -> Result<Artifact> {
+        let mut artifact = self.prepare_insert(artifact)?;
 
         // Generate content embedding
         let embedding = self
             .embedding
-            .embed(&content)
+            .embed(&self.embedding_text(&artifact))
             .await
             .context("Failed to generate embedding")?;
         artifact.embedding = Some(embedding);
+        self.record_composition(&mut artifact);
+        artifact.model_embeddings.clear();
+        self.embed_extra(std::slice::from_mut(&mut artifact)).await;
 
-        // Set context and generate context embedding if provided
-        artifact.context = context.clone();
-        if let Some(ctx) = &context {
+        // Generate context embedding if provided
+        artifact.context_embedding = None;
+        if let Some(ctx) = &artifact.context {
             let context_embedding = self
                 .embedding
                 .embed(ctx)
@@ -84,6 +667,8 @@ impl ArtifactService {
             artifact.context_embedding = Some(context_embedding);
         }
 
+        self.refresh_fields(&mut artifact);
+
         // Store in database
         self.db
             .insert(&artifact)
@@ -93,17 +678,200 @@ impl ArtifactService {
         Ok(artifact)
     }
 
-    /// Get artifact by ID
+    /// Get artifact by ID.
+    ///
+    /// An ID the artifact had before it was renamed or merged into another
+    /// still finds it; the artifact then carries its current ID and the one
+    /// asked for in `redirected_from`.
+    ///
+    /// An artifact of a kind the access policy does not let the caller read
+    /// is `Forbidden`.
     pub async fn get(&self, id: &str) -> Result<Option<Artifact>> {
-        self.db.get(id).await.context("Failed to get artifact")
+        let artifact = match self.db.get(id).await.context("Failed to get artifact")? {
+            Some(artifact) => Some(artifact),
+            None => self
+                .db
+                .find_by_alias(id)
+                .await
+                .context("Failed to look up artifact alias")?
+                .map(|mut artifact| {
+                    artifact.redirected_from = Some(id.to_string());
+                    artifact
+                }),
+        };
+        if let Some(artifact) = &artifact {
+            self.check_access(Access::Read, &artifact.kind)?;
+        }
+        Ok(artifact)
+    }
+
+    /// Get several artifacts by ID in one database round trip.
+    ///
+    /// Results line up with `ids`, with `None` for IDs that name no artifact.
+    /// Former IDs are followed as by [`get`](Self::get), one lookup each.
+    pub async fn get_many(&self, ids: &[String]) -> Result<Vec<Option<Artifact>>> {
+        let found: HashMap<String, Artifact> = self
+            .db
+            .get_many(ids)
+            .await
+            .context("Failed to get artifacts")?
+            .into_iter()
+            .map(|artifact| (artifact.id.clone(), artifact))
+            .collect();
+
+        let mut artifacts = Vec::with_capacity(ids.len());
+        for id in ids {
+            let artifact = match found.get(id) {
+                Some(artifact) => Some(artifact.clone()),
+                None => self
+                    .db
+                    .find_by_alias(id)
+                    .await
+                    .context("Failed to look up artifact alias")?
+                    .map(|mut artifact| {
+                        artifact.redirected_from = Some(id.clone());
+                        artifact
+                    }),
+            };
+            if let Some(artifact) = &artifact {
+                self.check_access(Access::Read, &artifact.kind)?;
+            }
+            artifacts.push(artifact);
+        }
+        Ok(artifacts)
+    }
+
+    /// Give an artifact a new ID.
+    ///
+    /// The old ID becomes an alias, so references to it keep resolving
+    /// through [`get`](Self::get).
+    pub async fn rename(&self, id: &str, new_id: &str) -> Result<Artifact, ServiceError> {
+        if new_id.trim().is_empty() {
+            return Err(ServiceError::Validation(
+                "New artifact ID cannot be empty".to_string(),
+            ));
+        }
+        let mut artifact = self
+            .get(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", id)))?;
+        self.check_access(Access::Write, &artifact.kind)?;
+        artifact.redirected_from = None;
+        let old_id = artifact.id.clone();
+        if new_id == old_id {
+            return Ok(artifact);
+        }
+        // Taking back one of its own former IDs is fine
+        if let Some(existing) = self.get(new_id).await? {
+            if existing.id != old_id {
+                return Err(ServiceError::Conflict(format!(
+                    "Artifact '{}' already exists",
+                    new_id
+                )));
+            }
+        }
+
+        artifact.aliases.retain(|alias| alias != new_id);
+        artifact.aliases.push(old_id.clone());
+        artifact.id = new_id.to_string();
+        artifact.updated_at = chrono::Utc::now();
+        // A signature covers the ID, so the old one no longer holds
+        if let Some(signer) = &self.signer {
+            signer.sign(&mut artifact);
+        }
+
+        // Insert first so a failure part-way never loses the artifact
+        self.db
+            .insert(&artifact)
+            .await
+            .context("Failed to insert renamed artifact")?;
+        self.db
+            .delete(&old_id)
+            .await
+            .context("Failed to remove artifact under its old ID")?;
+        Ok(artifact)
+    }
+
+    /// Resolve a full artifact ID from a unique prefix (git-style short IDs)
+    /// or, failing that, from a former ID.
+    ///
+    /// Returns `NotFound` when nothing matches and `Validation` listing the
+    /// candidates when the prefix is ambiguous.
+    pub async fn resolve_id(&self, id_or_prefix: &str) -> Result<String, ServiceError> {
+        match self.db.resolve_prefix(id_or_prefix).await {
+            Ok(Some(id)) => Ok(id),
+            Ok(None) => match self.db.find_by_alias(id_or_prefix).await {
+                Ok(Some(artifact)) => Ok(artifact.id),
+                Ok(None) => Err(ServiceError::NotFound(format!(
+                    "Artifact '{}' not found",
+                    id_or_prefix
+                ))),
+                Err(e) => Err(ServiceError::Internal(
+                    e.context("Failed to look up artifact alias"),
+                )),
+            },
+            Err(e) if e.is::<AmbiguousPrefix>() => Err(ServiceError::Validation(e.to_string())),
+            Err(e) => Err(ServiceError::Internal(
+                e.context("Failed to resolve artifact ID"),
+            )),
+        }
     }
 
     /// Get artifact by ID at a specific database version
     pub async fn get_at_version(&self, id: &str, version: u64) -> Result<Option<Artifact>> {
-        self.db
+        let artifact = self
+            .db
             .get_at_version(id, version)
             .await
-            .context("Failed to get artifact at version")
+            .context("Failed to get artifact at version")?;
+        if let Some(artifact) = &artifact {
+            self.check_access(Access::Read, &artifact.kind)?;
+        }
+        Ok(artifact)
+    }
+
+    /// Current database version
+    pub async fn version(&self) -> Result<u64> {
+        self.db
+            .version()
+            .await
+            .context("Failed to get database version")
+    }
+
+    /// Roll the database back to an earlier version.
+    ///
+    /// This rewrites every kind, so it is `Forbidden` under an access policy.
+    pub async fn restore(&self, version: u64) -> Result<()> {
+        if self.access.is_some() {
+            return Err(ServiceError::Forbidden(
+                "Restoring a version needs access to every kind".to_string(),
+            )
+            .into());
+        }
+        self.db
+            .restore(version)
+            .await
+            .with_context(|| format!("Failed to restore database version {}", version))
+    }
+
+    /// Write back an artifact exactly as it was read, embeddings included.
+    ///
+    /// Undoes a change to that one artifact, whether it has since been
+    /// updated or removed, without touching any other.
+    pub async fn put_back(&self, artifact: &Artifact) -> Result<()> {
+        self.check_access(Access::Write, &artifact.kind)?;
+        let exists = self
+            .db
+            .get(&artifact.id)
+            .await
+            .context("Failed to get artifact")?
+            .is_some();
+        if exists {
+            self.db.update(artifact).await
+        } else {
+            self.db.insert(artifact).await
+        }
+        .with_context(|| format!("Failed to put back artifact '{}'", artifact.id))
     }
 
     /// Update an existing artifact
@@ -116,13 +884,222 @@ impl ArtifactService {
         metadata: Option<HashMap<String, String>>,
         context: Option<String>,
     ) -> Result<Artifact, ServiceError> {
+        let prepared = self
+            .prepare_update(id, content, name, kind, metadata, context)
+            .await?;
+        self.write_update(prepared).await
+    }
+
+    /// Embed what a prepared update changed and write it
+    async fn write_update(&self, prepared: PreparedUpdate) -> Result<Artifact, ServiceError> {
+        let PreparedUpdate {
+            mut artifact,
+            reembed_content: needs_reembed,
+            reembed_context: needs_context_reembed,
+            ..
+        } = prepared;
+
+        // Re-embed content if changed
+        if needs_reembed {
+            let embedding = self
+                .embedding
+                .embed(&self.embedding_text(&artifact))
+                .await
+                .context("Failed to generate embedding")?;
+            artifact.embedding = Some(embedding);
+            artifact.embedding_model = self.embedding.model_id().to_string();
+            self.record_composition(&mut artifact);
+            artifact.model_embeddings.clear();
+            self.embed_extra(std::slice::from_mut(&mut artifact)).await;
+        }
+
+        // Re-embed context if changed
+        if needs_context_reembed {
+            if let Some(ctx) = &artifact.context {
+                let context_embedding = self
+                    .embedding
+                    .embed(ctx)
+                    .await
+                    .context("Failed to generate context embedding")?;
+                artifact.context_embedding = Some(context_embedding);
+            }
+        }
+
+        self.refresh_fields(&mut artifact);
+
+        // Update in database
+        self.db
+            .update(&artifact)
+            .await
+            .context("Failed to update artifact")?;
+
+        Ok(artifact)
+    }
+
+    /// Update many artifacts, embedding changed text with one provider call
+    /// per batch.
+    ///
+    /// As with [`add_batch`](Self::add_batch), every update is checked and
+    /// written on its own and results line up with the input. An ID given
+    /// more than once is only updated the first time.
+    pub async fn update_batch(
+        &self,
+        updates: Vec<ArtifactUpdate>,
+    ) -> Vec<Result<Artifact, ServiceError>> {
+        let mut results: Vec<Option<Result<Artifact, ServiceError>>> =
+            Vec::with_capacity(updates.len());
+        let mut ready = Vec::new();
+        let mut seen = HashSet::new();
+        for (index, update) in updates.into_iter().enumerate() {
+            if !seen.insert(update.id.clone()) {
+                results.push(Some(Err(ServiceError::Validation(format!(
+                    "Artifact '{}' is updated more than once in the batch",
+                    update.id
+                )))));
+                continue;
+            }
+            let prepared = self
+                .prepare_update(
+                    &update.id,
+                    update.content,
+                    update.name,
+                    update.kind,
+                    update.metadata,
+                    update.context,
+                )
+                .await;
+            match prepared {
+                Ok(prepared) => {
+                    results.push(None);
+                    ready.push((index, prepared));
+                },
+                Err(e) => results.push(Some(Err(e))),
+            }
+        }
+
+        let contents: Vec<String> = ready
+            .iter()
+            .filter(|(_, p)| p.reembed_content)
+            .map(|(_, p)| self.embedding_text(&p.artifact).into_owned())
+            .collect();
+        let contents: Vec<&str> = contents.iter().map(String::as_str).collect();
+        match self.embed_all(&contents).await {
+            Ok(embeddings) => {
+                let reembedded = ready.iter_mut().filter(|(_, p)| p.reembed_content);
+                for ((_, prepared), embedding) in reembedded.zip(embeddings) {
+                    let artifact = &mut prepared.artifact;
+                    artifact.embedding = Some(embedding);
+                    artifact.embedding_model = self.embedding.model_id().to_string();
+                    self.record_composition(artifact);
+                    artifact.model_embeddings.clear();
+                }
+            },
+            Err(e) => {
+                let err = ServiceError::from(e.context("Failed to generate embedding"));
+                let (failed, rest): (Vec<_>, Vec<_>) =
+                    ready.into_iter().partition(|(_, p)| p.reembed_content);
+                ready = rest;
+                for (index, _) in failed {
+                    results[index] = Some(Err(batch_failure(&err)));
+                }
+            },
+        }
+
+        let needs_context = |p: &PreparedUpdate| p.reembed_context && p.artifact.context.is_some();
+        let contexts: Vec<&str> = ready
+            .iter()
+            .filter(|(_, p)| needs_context(p))
+            .filter_map(|(_, p)| p.artifact.context.as_deref())
+            .collect();
+        match self.embed_all(&contexts).await {
+            Ok(embeddings) => {
+                let with_context = ready.iter_mut().filter(|(_, p)| needs_context(p));
+                for ((_, prepared), embedding) in with_context.zip(embeddings) {
+                    prepared.artifact.context_embedding = Some(embedding);
+                }
+            },
+            Err(e) => {
+                let err = ServiceError::from(e.context("Failed to generate context embedding"));
+                let (failed, rest): (Vec<_>, Vec<_>) =
+                    ready.into_iter().partition(|(_, p)| needs_context(p));
+                ready = rest;
+                for (index, _) in failed {
+                    results[index] = Some(Err(batch_failure(&err)));
+                }
+            },
+        }
+
+        let (reembedded, unchanged): (Vec<_>, Vec<_>) =
+            ready.into_iter().partition(|(_, p)| p.reembed_content);
+        let (indices, mut artifacts): (Vec<usize>, Vec<Artifact>) = reembedded
+            .into_iter()
+            .map(|(index, p)| (index, p.artifact))
+            .unzip();
+        self.embed_extra(&mut artifacts).await;
+        let written = indices
+            .into_iter()
+            .zip(artifacts)
+            .chain(unchanged.into_iter().map(|(index, p)| (index, p.artifact)));
+        for (index, mut artifact) in written {
+            self.refresh_fields(&mut artifact);
+            let updated = self
+                .db
+                .update(&artifact)
+                .await
+                .context("Failed to update artifact")
+                .map(|_| artifact)
+                .map_err(ServiceError::from);
+            results[index] = Some(updated);
+        }
+
+        results
+            .into_iter()
+            .map(|result| result.expect("every update is either rejected or written"))
+            .collect()
+    }
+
+    /// Work out what [`update`](Self::update) would store, without embedding or writing
+    pub async fn preview_update(
+        &self,
+        id: &str,
+        content: Option<String>,
+        name: Option<String>,
+        kind: Option<String>,
+        metadata: Option<HashMap<String, String>>,
+        context: Option<String>,
+    ) -> Result<ChangePreview, ServiceError> {
+        let prepared = self
+            .prepare_update(id, content, name, kind, metadata, context)
+            .await?;
+        let mut artifact = prepared.artifact;
+        self.refresh_fields(&mut artifact);
+
+        let embedding_calls = usize::from(prepared.reembed_content) * (1 + self.extra_models.len())
+            + usize::from(prepared.reembed_context && artifact.context.is_some());
+        Ok(self.preview(artifact, prepared.changed_fields, embedding_calls))
+    }
+
+    /// Apply requested changes to the stored artifact and validate the result
+    async fn prepare_update(
+        &self,
+        id: &str,
+        content: Option<String>,
+        name: Option<String>,
+        kind: Option<String>,
+        metadata: Option<HashMap<String, String>>,
+        context: Option<String>,
+    ) -> Result<PreparedUpdate, ServiceError> {
         // Get existing artifact
         let mut artifact = self
             .get(id)
             .await?
             .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", id)))?;
+        self.check_access(Access::Write, &artifact.kind)?;
+        artifact.redirected_from = None;
+        let embedded_text = self.embedding_text(&artifact).into_owned();
 
         // Update fields
+        let mut changed_fields = Vec::new();
         let mut needs_reembed = false;
         if let Some(new_content) = content {
             if new_content != artifact.content {
@@ -132,14 +1109,32 @@ impl ArtifactService {
         }
 
         if let Some(new_name) = name {
+            if artifact.name.as_ref() != Some(&new_name) {
+                changed_fields.push("name".to_string());
+            }
             artifact.name = Some(new_name);
         }
 
+        let mut kind_changed = false;
         if let Some(new_kind) = kind {
-            artifact.kind = slugify_kind(&new_kind);
+            let new_kind = slugify_kind(&new_kind);
+            self.check_access(Access::Write, &new_kind)?;
+            kind_changed = new_kind != artifact.kind;
+            artifact.kind = new_kind;
         }
 
+        let language_given = metadata
+            .as_ref()
+            .is_some_and(|m| m.contains_key(LANGUAGE_LABEL));
         if let Some(new_metadata) = metadata {
+            #[cfg(not(target_arch = "wasm32"))]
+            if let Some(manifest) = new_metadata
+                .get(ATTACHMENTS_LABEL)
+                .filter(|manifest| !manifest.is_empty())
+            {
+                attachment::check_manifest(manifest).map_err(ServiceError::Validation)?;
+            }
+            let before = artifact.metadata.clone();
             for (key, value) in new_metadata {
                 if value.is_empty() {
                     artifact.metadata.remove(&key);
@@ -147,6 +1142,9 @@ impl ArtifactService {
                     artifact.metadata.insert(key, value);
                 }
             }
+            if artifact.metadata != before {
+                changed_fields.push("labels".to_string());
+            }
         }
 
         // Update context and regenerate context embedding if changed
@@ -158,6 +1156,46 @@ impl ArtifactService {
             }
         }
 
+        self.limits
+            .check_artifact(&artifact)
+            .map_err(|e| ServiceError::Validation(e.to_string()))?;
+        if needs_reembed || kind_changed {
+            self.check_placeholders(&artifact)
+                .map_err(|e| ServiceError::Validation(e.to_string()))?;
+        }
+        if needs_reembed {
+            artifact
+                .format
+                .check(&artifact.content)
+                .map_err(|e| ServiceError::Validation(e.to_string()))?;
+        }
+
+        // Scan whatever changed, under the artifact's (possibly new) kind
+        if needs_reembed || kind_changed {
+            let scanned = self.scan(&artifact.kind, artifact.content.clone())?;
+            if scanned != artifact.content {
+                artifact.content = scanned;
+                needs_reembed = true;
+            }
+        }
+        if needs_context_reembed || kind_changed {
+            if let Some(ctx) = artifact.context.clone() {
+                let scanned = self.scan(&artifact.kind, ctx)?;
+                if artifact.context.as_ref() != Some(&scanned) {
+                    artifact.context = Some(scanned);
+                    needs_context_reembed = true;
+                }
+            }
+        }
+
+        if needs_reembed || language_given {
+            self.languages
+                .label(&mut artifact, needs_reembed && !language_given);
+            self.languages
+                .check(&artifact, self.embedding.model_id())
+                .map_err(|e| ServiceError::Validation(e.to_string()))?;
+        }
+
         artifact.updated_at = chrono::Utc::now();
 
         // Validate content and context length against model limits before re-embedding
@@ -186,52 +1224,345 @@ impl ArtifactService {
             }
         }
 
-        // Re-embed content if changed
+        // A name, label or kind change can alter the composed text too
+        let reembed_content = needs_reembed
+            || self.embedding_text(&artifact) != embedded_text
+            || artifact.embedding_text.as_ref() != self.embed_templates.get(&artifact.kind);
+
         if needs_reembed {
-            let embedding = self
-                .embedding
-                .embed(&artifact.content)
-                .await
-                .context("Failed to generate embedding")?;
-            artifact.embedding = Some(embedding);
-            artifact.embedding_model = self.embedding.model_id().to_string();
+            changed_fields.insert(0, "content".to_string());
+        }
+        if kind_changed {
+            changed_fields.push("kind".to_string());
         }
-
-        // Re-embed context if changed
         if needs_context_reembed {
-            if let Some(ctx) = &artifact.context {
-                let context_embedding = self
-                    .embedding
-                    .embed(ctx)
+            changed_fields.push("context".to_string());
+        }
+        if let Some(signer) = &self.signer {
+            if needs_reembed || kind_changed || needs_context_reembed {
+                signer.sign(&mut artifact);
+            }
+        }
+
+        Ok(PreparedUpdate {
+            artifact,
+            changed_fields,
+            reembed_content,
+            reembed_context: needs_context_reembed,
+        })
+    }
+
+    /// Sign an artifact as it stands, e.g. one written before signing was set up
+    pub async fn sign(&self, id: &str) -> Result<Artifact, ServiceError> {
+        let Some(signer) = &self.signer else {
+            return Err(ServiceError::Validation(
+                "No signing key configured; set key_path or key_env in [signing]".to_string(),
+            ));
+        };
+        let mut artifact = self
+            .get(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", id)))?;
+        self.check_access(Access::Write, &artifact.kind)?;
+        artifact.redirected_from = None;
+        signer.sign(&mut artifact);
+        artifact.updated_at = chrono::Utc::now();
+        self.db
+            .update(&artifact)
+            .await
+            .context("Failed to store signature")?;
+        Ok(artifact)
+    }
+
+    /// Change who owns and reviews an artifact.
+    ///
+    /// Handles are checked against [`with_owners`](Self::with_owners). Nothing
+    /// is re-embedded, and an assignment that changes nothing writes nothing.
+    pub async fn assign(
+        &self,
+        id: &str,
+        assignment: &Assignment,
+    ) -> Result<Artifact, ServiceError> {
+        let mut artifact = self
+            .get(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", id)))?;
+        self.check_access(Access::Write, &artifact.kind)?;
+        artifact.redirected_from = None;
+        let before = (artifact.owner.clone(), artifact.reviewers.clone());
+        assignment.apply(&mut artifact);
+        self.owners
+            .check_artifact(&mut artifact)
+            .map_err(|e| ServiceError::Validation(e.to_string()))?;
+        if (&artifact.owner, &artifact.reviewers) == (&before.0, &before.1) {
+            return Ok(artifact);
+        }
+        artifact.updated_at = chrono::Utc::now();
+        self.db
+            .update(&artifact)
+            .await
+            .context("Failed to update owner and reviewers")?;
+        Ok(artifact)
+    }
+
+    /// Deprecate an artifact, naming the artifact that replaces it if any.
+    ///
+    /// The successor may be given by a former ID and is recorded by its
+    /// current one; it must exist, be another artifact and not be deprecated
+    /// itself. Deprecating again replaces the successor and reason.
+    pub async fn deprecate(
+        &self,
+        id: &str,
+        deprecation: &Deprecation,
+    ) -> Result<Artifact, ServiceError> {
+        let artifact = self
+            .get(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", id)))?;
+        let mut deprecation = deprecation.clone();
+        if let Some(successor_id) = &deprecation.replaced_by {
+            let successor = self.get(successor_id).await?.ok_or_else(|| {
+                ServiceError::NotFound(format!("Successor '{}' not found", successor_id))
+            })?;
+            if successor.id == artifact.id {
+                return Err(ServiceError::Validation(format!(
+                    "Artifact '{}' cannot replace itself",
+                    artifact.id
+                )));
+            }
+            if let Some(chained) = Deprecation::of(&successor) {
+                let hint = chained
+                    .replaced_by
+                    .map(|next| format!("; use its successor '{}'", next))
+                    .unwrap_or_default();
+                return Err(ServiceError::Validation(format!(
+                    "Successor '{}' is deprecated itself{}",
+                    successor.id, hint
+                )));
+            }
+            deprecation.replaced_by = Some(successor.id);
+        }
+        self.update(
+            &artifact.id,
+            None,
+            None,
+            None,
+            Some(deprecation.labels()),
+            None,
+        )
+        .await
+    }
+
+    /// Read the metric, comparator, threshold and window of a monitor.
+    ///
+    /// Fails with a validation error when the artifact is of another kind or
+    /// its content does not describe a monitor.
+    pub async fn parse_monitor(&self, id: &str) -> Result<Monitor, ServiceError> {
+        let artifact = self
+            .get(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", id)))?;
+        if artifact.kind != MONITOR_KIND {
+            return Err(ServiceError::Validation(format!(
+                "Artifact '{}' is not a {} (kind: {})",
+                artifact.id, MONITOR_KIND, artifact.kind
+            )));
+        }
+        Monitor::from_artifact(&artifact)
+            .map_err(|e| ServiceError::Validation(format!("Artifact '{}': {}", artifact.id, e.0)))
+    }
+
+    /// Store text extracted from an artifact's attachments and embed it as
+    /// an extra context vector, or clear it with `None`.
+    ///
+    /// Only the leading words that fit the model's token limit are embedded;
+    /// the full text is kept.
+    pub async fn set_attachment_text(
+        &self,
+        id: &str,
+        text: Option<String>,
+    ) -> Result<Artifact, ServiceError> {
+        let mut artifact = self
+            .get(id)
+            .await?
+            .ok_or_else(|| ServiceError::NotFound(format!("Artifact '{}' not found", id)))?;
+        self.check_access(Access::Write, &artifact.kind)?;
+        artifact.redirected_from = None;
+        artifact.attachment_text = text
+            .map(|text| self.scanner.apply(&artifact.kind, text))
+            .transpose()?;
+        artifact.attachment_embedding = match &artifact.attachment_text {
+            Some(text) => Some(
+                self.embedding
+                    .embed(&self.embeddable_attachment_text(text))
                     .await
-                    .context("Failed to generate context embedding")?;
-                artifact.context_embedding = Some(context_embedding);
+                    .context("Failed to generate attachment embedding")?,
+            ),
+            None => None,
+        };
+        artifact.updated_at = chrono::Utc::now();
+        self.db
+            .update(&artifact)
+            .await
+            .context("Failed to store attachment text")?;
+        Ok(artifact)
+    }
+
+    /// The leading words of attachment text that fit the model's token limit
+    fn embeddable_attachment_text(&self, text: &str) -> String {
+        let max_tokens = get_model_info(self.embedding.model_id()).max_tokens;
+        let max_words = (max_tokens as f64 * 0.75) as usize;
+        text.split_whitespace()
+            .take(max_words)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Remove an artifact, by its current or a former ID
+    pub async fn remove(&self, id: &str) -> Result<bool> {
+        let id = self.writable_id(id).await?;
+        self.db
+            .delete(&id)
+            .await
+            .context("Failed to delete artifact")
+    }
+
+    /// Move an artifact to the archive, removing it from default list and search results.
+    pub async fn archive(&self, id: &str) -> Result<bool> {
+        let id = self.writable_id(id).await?;
+        self.db
+            .archive(&id)
+            .await
+            .context("Failed to archive artifact")
+    }
+
+    /// Archive every active artifact matching `filters` that was last updated
+    /// before `before`.
+    ///
+    /// Returns the IDs of the archived artifacts.
+    pub async fn archive_before(
+        &self,
+        before: chrono::DateTime<chrono::Utc>,
+        filters: SearchFilters,
+    ) -> Result<Vec<String>> {
+        let filters = SearchFilters {
+            before: Some(before),
+            include_archived: false,
+            ..self.restrict(Access::Write, filters)
+        };
+
+        let mut archived = Vec::new();
+        for artifact in self.list(filters).await? {
+            if self.archive(&artifact.id).await? {
+                archived.push(artifact.id);
             }
         }
 
-        // Update in database
+        Ok(archived)
+    }
+
+    /// List artifacts with filters
+    pub async fn list(&self, filters: SearchFilters) -> Result<Vec<Artifact>> {
         self.db
-            .update(&artifact)
+            .list(self.restrict(Access::Read, filters))
             .await
-            .context("Failed to update artifact")?;
-
-        Ok(artifact)
+            .context("Failed to list artifacts")
     }
 
-    /// Remove an artifact
-    pub async fn remove(&self, id: &str) -> Result<bool> {
+    /// Give every artifact of kind `from` the kind `to`, archived ones
+    /// included where the store supports it, returning how many changed.
+    ///
+    /// IDs, content and embeddings are kept; used to rename a kind or merge
+    /// it into another.
+    pub async fn rename_kind(&self, from: &str, to: &str) -> Result<usize> {
+        let (from, to) = (slugify_kind(from), slugify_kind(to));
+        if from == to {
+            return Err(ServiceError::Validation(format!(
+                "Kind '{}' is already called that",
+                from
+            ))
+            .into());
+        }
+        self.check_access(Access::Write, &from)?;
+        self.check_access(Access::Write, &to)?;
         self.db
-            .delete(id)
+            .rename_kind(&from, &to)
             .await
-            .context("Failed to delete artifact")
+            .context("Failed to change artifact kinds")
     }
 
-    /// List artifacts with filters
-    pub async fn list(&self, filters: SearchFilters) -> Result<Vec<Artifact>> {
+    /// Created, updated and deleted artifacts, from the store's version history
+    pub async fn change_history(&self, filters: SearchFilters) -> Result<ChangeHistory> {
+        changes::history(&*self.db, &self.restrict(Access::Read, filters))
+            .await
+            .context("Failed to read change history")
+    }
+
+    /// Groups of same-kind artifacts matching `filters` whose embeddings are
+    /// at least `threshold` similar (cosine), most similar first
+    pub async fn find_duplicates(
+        &self,
+        filters: SearchFilters,
+        threshold: f32,
+    ) -> Result<Vec<DuplicateGroup>> {
+        dedupe::find(&*self.db, &self.restrict(Access::Read, filters), threshold).await
+    }
+
+    /// Merge `duplicates` into `target`.
+    ///
+    /// The target gains the labels it lacks from the duplicates, and their
+    /// IDs as aliases so [`get`](Self::get) still finds it by them. Each
+    /// duplicate is archived with a [`MERGED_INTO_LABEL`] label naming the
+    /// target.
+    pub async fn merge_into(
+        &self,
+        target: &str,
+        duplicates: &[String],
+    ) -> Result<Artifact, ServiceError> {
+        if duplicates.iter().any(|id| id == target) {
+            return Err(ServiceError::Validation(format!(
+                "Cannot merge artifact '{}' into itself",
+                target
+            )));
+        }
+        let not_found = |id: &str| ServiceError::NotFound(format!("Artifact '{}' not found", id));
+        let target_artifact = self.get(target).await?.ok_or_else(|| not_found(target))?;
+        let mut merged = Vec::with_capacity(duplicates.len());
+        for id in duplicates {
+            let duplicate = self.get(id).await?.ok_or_else(|| not_found(id))?;
+            self.check_access(Access::Write, &duplicate.kind)?;
+            merged.push(duplicate);
+        }
+
+        let labels = dedupe::merge_labels(&target_artifact, &merged);
+        let mut target_artifact = self
+            .update(target, None, None, None, Some(labels), None)
+            .await?;
+        for duplicate in &merged {
+            for alias in std::iter::once(&duplicate.id).chain(&duplicate.aliases) {
+                if !target_artifact.aliases.contains(alias) {
+                    target_artifact.aliases.push(alias.clone());
+                }
+            }
+        }
         self.db
-            .list(filters)
+            .update(&target_artifact)
             .await
-            .context("Failed to list artifacts")
+            .context("Failed to record aliases")?;
+        for mut duplicate in merged {
+            duplicate
+                .metadata
+                .insert(MERGED_INTO_LABEL.to_string(), target_artifact.id.clone());
+            self.db
+                .update(&duplicate)
+                .await
+                .context("Failed to label merged duplicate")?;
+            self.db
+                .archive(&duplicate.id)
+                .await
+                .context("Failed to archive merged duplicate")?;
+        }
+        Ok(target_artifact)
     }
 
     /// Reindex all artifacts with current embedding model
@@ -248,12 +1579,11 @@ impl ArtifactService {
         filters: SearchFilters,
         target: ReindexTarget,
     ) -> Result<usize> {
-        let artifacts = self.list(filters).await?;
+        let mut artifacts = self.list(self.restrict(Access::Write, filters)).await?;
         let total = artifacts.len();
 
-        for mut artifact in artifacts {
-            self.reindex_artifact_embeddings(&mut artifact, target)
-                .await?;
+        for chunk in artifacts.chunks_mut(REINDEX_BATCH_SIZE) {
+            self.reindex_artifact_embeddings(chunk, target).await?;
         }
 
         Ok(total)
@@ -266,49 +1596,311 @@ impl ArtifactService {
         let Some(mut artifact) = self.get(id).await? else {
             return Ok(None);
         };
+        self.check_access(Access::Write, &artifact.kind)?;
 
-        self.reindex_artifact_embeddings(&mut artifact, target)
+        self.reindex_artifact_embeddings(std::slice::from_mut(&mut artifact), target)
             .await?;
 
         Ok(Some(artifact))
     }
 
-    /// Regenerate embeddings for an artifact and persist changes.
+    /// Classify why an artifact's stored embeddings can't be used with the
+    /// current provider, if at all.
+    ///
+    /// Storage writes an all-zero vector when an artifact has no content
+    /// embedding, so a zero vector is reported as missing.
+    pub fn embedding_issue(&self, artifact: &Artifact) -> Option<EmbeddingIssue> {
+        let content_missing = match &artifact.embedding {
+            None => true,
+            Some(embedding) => embedding.iter().all(|v| *v == 0.0),
+        };
+        if content_missing
+            || (artifact.context.is_some() && artifact.context_embedding.is_none())
+            || (artifact.attachment_text.is_some() && artifact.attachment_embedding.is_none())
+        {
+            return Some(EmbeddingIssue::Missing);
+        }
+
+        if artifact.embedding_model != self.embedding.model_id() {
+            return Some(EmbeddingIssue::ModelMismatch);
+        }
+
+        let dimensions = self.embedding.dimensions();
+        let content_len = artifact.embedding.as_ref().map_or(0, Vec::len);
+        let context_len = artifact.context_embedding.as_ref().map(Vec::len);
+        let attachment_len = artifact.attachment_embedding.as_ref().map(Vec::len);
+        if content_len != dimensions
+            || context_len.is_some_and(|len| len != dimensions)
+            || attachment_len.is_some_and(|len| len != dimensions)
+        {
+            return Some(EmbeddingIssue::DimensionMismatch);
+        }
+
+        if artifact.embedding_text.as_ref() != self.embed_templates.get(&artifact.kind) {
+            return Some(EmbeddingIssue::CompositionChanged);
+        }
+
+        None
+    }
+
+    /// Scan artifacts matching filters for missing or incompatible embeddings.
+    pub async fn embedding_health(&self, filters: SearchFilters) -> Result<EmbeddingHealth> {
+        let artifacts = self.list(filters).await?;
+
+        let issues = artifacts
+            .iter()
+            .filter_map(|a| self.embedding_issue(a).map(|issue| (a.id.clone(), issue)))
+            .collect();
+
+        Ok(EmbeddingHealth {
+            scanned: artifacts.len(),
+            issues,
+        })
+    }
+
+    /// Reindex only the artifacts whose embeddings are missing or incompatible.
+    ///
+    /// Returns the health report describing what was reindexed.
+    pub async fn reindex_missing(&self, filters: SearchFilters) -> Result<EmbeddingHealth> {
+        let artifacts = self.list(self.restrict(Access::Write, filters)).await?;
+        let mut health = EmbeddingHealth {
+            scanned: artifacts.len(),
+            issues: Vec::new(),
+        };
+
+        let mut stale = Vec::new();
+        for artifact in artifacts {
+            if let Some(issue) = self.embedding_issue(&artifact) {
+                health.issues.push((artifact.id.clone(), issue));
+                stale.push(artifact);
+            }
+        }
+
+        for chunk in stale.chunks_mut(REINDEX_BATCH_SIZE) {
+            self.reindex_artifact_embeddings(chunk, ReindexTarget::Both)
+                .await?;
+        }
+
+        Ok(health)
+    }
+
+    /// Embed the artifacts matching `filters` that have no embedding from
+    /// `model`, one of the extra models, and persist them.
+    ///
+    /// Returns how many artifacts were embedded. Stops at the first batch the
+    /// model fails on, so a rerun picks up where it left off.
+    pub async fn reindex_model(&self, model: &str, filters: SearchFilters) -> Result<usize> {
+        let Some(provider) = self.extra_models.iter().find(|m| m.model_id() == model) else {
+            return Err(ServiceError::Validation(format!(
+                "Model '{}' is not one of the extra models (configured: {})",
+                model,
+                self.extra_model_ids().join(", ")
+            ))
+            .into());
+        };
+        let slug = model_slug(model);
+        let mut missing: Vec<Artifact> = self
+            .list(self.restrict(Access::Write, filters))
+            .await?
+            .into_iter()
+            .filter(|a| !a.model_embeddings.contains_key(&slug))
+            .collect();
+
+        for chunk in missing.chunks_mut(REINDEX_BATCH_SIZE) {
+            let texts: Vec<Cow<str>> = chunk.iter().map(|a| self.embedding_text(a)).collect();
+            let texts: Vec<&str> = texts.iter().map(AsRef::as_ref).collect();
+            let embeddings = provider
+                .embed_batch(&texts)
+                .await
+                .with_context(|| format!("Failed to generate embeddings with {}", model))?;
+            anyhow::ensure!(
+                embeddings.len() == texts.len(),
+                "Embedding provider returned {} embeddings for {} artifacts",
+                embeddings.len(),
+                texts.len()
+            );
+            for (artifact, embedding) in chunk.iter_mut().zip(embeddings) {
+                artifact.model_embeddings.insert(slug.clone(), embedding);
+                self.db
+                    .update(artifact)
+                    .await
+                    .context("Failed to update artifact during reindex")?;
+            }
+        }
+
+        Ok(missing.len())
+    }
+
+    /// Model IDs of the extra models, in configured order
+    pub fn extra_model_ids(&self) -> Vec<&str> {
+        self.extra_models.iter().map(|m| m.model_id()).collect()
+    }
+
+    /// Regenerate embeddings for a group of artifacts and persist changes.
+    ///
+    /// Texts are embedded with one `embed_batch` call per target, so providers
+    /// that batch natively see the whole group at once.
     async fn reindex_artifact_embeddings(
         &self,
-        artifact: &mut Artifact,
+        artifacts: &mut [Artifact],
         target: ReindexTarget,
     ) -> Result<()> {
         let reindex_content = matches!(target, ReindexTarget::Content | ReindexTarget::Both);
         let reindex_context = matches!(target, ReindexTarget::Context | ReindexTarget::Both);
 
         if reindex_content {
-            let embedding = self
+            let model_id = self.embedding.model_id();
+            let primary = model_slug(model_id);
+            let mut pending = Vec::new();
+            for (i, artifact) in artifacts.iter_mut().enumerate() {
+                // Embeddings composed the old way are of no use to promote or keep
+                if artifact.embedding_text.as_ref() != self.embed_templates.get(&artifact.kind) {
+                    artifact.model_embeddings.clear();
+                    pending.push(i);
+                    continue;
+                }
+                // Keep the outgoing model's embedding while it is still an extra model
+                if artifact.embedding_model != model_id
+                    && self
+                        .extra_models
+                        .iter()
+                        .any(|m| m.model_id() == artifact.embedding_model)
+                {
+                    if let Some(old) = artifact
+                        .embedding
+                        .take()
+                        .filter(|e| e.iter().any(|v| *v != 0.0))
+                    {
+                        artifact
+                            .model_embeddings
+                            .entry(model_slug(&artifact.embedding_model))
+                            .or_insert(old);
+                    }
+                }
+                // Promote an embedding stored while the model was an extra one
+                match artifact.model_embeddings.remove(&primary) {
+                    Some(embedding) => {
+                        artifact.embedding = Some(embedding);
+                        artifact.embedding_model = model_id.to_string();
+                    },
+                    None => pending.push(i),
+                }
+            }
+
+            let texts: Vec<String> = pending
+                .iter()
+                .map(|&i| self.embedding_text(&artifacts[i]).into_owned())
+                .collect();
+            let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+            if !texts.is_empty() {
+                let embeddings = self
+                    .embedding
+                    .embed_batch(&texts)
+                    .await
+                    .context("Failed to generate content embeddings during reindex")?;
+                anyhow::ensure!(
+                    embeddings.len() == texts.len(),
+                    "Embedding provider returned {} embeddings for {} artifacts",
+                    embeddings.len(),
+                    texts.len()
+                );
+                for (i, embedding) in pending.into_iter().zip(embeddings) {
+                    artifacts[i].embedding = Some(embedding);
+                    artifacts[i].embedding_model = model_id.to_string();
+                    self.record_composition(&mut artifacts[i]);
+                }
+            }
+            self.embed_extra(artifacts).await;
+        }
+
+        if reindex_context && artifacts.iter().any(|a| a.context.is_some()) {
+            let with_context: Vec<usize> = (0..artifacts.len())
+                .filter(|&i| artifacts[i].context.is_some())
+                .collect();
+            let texts: Vec<&str> = with_context
+                .iter()
+                .filter_map(|&i| artifacts[i].context.as_deref())
+                .collect();
+            let embeddings = self
                 .embedding
-                .embed(&artifact.content)
+                .embed_batch(&texts)
                 .await
-                .context("Failed to generate content embedding during reindex")?;
-            artifact.embedding = Some(embedding);
-            artifact.embedding_model = self.embedding.model_id().to_string();
+                .context("Failed to generate context embeddings during reindex")?;
+            anyhow::ensure!(
+                embeddings.len() == texts.len(),
+                "Embedding provider returned {} embeddings for {} contexts",
+                embeddings.len(),
+                texts.len()
+            );
+            for (i, embedding) in with_context.into_iter().zip(embeddings) {
+                artifacts[i].context_embedding = Some(embedding);
+            }
         }
 
-        if reindex_context {
-            if let Some(ctx) = &artifact.context {
-                let context_embedding = self
-                    .embedding
-                    .embed(ctx)
-                    .await
-                    .context("Failed to generate context embedding during reindex")?;
-                artifact.context_embedding = Some(context_embedding);
+        if reindex_context && artifacts.iter().any(|a| a.attachment_text.is_some()) {
+            let with_text: Vec<usize> = (0..artifacts.len())
+                .filter(|&i| artifacts[i].attachment_text.is_some())
+                .collect();
+            let texts: Vec<String> = with_text
+                .iter()
+                .filter_map(|&i| artifacts[i].attachment_text.as_deref())
+                .map(|text| self.embeddable_attachment_text(text))
+                .collect();
+            let texts: Vec<&str> = texts.iter().map(String::as_str).collect();
+            let embeddings = self
+                .embedding
+                .embed_batch(&texts)
+                .await
+                .context("Failed to generate attachment embeddings during reindex")?;
+            anyhow::ensure!(
+                embeddings.len() == texts.len(),
+                "Embedding provider returned {} embeddings for {} attachment texts",
+                embeddings.len(),
+                texts.len()
+            );
+            for (i, embedding) in with_text.into_iter().zip(embeddings) {
+                artifacts[i].attachment_embedding = Some(embedding);
             }
         }
 
-        self.db
-            .update(artifact)
-            .await
-            .context("Failed to update artifact during reindex")?;
+        for artifact in artifacts.iter_mut() {
+            // Backfills fields for kinds configured after the artifact was stored
+            self.refresh_fields(artifact);
+
+            self.db
+                .update(artifact)
+                .await
+                .context("Failed to update artifact during reindex")?;
+        }
+
+        Ok(())
+    }
+}
 
+/// Namespaces name render directories, so they are kept to one path segment
+fn validate_namespace(namespace: &str) -> Result<(), InputRejected> {
+    let valid = !namespace.is_empty()
+        && !namespace.starts_with('.')
+        && namespace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
         Ok(())
+    } else {
+        Err(InputRejected(format!(
+            "Invalid namespace '{}': use letters, digits, '-', '_' or '.'",
+            namespace
+        )))
+    }
+}
+
+/// One item's share of an embedding failure that failed a whole batch
+fn batch_failure(err: &ServiceError) -> ServiceError {
+    match err {
+        ServiceError::RateLimited(msg) => ServiceError::RateLimited(msg.clone()),
+        ServiceError::ProviderUnavailable(msg) => ServiceError::ProviderUnavailable(msg.clone()),
+        ServiceError::Internal(e) => ServiceError::Internal(anyhow::anyhow!("{:#}", e)),
+        other => ServiceError::Internal(anyhow::anyhow!("{}", other)),
     }
 }
 
@@ -443,43 +2035,325 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn add_generates_embedding_and_inserts() {
+    async fn add_generates_embedding_and_inserts() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1, 0.2, 0.3]));
+        let service = ArtifactService::new(db.clone(), embedding);
+
+        let artifact = service
+            .add(
+                "intent".to_string(),
+                "test content".to_string(),
+                ContentFormat::Markdown,
+                Some("test-name".to_string()),
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(artifact.kind, "intent");
+        assert_eq!(artifact.content, "test content");
+        assert_eq!(artifact.name, Some("test-name".to_string()));
+        assert_eq!(artifact.embedding, Some(vec![0.1, 0.2, 0.3]));
+
+        // Verify it was inserted
+        let stored = db.get(&artifact.id).await.unwrap();
+        assert!(stored.is_some());
+    }
+
+    #[tokio::test]
+    async fn add_slugifies_kind() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding);
+
+        let artifact = service
+            .add(
+                "My Custom Type".to_string(),
+                "content".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(artifact.kind, "my-custom-type");
+    }
+
+    #[tokio::test]
+    async fn added_artifacts_take_the_service_namespace() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding).with_namespace(Some("payments".into()));
+
+        let added = service
+            .add(
+                "intent".to_string(),
+                "content".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(added.namespace.as_deref(), Some("payments"));
+
+        let mut own = Artifact::new(
+            "intent".to_string(),
+            "other".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            String::new(),
+        );
+        own.namespace = Some("billing".to_string());
+        let imported = service.import(own.clone()).await.unwrap();
+        assert_eq!(imported.namespace.as_deref(), Some("billing"));
+
+        own.id = String::new();
+        own.namespace = Some("../escape".to_string());
+        let err = service.import(own).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid namespace"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn add_uses_configured_id_generator() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let ids = IdConfig {
+            strategy: crate::services::IdStrategy::KindPrefix,
+            ..Default::default()
+        };
+        let service = ArtifactService::new(db.clone(), embedding).with_id_generator(Arc::new(ids));
+
+        let artifact = service
+            .add(
+                "contract".to_string(),
+                "content".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(artifact.id.starts_with("con-"));
+        assert!(db.get(&artifact.id).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn structured_fields_follow_content_and_backfill_on_reindex() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let plain = ArtifactService::new(db.clone(), embedding.clone());
+
+        let artifact = plain
+            .add(
+                "incident".to_string(),
+                "severity: high\ntitle: outage\n".to_string(),
+                ContentFormat::Yaml,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(artifact.fields.is_empty());
+
+        let fields = HashMap::from([("incident".to_string(), vec!["severity".to_string()])]);
+        let service = ArtifactService::new(db.clone(), embedding).with_structured_fields(fields);
+
+        service.reindex().await.unwrap();
+        let stored = db.get(&artifact.id).await.unwrap().unwrap();
+        assert_eq!(
+            stored.fields.get("severity").map(String::as_str),
+            Some("high")
+        );
+
+        let updated = service
+            .update(
+                &artifact.id,
+                Some("severity: critical\n".to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            updated.fields.get("severity").map(String::as_str),
+            Some("critical")
+        );
+    }
+
+    #[tokio::test]
+    async fn scanner_masks_on_add_and_blocks_on_update() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let scanner = ContentScanner::from_config(&crate::services::ScanConfig {
+            action: crate::services::ScanAction::Mask,
+            kinds: HashMap::from([("contract".to_string(), crate::services::ScanAction::Block)]),
+            ..Default::default()
+        })
+        .unwrap();
+        let service = ArtifactService::new(db.clone(), embedding).with_scanner(scanner);
+
+        let artifact = service
+            .add(
+                "intent".to_string(),
+                "owner is ops@example.com".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(artifact.content, "owner is [REDACTED:email]");
+
+        let err = service
+            .update(
+                &artifact.id,
+                Some("owner is ops@example.com".to_string()),
+                None,
+                Some("contract".to_string()),
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Validation(_)));
+        let stored = db.get(&artifact.id).await.unwrap().unwrap();
+        assert_eq!(stored.kind, "intent");
+    }
+
+    #[tokio::test]
+    async fn limits_reject_input_on_add_and_update() {
         let db = Arc::new(TestDatabase::new());
-        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1, 0.2, 0.3]));
-        let service = ArtifactService::new(db.clone(), embedding);
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db.clone(), embedding).with_limits(LimitsConfig {
+            max_content_bytes: 16,
+            ..Default::default()
+        });
+
+        let err = service
+            .add(
+                "intent".to_string(),
+                "much longer than sixteen bytes".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.is::<crate::services::InputRejected>());
 
         let artifact = service
             .add(
                 "intent".to_string(),
-                "test content".to_string(),
+                "short".to_string(),
                 ContentFormat::Markdown,
-                Some("test-name".to_string()),
+                None,
                 HashMap::new(),
                 None,
             )
             .await
             .unwrap();
+        let err = service
+            .update(
+                &artifact.id,
+                None,
+                Some("bell\u{7}".to_string()),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Validation(_)));
+    }
 
-        assert_eq!(artifact.kind, "intent");
-        assert_eq!(artifact.content, "test content");
-        assert_eq!(artifact.name, Some("test-name".to_string()));
-        assert_eq!(artifact.embedding, Some(vec![0.1, 0.2, 0.3]));
+    #[tokio::test]
+    async fn structured_content_must_parse_on_add_and_update() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db.clone(), embedding);
 
-        // Verify it was inserted
-        let stored = db.get(&artifact.id).await.unwrap();
-        assert!(stored.is_some());
+        let err = service
+            .add(
+                "contract".to_string(),
+                "{\"amount\": }".to_string(),
+                ContentFormat::Json,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.is::<crate::services::InputRejected>());
+        assert!(err.to_string().contains("line 1 column 12"), "{}", err);
+
+        let spec = service
+            .add(
+                "contract".to_string(),
+                "openapi: 3.0.0\npaths: {}\n".to_string(),
+                ContentFormat::OpenApi,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        let err = service
+            .update(
+                &spec.id,
+                Some("openapi: 3.0.0\ninfo: {}\n".to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Validation(_)));
     }
 
     #[tokio::test]
-    async fn add_slugifies_kind() {
+    async fn unfilled_template_placeholders_are_rejected() {
         let db = Arc::new(TestDatabase::new());
         let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
-        let service = ArtifactService::new(db, embedding);
+        let service =
+            ArtifactService::new(db.clone(), embedding).with_templates(HashMap::from([(
+                "evaluation".to_string(),
+                "Given {{given}}\nWhen {{when}}\nThen {{then}}".to_string(),
+            )]));
+
+        let err = service
+            .add(
+                "evaluation".to_string(),
+                "Given a user\nWhen {{when}}\nThen {{then}}".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.is::<crate::services::InputRejected>());
+        assert!(err.to_string().contains("{{when}}, {{then}}"));
 
+        // Placeholders the template doesn't define are left alone
         let artifact = service
             .add(
-                "My Custom Type".to_string(),
-                "content".to_string(),
+                "evaluation".to_string(),
+                "Given a user\nWhen they log in\nThen render {{name}}".to_string(),
                 ContentFormat::Markdown,
                 None,
                 HashMap::new(),
@@ -488,7 +2362,294 @@ mod tests {
             .await
             .unwrap();
 
-        assert_eq!(artifact.kind, "my-custom-type");
+        let err = service
+            .update(
+                &artifact.id,
+                Some("Given {{given}}".to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Validation(_)));
+    }
+
+    #[tokio::test]
+    async fn import_keeps_id_and_embeds() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1, 0.2]));
+        let service = ArtifactService::new(db.clone(), embedding);
+
+        let mut artifact = artifact_with_embedding("stale-model", None);
+        artifact.id = "k7v3m9xnp2".to_string();
+        artifact.kind = "Intent".to_string();
+        artifact.context = Some("why".to_string());
+
+        let imported = service.import(artifact.clone()).await.unwrap();
+        assert_eq!(imported.id, "k7v3m9xnp2");
+        assert_eq!(imported.kind, "intent");
+        assert_eq!(imported.embedding_model, "test-model");
+        assert_eq!(imported.context_embedding, Some(vec![0.1, 0.2]));
+        assert!(db.get("k7v3m9xnp2").await.unwrap().is_some());
+
+        assert!(service.import(artifact).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn add_batch_inserts_valid_artifacts_and_reports_the_rest() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1, 0.2]));
+        let service = ArtifactService::new(db.clone(), embedding).with_limits(LimitsConfig {
+            max_content_bytes: 16,
+            ..Default::default()
+        });
+
+        let mut with_context = artifact_with_embedding("stale-model", None);
+        with_context.id = String::new();
+        with_context.kind = "Intent".to_string();
+        with_context.content = "short".to_string();
+        with_context.context = Some("why".to_string());
+        let mut too_long = with_context.clone();
+        too_long.content = "much longer than sixteen bytes".to_string();
+        let mut plain = with_context.clone();
+        plain.context = None;
+
+        let results = service.add_batch(vec![with_context, too_long, plain]).await;
+        assert_eq!(results.len(), 3);
+
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.kind, "intent");
+        assert_eq!(first.embedding_model, "test-model");
+        assert_eq!(first.context_embedding, Some(vec![0.1, 0.2]));
+        assert!(results[1].is_err());
+        let third = results[2].as_ref().unwrap();
+        assert_eq!(third.embedding, Some(vec![0.1, 0.2]));
+        assert_eq!(third.context_embedding, None);
+        assert_ne!(first.id, third.id);
+        assert_eq!(db.count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn update_batch_writes_each_update_and_reports_the_rest() {
+        let mut stored = artifact_with_embedding("old-model", Some(vec![0.9, 0.9]));
+        stored.id = "a1".to_string();
+        let mut renamed = stored.clone();
+        renamed.id = "a2".to_string();
+        let db = Arc::new(TestDatabase::new());
+        db.insert(&stored).await.unwrap();
+        db.insert(&renamed).await.unwrap();
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1, 0.2]));
+        let service = ArtifactService::new(db.clone(), embedding);
+
+        let results = service
+            .update_batch(vec![
+                ArtifactUpdate {
+                    id: "a1".to_string(),
+                    content: Some("new content".to_string()),
+                    context: Some("why".to_string()),
+                    ..Default::default()
+                },
+                ArtifactUpdate {
+                    id: "a2".to_string(),
+                    name: Some("Renamed".to_string()),
+                    ..Default::default()
+                },
+                ArtifactUpdate {
+                    id: "missing".to_string(),
+                    name: Some("Nope".to_string()),
+                    ..Default::default()
+                },
+                ArtifactUpdate {
+                    id: "a1".to_string(),
+                    content: Some("again".to_string()),
+                    ..Default::default()
+                },
+            ])
+            .await;
+        assert_eq!(results.len(), 4);
+
+        let first = results[0].as_ref().unwrap();
+        assert_eq!(first.content, "new content");
+        assert_eq!(first.embedding, Some(vec![0.1, 0.2]));
+        assert_eq!(first.embedding_model, "test-model");
+        assert_eq!(first.context_embedding, Some(vec![0.1, 0.2]));
+        let second = results[1].as_ref().unwrap();
+        assert_eq!(second.name.as_deref(), Some("Renamed"));
+        assert_eq!(second.embedding, Some(vec![0.9, 0.9]));
+        assert!(matches!(results[2], Err(ServiceError::NotFound(_))));
+        assert!(matches!(results[3], Err(ServiceError::Validation(_))));
+
+        let a1 = db.get("a1").await.unwrap().unwrap();
+        assert_eq!(a1.content, "new content");
+    }
+
+    #[tokio::test]
+    async fn resolve_id_expands_unique_prefix() {
+        let mut a = artifact_with_embedding("m", None);
+        a.id = "k7v3m9xnp2".to_string();
+        let mut b = artifact_with_embedding("m", None);
+        b.id = "k7zzzzzzzz".to_string();
+
+        let db = Arc::new(TestDatabase::new());
+        db.insert(&a).await.unwrap();
+        db.insert(&b).await.unwrap();
+        let service = ArtifactService::new(db, Arc::new(TestEmbedding::new("m", vec![])));
+
+        assert_eq!(service.resolve_id("k7v").await.unwrap(), "k7v3m9xnp2");
+        assert!(matches!(
+            service.resolve_id("k7").await,
+            Err(ServiceError::Validation(msg)) if msg.contains("k7zzzzzzzz")
+        ));
+        assert!(matches!(
+            service.resolve_id("nope").await,
+            Err(ServiceError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn archive_before_hides_old_artifacts_by_default() {
+        let mut old = artifact_with_embedding("m", None);
+        old.updated_at = chrono::Utc::now() - chrono::Duration::days(30);
+        let recent = artifact_with_embedding("m", None);
+
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        db.insert(&old).await.unwrap();
+        db.insert(&recent).await.unwrap();
+        let service = ArtifactService::new(db, Arc::new(TestEmbedding::new("m", vec![])));
+
+        let cutoff = chrono::Utc::now() - chrono::Duration::days(7);
+        let archived = service
+            .archive_before(cutoff, SearchFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(archived, vec![old.id.clone()]);
+
+        let active = service.list(SearchFilters::default()).await.unwrap();
+        assert_eq!(active.len(), 1);
+        assert_eq!(active[0].id, recent.id);
+
+        let everything = service
+            .list(SearchFilters {
+                include_archived: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(everything.len(), 2);
+        assert!(service.get(&old.id).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn renamed_artifacts_resolve_by_their_old_ids() {
+        let first = artifact_with_embedding("m", Some(vec![1.0]));
+        let other = artifact_with_embedding("m", Some(vec![0.5]));
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        db.insert(&first).await.unwrap();
+        db.insert(&other).await.unwrap();
+        let service = ArtifactService::new(db, Arc::new(TestEmbedding::new("m", vec![])));
+
+        let renamed = service.rename(&first.id, "auth-intent").await.unwrap();
+        assert_eq!(renamed.aliases, std::slice::from_ref(&first.id));
+        let found = service.get(&first.id).await.unwrap().unwrap();
+        assert_eq!(found.id, "auth-intent");
+        assert_eq!(found.redirected_from, Some(first.id.clone()));
+
+        assert!(matches!(
+            service.rename("auth-intent", &other.id).await,
+            Err(ServiceError::Conflict(_))
+        ));
+        let back = service.rename("auth-intent", &first.id).await.unwrap();
+        assert_eq!(back.aliases, ["auth-intent"]);
+        let current = service.get(&first.id).await.unwrap().unwrap();
+        assert!(current.redirected_from.is_none());
+    }
+
+    #[tokio::test]
+    async fn renamed_artifacts_are_removed_and_archived_by_their_old_ids() {
+        let removed = artifact_with_embedding("m", Some(vec![1.0]));
+        let archived = artifact_with_embedding("m", Some(vec![0.5]));
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        db.insert(&removed).await.unwrap();
+        db.insert(&archived).await.unwrap();
+        let service = ArtifactService::new(db, Arc::new(TestEmbedding::new("m", vec![])));
+        service.rename(&removed.id, "removed-intent").await.unwrap();
+        service
+            .rename(&archived.id, "archived-intent")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            service.resolve_id(&removed.id).await.unwrap(),
+            "removed-intent"
+        );
+        assert!(service.remove(&removed.id).await.unwrap());
+        assert!(service.get("removed-intent").await.unwrap().is_none());
+
+        assert!(service.archive(&archived.id).await.unwrap());
+        assert!(service.get("archived-intent").await.unwrap().is_none());
+        let archived_ids: Vec<String> = service
+            .list(SearchFilters {
+                include_archived: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.id)
+            .collect();
+        assert_eq!(archived_ids, ["archived-intent"]);
+    }
+
+    #[tokio::test]
+    async fn merged_duplicates_redirect_to_the_target() {
+        let mut target = artifact_with_embedding("m", Some(vec![1.0, 0.0]));
+        target.metadata = HashMap::from([("domain".to_string(), "auth".to_string())]);
+        let mut duplicate = artifact_with_embedding("m", Some(vec![1.0, 0.01]));
+        duplicate.created_at = target.created_at + chrono::Duration::seconds(1);
+        duplicate.metadata = HashMap::from([
+            ("domain".to_string(), "billing".to_string()),
+            ("priority".to_string(), "high".to_string()),
+        ]);
+
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        db.insert(&target).await.unwrap();
+        db.insert(&duplicate).await.unwrap();
+        let service = ArtifactService::new(db, Arc::new(TestEmbedding::new("m", vec![])));
+
+        let groups = service
+            .find_duplicates(SearchFilters::default(), 0.95)
+            .await
+            .unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].artifacts[0].id, target.id);
+
+        assert!(matches!(
+            service.merge_into(&target.id, &[target.id.clone()]).await,
+            Err(ServiceError::Validation(_))
+        ));
+        let merged = service
+            .merge_into(&target.id, &[duplicate.id.clone()])
+            .await
+            .unwrap();
+        assert_eq!(merged.metadata["domain"], "auth");
+        assert_eq!(merged.metadata["priority"], "high");
+
+        let redirected = service.get(&duplicate.id).await.unwrap().unwrap();
+        assert_eq!(redirected.id, target.id);
+        assert_eq!(
+            redirected.redirected_from.as_deref(),
+            Some(duplicate.id.as_str())
+        );
+        let current = service.get(&target.id).await.unwrap().unwrap();
+        assert!(current.redirected_from.is_none());
+        assert!(service.get("missing").await.unwrap().is_none());
+        assert!(service
+            .find_duplicates(SearchFilters::default(), 0.95)
+            .await
+            .unwrap()
+            .is_empty());
     }
 
     #[tokio::test]
@@ -765,6 +2926,148 @@ mod tests {
         assert_eq!(count, 0);
     }
 
+    fn artifact_with_embedding(model: &str, embedding: Option<Vec<f32>>) -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            "content".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            model.to_string(),
+        );
+        artifact.embedding = embedding;
+        artifact
+    }
+
+    #[tokio::test]
+    async fn extra_models_are_embedded_and_promoted_without_reembedding() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let old = artifact_with_embedding("small", Some(vec![0.1, 0.2]));
+        db.insert(&old).await.unwrap();
+
+        let small = || -> Arc<dyn EmbeddingProvider> {
+            Arc::new(TestEmbedding::new("small", vec![0.1, 0.2]))
+        };
+        let large = || -> Arc<dyn EmbeddingProvider> {
+            Arc::new(TestEmbedding::new("large", vec![0.3, 0.4, 0.5]))
+        };
+        let service = ArtifactService::new(db.clone(), small()).with_extra_models(vec![large()]);
+        let added = service
+            .add(
+                "intent".to_string(),
+                "new".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            added.model_embeddings.get("large"),
+            Some(&vec![0.3, 0.4, 0.5])
+        );
+
+        // Artifacts from before the extra model are filled in gradually
+        let filled = service
+            .reindex_model("large", SearchFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(filled, 1);
+        assert!(service
+            .reindex_model("missing", SearchFilters::default())
+            .await
+            .is_err());
+
+        // Switching models keeps both sets of embeddings
+        let switched = ArtifactService::new(db, large()).with_extra_models(vec![small()]);
+        let health = switched
+            .reindex_missing(SearchFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(health.issues.len(), 2);
+        let promoted = switched.get(&old.id).await.unwrap().unwrap();
+        assert_eq!(promoted.embedding_model, "large");
+        assert_eq!(promoted.embedding, Some(vec![0.3, 0.4, 0.5]));
+        assert_eq!(
+            promoted.model_embeddings.get("small"),
+            Some(&vec![0.1, 0.2])
+        );
+        assert!(!promoted.model_embeddings.contains_key("large"));
+    }
+
+    #[tokio::test]
+    async fn embedding_health_classifies_issues() {
+        let healthy = artifact_with_embedding("current", Some(vec![0.1, 0.2]));
+        let missing = artifact_with_embedding("current", None);
+        let zeroed = artifact_with_embedding("current", Some(vec![0.0, 0.0]));
+        let stale = artifact_with_embedding("old-model", Some(vec![0.1, 0.2]));
+        let short = artifact_with_embedding("current", Some(vec![0.1]));
+        let mut no_context_embedding = artifact_with_embedding("current", Some(vec![0.1, 0.2]));
+        no_context_embedding.context = Some("why".to_string());
+
+        let db = Arc::new(TestDatabase::new());
+        for a in [
+            &healthy,
+            &missing,
+            &zeroed,
+            &stale,
+            &short,
+            &no_context_embedding,
+        ] {
+            db.insert(a).await.unwrap();
+        }
+
+        let embedding = Arc::new(TestEmbedding::new("current", vec![0.5, 0.5]));
+        let service = ArtifactService::new(db, embedding);
+
+        let health = service
+            .embedding_health(SearchFilters::default())
+            .await
+            .unwrap();
+
+        assert_eq!(health.scanned, 6);
+        assert_eq!(health.count(EmbeddingIssue::Missing), 3);
+        assert_eq!(health.count(EmbeddingIssue::ModelMismatch), 1);
+        assert_eq!(health.count(EmbeddingIssue::DimensionMismatch), 1);
+        assert!(!health.issues.iter().any(|(id, _)| *id == healthy.id));
+    }
+
+    #[tokio::test]
+    async fn reindex_missing_only_touches_unhealthy_artifacts() {
+        let healthy = artifact_with_embedding("current", Some(vec![0.1, 0.2]));
+        let missing = artifact_with_embedding("current", None);
+
+        let db = Arc::new(TestDatabase::new());
+        db.insert(&healthy).await.unwrap();
+        db.insert(&missing).await.unwrap();
+
+        let embedding = Arc::new(TestEmbedding::new("current", vec![0.5, 0.5]));
+        let service = ArtifactService::new(db.clone(), embedding);
+
+        let health = service
+            .reindex_missing(SearchFilters::default())
+            .await
+            .unwrap();
+
+        assert_eq!(health.scanned, 2);
+        assert_eq!(
+            health.issues,
+            vec![(missing.id.clone(), EmbeddingIssue::Missing)]
+        );
+
+        let repaired = db.get(&missing.id).await.unwrap().unwrap();
+        assert_eq!(repaired.embedding, Some(vec![0.5, 0.5]));
+        let untouched = db.get(&healthy.id).await.unwrap().unwrap();
+        assert_eq!(untouched.embedding, Some(vec![0.1, 0.2]));
+
+        let after = service
+            .embedding_health(SearchFilters::default())
+            .await
+            .unwrap();
+        assert!(after.is_healthy());
+    }
+
     #[tokio::test]
     async fn update_metadata_preserves_existing() {
         let mut initial_metadata = HashMap::new();
@@ -824,18 +3127,73 @@ mod tests {
             .update(&artifact_id, None, None, None, Some(remove_label), None)
             .await
             .unwrap();
-
-        // Label "env" should be removed, not set to empty string
-        assert!(
-            !updated.metadata.contains_key("env"),
-            "Label 'env' should be removed when updated with empty value"
-        );
-        // Label "team" should remain unchanged
-        assert_eq!(
-            updated.metadata.get("team"),
-            Some(&"platform".to_string()),
-            "Label 'team' should remain unchanged"
-        );
+
+        // Label "env" should be removed, not set to empty string
+        assert!(
+            !updated.metadata.contains_key("env"),
+            "Label 'env' should be removed when updated with empty value"
+        );
+        // Label "team" should remain unchanged
+        assert_eq!(
+            updated.metadata.get("team"),
+            Some(&"platform".to_string()),
+            "Label 'team' should remain unchanged"
+        );
+    }
+
+    #[tokio::test]
+    async fn update_rejects_attachment_manifests_naming_paths() {
+        let artifact = Artifact::new(
+            "spec".to_string(),
+            "content".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        let artifact_id = artifact.id.clone();
+        let db = Arc::new(TestDatabase::with_artifact(artifact));
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![]));
+        let service = ArtifactService::new(db, embedding);
+        let manifest = |value: &str| {
+            Some(HashMap::from([(
+                ATTACHMENTS_LABEL.to_string(),
+                value.to_string(),
+            )]))
+        };
+
+        let err = service
+            .update(
+                &artifact_id,
+                None,
+                None,
+                None,
+                manifest(r#"[{"name":"../x","size":1}]"#),
+                None,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Validation(_)), "{err}");
+        assert!(service
+            .get(&artifact_id)
+            .await
+            .unwrap()
+            .unwrap()
+            .metadata
+            .is_empty());
+
+        let updated = service
+            .update(
+                &artifact_id,
+                None,
+                None,
+                None,
+                manifest(r#"[{"name":"flow.png","size":1}]"#),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(attachment::attachments(&updated).len(), 1);
     }
 
     #[tokio::test]
@@ -931,4 +3289,617 @@ mod tests {
             result.err()
         );
     }
+
+    #[tokio::test]
+    async fn preview_update_reports_changes_without_writing() {
+        let artifact = Artifact::new(
+            "intent".to_string(),
+            "old content".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        let artifact_id = artifact.id.clone();
+
+        let db = Arc::new(TestDatabase::with_artifact(artifact));
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding);
+
+        let preview = service
+            .preview_update(
+                &artifact_id,
+                Some("new content".to_string()),
+                Some("Renamed".to_string()),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(preview.changed_fields, vec!["content", "name"]);
+        assert_eq!(preview.embedding_calls, 1);
+        assert_eq!(preview.artifact.content, "new content");
+        assert!(preview.content_tokens > 0);
+
+        let stored = service.get(&artifact_id).await.unwrap().unwrap();
+        assert_eq!(stored.content, "old content");
+        assert_eq!(stored.name, None);
+    }
+
+    #[test]
+    fn preview_add_counts_context_embedding() {
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding);
+
+        let preview = service
+            .preview_add(
+                "intent".to_string(),
+                "content".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                Some("why it matters".to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(preview.embedding_calls, 2);
+        assert!(preview.context_tokens.is_some());
+        assert!(!preview.artifact.id.is_empty());
+    }
+
+    #[tokio::test]
+    async fn access_policy_limits_kinds_read_and_written() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let admin = ArtifactService::new(db.clone(), embedding.clone());
+        async fn add(service: &ArtifactService, kind: &str) -> Result<Artifact> {
+            service
+                .add(
+                    kind.to_string(),
+                    "content".to_string(),
+                    ContentFormat::Markdown,
+                    None,
+                    HashMap::new(),
+                    None,
+                )
+                .await
+        }
+        let invariant = add(&admin, "invariant").await.unwrap();
+        let boundary = add(&admin, "boundary").await.unwrap();
+
+        let agent = ArtifactService::new(db, embedding).with_access_policy(Arc::new(
+            crate::services::KindAccess::default()
+                .with_read(vec!["invariant".to_string()])
+                .with_write(vec!["evaluation".to_string()]),
+        ));
+        let forbidden =
+            |err: anyhow::Error| matches!(ServiceError::classify(err), ServiceError::Forbidden(_));
+
+        assert!(add(&agent, "evaluation").await.is_ok());
+        assert!(forbidden(add(&agent, "invariant").await.unwrap_err()));
+        assert!(agent.get(&invariant.id).await.unwrap().is_some());
+        assert!(forbidden(agent.get(&boundary.id).await.unwrap_err()));
+        assert!(forbidden(agent.remove(&invariant.id).await.unwrap_err()));
+        assert!(matches!(
+            agent
+                .update(
+                    &invariant.id,
+                    Some("changed".into()),
+                    None,
+                    None,
+                    None,
+                    None
+                )
+                .await,
+            Err(ServiceError::Forbidden(_))
+        ));
+
+        let mut kinds: Vec<String> = agent
+            .list(SearchFilters::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.kind)
+            .collect();
+        kinds.sort();
+        assert_eq!(kinds, ["evaluation", "invariant"]);
+    }
+
+    #[tokio::test]
+    async fn writes_are_signed_with_the_configured_key() {
+        use crate::services::{Signer, Verification, Verifier};
+
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let signer = Signer::new("alice", &Signer::generate_key()).unwrap();
+        let verifier = Verifier::default()
+            .trust("alice", &signer.public_key())
+            .unwrap();
+        let signing = ArtifactService::new(db.clone(), embedding.clone()).with_signer(Some(signer));
+        let unsigned = ArtifactService::new(db, embedding);
+
+        let added = signing
+            .add(
+                "invariant".to_string(),
+                "Orders ship after payment".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        let verified = Verification::Verified {
+            signer: "alice".to_string(),
+        };
+        assert_eq!(verifier.verify(&added), verified);
+
+        let edited = unsigned
+            .update(
+                &added.id,
+                Some("Orders ship first".into()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(matches!(
+            verifier.verify(&edited),
+            Verification::Invalid { .. }
+        ));
+
+        let resigned = signing.sign(&added.id).await.unwrap();
+        assert_eq!(verifier.verify(&resigned), verified);
+        assert!(unsigned.sign(&added.id).await.is_err());
+
+        let renamed = signing.rename(&added.id, "shipping").await.unwrap();
+        assert_eq!(verifier.verify(&renamed), verified);
+    }
+
+    #[tokio::test]
+    async fn writes_are_labeled_with_their_language() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("BAAI/bge-small-en-v1.5", vec![0.1]));
+        let service = ArtifactService::new(db, embedding).with_languages(LanguagesConfig {
+            detect: true,
+            require_multilingual: true,
+            models: std::collections::BTreeMap::from([(
+                "deu".to_string(),
+                "intfloat/multilingual-e5-small".to_string(),
+            )]),
+        });
+        let add = |content: &str| {
+            service.add(
+                "intent".to_string(),
+                content.to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+        };
+
+        let added = add(
+            "Bestellungen werden erst nach dem Eingang der Zahlung versendet, damit keine \
+             offenen Rechnungen entstehen.",
+        )
+        .await
+        .unwrap();
+        assert_eq!(added.metadata[LANGUAGE_LABEL], "deu");
+
+        let updated = service
+            .update(
+                &added.id,
+                Some(
+                    "Orders ship only after the payment has been received in full, so that no \
+                     invoices are left open."
+                        .to_string(),
+                ),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.metadata[LANGUAGE_LABEL], "eng");
+
+        let err = add(
+            "Les commandes sont expédiées seulement après la réception du paiement, afin \
+             qu'aucune facture ne reste ouverte.",
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("'fra'"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn embed_templates_compose_the_embedded_text() {
+        use crate::testing::TestEmbedding;
+
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let template = "{name}\n{content}".to_string();
+        let service = ArtifactService::new(db.clone(), Arc::new(TestEmbedding))
+            .with_embed_templates(HashMap::from([("runbook".to_string(), template.clone())]));
+        let embedded = |text: &str| {
+            let text = text.to_string();
+            async move { TestEmbedding.embed(&text).await.unwrap() }
+        };
+
+        let added = service
+            .add(
+                "runbook".to_string(),
+                "Restart the worker pool.".to_string(),
+                ContentFormat::Markdown,
+                Some("Stuck queue".to_string()),
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            added.embedding,
+            Some(embedded("Stuck queue\nRestart the worker pool.").await)
+        );
+        assert_eq!(added.embedding_text, Some(template));
+
+        // Renaming changes the composed text, so the content is re-embedded
+        let renamed = service
+            .update(
+                &added.id,
+                None,
+                Some("Stalled queue".to_string()),
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            renamed.embedding,
+            Some(embedded("Stalled queue\nRestart the worker pool.").await)
+        );
+
+        // Dropping the template leaves the stored embedding stale until reindexed
+        let plain = ArtifactService::new(db, Arc::new(TestEmbedding));
+        assert_eq!(
+            plain.embedding_issue(&renamed),
+            Some(EmbeddingIssue::CompositionChanged)
+        );
+        let health = plain
+            .reindex_missing(SearchFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(health.count(EmbeddingIssue::CompositionChanged), 1);
+        let reindexed = plain.get(&added.id).await.unwrap().unwrap();
+        assert_eq!(
+            reindexed.embedding,
+            Some(embedded("Restart the worker pool.").await)
+        );
+        assert_eq!(reindexed.embedding_text, None);
+        assert_eq!(plain.embedding_issue(&reindexed), None);
+    }
+
+    #[tokio::test]
+    async fn attachment_text_is_embedded_for_context_search() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db.clone(), embedding);
+
+        let added = service
+            .add(
+                "spec".to_string(),
+                "Checkout flow".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        let context_hits = || async {
+            db.search_context(&[0.1], SearchFilters::default())
+                .await
+                .unwrap()
+                .len()
+        };
+        assert_eq!(context_hits().await, 0);
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let updated = service
+            .set_attachment_text(&added.id, Some("# runbook.md\n\nRetry".to_string()))
+            .await
+            .unwrap();
+        assert_eq!(updated.attachment_embedding, Some(vec![0.1]));
+        assert_eq!(service.embedding_issue(&updated), None);
+        assert!(updated.updated_at > added.updated_at);
+        assert_eq!(context_hits().await, 1);
+
+        let cleared = service.set_attachment_text(&added.id, None).await.unwrap();
+        assert_eq!(cleared.attachment_embedding, None);
+        assert_eq!(context_hits().await, 0);
+    }
+
+    fn record(content: &str) -> Artifact {
+        Artifact::new(
+            "intent".to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            Some("Checkout".to_string()),
+            HashMap::from([("team".to_string(), "payments".to_string())]),
+            String::new(),
+        )
+    }
+
+    #[tokio::test]
+    async fn upsert_updates_what_changed_instead_of_duplicating() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db.clone(), embedding);
+
+        let created = service.upsert("JIRA-1", record("v1")).await.unwrap();
+        assert_eq!(created.action, UpsertAction::Created);
+        assert_eq!(created.artifact.metadata[EXTERNAL_ID_LABEL], "JIRA-1");
+        let version = db.version().await.unwrap();
+
+        let again = service.upsert("JIRA-1", record("v1")).await.unwrap();
+        assert_eq!(again.action, UpsertAction::Unchanged);
+        assert_eq!(again.artifact.id, created.artifact.id);
+        assert_eq!(db.version().await.unwrap(), version);
+
+        let updated = service.upsert("JIRA-1", record("v2")).await.unwrap();
+        assert_eq!(updated.action, UpsertAction::Updated);
+        assert_eq!(updated.artifact.id, created.artifact.id);
+        assert_eq!(updated.changed_fields, vec!["content"]);
+
+        let other = service.upsert("JIRA-2", record("v1")).await.unwrap();
+        assert_ne!(other.artifact.id, created.artifact.id);
+        assert_eq!(db.count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn upsert_does_not_re_add_archived_artifacts() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding);
+
+        let created = service.upsert("JIRA-1", record("v1")).await.unwrap();
+        service.archive(&created.artifact.id).await.unwrap();
+
+        let err = service.upsert("JIRA-1", record("v2")).await.unwrap_err();
+        assert!(matches!(ServiceError::from(err), ServiceError::Conflict(_)));
+        assert!(service.upsert(" ", record("v1")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn get_many_lines_results_up_with_the_ids() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding);
+
+        let first = service
+            .upsert("A-1", record("first"))
+            .await
+            .unwrap()
+            .artifact;
+        let second = service
+            .upsert("A-2", record("second"))
+            .await
+            .unwrap()
+            .artifact;
+        service.rename(&second.id, "renamed").await.unwrap();
+
+        let ids = vec![
+            "renamed".to_string(),
+            "missing".to_string(),
+            first.id.clone(),
+            second.id.clone(),
+        ];
+        let artifacts = service.get_many(&ids).await.unwrap();
+        let found: Vec<Option<&str>> = artifacts
+            .iter()
+            .map(|a| a.as_ref().map(|a| a.id.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                Some("renamed"),
+                None,
+                Some(first.id.as_str()),
+                Some("renamed")
+            ]
+        );
+        assert_eq!(
+            artifacts[3].as_ref().unwrap().redirected_from.as_deref(),
+            Some(second.id.as_str())
+        );
+        assert!(service.get_many(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn rename_kind_moves_active_and_archived_artifacts() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding);
+
+        let active = service.import(record("active")).await.unwrap();
+        let archived = service.import(record("archived")).await.unwrap();
+        service.archive(&archived.id).await.unwrap();
+
+        assert_eq!(service.rename_kind("intent", "Rule").await.unwrap(), 2);
+        assert_eq!(service.get(&active.id).await.unwrap().unwrap().kind, "rule");
+        let filters = SearchFilters {
+            kind: Some("rule".to_string()),
+            include_archived: true,
+            ..Default::default()
+        };
+        assert_eq!(service.list(filters).await.unwrap().len(), 2);
+
+        let err = service.rename_kind("rule", "rule").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ServiceError>(),
+            Some(ServiceError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn assign_checks_owners_and_filters_by_them() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding).with_owners(OwnersConfig {
+            people: vec!["@alice".to_string()],
+            teams: vec!["@payments".to_string()],
+        });
+
+        let mut owned = record("owned");
+        owned.owner = Some("payments".to_string());
+        let owned = service.import(owned).await.unwrap();
+        assert_eq!(owned.owner.as_deref(), Some("@payments"));
+        let mut unknown = record("unknown");
+        unknown.owner = Some("@bob".to_string());
+        assert!(service.import(unknown).await.is_err());
+
+        let other = service.import(record("other")).await.unwrap();
+        let assignment = Assignment {
+            owner: None,
+            reviewers: Some(vec!["alice".to_string()]),
+        };
+        let reviewed = service.assign(&other.id, &assignment).await.unwrap();
+        assert_eq!(reviewed.reviewers, ["@alice"]);
+        assert_eq!(reviewed.owner, None);
+
+        let bad = Assignment {
+            owner: Some(Some("@bob".to_string())),
+            reviewers: None,
+        };
+        let err = service.assign(&other.id, &bad).await.unwrap_err();
+        assert!(matches!(err, ServiceError::Validation(_)));
+
+        let by_owner = SearchFilters {
+            owner: Some("@payments".to_string()),
+            ..Default::default()
+        };
+        let found = service.list(by_owner).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, owned.id);
+        let by_reviewer = SearchFilters {
+            reviewer: Some("@alice".to_string()),
+            ..Default::default()
+        };
+        let found = service.list(by_reviewer).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, other.id);
+    }
+
+    #[tokio::test]
+    async fn deprecate_records_the_successor_and_hides_from_filtered_lists() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding);
+        let old = service.import(record("old")).await.unwrap();
+        let new = service.import(record("new")).await.unwrap();
+
+        let err = service
+            .deprecate(
+                &old.id,
+                &Deprecation {
+                    replaced_by: Some(old.id.clone()),
+                    reason: None,
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::Validation(_)));
+
+        let err = service
+            .deprecate(
+                &old.id,
+                &Deprecation {
+                    replaced_by: Some("missing".to_string()),
+                    reason: None,
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ServiceError::NotFound(_)), "{err}");
+        assert!(Deprecation::of(&service.get(&old.id).await.unwrap().unwrap()).is_none());
+
+        let deprecation = Deprecation {
+            replaced_by: Some(new.id.clone()),
+            reason: Some("superseded by the v2 flow".to_string()),
+        };
+        service.deprecate(&old.id, &deprecation).await.unwrap();
+        let fetched = service.get(&old.id).await.unwrap().unwrap();
+        assert_eq!(Deprecation::of(&fetched), Some(deprecation));
+
+        let err = service
+            .deprecate(
+                &new.id,
+                &Deprecation {
+                    replaced_by: Some(old.id.clone()),
+                    reason: None,
+                },
+            )
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains(&format!("use its successor '{}'", new.id)));
+
+        let current = SearchFilters {
+            exclude_deprecated: true,
+            ..Default::default()
+        };
+        let found = service.list(current).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, new.id);
+        assert_eq!(
+            service.list(SearchFilters::default()).await.unwrap().len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn parse_monitor_reads_monitors_only() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1]));
+        let service = ArtifactService::new(db, embedding);
+        let add = |content: &str, kind: &str| {
+            service.add(
+                kind.to_string(),
+                content.to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+        };
+        let latency = add("p99_latency < 400ms over 5m", MONITOR_KIND)
+            .await
+            .unwrap();
+        let prose = add("Checkout stays fast.", MONITOR_KIND).await.unwrap();
+        let intent = add("p99_latency < 400ms", "intent").await.unwrap();
+
+        let monitor = service.parse_monitor(&latency.id).await.unwrap();
+        assert_eq!(monitor.metric, "p99_latency");
+        assert_eq!(monitor.window.as_deref(), Some("5m"));
+
+        let err = service.parse_monitor(&prose.id).await.unwrap_err();
+        assert!(matches!(err, ServiceError::Validation(_)));
+        let err = service.parse_monitor(&intent.id).await.unwrap_err();
+        assert!(
+            err.to_string().contains("is not a monitor (kind: intent)"),
+            "{}",
+            err
+        );
+        assert!(matches!(
+            service.parse_monitor("missing").await.unwrap_err(),
+            ServiceError::NotFound(_)
+        ));
+    }
 }
@@ -0,0 +1,208 @@
+//! Answering questions from the store.
+//!
+//! The artifacts most relevant to a question are assembled into a context
+//! block, each headed by its ID so answers can cite it. With a completion
+//! model configured the block is handed to the model to answer from;
+//! without one the block itself is the result, for the caller's own model.
+
+use super::completion::Completion;
+use super::search::SearchService;
+use super::types::{SearchFilters, SearchResult};
+use super::ServiceError;
+use anyhow::Result;
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Longest excerpt of one artifact in the context block, in characters
+const MAX_CHARS_PER_SOURCE: usize = 2000;
+
+const SYSTEM_PROMPT: &str = "You answer questions about a project from its truth \
+artifacts. Use only the artifacts given. Cite the ID of each artifact you rely on \
+in square brackets right after the claim, e.g. [k7v3m9xnp2]. If the artifacts do \
+not answer the question, say so instead of guessing.";
+
+/// An artifact the context block was built from
+#[derive(Debug, Clone, Serialize)]
+pub struct AskSource {
+    pub id: String,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub score: f32,
+}
+
+/// The answer to a question, with what it was based on
+#[derive(Debug, Clone, Serialize)]
+pub struct Answer {
+    pub question: String,
+    /// The model's answer with inline `[id]` citations; absent without a
+    /// completion model or when nothing relevant was found
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub answer: Option<String>,
+    /// The retrieved artifacts, each headed by its ID
+    pub context: String,
+    pub sources: Vec<AskSource>,
+}
+
+/// Service that answers questions from the artifacts search finds for them
+pub struct AskService {
+    search: Arc<SearchService>,
+    completion: Option<Arc<dyn Completion>>,
+}
+
+impl AskService {
+    pub fn new(search: Arc<SearchService>) -> Self {
+        Self {
+            search,
+            completion: None,
+        }
+    }
+
+    /// Have a model answer from the context; without one only the context
+    /// is returned
+    pub fn with_completion(mut self, completion: Option<Arc<dyn Completion>>) -> Self {
+        self.completion = completion;
+        self
+    }
+
+    /// Retrieve the artifacts matching `filters` most relevant to `question`
+    /// (as many as `filters.limit`) and answer from them
+    pub async fn ask(&self, question: &str, filters: SearchFilters) -> Result<Answer> {
+        let question = question.trim();
+        if question.is_empty() {
+            return Err(ServiceError::Validation("Question cannot be empty".to_string()).into());
+        }
+
+        let results = self.search.search(question, filters).await?;
+        let context = context_block(&results);
+        let answer = match &self.completion {
+            Some(completion) if !results.is_empty() => {
+                let prompt = format!("Artifacts:\n\n{}\nQuestion: {}", context, question);
+                Some(completion.complete(SYSTEM_PROMPT, &prompt).await?)
+            },
+            _ => None,
+        };
+
+        Ok(Answer {
+            question: question.to_string(),
+            answer,
+            context,
+            sources: results
+                .iter()
+                .map(|result| AskSource {
+                    id: result.artifact.id.clone(),
+                    kind: result.artifact.kind.clone(),
+                    name: result.artifact.name.clone(),
+                    score: result.score,
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Search results as a block of context, each artifact headed by its ID,
+/// kind and name so it can be cited
+pub fn context_block(results: &[SearchResult]) -> String {
+    let mut block = String::new();
+    for result in results {
+        let artifact = &result.artifact;
+        block.push_str(&format!("[{}] {}", artifact.id, artifact.kind));
+        if let Some(name) = &artifact.name {
+            block.push_str(&format!(": {}", name));
+        }
+        block.push('\n');
+        let content = artifact.content.trim();
+        if content.chars().count() > MAX_CHARS_PER_SOURCE {
+            let excerpt: String = content.chars().take(MAX_CHARS_PER_SOURCE).collect();
+            block.push_str(excerpt.trim_end());
+            block.push_str("...");
+        } else {
+            block.push_str(content);
+        }
+        block.push_str("\n\n");
+    }
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::services::{Artifact, ContentFormat};
+    use crate::testing::{TestDatabase, TestEmbedding};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    /// Records the prompt and cites the first artifact in it
+    #[derive(Default)]
+    struct Citing(Mutex<String>);
+
+    #[async_trait::async_trait]
+    impl Completion for Citing {
+        async fn complete(&self, _system: &str, prompt: &str) -> Result<String> {
+            *self.0.lock().unwrap() = prompt.to_string();
+            let id = prompt
+                .split('[')
+                .nth(1)
+                .and_then(|rest| rest.split(']').next())
+                .unwrap_or_default();
+            Ok(format!("Payment is captured first [{}].", id))
+        }
+    }
+
+    async fn search_over(contents: &[&str]) -> Arc<SearchService> {
+        let db = Arc::new(TestDatabase::new());
+        for content in contents {
+            let mut artifact = Artifact::new(
+                "invariant".to_string(),
+                content.to_string(),
+                ContentFormat::Markdown,
+                Some("shipping".to_string()),
+                HashMap::new(),
+                "test".to_string(),
+            );
+            artifact.embedding = Some(vec![1.0; 384]);
+            db.insert(&artifact).await.unwrap();
+        }
+        Arc::new(SearchService::new(db, Arc::new(TestEmbedding)))
+    }
+
+    #[tokio::test]
+    async fn answers_cite_the_retrieved_artifacts() {
+        let search = search_over(&["Orders ship only after payment is captured."]).await;
+        let completion = Arc::new(Citing::default());
+        let service = AskService::new(search).with_completion(Some(completion.clone()));
+
+        let answer = service
+            .ask("why pay before shipping?", SearchFilters::default())
+            .await
+            .unwrap();
+        let id = &answer.sources[0].id;
+        assert!(answer
+            .context
+            .starts_with(&format!("[{}] invariant: shipping\n", id)));
+        assert!(answer.context.contains("Orders ship only after payment"));
+        assert_eq!(
+            answer.answer.as_deref(),
+            Some(format!("Payment is captured first [{}].", id).as_str())
+        );
+        let prompt = completion.0.lock().unwrap().clone();
+        assert!(prompt.ends_with("Question: why pay before shipping?"));
+    }
+
+    #[tokio::test]
+    async fn without_a_model_only_the_context_is_returned() {
+        let search = search_over(&["Refunds go back to the original card."]).await;
+        let service = AskService::new(search);
+
+        let answer = service
+            .ask("how do refunds work?", SearchFilters::default())
+            .await
+            .unwrap();
+        assert!(answer.answer.is_none());
+        assert_eq!(answer.sources.len(), 1);
+        assert!(answer.context.contains("original card"));
+
+        assert!(service.ask("  ", SearchFilters::default()).await.is_err());
+    }
+}
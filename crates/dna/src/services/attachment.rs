@@ -0,0 +1,333 @@
+//! Files attached to artifacts.
+//!
+//! Diagrams, spec files and other binaries don't belong in embedded text, so
+//! they are stored next to the store, under `<location>/<artifact id>/<name>`,
+//! and listed in a manifest kept in the artifact's `attachments` label.
+//!
+//! Text is extracted from Markdown, HTML and PDF attachments so it can be
+//! embedded as an extra context vector and searched with the artifact.
+
+use super::backup::Location;
+use super::types::{Artifact, S3Config};
+use anyhow::{Context, Result};
+use object_store::PutPayload;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Label holding an artifact's attachment manifest, as a JSON array
+pub const ATTACHMENTS_LABEL: &str = "attachments";
+
+/// A file attached to an artifact, as listed in its manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Attachment {
+    /// File name, unique within the artifact
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+}
+
+impl Attachment {
+    /// Path of the file relative to the attachment location
+    pub fn key(&self, artifact_id: &str) -> String {
+        format!("{}/{}", artifact_id, self.name)
+    }
+}
+
+/// Attachments listed in an artifact's manifest, in the order they were added.
+///
+/// The manifest is a label, so entries whose name is not a plain file name
+/// are skipped rather than used as paths.
+pub fn attachments(artifact: &Artifact) -> Vec<Attachment> {
+    artifact
+        .metadata
+        .get(ATTACHMENTS_LABEL)
+        .and_then(|manifest| serde_json::from_str::<Vec<Attachment>>(manifest).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|attached| is_valid_attachment_name(&attached.name))
+        .collect()
+}
+
+/// Check a manifest written to the [`ATTACHMENTS_LABEL`] label: a JSON list
+/// of attachments with plain file names
+pub fn check_manifest(manifest: &str) -> Result<(), String> {
+    let attachments: Vec<Attachment> = serde_json::from_str(manifest).map_err(|e| {
+        format!(
+            "Label '{}' must be a JSON list of attachments: {}",
+            ATTACHMENTS_LABEL, e
+        )
+    })?;
+    match attachments
+        .iter()
+        .find(|attached| !is_valid_attachment_name(&attached.name))
+    {
+        Some(attached) => Err(format!(
+            "Invalid attachment name '{}': use a plain file name",
+            attached.name
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Labels that record `attachment` in `artifact`'s manifest, replacing any
+/// attachment of the same name, to pass to an update
+pub fn manifest_with(
+    artifact: &Artifact,
+    attachment: Attachment,
+) -> Result<HashMap<String, String>> {
+    let mut manifest = attachments(artifact);
+    match manifest.iter_mut().find(|a| a.name == attachment.name) {
+        Some(existing) => *existing = attachment,
+        None => manifest.push(attachment),
+    }
+    Ok(HashMap::from([(
+        ATTACHMENTS_LABEL.to_string(),
+        serde_json::to_string(&manifest)?,
+    )]))
+}
+
+/// Whether `name` can be used as an attachment name: a plain file name
+pub fn is_valid_attachment_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "."
+        && name != ".."
+        && !name.contains(['/', '\\'])
+        && !name.chars().any(char::is_control)
+}
+
+/// Text of an attachment, for the formats whose text can be extracted,
+/// chosen by the file extension
+pub fn extract_text(name: &str, data: &[u8]) -> Option<String> {
+    let extension = name.rsplit_once('.')?.1.to_ascii_lowercase();
+    let text = match extension.as_str() {
+        "md" | "markdown" | "txt" => String::from_utf8_lossy(data).into_owned(),
+        "html" | "htm" => html2md::parse_html(&String::from_utf8_lossy(data)),
+        "pdf" => match pdf_extract::extract_text_from_mem(data) {
+            Ok(text) => text,
+            Err(e) => {
+                tracing::warn!("Failed to extract text from {}: {}", name, e);
+                return None;
+            },
+        },
+        _ => return None,
+    };
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Service that stores attachment files in a local directory or on S3
+pub struct AttachmentService {
+    location: Location,
+}
+
+impl AttachmentService {
+    /// Store attachments at `location`, a local directory or `s3://` URI
+    pub fn new(location: &str, s3: &S3Config) -> Result<Self> {
+        Ok(Self {
+            location: Location::open(location, s3)?,
+        })
+    }
+
+    /// Store `data` as `name` for the artifact with ID `artifact_id`,
+    /// replacing any file of that name
+    pub async fn put(&self, artifact_id: &str, name: &str, data: Vec<u8>) -> Result<Attachment> {
+        if !is_valid_attachment_name(name) {
+            anyhow::bail!("Invalid attachment name '{}': use a plain file name", name);
+        }
+        let attachment = Attachment {
+            name: name.to_string(),
+            size: data.len() as u64,
+        };
+        let key = attachment.key(artifact_id);
+        self.location
+            .store
+            .put(&self.location.path(&key)?, PutPayload::from(data))
+            .await
+            .with_context(|| format!("Failed to write attachment {}", key))?;
+        Ok(attachment)
+    }
+
+    /// Read an attachment back, checking it is the size the manifest records
+    pub async fn get(&self, artifact_id: &str, attachment: &Attachment) -> Result<Vec<u8>> {
+        let key = attachment.key(artifact_id);
+        let data = async {
+            self.location
+                .store
+                .get(&self.location.path(&key)?)
+                .await?
+                .bytes()
+                .await
+                .map_err(anyhow::Error::from)
+        }
+        .await
+        .with_context(|| format!("Failed to read attachment {}", key))?;
+        if data.len() as u64 != attachment.size {
+            anyhow::bail!(
+                "Attachment {} is {} bytes, but {} were attached",
+                key,
+                data.len(),
+                attachment.size
+            );
+        }
+        Ok(data.to_vec())
+    }
+
+    /// Text extracted from all of an artifact's attachments, each under a
+    /// heading naming the file, or `None` when none has any
+    pub async fn extract_all(&self, artifact: &Artifact) -> Result<Option<String>> {
+        let mut sections = Vec::new();
+        for attachment in attachments(artifact) {
+            let data = self.get(&artifact.id, &attachment).await?;
+            if let Some(text) = extract_text(&attachment.name, &data) {
+                sections.push(format!("# {}\n\n{}", attachment.name, text));
+            }
+        }
+        Ok((!sections.is_empty()).then(|| sections.join("\n\n")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use tempfile::TempDir;
+
+    fn artifact() -> Artifact {
+        Artifact::new(
+            "spec".to_string(),
+            "Checkout flow".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        )
+    }
+
+    #[test]
+    fn manifest_replaces_attachments_by_name() {
+        let mut artifact = artifact();
+        assert!(attachments(&artifact).is_empty());
+
+        for (name, size) in [("flow.png", 10), ("api.yaml", 20), ("flow.png", 30)] {
+            let labels = manifest_with(
+                &artifact,
+                Attachment {
+                    name: name.to_string(),
+                    size,
+                },
+            )
+            .unwrap();
+            artifact.metadata.extend(labels);
+        }
+
+        let names: Vec<(String, u64)> = attachments(&artifact)
+            .into_iter()
+            .map(|a| (a.name, a.size))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("flow.png".to_string(), 30), ("api.yaml".to_string(), 20)]
+        );
+    }
+
+    #[test]
+    fn attachment_names_are_plain_file_names() {
+        assert!(is_valid_attachment_name("diagram.png"));
+        assert!(is_valid_attachment_name("Design Doc v2.pdf"));
+        for name in [
+            "",
+            ".",
+            "..",
+            "../secrets",
+            "docs/diagram.png",
+            "a\\b",
+            "a\nb",
+        ] {
+            assert!(!is_valid_attachment_name(name), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn manifest_entries_that_are_not_file_names_are_ignored() {
+        let mut artifact = artifact();
+        let manifest = r#"[{"name":"../x","size":1},{"name":"flow.png","size":2}]"#;
+        artifact
+            .metadata
+            .insert(ATTACHMENTS_LABEL.to_string(), manifest.to_string());
+
+        let names: Vec<String> = attachments(&artifact).into_iter().map(|a| a.name).collect();
+        assert_eq!(names, ["flow.png"]);
+        assert!(check_manifest(manifest).unwrap_err().contains("'../x'"));
+        assert!(check_manifest("not json").is_err());
+        assert_eq!(check_manifest(r#"[{"name":"flow.png","size":2}]"#), Ok(()));
+    }
+
+    #[test]
+    fn text_is_extracted_from_markdown_and_html() {
+        assert_eq!(
+            extract_text("notes.MD", b"# Retries\n\nBack off exponentially.\n").as_deref(),
+            Some("# Retries\n\nBack off exponentially.")
+        );
+        let html = extract_text("page.html", b"<h1>Retries</h1><p>Back off.</p>").unwrap();
+        assert!(html.contains("Retries"), "{html}");
+        assert!(html.contains("Back off."), "{html}");
+        assert!(!html.contains("<p>"), "{html}");
+        assert_eq!(extract_text("diagram.png", b"\x89PNG"), None);
+        assert_eq!(extract_text("empty.md", b"  \n"), None);
+        assert_eq!(extract_text("broken.pdf", b"not a pdf"), None);
+    }
+
+    #[tokio::test]
+    async fn extracted_text_names_each_attachment() {
+        let dir = TempDir::new().unwrap();
+        let service =
+            AttachmentService::new(dir.path().to_str().unwrap(), &S3Config::default()).unwrap();
+        let mut artifact = artifact();
+        assert_eq!(service.extract_all(&artifact).await.unwrap(), None);
+
+        for (name, data) in [
+            ("flow.png", b"\x89PNG".to_vec()),
+            ("runbook.md", b"Restart the worker.".to_vec()),
+        ] {
+            let attachment = service.put(&artifact.id, name, data).await.unwrap();
+            artifact
+                .metadata
+                .extend(manifest_with(&artifact, attachment).unwrap());
+        }
+
+        assert_eq!(
+            service.extract_all(&artifact).await.unwrap().as_deref(),
+            Some("# runbook.md\n\nRestart the worker.")
+        );
+    }
+
+    #[tokio::test]
+    async fn stored_attachments_read_back() {
+        let dir = TempDir::new().unwrap();
+        let service =
+            AttachmentService::new(dir.path().to_str().unwrap(), &S3Config::default()).unwrap();
+
+        let attachment = service
+            .put("abc123", "diagram.png", b"\x89PNG data".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(attachment.size, 9);
+        assert!(dir.path().join("abc123").join("diagram.png").exists());
+        assert_eq!(
+            service.get("abc123", &attachment).await.unwrap(),
+            b"\x89PNG data"
+        );
+
+        let truncated = Attachment {
+            size: 4,
+            ..attachment
+        };
+        let err = service.get("abc123", &truncated).await.unwrap_err();
+        assert!(err.to_string().contains("were attached"), "{err}");
+
+        assert!(service
+            .put("abc123", "../escape", Vec::new())
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,535 @@
+use super::types::{is_s3_uri, S3Config};
+use crate::db::lock::{self, WriterLock};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, SubsecRound, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use futures::{Stream, StreamExt, TryStreamExt};
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path as ObjectPath;
+use object_store::{ObjectMeta, ObjectStore, PutPayload, WriteMultipart};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+const NAME_PREFIX: &str = "dna-backup-";
+const NAME_SUFFIX: &str = ".tar.gz";
+const NAME_TIME_FORMAT: &str = "%Y%m%dT%H%M%S%.3fZ";
+
+/// Upload parts of a snapshot in flight at once; each is 5 MB
+const PARTS_IN_FLIGHT: usize = 4;
+
+/// A snapshot in the backup location
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BackupInfo {
+    /// File name, e.g. `dna-backup-20240502T101500.000Z.tar.gz`
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    /// Compressed size in bytes
+    pub size: u64,
+}
+
+/// An object store and the path inside it that everything is relative to
+#[derive(Clone)]
+pub(super) struct Location {
+    pub(super) store: Arc<dyn ObjectStore>,
+    prefix: ObjectPath,
+}
+
+impl Location {
+    /// Open a local directory (created if missing) or an `s3://` URI
+    pub(super) fn open(uri: &str, s3: &S3Config) -> Result<Self> {
+        if is_s3_uri(uri) {
+            let url = reqwest::Url::parse(uri).with_context(|| format!("Invalid URI '{}'", uri))?;
+            let mut builder = AmazonS3Builder::from_env().with_url(uri);
+            if let Some(region) = &s3.region {
+                builder = builder.with_region(region);
+            }
+            if let Some(endpoint) = &s3.endpoint {
+                builder = builder
+                    .with_endpoint(endpoint)
+                    .with_allow_http(endpoint.starts_with("http://"));
+            }
+            return Ok(Self {
+                store: Arc::new(builder.build()?),
+                prefix: ObjectPath::from_url_path(url.path())?,
+            });
+        }
+
+        std::fs::create_dir_all(uri).with_context(|| format!("Failed to create {}", uri))?;
+        Ok(Self {
+            store: Arc::new(LocalFileSystem::new_with_prefix(uri)?),
+            prefix: ObjectPath::default(),
+        })
+    }
+
+    pub(super) async fn list(&self) -> Result<Vec<ObjectMeta>> {
+        self.store
+            .list(Some(&self.prefix))
+            .try_collect()
+            .await
+            .context("Failed to list files")
+    }
+
+    pub(super) fn path(&self, relative: &str) -> Result<ObjectPath> {
+        let relative = ObjectPath::parse(relative)?;
+        Ok(self.prefix.parts().chain(relative.parts()).collect())
+    }
+
+    fn relative(&self, path: &ObjectPath) -> Option<String> {
+        let parts: Vec<_> = path.prefix_match(&self.prefix)?.collect();
+        Some(
+            parts
+                .iter()
+                .map(|part| part.as_ref())
+                .collect::<Vec<_>>()
+                .join("/"),
+        )
+    }
+}
+
+/// Reads an object store download from blocking code, a chunk at a time
+struct DownloadReader<S, B> {
+    handle: Handle,
+    stream: S,
+    chunk: Option<B>,
+    offset: usize,
+}
+
+impl<S, B> DownloadReader<S, B> {
+    fn new(handle: Handle, stream: S) -> Self {
+        Self {
+            handle,
+            stream,
+            chunk: None,
+            offset: 0,
+        }
+    }
+}
+
+impl<S, B> Read for DownloadReader<S, B>
+where
+    S: Stream<Item = object_store::Result<B>> + Unpin,
+    B: AsRef<[u8]>,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            if let Some(chunk) = &self.chunk {
+                let rest = &chunk.as_ref()[self.offset..];
+                if !rest.is_empty() {
+                    let len = rest.len().min(buf.len());
+                    buf[..len].copy_from_slice(&rest[..len]);
+                    self.offset += len;
+                    return Ok(len);
+                }
+            }
+            match self.handle.block_on(self.stream.next()) {
+                Some(chunk) => {
+                    self.chunk = Some(chunk.map_err(io::Error::other)?);
+                    self.offset = 0;
+                },
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+/// Writes a snapshot to a multipart upload from blocking code, waiting
+/// whenever [`PARTS_IN_FLIGHT`] parts are still being sent
+struct UploadWriter {
+    handle: Handle,
+    upload: WriteMultipart,
+    written: u64,
+}
+
+impl Write for UploadWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.handle
+            .block_on(self.upload.wait_for_capacity(PARTS_IN_FLIGHT))
+            .map_err(io::Error::other)?;
+        self.upload.write(buf);
+        self.written += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Service that snapshots the store's files to a backup location and
+/// restores them.
+///
+/// A snapshot is a gzipped tar of every file under the storage URI: the
+/// artifacts table, the archive and their version history. Version history
+/// lives in the same files as the data, so it is no protection against
+/// losing the bucket or directory; snapshots kept elsewhere are.
+///
+/// Files are streamed between the store and the backup location, so neither
+/// a snapshot nor the store has to fit in memory. A local store is
+/// snapshotted and restored under its writer lock, so a commit can't land
+/// halfway through; writes to an S3 store can't be held off that way, so
+/// back those up when nothing is writing, or rely on the snapshot only for
+/// versions committed before it started.
+pub struct BackupService {
+    source: Location,
+    target: Location,
+    /// Writer lock guarding a local store
+    lock_path: Option<PathBuf>,
+    keep: Option<usize>,
+    commit_store: bool,
+}
+
+impl BackupService {
+    /// Back up the store at `storage_uri` to `location`; `s3` applies to
+    /// whichever of the two is on S3
+    pub fn new(storage_uri: &str, location: &str, s3: &S3Config) -> Result<Self> {
+        Ok(Self {
+            source: Location::open(storage_uri, s3)?,
+            target: Location::open(location, s3)?,
+            lock_path: (!is_s3_uri(storage_uri)).then(|| WriterLock::path_for(storage_uri)),
+            keep: None,
+            commit_store: s3.commit_table().is_some(),
+        })
+    }
+
+    /// Number of snapshots `prune` keeps
+    pub fn with_keep(mut self, keep: Option<usize>) -> Self {
+        self.keep = keep;
+        self
+    }
+
+    /// Hold off writers to a local store; see the type docs for S3
+    async fn lock_source(&self) -> Result<Option<WriterLock>> {
+        match &self.lock_path {
+            Some(path) => Ok(Some(WriterLock::acquire(path, lock::WAIT_FOR).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Write a new snapshot of the store
+    pub async fn create(&self) -> Result<BackupInfo> {
+        let _lock = self.lock_source().await?;
+        let files: Vec<_> = self
+            .source
+            .list()
+            .await?
+            .into_iter()
+            .filter_map(|file| Some((self.source.relative(&file.location)?, file)))
+            .collect();
+        if files.is_empty() {
+            anyhow::bail!("The store is empty; there is nothing to back up");
+        }
+
+        // Millisecond precision, as recorded in the name
+        let created_at = Utc::now().trunc_subsecs(3);
+        let name = format!(
+            "{}{}{}",
+            NAME_PREFIX,
+            created_at.format(NAME_TIME_FORMAT),
+            NAME_SUFFIX
+        );
+        let upload = self
+            .target
+            .store
+            .put_multipart(&self.target.path(&name)?)
+            .await
+            .with_context(|| format!("Failed to write backup {}", name))?;
+
+        let handle = Handle::current();
+        let source = self.source.clone();
+        let mut writer = UploadWriter {
+            handle: handle.clone(),
+            upload: WriteMultipart::new(upload),
+            written: 0,
+        };
+        let (count, size) = tokio::task::spawn_blocking(move || {
+            match write_archive(&handle, &source, &files, &mut writer) {
+                Ok(count) => {
+                    handle.block_on(writer.upload.finish())?;
+                    Ok((count, writer.written))
+                },
+                Err(err) => {
+                    // Don't leave a partial snapshot behind
+                    let _ = handle.block_on(writer.upload.abort());
+                    Err(err)
+                },
+            }
+        })
+        .await?
+        .with_context(|| format!("Failed to write backup {}", name))?;
+
+        tracing::info!("Backed up {} files to {}", count, name);
+        Ok(BackupInfo {
+            name,
+            created_at,
+            size,
+        })
+    }
+
+    /// Snapshots in the backup location, newest first
+    pub async fn list(&self) -> Result<Vec<BackupInfo>> {
+        let mut backups: Vec<_> = self
+            .target
+            .list()
+            .await?
+            .into_iter()
+            .filter_map(|file| {
+                let name = self.target.relative(&file.location)?;
+                let stamp = name.strip_prefix(NAME_PREFIX)?.strip_suffix(NAME_SUFFIX)?;
+                let created_at = NaiveDateTime::parse_from_str(stamp, NAME_TIME_FORMAT)
+                    .map(|t| t.and_utc())
+                    .unwrap_or(file.last_modified);
+                Some(BackupInfo {
+                    name,
+                    created_at,
+                    size: file.size,
+                })
+            })
+            .collect();
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
+        Ok(backups)
+    }
+
+    /// Delete all but the newest snapshots, per `with_keep`; returns the deleted ones
+    pub async fn prune(&self) -> Result<Vec<BackupInfo>> {
+        let Some(keep) = self.keep else {
+            return Ok(Vec::new());
+        };
+        let old: Vec<_> = self.list().await?.into_iter().skip(keep).collect();
+        for backup in &old {
+            self.target
+                .store
+                .delete(&self.target.path(&backup.name)?)
+                .await
+                .with_context(|| format!("Failed to delete backup {}", backup.name))?;
+        }
+        Ok(old)
+    }
+
+    /// Replace the store's files with a snapshot's, by name or `latest`.
+    ///
+    /// Returns the number of files restored. Writes to a local store wait
+    /// for the restore; stop anything writing to an S3 store first, or its
+    /// writes can be lost. Files not in the snapshot are
+    /// deleted last, so an interrupted restore can be finished by running it
+    /// again.
+    pub async fn restore(&self, name: &str) -> Result<usize> {
+        if self.commit_store {
+            anyhow::bail!(
+                "Restoring is not supported with a commit store, which would still \
+                 point at the replaced versions"
+            );
+        }
+
+        let backups = self.list().await?;
+        let backup = if name == "latest" {
+            backups.first()
+        } else {
+            backups.iter().find(|b| b.name == name)
+        }
+        .with_context(|| format!("No backup named '{}'", name))?;
+
+        let _lock = self.lock_source().await?;
+        let download = self
+            .target
+            .store
+            .get(&self.target.path(&backup.name)?)
+            .await
+            .with_context(|| format!("Failed to read backup {}", backup.name))?;
+
+        let handle = Handle::current();
+        let source = self.source.clone();
+        let restored = tokio::task::spawn_blocking(move || {
+            let reader = DownloadReader::new(handle.clone(), download.into_stream());
+            let mut restored = HashSet::new();
+            let mut archive = tar::Archive::new(GzDecoder::new(reader));
+            // One file at a time, written as soon as it is read
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                let relative = entry.path()?.to_string_lossy().to_string();
+                let path = source
+                    .path(&relative)
+                    .with_context(|| format!("Invalid path '{}' in backup", relative))?;
+                let mut content = Vec::new();
+                entry.read_to_end(&mut content)?;
+                handle
+                    .block_on(source.store.put(&path, PutPayload::from(content)))
+                    .with_context(|| format!("Failed to write {}", path))?;
+                restored.insert(path);
+            }
+            anyhow::Ok(restored)
+        })
+        .await?
+        .with_context(|| format!("Failed to restore backup {}", backup.name))?;
+        if restored.is_empty() {
+            anyhow::bail!("Backup {} is empty", backup.name);
+        }
+
+        for file in self.source.list().await? {
+            if !restored.contains(&file.location) {
+                self.source.store.delete(&file.location).await?;
+            }
+        }
+        let count = restored.len();
+
+        tracing::info!("Restored {} files from {}", count, backup.name);
+        Ok(count)
+    }
+}
+
+/// Tar and gzip `files` from `source` into `writer`, one file at a time;
+/// returns the number of files written.
+///
+/// Files deleted since they were listed are skipped: on S3 a writer can
+/// clean up old versions while a snapshot is taken.
+fn write_archive(
+    handle: &Handle,
+    source: &Location,
+    files: &[(String, ObjectMeta)],
+    writer: &mut UploadWriter,
+) -> Result<usize> {
+    let mut archive = tar::Builder::new(GzEncoder::new(writer, Compression::default()));
+    let mut count = 0;
+    for (relative, file) in files {
+        let download = match handle.block_on(source.store.get(&file.location)) {
+            Ok(download) => download,
+            Err(object_store::Error::NotFound { .. }) => {
+                tracing::warn!("Skipping {}, deleted while backing up", file.location);
+                continue;
+            },
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to read {}", file.location))
+            },
+        };
+        let mut header = tar::Header::new_gnu();
+        header.set_size(download.meta.size);
+        header.set_mode(0o644);
+        header.set_mtime(download.meta.last_modified.timestamp().max(0) as u64);
+        let reader = DownloadReader::new(handle.clone(), download.into_stream());
+        archive
+            .append_data(&mut header, relative, reader)
+            .with_context(|| format!("Failed to read {}", file.location))?;
+        count += 1;
+    }
+    archive.into_inner()?.finish()?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &std::path::Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[tokio::test]
+    async fn restores_the_store_as_it_was() {
+        let dir = TempDir::new().unwrap();
+        let store = dir.path().join("artifacts.lance");
+        write(&store, "artifacts.lance/_versions/1.manifest", "v1");
+        write(&store, "artifacts.lance/data/a.lance", "rows");
+        let service = BackupService::new(
+            store.to_str().unwrap(),
+            dir.path().join("backups").to_str().unwrap(),
+            &S3Config::default(),
+        )
+        .unwrap();
+
+        let backup = service.create().await.unwrap();
+        assert!(backup.name.starts_with(NAME_PREFIX), "{}", backup.name);
+        assert_eq!(service.list().await.unwrap(), vec![backup.clone()]);
+
+        write(&store, "artifacts.lance/_versions/2.manifest", "v2");
+        write(&store, "artifacts.lance/data/a.lance", "damaged");
+
+        assert_eq!(service.restore("latest").await.unwrap(), 2);
+        let read = |relative: &str| fs::read_to_string(store.join(relative)).ok();
+        assert_eq!(
+            read("artifacts.lance/data/a.lance").as_deref(),
+            Some("rows")
+        );
+        assert_eq!(
+            read("artifacts.lance/_versions/1.manifest").as_deref(),
+            Some("v1")
+        );
+        assert_eq!(read("artifacts.lance/_versions/2.manifest"), None);
+
+        let err = service.restore("dna-backup-missing").await.unwrap_err();
+        assert!(err.to_string().contains("No backup named"), "{err}");
+    }
+
+    #[tokio::test]
+    async fn streams_files_larger_than_an_upload_part() {
+        let dir = TempDir::new().unwrap();
+        let store = dir.path().join("store");
+        // Noise doesn't compress, so the snapshot spans several 5 MB parts
+        let mut seed = 0x2545_f491_4f6c_dd1du64;
+        let rows: Vec<u8> = (0..12 * 1024 * 1024)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                seed as u8
+            })
+            .collect();
+        fs::create_dir_all(store.join("artifacts.lance/data")).unwrap();
+        fs::write(store.join("artifacts.lance/data/a.lance"), &rows).unwrap();
+        let service = BackupService::new(
+            store.to_str().unwrap(),
+            dir.path().join("backups").to_str().unwrap(),
+            &S3Config::default(),
+        )
+        .unwrap();
+
+        let backup = service.create().await.unwrap();
+        assert!(backup.size > rows.len() as u64, "{}", backup.size);
+        assert_eq!(service.list().await.unwrap(), vec![backup]);
+
+        fs::write(store.join("artifacts.lance/data/a.lance"), "damaged").unwrap();
+        assert_eq!(service.restore("latest").await.unwrap(), 1);
+        assert!(fs::read(store.join("artifacts.lance/data/a.lance")).unwrap() == rows);
+    }
+
+    #[tokio::test]
+    async fn prune_keeps_the_newest_backups() {
+        let dir = TempDir::new().unwrap();
+        let store = dir.path().join("store");
+        write(&store, "artifacts.lance/data/a.lance", "rows");
+        let service = BackupService::new(
+            store.to_str().unwrap(),
+            dir.path().join("backups").to_str().unwrap(),
+            &S3Config::default(),
+        )
+        .unwrap()
+        .with_keep(Some(2));
+
+        let mut created = Vec::new();
+        for _ in 0..3 {
+            created.push(service.create().await.unwrap());
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+
+        let removed = service.prune().await.unwrap();
+        assert_eq!(removed, vec![created[0].clone()]);
+        let names: Vec<_> = service
+            .list()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|b| b.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![created[2].name.clone(), created[1].name.clone()]
+        );
+    }
+}
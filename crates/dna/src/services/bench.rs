@@ -0,0 +1,288 @@
+//! Retrieval quality benchmarks.
+//!
+//! A golden set maps queries to the artifacts a good search should return.
+//! Running it under the current model and search settings gives recall@k and
+//! mean reciprocal rank, so models and chunking strategies can be compared
+//! before migrating a store to them.
+
+use super::types::{Artifact, SearchFilters, SearchWeights};
+use super::{SearchService, ServiceError};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Results scored per query when neither the golden set nor the caller says
+pub const DEFAULT_BENCH_K: usize = 10;
+
+/// Queries and the artifacts each should retrieve.
+///
+/// ```yaml
+/// k: 5
+/// queries:
+///   - query: how do users sign in?
+///     expected: [k7v3m9, x2p9q4]
+///   - query: retention period for audit logs
+///     kind: constraint
+///     expected: [m4n8r2]
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoldenSet {
+    /// Results scored per query
+    #[serde(default)]
+    pub k: Option<usize>,
+    pub queries: Vec<GoldenQuery>,
+}
+
+/// A query with the artifacts it should retrieve
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GoldenQuery {
+    pub query: String,
+    /// IDs of the relevant artifacts; former IDs of renamed artifacts match
+    pub expected: Vec<String>,
+    /// Only search artifacts of this kind
+    #[serde(default)]
+    pub kind: Option<String>,
+    /// Only search artifacts in this namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+}
+
+impl GoldenSet {
+    /// Parse a golden set from YAML (or JSON, which is valid YAML)
+    pub fn parse(text: &str) -> Result<Self> {
+        let golden: Self = serde_yaml::from_str(text).context("Failed to parse golden set")?;
+        golden.validate()?;
+        Ok(golden)
+    }
+
+    fn validate(&self) -> Result<(), ServiceError> {
+        if self.queries.is_empty() {
+            return Err(ServiceError::Validation(
+                "Golden set has no queries".to_string(),
+            ));
+        }
+        if self.k == Some(0) {
+            return Err(ServiceError::Validation("k must be at least 1".to_string()));
+        }
+        for (i, q) in self.queries.iter().enumerate() {
+            if q.query.trim().is_empty() {
+                return Err(ServiceError::Validation(format!(
+                    "Query {} is empty",
+                    i + 1
+                )));
+            }
+            if q.expected.is_empty() {
+                return Err(ServiceError::Validation(format!(
+                    "Query {} ({:?}) expects no artifacts",
+                    i + 1,
+                    q.query
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How one golden query fared
+#[derive(Debug, Clone, Serialize)]
+pub struct QueryScore {
+    pub query: String,
+    pub expected: Vec<String>,
+    /// IDs of the top k results, best first
+    pub retrieved: Vec<String>,
+    /// Expected IDs not among the top k
+    pub missed: Vec<String>,
+    /// Share of the expected artifacts among the top k
+    pub recall: f64,
+    /// 1 / rank of the first expected artifact, or 0 if none was retrieved
+    pub reciprocal_rank: f64,
+}
+
+/// Retrieval quality of a golden set under one model and set of settings
+#[derive(Debug, Clone, Serialize)]
+pub struct RetrievalReport {
+    pub model: String,
+    pub k: usize,
+    /// Mean recall@k over the queries
+    pub recall: f64,
+    /// Mean reciprocal rank over the queries
+    pub mrr: f64,
+    pub queries: Vec<QueryScore>,
+}
+
+/// Service running golden sets against search
+pub struct BenchService {
+    search: SearchService,
+    weights: SearchWeights,
+    boosts: HashMap<String, f32>,
+}
+
+impl BenchService {
+    /// Create a bench service searching with `search`
+    pub fn new(search: SearchService) -> Self {
+        Self {
+            search,
+            weights: SearchWeights::default(),
+            boosts: HashMap::new(),
+        }
+    }
+
+    /// Blend content and context similarity as `dna search --weights` does
+    pub fn with_weights(mut self, weights: SearchWeights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// Per-kind boosts overriding the search service's defaults
+    pub fn with_boosts(mut self, boosts: HashMap<String, f32>) -> Self {
+        self.boosts = boosts;
+        self
+    }
+
+    /// Run every query of `golden`, scoring the top `k` results.
+    ///
+    /// `k` overrides the golden set's own; without either,
+    /// [`DEFAULT_BENCH_K`] results are scored.
+    pub async fn retrieval(&self, golden: &GoldenSet, k: Option<usize>) -> Result<RetrievalReport> {
+        let k = k.or(golden.k).unwrap_or(DEFAULT_BENCH_K);
+        if k == 0 {
+            return Err(ServiceError::Validation("k must be at least 1".to_string()).into());
+        }
+
+        let mut queries = Vec::with_capacity(golden.queries.len());
+        for golden_query in &golden.queries {
+            let filters = SearchFilters {
+                kind: golden_query.kind.clone(),
+                namespace: golden_query.namespace.clone(),
+                limit: Some(k),
+                ..Default::default()
+            };
+            let results = self
+                .search
+                .search_boosted(&golden_query.query, filters, self.weights, &self.boosts)
+                .await
+                .with_context(|| format!("Failed to search for {:?}", golden_query.query))?;
+            let retrieved: Vec<&Artifact> = results.iter().take(k).map(|r| &r.artifact).collect();
+            queries.push(score_query(golden_query, &retrieved));
+        }
+
+        Ok(RetrievalReport {
+            model: self.search.model_id().to_string(),
+            k,
+            recall: mean(queries.iter().map(|q| q.recall)),
+            mrr: mean(queries.iter().map(|q| q.reciprocal_rank)),
+            queries,
+        })
+    }
+}
+
+/// Whether `artifact` is the one `id` names, now or before a rename
+fn is_match(artifact: &Artifact, id: &str) -> bool {
+    artifact.id == id || artifact.aliases.iter().any(|a| a == id)
+}
+
+fn score_query(golden: &GoldenQuery, retrieved: &[&Artifact]) -> QueryScore {
+    let missed: Vec<String> = golden
+        .expected
+        .iter()
+        .filter(|id| !retrieved.iter().any(|a| is_match(a, id)))
+        .cloned()
+        .collect();
+    let found = golden.expected.len() - missed.len();
+    let first_hit = retrieved
+        .iter()
+        .position(|a| golden.expected.iter().any(|id| is_match(a, id)));
+
+    QueryScore {
+        query: golden.query.clone(),
+        expected: golden.expected.clone(),
+        retrieved: retrieved.iter().map(|a| a.id.clone()).collect(),
+        missed,
+        recall: found as f64 / golden.expected.len() as f64,
+        reciprocal_rank: first_hit.map_or(0.0, |rank| 1.0 / (rank + 1) as f64),
+    }
+}
+
+fn mean(values: impl Iterator<Item = f64>) -> f64 {
+    let (sum, count) = values.fold((0.0, 0usize), |(sum, n), v| (sum + v, n + 1));
+    if count == 0 {
+        0.0
+    } else {
+        sum / count as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::types::ContentFormat;
+
+    fn artifact(id: &str) -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            format!("artifact {}", id),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "test".to_string(),
+        );
+        artifact.id = id.to_string();
+        artifact
+    }
+
+    fn query(expected: &[&str]) -> GoldenQuery {
+        GoldenQuery {
+            query: "how do users sign in?".to_string(),
+            expected: expected.iter().map(|s| s.to_string()).collect(),
+            kind: None,
+            namespace: None,
+        }
+    }
+
+    #[test]
+    fn scores_recall_and_reciprocal_rank() {
+        let a = artifact("aaa");
+        let b = artifact("bbb");
+        let c = artifact("ccc");
+        let retrieved = [&a, &b, &c];
+
+        let score = score_query(&query(&["bbb", "zzz"]), &retrieved);
+        assert_eq!(score.recall, 0.5);
+        assert_eq!(score.reciprocal_rank, 0.5);
+        assert_eq!(score.missed, vec!["zzz".to_string()]);
+        assert_eq!(score.retrieved, vec!["aaa", "bbb", "ccc"]);
+
+        let score = score_query(&query(&["zzz"]), &retrieved);
+        assert_eq!(score.recall, 0.0);
+        assert_eq!(score.reciprocal_rank, 0.0);
+    }
+
+    #[test]
+    fn former_ids_match_renamed_artifacts() {
+        let mut renamed = artifact("new-id");
+        renamed.aliases.push("old-id".to_string());
+
+        let score = score_query(&query(&["old-id"]), &[&renamed]);
+        assert_eq!(score.recall, 1.0);
+        assert_eq!(score.reciprocal_rank, 1.0);
+        assert!(score.missed.is_empty());
+    }
+
+    #[test]
+    fn parse_rejects_queries_without_expectations() {
+        let golden = GoldenSet::parse(
+            "k: 3\nqueries:\n  - query: sign in\n    expected: [aaa]\n    kind: intent\n",
+        )
+        .unwrap();
+        assert_eq!(golden.k, Some(3));
+        assert_eq!(golden.queries[0].kind.as_deref(), Some("intent"));
+
+        assert!(GoldenSet::parse("queries: []\n").is_err());
+        assert!(GoldenSet::parse("queries:\n  - query: sign in\n    expected: []\n").is_err());
+        assert!(
+            GoldenSet::parse("queries:\n  - query: x\n    expected: [a]\n    top: 3\n").is_err()
+        );
+    }
+}
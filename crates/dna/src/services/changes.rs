@@ -0,0 +1,766 @@
+//! Reports of what changed in a time window, for `dna diff` and `dna_changes`.
+//!
+//! A report is built either from `updated_at` timestamps, which is cheap but
+//! cannot see deletions, or from the store's version history by [`history`].
+//! [`ChangeWatcher`] follows changes as they are committed, for `dna watch`.
+
+use super::types::{Artifact, SearchFilters};
+use crate::db::{Database, VersionInfo};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[cfg(feature = "openapi")]
+use utoipa::ToSchema;
+
+/// How an artifact changed within the window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeType {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl std::fmt::Display for ChangeType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeType::Created => write!(f, "created"),
+            ChangeType::Updated => write!(f, "updated"),
+            ChangeType::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
+/// An artifact changed within the window
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct ChangedArtifact {
+    pub change: ChangeType,
+    /// Version of the artifact's last change, when read from the history
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<u64>,
+    /// The artifact after the change; for deletions, its last stored state
+    #[serde(flatten)]
+    pub artifact: Artifact,
+}
+
+impl ChangedArtifact {
+    /// Classify an artifact updated in a window starting at `after`.
+    ///
+    /// It counts as created when its creation falls in the window too. With
+    /// no start to the window, only artifacts never updated since their
+    /// creation count as created.
+    pub fn from_timestamps(artifact: Artifact, after: Option<DateTime<Utc>>) -> Self {
+        let created = match after {
+            Some(after) => artifact.created_at >= after,
+            None => artifact.created_at == artifact.updated_at,
+        };
+        Self {
+            change: if created {
+                ChangeType::Created
+            } else {
+                ChangeType::Updated
+            },
+            version: None,
+            artifact,
+        }
+    }
+}
+
+/// Changes to the artifacts of one kind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct KindChanges {
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+}
+
+/// Artifacts changed in a time window, counted by kind
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeReport {
+    /// Plain-text description of the counts, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<DateTime<Utc>>,
+    pub total: usize,
+    pub kinds: BTreeMap<String, KindChanges>,
+    /// Most recent first
+    pub changes: Vec<ChangedArtifact>,
+    /// Oldest version left, when the window reaches back past it to
+    /// versions that were cleaned up
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub truncated_at: Option<u64>,
+}
+
+impl ChangeReport {
+    /// Report on `artifacts`, the ones updated between `after` and `before`,
+    /// classified by [`ChangedArtifact::from_timestamps`]
+    pub fn new(
+        artifacts: Vec<Artifact>,
+        after: Option<DateTime<Utc>>,
+        before: Option<DateTime<Utc>>,
+    ) -> Self {
+        let changes = artifacts
+            .into_iter()
+            .map(|artifact| ChangedArtifact::from_timestamps(artifact, after))
+            .collect();
+        Self::from_changes(changes, after, before)
+    }
+
+    /// Report on changes made between `after` and `before`
+    pub fn from_changes(
+        mut changes: Vec<ChangedArtifact>,
+        after: Option<DateTime<Utc>>,
+        before: Option<DateTime<Utc>>,
+    ) -> Self {
+        changes.sort_by(|a, b| {
+            b.version
+                .cmp(&a.version)
+                .then_with(|| b.artifact.updated_at.cmp(&a.artifact.updated_at))
+                .then_with(|| a.artifact.id.cmp(&b.artifact.id))
+        });
+
+        let mut kinds: BTreeMap<String, KindChanges> = BTreeMap::new();
+        for change in &changes {
+            let counts = kinds.entry(change.artifact.kind.clone()).or_default();
+            match change.change {
+                ChangeType::Created => counts.created += 1,
+                ChangeType::Updated => counts.updated += 1,
+                ChangeType::Deleted => counts.deleted += 1,
+            }
+        }
+
+        Self {
+            summary: None,
+            after,
+            before,
+            total: changes.len(),
+            kinds,
+            changes,
+            truncated_at: None,
+        }
+    }
+
+    /// Report on changes read from the version history
+    pub fn from_history(
+        history: ChangeHistory,
+        after: Option<DateTime<Utc>>,
+        before: Option<DateTime<Utc>>,
+    ) -> Self {
+        let mut report = Self::from_changes(history.changes, after, before);
+        report.truncated_at = history.truncated_at;
+        report
+    }
+
+    /// Why the report may be missing changes, when the history it was read
+    /// from was cut short
+    pub fn truncation_note(&self) -> Option<String> {
+        self.truncated_at.map(|version| {
+            format!(
+                "History before version {} was cleaned up, so earlier changes are missing.",
+                version
+            )
+        })
+    }
+
+    /// Include the [`summarize`](Self::summarize) text in the report
+    pub fn with_summary(mut self) -> Self {
+        self.summary = Some(self.summarize());
+        self
+    }
+
+    /// Describe the counts in a sentence or two, e.g. "3 artifacts changed
+    /// since 2024-06-01: 1 created, 2 updated. intent: 1 created, 1 updated;
+    /// contract: 1 updated."
+    pub fn summarize(&self) -> String {
+        let mut window = String::new();
+        if let Some(after) = self.after {
+            window.push_str(&format!(" since {}", describe(after)));
+        }
+        if let Some(before) = self.before {
+            window.push_str(&format!(" before {}", describe(before)));
+        }
+        let note = self
+            .truncation_note()
+            .map(|note| format!(" {}", note))
+            .unwrap_or_default();
+        if self.total == 0 {
+            return format!("No artifacts changed{}.{}", window, note);
+        }
+
+        let totals = self
+            .kinds
+            .values()
+            .fold(KindChanges::default(), |sum, k| KindChanges {
+                created: sum.created + k.created,
+                updated: sum.updated + k.updated,
+                deleted: sum.deleted + k.deleted,
+            });
+        let per_kind: Vec<String> = self
+            .kinds
+            .iter()
+            .map(|(kind, counts)| format!("{}: {}", kind, counts_text(counts)))
+            .collect();
+        format!(
+            "{} artifact{} changed{}: {}. {}.{}",
+            self.total,
+            if self.total == 1 { "" } else { "s" },
+            window,
+            counts_text(&totals),
+            per_kind.join("; "),
+            note
+        )
+    }
+}
+
+/// "1 created, 2 updated", leaving out zero counts
+fn counts_text(counts: &KindChanges) -> String {
+    [
+        (counts.created, "created"),
+        (counts.updated, "updated"),
+        (counts.deleted, "deleted"),
+    ]
+    .iter()
+    .filter(|(count, _)| *count > 0)
+    .map(|(count, change)| format!("{} {}", count, change))
+    .collect::<Vec<_>>()
+    .join(", ")
+}
+
+/// Changes read from the store's version history by [`history`]
+#[derive(Debug, Clone, Default)]
+pub struct ChangeHistory {
+    pub changes: Vec<ChangedArtifact>,
+    /// The oldest version left, when the window reaches back past it to
+    /// versions that were cleaned up. Changes committed before it are
+    /// missing rather than guessed at.
+    pub truncated_at: Option<u64>,
+}
+
+/// Changes committed between `filters.after` and `filters.before`, found by
+/// comparing consecutive versions of the store.
+///
+/// The time filters apply to when versions were committed, so unlike
+/// `updated_at` they also catch deletions; the other filters apply to the
+/// artifacts. Each artifact appears once with its net change over the window
+/// and the version of its last change. Archiving counts as a deletion, and
+/// artifacts both created and deleted within the window are left out. Each
+/// version in the window is read only as far as when its artifacts were last
+/// updated, and just the artifacts that changed are read in full.
+///
+/// When the versions at the start of the window were cleaned up, the oldest
+/// one left is the baseline, and the result says the history was truncated.
+pub async fn history(db: &dyn Database, filters: &SearchFilters) -> Result<ChangeHistory> {
+    let mut versions = db.list_versions(None).await?;
+    versions.sort_by_key(|v| v.version);
+    let in_window = |v: &VersionInfo| {
+        filters.after.is_none_or(|after| v.timestamp >= after)
+            && filters.before.is_none_or(|before| v.timestamp < before)
+    };
+    let Some(start) = versions.iter().position(in_window) else {
+        return Ok(ChangeHistory::default());
+    };
+    let end = versions.iter().rposition(in_window).unwrap_or(start);
+
+    // Version 1 is the empty store; any later version listed first is all
+    // that's left after cleanup, so it stands in for what came before
+    let (mut previous, mut previous_version, first, truncated_at) = match start.checked_sub(1) {
+        Some(i) => {
+            let version = versions[i].version;
+            (db.updated_at_version(version).await?, version, start, None)
+        },
+        None if versions[start].version > 1 => {
+            let oldest = versions[start].version;
+            let updated = db.updated_at_version(oldest).await?;
+            (updated, oldest, start + 1, Some(oldest))
+        },
+        None => (HashMap::new(), 0, start, None),
+    };
+    let existed: HashSet<String> = previous.keys().cloned().collect();
+
+    // Last change to each artifact: the version of the change and the
+    // version its state afterwards (or before, if deleted) is read from
+    let mut touched: HashMap<String, (u64, u64)> = HashMap::new();
+    for info in &versions[first..end + 1] {
+        let current = db.updated_at_version(info.version).await?;
+        for (id, updated_at) in &current {
+            if previous.get(id) != Some(updated_at) {
+                touched.insert(id.clone(), (info.version, info.version));
+            }
+        }
+        for id in previous.keys() {
+            if !current.contains_key(id) {
+                touched.insert(id.clone(), (info.version, previous_version));
+            }
+        }
+        previous = current;
+        previous_version = info.version;
+    }
+
+    // Only the artifacts that changed are read in full
+    let mut changed: Vec<(String, ChangeType, u64)> = Vec::new();
+    let mut reads: HashMap<u64, Vec<String>> = HashMap::new();
+    for (id, (version, read_at)) in touched {
+        let change = match (existed.contains(&id), previous.contains_key(&id)) {
+            (false, true) => ChangeType::Created,
+            (true, true) => ChangeType::Updated,
+            (true, false) => ChangeType::Deleted,
+            (false, false) => continue,
+        };
+        reads.entry(read_at).or_default().push(id.clone());
+        changed.push((id, change, version));
+    }
+    let mut artifacts: HashMap<String, Artifact> = HashMap::new();
+    for (version, ids) in reads {
+        for artifact in db.get_many_at_version(&ids, version).await? {
+            artifacts.insert(artifact.id.clone(), artifact);
+        }
+    }
+
+    let artifact_filters = SearchFilters {
+        after: None,
+        before: None,
+        ..filters.clone()
+    };
+    let changes = changed
+        .into_iter()
+        .filter_map(|(id, change, version)| {
+            let artifact = artifacts.remove(&id)?;
+            artifact_filters
+                .matches(&artifact)
+                .then_some(ChangedArtifact {
+                    change,
+                    version: Some(version),
+                    artifact,
+                })
+        })
+        .collect();
+    Ok(ChangeHistory {
+        changes,
+        truncated_at,
+    })
+}
+
+/// A change seen by [`ChangeWatcher`]
+#[derive(Debug, Clone)]
+pub struct WatchedChange {
+    pub change: ChangeType,
+    /// Store version the change was seen at
+    pub version: u64,
+    /// The artifact before the change; None when created
+    pub before: Option<Artifact>,
+    /// The artifact after the change; None when deleted
+    pub after: Option<Artifact>,
+}
+
+impl WatchedChange {
+    /// The artifact after the change, or its last state when deleted
+    pub fn artifact(&self) -> &Artifact {
+        self.after
+            .as_ref()
+            .or(self.before.as_ref())
+            .expect("a change has a state before or after it")
+    }
+
+    /// Names of the parts of an updated artifact that changed, such as
+    /// `content` and `labels`; empty for creations and deletions
+    pub fn changed_fields(&self) -> Vec<&'static str> {
+        let (Some(before), Some(after)) = (&self.before, &self.after) else {
+            return Vec::new();
+        };
+        [
+            ("kind", before.kind != after.kind),
+            ("namespace", before.namespace != after.namespace),
+            ("name", before.name != after.name),
+            ("content", before.content != after.content),
+            ("labels", before.metadata != after.metadata),
+            ("context", before.context != after.context),
+            ("aliases", before.aliases != after.aliases),
+            ("owner", before.owner != after.owner),
+            ("reviewers", before.reviewers != after.reviewers),
+        ]
+        .into_iter()
+        .filter_map(|(field, changed)| changed.then_some(field))
+        .collect()
+    }
+}
+
+/// Follows the store, reporting the changes committed since it last looked.
+///
+/// Each [`poll`](Self::poll) compares the store's latest version with the
+/// one seen before, so changes made and undone between polls go unseen.
+pub struct ChangeWatcher {
+    filters: SearchFilters,
+    version: u64,
+    seen: HashMap<String, Artifact>,
+}
+
+impl ChangeWatcher {
+    /// Start watching the artifacts that pass `filters` from the store's
+    /// current state.
+    ///
+    /// Time filters are ignored. An artifact counts as watched when it
+    /// passes the filters either before or after a change, so moving it to
+    /// another kind is reported too.
+    pub async fn start(db: &dyn Database, filters: SearchFilters) -> Result<Self> {
+        let version = db.version().await?;
+        Ok(Self {
+            filters: SearchFilters {
+                after: None,
+                before: None,
+                ..filters
+            },
+            version,
+            seen: snapshot(db, version).await?,
+        })
+    }
+
+    /// Version of the store last looked at
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    /// Changes committed since the last poll: creations and updates in the
+    /// order they were made, then deletions.
+    pub async fn poll(&mut self, db: &dyn Database) -> Result<Vec<WatchedChange>> {
+        let version = db.version().await?;
+        if version == self.version {
+            return Ok(Vec::new());
+        }
+        let current = snapshot(db, version).await?;
+
+        let mut changes: Vec<WatchedChange> = current
+            .values()
+            .filter_map(|artifact| {
+                let before = self.seen.get(&artifact.id);
+                let change = match before {
+                    None => ChangeType::Created,
+                    Some(old) if old.updated_at != artifact.updated_at => ChangeType::Updated,
+                    Some(_) => return None,
+                };
+                Some(WatchedChange {
+                    change,
+                    version,
+                    before: before.cloned(),
+                    after: Some(artifact.clone()),
+                })
+            })
+            .collect();
+        changes.sort_by(|a, b| {
+            let (a, b) = (a.artifact(), b.artifact());
+            a.updated_at
+                .cmp(&b.updated_at)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+
+        let mut deleted: Vec<WatchedChange> = self
+            .seen
+            .values()
+            .filter(|artifact| !current.contains_key(&artifact.id))
+            .map(|artifact| WatchedChange {
+                change: ChangeType::Deleted,
+                version,
+                before: Some(artifact.clone()),
+                after: None,
+            })
+            .collect();
+        deleted.sort_by(|a, b| a.artifact().id.cmp(&b.artifact().id));
+        changes.extend(deleted);
+
+        changes.retain(|change| {
+            [&change.before, &change.after]
+                .into_iter()
+                .flatten()
+                .any(|artifact| self.filters.matches(artifact))
+        });
+        self.version = version;
+        self.seen = current;
+        Ok(changes)
+    }
+}
+
+/// Active artifacts at a version, by ID
+async fn snapshot(db: &dyn Database, version: u64) -> Result<HashMap<String, Artifact>> {
+    Ok(db
+        .list_at_version(version)
+        .await?
+        .into_iter()
+        .map(|a| (a.id.clone(), a))
+        .collect())
+}
+
+/// A date alone when at midnight UTC, else the full timestamp
+pub(super) fn describe(dt: DateTime<Utc>) -> String {
+    if dt.time() == chrono::NaiveTime::MIN {
+        dt.format("%Y-%m-%d").to_string()
+    } else {
+        dt.to_rfc3339()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn artifact(id: &str, kind: &str, created: u32, updated: u32) -> Artifact {
+        let mut artifact = Artifact::new(
+            kind.to_string(),
+            format!("{} content", id),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        artifact.id = id.to_string();
+        artifact.created_at = Utc.with_ymd_and_hms(2024, 6, created, 9, 0, 0).unwrap();
+        artifact.updated_at = Utc.with_ymd_and_hms(2024, 6, updated, 9, 0, 0).unwrap();
+        artifact
+    }
+
+    #[test]
+    fn counts_created_and_updated_per_kind() {
+        let after = Utc.with_ymd_and_hms(2024, 6, 10, 0, 0, 0).unwrap();
+        let report = ChangeReport::new(
+            vec![
+                artifact("old", "intent", 1, 11),
+                artifact("new", "intent", 12, 12),
+                artifact("api", "contract", 5, 14),
+            ],
+            Some(after),
+            None,
+        );
+
+        assert_eq!(report.total, 3);
+        assert_eq!(
+            report.kinds["intent"],
+            KindChanges {
+                created: 1,
+                updated: 1,
+                deleted: 0
+            }
+        );
+        assert_eq!(
+            report.kinds["contract"],
+            KindChanges {
+                created: 0,
+                updated: 1,
+                deleted: 0
+            }
+        );
+        let order: Vec<(&str, ChangeType)> = report
+            .changes
+            .iter()
+            .map(|c| (c.artifact.id.as_str(), c.change))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                ("api", ChangeType::Updated),
+                ("new", ChangeType::Created),
+                ("old", ChangeType::Updated),
+            ]
+        );
+        assert_eq!(
+            report.summarize(),
+            "3 artifacts changed since 2024-06-10: 1 created, 2 updated. \
+             contract: 1 updated; intent: 1 created, 1 updated."
+        );
+    }
+
+    #[test]
+    fn unbounded_window_counts_never_updated_as_created() {
+        let report = ChangeReport::new(
+            vec![artifact("a", "intent", 3, 3), artifact("b", "intent", 3, 4)],
+            None,
+            None,
+        );
+        assert_eq!(
+            report.kinds["intent"],
+            KindChanges {
+                created: 1,
+                updated: 1,
+                deleted: 0
+            }
+        );
+    }
+
+    #[test]
+    fn summary_is_opt_in_and_handles_no_changes() {
+        let report = ChangeReport::new(Vec::new(), None, None);
+        let json = serde_json::to_value(&report).unwrap();
+        assert!(json.get("summary").is_none());
+
+        let json = serde_json::to_value(report.with_summary()).unwrap();
+        assert_eq!(json["summary"], "No artifacts changed.");
+        assert_eq!(json["total"], 0);
+    }
+
+    #[tokio::test]
+    async fn history_reports_deletions_with_versions() {
+        let db = crate::testing::TestDatabase::new();
+        let mut kept = artifact("kept", "intent", 1, 1);
+        let removed = artifact("removed", "contract", 1, 1);
+        db.insert(&kept).await.unwrap();
+        db.insert(&removed).await.unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let after = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+
+        kept.updated_at = Utc::now();
+        db.update(&kept).await.unwrap(); // version 4
+        db.delete("removed").await.unwrap(); // version 5
+        db.insert(&artifact("added", "intent", 20, 20))
+            .await
+            .unwrap(); // version 6
+        db.insert(&artifact("brief", "intent", 20, 20))
+            .await
+            .unwrap();
+        db.delete("brief").await.unwrap();
+
+        let filters = SearchFilters {
+            after: Some(after),
+            ..Default::default()
+        };
+        let history_read = history(&db, &filters).await.unwrap();
+        assert_eq!(history_read.truncated_at, None);
+        let report = ChangeReport::from_history(history_read, Some(after), None);
+
+        let changes: Vec<(&str, ChangeType, Option<u64>)> = report
+            .changes
+            .iter()
+            .map(|c| (c.artifact.id.as_str(), c.change, c.version))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("added", ChangeType::Created, Some(6)),
+                ("removed", ChangeType::Deleted, Some(5)),
+                ("kept", ChangeType::Updated, Some(4)),
+            ]
+        );
+        assert_eq!(report.kinds["contract"].deleted, 1);
+
+        let intents = SearchFilters {
+            kind: Some("intent".to_string()),
+            ..filters
+        };
+        assert_eq!(history(&db, &intents).await.unwrap().changes.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn history_reports_signing_as_an_update() {
+        use crate::services::{ArtifactService, Signer};
+        use std::sync::Arc;
+
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        db.insert(&artifact("signed", "intent", 1, 1))
+            .await
+            .unwrap();
+        let service = ArtifactService::new(db.clone(), Arc::new(crate::testing::TestEmbedding))
+            .with_signer(Some(Signer::new("alice", &Signer::generate_key()).unwrap()));
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let after = Utc::now();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        service.sign("signed").await.unwrap();
+
+        let filters = SearchFilters {
+            after: Some(after),
+            ..Default::default()
+        };
+        let changes = history(db.as_ref(), &filters).await.unwrap().changes;
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].change, ChangeType::Updated);
+        assert_eq!(changes[0].artifact.metadata["signed_by"], "alice");
+    }
+
+    #[tokio::test]
+    async fn history_after_cleanup_starts_from_the_oldest_version_left() {
+        let db = crate::testing::TestDatabase::new();
+        let mut kept = artifact("kept", "intent", 1, 1);
+        db.insert(&kept).await.unwrap();
+        db.insert(&artifact("old", "intent", 1, 1)).await.unwrap();
+        db.cleanup_versions(1).await.unwrap(); // only version 3 is left
+
+        kept.updated_at = Utc::now();
+        db.update(&kept).await.unwrap(); // version 4
+        db.insert(&artifact("added", "contract", 20, 20))
+            .await
+            .unwrap(); // version 5
+
+        let history_read = history(&db, &SearchFilters::default()).await.unwrap();
+        assert_eq!(history_read.truncated_at, Some(3));
+        let report = ChangeReport::from_history(history_read, None, None).with_summary();
+        let changes: Vec<(&str, ChangeType)> = report
+            .changes
+            .iter()
+            .map(|c| (c.artifact.id.as_str(), c.change))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("added", ChangeType::Created),
+                ("kept", ChangeType::Updated),
+            ]
+        );
+        assert!(report
+            .summary
+            .unwrap()
+            .ends_with("History before version 3 was cleaned up, so earlier changes are missing."));
+    }
+
+    #[tokio::test]
+    async fn watcher_reports_changes_since_the_last_poll() {
+        let db = crate::testing::TestDatabase::new();
+        let mut kept = artifact("kept", "intent", 1, 1);
+        db.insert(&kept).await.unwrap();
+        db.insert(&artifact("removed", "intent", 1, 1))
+            .await
+            .unwrap();
+        db.insert(&artifact("other", "contract", 1, 1))
+            .await
+            .unwrap();
+
+        let filters = SearchFilters {
+            kinds: Some(vec!["intent".to_string()]),
+            ..Default::default()
+        };
+        let mut watcher = ChangeWatcher::start(&db, filters).await.unwrap();
+        assert!(watcher.poll(&db).await.unwrap().is_empty());
+
+        kept.content = "kept, reworded".to_string();
+        kept.metadata.insert("team".to_string(), "core".to_string());
+        kept.updated_at = Utc.with_ymd_and_hms(2024, 6, 3, 9, 0, 0).unwrap();
+        db.update(&kept).await.unwrap();
+        db.delete("removed").await.unwrap();
+        db.insert(&artifact("added", "intent", 2, 2)).await.unwrap();
+        db.delete("other").await.unwrap();
+
+        let changes = watcher.poll(&db).await.unwrap();
+        let seen: Vec<(&str, ChangeType)> = changes
+            .iter()
+            .map(|c| (c.artifact().id.as_str(), c.change))
+            .collect();
+        assert_eq!(
+            seen,
+            vec![
+                ("added", ChangeType::Created),
+                ("kept", ChangeType::Updated),
+                ("removed", ChangeType::Deleted),
+            ]
+        );
+        assert_eq!(changes[1].changed_fields(), vec!["content", "labels"]);
+        assert_eq!(changes[0].version, watcher.version());
+        assert!(watcher.poll(&db).await.unwrap().is_empty());
+    }
+}
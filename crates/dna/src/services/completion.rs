@@ -0,0 +1,154 @@
+//! Text written by a chat model, for the features that produce prose:
+//! `dna digest` summaries and `dna ask` answers.
+//!
+//! Any API compatible with OpenAI's chat completions works, which covers
+//! OpenAI itself, Ollama and most hosted or self-hosted model servers.
+
+use super::types::CompletionConfig;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// A model that replies to a prompt
+#[async_trait::async_trait]
+pub trait Completion: Send + Sync {
+    /// The model's reply to `prompt`, following the `system` instructions
+    async fn complete(&self, system: &str, prompt: &str) -> Result<String>;
+}
+
+/// Completion provider configured under `[completion]`, or `None` when no
+/// provider is set
+pub fn create_completion(config: &CompletionConfig) -> Result<Option<Arc<dyn Completion>>> {
+    let Some(provider) = config.provider.as_deref() else {
+        return Ok(None);
+    };
+    let (default_url, default_key_env) = match provider {
+        "openai" => ("https://api.openai.com/v1", Some("OPENAI_API_KEY")),
+        "ollama" => ("http://localhost:11434/v1", None),
+        _ => anyhow::bail!(
+            "Unknown completion provider '{}': use openai or ollama",
+            provider
+        ),
+    };
+    let model = config
+        .model
+        .as_deref()
+        .with_context(|| format!("The {} completion provider needs a model", provider))?;
+    let api_key = match config.api_key_env.as_deref().or(default_key_env) {
+        Some(var) => Some(std::env::var(var).with_context(|| {
+            format!("Environment variable {} with the API key is not set", var)
+        })?),
+        None => None,
+    };
+    let url = config.base_url.as_deref().unwrap_or(default_url);
+    Ok(Some(Arc::new(
+        ChatCompletion::new(url, model).with_api_key(api_key),
+    )))
+}
+
+/// A model behind an OpenAI-compatible chat completions API
+pub struct ChatCompletion {
+    client: reqwest::Client,
+    url: String,
+    model: String,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatMessage {
+    content: String,
+}
+
+impl ChatCompletion {
+    /// Chat with `model` at `url`, the API's base URL
+    pub fn new(url: &str, model: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Authenticate with an API key
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+}
+
+#[async_trait::async_trait]
+impl Completion for ChatCompletion {
+    async fn complete(&self, system: &str, prompt: &str) -> Result<String> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "messages": [
+                { "role": "system", "content": system },
+                { "role": "user", "content": prompt },
+            ],
+        });
+        let url = format!("{}/chat/completions", self.url);
+        let mut request = self.client.post(&url).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response: ChatResponse = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", url))?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("Unexpected chat response from {}", url))?;
+        let reply = response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content.trim().to_string())
+            .unwrap_or_default();
+        if reply.is_empty() {
+            anyhow::bail!("{} returned an empty reply", url);
+        }
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_is_optional_but_must_be_known() {
+        assert!(create_completion(&CompletionConfig::default())
+            .unwrap()
+            .is_none());
+
+        let ollama = CompletionConfig {
+            provider: Some("ollama".to_string()),
+            model: Some("llama3.2".to_string()),
+            ..Default::default()
+        };
+        assert!(create_completion(&ollama).unwrap().is_some());
+
+        let unknown = CompletionConfig {
+            provider: Some("parrot".to_string()),
+            ..Default::default()
+        };
+        assert!(create_completion(&unknown).is_err());
+        let no_model = CompletionConfig {
+            provider: Some("ollama".to_string()),
+            ..Default::default()
+        };
+        assert!(create_completion(&no_model).is_err());
+    }
+}
@@ -1,4 +1,9 @@
-use super::types::ProjectConfig;
+use super::types::{
+    AttachmentsConfig, BackupConfig, IdStrategy, LabelValueType, LintSeverity, ModelConfig,
+    ProjectConfig, StorageConfig, MIN_ID_LENGTH,
+};
+#[cfg(feature = "lance")]
+use crate::db::lance::LanceDatabase;
 use anyhow::{Context, Result};
 use figment::{
     providers::{Env, Format, Serialized, Toml},
@@ -6,6 +11,61 @@ use figment::{
 };
 use std::path::{Path, PathBuf};
 
+impl StorageConfig {
+    /// Storage URI with local paths resolved against `project_root`
+    pub fn resolve_uri(&self, project_root: &Path) -> String {
+        match &self.uri {
+            Some(uri) if super::is_s3_uri(uri) => uri.clone(),
+            Some(uri) => project_root.join(uri).to_string_lossy().to_string(),
+            None => project_root
+                .join(".dna")
+                .join("db")
+                .join("artifacts.lance")
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+
+    /// Open the store, applying any S3 connection options
+    #[cfg(feature = "lance")]
+    pub async fn open(&self, project_root: &Path) -> Result<LanceDatabase> {
+        Ok(LanceDatabase::new(&self.resolve_uri(project_root))
+            .await?
+            .with_s3_config(&self.s3)?
+            .with_embedding_precision(self.embedding_precision))
+    }
+}
+
+impl BackupConfig {
+    /// Backup location with local paths resolved against `project_root`
+    pub fn resolve_location(&self, project_root: &Path) -> String {
+        match &self.location {
+            Some(location) if super::is_s3_uri(location) => location.clone(),
+            Some(location) => project_root.join(location).to_string_lossy().to_string(),
+            None => project_root
+                .join(".dna")
+                .join("backups")
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+}
+
+impl AttachmentsConfig {
+    /// Attachment location with local paths resolved against `project_root`
+    pub fn resolve_location(&self, project_root: &Path) -> String {
+        match &self.location {
+            Some(location) if super::is_s3_uri(location) => location.clone(),
+            Some(location) => project_root.join(location).to_string_lossy().to_string(),
+            None => project_root
+                .join(".dna")
+                .join("attachments")
+                .to_string_lossy()
+                .to_string(),
+        }
+    }
+}
+
 /// Service for configuration management
 pub struct ConfigService {
     config_path: PathBuf,
@@ -36,9 +96,39 @@ impl ConfigService {
         figment = figment.merge(Env::prefixed("DNA_").split("__"));
 
         let config: ProjectConfig = figment.extract().context("Failed to load configuration")?;
+        if let Some((_, message)) = unusable_values(&config).into_iter().next() {
+            anyhow::bail!("Invalid configuration: {}", message);
+        }
+
+        if let Ok(text) = std::fs::read_to_string(&self.config_path) {
+            if let Ok(file) = text.parse::<toml::Table>() {
+                for key in unknown_keys(file) {
+                    tracing::warn!(
+                        "Ignoring unknown key `{}` in {}",
+                        key,
+                        self.config_path.display()
+                    );
+                }
+            }
+        }
+
         Ok(config)
     }
 
+    /// Path of the config file
+    pub fn path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// Check the config file for syntax errors, unknown keys and invalid values.
+    ///
+    /// Environment overrides are not applied: only what the file says is checked.
+    pub fn validate(&self) -> Result<Vec<ConfigIssue>> {
+        let text = std::fs::read_to_string(&self.config_path)
+            .with_context(|| format!("Failed to read {}", self.config_path.display()))?;
+        Ok(validate_config(&text))
+    }
+
     /// Save configuration to file
     pub fn save(&self, config: &ProjectConfig) -> Result<()> {
         let content = toml::to_string_pretty(config).context("Failed to serialize config")?;
@@ -68,7 +158,14 @@ impl ConfigService {
             "model.name" => Ok(config.model.name),
             "model.api_key" => Ok(config.model.api_key.unwrap_or_default()),
             "model.base_url" => Ok(config.model.base_url.unwrap_or_default()),
+            "model.cache_dir" => Ok(config.model.cache_dir.unwrap_or_default()),
+            "model.offline" => Ok(config.model.offline.unwrap_or(false).to_string()),
             "storage.uri" => Ok(config.storage.uri.unwrap_or_default()),
+            "storage.embedding_precision" => Ok(config.storage.embedding_precision.to_string()),
+            "storage.s3.region" => Ok(config.storage.s3.region.unwrap_or_default()),
+            "storage.s3.endpoint" => Ok(config.storage.s3.endpoint.unwrap_or_default()),
+            "storage.s3.profile" => Ok(config.storage.s3.profile.unwrap_or_default()),
+            "storage.s3.commit_store" => Ok(config.storage.s3.commit_store.unwrap_or_default()),
             _ => Err(anyhow::anyhow!("Unknown config key: {}", key)),
         }
     }
@@ -81,7 +178,20 @@ impl ConfigService {
             "model.name" => config.model.name = value,
             "model.api_key" => config.model.api_key = Some(value),
             "model.base_url" => config.model.base_url = Some(value),
+            "model.cache_dir" => config.model.cache_dir = Some(value),
+            "model.offline" => {
+                config.model.offline = Some(
+                    value
+                        .parse()
+                        .context("model.offline must be true or false")?,
+                )
+            },
             "storage.uri" => config.storage.uri = Some(value),
+            "storage.embedding_precision" => config.storage.embedding_precision = value.parse()?,
+            "storage.s3.region" => config.storage.s3.region = Some(value),
+            "storage.s3.endpoint" => config.storage.s3.endpoint = Some(value),
+            "storage.s3.profile" => config.storage.s3.profile = Some(value),
+            "storage.s3.commit_store" => config.storage.s3.commit_store = Some(value),
             _ => return Err(anyhow::anyhow!("Unknown config key: {}", key)),
         }
         self.save(&config)?;
@@ -90,18 +200,13 @@ impl ConfigService {
 
     /// Resolve the storage URI from config, defaulting to local path
     pub fn resolve_storage_uri(&self, project_root: &Path) -> Result<String> {
-        let config = self.load()?;
-        let uri = match &config.storage.uri {
-            Some(uri) if uri.starts_with("s3://") => uri.clone(),
-            Some(uri) => project_root.join(uri).to_string_lossy().to_string(),
-            None => project_root
-                .join(".dna")
-                .join("db")
-                .join("artifacts.lance")
-                .to_string_lossy()
-                .to_string(),
-        };
-        Ok(uri)
+        Ok(self.load()?.storage.resolve_uri(project_root))
+    }
+
+    /// Open the configured store, applying any S3 connection options
+    #[cfg(feature = "lance")]
+    pub async fn open_database(&self, project_root: &Path) -> Result<LanceDatabase> {
+        self.load()?.storage.open(project_root).await
     }
 
     /// Initialize with kinds and labels from a template
@@ -112,18 +217,7 @@ impl ConfigService {
             ProjectConfig::default()
         };
 
-        for kind in template.kinds {
-            config
-                .kinds
-                .add(kind.slug.to_string(), kind.description.to_string());
-        }
-
-        for label in template.labels {
-            config
-                .labels
-                .add(label.key.to_string(), label.description.to_string());
-        }
-
+        config.apply_template(template);
         self.save(&config)?;
         Ok(config)
     }
@@ -133,11 +227,65 @@ impl ConfigService {
     /// Validates the slug before adding. Returns an error if the slug is invalid.
     /// Returns Ok(false) if the kind already exists.
     pub fn add_kind(&self, slug: &str, description: &str) -> Result<bool> {
+        self.add_kind_with_fields(slug, description, &[])
+    }
+
+    /// Add a kind whose listed top-level content fields are stored as columns.
+    ///
+    /// Field names must be lowercase identifiers so they can be filtered on.
+    pub fn add_kind_with_fields(
+        &self,
+        slug: &str,
+        description: &str,
+        fields: &[String],
+    ) -> Result<bool> {
+        self.add_kind_definition(super::KindDefinition {
+            slug: slug.to_string(),
+            description: description.to_string(),
+            fields: fields.to_vec(),
+            ..Default::default()
+        })
+    }
+
+    /// Add a kind with its structured fields, default format and content template
+    pub fn add_kind_definition(&self, mut definition: super::KindDefinition) -> Result<bool> {
         // Validate slug before adding
-        super::validate_kind_slug(slug)?;
+        super::validate_kind_slug(&definition.slug)?;
+        if let Some(field) = definition
+            .fields
+            .iter()
+            .find(|f| !crate::db::is_valid_field_name(f))
+        {
+            anyhow::bail!(
+                "Invalid field name '{}': use lowercase letters, digits and underscores, starting with a letter",
+                field
+            );
+        }
+        if let Some(template) = &definition.template {
+            if template.trim().is_empty() {
+                anyhow::bail!("Template for kind '{}' is empty", definition.slug);
+            }
+        }
+        if let Some(embed) = &definition.embed {
+            super::embed_text::template(embed).map_err(|e| {
+                anyhow::anyhow!(
+                    "Invalid embed setting for kind '{}': {}",
+                    definition.slug,
+                    e
+                )
+            })?;
+        }
+
+        let mut fields = Vec::new();
+        for field in definition.fields.drain(..) {
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+        definition.fields = fields;
 
         let mut config = self.load()?;
-        let added = config.kinds.add(slug.to_string(), description.to_string());
+        let added = config.kinds.insert(definition);
         if added {
             self.save(&config)?;
         }
@@ -154,15 +302,48 @@ impl ConfigService {
         Ok(removed)
     }
 
+    /// Point the config for kind `from` at kind `to`; see [`ProjectConfig::move_kind`]
+    pub fn move_kind(&self, from: &str, to: &str) -> Result<()> {
+        let mut config = self.load()?;
+        config.move_kind(from, to);
+        self.save(&config)
+    }
+
     /// Add a label to the config.
     ///
     /// Validates the key before adding (same rules as kind slugs).
     /// Returns Ok(false) if the label already exists.
     pub fn add_label(&self, key: &str, description: &str) -> Result<bool> {
-        super::validate_kind_slug(key)?;
+        self.add_label_definition(super::LabelDefinition::new(key, description))
+    }
+
+    /// Add a typed label definition to the config.
+    ///
+    /// Enum labels must list at least one allowed value, and every kind the
+    /// label is scoped to must be registered.
+    pub fn add_label_definition(&self, definition: super::LabelDefinition) -> Result<bool> {
+        super::validate_kind_slug(&definition.key)?;
 
         let mut config = self.load()?;
-        let added = config.labels.add(key.to_string(), description.to_string());
+        match definition.value_type {
+            super::LabelValueType::Enum if definition.values.is_empty() => {
+                anyhow::bail!("Enum label '{}' needs at least one value", definition.key);
+            },
+            super::LabelValueType::Enum => {},
+            _ if !definition.values.is_empty() => {
+                anyhow::bail!(
+                    "Allowed values only apply to enum labels (label '{}' is {})",
+                    definition.key,
+                    definition.value_type
+                );
+            },
+            _ => {},
+        }
+        if let Some(kind) = definition.kinds.iter().find(|k| !config.kinds.has(k)) {
+            anyhow::bail!("Kind '{}' is not registered", kind);
+        }
+
+        let added = config.labels.insert(definition);
         if added {
             self.save(&config)?;
         }
@@ -185,6 +366,360 @@ impl ConfigService {
     }
 }
 
+/// Embedding providers `create_provider` knows
+const PROVIDERS: &[&str] = &["local", "openai", "ollama"];
+
+/// A problem found in `config.toml`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub severity: LintSeverity,
+    /// 1-based line of the offending key, when it can be found
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn error(text: &str, key: &str, message: String) -> Self {
+        Self {
+            severity: LintSeverity::Error,
+            line: line_of(text, key),
+            message,
+        }
+    }
+
+    fn warning(text: &str, key: &str, message: String) -> Self {
+        Self {
+            severity: LintSeverity::Warning,
+            line: line_of(text, key),
+            message,
+        }
+    }
+}
+
+/// Problems in the text of a config file, errors first, then by line
+fn validate_config(text: &str) -> Vec<ConfigIssue> {
+    let file = match text.parse::<toml::Table>() {
+        Ok(file) => file,
+        Err(e) => {
+            let line = e.span().map(|span| line_at(text, span.start));
+            return vec![ConfigIssue {
+                severity: LintSeverity::Error,
+                line,
+                message: e.message().to_string(),
+            }];
+        },
+    };
+
+    let extracted = Figment::from(Serialized::defaults(ProjectConfig::default()))
+        .merge(Toml::string(text))
+        .extract::<ProjectConfig>();
+    let config = match extracted {
+        Ok(config) => config,
+        Err(errors) => {
+            return errors
+                .into_iter()
+                .map(|e| {
+                    let key = e.path.join(".");
+                    ConfigIssue::error(text, &key, format!("`{}`: {}", key, e.kind))
+                })
+                .collect();
+        },
+    };
+
+    let mut issues = check_values(&config, text);
+    issues.extend(unknown_keys(file).into_iter().map(|key| {
+        let message = format!("unknown key `{}` is ignored", key);
+        ConfigIssue::warning(text, &key, message)
+    }));
+    issues.sort_by_key(|issue| (std::cmp::Reverse(issue.severity), issue.line));
+    issues
+}
+
+/// Sections of `config.toml` read by other binaries rather than `ProjectConfig`
+const OTHER_SECTIONS: &[&str] = &["server"];
+
+/// Dotted paths of keys in `file` that no config field reads
+fn unknown_keys(mut file: toml::Table) -> Vec<String> {
+    file.retain(|key, _| !OTHER_SECTIONS.contains(&key));
+
+    // Deserialize over the defaults, as `load` does, so that sections the
+    // file leaves out don't fail on their required fields
+    let Ok(mut merged) = toml::Value::try_from(ProjectConfig::default()) else {
+        return Vec::new();
+    };
+    merge(&mut merged, toml::Value::Table(file));
+
+    let mut unknown = Vec::new();
+    let _: Result<ProjectConfig, _> = serde_ignored::deserialize(merged, |path| {
+        let key = path
+            .to_string()
+            .split('.')
+            .filter(|segment| *segment != "?")
+            .collect::<Vec<_>>()
+            .join(".");
+        unknown.push(key);
+    });
+    unknown
+}
+
+/// Merge `over` into `base`, table by table
+fn merge(base: &mut toml::Value, over: toml::Value) {
+    match (base, over) {
+        (toml::Value::Table(base), toml::Value::Table(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    },
+                }
+            }
+        },
+        (base, over) => *base = over,
+    }
+}
+
+/// Values that would leave the project unusable, as the dotted key to
+/// report and why; `load` refuses a config with any of them
+fn unusable_values(config: &ProjectConfig) -> Vec<(String, String)> {
+    let mut unusable = Vec::new();
+    for (i, label) in config.labels.definitions.iter().enumerate() {
+        if label.value_type == LabelValueType::Enum && label.values.is_empty() {
+            unusable.push((
+                format!("labels.definitions.{}", i),
+                format!(
+                    "enum label `{}` lists no values, so no value could pass it",
+                    label.key
+                ),
+            ));
+        }
+    }
+    let ids = &config.ids;
+    if ids.strategy != IdStrategy::Ulid && ids.length < MIN_ID_LENGTH {
+        unusable.push((
+            "ids.length".to_string(),
+            format!(
+                "`ids.length` is {}, but IDs need at least {} random characters; shorter ones would soon collide",
+                ids.length, MIN_ID_LENGTH
+            ),
+        ));
+    }
+    unusable
+}
+
+/// Values that parse but can't work
+fn check_values(config: &ProjectConfig, text: &str) -> Vec<ConfigIssue> {
+    let mut issues: Vec<ConfigIssue> = unusable_values(config)
+        .into_iter()
+        .map(|(key, message)| ConfigIssue::error(text, &key, message))
+        .collect();
+
+    let models = std::iter::once(("model".to_string(), &config.model)).chain(
+        config
+            .extra_models
+            .iter()
+            .enumerate()
+            .map(|(i, model)| (format!("extra_models.{}", i), model)),
+    );
+    let mut seen: Vec<&ModelConfig> = Vec::new();
+    for (path, model) in models {
+        issues.extend(check_model(&path, model, text));
+        if seen
+            .iter()
+            .any(|other| other.provider == model.provider && other.name == model.name)
+        {
+            issues.push(ConfigIssue::warning(
+                text,
+                &format!("{}.name", path),
+                format!(
+                    "`{}` repeats the model {}:{}; its embeddings would be stored twice",
+                    path, model.provider, model.name
+                ),
+            ));
+        }
+        seen.push(model);
+    }
+
+    let storage = &config.storage;
+    if let Some(uri) = &storage.uri {
+        if uri.contains("://") && !super::is_s3_uri(uri) {
+            issues.push(ConfigIssue::error(
+                text,
+                "storage.uri",
+                format!(
+                    "`storage.uri`: unsupported scheme in `{}` (expected a local path, s3:// or s3+ddb://)",
+                    uri
+                ),
+            ));
+        }
+    }
+    let on_s3 = storage.uri.as_deref().is_some_and(super::is_s3_uri);
+    if !storage.s3.is_empty() && !on_s3 {
+        issues.push(ConfigIssue::warning(
+            text,
+            "storage.s3",
+            "`storage.s3` is only used when `storage.uri` is an s3:// URI".to_string(),
+        ));
+    }
+
+    for (i, kind) in config.kinds.definitions.iter().enumerate() {
+        if let Some(Err(e)) = kind.embed.as_deref().map(super::embed_text::template) {
+            let path = format!("kinds.definitions.{}.embed", i);
+            issues.push(ConfigIssue::error(
+                text,
+                &path,
+                format!("`{}` of kind `{}`: {}", path, kind.slug, e),
+            ));
+        }
+    }
+
+    for (language, model) in &config.languages.models {
+        let configured = std::iter::once(&config.model)
+            .chain(&config.extra_models)
+            .any(|m| m.name == *model);
+        if !configured {
+            issues.push(ConfigIssue::error(
+                text,
+                &format!("languages.models.{}", language),
+                format!(
+                    "`languages.models.{}`: model `{}` is neither [model] nor one of [[extra_models]]",
+                    language, model
+                ),
+            ));
+        }
+    }
+
+    if let Some(location) = &config.backup.location {
+        if location.contains("://") && !super::is_s3_uri(location) {
+            issues.push(ConfigIssue::error(
+                text,
+                "backup.location",
+                format!(
+                    "`backup.location`: unsupported scheme in `{}` (expected a local path or s3://)",
+                    location
+                ),
+            ));
+        }
+    }
+
+    issues
+}
+
+/// Problems with one `[model]` or `[[extra_models]]` entry at `path`
+fn check_model(path: &str, model: &ModelConfig, text: &str) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+    let key = |field: &str| format!("{}.{}", path, field);
+
+    if !PROVIDERS.contains(&model.provider.as_str()) {
+        issues.push(ConfigIssue::error(
+            text,
+            &key("provider"),
+            format!(
+                "`{}`: unknown provider `{}` (expected one of: {})",
+                key("provider"),
+                model.provider,
+                PROVIDERS.join(", ")
+            ),
+        ));
+    }
+    if model.name.trim().is_empty() {
+        issues.push(ConfigIssue::error(
+            text,
+            &key("name"),
+            format!("`{}` is empty", key("name")),
+        ));
+    }
+    if model.provider == "openai" && model.api_key.is_none() {
+        issues.push(ConfigIssue::warning(
+            text,
+            &key("provider"),
+            format!(
+                "`{}` uses openai but `{}` is not set; it must come from the environment",
+                path,
+                key("api_key")
+            ),
+        ));
+    }
+    if let Some(url) = &model.base_url {
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            issues.push(ConfigIssue::error(
+                text,
+                &key("base_url"),
+                format!("`{}`: `{}` is not an http(s) URL", key("base_url"), url),
+            ));
+        }
+    }
+    for (field, value) in [("threads", model.threads), ("batch_size", model.batch_size)] {
+        if value == Some(0) {
+            issues.push(ConfigIssue::error(
+                text,
+                &key(field),
+                format!("`{}` must be at least 1", key(field)),
+            ));
+        }
+    }
+
+    issues
+}
+
+/// 1-based line holding the key or table header at dotted `path`.
+///
+/// Entries of `[[array]]` tables are numbered from 0, as in
+/// `extra_models.1.name`; keys inside inline tables are not found.
+fn line_of(text: &str, path: &str) -> Option<usize> {
+    let wanted: Vec<&str> = path.split('.').collect();
+    let mut table: Vec<String> = Vec::new();
+    let mut array_counts: std::collections::HashMap<String, usize> =
+        std::collections::HashMap::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if let Some(header) = line.strip_prefix("[[") {
+            let name = header.split("]]").next().unwrap_or_default();
+            let count = array_counts.entry(name.trim().to_string()).or_insert(0);
+            table = split_key(name);
+            table.push(count.to_string());
+            *count += 1;
+        } else if let Some(header) = line.strip_prefix('[') {
+            table = split_key(header.split(']').next().unwrap_or_default());
+        } else if let Some((key, _)) = line.split_once('=') {
+            if line.starts_with('#') {
+                continue;
+            }
+            let mut full = table.clone();
+            full.extend(split_key(key));
+            if full == wanted {
+                return Some(number + 1);
+            }
+            continue;
+        } else {
+            continue;
+        }
+        if table == wanted {
+            return Some(number + 1);
+        }
+    }
+    None
+}
+
+/// Segments of a dotted TOML key, unquoted
+fn split_key(key: &str) -> Vec<String> {
+    key.split('.')
+        .map(|segment| {
+            segment
+                .trim()
+                .trim_matches('"')
+                .trim_matches('\'')
+                .to_string()
+        })
+        .collect()
+}
+
+/// 1-based line of the byte at `offset`
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -244,6 +779,20 @@ mod tests {
         assert_eq!(loaded.model.name, "text-embedding-3-small");
     }
 
+    #[test]
+    fn set_model_offline_parses_bool() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = ConfigService::new(temp_dir.path());
+        service.init().unwrap();
+
+        assert_eq!(service.get("model.offline").unwrap(), "false");
+        service.set("model.offline", "true".to_string()).unwrap();
+        assert_eq!(service.get("model.offline").unwrap(), "true");
+        assert!(service
+            .set("model.offline", "sometimes".to_string())
+            .is_err());
+    }
+
     #[test]
     fn exists_returns_false_before_init() {
         let temp_dir = TempDir::new().unwrap();
@@ -304,4 +853,219 @@ mod tests {
             .to_string();
         assert_eq!(uri, expected);
     }
+
+    #[test]
+    fn add_kind_with_fields_persists_and_validates_fields() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = ConfigService::new(temp_dir.path());
+        service.init().unwrap();
+
+        let fields = vec!["severity".to_string(), "owner".to_string()];
+        assert!(service
+            .add_kind_with_fields("incident", "Incidents", &fields)
+            .unwrap());
+        let loaded = service.load().unwrap();
+        assert_eq!(loaded.kinds.get("incident").unwrap().fields, fields);
+        assert_eq!(
+            loaded.kinds.structured_fields().get("incident"),
+            Some(&fields)
+        );
+
+        let bad = vec!["Owner-Team".to_string()];
+        assert!(service
+            .add_kind_with_fields("postmortem", "Postmortems", &bad)
+            .is_err());
+        assert!(!service.load().unwrap().kinds.has("postmortem"));
+    }
+
+    #[test]
+    fn add_kind_definition_persists_format_and_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = ConfigService::new(temp_dir.path());
+        service.init().unwrap();
+
+        let definition = crate::services::KindDefinition {
+            slug: "evaluation".to_string(),
+            description: "Evaluations".to_string(),
+            format: Some(crate::services::ContentFormat::Yaml),
+            template: Some("given: {{given}}\nthen: {{then}}\n".to_string()),
+            ..Default::default()
+        };
+        assert!(service.add_kind_definition(definition.clone()).unwrap());
+        assert!(!service.add_kind_definition(definition).unwrap());
+
+        let loaded = service.load().unwrap();
+        let kind = loaded.kinds.get("evaluation").unwrap();
+        assert_eq!(kind.format, Some(crate::services::ContentFormat::Yaml));
+        assert_eq!(kind.placeholders(), vec!["given", "then"]);
+
+        let empty = crate::services::KindDefinition {
+            slug: "review".to_string(),
+            description: "Reviews".to_string(),
+            template: Some("  \n".to_string()),
+            ..Default::default()
+        };
+        assert!(service.add_kind_definition(empty).is_err());
+    }
+
+    #[test]
+    fn add_label_definition_persists_typed_label() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = ConfigService::new(temp_dir.path());
+        service.init().unwrap();
+        service.add_kind("incident", "Incidents").unwrap();
+
+        let definition = crate::services::LabelDefinition {
+            value_type: crate::services::LabelValueType::Enum,
+            values: vec!["low".to_string(), "high".to_string()],
+            kinds: vec!["incident".to_string()],
+            ..crate::services::LabelDefinition::new("severity", "Impact")
+        };
+        assert!(service.add_label_definition(definition.clone()).unwrap());
+
+        let loaded = service.load().unwrap();
+        assert_eq!(loaded.labels.get("severity"), Some(&definition));
+    }
+
+    #[test]
+    fn add_label_definition_rejects_invalid_definitions() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = ConfigService::new(temp_dir.path());
+        service.init().unwrap();
+
+        let empty_enum = crate::services::LabelDefinition {
+            value_type: crate::services::LabelValueType::Enum,
+            ..crate::services::LabelDefinition::new("severity", "Impact")
+        };
+        assert!(service.add_label_definition(empty_enum).is_err());
+
+        let unknown_kind = crate::services::LabelDefinition {
+            kinds: vec!["missing".to_string()],
+            ..crate::services::LabelDefinition::new("owner", "Owner")
+        };
+        assert!(service.add_label_definition(unknown_kind).is_err());
+        assert!(service.load().unwrap().labels.definitions.is_empty());
+    }
+
+    #[test]
+    fn validate_accepts_a_saved_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = ConfigService::new(temp_dir.path());
+        service.init().unwrap();
+
+        assert!(service.validate().unwrap().is_empty());
+
+        // dna-server reads [server] from the same file
+        let text = "[server]\nbind = \"127.0.0.1:3000\"\n";
+        assert!(validate_config(text).is_empty());
+    }
+
+    #[test]
+    fn validate_reports_unknown_keys_with_their_lines() {
+        let text = "[model]\nprovder = \"openai\"\nname = \"m\"\n\n[[extra_models]]\nprovider = \"ollama\"\nname = \"a\"\n\n[[extra_models]]\nprovider = \"ollama\"\nname = \"b\"\nthread = 2\n\n[modle]\nname = \"x\"\n";
+        let issues = validate_config(text);
+
+        let unknown: Vec<(Option<usize>, &str)> = issues
+            .iter()
+            .map(|issue| (issue.line, issue.message.as_str()))
+            .collect();
+        assert_eq!(
+            unknown,
+            [
+                (Some(2), "unknown key `model.provder` is ignored"),
+                (Some(12), "unknown key `extra_models.1.thread` is ignored"),
+                (Some(14), "unknown key `modle` is ignored"),
+            ]
+        );
+        assert!(issues
+            .iter()
+            .all(|issue| issue.severity == LintSeverity::Warning));
+    }
+
+    #[test]
+    fn validate_reports_invalid_values() {
+        let text = "[model]\nprovider = \"openia\"\nname = \"m\"\nbatch_size = 0\n\n[storage]\nuri = \"gs://bucket/db\"\n";
+        let issues = validate_config(text);
+
+        let errors: Vec<Option<usize>> = issues
+            .iter()
+            .filter(|issue| issue.severity == LintSeverity::Error)
+            .map(|issue| issue.line)
+            .collect();
+        assert_eq!(errors, [Some(2), Some(4), Some(7)]);
+        assert!(issues[0].message.contains("unknown provider `openia`"));
+    }
+
+    #[test]
+    fn validate_reports_language_routes_to_unconfigured_models() {
+        let text = "[model]\nprovider = \"local\"\nname = \"BAAI/bge-small-en-v1.5\"\n\n[[extra_models]]\nprovider = \"ollama\"\nname = \"multilingual-e5\"\n\n[languages.models]\ndeu = \"multilingual-e5\"\nfra = \"labse\"\n";
+        let issues = validate_config(text);
+
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].severity, LintSeverity::Error);
+        assert_eq!(issues[0].line, Some(11));
+        assert!(issues[0].message.contains("`labse`"));
+    }
+
+    #[test]
+    fn validate_reports_invalid_embed_settings() {
+        let text = "[[kinds.definitions]]\nslug = \"runbook\"\ndescription = \"Runbooks\"\nembed = \"full\"\n\n[[kinds.definitions]]\nslug = \"faq\"\ndescription = \"FAQs\"\nembed = \"{question}: {content}\"\n";
+        let issues = validate_config(text);
+
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].severity, LintSeverity::Error);
+        assert_eq!(issues[0].line, Some(9));
+        assert!(issues[0].message.contains("{question}"));
+    }
+
+    #[test]
+    fn load_rejects_enum_labels_without_values() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = ConfigService::new(temp_dir.path());
+        let text =
+            "[[labels.definitions]]\nkey = \"tier\"\ndescription = \"Tier\"\ntype = \"enum\"\n";
+        std::fs::create_dir_all(temp_dir.path().join(".dna")).unwrap();
+        std::fs::write(service.path(), text).unwrap();
+
+        let err = service.load().unwrap_err();
+        assert!(err.to_string().contains("`tier` lists no values"), "{err}");
+
+        let issues = validate_config(text);
+        assert_eq!(issues.len(), 1, "{:?}", issues);
+        assert_eq!(issues[0].severity, LintSeverity::Error);
+        assert_eq!(issues[0].line, Some(1));
+    }
+
+    #[test]
+    fn load_rejects_ids_too_short_to_stay_unique() {
+        let temp_dir = TempDir::new().unwrap();
+        let service = ConfigService::new(temp_dir.path());
+        std::fs::create_dir_all(temp_dir.path().join(".dna")).unwrap();
+
+        for length in [0, MIN_ID_LENGTH - 1] {
+            let text = format!("[ids]\nlength = {}\n", length);
+            std::fs::write(service.path(), &text).unwrap();
+            let err = service.load().unwrap_err();
+            assert!(err.to_string().contains("at least 6"), "{err}");
+            assert_eq!(validate_config(&text)[0].line, Some(2));
+        }
+
+        std::fs::write(service.path(), "[ids]\nstrategy = \"ulid\"\nlength = 0\n").unwrap();
+        assert!(service.load().is_ok());
+        std::fs::write(service.path(), "[ids]\nlength = 6\n").unwrap();
+        assert_eq!(service.load().unwrap().ids.length, MIN_ID_LENGTH);
+    }
+
+    #[test]
+    fn validate_reports_type_and_syntax_errors() {
+        let issues = validate_config("[limits]\nmax_content_bytes = \"big\"\n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, LintSeverity::Error);
+        assert_eq!(issues[0].line, Some(2));
+        assert!(issues[0].message.contains("limits.max_content_bytes"));
+
+        let issues = validate_config("[model]\nprovider = \n");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].line, Some(2));
+    }
 }
@@ -0,0 +1,139 @@
+//! Dates accepted by the `after`, `before` and `since` filters.
+//!
+//! Agents and people rarely have an RFC3339 timestamp at hand, so besides
+//! exact dates these filters take durations ago (`7d`) and a few names
+//! (`yesterday`, `last monday`). Calendar dates and named days start at
+//! midnight UTC.
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, TimeZone, Utc, Weekday};
+
+/// Parse a date filter, counting relative dates back from now
+pub fn parse_date(s: &str) -> Result<DateTime<Utc>> {
+    parse_date_at(s, Utc::now())
+}
+
+/// Parse a date filter, counting relative dates back from `now`.
+///
+/// Accepts RFC3339 datetimes, `YYYY-MM-DD`, `YYYY-MM`, durations such as
+/// `30m`, `12h`, `7d`, `2w` or `3 days ago`, and `now`, `today`,
+/// `yesterday`, `last week`, `last month` and `last <weekday>`.
+pub fn parse_date_at(s: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    let input = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(input) {
+        return Ok(dt.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Ok(midnight(date));
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&format!("{}-01", input), "%Y-%m-%d") {
+        return Ok(midnight(date));
+    }
+
+    relative(&input.to_lowercase(), now).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid date '{}'. Use YYYY-MM-DD, YYYY-MM, RFC3339 (e.g. 2024-01-15T10:30:00Z), \
+             a duration ago such as 7d or 2w, or today, yesterday or last monday.",
+            s
+        )
+    })
+}
+
+/// A named or relative date, if `s` is one
+fn relative(s: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let today = now.date_naive();
+    match s {
+        "now" => return Some(now),
+        "today" => return Some(midnight(today)),
+        "yesterday" => return Some(midnight(today.pred_opt()?)),
+        "last week" => return now.checked_sub_signed(Duration::weeks(1)),
+        "last month" => return now.checked_sub_months(Months::new(1)),
+        _ => {},
+    }
+
+    if let Some(day) = s.strip_prefix("last ") {
+        // The most recent such day before today
+        let weekday: Weekday = day.trim().parse().ok()?;
+        let back =
+            (today.weekday().num_days_from_monday() + 6 - weekday.num_days_from_monday()) % 7 + 1;
+        return Some(midnight(today - Duration::days(back.into())));
+    }
+
+    now.checked_sub_signed(duration_ago(s)?)
+}
+
+/// `7d`, `7 days` or `7 days ago`
+fn duration_ago(s: &str) -> Option<Duration> {
+    let s = s.strip_suffix(" ago").unwrap_or(s).trim();
+    let (count, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit())?);
+    let count: i64 = count.parse().ok()?;
+    match unit.trim() {
+        "m" | "min" | "mins" | "minute" | "minutes" => Duration::try_minutes(count),
+        "h" | "hour" | "hours" => Duration::try_hours(count),
+        "d" | "day" | "days" => Duration::try_days(count),
+        "w" | "week" | "weeks" => Duration::try_weeks(count),
+        _ => None,
+    }
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Wednesday, 2024-06-12 15:30 UTC
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 12, 15, 30, 0).unwrap()
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        parse_date_at(s, now()).unwrap()
+    }
+
+    fn day(y: i32, m: u32, d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn exact_dates() {
+        assert_eq!(at("2024-01-15"), day(2024, 1, 15));
+        assert_eq!(at("2024-06"), day(2024, 6, 1));
+        assert_eq!(
+            at("2024-01-15T10:30:00+02:00"),
+            Utc.with_ymd_and_hms(2024, 1, 15, 8, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn durations_ago() {
+        assert_eq!(at("7d"), now() - Duration::days(7));
+        assert_eq!(at("2w"), now() - Duration::weeks(2));
+        assert_eq!(at("12h"), now() - Duration::hours(12));
+        assert_eq!(at("3 days ago"), now() - Duration::days(3));
+        assert_eq!(at("30 Minutes"), now() - Duration::minutes(30));
+    }
+
+    #[test]
+    fn named_days() {
+        assert_eq!(at("now"), now());
+        assert_eq!(at("today"), day(2024, 6, 12));
+        assert_eq!(at("Yesterday"), day(2024, 6, 11));
+        assert_eq!(at("last monday"), day(2024, 6, 10));
+        assert_eq!(at("last wed"), day(2024, 6, 5));
+        assert_eq!(at("last thursday"), day(2024, 6, 6));
+        assert_eq!(at("last month"), now() - Duration::days(31));
+    }
+
+    #[test]
+    fn unknown_input_names_accepted_forms() {
+        for input in ["soon", "7 fortnights", "last payday", "2024-13", ""] {
+            let err = parse_date_at(input, now()).unwrap_err().to_string();
+            assert!(err.contains("Invalid date"), "{}", err);
+            assert!(err.contains("YYYY-MM-DD"));
+            assert!(err.contains("RFC3339"));
+        }
+    }
+}
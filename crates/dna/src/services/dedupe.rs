@@ -0,0 +1,223 @@
+//! Finding near-identical artifacts and merging them.
+//!
+//! Stores fed by agents pick up the same truth several times over. Each
+//! artifact's nearest neighbors are looked up with the store's vector search,
+//! and pairs whose stored embeddings are at least `threshold` similar are
+//! joined into groups. Merging a group archives the duplicates, and their IDs
+//! become aliases of the artifact they were merged into so they keep working.
+
+use super::snippet::cosine_similarity;
+use super::types::{Artifact, SearchFilters};
+use super::ServiceError;
+use crate::db::Database;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+
+/// Label on an archived duplicate naming the artifact it was merged into
+pub const MERGED_INTO_LABEL: &str = "merged_into";
+
+/// Neighbors looked up per artifact; duplicates beyond them are not linked
+const NEIGHBORS: usize = 10;
+
+/// Artifacts similar enough to be duplicates of each other
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    /// Members, oldest first; the oldest is the suggested merge target
+    pub artifacts: Vec<Artifact>,
+    /// Lowest similarity of the pairs that linked the group
+    pub similarity: f32,
+}
+
+impl DuplicateGroup {
+    /// Whether `id` is a member
+    pub fn contains(&self, id: &str) -> bool {
+        self.artifacts.iter().any(|a| a.id == id)
+    }
+}
+
+/// Groups of same-kind artifacts at least `threshold` similar, among those
+/// matching `filters`, most similar first
+pub async fn find(
+    db: &dyn Database,
+    filters: &SearchFilters,
+    threshold: f32,
+) -> Result<Vec<DuplicateGroup>> {
+    if !(threshold > 0.0 && threshold <= 1.0) {
+        return Err(ServiceError::Validation(format!(
+            "Threshold must be above 0 and at most 1, got {}",
+            threshold
+        ))
+        .into());
+    }
+
+    let artifacts = db
+        .list(SearchFilters {
+            limit: None,
+            ..filters.clone()
+        })
+        .await?;
+    let index: HashMap<&str, usize> = artifacts
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (a.id.as_str(), i))
+        .collect();
+
+    let mut groups = Groups::new(artifacts.len());
+    for (i, artifact) in artifacts.iter().enumerate() {
+        let Some(embedding) = &artifact.embedding else {
+            continue;
+        };
+        let neighbors = db
+            .search(
+                embedding,
+                SearchFilters {
+                    kind: Some(artifact.kind.clone()),
+                    limit: Some(NEIGHBORS + 1),
+                    ..filters.clone()
+                },
+            )
+            .await?;
+        for neighbor in neighbors {
+            // Each pair is seen from both ends; link it once
+            let Some(&j) = index.get(neighbor.artifact.id.as_str()) else {
+                continue;
+            };
+            if j <= i {
+                continue;
+            }
+            let Some(other) = &artifacts[j].embedding else {
+                continue;
+            };
+            let similarity = cosine_similarity(embedding, other);
+            if similarity >= threshold {
+                groups.link(i, j, similarity);
+            }
+        }
+    }
+
+    let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for i in 0..artifacts.len() {
+        members.entry(groups.root(i)).or_default().push(i);
+    }
+    let mut found: Vec<DuplicateGroup> = members
+        .into_iter()
+        .filter(|(_, members)| members.len() > 1)
+        .map(|(root, members)| {
+            let mut group: Vec<Artifact> =
+                members.into_iter().map(|i| artifacts[i].clone()).collect();
+            group.sort_by(|a, b| a.created_at.cmp(&b.created_at).then(a.id.cmp(&b.id)));
+            DuplicateGroup {
+                artifacts: group,
+                similarity: groups.similarity[root],
+            }
+        })
+        .collect();
+    found.sort_by(|a, b| {
+        b.similarity
+            .partial_cmp(&a.similarity)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    Ok(found)
+}
+
+/// Labels of `target` with those of `duplicates` it lacks added; where
+/// duplicates disagree, the first wins
+pub fn merge_labels(target: &Artifact, duplicates: &[Artifact]) -> HashMap<String, String> {
+    let mut labels = target.metadata.clone();
+    for duplicate in duplicates {
+        for (key, value) in &duplicate.metadata {
+            labels.entry(key.clone()).or_insert_with(|| value.clone());
+        }
+    }
+    labels
+}
+
+/// Union-find over artifact indexes, tracking each group's weakest link
+struct Groups {
+    parent: Vec<usize>,
+    similarity: Vec<f32>,
+}
+
+impl Groups {
+    fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            similarity: vec![1.0; len],
+        }
+    }
+
+    fn root(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn link(&mut self, a: usize, b: usize, similarity: f32) {
+        let (a, b) = (self.root(a), self.root(b));
+        let weakest = self.similarity[a].min(self.similarity[b]).min(similarity);
+        self.parent[b] = a;
+        self.similarity[a] = weakest;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use crate::testing::TestDatabase;
+
+    fn artifact(id: &str, kind: &str, embedding: Vec<f32>) -> Artifact {
+        let mut artifact = Artifact::new(
+            kind.to_string(),
+            format!("{} content", id),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "test".to_string(),
+        );
+        artifact.id = id.to_string();
+        artifact.embedding = Some(embedding);
+        artifact
+    }
+
+    #[tokio::test]
+    async fn groups_similar_artifacts_of_a_kind() {
+        let db = TestDatabase::new();
+        for a in [
+            artifact("a", "intent", vec![1.0, 0.0, 0.0]),
+            artifact("b", "intent", vec![0.99, 0.05, 0.0]),
+            artifact("c", "intent", vec![0.98, 0.1, 0.0]),
+            artifact("d", "intent", vec![0.0, 1.0, 0.0]),
+            artifact("e", "contract", vec![1.0, 0.0, 0.0]),
+        ] {
+            db.insert(&a).await.unwrap();
+        }
+
+        let groups = find(&db, &SearchFilters::default(), 0.99).await.unwrap();
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<&str> = groups[0].artifacts.iter().map(|a| a.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b", "c"]);
+        assert!(groups[0].similarity >= 0.99 && groups[0].similarity < 1.0);
+        assert!(groups[0].contains("c") && !groups[0].contains("e"));
+
+        assert!(find(&db, &SearchFilters::default(), 1.5).await.is_err());
+    }
+
+    #[test]
+    fn merged_labels_keep_the_target_values() {
+        let mut target = artifact("a", "intent", vec![1.0]);
+        target.metadata = HashMap::from([("domain".to_string(), "auth".to_string())]);
+        let mut duplicate = artifact("b", "intent", vec![1.0]);
+        duplicate.metadata = HashMap::from([
+            ("domain".to_string(), "billing".to_string()),
+            ("priority".to_string(), "high".to_string()),
+        ]);
+
+        let labels = merge_labels(&target, &[duplicate]);
+        assert_eq!(labels["domain"], "auth");
+        assert_eq!(labels["priority"], "high");
+    }
+}
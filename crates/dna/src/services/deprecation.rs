@@ -0,0 +1,101 @@
+//! Deprecating artifacts in favour of the ones that replace them.
+//!
+//! A deprecated artifact keeps its ID and content but gets the `status`
+//! label `deprecated`, with the successor and the reason as labels too.
+//! Searches leave deprecated artifacts out unless asked for them, and
+//! fetching one by ID still works, so old references lead to the successor.
+
+use super::pack::STATUS_LABEL;
+use super::types::Artifact;
+use std::collections::HashMap;
+
+/// `status` label value of a deprecated artifact
+pub const DEPRECATED_STATUS: &str = "deprecated";
+
+/// Label naming the artifact that replaces a deprecated one
+pub const REPLACED_BY_LABEL: &str = "replaced_by";
+
+/// Label holding why an artifact was deprecated
+pub const DEPRECATION_REASON_LABEL: &str = "deprecation_reason";
+
+/// Why an artifact is deprecated and what replaces it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Deprecation {
+    /// ID of the successor
+    pub replaced_by: Option<String>,
+    pub reason: Option<String>,
+}
+
+impl Deprecation {
+    /// The deprecation recorded on `artifact`, if it is deprecated
+    pub fn of(artifact: &Artifact) -> Option<Self> {
+        if artifact.metadata.get(STATUS_LABEL)? != DEPRECATED_STATUS {
+            return None;
+        }
+        Some(Self {
+            replaced_by: artifact.metadata.get(REPLACED_BY_LABEL).cloned(),
+            reason: artifact.metadata.get(DEPRECATION_REASON_LABEL).cloned(),
+        })
+    }
+
+    /// Labels recording the deprecation, as an update applies them; empty
+    /// values remove a successor or reason set before
+    pub fn labels(&self) -> HashMap<String, String> {
+        HashMap::from([
+            (STATUS_LABEL.to_string(), DEPRECATED_STATUS.to_string()),
+            (
+                REPLACED_BY_LABEL.to_string(),
+                self.replaced_by.clone().unwrap_or_default(),
+            ),
+            (
+                DEPRECATION_REASON_LABEL.to_string(),
+                self.reason.clone().unwrap_or_default(),
+            ),
+        ])
+    }
+
+    /// One line for people, e.g. "Deprecated: replaced by b5n7r2q8wd (moved
+    /// to the billing API)"
+    pub fn describe(&self) -> String {
+        let mut line = "Deprecated".to_string();
+        if let Some(successor) = &self.replaced_by {
+            line.push_str(&format!(": replaced by {}", successor));
+        }
+        if let Some(reason) = &self.reason {
+            line.push_str(&format!(" ({})", reason));
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+
+    #[test]
+    fn reads_back_the_labels_it_writes() {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            "content".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::from([(STATUS_LABEL.to_string(), "approved".to_string())]),
+            String::new(),
+        );
+        assert_eq!(Deprecation::of(&artifact), None);
+
+        let deprecation = Deprecation {
+            replaced_by: Some("b5n7r2q8wd".to_string()),
+            reason: None,
+        };
+        artifact.metadata.extend(
+            deprecation
+                .labels()
+                .into_iter()
+                .filter(|(_, value)| !value.is_empty()),
+        );
+        assert_eq!(Deprecation::of(&artifact), Some(deprecation.clone()));
+        assert_eq!(deprecation.describe(), "Deprecated: replaced by b5n7r2q8wd");
+    }
+}
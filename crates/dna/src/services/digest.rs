@@ -0,0 +1,429 @@
+//! Digest artifacts summarizing groups of artifacts of a kind.
+//!
+//! Agents answer questions faster from a compact digest than from raw search
+//! over hundreds of artifacts. `dna digest` groups the artifacts of a kind by
+//! a label, summarizes each group and stores the summary as a `digest`
+//! artifact labeled `generated=true`. Digests are matched to their group by
+//! the `source` label, as ingested artifacts are, so running it again updates
+//! them in place, and groups whose artifacts did not change are not
+//! summarized again.
+
+use super::completion::{create_completion, Completion};
+use super::ingest::{IngestItem, IngestPlan, IngestService, SOURCE_LABEL};
+use super::plan::ApplyReport;
+use super::snippet::cosine_similarity;
+use super::types::{Artifact, ProjectConfig, SearchFilters};
+use super::{ArtifactService, ServiceError};
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+/// Kind of the digest artifacts
+pub const DIGEST_KIND: &str = "digest";
+
+/// Label marking artifacts written by dna rather than by people
+pub const GENERATED_LABEL: &str = "generated";
+
+/// Label naming the kind a digest summarizes
+pub const DIGEST_OF_LABEL: &str = "digest_of";
+
+/// Label recording how many artifacts a digest covers and when the newest was
+/// last updated, to tell whether it is stale
+const SOURCES_LABEL: &str = "digest_sources";
+
+const DEFAULT_MAX_POINTS: usize = 8;
+
+/// Longest excerpt of one artifact sent to the model, in characters
+const MAX_CHARS_PER_ARTIFACT: usize = 1000;
+
+/// Writes the summary of a group of artifacts
+#[async_trait::async_trait]
+pub trait Summarizer: Send + Sync {
+    /// Markdown capturing the gist of `artifacts`, which share `topic`
+    async fn summarize(&self, topic: &str, artifacts: &[Artifact]) -> Result<String>;
+}
+
+/// Summarizer using the `[completion]` model, or quoting artifacts when
+/// none is configured
+pub fn create_summarizer(config: &ProjectConfig) -> Result<Arc<dyn Summarizer>> {
+    Ok(match create_completion(&config.completion)? {
+        Some(completion) => Arc::new(ModelSummarizer::new(completion)),
+        None => Arc::new(ExtractiveSummarizer::new(
+            config.digest.max_points.unwrap_or(DEFAULT_MAX_POINTS),
+        )),
+    })
+}
+
+/// Summarizes without a model by quoting the opening sentence of the
+/// artifacts closest to the group's centroid
+pub struct ExtractiveSummarizer {
+    max_points: usize,
+}
+
+impl ExtractiveSummarizer {
+    /// Keep at most `max_points` sentences
+    pub fn new(max_points: usize) -> Self {
+        Self { max_points }
+    }
+}
+
+#[async_trait::async_trait]
+impl Summarizer for ExtractiveSummarizer {
+    async fn summarize(&self, _topic: &str, artifacts: &[Artifact]) -> Result<String> {
+        let centroid = centroid(artifacts);
+        let mut ranked: Vec<(f32, &Artifact)> = artifacts
+            .iter()
+            .map(|artifact| {
+                let score = match (&centroid, &artifact.embedding) {
+                    (Some(centroid), Some(embedding)) if embedding.len() == centroid.len() => {
+                        cosine_similarity(embedding, centroid)
+                    },
+                    _ => f32::MIN,
+                };
+                (score, artifact)
+            })
+            .collect();
+        // Stable, so artifacts without embeddings keep their order
+        ranked.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut seen = HashSet::new();
+        let mut points = Vec::new();
+        for (_, artifact) in ranked {
+            if points.len() == self.max_points {
+                break;
+            }
+            match first_sentence(&artifact.content) {
+                Some(sentence) if seen.insert(sentence.clone()) => {
+                    points.push(format!("- {} ({})", sentence, artifact.id));
+                },
+                _ => {},
+            }
+        }
+        Ok(points.join("\n"))
+    }
+}
+
+/// Mean of the embeddings of the same length as the first one
+fn centroid(artifacts: &[Artifact]) -> Option<Vec<f32>> {
+    let mut embeddings = artifacts.iter().filter_map(|a| a.embedding.as_ref());
+    let mut sum = embeddings.next()?.clone();
+    let mut count = 1.0;
+    let dimension = sum.len();
+    for embedding in embeddings.filter(|e| e.len() == dimension) {
+        for (total, value) in sum.iter_mut().zip(embedding) {
+            *total += value;
+        }
+        count += 1.0;
+    }
+    sum.iter_mut().for_each(|v| *v /= count);
+    Some(sum)
+}
+
+/// The first sentence of the first line of prose, without list or quote markers
+fn first_sentence(content: &str) -> Option<String> {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with("```"))
+        .map(|line| line.trim_start_matches(['-', '*', '>', ' ']))
+        .find(|line| !line.is_empty())?;
+    let end = line
+        .match_indices(['.', '!', '?'])
+        .map(|(i, _)| i + 1)
+        .find(|&i| line[i..].is_empty() || line[i..].starts_with(' '))
+        .unwrap_or(line.len());
+    let sentence = &line[..end];
+    if sentence.chars().count() > 200 {
+        let cut: String = sentence.chars().take(200).collect();
+        Some(format!("{}...", cut.trim_end()))
+    } else {
+        Some(sentence.to_string())
+    }
+}
+
+/// Summarizes by asking a model
+pub struct ModelSummarizer {
+    completion: Arc<dyn Completion>,
+}
+
+impl ModelSummarizer {
+    pub fn new(completion: Arc<dyn Completion>) -> Self {
+        Self { completion }
+    }
+}
+
+#[async_trait::async_trait]
+impl Summarizer for ModelSummarizer {
+    async fn summarize(&self, topic: &str, artifacts: &[Artifact]) -> Result<String> {
+        let mut prompt = format!(
+            "Summarize the gist of these {} artifacts ({}) as a short markdown \
+             list. Merge points they share, keep concrete rules and numbers, \
+             and cite artifact IDs in parentheses.\n",
+            artifacts.len(),
+            topic
+        );
+        for artifact in artifacts {
+            let excerpt: String = artifact
+                .content
+                .chars()
+                .take(MAX_CHARS_PER_ARTIFACT)
+                .collect();
+            prompt.push_str(&format!("\n[{}]\n{}\n", artifact.id, excerpt));
+        }
+        self.completion
+            .complete(
+                "You write compact digests of project knowledge for other agents. \
+                 Reply with the digest only.",
+                &prompt,
+            )
+            .await
+    }
+}
+
+/// Service that keeps digest artifacts in line with the artifacts they
+/// summarize
+pub struct DigestService {
+    artifacts: Arc<ArtifactService>,
+    summarizer: Arc<dyn Summarizer>,
+    ingest: IngestService,
+}
+
+impl DigestService {
+    pub fn new(artifacts: Arc<ArtifactService>, summarizer: Arc<dyn Summarizer>) -> Self {
+        Self {
+            ingest: IngestService::new(Arc::clone(&artifacts)),
+            artifacts,
+            summarizer,
+        }
+    }
+
+    /// Summarize the artifacts of `kind`, one digest per value of the
+    /// `group_by` label or one for the whole kind, without writing anything.
+    ///
+    /// Artifacts without the `group_by` label are left out, as are generated
+    /// artifacts. Groups unchanged since their digest was written keep it
+    /// without being summarized again.
+    pub async fn plan(&self, kind: &str, group_by: Option<&str>) -> Result<IngestPlan> {
+        let filters = SearchFilters {
+            kind: Some(kind.to_string()),
+            metadata_not: HashMap::from([(GENERATED_LABEL.to_string(), "true".to_string())]),
+            ..Default::default()
+        };
+        let mut groups: BTreeMap<Option<String>, Vec<Artifact>> = BTreeMap::new();
+        for artifact in self.artifacts.list(filters).await? {
+            let key = match group_by {
+                Some(label) => match artifact.metadata.get(label) {
+                    Some(value) if !value.is_empty() => Some(value.clone()),
+                    _ => continue,
+                },
+                None => None,
+            };
+            groups.entry(key).or_default().push(artifact);
+        }
+
+        let existing: HashMap<String, Artifact> = self
+            .artifacts
+            .list(SearchFilters {
+                kind: Some(DIGEST_KIND.to_string()),
+                metadata: HashMap::from([(DIGEST_OF_LABEL.to_string(), kind.to_string())]),
+                ..Default::default()
+            })
+            .await?
+            .into_iter()
+            .filter_map(|digest| Some((digest.metadata.get(SOURCE_LABEL)?.clone(), digest)))
+            .collect();
+
+        let mut items = Vec::with_capacity(groups.len());
+        for (value, members) in groups {
+            let (topic, source) = match (group_by, &value) {
+                (Some(label), Some(value)) => (
+                    format!("{} {}={}", kind, label, value),
+                    format!("digest:{}:{}={}", kind, label, value),
+                ),
+                _ => (kind.to_string(), format!("digest:{}", kind)),
+            };
+            let newest = members
+                .iter()
+                .map(|a| a.updated_at)
+                .max()
+                .unwrap_or_default();
+            let sources = format!("{}@{}", members.len(), newest.to_rfc3339());
+
+            let content = match existing.get(&source) {
+                Some(digest) if digest.metadata.get(SOURCES_LABEL) == Some(&sources) => {
+                    digest.content.clone()
+                },
+                _ => {
+                    let summary = self
+                        .summarizer
+                        .summarize(&topic, &members)
+                        .await
+                        .with_context(|| format!("Failed to summarize {}", topic))?;
+                    format!(
+                        "# Digest: {}\n\nSummarizes {} artifact(s).\n\n{}\n",
+                        topic,
+                        members.len(),
+                        summary
+                    )
+                },
+            };
+
+            let mut labels = HashMap::from([
+                (GENERATED_LABEL.to_string(), "true".to_string()),
+                (DIGEST_OF_LABEL.to_string(), kind.to_string()),
+                (SOURCES_LABEL.to_string(), sources),
+            ]);
+            if let (Some(label), Some(value)) = (group_by, value) {
+                labels.insert(label.to_string(), value);
+            }
+            items.push(IngestItem {
+                source,
+                kind: DIGEST_KIND.to_string(),
+                name: format!("{} digest", topic),
+                content,
+                labels,
+            });
+        }
+
+        self.ingest.plan(&items).await
+    }
+
+    /// Write a digest plan all-or-nothing, filling in the IDs of created digests
+    pub async fn apply(&self, plan: &mut IngestPlan) -> Result<ApplyReport, ServiceError> {
+        self.ingest.apply(plan).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::sync::SyncAction;
+    use crate::services::ContentFormat;
+    use crate::testing::{TestDatabase, TestEmbedding};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Lists each artifact's ID and counts calls
+    #[derive(Default)]
+    struct Counting(AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl Summarizer for Counting {
+        async fn summarize(&self, _topic: &str, artifacts: &[Artifact]) -> Result<String> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(artifacts
+                .iter()
+                .map(|a| format!("- {}", a.id))
+                .collect::<Vec<_>>()
+                .join("\n"))
+        }
+    }
+
+    async fn add(artifacts: &ArtifactService, content: &str, domain: &str) -> Artifact {
+        artifacts
+            .add(
+                "intent".to_string(),
+                content.to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::from([("domain".to_string(), domain.to_string())]),
+                None,
+            )
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn digests_each_group_and_skips_unchanged_ones() {
+        let artifacts = Arc::new(ArtifactService::new(
+            Arc::new(TestDatabase::new()),
+            Arc::new(TestEmbedding),
+        ));
+        let login = add(&artifacts, "Users sign in with email.", "auth").await;
+        add(&artifacts, "Sessions expire after 30 days.", "auth").await;
+        add(&artifacts, "Invoices are sent monthly.", "billing").await;
+        let summarizer = Arc::new(Counting::default());
+        let service = DigestService::new(Arc::clone(&artifacts), summarizer.clone());
+
+        let mut plan = service.plan("intent", Some("domain")).await.unwrap();
+        assert_eq!(plan.count(SyncAction::Create), 2);
+        service.apply(&mut plan).await.unwrap();
+        assert_eq!(summarizer.0.load(Ordering::SeqCst), 2);
+
+        let digests = artifacts
+            .list(SearchFilters {
+                kind: Some(DIGEST_KIND.to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(digests.len(), 2);
+        let auth = digests
+            .iter()
+            .find(|d| d.metadata.get("domain").map(String::as_str) == Some("auth"))
+            .unwrap();
+        assert_eq!(auth.metadata[GENERATED_LABEL], "true");
+        assert_eq!(auth.metadata[DIGEST_OF_LABEL], "intent");
+        assert!(auth.content.contains(&login.id));
+
+        let plan = service.plan("intent", Some("domain")).await.unwrap();
+        assert!(plan.is_empty());
+        assert_eq!(plan.unchanged, 2);
+        assert_eq!(summarizer.0.load(Ordering::SeqCst), 2);
+
+        artifacts
+            .update(
+                &login.id,
+                Some("Users sign in with a passkey.".to_string()),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .unwrap();
+        let plan = service.plan("intent", Some("domain")).await.unwrap();
+        assert_eq!(plan.count(SyncAction::Update), 1);
+        assert_eq!(plan.unchanged, 1);
+        assert_eq!(summarizer.0.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn extractive_digest_quotes_central_opening_sentences() {
+        let mut artifacts = Vec::new();
+        for (id, content, embedding) in [
+            (
+                "a",
+                "# Login\n\nUsers sign in with email. Then more.",
+                vec![1.0, 0.0],
+            ),
+            ("b", "Sessions expire after 30 days.", vec![0.9, 0.1]),
+            ("c", "Unrelated outlier!", vec![0.0, 1.0]),
+        ] {
+            let mut artifact = Artifact::new(
+                "intent".to_string(),
+                content.to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                "test".to_string(),
+            );
+            artifact.id = id.to_string();
+            artifact.embedding = Some(embedding);
+            artifacts.push(artifact);
+        }
+
+        let summary = ExtractiveSummarizer::new(2)
+            .summarize("intent", &artifacts)
+            .await
+            .unwrap();
+        assert_eq!(
+            summary,
+            "- Sessions expire after 30 days. (b)\n- Users sign in with email. (a)"
+        );
+        assert_eq!(
+            first_sentence("Users sign in with email. Then more."),
+            Some("Users sign in with email.".to_string())
+        );
+        assert_eq!(first_sentence("\n\n"), None);
+    }
+}
@@ -0,0 +1,139 @@
+//! What is embedded for an artifact's content.
+//!
+//! Only the content is embedded unless a kind's `embed` setting says
+//! otherwise: `full` adds the artifact's name and labels, and a template
+//! such as `{kind}: {name}\n{content}` composes the text freely. The
+//! template an embedding was made with is stored alongside it, so a change
+//! of setting shows up as an [`EmbeddingIssue`](super::EmbeddingIssue)
+//! that `dna reindex --missing-only` repairs.
+
+use super::types::Artifact;
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// `embed` setting for the content alone, the default
+pub const CONTENT: &str = "content";
+
+/// `embed` setting for name, labels and content
+pub const FULL: &str = "full";
+
+/// Template `full` stands for
+const FULL_TEMPLATE: &str = "{name}\n{labels}\n{content}";
+
+/// Placeholders a template can use
+pub const PLACEHOLDERS: &[&str] = &["kind", "name", "namespace", "labels", "content"];
+
+static PLACEHOLDER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\{([a-z_]+)\}").unwrap());
+
+/// The template an `embed` setting stands for, or `None` for the content
+/// alone.
+///
+/// Rejects templates with unknown placeholders or without `{content}`.
+pub fn template(setting: &str) -> Result<Option<String>, String> {
+    match setting.trim() {
+        CONTENT | "" | "{content}" => return Ok(None),
+        FULL => return Ok(Some(FULL_TEMPLATE.to_string())),
+        _ => {},
+    }
+    if let Some(unknown) = PLACEHOLDER
+        .captures_iter(setting)
+        .map(|c| c.get(1).unwrap().as_str())
+        .find(|name| !PLACEHOLDERS.contains(name))
+    {
+        return Err(format!(
+            "unknown placeholder {{{}}} (expected one of {})",
+            unknown,
+            PLACEHOLDERS
+                .iter()
+                .map(|p| format!("{{{}}}", p))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    if !setting.contains("{content}") {
+        return Err(format!(
+            "`{}` is neither `{}` nor `{}` and has no {{content}} placeholder",
+            setting, CONTENT, FULL
+        ));
+    }
+    Ok(Some(setting.to_string()))
+}
+
+/// `template` filled in from `artifact`, trimmed.
+///
+/// Labels read as `key: value`, sorted by key and comma-separated; a
+/// missing name or namespace is left empty.
+pub fn compose(template: &str, artifact: &Artifact) -> String {
+    let composed = PLACEHOLDER.replace_all(template, |caps: &regex::Captures| match &caps[1] {
+        "kind" => artifact.kind.clone(),
+        "name" => artifact.name.clone().unwrap_or_default(),
+        "namespace" => artifact.namespace.clone().unwrap_or_default(),
+        "labels" => {
+            let mut labels: Vec<_> = artifact.metadata.iter().collect();
+            labels.sort();
+            labels
+                .into_iter()
+                .map(|(key, value)| format!("{}: {}", key, value))
+                .collect::<Vec<_>>()
+                .join(", ")
+        },
+        "content" => artifact.content.clone(),
+        _ => caps[0].to_string(),
+    });
+    composed.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use std::collections::HashMap;
+
+    fn artifact() -> Artifact {
+        Artifact::new(
+            "runbook".to_string(),
+            "Restart the worker pool.".to_string(),
+            ContentFormat::Markdown,
+            Some("Stuck queue".to_string()),
+            HashMap::from([
+                ("team".to_string(), "infra".to_string()),
+                ("area".to_string(), "queue".to_string()),
+            ]),
+            "test".to_string(),
+        )
+    }
+
+    #[test]
+    fn settings_resolve_to_templates() {
+        assert_eq!(template("content").unwrap(), None);
+        assert_eq!(template("full").unwrap().as_deref(), Some(FULL_TEMPLATE));
+        assert_eq!(
+            template("{kind}: {name}\n{content}").unwrap().as_deref(),
+            Some("{kind}: {name}\n{content}")
+        );
+        assert!(template("{title}\n{content}")
+            .unwrap_err()
+            .contains("{title}"));
+        assert!(template("{name}").unwrap_err().contains("{content}"));
+    }
+
+    #[test]
+    fn compose_fills_in_name_labels_and_content() {
+        let mut artifact = artifact();
+        assert_eq!(
+            compose(FULL_TEMPLATE, &artifact),
+            "Stuck queue\narea: queue, team: infra\nRestart the worker pool."
+        );
+        assert_eq!(
+            compose("{kind}: {name}\n{content}", &artifact),
+            "runbook: Stuck queue\nRestart the worker pool."
+        );
+
+        artifact.name = None;
+        artifact.metadata.clear();
+        assert_eq!(
+            compose(FULL_TEMPLATE, &artifact),
+            "Restart the worker pool."
+        );
+    }
+}
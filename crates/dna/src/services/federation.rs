@@ -0,0 +1,528 @@
+//! Search across several stores at once.
+//!
+//! Platform teams often keep a store per domain while agents need one place
+//! to search. A federated search sends the query to every store, merges the
+//! hits into a single ranking and tags each with the store it came from.
+
+use super::language::LANGUAGE_LABEL;
+use super::search::SearchService;
+use super::types::{SearchFilters, SearchResult, SearchWeights};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Name results from the project's own store are tagged with
+pub const LOCAL_STORE: &str = "local";
+
+/// Pages of a remote store's results asked for before settling for fewer
+/// hits than the limit
+const MAX_PAGES: usize = 5;
+
+/// A store a federated search can query
+#[async_trait::async_trait]
+pub trait SearchSource: Send + Sync {
+    /// Ranked results for `query`, as [`SearchService::search_boosted`] returns them
+    async fn query(
+        &self,
+        query: &str,
+        filters: SearchFilters,
+        weights: SearchWeights,
+        boosts: &HashMap<String, f32>,
+    ) -> Result<Vec<SearchResult>>;
+}
+
+#[async_trait::async_trait]
+impl SearchSource for SearchService {
+    async fn query(
+        &self,
+        query: &str,
+        filters: SearchFilters,
+        weights: SearchWeights,
+        boosts: &HashMap<String, f32>,
+    ) -> Result<Vec<SearchResult>> {
+        self.search_boosted(query, filters, weights, boosts).await
+    }
+}
+
+/// A store served by dna-server, searched through its REST API
+pub struct HttpStore {
+    client: reqwest::Client,
+    url: String,
+    api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SearchResponse {
+    results: Vec<SearchResult>,
+}
+
+impl HttpStore {
+    /// Store served at `url`, the server's base URL
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Authenticate with an API key
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// One page of the remote store's results, leaving out `exclude_ids`
+    async fn fetch(
+        &self,
+        query: &str,
+        filters: &SearchFilters,
+        exclude_ids: Vec<String>,
+        weights: SearchWeights,
+        boosts: &HashMap<String, f32>,
+    ) -> Result<Vec<SearchResult>> {
+        let body = serde_json::json!({
+            "query": query,
+            "kind": filters.kind,
+            "namespace": filters.namespace,
+            "limit": filters.limit,
+            "weights": weights,
+            "boosts": boosts,
+            "kind_not": filters.kind_not,
+            "metadata_not": filters.metadata_not,
+            "exclude_ids": exclude_ids,
+            "include_deprecated": !filters.exclude_deprecated,
+            "language": filters.metadata.get(LANGUAGE_LABEL),
+        });
+        let url = format!("{}/api/v1/search", self.url);
+        let mut request = self.client.post(&url).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response: SearchResponse = request
+            .send()
+            .await
+            .with_context(|| format!("Failed to reach {}", url))?
+            .error_for_status()?
+            .json()
+            .await
+            .with_context(|| format!("Unexpected search response from {}", url))?;
+        Ok(response.results)
+    }
+}
+
+#[async_trait::async_trait]
+impl SearchSource for HttpStore {
+    async fn query(
+        &self,
+        query: &str,
+        filters: SearchFilters,
+        weights: SearchWeights,
+        boosts: &HashMap<String, f32>,
+    ) -> Result<Vec<SearchResult>> {
+        // The REST API takes no label, field or date filters besides the
+        // language; apply them here, asking for more until enough pass
+        fill_filtered(&filters, |exclude_ids| {
+            self.fetch(query, &filters, exclude_ids, weights, boosts)
+        })
+        .await
+    }
+}
+
+/// Results of `fetch` that pass `filters`, up to `filters.limit`.
+///
+/// `fetch` is given the IDs to leave out and returns the next page of a
+/// ranking, at most `filters.limit` long. Pages are fetched until enough
+/// results pass, the ranking runs out, or [`MAX_PAGES`] have been read.
+async fn fill_filtered<F, Fut>(filters: &SearchFilters, mut fetch: F) -> Result<Vec<SearchResult>>
+where
+    F: FnMut(Vec<String>) -> Fut,
+    Fut: std::future::Future<Output = Result<Vec<SearchResult>>>,
+{
+    let mut exclude_ids = filters.exclude_ids.clone();
+    let mut results = Vec::new();
+    for _ in 0..MAX_PAGES {
+        let page = fetch(exclude_ids.clone()).await?;
+        let exhausted = filters.limit.is_none_or(|limit| page.len() < limit);
+        for result in page {
+            exclude_ids.push(result.artifact.id.clone());
+            if filters.matches(&result.artifact) {
+                results.push(result);
+            }
+        }
+        if exhausted || filters.limit.is_some_and(|limit| results.len() >= limit) {
+            break;
+        }
+    }
+    if let Some(limit) = filters.limit {
+        results.truncate(limit);
+    }
+    Ok(results)
+}
+
+/// A search hit and the store it came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FederatedResult {
+    #[serde(flatten)]
+    pub result: SearchResult,
+    pub store: String,
+}
+
+/// A store that could not be searched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreFailure {
+    pub store: String,
+    pub error: String,
+}
+
+/// Merged results of a federated search
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FederatedResults {
+    /// Hits from every store, best first
+    pub results: Vec<FederatedResult>,
+    /// Stores left out because their search failed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<StoreFailure>,
+}
+
+struct FederatedStore {
+    name: String,
+    source: Arc<dyn SearchSource>,
+    weight: f32,
+}
+
+/// Fans a search out to several stores and merges the results
+pub struct FederatedSearch {
+    stores: Vec<FederatedStore>,
+}
+
+impl FederatedSearch {
+    /// Federation of the project's own store, tagged [`LOCAL_STORE`]
+    pub fn new(local: Arc<dyn SearchSource>) -> Self {
+        Self {
+            stores: vec![FederatedStore {
+                name: LOCAL_STORE.to_string(),
+                source: local,
+                weight: 1.0,
+            }],
+        }
+    }
+
+    /// Also search `source`, multiplying its scores by `weight`
+    pub fn with_store(
+        mut self,
+        name: impl Into<String>,
+        source: Arc<dyn SearchSource>,
+        weight: f32,
+    ) -> Self {
+        self.stores.push(FederatedStore {
+            name: name.into(),
+            source,
+            weight,
+        });
+        self
+    }
+
+    /// Federation of `local` with the stores configured under
+    /// `[[search.federate]]`, opening stores on disk relative to `project_root`
+    #[cfg(feature = "lance")]
+    pub async fn from_config(
+        local: Arc<SearchService>,
+        stores: &[super::FederatedStoreConfig],
+        project_root: &std::path::Path,
+    ) -> Result<Self> {
+        let mut federation = Self::new(local);
+        for store in stores {
+            let weight = store.weight.unwrap_or(1.0);
+            if !weight.is_finite() || weight <= 0.0 {
+                anyhow::bail!(
+                    "Federated store '{}' has weight {}; weights must be positive",
+                    store.name,
+                    weight
+                );
+            }
+            if federation.store_names().any(|name| name == store.name) {
+                anyhow::bail!("Federated store name '{}' is used twice", store.name);
+            }
+            let source = open_store(store, project_root)
+                .await
+                .with_context(|| format!("Failed to open federated store '{}'", store.name))?;
+            federation = federation.with_store(store.name.clone(), source, weight);
+        }
+        Ok(federation)
+    }
+
+    /// Names of the searched stores, the project's own first
+    pub fn store_names(&self) -> impl Iterator<Item = &str> {
+        self.stores.iter().map(|store| store.name.as_str())
+    }
+
+    /// Search every store and rank the hits together by weighted score.
+    ///
+    /// `filters.limit` applies to each store and to the merged results. A
+    /// store that fails is reported in `failures` instead of failing the
+    /// search, unless every store fails.
+    pub async fn search(
+        &self,
+        query: &str,
+        filters: SearchFilters,
+        weights: SearchWeights,
+        boosts: &HashMap<String, f32>,
+    ) -> Result<FederatedResults> {
+        let searches = self
+            .stores
+            .iter()
+            .map(|store| store.source.query(query, filters.clone(), weights, boosts));
+        let outcomes = futures::future::join_all(searches).await;
+
+        let mut merged = FederatedResults::default();
+        let mut first_error = None;
+        for (store, outcome) in self.stores.iter().zip(outcomes) {
+            match outcome {
+                Ok(results) => merged.results.extend(results.into_iter().map(|mut result| {
+                    result.score *= store.weight;
+                    FederatedResult {
+                        result,
+                        store: store.name.clone(),
+                    }
+                })),
+                Err(err) => {
+                    merged.failures.push(StoreFailure {
+                        store: store.name.clone(),
+                        error: format!("{:#}", err),
+                    });
+                    first_error.get_or_insert_with(|| {
+                        err.context(format!("Failed to search store '{}'", store.name))
+                    });
+                },
+            }
+        }
+        if merged.failures.len() == self.stores.len() {
+            if let Some(err) = first_error {
+                return Err(err);
+            }
+        }
+
+        // Stable, so ties keep the order stores were configured in
+        merged.results.sort_by(|a, b| {
+            b.result
+                .score
+                .partial_cmp(&a.result.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        if let Some(limit) = filters.limit {
+            merged.results.truncate(limit);
+        }
+        Ok(merged)
+    }
+}
+
+/// Searchable form of a configured store
+#[cfg(feature = "lance")]
+async fn open_store(
+    store: &super::FederatedStoreConfig,
+    project_root: &std::path::Path,
+) -> Result<Arc<dyn SearchSource>> {
+    use super::stores::{self, StoreRegistry};
+
+    match (&store.store, &store.url) {
+        (Some(spec), None) => {
+            let root = stores::project_root(&project_root.join(spec));
+            let root = if stores::is_store(&root) {
+                root
+            } else {
+                StoreRegistry::from_env()?.resolve(spec)?
+            };
+            let config_service = super::ConfigService::new(&root);
+            let config = config_service.load()?;
+            let db = Arc::new(config_service.open_database(&root).await?);
+            let embedding = crate::embedding::create_provider(&config.model).await?;
+            Ok(Arc::new(SearchService::from_config(db, embedding, &config)))
+        },
+        (None, Some(url)) => {
+            let api_key = match &store.api_key_env {
+                Some(var) => Some(std::env::var(var).with_context(|| {
+                    format!("Environment variable {} with the API key is not set", var)
+                })?),
+                None => None,
+            };
+            Ok(Arc::new(HttpStore::new(url).with_api_key(api_key)))
+        },
+        _ => anyhow::bail!("Set exactly one of `store` and `url`"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::services::{Artifact, ContentFormat};
+    use crate::testing::{TestDatabase, TestEmbedding};
+
+    async fn store_with(kinds: &[&str]) -> Arc<dyn SearchSource> {
+        let db = Arc::new(TestDatabase::new());
+        for kind in kinds {
+            let artifact = Artifact::new(
+                kind.to_string(),
+                format!("{} content", kind),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                "test".to_string(),
+            );
+            db.insert(&artifact).await.unwrap();
+        }
+        Arc::new(SearchService::new(db, Arc::new(TestEmbedding)))
+    }
+
+    struct Unreachable;
+
+    #[async_trait::async_trait]
+    impl SearchSource for Unreachable {
+        async fn query(
+            &self,
+            _query: &str,
+            _filters: SearchFilters,
+            _weights: SearchWeights,
+            _boosts: &HashMap<String, f32>,
+        ) -> Result<Vec<SearchResult>> {
+            anyhow::bail!("connection refused")
+        }
+    }
+
+    #[tokio::test]
+    async fn merges_stores_by_weighted_score_and_tags_each_hit() {
+        let federation = FederatedSearch::new(store_with(&["intent"]).await)
+            .with_store("payments", store_with(&["contract"]).await, 1.1)
+            .with_store("search", store_with(&["invariant"]).await, 0.5);
+
+        let merged = federation
+            .search(
+                "anything",
+                SearchFilters::default(),
+                SearchWeights::default(),
+                &HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        let order: Vec<(&str, &str)> = merged
+            .results
+            .iter()
+            .map(|hit| (hit.store.as_str(), hit.result.artifact.kind.as_str()))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                ("payments", "contract"),
+                ("local", "intent"),
+                ("search", "invariant")
+            ]
+        );
+        assert!(merged.failures.is_empty());
+
+        let json = serde_json::to_value(&merged.results[0]).unwrap();
+        assert_eq!(json["store"], "payments");
+        assert_eq!(json["artifact"]["kind"], "contract");
+    }
+
+    #[tokio::test]
+    async fn failed_stores_are_reported_unless_all_fail() {
+        let federation = FederatedSearch::new(store_with(&["intent", "contract"]).await)
+            .with_store("remote", Arc::new(Unreachable), 1.0);
+        let filters = SearchFilters {
+            limit: Some(1),
+            ..Default::default()
+        };
+
+        let merged = federation
+            .search(
+                "anything",
+                filters,
+                SearchWeights::default(),
+                &HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(merged.results.len(), 1);
+        assert_eq!(merged.failures.len(), 1);
+        assert_eq!(merged.failures[0].store, "remote");
+        assert!(merged.failures[0].error.contains("connection refused"));
+
+        let err = FederatedSearch::new(Arc::new(Unreachable))
+            .search(
+                "anything",
+                SearchFilters::default(),
+                SearchWeights::default(),
+                &HashMap::new(),
+            )
+            .await
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("store 'local'"));
+    }
+
+    #[tokio::test]
+    async fn remote_results_are_refilled_past_hits_the_filters_leave_out() {
+        let ranking: Vec<SearchResult> = [
+            ("draft", "docs"),
+            ("a", "core"),
+            ("b", "core"),
+            ("c", "core"),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(rank, (id, team))| {
+            let mut artifact = Artifact::new(
+                "intent".to_string(),
+                format!("{} content", id),
+                ContentFormat::Markdown,
+                None,
+                HashMap::from([("team".to_string(), team.to_string())]),
+                "test".to_string(),
+            );
+            artifact.id = id.to_string();
+            SearchResult {
+                artifact,
+                score: 1.0 - rank as f32 / 10.0,
+            }
+        })
+        .collect();
+        // Serves `ranking` as the REST search does, honoring the limit and
+        // excluded IDs but not the label filter
+        let remote = |limit: usize, pages: &mut usize, exclude_ids: Vec<String>| {
+            *pages += 1;
+            let page: Vec<SearchResult> = ranking
+                .iter()
+                .filter(|r| !exclude_ids.contains(&r.artifact.id))
+                .take(limit)
+                .cloned()
+                .collect();
+            async move { Ok(page) }
+        };
+        let filters = |limit| SearchFilters {
+            metadata: HashMap::from([("team".to_string(), "core".to_string())]),
+            limit: Some(limit),
+            ..Default::default()
+        };
+
+        let mut pages = 0;
+        let results = fill_filtered(&filters(2), |exclude_ids| {
+            remote(2, &mut pages, exclude_ids)
+        })
+        .await
+        .unwrap();
+        let ids: Vec<&str> = results.iter().map(|r| r.artifact.id.as_str()).collect();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(pages, 2);
+
+        let mut pages = 0;
+        let results = fill_filtered(&filters(5), |exclude_ids| {
+            remote(5, &mut pages, exclude_ids)
+        })
+        .await
+        .unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(pages, 1);
+    }
+}
@@ -0,0 +1,202 @@
+//! YAML frontmatter of artifact files.
+//!
+//! `dna render` writes it and `dna sync` reads it back through the same
+//! [`Frontmatter`] type, so values with colons, quotes or newlines survive
+//! the round trip unchanged.
+
+use super::types::{Artifact, ContentFormat};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Opens the "Related" and "Attachments" sections `dna render` appends to
+/// Markdown files.
+///
+/// The sections are generated from labels, so everything from this line on
+/// is dropped when a file is read back.
+pub const RELATED_SECTION_MARKER: &str = "<!-- dna:related -->";
+
+/// Frontmatter fields of an artifact file; unknown fields are ignored
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Frontmatter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<ContentFormat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Labels; hand-written files may use numbers or booleans as values
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, serde_yaml::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Paths of related files, relative to this one (written, never read)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+impl Frontmatter {
+    /// Frontmatter describing a stored artifact
+    pub fn from_artifact(artifact: &Artifact) -> Self {
+        Self {
+            id: Some(artifact.id.clone()),
+            kind: Some(artifact.kind.clone()),
+            format: Some(artifact.format),
+            name: artifact.name.clone(),
+            metadata: artifact
+                .metadata
+                .iter()
+                .map(|(k, v)| (k.clone(), serde_yaml::Value::String(v.clone())))
+                .collect(),
+            context: artifact.context.clone(),
+            links: Vec::new(),
+            created_at: Some(artifact.created_at.to_rfc3339()),
+            updated_at: Some(artifact.updated_at.to_rfc3339()),
+        }
+    }
+
+    /// Parse the YAML between the `---` delimiters
+    pub fn parse(yaml: &str) -> Result<Self> {
+        if yaml.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_yaml::from_str(yaml).context("invalid frontmatter")
+    }
+
+    /// Serialize as YAML, ending in a newline
+    pub fn to_yaml(&self) -> Result<String> {
+        serde_yaml::to_string(self).context("Failed to serialize frontmatter")
+    }
+
+    /// Labels as strings; numbers and booleans are converted, null drops the label
+    pub fn labels(&self) -> Result<HashMap<String, String>> {
+        let mut labels = HashMap::new();
+        for (key, value) in &self.metadata {
+            let value = match value {
+                serde_yaml::Value::String(s) => s.clone(),
+                serde_yaml::Value::Bool(b) => b.to_string(),
+                serde_yaml::Value::Number(n) => n.to_string(),
+                serde_yaml::Value::Null => continue,
+                _ => anyhow::bail!("label '{}' must be a single value", key),
+            };
+            labels.insert(key.clone(), value);
+        }
+        Ok(labels)
+    }
+}
+
+/// Split `---` frontmatter from the body of a file.
+///
+/// The blank line `dna render` writes after the frontmatter and the related
+/// links it appends after the content are not part of the body.
+pub fn split(text: &str) -> Option<(&str, &str)> {
+    let rest = text.strip_prefix("---\n")?;
+    let (frontmatter, body) = match rest.strip_prefix("---") {
+        Some(body) => ("", body),
+        None => {
+            let end = rest.find("\n---")?;
+            // Keep the newline ending the last field, as YAML emitters write it
+            (&rest[..end + 1], &rest[end + 4..])
+        },
+    };
+    // Drop the end of the delimiter line and the blank line render writes
+    let body = body.strip_prefix('\n').unwrap_or(body);
+    let body = body.strip_prefix('\n').unwrap_or(body);
+    // And the related links render appends after the content
+    let body = match body.rfind(RELATED_SECTION_MARKER) {
+        Some(start) => body[..start].strip_suffix("\n\n").unwrap_or(&body[..start]),
+        None => body,
+    };
+    Some((frontmatter, body))
+}
+
+/// A file of `frontmatter` followed by `body`, as [`split`] reads it
+pub fn join(frontmatter: &Frontmatter, body: &str) -> Result<String> {
+    Ok(format!("---\n{}---\n\n{}", frontmatter.to_yaml()?, body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::strategies;
+    use proptest::prelude::*;
+
+    #[test]
+    fn awkward_values_round_trip() {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            "Users can log in\n".to_string(),
+            ContentFormat::Markdown,
+            Some("login: v2".to_string()),
+            HashMap::from([
+                ("owner".to_string(), "team: auth # core".to_string()),
+                ("version".to_string(), "2".to_string()),
+                ("quote".to_string(), "it's \"fine\"".to_string()),
+                ("empty".to_string(), String::new()),
+            ]),
+            "test-model".to_string(),
+        );
+        artifact.context = Some("first line\nsecond: line\n".to_string());
+
+        let text = join(&Frontmatter::from_artifact(&artifact), &artifact.content).unwrap();
+        let (yaml, body) = split(&text).unwrap();
+        let parsed = Frontmatter::parse(yaml).unwrap();
+
+        assert_eq!(body, artifact.content);
+        assert_eq!(parsed, Frontmatter::from_artifact(&artifact));
+        assert_eq!(parsed.labels().unwrap(), artifact.metadata);
+        assert_eq!(parsed.name, artifact.name);
+        assert_eq!(parsed.context, artifact.context);
+    }
+
+    #[test]
+    fn labels_accept_scalars_only() {
+        let parsed =
+            Frontmatter::parse("metadata:\n  version: 2\n  draft: true\n  gone: null\n").unwrap();
+        let labels = parsed.labels().unwrap();
+        assert_eq!(labels.get("version").map(String::as_str), Some("2"));
+        assert_eq!(labels.get("draft").map(String::as_str), Some("true"));
+        assert!(!labels.contains_key("gone"));
+
+        let nested = Frontmatter::parse("metadata:\n  owner:\n    team: auth\n").unwrap();
+        assert!(nested.labels().is_err());
+    }
+
+    #[test]
+    fn split_handles_empty_frontmatter_and_missing_delimiters() {
+        assert_eq!(split("---\n---\n\nbody"), Some(("", "body")));
+        assert_eq!(split("no frontmatter"), None);
+        assert_eq!(split("---\nid: a\n"), None);
+    }
+
+    proptest! {
+        #[test]
+        fn adversarial_artifacts_round_trip(artifact in strategies::artifact()) {
+            prop_assume!(!artifact.content.contains(RELATED_SECTION_MARKER));
+
+            let written = Frontmatter::from_artifact(&artifact);
+            let text = join(&written, &artifact.content).unwrap();
+            let (yaml, body) = split(&text).expect("join writes both delimiters");
+            let parsed = Frontmatter::parse(yaml).unwrap();
+
+            prop_assert_eq!(body, artifact.content.as_str());
+            prop_assert_eq!(parsed.labels().unwrap(), artifact.metadata.clone());
+            prop_assert_eq!(parsed, written);
+        }
+
+        #[test]
+        fn arbitrary_files_parse_or_fail_cleanly(text in any::<String>()) {
+            if let Some((yaml, _)) = split(&text) {
+                if let Ok(frontmatter) = Frontmatter::parse(yaml) {
+                    let _ = frontmatter.labels();
+                }
+            }
+        }
+    }
+}
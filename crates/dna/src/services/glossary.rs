@@ -0,0 +1,468 @@
+//! Terminology checks against the glossary.
+//!
+//! Each glossary artifact defines one term: its name, or else the text
+//! before the colon on its first line, as in `MRR: Monthly Recurring
+//! Revenue`. Other artifacts are scanned for near misses of those terms
+//! (misspellings, other casings of acronyms) and for acronyms the glossary
+//! does not define, each reported with the closest defined term.
+//!
+//! The glossary also serves as a synonym dictionary for search: a short
+//! definition (`ICP: Ideal Customer Profile`) and the values of a
+//! `synonyms` label are other names for the term, and
+//! [`Glossary::expand`] adds them to queries that use any one of them.
+
+use super::types::Artifact;
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// Kind holding glossary entries unless configured otherwise
+pub const DEFAULT_GLOSSARY_KIND: &str = "glossary";
+
+/// Label listing other names for a glossary term, comma-separated
+pub const SYNONYMS_LABEL: &str = "synonyms";
+
+/// Acronyms common enough to need no glossary entry
+const COMMON_ACRONYMS: &[&str] = &[
+    "AI", "API", "CLI", "CPU", "CSV", "DB", "DNS", "HTML", "HTTP", "HTTPS", "ID", "IP", "JSON",
+    "JWT", "LLM", "MCP", "OK", "PDF", "REST", "SDK", "SQL", "SSO", "TLS", "TODO", "UI", "URL",
+    "UTC", "UUID", "YAML",
+];
+
+/// Longest term, in words, taken from a first line
+const MAX_TERM_WORDS: usize = 5;
+
+static CODE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)```.*?```|`[^`\n]*`").unwrap());
+
+static WORD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9]+(?:['-][A-Za-z0-9]+)*").unwrap());
+
+/// A term defined in the glossary
+#[derive(Debug, Clone)]
+struct Term {
+    text: String,
+    lower: String,
+    words: usize,
+    /// Other names for the term
+    synonyms: Vec<String>,
+}
+
+/// How an artifact's wording departs from the glossary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TermIssue {
+    /// `found` looks like the defined term `term` but is written differently
+    Variant { found: String, term: String },
+    /// `found` is an acronym the glossary does not define; `suggestion` is
+    /// the closest defined term, if any is close
+    Undefined {
+        found: String,
+        suggestion: Option<String>,
+    },
+}
+
+/// Index of the terms defined by glossary artifacts
+#[derive(Debug, Clone, Default)]
+pub struct Glossary {
+    terms: Vec<Term>,
+}
+
+impl Glossary {
+    /// Index the terms `artifacts` define
+    pub fn new(artifacts: &[Artifact]) -> Self {
+        let mut seen = HashSet::new();
+        let mut terms: Vec<Term> = artifacts
+            .iter()
+            .filter_map(|artifact| Some((defined_term(artifact)?, artifact)))
+            .filter(|(term, _)| seen.insert(term.to_lowercase()))
+            .map(|(text, artifact)| Term {
+                lower: text.to_lowercase(),
+                words: text.split_whitespace().count(),
+                synonyms: synonyms(artifact, &text),
+                text,
+            })
+            .collect();
+        // Longest first, so "active user" is matched before "user"
+        terms.sort_by(|a, b| b.words.cmp(&a.words).then(a.lower.cmp(&b.lower)));
+        Self { terms }
+    }
+
+    /// Number of defined terms
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Whether no terms are defined
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Departures from the glossary in `artifact`, each word reported once.
+    /// Words in code and in `ignore` are left alone.
+    pub fn check(&self, artifact: &Artifact, ignore: &[String]) -> Vec<TermIssue> {
+        let prose = CODE.replace_all(&artifact.content, " ");
+        let words: Vec<&str> = WORD.find_iter(&prose).map(|m| m.as_str()).collect();
+        let ignored = |word: &str| {
+            COMMON_ACRONYMS.contains(&word) || ignore.iter().any(|i| i.eq_ignore_ascii_case(word))
+        };
+
+        let mut issues = Vec::new();
+        let mut reported = HashSet::new();
+        let mut covered = vec![false; words.len()];
+        for term in &self.terms {
+            for start in 0..words.len().saturating_sub(term.words - 1) {
+                let window = &words[start..start + term.words];
+                if covered[start..start + term.words].contains(&true) {
+                    continue;
+                }
+                let found = window.join(" ");
+                let lower = found.to_lowercase();
+                let exact = lower == term.lower || singular(&lower) == term.lower;
+                let variant = if exact {
+                    // Acronyms are only right in the glossary's casing
+                    is_acronym(&term.text) && singular(&found) != term.text
+                } else {
+                    close(&singular(&lower), &term.lower) && !self.defines(&lower)
+                };
+                if !exact && !variant {
+                    continue;
+                }
+                covered[start..start + term.words].fill(true);
+                if variant && !ignored(&found) && reported.insert(lower) {
+                    issues.push(TermIssue::Variant {
+                        found,
+                        term: term.text.clone(),
+                    });
+                }
+            }
+        }
+
+        for (i, word) in words.iter().enumerate() {
+            let acronym = singular(word);
+            if covered[i] || !is_acronym(&acronym) || ignored(&acronym) {
+                continue;
+            }
+            if self.defines(&acronym.to_lowercase()) || !reported.insert(acronym.to_lowercase()) {
+                continue;
+            }
+            issues.push(TermIssue::Undefined {
+                suggestion: self.closest(&acronym.to_lowercase()),
+                found: acronym,
+            });
+        }
+        issues
+    }
+
+    /// `query` followed by the other names of each term it uses, as in
+    /// `ICP churn (Ideal Customer Profile)`.
+    ///
+    /// Terms and synonyms match whole words, ignoring case; there is no
+    /// stemming. Names already in the query are not repeated, and a query
+    /// using no term comes back unchanged.
+    pub fn expand(&self, query: &str) -> String {
+        let words = lower_words(query);
+        let mut additions: Vec<&str> = Vec::new();
+        for term in self.terms.iter().filter(|t| !t.synonyms.is_empty()) {
+            let names: Vec<&str> = std::iter::once(term.text.as_str())
+                .chain(term.synonyms.iter().map(String::as_str))
+                .collect();
+            let used = |name: &str| contains_words(&words, &lower_words(name));
+            if !names.iter().any(|name| used(name)) {
+                continue;
+            }
+            for name in names {
+                let lower = name.to_lowercase();
+                if !used(name) && !additions.iter().any(|a| a.to_lowercase() == lower) {
+                    additions.push(name);
+                }
+            }
+        }
+        if additions.is_empty() {
+            query.to_string()
+        } else {
+            format!("{} ({})", query, additions.join(", "))
+        }
+    }
+
+    /// Whether `lower` is a defined term, ignoring case and a plural `s`
+    fn defines(&self, lower: &str) -> bool {
+        let singular = singular(lower);
+        self.terms
+            .iter()
+            .any(|term| term.lower == lower || term.lower == singular)
+    }
+
+    /// The defined term closest to `lower`, if it is close
+    fn closest(&self, lower: &str) -> Option<String> {
+        self.terms
+            .iter()
+            .filter(|term| term.lower.len().abs_diff(lower.len()) <= 1)
+            .map(|term| (distance(&term.lower, lower), term))
+            .filter(|(d, _)| *d <= 1)
+            .min_by_key(|(d, _)| *d)
+            .map(|(_, term)| term.text.clone())
+    }
+}
+
+/// The term a glossary artifact defines: its name, else the text before
+/// the colon on its first line
+fn defined_term(artifact: &Artifact) -> Option<String> {
+    if let Some(name) = artifact.name.as_deref().map(str::trim) {
+        if !name.is_empty() {
+            return Some(name.to_string());
+        }
+    }
+    let line = artifact
+        .content
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())?;
+    let line = line.trim_start_matches(['#', '-', '*', ' ']);
+    let (term, _) = line.split_once(':')?;
+    let term = term.trim().trim_matches('*').trim();
+    let words = term.split_whitespace().count();
+    (words > 0 && words <= MAX_TERM_WORDS).then(|| term.to_string())
+}
+
+/// Other names a glossary artifact gives its term: a definition of at most
+/// a few words on its first line, and the values of its `synonyms` label
+fn synonyms(artifact: &Artifact, term: &str) -> Vec<String> {
+    let mut synonyms = Vec::new();
+    let first_line = artifact
+        .content
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(|l| l.trim_start_matches(['#', '-', '*', ' ']));
+    let definition = first_line.and_then(|line| match line.split_once(':') {
+        Some((_, definition)) => Some(definition),
+        // A named entry's first line may be nothing but the long form
+        None if artifact.name.is_some() => Some(line),
+        None => None,
+    });
+    if let Some(definition) = definition {
+        let definition = definition
+            .trim()
+            .trim_matches('*')
+            .trim_end_matches('.')
+            .trim();
+        let words = definition.split_whitespace().count();
+        if words > 0 && words <= MAX_TERM_WORDS {
+            synonyms.push(definition.to_string());
+        }
+    }
+    if let Some(listed) = artifact.metadata.get(SYNONYMS_LABEL) {
+        synonyms.extend(
+            listed
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string),
+        );
+    }
+
+    let mut seen = HashSet::from([term.to_lowercase()]);
+    synonyms.retain(|s| seen.insert(s.to_lowercase()));
+    synonyms
+}
+
+/// The words of `text`, lowercased
+fn lower_words(text: &str) -> Vec<String> {
+    WORD.find_iter(text)
+        .map(|m| m.as_str().to_lowercase())
+        .collect()
+}
+
+/// Whether `phrase` occurs in `words` as consecutive words
+fn contains_words(words: &[String], phrase: &[String]) -> bool {
+    !phrase.is_empty() && words.windows(phrase.len()).any(|window| window == phrase)
+}
+
+/// An all-caps word of two to six letters or digits, starting with a letter
+fn is_acronym(word: &str) -> bool {
+    let len = word.chars().count();
+    (2..=6).contains(&len)
+        && word.starts_with(|c: char| c.is_ascii_uppercase())
+        && word
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+        && word.chars().filter(char::is_ascii_uppercase).count() >= 2
+}
+
+/// `word` without a plural `s`, as in `SLAs` or `users`
+fn singular(word: &str) -> String {
+    match word.strip_suffix('s') {
+        Some(stem) if stem.len() >= 2 && !stem.ends_with('s') => stem.to_string(),
+        _ => word.to_string(),
+    }
+}
+
+/// Whether `word` is a likely misspelling of `term`: one edit away for
+/// terms of five to ten characters, two for longer ones
+fn close(word: &str, term: &str) -> bool {
+    let len = term.chars().count();
+    let allowed = match len {
+        0..=4 => return false,
+        5..=10 => 1,
+        _ => 2,
+    };
+    word.chars().count().abs_diff(len) <= allowed && distance(word, term) <= allowed
+}
+
+/// Edit distance counting a swap of neighboring characters as one edit
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use std::collections::HashMap;
+
+    fn artifact(kind: &str, content: &str) -> Artifact {
+        Artifact::new(
+            kind.to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "test".to_string(),
+        )
+    }
+
+    fn glossary() -> Glossary {
+        Glossary::new(&[
+            artifact("glossary", "MRR: Monthly Recurring Revenue"),
+            artifact(
+                "glossary",
+                "Active user: any user who logged in within 30 days",
+            ),
+            artifact(
+                "glossary",
+                "Churn: customer who cancels within the billing period",
+            ),
+            artifact("glossary", "A definition without a term"),
+        ])
+    }
+
+    #[test]
+    fn indexes_terms_from_names_and_first_lines() {
+        let mut named = artifact("glossary", "Ideal customer profile.");
+        named.name = Some("ICP".to_string());
+        let glossary = Glossary::new(&[named, artifact("glossary", "**SLA**: uptime promise")]);
+        let mut terms: Vec<&str> = glossary.terms.iter().map(|t| t.text.as_str()).collect();
+        terms.sort();
+        assert_eq!(terms, ["ICP", "SLA"]);
+        assert_eq!(self::glossary().len(), 3);
+    }
+
+    #[test]
+    fn expands_queries_with_synonyms() {
+        let mut icp = artifact("glossary", "Ideal customer profile.");
+        icp.name = Some("ICP".to_string());
+        let mut arr = artifact("glossary", "ARR: Annual Recurring Revenue");
+        arr.metadata.insert(
+            SYNONYMS_LABEL.to_string(),
+            "annual run rate, arr".to_string(),
+        );
+        let glossary = Glossary::new(&[
+            icp,
+            arr,
+            artifact("glossary", "MRR: Monthly Recurring Revenue"),
+        ]);
+
+        assert_eq!(
+            glossary.expand("ICP churn"),
+            "ICP churn (Ideal customer profile)"
+        );
+        assert_eq!(
+            glossary.expand("who matches the ideal customer profile"),
+            "who matches the ideal customer profile (ICP)"
+        );
+        assert_eq!(
+            glossary.expand("arr by region"),
+            "arr by region (Annual Recurring Revenue, annual run rate)"
+        );
+        assert_eq!(
+            glossary.expand("ICP and MRR"),
+            "ICP and MRR (Ideal customer profile, Monthly Recurring Revenue)"
+        );
+        // Whole words only
+        assert_eq!(glossary.expand("ICPs in EMEA"), "ICPs in EMEA");
+    }
+
+    #[test]
+    fn flags_variants_of_defined_terms() {
+        let issues = glossary().check(
+            &artifact(
+                "metric",
+                "Track Mrr weekly. Activ users and churn count, but `Mrr` in code is fine.",
+            ),
+            &[],
+        );
+        assert_eq!(
+            issues,
+            [
+                TermIssue::Variant {
+                    found: "Activ users".to_string(),
+                    term: "Active user".to_string(),
+                },
+                TermIssue::Variant {
+                    found: "Mrr".to_string(),
+                    term: "MRR".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn flags_undefined_acronyms_with_suggestions() {
+        let issues = glossary().check(
+            &artifact(
+                "metric",
+                "MMR and NPS feed the API dashboard; MRR is fine. NPS again.",
+            ),
+            &["KPI".to_string()],
+        );
+        assert_eq!(
+            issues,
+            [
+                TermIssue::Undefined {
+                    found: "MMR".to_string(),
+                    suggestion: Some("MRR".to_string()),
+                },
+                TermIssue::Undefined {
+                    found: "NPS".to_string(),
+                    suggestion: None,
+                },
+            ]
+        );
+        assert!(glossary()
+            .check(
+                &artifact("metric", "Our KPIs are SLAs."),
+                &["KPI".to_string(), "SLA".to_string()]
+            )
+            .is_empty());
+    }
+}
@@ -0,0 +1,221 @@
+//! Composite artifacts built from others with include directives.
+//!
+//! A line such as `<!-- dna:include k7v3m9xnp2 -->` in content stands for the
+//! content of that artifact, so a higher-level document can be composed from
+//! atomic truths instead of copying them and drifting. Directives are
+//! expanded when content is rendered or packed; stored content keeps them.
+//! Included artifacts may include others in turn, but never themselves.
+
+use super::artifact::ArtifactService;
+use super::types::Artifact;
+use super::ServiceError;
+use anyhow::Result;
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+static INCLUDE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"<!--\s*dna:include\s+([A-Za-z0-9_-]+)\s*-->").unwrap());
+
+/// Error returned when artifacts include each other in a loop
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("include cycle: {}", .0.join(" -> "))]
+pub struct IncludeCycle(pub Vec<String>);
+
+/// IDs named by the include directives in `text`, in order of first appearance
+pub fn include_ids(text: &str) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for cap in INCLUDE.captures_iter(text) {
+        if !ids.iter().any(|id| id == &cap[1]) {
+            ids.push(cap[1].to_string());
+        }
+    }
+    ids
+}
+
+/// Expands include directives, looking up artifacts outside the set being
+/// expanded through an [`ArtifactService`]
+struct Expander<'a> {
+    service: &'a ArtifactService,
+    /// Artifacts by ID, as given or looked up; None when not found
+    known: HashMap<String, Option<Artifact>>,
+    /// Content with every include expanded, by ID
+    expanded: HashMap<String, String>,
+    /// Includes of artifacts that don't exist, as (including, included)
+    stale: Vec<(String, String)>,
+}
+
+impl Expander<'_> {
+    async fn lookup(&mut self, id: &str) -> Result<Option<Artifact>> {
+        if let Some(found) = self.known.get(id) {
+            return Ok(found.clone());
+        }
+        let found = match self.service.get(id).await {
+            Ok(found) => found,
+            // An artifact the caller can't read is left out like a missing one
+            Err(e) => match ServiceError::classify(e) {
+                ServiceError::Forbidden(_) => None,
+                e => return Err(e.into()),
+            },
+        };
+        self.known.insert(id.to_string(), found.clone());
+        Ok(found)
+    }
+
+    /// Content of `artifact` with its includes expanded; `path` holds the
+    /// IDs being expanded that led here
+    async fn expand(&mut self, artifact: &Artifact, path: &mut Vec<String>) -> Result<String> {
+        if let Some(content) = self.expanded.get(&artifact.id) {
+            return Ok(content.clone());
+        }
+        if let Some(start) = path.iter().position(|id| id == &artifact.id) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(artifact.id.clone());
+            return Err(IncludeCycle(cycle).into());
+        }
+        path.push(artifact.id.clone());
+
+        let mut parts: HashMap<String, String> = HashMap::new();
+        for id in include_ids(&artifact.content) {
+            match self.lookup(&id).await? {
+                Some(included) => {
+                    if included.redirected_from.is_some() {
+                        tracing::warn!(
+                            "{} includes {} by its former ID; include {} instead",
+                            artifact.id,
+                            id,
+                            included.id
+                        );
+                    }
+                    let content = Box::pin(self.expand(&included, path)).await?;
+                    parts.insert(id, content);
+                },
+                None => self.stale.push((artifact.id.clone(), id)),
+            }
+        }
+        let content = INCLUDE
+            .replace_all(&artifact.content, |cap: &regex::Captures| {
+                parts
+                    .get(&cap[1])
+                    .cloned()
+                    .unwrap_or_else(|| cap[0].to_string())
+            })
+            .into_owned();
+
+        path.pop();
+        self.expanded.insert(artifact.id.clone(), content.clone());
+        Ok(content)
+    }
+}
+
+/// Replace the include directives in each artifact's content with the
+/// content of the artifact they name, expanded in turn.
+///
+/// Artifacts are looked up among `artifacts` first, then through `service`.
+/// Directives naming an artifact that doesn't exist are left as written and
+/// returned as (including ID, included ID), with a warning logged for each.
+/// Fails with [`IncludeCycle`] if artifacts include each other in a loop.
+pub async fn expand_includes(
+    service: &ArtifactService,
+    artifacts: &mut [Artifact],
+) -> Result<Vec<(String, String)>> {
+    if !artifacts.iter().any(|a| INCLUDE.is_match(&a.content)) {
+        return Ok(Vec::new());
+    }
+    let mut expander = Expander {
+        service,
+        known: artifacts
+            .iter()
+            .map(|a| (a.id.clone(), Some(a.clone())))
+            .collect(),
+        expanded: HashMap::new(),
+        stale: Vec::new(),
+    };
+    let mut contents = Vec::with_capacity(artifacts.len());
+    for artifact in artifacts.iter() {
+        contents.push(expander.expand(artifact, &mut Vec::new()).await?);
+    }
+    for (artifact, content) in artifacts.iter_mut().zip(contents) {
+        artifact.content = content;
+    }
+
+    for (including, included) in &expander.stale {
+        tracing::warn!(
+            "{} includes {}, which doesn't exist; left as written",
+            including,
+            included
+        );
+    }
+    Ok(expander.stale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::services::ContentFormat;
+    use crate::testing::{TestDatabase, TestEmbedding};
+    use std::sync::Arc;
+
+    fn artifact(id: &str, content: &str) -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            String::new(),
+        );
+        artifact.id = id.to_string();
+        artifact
+    }
+
+    fn service(db: Arc<TestDatabase>) -> ArtifactService {
+        ArtifactService::new(db, Arc::new(TestEmbedding))
+    }
+
+    #[test]
+    fn finds_include_ids() {
+        let text = "Intro\n<!-- dna:include k7v3m9xnp2 -->\n<!--dna:include  b5n7r2 -->\n\
+                    <!-- dna:include k7v3m9xnp2 -->";
+        assert_eq!(include_ids(text), vec!["k7v3m9xnp2", "b5n7r2"]);
+    }
+
+    #[tokio::test]
+    async fn expands_nested_includes_and_reports_stale_ones() {
+        let db = Arc::new(TestDatabase::new());
+        db.insert(&artifact("leaf", "Orders ship after payment"))
+            .await
+            .unwrap();
+        let service = service(db);
+
+        let mut artifacts = vec![
+            artifact(
+                "doc",
+                "# Checkout\n<!-- dna:include middle -->\n<!-- dna:include gone -->",
+            ),
+            artifact("middle", "Rules:\n<!-- dna:include leaf -->"),
+        ];
+        let stale = expand_includes(&service, &mut artifacts).await.unwrap();
+
+        assert_eq!(
+            artifacts[0].content,
+            "# Checkout\nRules:\nOrders ship after payment\n<!-- dna:include gone -->"
+        );
+        assert_eq!(artifacts[1].content, "Rules:\nOrders ship after payment");
+        assert_eq!(stale, vec![("doc".to_string(), "gone".to_string())]);
+    }
+
+    #[tokio::test]
+    async fn rejects_include_cycles() {
+        let service = service(Arc::new(TestDatabase::new()));
+        let mut artifacts = vec![
+            artifact("a", "<!-- dna:include b -->"),
+            artifact("b", "<!-- dna:include c -->"),
+            artifact("c", "<!-- dna:include a -->"),
+        ];
+        let err = expand_includes(&service, &mut artifacts).await.unwrap_err();
+        assert_eq!(err.to_string(), "include cycle: a -> b -> c -> a");
+        assert_eq!(artifacts[0].content, "<!-- dna:include b -->");
+    }
+}
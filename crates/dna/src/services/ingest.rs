@@ -0,0 +1,282 @@
+use super::plan::{ApplyReport, Plan, PlanOperation, PlanService};
+use super::sync::SyncAction;
+use super::types::{slugify_kind, Artifact, ContentFormat, LabelsConfig, SearchFilters};
+use super::{ArtifactService, ServiceError};
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Label recording where an ingested artifact came from, e.g. `jira:PROJ-12`
+pub const SOURCE_LABEL: &str = "source";
+
+/// A record pulled from an external system, ready to become an artifact
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngestItem {
+    /// Stable identity in the source system, stored as the `source` label
+    pub source: String,
+    pub kind: String,
+    pub name: String,
+    /// Markdown content
+    pub content: String,
+    /// Labels besides `source`; existing labels not listed here are kept
+    pub labels: HashMap<String, String>,
+}
+
+/// What ingesting will do to one artifact
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestChange {
+    pub action: SyncAction,
+    pub source: String,
+    /// Artifact ID (filled in for created artifacts once applied)
+    pub id: Option<String>,
+    /// Fields that differ, for updates
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+}
+
+/// Changes needed to bring the store in line with the source, ready to apply
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestPlan {
+    /// One change per plan operation, in the same order
+    pub changes: Vec<IngestChange>,
+    pub unchanged: usize,
+    #[serde(skip)]
+    plan: Plan,
+}
+
+impl IngestPlan {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn count(&self, action: SyncAction) -> usize {
+        self.changes.iter().filter(|c| c.action == action).count()
+    }
+}
+
+/// Service that mirrors records from trackers and wikis into the store.
+///
+/// Artifacts are matched to records by their `source` label, so running the
+/// same ingestion again updates what changed instead of adding duplicates.
+/// Records that disappeared from the source are left alone. Changes are
+/// applied as one plan, so a failure rolls everything back.
+pub struct IngestService {
+    artifacts: Arc<ArtifactService>,
+    labels: LabelsConfig,
+}
+
+impl IngestService {
+    pub fn new(artifacts: Arc<ArtifactService>) -> Self {
+        Self {
+            artifacts,
+            labels: LabelsConfig::default(),
+        }
+    }
+
+    /// Validate ingested labels against a label registry
+    pub fn with_labels(mut self, labels: LabelsConfig) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Compare records with the store without changing anything
+    pub async fn plan(&self, items: &[IngestItem]) -> Result<IngestPlan> {
+        let mut seen = HashSet::new();
+        for item in items {
+            if !seen.insert(item.source.as_str()) {
+                anyhow::bail!("Source '{}' appears more than once", item.source);
+            }
+        }
+
+        // Archived artifacts still count, so archiving one doesn't resurrect it
+        let filters = SearchFilters {
+            include_archived: true,
+            ..Default::default()
+        };
+        let mut existing: HashMap<String, Artifact> = HashMap::new();
+        for artifact in self.artifacts.list(filters).await? {
+            let Some(source) = artifact.metadata.get(SOURCE_LABEL) else {
+                continue;
+            };
+            if !seen.contains(source.as_str()) {
+                continue;
+            }
+            // Keep the oldest if a source was somehow stored twice
+            match existing.get(source) {
+                Some(current) if current.created_at <= artifact.created_at => {},
+                _ => {
+                    existing.insert(source.clone(), artifact);
+                },
+            }
+        }
+
+        let mut plan = Plan::default();
+        let mut changes = Vec::new();
+        let mut unchanged = 0;
+
+        for item in items {
+            let mut labels = item.labels.clone();
+            labels.insert(SOURCE_LABEL.to_string(), item.source.clone());
+
+            let Some(current) = existing.get(&item.source) else {
+                plan.operations.push(PlanOperation::Add {
+                    id: None,
+                    kind: item.kind.clone(),
+                    content: item.content.clone(),
+                    format: Some(ContentFormat::Markdown),
+                    name: Some(item.name.clone()),
+                    labels,
+                    context: None,
+                    reference: None,
+                });
+                changes.push(IngestChange {
+                    action: SyncAction::Create,
+                    source: item.source.clone(),
+                    id: None,
+                    fields: Vec::new(),
+                });
+                continue;
+            };
+
+            let mut fields = Vec::new();
+            let content = (item.content != current.content).then(|| item.content.clone());
+            if content.is_some() {
+                fields.push("content".to_string());
+            }
+            let kind = slugify_kind(&item.kind);
+            let kind = (kind != current.kind).then_some(kind);
+            if kind.is_some() {
+                fields.push("kind".to_string());
+            }
+            let name = (current.name.as_ref() != Some(&item.name)).then(|| item.name.clone());
+            if name.is_some() {
+                fields.push("name".to_string());
+            }
+            labels.retain(|key, value| current.metadata.get(key) != Some(value));
+            if !labels.is_empty() {
+                fields.push("labels".to_string());
+            }
+
+            if fields.is_empty() {
+                unchanged += 1;
+                continue;
+            }
+
+            changes.push(IngestChange {
+                action: SyncAction::Update,
+                source: item.source.clone(),
+                id: Some(current.id.clone()),
+                fields,
+            });
+            plan.operations.push(PlanOperation::Update {
+                id: current.id.clone(),
+                content,
+                name,
+                kind,
+                labels: (!labels.is_empty()).then_some(labels),
+                context: None,
+            });
+        }
+
+        Ok(IngestPlan {
+            changes,
+            unchanged,
+            plan,
+        })
+    }
+
+    /// Apply an ingest plan all-or-nothing, filling in the IDs of created artifacts
+    pub async fn apply(&self, plan: &mut IngestPlan) -> Result<ApplyReport, ServiceError> {
+        if plan.is_empty() {
+            let version = self.artifacts.version().await?;
+            return Ok(ApplyReport {
+                version_before: version,
+                version_after: version,
+                steps: Vec::new(),
+            });
+        }
+
+        let plans = PlanService::new(Arc::clone(&self.artifacts)).with_labels(self.labels.clone());
+        let report = plans.apply(&plan.plan).await?;
+        for (change, step) in plan.changes.iter_mut().zip(&report.steps) {
+            if change.id.is_none() {
+                change.id = Some(step.target.clone());
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{TestDatabase, TestEmbedding};
+
+    fn service() -> (Arc<ArtifactService>, IngestService) {
+        let artifacts = Arc::new(ArtifactService::new(
+            Arc::new(TestDatabase::new()),
+            Arc::new(TestEmbedding),
+        ));
+        (Arc::clone(&artifacts), IngestService::new(artifacts))
+    }
+
+    fn item(source: &str, content: &str) -> IngestItem {
+        IngestItem {
+            source: source.to_string(),
+            kind: "Intent".to_string(),
+            name: format!("Ticket {}", source),
+            content: content.to_string(),
+            labels: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reruns_update_instead_of_duplicating() {
+        let (artifacts, service) = service();
+
+        let mut plan = service
+            .plan(&[item("jira:A-1", "one"), item("jira:A-2", "two")])
+            .await
+            .unwrap();
+        assert_eq!(plan.count(SyncAction::Create), 2);
+        service.apply(&mut plan).await.unwrap();
+        let created = plan.changes[0].id.clone().unwrap();
+
+        // A label added by hand survives re-ingestion
+        let mut labels = HashMap::new();
+        labels.insert("team".to_string(), "auth".to_string());
+        artifacts
+            .update(&created, None, None, None, Some(labels), None)
+            .await
+            .unwrap();
+
+        let mut plan = service
+            .plan(&[item("jira:A-1", "one, edited"), item("jira:A-2", "two")])
+            .await
+            .unwrap();
+        assert_eq!(plan.unchanged, 1);
+        assert_eq!(plan.changes.len(), 1);
+        assert_eq!(plan.changes[0].action, SyncAction::Update);
+        assert_eq!(plan.changes[0].id.as_deref(), Some(created.as_str()));
+        assert_eq!(plan.changes[0].fields, vec!["content"]);
+        service.apply(&mut plan).await.unwrap();
+
+        let all = artifacts.list(SearchFilters::default()).await.unwrap();
+        assert_eq!(all.len(), 2);
+        let updated = artifacts.get(&created).await.unwrap().unwrap();
+        assert_eq!(updated.content, "one, edited");
+        assert_eq!(updated.metadata["team"], "auth");
+        assert_eq!(updated.metadata[SOURCE_LABEL], "jira:A-1");
+    }
+
+    #[tokio::test]
+    async fn rejects_repeated_sources() {
+        let (_, service) = service();
+        let err = service
+            .plan(&[item("linear:ENG-1", "a"), item("linear:ENG-1", "b")])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("linear:ENG-1"), "{err}");
+    }
+}
@@ -13,6 +13,7 @@ pub struct KindService {
     kind_slug: String,
     db: Arc<dyn Database>,
     embedding: Arc<dyn EmbeddingProvider>,
+    id_generator: Arc<dyn IdGenerator>,
 }
 
 impl KindService {
@@ -25,9 +26,16 @@ impl KindService {
             kind_slug,
             db,
             embedding,
+            id_generator: Arc::new(IdConfig::default()),
         }
     }
 
+    /// Use a custom ID generator for newly added artifacts
+    pub fn with_id_generator(mut self, id_generator: Arc<dyn IdGenerator>) -> Self {
+        self.id_generator = id_generator;
+        self
+    }
+
     pub fn kind_slug(&self) -> &str {
         &self.kind_slug
     }
@@ -49,6 +57,7 @@ impl KindService {
             metadata,
             self.embedding.model_id().to_string(),
         );
+        artifact.id = Artifact::generate_id_with(self.id_generator.as_ref(), &self.kind_slug);
 
         // Generate content embedding
         let embedding = self
@@ -0,0 +1,147 @@
+//! Language detection and per-language embedding models.
+//!
+//! English-only models such as the default BGE rank non-English text poorly.
+//! With `[languages] detect`, [`ArtifactService`](super::ArtifactService)
+//! labels artifacts with the language of their content, so searches can
+//! filter on it, and [`SearchService`](super::SearchService) searches the
+//! embeddings of the model routed to the language the filter names.
+
+use super::limits::InputRejected;
+use super::types::{get_model_info, Artifact, LanguagesConfig};
+
+/// Label holding the ISO 639-3 code of an artifact's language
+pub const LANGUAGE_LABEL: &str = "language";
+
+/// ISO 639-3 code for English, the one language every model knows
+pub const ENGLISH: &str = "eng";
+
+/// ISO 639-3 code of the language `text` is written in, when it can be told
+/// reliably
+pub fn detect(text: &str) -> Option<&'static str> {
+    whatlang::detect(text)
+        .filter(|info| info.is_reliable())
+        .map(|info| info.lang().code())
+}
+
+impl LanguagesConfig {
+    /// Model whose embeddings are searched for `language`, if one is routed
+    pub fn model_for(&self, language: &str) -> Option<&str> {
+        self.models.get(language).map(String::as_str)
+    }
+
+    /// Label `artifact` with the language detected in its content.
+    ///
+    /// A language label the artifact already has is kept unless `redetect`
+    /// is set, as it is when the content changed; text too short to tell
+    /// keeps it either way.
+    pub fn label(&self, artifact: &mut Artifact, redetect: bool) {
+        if !self.detect || (!redetect && artifact.metadata.contains_key(LANGUAGE_LABEL)) {
+            return;
+        }
+        if let Some(language) = detect(&artifact.content) {
+            artifact
+                .metadata
+                .insert(LANGUAGE_LABEL.to_string(), language.to_string());
+        }
+    }
+
+    /// With `require_multilingual`, reject an artifact in a language that
+    /// `model` was not trained on and that no model is routed to
+    pub fn check(&self, artifact: &Artifact, model: &str) -> Result<(), InputRejected> {
+        if !self.require_multilingual || get_model_info(model).multilingual {
+            return Ok(());
+        }
+        match artifact.metadata.get(LANGUAGE_LABEL) {
+            Some(language) if language != ENGLISH && !self.models.contains_key(language) => {
+                Err(InputRejected(format!(
+                    "Content is in '{}' but {} only knows English; route '{}' to a \
+                     multilingual model under [languages.models]",
+                    language, model, language
+                )))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use std::collections::{BTreeMap, HashMap};
+
+    fn artifact(content: &str) -> Artifact {
+        Artifact::new(
+            "intent".to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "BAAI/bge-small-en-v1.5".to_string(),
+        )
+    }
+
+    const GERMAN: &str = "Bestellungen werden erst nach dem Eingang der Zahlung \
+                          versendet, damit keine offenen Rechnungen entstehen.";
+
+    #[test]
+    fn detection_names_languages_by_iso_639_3_code() {
+        assert_eq!(detect(GERMAN), Some("deu"));
+        assert_eq!(
+            detect("Orders ship only after payment has been received in full."),
+            Some(ENGLISH)
+        );
+        assert_eq!(detect(""), None);
+    }
+
+    #[test]
+    fn label_keeps_a_given_language_until_content_changes() {
+        let config = LanguagesConfig {
+            detect: true,
+            ..Default::default()
+        };
+        let mut german = artifact(GERMAN);
+        german
+            .metadata
+            .insert(LANGUAGE_LABEL.to_string(), "nld".to_string());
+        config.label(&mut german, false);
+        assert_eq!(german.metadata[LANGUAGE_LABEL], "nld");
+        config.label(&mut german, true);
+        assert_eq!(german.metadata[LANGUAGE_LABEL], "deu");
+
+        let mut unlabeled = artifact(GERMAN);
+        LanguagesConfig::default().label(&mut unlabeled, true);
+        assert!(!unlabeled.metadata.contains_key(LANGUAGE_LABEL));
+    }
+
+    #[test]
+    fn english_only_models_need_a_route_for_other_languages() {
+        let mut german = artifact(GERMAN);
+        let mut config = LanguagesConfig {
+            detect: true,
+            require_multilingual: true,
+            ..Default::default()
+        };
+        config.label(&mut german, false);
+
+        let err = config.check(&german, "BAAI/bge-small-en-v1.5").unwrap_err();
+        assert!(err.to_string().contains("'deu'"), "{err}");
+        assert!(config.check(&german, "text-embedding-3-small").is_ok());
+        assert!(config
+            .check(
+                &artifact("Orders ship after payment"),
+                "BAAI/bge-small-en-v1.5"
+            )
+            .is_ok());
+
+        config.models = BTreeMap::from([(
+            "deu".to_string(),
+            "intfloat/multilingual-e5-small".to_string(),
+        )]);
+        assert!(config.check(&german, "BAAI/bge-small-en-v1.5").is_ok());
+        assert_eq!(
+            config.model_for("deu"),
+            Some("intfloat/multilingual-e5-small")
+        );
+    }
+}
@@ -0,0 +1,182 @@
+//! Size and character checks on artifact input.
+//!
+//! [`LimitsConfig`] is checked by [`ArtifactService`](super::ArtifactService)
+//! before content is scanned or embedded, so the REST API, MCP tools and CLI
+//! all reject oversized or malformed input the same way, and before a
+//! provider is asked to embed it.
+
+use super::types::{Artifact, LimitsConfig};
+use std::collections::HashMap;
+
+/// Error returned when artifact input breaks a configured limit
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("{0}")]
+pub struct InputRejected(pub String);
+
+impl LimitsConfig {
+    /// Check the user-supplied parts of an artifact against the limits
+    pub fn check_artifact(&self, artifact: &Artifact) -> Result<(), InputRejected> {
+        self.check_text("content", &artifact.content)?;
+        if let Some(context) = &artifact.context {
+            self.check_text("context", context)?;
+        }
+        if let Some(name) = &artifact.name {
+            self.check_line("name", name)?;
+        }
+        self.check_metadata(&artifact.metadata)
+    }
+
+    /// Content and context: bounded size, newlines and tabs allowed
+    fn check_text(&self, field: &str, text: &str) -> Result<(), InputRejected> {
+        if text.len() > self.max_content_bytes {
+            return Err(InputRejected(format!(
+                "{} is {} bytes, over the limit of {}",
+                capitalize(field),
+                text.len(),
+                self.max_content_bytes
+            )));
+        }
+        if self.reject_control_chars {
+            if let Some(c) = text
+                .chars()
+                .find(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t'))
+            {
+                return Err(control_char(field, c));
+            }
+        }
+        Ok(())
+    }
+
+    /// Names, label keys and values: no control characters at all
+    fn check_line(&self, field: &str, text: &str) -> Result<(), InputRejected> {
+        if self.reject_control_chars {
+            if let Some(c) = text.chars().find(|c| c.is_control()) {
+                return Err(control_char(field, c));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_metadata(&self, metadata: &HashMap<String, String>) -> Result<(), InputRejected> {
+        if metadata.len() > self.max_metadata_entries {
+            return Err(InputRejected(format!(
+                "{} metadata entries, over the limit of {}",
+                metadata.len(),
+                self.max_metadata_entries
+            )));
+        }
+        for (key, value) in metadata {
+            if key.len() > self.max_metadata_key_length {
+                return Err(InputRejected(format!(
+                    "Metadata key '{}...' is over the limit of {} bytes",
+                    key.chars().take(32).collect::<String>(),
+                    self.max_metadata_key_length
+                )));
+            }
+            if value.len() > self.max_metadata_value_length {
+                return Err(InputRejected(format!(
+                    "Metadata value for '{}' is over the limit of {} bytes",
+                    key, self.max_metadata_value_length
+                )));
+            }
+            self.check_line("metadata key", key)?;
+            self.check_line(&format!("metadata value for '{}'", key), value)?;
+        }
+        Ok(())
+    }
+}
+
+fn control_char(field: &str, c: char) -> InputRejected {
+    InputRejected(format!(
+        "{} contains control character U+{:04X}",
+        capitalize(field),
+        c as u32
+    ))
+}
+
+fn capitalize(field: &str) -> String {
+    let mut chars = field.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+
+    fn artifact(content: &str) -> Artifact {
+        Artifact::new(
+            "intent".to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        )
+    }
+
+    #[test]
+    fn accepts_ordinary_input() {
+        let limits = LimitsConfig::default();
+        let mut artifact = artifact("# Title\n\n\tIndented line\r\n");
+        artifact.name = Some("Login flow".to_string());
+        artifact
+            .metadata
+            .insert("team".to_string(), "platform".to_string());
+        assert!(limits.check_artifact(&artifact).is_ok());
+    }
+
+    #[test]
+    fn rejects_oversized_content_and_context() {
+        let limits = LimitsConfig {
+            max_content_bytes: 8,
+            ..Default::default()
+        };
+        let err = limits.check_artifact(&artifact("123456789")).unwrap_err();
+        assert_eq!(err.0, "Content is 9 bytes, over the limit of 8");
+
+        let mut with_context = artifact("short");
+        with_context.context = Some("far too long".to_string());
+        assert!(limits.check_artifact(&with_context).is_err());
+    }
+
+    #[test]
+    fn rejects_control_characters_unless_disabled() {
+        let limits = LimitsConfig::default();
+        let err = limits.check_artifact(&artifact("nul\0byte")).unwrap_err();
+        assert_eq!(err.0, "Content contains control character U+0000");
+
+        let mut named = artifact("fine");
+        named.name = Some("two\nlines".to_string());
+        assert!(limits.check_artifact(&named).is_err());
+
+        let permissive = LimitsConfig {
+            reject_control_chars: false,
+            ..Default::default()
+        };
+        assert!(permissive.check_artifact(&artifact("nul\0byte")).is_ok());
+    }
+
+    #[test]
+    fn limits_metadata_entries_and_lengths() {
+        let limits = LimitsConfig {
+            max_metadata_entries: 1,
+            max_metadata_key_length: 4,
+            ..Default::default()
+        };
+
+        let mut too_many = artifact("fine");
+        too_many.metadata.insert("a".to_string(), "1".to_string());
+        too_many.metadata.insert("b".to_string(), "2".to_string());
+        assert!(limits.check_artifact(&too_many).is_err());
+
+        let mut long_key = artifact("fine");
+        long_key
+            .metadata
+            .insert("owner".to_string(), "me".to_string());
+        assert!(limits.check_artifact(&long_key).is_err());
+    }
+}
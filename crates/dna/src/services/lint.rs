@@ -0,0 +1,635 @@
+//! Content lint rules per kind.
+//!
+//! Kind descriptions and templates set expectations for how artifacts are
+//! written, such as short imperative statements with an example. `dna lint`
+//! checks artifacts against the rules configured for their kind under
+//! `[lint.kinds.<slug>]` and can fix the mechanical ones: replaceable words,
+//! stray whitespace and required labels that have a default. With
+//! `[lint.glossary]` set, wording is also checked against the terms the
+//! glossary defines.
+
+use super::glossary::{Glossary, TermIssue, DEFAULT_GLOSSARY_KIND};
+use super::types::{
+    Artifact, GlossaryLintConfig, LintConfig, LintRules, LintSeverity, SearchFilters,
+};
+use super::{ArtifactService, ServiceError};
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock};
+
+/// Rule names with their default severities, in reporting order
+const RULES: &[(&str, LintSeverity)] = &[
+    ("required_labels", LintSeverity::Error),
+    ("max_length", LintSeverity::Warning),
+    ("modal", LintSeverity::Warning),
+    ("forbidden_words", LintSeverity::Warning),
+    ("require_example", LintSeverity::Warning),
+    ("replace", LintSeverity::Info),
+    ("trim_whitespace", LintSeverity::Info),
+];
+
+/// Glossary rule names with their default severities
+const GLOSSARY_RULES: &[(&str, LintSeverity)] = &[
+    ("glossary_variant", LintSeverity::Warning),
+    ("glossary_undefined", LintSeverity::Info),
+];
+
+static EXAMPLE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)```|\be\.g\.|\bexamples?\b|\bex:|\bfor instance\b").unwrap());
+
+/// A rule an artifact breaks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Violation {
+    pub id: String,
+    pub kind: String,
+    pub rule: String,
+    pub severity: LintSeverity,
+    pub message: String,
+    /// Whether `--fix` can fix it
+    pub fixable: bool,
+    /// Wording to use instead, for glossary violations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// Rules of one kind, ready to apply
+struct KindRules {
+    rules: LintRules,
+    modal: Vec<Regex>,
+    forbidden: Vec<(String, Regex)>,
+    replace: Vec<(Regex, String)>,
+    /// Required label keys with the default `--fix` sets, if any
+    labels: Vec<(String, Option<String>)>,
+}
+
+/// Checks artifacts against the lint rules of their kind
+#[derive(Default)]
+pub struct Linter {
+    kinds: HashMap<String, KindRules>,
+    glossary: Option<GlossaryLintConfig>,
+}
+
+impl Linter {
+    /// Build a linter from project configuration.
+    ///
+    /// Fails on an unknown rule name in a severity override.
+    pub fn from_config(config: &LintConfig) -> Result<Self> {
+        if let Some(glossary) = &config.glossary {
+            check_severities(&glossary.severity, GLOSSARY_RULES, "the glossary")?;
+        }
+        let mut kinds = HashMap::new();
+        for (kind, rules) in &config.kinds {
+            check_severities(&rules.severity, RULES, &format!("kind '{}'", kind))?;
+            let labels = rules
+                .required_labels
+                .iter()
+                .map(|label| match label.split_once('=') {
+                    Some((key, default)) => (key.trim().to_string(), Some(default.to_string())),
+                    None => (label.trim().to_string(), None),
+                })
+                .collect();
+            let mut replace: Vec<(Regex, String)> = rules
+                .replace
+                .iter()
+                .map(|(word, replacement)| Ok((word_pattern(word)?, replacement.clone())))
+                .collect::<Result<_>>()?;
+            // Longest first, so a phrase wins over a word inside it
+            replace.sort_by_key(|(pattern, _)| std::cmp::Reverse(pattern.as_str().len()));
+            kinds.insert(
+                kind.clone(),
+                KindRules {
+                    modal: rules
+                        .modal
+                        .iter()
+                        .map(|word| word_pattern(word))
+                        .collect::<Result<_>>()?,
+                    forbidden: rules
+                        .forbidden_words
+                        .iter()
+                        .map(|word| Ok((word.clone(), word_pattern(word)?)))
+                        .collect::<Result<_>>()?,
+                    replace,
+                    labels,
+                    rules: rules.clone(),
+                },
+            );
+        }
+        Ok(Self {
+            kinds,
+            glossary: config.glossary.clone(),
+        })
+    }
+
+    /// Whether no kind has rules and the glossary is not checked
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty() && self.glossary.is_none()
+    }
+
+    /// Kind holding the glossary, when terminology is checked
+    pub fn glossary_kind(&self) -> Option<&str> {
+        let config = self.glossary.as_ref()?;
+        Some(config.kind.as_deref().unwrap_or(DEFAULT_GLOSSARY_KIND))
+    }
+
+    /// Whether artifacts of `kind` are checked
+    pub fn applies_to(&self, kind: &str) -> bool {
+        self.kinds.contains_key(kind) || self.checks_terms(kind)
+    }
+
+    /// Whether artifacts of `kind` are checked against the glossary
+    fn checks_terms(&self, kind: &str) -> bool {
+        match (&self.glossary, self.glossary_kind()) {
+            (Some(config), Some(glossary_kind)) => {
+                kind != glossary_kind
+                    && (config.kinds.is_empty() || config.kinds.iter().any(|k| k == kind))
+            },
+            _ => false,
+        }
+    }
+
+    /// Where the wording of `artifact` departs from `glossary`
+    pub fn check_terms(&self, artifact: &Artifact, glossary: &Glossary) -> Vec<Violation> {
+        let Some(config) = self
+            .glossary
+            .as_ref()
+            .filter(|_| self.checks_terms(&artifact.kind))
+        else {
+            return Vec::new();
+        };
+        glossary
+            .check(artifact, &config.ignore)
+            .into_iter()
+            .map(|issue| {
+                let (rule, message, suggestion) = match issue {
+                    TermIssue::Variant { found, term } => (
+                        "glossary_variant",
+                        format!("'{}' looks like the glossary term '{}'", found, term),
+                        Some(term),
+                    ),
+                    TermIssue::Undefined { found, suggestion } => (
+                        "glossary_undefined",
+                        match &suggestion {
+                            Some(term) => format!(
+                                "'{}' is not in the glossary; did you mean '{}'?",
+                                found, term
+                            ),
+                            None => format!("'{}' is not in the glossary", found),
+                        },
+                        suggestion,
+                    ),
+                };
+                Violation {
+                    id: artifact.id.clone(),
+                    kind: artifact.kind.clone(),
+                    rule: rule.to_string(),
+                    severity: severity(&config.severity, GLOSSARY_RULES, rule),
+                    message,
+                    fixable: false,
+                    suggestion,
+                }
+            })
+            .collect()
+    }
+
+    /// The rules `artifact` breaks, most severe first
+    pub fn check(&self, artifact: &Artifact) -> Vec<Violation> {
+        let Some(kind) = self.kinds.get(&artifact.kind) else {
+            return Vec::new();
+        };
+        let rules = &kind.rules;
+        let content = &artifact.content;
+        let mut found: Vec<(&str, String, bool)> = Vec::new();
+
+        for (key, default) in &kind.labels {
+            if artifact.metadata.get(key).is_none_or(|v| v.is_empty()) {
+                found.push((
+                    "required_labels",
+                    format!("missing label '{}'", key),
+                    default.is_some(),
+                ));
+            }
+        }
+        if let Some(max) = rules.max_length {
+            let length = content.chars().count();
+            if length > max {
+                found.push((
+                    "max_length",
+                    format!(
+                        "content is {} characters, over the limit of {}",
+                        length, max
+                    ),
+                    false,
+                ));
+            }
+        }
+        if !kind.modal.is_empty() {
+            let sentence = first_sentence(content);
+            if !kind.modal.iter().any(|pattern| pattern.is_match(sentence)) {
+                found.push((
+                    "modal",
+                    format!(
+                        "first sentence does not use {}",
+                        quoted_list(&rules.modal, "or")
+                    ),
+                    false,
+                ));
+            }
+        }
+        let vague: Vec<String> = kind
+            .forbidden
+            .iter()
+            .filter(|(_, pattern)| pattern.is_match(content))
+            .map(|(word, _)| word.clone())
+            .collect();
+        if !vague.is_empty() {
+            found.push((
+                "forbidden_words",
+                format!("uses {}", quoted_list(&vague, "and")),
+                false,
+            ));
+        }
+        if rules.require_example && !EXAMPLE.is_match(content) {
+            found.push(("require_example", "has no example".to_string(), false));
+        }
+        let replaceable: Vec<String> = kind
+            .replace
+            .iter()
+            .filter_map(|(pattern, _)| pattern.find(content))
+            .map(|m| m.as_str().to_string())
+            .collect();
+        if !replaceable.is_empty() {
+            found.push((
+                "replace",
+                format!("uses {}", quoted_list(&replaceable, "and")),
+                true,
+            ));
+        }
+        if rules.trim_whitespace && trim_whitespace(content) != *content {
+            found.push((
+                "trim_whitespace",
+                "has trailing spaces or extra blank lines".to_string(),
+                true,
+            ));
+        }
+
+        let mut violations: Vec<Violation> = found
+            .into_iter()
+            .map(|(rule, message, fixable)| Violation {
+                id: artifact.id.clone(),
+                kind: artifact.kind.clone(),
+                rule: rule.to_string(),
+                severity: severity(&rules.severity, RULES, rule),
+                message,
+                fixable,
+                suggestion: None,
+            })
+            .collect();
+        // Stable, so rules of equal severity keep their order
+        violations.sort_by_key(|v| std::cmp::Reverse(v.severity));
+        violations
+    }
+
+    /// Content and labels with the mechanical fixes applied, or `None` when
+    /// there is nothing to fix
+    pub fn fix(&self, artifact: &Artifact) -> Option<(String, HashMap<String, String>)> {
+        let kind = self.kinds.get(&artifact.kind)?;
+        let mut content = artifact.content.clone();
+        for (pattern, replacement) in &kind.replace {
+            content = pattern
+                .replace_all(&content, |caps: &regex::Captures| {
+                    match_case(&caps[0], replacement)
+                })
+                .into_owned();
+        }
+        if kind.rules.trim_whitespace {
+            content = trim_whitespace(&content);
+        }
+        let labels: HashMap<String, String> = kind
+            .labels
+            .iter()
+            .filter(|(key, _)| artifact.metadata.get(key).is_none_or(|v| v.is_empty()))
+            .filter_map(|(key, default)| Some((key.clone(), default.clone()?)))
+            .collect();
+
+        if content == artifact.content && labels.is_empty() {
+            None
+        } else {
+            Some((content, labels))
+        }
+    }
+}
+
+/// Severity of `rule`, one of `defaults`, after `overrides`
+fn severity(
+    overrides: &HashMap<String, LintSeverity>,
+    defaults: &[(&str, LintSeverity)],
+    rule: &str,
+) -> LintSeverity {
+    overrides.get(rule).copied().unwrap_or_else(|| {
+        defaults
+            .iter()
+            .find(|(name, _)| *name == rule)
+            .map_or(LintSeverity::Warning, |(_, severity)| *severity)
+    })
+}
+
+/// Fail if `overrides` names a rule not among `rules`
+fn check_severities(
+    overrides: &HashMap<String, LintSeverity>,
+    rules: &[(&str, LintSeverity)],
+    scope: &str,
+) -> Result<()> {
+    match overrides
+        .keys()
+        .find(|rule| !rules.iter().any(|(name, _)| name == rule))
+    {
+        Some(rule) => Err(ServiceError::Validation(format!(
+            "Unknown lint rule '{}' in the severities for {}",
+            rule, scope
+        ))
+        .into()),
+        None => Ok(()),
+    }
+}
+
+/// Case-insensitive pattern matching `word` as a whole word or phrase
+fn word_pattern(word: &str) -> Result<Regex> {
+    Regex::new(&format!(r"(?i)\b{}\b", regex::escape(word.trim())))
+        .with_context(|| format!("Invalid lint word '{}'", word))
+}
+
+/// `replacement` capitalized when the word it replaces is
+fn match_case(original: &str, replacement: &str) -> String {
+    let mut chars = replacement.chars();
+    match (original.chars().next(), chars.next()) {
+        (Some(first), Some(head)) if first.is_uppercase() => {
+            head.to_uppercase().chain(chars).collect()
+        },
+        _ => replacement.to_string(),
+    }
+}
+
+/// The first sentence of the first line of prose
+fn first_sentence(content: &str) -> &str {
+    let line = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#') && !line.starts_with("```"))
+        .map(|line| line.trim_start_matches(['-', '*', '>', ' ']))
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let end = line
+        .match_indices(['.', '!', '?'])
+        .map(|(i, _)| i + 1)
+        .find(|&i| line[i..].is_empty() || line[i..].starts_with(' '))
+        .unwrap_or(line.len());
+    &line[..end]
+}
+
+/// Content without trailing spaces, leading or trailing blank lines, or
+/// more than one blank line in a row
+fn trim_whitespace(content: &str) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+    for line in content.trim().lines().map(str::trim_end) {
+        if line.is_empty() && lines.last().is_some_and(|last| last.is_empty()) {
+            continue;
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// `'a', 'b' or 'c'`
+fn quoted_list(words: &[String], conjunction: &str) -> String {
+    let quoted: Vec<String> = words.iter().map(|w| format!("'{}'", w)).collect();
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => {
+            format!("{} {} {}", rest.join(", "), conjunction, last)
+        },
+        _ => quoted.join(""),
+    }
+}
+
+/// Outcome of linting the store
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LintReport {
+    /// Artifacts of kinds with rules that were checked
+    pub checked: usize,
+    /// Violations left, after any fixes
+    pub violations: Vec<Violation>,
+    /// IDs of artifacts `--fix` changed
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fixed: Vec<String>,
+}
+
+impl LintReport {
+    /// Number of violations of `severity`
+    pub fn count(&self, severity: LintSeverity) -> usize {
+        self.violations
+            .iter()
+            .filter(|v| v.severity == severity)
+            .count()
+    }
+}
+
+/// Service that lints the artifacts in the store
+pub struct LintService {
+    artifacts: Arc<ArtifactService>,
+    linter: Linter,
+}
+
+impl LintService {
+    pub fn new(artifacts: Arc<ArtifactService>, linter: Linter) -> Self {
+        Self { artifacts, linter }
+    }
+
+    /// Lint the artifacts matching `filters`, first applying mechanical
+    /// fixes when `fix` is set
+    pub async fn run(&self, filters: SearchFilters, fix: bool) -> Result<LintReport> {
+        let artifacts = self
+            .artifacts
+            .list(SearchFilters {
+                limit: None,
+                ..filters
+            })
+            .await?;
+
+        // The glossary applies across namespaces, whatever `filters` select
+        let glossary = match self.linter.glossary_kind() {
+            Some(kind) => Some(Glossary::new(
+                &self
+                    .artifacts
+                    .list(SearchFilters {
+                        kind: Some(kind.to_string()),
+                        ..Default::default()
+                    })
+                    .await?,
+            )),
+            None => None,
+        };
+
+        let mut report = LintReport::default();
+        for mut artifact in artifacts {
+            if !self.linter.applies_to(&artifact.kind) {
+                continue;
+            }
+            report.checked += 1;
+            if fix {
+                if let Some((content, labels)) = self.linter.fix(&artifact) {
+                    let content = (content != artifact.content).then_some(content);
+                    let labels = (!labels.is_empty()).then_some(labels);
+                    artifact = self
+                        .artifacts
+                        .update(&artifact.id, content, None, None, labels, None)
+                        .await?;
+                    report.fixed.push(artifact.id.clone());
+                }
+            }
+            let mut violations = self.linter.check(&artifact);
+            if let Some(glossary) = &glossary {
+                violations.extend(self.linter.check_terms(&artifact, glossary));
+                violations.sort_by_key(|v| std::cmp::Reverse(v.severity));
+            }
+            report.violations.extend(violations);
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+
+    fn linter(rules: LintRules) -> Linter {
+        Linter::from_config(&LintConfig {
+            kinds: HashMap::from([("invariant".to_string(), rules)]),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn invariant(content: &str) -> Artifact {
+        Artifact::new(
+            "invariant".to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "test".to_string(),
+        )
+    }
+
+    #[test]
+    fn reports_violations_with_severities() {
+        let linter = linter(LintRules {
+            max_length: Some(40),
+            modal: vec!["must".to_string(), "should".to_string()],
+            forbidden_words: vec!["appropriate".to_string(), "etc".to_string()],
+            required_labels: vec!["domain".to_string()],
+            require_example: true,
+            severity: HashMap::from([("modal".to_string(), LintSeverity::Error)]),
+            ..Default::default()
+        });
+
+        let artifact =
+            invariant("Orders ship after an appropriate check. Payment must clear first, etc.");
+        let violations = linter.check(&artifact);
+        let rules: Vec<(&str, LintSeverity)> = violations
+            .iter()
+            .map(|v| (v.rule.as_str(), v.severity))
+            .collect();
+        assert_eq!(
+            rules,
+            [
+                ("required_labels", LintSeverity::Error),
+                ("modal", LintSeverity::Error),
+                ("max_length", LintSeverity::Warning),
+                ("forbidden_words", LintSeverity::Warning),
+                ("require_example", LintSeverity::Warning),
+            ]
+        );
+
+        let mut clean = invariant("Orders must ship paid, e.g. by card.");
+        clean
+            .metadata
+            .insert("domain".to_string(), "billing".to_string());
+        assert!(linter.check(&clean).is_empty());
+
+        // Other kinds are not checked
+        let mut other = artifact.clone();
+        other.kind = "intent".to_string();
+        assert!(linter.check(&other).is_empty());
+    }
+
+    #[test]
+    fn fixes_mechanical_violations() {
+        let linter = linter(LintRules {
+            replace: HashMap::from([("utilize".to_string(), "use".to_string())]),
+            required_labels: vec!["status=draft".to_string(), "domain".to_string()],
+            trim_whitespace: true,
+            ..Default::default()
+        });
+        let artifact = invariant("\nUtilize the cache.  \n\n\n\nAgents utilize it too.\n");
+
+        let (content, labels) = linter.fix(&artifact).unwrap();
+        assert_eq!(content, "Use the cache.\n\nAgents use it too.");
+        assert_eq!(
+            labels,
+            HashMap::from([("status".to_string(), "draft".to_string())])
+        );
+
+        let mut fixed = artifact.clone();
+        fixed.content = content;
+        fixed.metadata.extend(labels);
+        let violations = linter.check(&fixed);
+        let left: Vec<&str> = violations.iter().map(|v| v.rule.as_str()).collect();
+        assert_eq!(left, ["required_labels"]);
+        assert!(linter.fix(&fixed).is_none());
+    }
+
+    #[test]
+    fn checks_wording_against_the_glossary() {
+        let linter = Linter::from_config(&LintConfig {
+            glossary: Some(GlossaryLintConfig {
+                ignore: vec!["KPI".to_string()],
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .unwrap();
+        let mut entry = invariant("MRR: Monthly Recurring Revenue");
+        entry.kind = DEFAULT_GLOSSARY_KIND.to_string();
+        let glossary = Glossary::new(&[entry.clone()]);
+
+        assert!(linter.applies_to("invariant") && !linter.applies_to(DEFAULT_GLOSSARY_KIND));
+        let violations = linter.check_terms(&invariant("Mrr and MMR are KPIs."), &glossary);
+        let found: Vec<(&str, LintSeverity, Option<&str>)> = violations
+            .iter()
+            .map(|v| (v.rule.as_str(), v.severity, v.suggestion.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("glossary_variant", LintSeverity::Warning, Some("MRR")),
+                ("glossary_undefined", LintSeverity::Info, Some("MRR")),
+            ]
+        );
+        assert!(linter.check_terms(&entry, &glossary).is_empty());
+    }
+
+    #[test]
+    fn unknown_severity_overrides_are_rejected() {
+        let rules = LintRules {
+            severity: HashMap::from([("max_lenght".to_string(), LintSeverity::Error)]),
+            ..Default::default()
+        };
+        assert!(Linter::from_config(&LintConfig {
+            kinds: HashMap::from([("invariant".to_string(), rules)]),
+            ..Default::default()
+        })
+        .is_err());
+    }
+}
@@ -1,20 +1,123 @@
+pub mod access;
 pub mod artifact;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod ask;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod attachment;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod backup;
+pub mod bench;
+pub mod changes;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod completion;
 pub mod config;
+pub mod dates;
+pub mod dedupe;
+pub mod deprecation;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod digest;
+pub mod embed_text;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod federation;
+pub mod frontmatter;
+pub mod glossary;
+pub mod include;
+pub mod ingest;
 pub mod kind;
+pub mod language;
+pub mod limits;
+pub mod lint;
+pub mod monitor;
+pub mod notify;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod outbox;
+pub mod owners;
+pub mod pack;
+pub mod plan;
+pub mod scan;
 pub mod search;
+pub mod seed;
+pub mod signing;
+pub mod snippet;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod stores;
+pub mod sync;
+pub mod syntax;
 pub mod types;
+pub mod variables;
 
-pub use artifact::ArtifactService;
-pub use config::ConfigService;
+pub use access::{Access, AccessPolicy, KindAccess};
+pub use artifact::{ArtifactService, EXTERNAL_ID_LABEL};
+#[cfg(not(target_arch = "wasm32"))]
+pub use ask::{Answer, AskService, AskSource};
+#[cfg(not(target_arch = "wasm32"))]
+pub use attachment::{Attachment, AttachmentService, ATTACHMENTS_LABEL};
+#[cfg(not(target_arch = "wasm32"))]
+pub use backup::{BackupInfo, BackupService};
+pub use bench::{
+    BenchService, GoldenQuery, GoldenSet, QueryScore, RetrievalReport, DEFAULT_BENCH_K,
+};
+pub use changes::{
+    ChangeHistory, ChangeReport, ChangeType, ChangeWatcher, ChangedArtifact, KindChanges,
+    WatchedChange,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use completion::{ChatCompletion, Completion};
+pub use config::{ConfigIssue, ConfigService};
+pub use dates::parse_date;
+pub use dedupe::{DuplicateGroup, MERGED_INTO_LABEL};
+pub use deprecation::{
+    Deprecation, DEPRECATED_STATUS, DEPRECATION_REASON_LABEL, REPLACED_BY_LABEL,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use digest::{
+    DigestService, ExtractiveSummarizer, ModelSummarizer, Summarizer, DIGEST_KIND, GENERATED_LABEL,
+};
+#[cfg(not(target_arch = "wasm32"))]
+pub use federation::{
+    FederatedResult, FederatedResults, FederatedSearch, HttpStore, SearchSource, StoreFailure,
+};
+pub use frontmatter::{Frontmatter, RELATED_SECTION_MARKER};
+pub use glossary::{Glossary, TermIssue, DEFAULT_GLOSSARY_KIND};
+pub use include::{expand_includes, IncludeCycle};
+pub use ingest::{IngestChange, IngestItem, IngestPlan, IngestService, SOURCE_LABEL};
 pub use kind::KindService;
+pub use language::LANGUAGE_LABEL;
+pub use limits::InputRejected;
+pub use lint::{LintReport, LintService, Linter, Violation};
+pub use monitor::{Comparator, Monitor, MONITOR_KIND};
+pub use notify::{owner_digests, NotifyFormat, OwnerDigest, OwnerMap, Recipient};
+#[cfg(not(target_arch = "wasm32"))]
+pub use outbox::{FlushReport, Outbox, QueuedWrite};
+pub use owners::{normalize_handle, ownership_report, Assignment, KindOwnership};
+pub use pack::{Pack, PackEntry, PackService, STATUS_LABEL};
+pub use plan::{ApplyReport, Plan, PlanOperation, PlanService, PlanStep, DEFAULT_LINK_LABEL};
+pub use scan::{ContentBlocked, ContentScanner, ScanFinding};
 pub use search::SearchService;
+pub use seed::{SeedService, SEED_LABEL};
+pub use signing::{Signer, Verification, Verifier, SIGNATURE_LABEL, SIGNED_BY_LABEL};
+pub use snippet::Snippet;
+#[cfg(not(target_arch = "wasm32"))]
+pub use stores::{GlobalConfig, StoreRegistry};
+pub use sync::{Reconciliation, SyncAction, SyncChange, SyncDocument, SyncService};
 pub use types::{
-    get_template, list_templates, slugify_kind, validate_kind_slug, Artifact, ContentFormat,
-    KindDefinition, KindValidationError, KindsConfig, LabelDefinition, LabelsConfig, ModelConfig,
-    ProjectConfig, ReindexTarget, SearchFilters, SearchResult, StorageConfig, Template,
-    TemplateKind, TemplateLabel, KIND_SLUG_MAX_LENGTH, KIND_SLUG_MIN_LENGTH, RESERVED_KIND_SLUGS,
+    get_template, is_s3_uri, list_templates, parse_kind_boost, slugify_kind, template_placeholders,
+    validate_boosts, validate_kind_slug, validate_labels, Artifact, ArtifactUpdate,
+    AttachmentsConfig, BackupConfig, ChangePreview, CompletionConfig, ContentFormat, DigestConfig,
+    EmbeddingHealth, EmbeddingIssue, EmbeddingPrecision, FederatedStoreConfig, GlossaryLintConfig,
+    IdConfig, IdGenerator, IdStrategy, KindDefinition, KindUsage, KindValidationError, KindsConfig,
+    LabelDefinition, LabelValidationError, LabelValueType, LabelsConfig, LanguagesConfig,
+    LimitsConfig, LintConfig, LintRules, LintSeverity, MatchedVector, McpConfig, ModelConfig,
+    OwnersConfig, ProjectConfig, ReindexTarget, S3Config, ScanAction, ScanConfig, ScanRule,
+    SearchConfig, SearchExplanation, SearchFilters, SearchResult, SearchWeights, SigningConfig,
+    StorageConfig, Template, TemplateKind, TemplateLabel, UpsertAction, Upserted, VariablesConfig,
+    VectorMatch, DEFAULT_ID_LENGTH, KIND_SLUG_MAX_LENGTH, KIND_SLUG_MIN_LENGTH, MIN_ID_LENGTH,
+    RESERVED_KIND_SLUGS,
 };
+pub use variables::{UnresolvedVariables, Variables};
 
+/// Failures surfaced by the services, mapped by each front end to its own
+/// error codes (HTTP status, JSON-RPC code, process exit code).
 #[derive(Debug, thiserror::Error)]
 pub enum ServiceError {
     #[error("not found: {0}")]
@@ -23,6 +126,166 @@ pub enum ServiceError {
     #[error("validation error: {0}")]
     Validation(String),
 
+    /// The operation clashes with existing state, e.g. a duplicate ID
+    #[error("conflict: {0}")]
+    Conflict(String),
+
+    /// The caller's access policy does not allow the operation
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+
+    /// The embedding provider asked us to slow down
+    #[error("rate limited: {0}")]
+    RateLimited(String),
+
+    /// The embedding provider could not be reached
+    #[error("provider unavailable: {0}")]
+    ProviderUnavailable(String),
+
+    /// Stored data could not be read back
+    #[error("storage corruption: {0}")]
+    StorageCorruption(String),
+
     #[error("{0}")]
-    Internal(#[from] anyhow::Error),
+    Internal(anyhow::Error),
+}
+
+impl ServiceError {
+    /// Work out which kind of failure an error chain represents.
+    ///
+    /// Typed errors anywhere in the chain (blocked content, rejected input,
+    /// provider and storage failures) take precedence over the generic
+    /// internal error.
+    pub fn classify(err: anyhow::Error) -> Self {
+        let err = match err.downcast::<ServiceError>() {
+            Ok(service) => return service,
+            Err(err) => err,
+        };
+        for cause in err.chain() {
+            if cause.is::<ContentBlocked>()
+                || cause.is::<InputRejected>()
+                || cause.is::<crate::db::AmbiguousPrefix>()
+                || cause.is::<UnresolvedVariables>()
+                || cause.is::<IncludeCycle>()
+            {
+                return Self::Validation(cause.to_string());
+            }
+            if let Some(Self::Forbidden(msg)) = cause.downcast_ref::<ServiceError>() {
+                return Self::Forbidden(msg.clone());
+            }
+            if let Some(provider) = cause.downcast_ref::<crate::embedding::ProviderError>() {
+                return match provider {
+                    crate::embedding::ProviderError::RateLimited(msg) => {
+                        Self::RateLimited(msg.clone())
+                    },
+                    crate::embedding::ProviderError::Unavailable(msg) => {
+                        Self::ProviderUnavailable(msg.clone())
+                    },
+                };
+            }
+            if let Some(corruption) = cause.downcast_ref::<crate::db::StorageCorruption>() {
+                return Self::StorageCorruption(corruption.0.clone());
+            }
+            #[cfg(not(target_arch = "wasm32"))]
+            if cause.is::<crate::db::StoreLocked>() {
+                return Self::Conflict(cause.to_string());
+            }
+        }
+        Self::Internal(err)
+    }
+
+    /// Stable machine-readable name, used as the REST error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "not_found",
+            Self::Validation(_) => "validation_error",
+            Self::Conflict(_) => "conflict",
+            Self::RateLimited(_) => "rate_limited",
+            Self::ProviderUnavailable(_) => "provider_unavailable",
+            Self::StorageCorruption(_) => "storage_corruption",
+            Self::Forbidden(_) => "forbidden",
+            Self::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Process exit code for the CLI. 2 is left to clap for usage errors.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Self::Internal(_) => 1,
+            Self::NotFound(_) => 3,
+            Self::Validation(_) => 4,
+            Self::Conflict(_) => 5,
+            Self::RateLimited(_) => 6,
+            Self::ProviderUnavailable(_) => 7,
+            Self::StorageCorruption(_) => 8,
+            Self::Forbidden(_) => 9,
+        }
+    }
+
+    /// JSON-RPC error code, shared by the MCP and editor servers.
+    ///
+    /// Not found and validation use the standard codes; the rest use
+    /// -32010 and up from the server-defined range.
+    pub fn json_rpc_code(&self) -> i32 {
+        match self {
+            Self::NotFound(_) => -32002,
+            Self::Validation(_) => -32602,
+            Self::Conflict(_) => -32010,
+            Self::RateLimited(_) => -32011,
+            Self::ProviderUnavailable(_) => -32012,
+            Self::StorageCorruption(_) => -32013,
+            Self::Forbidden(_) => -32014,
+            Self::Internal(_) => -32603,
+        }
+    }
+}
+
+impl From<anyhow::Error> for ServiceError {
+    fn from(err: anyhow::Error) -> Self {
+        Self::classify(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn classifies_typed_errors_through_context() {
+        let blocked: anyhow::Error = InputRejected("too long".to_string()).into();
+        let err = ServiceError::classify(blocked.context("Failed to create artifact"));
+        assert!(matches!(err, ServiceError::Validation(msg) if msg == "too long"));
+
+        let limited: anyhow::Result<()> =
+            Err(crate::embedding::ProviderError::RateLimited("429".to_string()).into());
+        let err = ServiceError::from(limited.context("Failed to embed").unwrap_err());
+        assert_eq!(err.code(), "rate_limited");
+        assert_eq!(err.exit_code(), 6);
+
+        let corrupt: anyhow::Error =
+            crate::db::StorageCorruption("missing id column".into()).into();
+        assert_eq!(ServiceError::classify(corrupt).exit_code(), 8);
+
+        let locked: anyhow::Error = crate::db::StoreLocked {
+            holder: "'dna mcp' (pid 42)".into(),
+            path: "db.lance.lock".into(),
+        }
+        .into();
+        let err = ServiceError::classify(locked.context("Failed to update artifact"));
+        assert_eq!(err.code(), "conflict");
+    }
+
+    #[test]
+    fn passes_service_errors_through_and_defaults_to_internal() {
+        let conflict: anyhow::Error = ServiceError::Conflict("exists".to_string()).into();
+        assert!(matches!(
+            ServiceError::classify(conflict),
+            ServiceError::Conflict(_)
+        ));
+
+        let other = ServiceError::classify(anyhow::anyhow!("disk full"));
+        assert_eq!(other.code(), "internal_error");
+        assert_eq!(other.exit_code(), 1);
+    }
 }
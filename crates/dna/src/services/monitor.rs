@@ -0,0 +1,291 @@
+//! Monitors as structured data: a metric, a comparison and a threshold,
+//! measured over an optional window.
+//!
+//! A YAML or JSON monitor holds them as fields:
+//!
+//! ```yaml
+//! metric: http_request_duration_p99
+//! comparator: "<"
+//! threshold: 200ms
+//! window: 5m
+//! ```
+//!
+//! A Markdown or text monitor holds them as an expression on a line of its
+//! own, as in `p99_latency < 200ms over 5m`. The threshold may carry a unit,
+//! given with the number or as a `unit` field, and the metric may carry
+//! Prometheus-style labels, as in `http_errors{route="/orders"} < 2%`.
+
+use super::limits::InputRejected;
+use super::types::{Artifact, ContentFormat};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+/// Kind whose artifacts are monitors
+pub const MONITOR_KIND: &str = "monitor";
+
+/// A number followed by an optional unit, such as `200ms`, `2 %` or `0.5`
+static THRESHOLD: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?P<value>[-+]?\d+(?:\.\d+)?)\s*(?P<unit>%|[A-Za-z][A-Za-z/]*)?\s*$").unwrap()
+});
+
+/// `metric comparator threshold [over window]`
+static EXPRESSION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?P<metric>[A-Za-z_][\w.:]*(?:\{[^}]*\})?)\s*(?P<comparator><=|>=|==|!=|<|>)\s*(?P<threshold>[-+]?\d+(?:\.\d+)?\s*(?:%|[A-Za-z][A-Za-z/]*)?)(?:\s+(?:over|per|for|within)\s+(?P<window>\S+))?$",
+    )
+    .unwrap()
+});
+
+/// How a metric is compared with its threshold; the metric is healthy while
+/// the comparison holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Comparator {
+    #[serde(rename = "<", alias = "lt")]
+    Lt,
+    #[serde(rename = "<=", alias = "le")]
+    Le,
+    #[serde(rename = ">", alias = "gt")]
+    Gt,
+    #[serde(rename = ">=", alias = "ge")]
+    Ge,
+    #[serde(rename = "==", alias = "eq")]
+    Eq,
+    #[serde(rename = "!=", alias = "ne")]
+    Ne,
+}
+
+impl Comparator {
+    /// Whether `value` satisfies the comparison with `threshold`
+    pub fn holds(&self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparator::Lt => value < threshold,
+            Comparator::Le => value <= threshold,
+            Comparator::Gt => value > threshold,
+            Comparator::Ge => value >= threshold,
+            Comparator::Eq => value == threshold,
+            Comparator::Ne => value != threshold,
+        }
+    }
+}
+
+impl std::fmt::Display for Comparator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let symbol = match self {
+            Comparator::Lt => "<",
+            Comparator::Le => "<=",
+            Comparator::Gt => ">",
+            Comparator::Ge => ">=",
+            Comparator::Eq => "==",
+            Comparator::Ne => "!=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+impl std::str::FromStr for Comparator {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "<" | "lt" => Ok(Comparator::Lt),
+            "<=" | "le" => Ok(Comparator::Le),
+            ">" | "gt" => Ok(Comparator::Gt),
+            ">=" | "ge" => Ok(Comparator::Ge),
+            "==" | "eq" => Ok(Comparator::Eq),
+            "!=" | "ne" => Ok(Comparator::Ne),
+            _ => Err(anyhow::anyhow!(
+                "Invalid comparator: {} (expected <, <=, >, >=, == or !=)",
+                s
+            )),
+        }
+    }
+}
+
+/// What a monitor watches and when it is healthy
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Monitor {
+    /// Metric name, with any Prometheus-style labels
+    pub metric: String,
+    pub comparator: Comparator,
+    pub threshold: f64,
+    /// Unit of the threshold, e.g. `ms` or `%`
+    pub unit: Option<String>,
+    /// Period the metric is measured over, e.g. `5m`
+    pub window: Option<String>,
+}
+
+/// Fields of a YAML or JSON monitor; others are allowed and ignored
+#[derive(Deserialize)]
+struct MonitorFields {
+    metric: String,
+    comparator: Comparator,
+    threshold: ThresholdField,
+    #[serde(default)]
+    unit: Option<String>,
+    #[serde(default)]
+    window: Option<String>,
+}
+
+/// A threshold given as a bare number or as text with a unit
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ThresholdField {
+    Number(f64),
+    Text(String),
+}
+
+impl Monitor {
+    /// Read the monitor `artifact` describes, from its fields when YAML or
+    /// JSON and from its first expression line when Markdown or text
+    pub fn from_artifact(artifact: &Artifact) -> Result<Self, InputRejected> {
+        Self::parse(&artifact.content, artifact.format)
+    }
+
+    /// Read a monitor from `content` in `format`
+    pub fn parse(content: &str, format: ContentFormat) -> Result<Self, InputRejected> {
+        match format {
+            ContentFormat::Yaml | ContentFormat::Json => Self::from_fields(content),
+            ContentFormat::Markdown | ContentFormat::Text => content
+                .lines()
+                .find_map(|line| Self::from_expression(line.trim().trim_matches('`')))
+                .ok_or_else(|| {
+                    InputRejected(
+                        "No monitor expression found; expected a line such as \
+                         'p99_latency < 200ms over 5m'"
+                            .to_string(),
+                    )
+                }),
+            ContentFormat::OpenApi => Err(InputRejected(
+                "OpenAPI content cannot describe a monitor".to_string(),
+            )),
+        }
+    }
+
+    fn from_fields(content: &str) -> Result<Self, InputRejected> {
+        let fields: MonitorFields = serde_yaml::from_str(content)
+            .map_err(|e| InputRejected(format!("Invalid monitor: {}", e)))?;
+        let (threshold, unit) = match fields.threshold {
+            ThresholdField::Number(value) => (value, None),
+            ThresholdField::Text(text) => parse_threshold(&text).ok_or_else(|| {
+                InputRejected(format!(
+                    "Invalid monitor: threshold '{}' is not a number with an optional unit",
+                    text
+                ))
+            })?,
+        };
+        let metric = fields.metric.trim().to_string();
+        if metric.is_empty() {
+            return Err(InputRejected(
+                "Invalid monitor: metric is empty".to_string(),
+            ));
+        }
+        Ok(Self {
+            metric,
+            comparator: fields.comparator,
+            threshold,
+            unit: fields.unit.or(unit),
+            window: fields.window,
+        })
+    }
+
+    /// Read an expression such as `p99_latency < 200ms over 5m`
+    fn from_expression(line: &str) -> Option<Self> {
+        let captures = EXPRESSION.captures(line)?;
+        let (threshold, unit) = parse_threshold(&captures["threshold"])?;
+        Some(Self {
+            metric: captures["metric"].to_string(),
+            comparator: captures["comparator"].parse().ok()?,
+            threshold,
+            unit,
+            window: captures.name("window").map(|m| m.as_str().to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for Monitor {
+    /// The monitor as an expression, e.g. `p99_latency < 200ms over 5m`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {} {}{}",
+            self.metric,
+            self.comparator,
+            self.threshold,
+            self.unit.as_deref().unwrap_or_default()
+        )?;
+        if let Some(window) = &self.window {
+            write!(f, " over {}", window)?;
+        }
+        Ok(())
+    }
+}
+
+/// Split `200ms` into 200 and `ms`
+fn parse_threshold(text: &str) -> Option<(f64, Option<String>)> {
+    let captures = THRESHOLD.captures(text)?;
+    let value = captures["value"].parse().ok()?;
+    Some((value, captures.name("unit").map(|m| m.as_str().to_string())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_fields_and_expressions_alike() {
+        let yaml = Monitor::parse(
+            "metric: p99_latency\ncomparator: \"<\"\nthreshold: 200ms\nwindow: 5m\nseverity: high\n",
+            ContentFormat::Yaml,
+        )
+        .unwrap();
+        let expression = Monitor::parse(
+            "# Checkout latency\n\n`p99_latency < 200ms over 5m`\n",
+            ContentFormat::Markdown,
+        )
+        .unwrap();
+        assert_eq!(yaml, expression);
+        assert_eq!(yaml.threshold, 200.0);
+        assert_eq!(yaml.unit.as_deref(), Some("ms"));
+        assert_eq!(yaml.to_string(), "p99_latency < 200ms over 5m");
+
+        let json = Monitor::parse(
+            r#"{"metric": "payment_failures", "comparator": "lt", "threshold": 2, "unit": "%"}"#,
+            ContentFormat::Json,
+        )
+        .unwrap();
+        assert_eq!(json.comparator, Comparator::Lt);
+        assert_eq!(json.to_string(), "payment_failures < 2%");
+
+        let labelled = Monitor::parse(
+            "http_errors{route=\"/orders\"} >= 0.5 per 1h",
+            ContentFormat::Text,
+        )
+        .unwrap();
+        assert_eq!(labelled.metric, "http_errors{route=\"/orders\"}");
+        assert_eq!(labelled.window.as_deref(), Some("1h"));
+        assert!(labelled.comparator.holds(0.5, labelled.threshold));
+    }
+
+    #[test]
+    fn rejects_monitors_without_a_threshold() {
+        let prose = Monitor::parse(
+            "p99 latency of POST /api/orders stays below 400ms.",
+            ContentFormat::Markdown,
+        )
+        .unwrap_err();
+        assert!(prose.0.starts_with("No monitor expression found"));
+
+        let missing = Monitor::parse("metric: p99_latency\nthreshold: 200\n", ContentFormat::Yaml)
+            .unwrap_err();
+        assert!(missing.0.contains("comparator"), "{}", missing);
+
+        let unitless = Monitor::parse(
+            "metric: p99_latency\ncomparator: \">\"\nthreshold: fast\n",
+            ContentFormat::Yaml,
+        )
+        .unwrap_err();
+        assert!(unitless.0.contains("threshold 'fast'"), "{}", unitless);
+        assert!("~".parse::<Comparator>().is_err());
+    }
+}
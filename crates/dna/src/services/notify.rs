@@ -0,0 +1,338 @@
+//! Digests telling owners what changed in the artifacts they own, for
+//! `dna notify`.
+//!
+//! Changes are grouped by the owner of each artifact. An owner map, a TOML
+//! file with a table per `@handle`, says where each owner's digest goes, and
+//! a digest becomes an email or Slack message payload a sender can post as
+//! is. Artifacts without an owner are left out of every digest.
+
+use super::changes::{describe, ChangeReport, ChangedArtifact};
+use super::owners::normalize_handle;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Most characters Slack accepts in the text of a section block
+const SLACK_SECTION_LIMIT: usize = 3000;
+
+/// Shape of the payload built for each digest
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NotifyFormat {
+    /// `{to, subject, text}` for an email sender
+    #[default]
+    Email,
+    /// A `chat.postMessage` body with `channel`, `text` and `blocks`
+    SlackJson,
+}
+
+impl std::fmt::Display for NotifyFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotifyFormat::Email => write!(f, "email"),
+            NotifyFormat::SlackJson => write!(f, "slack-json"),
+        }
+    }
+}
+
+impl std::str::FromStr for NotifyFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "email" => Ok(NotifyFormat::Email),
+            "slack-json" | "slack" => Ok(NotifyFormat::SlackJson),
+            _ => Err(anyhow::anyhow!(
+                "Invalid notify format: {} (expected email or slack-json)",
+                s
+            )),
+        }
+    }
+}
+
+/// Where one owner's digest is sent
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Recipient {
+    /// Name to address the owner by, in place of the handle
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Email address, for the email format
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Slack channel or user ID, for the slack-json format
+    #[serde(default)]
+    pub slack: Option<String>,
+}
+
+/// Recipients by owner handle, read from an owner map file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnerMap {
+    recipients: BTreeMap<String, Recipient>,
+}
+
+impl OwnerMap {
+    /// Read the owner map in the TOML file at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read owner map {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Invalid owner map {}", path.display()))
+    }
+
+    /// Parse an owner map, where each table is named for a handle, with or
+    /// without its `@`
+    pub fn parse(text: &str) -> Result<Self> {
+        let tables: BTreeMap<String, Recipient> = toml::from_str(text)?;
+        Ok(Self {
+            recipients: tables
+                .into_iter()
+                .map(|(handle, recipient)| (normalize_handle(&handle), recipient))
+                .collect(),
+        })
+    }
+
+    /// Where to send `owner`'s digest, if the map says
+    pub fn get(&self, owner: &str) -> Option<&Recipient> {
+        self.recipients.get(&normalize_handle(owner))
+    }
+}
+
+/// Changes to the artifacts one owner owns
+#[derive(Debug, Clone)]
+pub struct OwnerDigest {
+    pub owner: String,
+    pub report: ChangeReport,
+}
+
+/// Group `changes` made between `after` and `before` into a digest per
+/// owner, in handle order; changes to unowned artifacts are dropped
+pub fn owner_digests(
+    changes: Vec<ChangedArtifact>,
+    after: Option<DateTime<Utc>>,
+    before: Option<DateTime<Utc>>,
+) -> Vec<OwnerDigest> {
+    let mut owners: BTreeMap<String, Vec<ChangedArtifact>> = BTreeMap::new();
+    for change in changes {
+        if let Some(owner) = change.artifact.owner.clone() {
+            owners.entry(owner).or_default().push(change);
+        }
+    }
+    owners
+        .into_iter()
+        .map(|(owner, changes)| OwnerDigest {
+            owner,
+            report: ChangeReport::from_changes(changes, after, before),
+        })
+        .collect()
+}
+
+impl OwnerDigest {
+    /// One line naming what changed, e.g. "2 artifacts owned by @payments
+    /// changed since 2024-06-01"
+    pub fn subject(&self) -> String {
+        let total = self.report.total;
+        let mut subject = format!(
+            "{} artifact{} owned by {} changed",
+            total,
+            if total == 1 { "" } else { "s" },
+            self.owner
+        );
+        if let Some(after) = self.report.after {
+            subject.push_str(&format!(" since {}", describe(after)));
+        }
+        if let Some(before) = self.report.before {
+            subject.push_str(&format!(" before {}", describe(before)));
+        }
+        subject
+    }
+
+    /// Plain-text body: a greeting, the counts by kind and a line per change
+    pub fn text(&self, recipient: Option<&Recipient>) -> String {
+        let name = recipient
+            .and_then(|r| r.name.as_deref())
+            .unwrap_or(&self.owner);
+        let mut text = format!("Hi {},\n\n{}\n\n", name, self.report.summarize());
+        for change in &self.report.changes {
+            text.push_str(&format!("- {} {}\n", change.change, label(change)));
+        }
+        text
+    }
+
+    /// The digest as a payload in `format`, addressed from `recipient`; the
+    /// address is null when the owner map has none
+    pub fn payload(&self, format: NotifyFormat, recipient: Option<&Recipient>) -> Value {
+        match format {
+            NotifyFormat::Email => json!({
+                "owner": self.owner,
+                "to": recipient.and_then(|r| r.email.as_deref()),
+                "subject": self.subject(),
+                "text": self.text(recipient),
+            }),
+            NotifyFormat::SlackJson => {
+                let heading = format!("*{}*\n{}", self.subject(), self.report.summarize());
+                let mut blocks = vec![json!({
+                    "type": "section",
+                    "text": { "type": "mrkdwn", "text": heading },
+                })];
+                let lines = self.report.changes.iter().map(|change| {
+                    format!("• *{}* {}", change.change, slack_escape(&label(change)))
+                });
+                for chunk in chunk_lines(lines, SLACK_SECTION_LIMIT) {
+                    blocks.push(json!({
+                        "type": "section",
+                        "text": { "type": "mrkdwn", "text": chunk },
+                    }));
+                }
+                json!({
+                    "owner": self.owner,
+                    "channel": recipient.and_then(|r| r.slack.as_deref()),
+                    "text": self.subject(),
+                    "blocks": blocks,
+                })
+            },
+        }
+    }
+}
+
+/// "kind/name (id)", or "kind/id" for an unnamed artifact
+fn label(change: &ChangedArtifact) -> String {
+    let artifact = &change.artifact;
+    match &artifact.name {
+        Some(name) => format!("{}/{} ({})", artifact.kind, name, artifact.id),
+        None => format!("{}/{}", artifact.kind, artifact.id),
+    }
+}
+
+/// Escape the characters Slack treats as markup
+fn slack_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Join `lines` with newlines into chunks of at most `limit` characters
+fn chunk_lines(lines: impl Iterator<Item = String>, limit: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        let line: String = line.chars().take(limit).collect();
+        if !current.is_empty() && current.chars().count() + 1 + line.chars().count() > limit {
+            chunks.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&line);
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::changes::ChangeType;
+    use crate::services::{Artifact, ContentFormat};
+    use chrono::TimeZone;
+    use std::collections::HashMap;
+
+    fn change(id: &str, change: ChangeType, owner: Option<&str>) -> ChangedArtifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            "content".to_string(),
+            ContentFormat::Markdown,
+            Some(format!("<{}>", id)),
+            HashMap::new(),
+            String::new(),
+        );
+        artifact.id = id.to_string();
+        artifact.owner = owner.map(str::to_string);
+        ChangedArtifact {
+            change,
+            version: None,
+            artifact,
+        }
+    }
+
+    #[test]
+    fn groups_changes_by_owner_into_payloads() {
+        let since = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        let digests = owner_digests(
+            vec![
+                change("a1", ChangeType::Updated, Some("@payments")),
+                change("a2", ChangeType::Created, None),
+                change("a3", ChangeType::Deleted, Some("@alice")),
+                change("a4", ChangeType::Created, Some("@payments")),
+            ],
+            Some(since),
+            None,
+        );
+        assert_eq!(
+            digests.iter().map(|d| d.owner.as_str()).collect::<Vec<_>>(),
+            vec!["@alice", "@payments"]
+        );
+
+        let payments = &digests[1];
+        assert_eq!(
+            payments.subject(),
+            "2 artifacts owned by @payments changed since 2024-06-01"
+        );
+        let recipient = Recipient {
+            name: Some("Payments".to_string()),
+            email: Some("payments@example.com".to_string()),
+            slack: Some("#payments".to_string()),
+        };
+        let email = payments.payload(NotifyFormat::Email, Some(&recipient));
+        assert_eq!(email["to"], "payments@example.com");
+        let text = email["text"].as_str().unwrap();
+        assert!(text.starts_with("Hi Payments,\n\n2 artifacts changed since 2024-06-01"));
+        assert!(text.contains("- updated intent/<a1> (a1)\n"));
+        assert!(text.contains("- created intent/<a4> (a4)\n"));
+
+        let slack = digests[0].payload(NotifyFormat::SlackJson, None);
+        assert!(slack["channel"].is_null());
+        assert_eq!(
+            slack["blocks"][1]["text"]["text"],
+            "• *deleted* intent/&lt;a3&gt; (a3)"
+        );
+    }
+
+    #[test]
+    fn parses_owner_maps_and_formats() {
+        let map = OwnerMap::parse(
+            r##"
+            ["@payments"]
+            email = "payments@example.com"
+            slack = "#payments"
+
+            [alice]
+            name = "Alice"
+            "##,
+        )
+        .unwrap();
+        assert_eq!(
+            map.get("payments").and_then(|r| r.slack.as_deref()),
+            Some("#payments")
+        );
+        assert_eq!(
+            map.get("@alice").and_then(|r| r.name.as_deref()),
+            Some("Alice")
+        );
+        assert!(map.get("@bob").is_none());
+        assert!(OwnerMap::parse("[alice]\nphone = \"555\"").is_err());
+
+        assert_eq!(
+            "slack-json".parse::<NotifyFormat>().unwrap(),
+            NotifyFormat::SlackJson
+        );
+        assert!("sms".parse::<NotifyFormat>().is_err());
+        assert_eq!(
+            chunk_lines(["ab", "cd", "ef"].iter().map(|s| s.to_string()), 5),
+            vec!["ab\ncd", "ef"]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! Writes queued while offline.
+//!
+//! In offline mode, a write that can't reach the embedding provider or the
+//! store is kept in a journal under `.dna/outbox`, one JSON file per write,
+//! instead of failing. Queued artifacts carry no embeddings: nothing is
+//! embedded until [`Outbox::flush`] applies the writes, in the order they
+//! were made, once the provider and store can be reached again.
+
+use super::artifact::ArtifactService;
+use super::types::Artifact;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// A write waiting in the outbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum QueuedWrite {
+    /// Add an artifact, keeping the ID it was given when queued
+    Add {
+        artifact: Box<Artifact>,
+    },
+    Update {
+        id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        kind: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        labels: Option<HashMap<String, String>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        context: Option<String>,
+    },
+    Remove {
+        id: String,
+    },
+}
+
+impl QueuedWrite {
+    /// One-line summary, such as `add intent k7v3m9xnp2`
+    pub fn describe(&self) -> String {
+        match self {
+            QueuedWrite::Add { artifact } => format!("add {} {}", artifact.kind, artifact.id),
+            QueuedWrite::Update { id, .. } => format!("update {}", id),
+            QueuedWrite::Remove { id } => format!("remove {}", id),
+        }
+    }
+
+    /// Embed and write this to the store through `service`.
+    ///
+    /// Updates and removals are queued with the ID as typed, which may be a
+    /// prefix, so it is resolved here; one that matches nothing fails.
+    async fn apply(self, service: &ArtifactService) -> Result<()> {
+        match self {
+            QueuedWrite::Add { artifact } => {
+                service.import(*artifact).await?;
+            },
+            QueuedWrite::Update {
+                id,
+                content,
+                name,
+                kind,
+                labels,
+                context,
+            } => {
+                let id = service.resolve_id(&id).await?;
+                service
+                    .update(&id, content, name, kind, labels, context)
+                    .await?;
+            },
+            QueuedWrite::Remove { id } => {
+                let id = service.resolve_id(&id).await?;
+                service.remove(&id).await?;
+            },
+        }
+        Ok(())
+    }
+}
+
+/// What [`Outbox::flush`] got through
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FlushReport {
+    /// Writes applied and taken out of the outbox, in order
+    pub flushed: Vec<String>,
+    /// The write that failed, stopping the flush, and why
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed: Option<(String, String)>,
+    /// Writes still queued, including the failed one
+    pub pending: usize,
+}
+
+/// Journal of writes made while offline
+pub struct Outbox {
+    dir: PathBuf,
+}
+
+impl Outbox {
+    /// The outbox of the project at `project_root`
+    pub fn new(project_root: &Path) -> Self {
+        Self {
+            dir: project_root.join(".dna").join("outbox"),
+        }
+    }
+
+    /// Directory holding the queued writes
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Queue `write` after every write already queued
+    pub fn push(&self, write: &QueuedWrite) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        // Names sort in the order writes were queued
+        let mut stamp = chrono::Utc::now().timestamp_micros();
+        let path = loop {
+            let path = self.dir.join(format!("{:020}.json", stamp));
+            if !path.exists() {
+                break path;
+            }
+            stamp += 1;
+        };
+        std::fs::write(&path, serde_json::to_vec_pretty(write)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Queued writes, oldest first
+    pub fn pending(&self) -> Result<Vec<(PathBuf, QueuedWrite)>> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", self.dir.display()))
+            },
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| {
+                let text = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let write = serde_json::from_str(&text)
+                    .with_context(|| format!("Failed to parse {}", path.display()))?;
+                Ok((path, write))
+            })
+            .collect()
+    }
+
+    /// Embed and apply the queued writes in order, taking each out of the
+    /// outbox once it is written.
+    ///
+    /// Stops at the first write that fails, so later writes that depend on
+    /// it, such as an update of a queued add, stay queued behind it.
+    pub async fn flush(&self, service: &ArtifactService) -> Result<FlushReport> {
+        let pending = self.pending()?;
+        let mut report = FlushReport {
+            pending: pending.len(),
+            ..Default::default()
+        };
+        for (path, write) in pending {
+            let description = write.describe();
+            if let Err(e) = write.apply(service).await {
+                report.failed = Some((description, format!("{:#}", e)));
+                break;
+            }
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove {}", path.display()))?;
+            report.flushed.push(description);
+            report.pending -= 1;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use crate::testing::{TestDatabase, TestEmbedding};
+    use std::sync::Arc;
+
+    fn artifact(id: &str) -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            "Orders ship after payment".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            String::new(),
+        );
+        artifact.id = id.to_string();
+        artifact
+    }
+
+    #[tokio::test]
+    async fn flush_applies_queued_writes_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::new(dir.path());
+        assert!(outbox.pending().unwrap().is_empty());
+
+        outbox
+            .push(&QueuedWrite::Add {
+                artifact: Box::new(artifact("a1")),
+            })
+            .unwrap();
+        outbox
+            .push(&QueuedWrite::Update {
+                id: "a1".to_string(),
+                content: Some("Orders ship once paid".to_string()),
+                name: None,
+                kind: None,
+                labels: None,
+                context: None,
+            })
+            .unwrap();
+        outbox
+            .push(&QueuedWrite::Update {
+                id: "missing".to_string(),
+                content: None,
+                name: Some("Nope".to_string()),
+                kind: None,
+                labels: None,
+                context: None,
+            })
+            .unwrap();
+        outbox
+            .push(&QueuedWrite::Remove {
+                id: "a1".to_string(),
+            })
+            .unwrap();
+        assert_eq!(outbox.pending().unwrap().len(), 4);
+
+        let db = Arc::new(TestDatabase::new());
+        let service = ArtifactService::new(db.clone(), Arc::new(TestEmbedding));
+        let report = outbox.flush(&service).await.unwrap();
+        assert_eq!(report.flushed, vec!["add intent a1", "update a1"]);
+        assert_eq!(report.failed.as_ref().unwrap().0, "update missing");
+        assert_eq!(report.pending, 2);
+
+        let stored = service.get("a1").await.unwrap().unwrap();
+        assert_eq!(stored.content, "Orders ship once paid");
+        assert!(stored.embedding.is_some());
+        assert_eq!(stored.embedding_model, "test-embedding-model");
+
+        let pending = outbox.pending().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].1.describe(), "update missing");
+    }
+
+    #[tokio::test]
+    async fn flush_resolves_queued_prefixes() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::new(dir.path());
+        let db = Arc::new(TestDatabase::new());
+        let service = ArtifactService::new(db.clone(), Arc::new(TestEmbedding));
+        service.import(artifact("k7v3m9xnp2")).await.unwrap();
+        service.import(artifact("x2p9q4wmr8")).await.unwrap();
+
+        outbox
+            .push(&QueuedWrite::Update {
+                id: "k7v3".to_string(),
+                content: Some("Orders ship once paid".to_string()),
+                name: None,
+                kind: None,
+                labels: None,
+                context: None,
+            })
+            .unwrap();
+        outbox
+            .push(&QueuedWrite::Remove {
+                id: "x2p9".to_string(),
+            })
+            .unwrap();
+
+        let report = outbox.flush(&service).await.unwrap();
+        assert!(report.failed.is_none(), "{:?}", report.failed);
+        assert_eq!(report.pending, 0);
+        let updated = service.get("k7v3m9xnp2").await.unwrap().unwrap();
+        assert_eq!(updated.content, "Orders ship once paid");
+        assert!(service.get("x2p9q4wmr8").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn flush_fails_a_removal_that_matches_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let outbox = Outbox::new(dir.path());
+        let db = Arc::new(TestDatabase::new());
+        let service = ArtifactService::new(db.clone(), Arc::new(TestEmbedding));
+        service.import(artifact("k7v3m9xnp2")).await.unwrap();
+
+        outbox
+            .push(&QueuedWrite::Remove {
+                id: "zz".to_string(),
+            })
+            .unwrap();
+
+        let report = outbox.flush(&service).await.unwrap();
+        assert!(report.flushed.is_empty());
+        let (write, reason) = report.failed.unwrap();
+        assert_eq!(write, "remove zz");
+        assert!(reason.contains("not found"), "{reason}");
+        assert_eq!(report.pending, 1);
+        assert_eq!(outbox.pending().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,204 @@
+//! Who is accountable for each artifact.
+//!
+//! An artifact can name an owner, the person or team answerable for it, and
+//! reviewers who look over changes to it. Both are `@handle`s. With people
+//! and teams listed under `[owners]`, only those can be named, so a typo
+//! can't leave a truth owned by nobody in particular.
+
+use super::limits::InputRejected;
+use super::types::{Artifact, OwnersConfig};
+use std::collections::BTreeMap;
+
+/// A change to who owns and reviews an artifact; `None` keeps what is set
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Assignment {
+    /// New owner, or `Some(None)` to leave the artifact unowned
+    pub owner: Option<Option<String>>,
+    /// New reviewers, replacing the current ones
+    pub reviewers: Option<Vec<String>>,
+}
+
+impl Assignment {
+    /// Whether the assignment changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.owner.is_none() && self.reviewers.is_none()
+    }
+
+    /// Check every handle the assignment names against `owners`
+    pub fn check(&self, owners: &OwnersConfig) -> Result<(), InputRejected> {
+        let owner = self.owner.iter().flatten();
+        for handle in owner.chain(self.reviewers.iter().flatten()) {
+            owners.check(handle)?;
+        }
+        Ok(())
+    }
+
+    /// Apply the assignment to `artifact`
+    pub fn apply(&self, artifact: &mut Artifact) {
+        if let Some(owner) = &self.owner {
+            artifact.owner = owner.clone();
+        }
+        if let Some(reviewers) = &self.reviewers {
+            artifact.reviewers = reviewers.clone();
+        }
+    }
+}
+
+/// `handle` with a leading `@`, so `payments` and `@payments` are the same
+pub fn normalize_handle(handle: &str) -> String {
+    let handle = handle.trim();
+    if handle.starts_with('@') {
+        handle.to_string()
+    } else {
+        format!("@{}", handle)
+    }
+}
+
+impl OwnersConfig {
+    /// Whether no people or teams are configured, so any handle is accepted
+    pub fn is_default(&self) -> bool {
+        self.people.is_empty() && self.teams.is_empty()
+    }
+
+    /// `handle` normalized, or an error if it is malformed or, with people
+    /// and teams configured, not one of them
+    pub fn check(&self, handle: &str) -> Result<String, InputRejected> {
+        let handle = normalize_handle(handle);
+        let name = &handle[1..];
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '/'));
+        if !valid {
+            return Err(InputRejected(format!(
+                "Invalid owner or reviewer '{}': use @ followed by letters, digits, '-', '_', '.' or '/'",
+                handle
+            )));
+        }
+        let known = self.is_default()
+            || self
+                .people
+                .iter()
+                .chain(&self.teams)
+                .any(|h| normalize_handle(h) == handle);
+        if !known {
+            return Err(InputRejected(format!(
+                "Unknown owner or reviewer '{}': add it to people or teams in [owners]",
+                handle
+            )));
+        }
+        Ok(handle)
+    }
+
+    /// Normalize and check an artifact's owner and reviewers, dropping
+    /// repeated reviewers
+    pub fn check_artifact(&self, artifact: &mut Artifact) -> Result<(), InputRejected> {
+        if let Some(owner) = &artifact.owner {
+            artifact.owner = Some(self.check(owner)?);
+        }
+        let mut reviewers: Vec<String> = Vec::with_capacity(artifact.reviewers.len());
+        for reviewer in &artifact.reviewers {
+            let reviewer = self.check(reviewer)?;
+            if !reviewers.contains(&reviewer) {
+                reviewers.push(reviewer);
+            }
+        }
+        artifact.reviewers = reviewers;
+        Ok(())
+    }
+}
+
+/// How many artifacts of one kind have an owner, and which don't
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KindOwnership {
+    pub kind: String,
+    pub total: usize,
+    pub owned: usize,
+    /// Artifacts without an owner, as (ID, name)
+    pub unowned: Vec<(String, Option<String>)>,
+}
+
+/// Ownership of `artifacts` by kind, in kind order
+pub fn ownership_report(artifacts: &[Artifact]) -> Vec<KindOwnership> {
+    let mut kinds: BTreeMap<&str, KindOwnership> = BTreeMap::new();
+    for artifact in artifacts {
+        let entry = kinds
+            .entry(&artifact.kind)
+            .or_insert_with(|| KindOwnership {
+                kind: artifact.kind.clone(),
+                total: 0,
+                owned: 0,
+                unowned: Vec::new(),
+            });
+        entry.total += 1;
+        if artifact.owner.is_some() {
+            entry.owned += 1;
+        } else {
+            entry
+                .unowned
+                .push((artifact.id.clone(), artifact.name.clone()));
+        }
+    }
+    kinds.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use std::collections::HashMap;
+
+    fn artifact(id: &str, kind: &str, owner: Option<&str>) -> Artifact {
+        let mut artifact = Artifact::new(
+            kind.to_string(),
+            "content".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            String::new(),
+        );
+        artifact.id = id.to_string();
+        artifact.owner = owner.map(str::to_string);
+        artifact
+    }
+
+    #[test]
+    fn checks_handles_against_configured_people_and_teams() {
+        let open = OwnersConfig::default();
+        assert_eq!(open.check("payments").unwrap(), "@payments");
+        assert!(open.check("@").is_err());
+        assert!(open.check("@pay ments").is_err());
+
+        let config = OwnersConfig {
+            people: vec!["@alice".to_string()],
+            teams: vec!["payments".to_string()],
+        };
+        assert_eq!(config.check("@payments").unwrap(), "@payments");
+        let err = config.check("@bob").unwrap_err();
+        assert!(err.to_string().contains("Unknown owner or reviewer '@bob'"));
+
+        let mut owned = artifact("a1", "intent", Some("payments"));
+        owned.reviewers = vec!["alice".to_string(), "@alice".to_string()];
+        config.check_artifact(&mut owned).unwrap();
+        assert_eq!(owned.owner.as_deref(), Some("@payments"));
+        assert_eq!(owned.reviewers, vec!["@alice"]);
+    }
+
+    #[test]
+    fn reports_unowned_artifacts_per_kind() {
+        let artifacts = vec![
+            artifact("i1", "intent", Some("@payments")),
+            artifact("i2", "intent", None),
+            artifact("c1", "contract", None),
+        ];
+        let report = ownership_report(&artifacts);
+        assert_eq!(
+            report
+                .iter()
+                .map(|k| (k.kind.as_str(), k.total, k.owned))
+                .collect::<Vec<_>>(),
+            vec![("contract", 1, 0), ("intent", 2, 1)]
+        );
+        assert_eq!(report[1].unowned, vec![("i2".to_string(), None)]);
+    }
+}
@@ -0,0 +1,304 @@
+//! Context packs for bootstrapping agents.
+//!
+//! Teams seed agent system prompts with the truth that matters most. A pack
+//! ranks the artifacts matching a filter by importance (kind boost, how
+//! recently they changed and their `status` label) and keeps the best ones
+//! whole until a token budget is spent, rendered as one Markdown document.
+
+use super::types::{estimate_tokens, Artifact, SearchFilters};
+use super::{expand_includes, ArtifactService, ServiceError, Variables};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Label holding an artifact's approval state
+pub const STATUS_LABEL: &str = "status";
+
+const TITLE: &str = "# Project context";
+
+/// Days for the recency part of the score to halve
+const RECENCY_HALF_LIFE_DAYS: f64 = 90.0;
+
+/// Weight of an artifact's `status` label in the ranking; statuses weighted
+/// zero are left out of packs
+fn status_weight(status: Option<&str>) -> f32 {
+    match status.map(str::to_lowercase).as_deref() {
+        Some("approved" | "accepted") => 1.5,
+        Some("draft" | "proposed") => 0.5,
+        Some("deprecated" | "rejected" | "superseded") => 0.0,
+        _ => 1.0,
+    }
+}
+
+/// An artifact in a pack
+#[derive(Debug, Clone, Serialize)]
+pub struct PackEntry {
+    pub id: String,
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    pub metadata: HashMap<String, String>,
+    pub updated_at: DateTime<Utc>,
+    /// Importance the artifact was ranked by
+    pub score: f32,
+    /// Estimated tokens of its Markdown section
+    pub tokens: usize,
+}
+
+/// The most important artifacts that fit a token budget
+#[derive(Debug, Clone, Serialize)]
+pub struct Pack {
+    pub budget: usize,
+    /// Estimated tokens of the rendered pack
+    pub tokens: usize,
+    /// Entries grouped by kind, most important first within each
+    pub artifacts: Vec<PackEntry>,
+    /// IDs of matching artifacts left out because they did not fit
+    pub omitted: Vec<String>,
+}
+
+impl Pack {
+    /// The pack as one Markdown document, a section per kind
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!("{}\n", TITLE);
+        let mut kind = None;
+        for entry in &self.artifacts {
+            if kind != Some(&entry.kind) {
+                markdown.push_str(&format!("\n## {}\n", entry.kind));
+                kind = Some(&entry.kind);
+            }
+            markdown.push('\n');
+            markdown.push_str(&section(entry));
+        }
+        markdown
+    }
+}
+
+/// Markdown section of an entry: its heading, content and context
+fn section(entry: &PackEntry) -> String {
+    let mut section = match &entry.name {
+        Some(name) => format!("### {} ({})\n\n", name, entry.id),
+        None => format!("### {}\n\n", entry.id),
+    };
+    section.push_str(entry.content.trim());
+    section.push('\n');
+    if let Some(context) = &entry.context {
+        section.push_str(&format!("\n_Why:_ {}\n", context.trim()));
+    }
+    section
+}
+
+/// Service that builds context packs
+pub struct PackService {
+    artifacts: Arc<ArtifactService>,
+    boosts: HashMap<String, f32>,
+    variables: Option<Variables>,
+}
+
+impl PackService {
+    pub fn new(artifacts: Arc<ArtifactService>) -> Self {
+        Self {
+            artifacts,
+            boosts: HashMap::new(),
+            variables: None,
+        }
+    }
+
+    /// Rank kinds by these factors, usually the project's search boosts
+    pub fn with_boosts(mut self, boosts: HashMap<String, f32>) -> Self {
+        self.boosts = boosts;
+        self
+    }
+
+    /// Fill `{{name}}` placeholders in packed content from `variables`
+    pub fn with_variables(mut self, variables: Variables) -> Self {
+        self.variables = Some(variables);
+        self
+    }
+
+    /// Pack the most important artifacts matching `filters`, of any of
+    /// `kinds` when given, into `budget` tokens
+    pub async fn pack(
+        &self,
+        filters: SearchFilters,
+        kinds: &[String],
+        budget: usize,
+    ) -> Result<Pack> {
+        if budget == 0 {
+            return Err(ServiceError::Validation("Budget must be above 0".to_string()).into());
+        }
+        let mut artifacts = self
+            .artifacts
+            .list(SearchFilters {
+                limit: None,
+                ..filters
+            })
+            .await?;
+        if !kinds.is_empty() {
+            artifacts.retain(|artifact| kinds.contains(&artifact.kind));
+        }
+        expand_includes(&self.artifacts, &mut artifacts).await?;
+        if let Some(variables) = &self.variables {
+            variables.apply(&mut artifacts)?;
+        }
+        Ok(select(artifacts, kinds, &self.boosts, budget, Utc::now()))
+    }
+}
+
+/// Importance of `artifact` as of `now`: kind boost times status weight
+/// times a recency factor between 0.5 and 1
+fn score(artifact: &Artifact, boosts: &HashMap<String, f32>, now: DateTime<Utc>) -> f32 {
+    let boost = boosts.get(&artifact.kind).copied().unwrap_or(1.0);
+    let status = status_weight(artifact.metadata.get(STATUS_LABEL).map(String::as_str));
+    let age_days = (now - artifact.updated_at).num_seconds().max(0) as f64 / 86_400.0;
+    let recency = 0.5 + 0.5 * 0.5f64.powf(age_days / RECENCY_HALF_LIFE_DAYS);
+    boost * status * recency as f32
+}
+
+/// Keep the highest scoring artifacts whose sections fit in `budget`,
+/// grouped by kind in the order of `kinds`, else of each kind's best entry
+fn select(
+    artifacts: Vec<Artifact>,
+    kinds: &[String],
+    boosts: &HashMap<String, f32>,
+    budget: usize,
+    now: DateTime<Utc>,
+) -> Pack {
+    let mut ranked: Vec<PackEntry> = artifacts
+        .into_iter()
+        .map(|artifact| {
+            let score = score(&artifact, boosts, now);
+            let mut entry = PackEntry {
+                id: artifact.id,
+                kind: artifact.kind,
+                name: artifact.name,
+                content: artifact.content,
+                context: artifact.context,
+                metadata: artifact.metadata,
+                updated_at: artifact.updated_at,
+                score,
+                tokens: 0,
+            };
+            entry.tokens = estimate_tokens(&section(&entry));
+            entry
+        })
+        .filter(|entry| entry.score > 0.0)
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.updated_at.cmp(&a.updated_at))
+            .then(a.id.cmp(&b.id))
+    });
+
+    // Headings cost tokens too; leave room for the title and each kind's
+    let mut remaining = budget.saturating_sub(estimate_tokens(TITLE));
+    let mut kept: Vec<PackEntry> = Vec::new();
+    let mut omitted = Vec::new();
+    for entry in ranked {
+        let heading = if kept.iter().any(|kept| kept.kind == entry.kind) {
+            0
+        } else {
+            estimate_tokens(&format!("## {}", entry.kind))
+        };
+        if entry.tokens + heading <= remaining {
+            remaining -= entry.tokens + heading;
+            kept.push(entry);
+        } else {
+            omitted.push(entry.id);
+        }
+    }
+
+    let mut order: Vec<String> = kinds.to_vec();
+    for entry in &kept {
+        if !order.contains(&entry.kind) {
+            order.push(entry.kind.clone());
+        }
+    }
+    // Stable, so each kind keeps its entries best first
+    kept.sort_by_key(|entry| order.iter().position(|kind| *kind == entry.kind));
+
+    let mut pack = Pack {
+        budget,
+        tokens: 0,
+        artifacts: kept,
+        omitted,
+    };
+    pack.tokens = estimate_tokens(&pack.to_markdown());
+    pack
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+    use chrono::Duration;
+
+    fn artifact(
+        id: &str,
+        kind: &str,
+        words: usize,
+        age_days: i64,
+        status: Option<&str>,
+    ) -> Artifact {
+        let mut artifact = Artifact::new(
+            kind.to_string(),
+            vec!["word"; words].join(" "),
+            ContentFormat::Markdown,
+            None,
+            status
+                .map(|s| HashMap::from([(STATUS_LABEL.to_string(), s.to_string())]))
+                .unwrap_or_default(),
+            "test".to_string(),
+        );
+        artifact.id = id.to_string();
+        artifact.updated_at = Utc::now() - Duration::days(age_days);
+        artifact
+    }
+
+    #[test]
+    fn ranks_by_status_boost_and_recency() {
+        let now = Utc::now();
+        let boosts = HashMap::from([("invariant".to_string(), 2.0)]);
+        let artifacts = vec![
+            artifact("old", "boundary", 3, 365, None),
+            artifact("new", "boundary", 3, 0, None),
+            artifact("approved", "boundary", 3, 365, Some("approved")),
+            artifact("deprecated", "boundary", 3, 0, Some("deprecated")),
+            artifact("boosted", "invariant", 3, 365, None),
+        ];
+
+        let pack = select(artifacts, &[], &boosts, 1000, now);
+        let ids: Vec<&str> = pack.artifacts.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["boosted", "new", "approved", "old"]);
+        assert!(pack.omitted.is_empty());
+    }
+
+    #[test]
+    fn keeps_whole_artifacts_within_the_budget() {
+        let now = Utc::now();
+        // 30 words is 40 tokens of content, plus the heading
+        let artifacts = vec![
+            artifact("a", "invariant", 30, 0, None),
+            artifact("b", "invariant", 30, 1, None),
+            artifact("c", "boundary", 3, 2, None),
+        ];
+        let kinds = ["boundary".to_string(), "invariant".to_string()];
+
+        let pack = select(artifacts, &kinds, &HashMap::new(), 60, now);
+        let ids: Vec<&str> = pack.artifacts.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, ["c", "a"]);
+        assert_eq!(pack.omitted, ["b"]);
+        assert!(pack.tokens <= 60);
+
+        let markdown = pack.to_markdown();
+        assert!(markdown.starts_with("# Project context\n\n## boundary\n\n### c\n"));
+        assert!(markdown.contains("\n## invariant\n\n### a\n"));
+    }
+}
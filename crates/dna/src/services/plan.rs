@@ -0,0 +1,840 @@
+use super::types::{slugify_kind, Artifact, ContentFormat, LabelsConfig};
+use super::{ArtifactService, ServiceError};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// Label key used by `link` operations when none is given
+pub const DEFAULT_LINK_LABEL: &str = "related";
+
+/// A declarative batch of artifact changes, applied all-or-nothing.
+///
+/// ```yaml
+/// operations:
+///   - op: add
+///     ref: login
+///     kind: intent
+///     content: Users can log in with email
+///   - op: update
+///     id: k7v3m9
+///     labels: { domain: auth }
+///   - op: link
+///     from: "@login"
+///     to: k7v3m9
+///   - op: remove
+///     id: x2p9q4
+/// ```
+///
+/// IDs may be prefixes. `@name` refers to an artifact added earlier in the
+/// same plan under `ref: name`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Plan {
+    #[serde(default)]
+    pub operations: Vec<PlanOperation>,
+}
+
+impl Plan {
+    /// Parse a plan from YAML (or JSON, which is valid YAML)
+    pub fn parse(text: &str) -> Result<Self> {
+        serde_yaml::from_str(text).context("Failed to parse plan")
+    }
+}
+
+/// A single operation within a plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase", deny_unknown_fields)]
+pub enum PlanOperation {
+    Add {
+        /// Create the artifact with this ID instead of generating one
+        #[serde(default)]
+        id: Option<String>,
+        kind: String,
+        content: String,
+        #[serde(default)]
+        format: Option<ContentFormat>,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        labels: HashMap<String, String>,
+        #[serde(default)]
+        context: Option<String>,
+        /// Name later operations can use (as `@name`) to refer to this artifact
+        #[serde(default, rename = "ref")]
+        reference: Option<String>,
+    },
+    Update {
+        id: String,
+        #[serde(default)]
+        content: Option<String>,
+        #[serde(default)]
+        name: Option<String>,
+        #[serde(default)]
+        kind: Option<String>,
+        #[serde(default)]
+        labels: Option<HashMap<String, String>>,
+        #[serde(default)]
+        context: Option<String>,
+    },
+    Remove {
+        id: String,
+    },
+    /// Record `to` in the `label` label of `from` (comma-separated list of IDs)
+    Link {
+        from: String,
+        to: String,
+        #[serde(default)]
+        label: Option<String>,
+    },
+}
+
+impl PlanOperation {
+    /// Short name of the operation (`add`, `update`, `remove`, `link`)
+    pub fn action(&self) -> &'static str {
+        match self {
+            PlanOperation::Add { .. } => "add",
+            PlanOperation::Update { .. } => "update",
+            PlanOperation::Remove { .. } => "remove",
+            PlanOperation::Link { .. } => "link",
+        }
+    }
+}
+
+/// A validated operation, as it will be executed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlanStep {
+    /// Position in the plan (1-based)
+    pub index: usize,
+    pub action: &'static str,
+    /// Resolved artifact ID, or `@name` for artifacts the plan creates
+    pub target: String,
+}
+
+/// Outcome of a successfully applied plan
+#[derive(Debug, Clone, Serialize)]
+pub struct ApplyReport {
+    /// Database version before the plan ran
+    pub version_before: u64,
+    pub version_after: u64,
+    /// Executed steps, with `@name` targets replaced by the created IDs
+    pub steps: Vec<PlanStep>,
+}
+
+/// Artifact reference after validation
+#[derive(Debug, Clone)]
+enum Target {
+    Existing(String),
+    Ref(String),
+}
+
+impl Target {
+    fn display(&self) -> String {
+        match self {
+            Target::Existing(id) => id.clone(),
+            Target::Ref(name) => format!("@{}", name),
+        }
+    }
+}
+
+/// How to reverse one write made by a plan
+#[derive(Debug, Clone)]
+enum Undo {
+    /// Remove an artifact the plan added
+    Remove(String),
+    /// Write back an artifact as it was before the plan changed it
+    PutBack(Box<Artifact>),
+}
+
+/// Service that validates and applies plans atomically.
+///
+/// Every operation is checked before anything is written. If an operation
+/// fails, the plan's earlier writes are reversed one by one, newest first,
+/// so writes other processes made to other artifacts in the meantime are
+/// kept.
+pub struct PlanService {
+    artifacts: Arc<ArtifactService>,
+    labels: LabelsConfig,
+}
+
+impl PlanService {
+    pub fn new(artifacts: Arc<ArtifactService>) -> Self {
+        Self {
+            artifacts,
+            labels: LabelsConfig::default(),
+        }
+    }
+
+    /// Validate labels in the plan against a label registry
+    pub fn with_labels(mut self, labels: LabelsConfig) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Check every operation without writing anything.
+    ///
+    /// Returns the steps that would run, or a validation error listing every
+    /// problem found.
+    pub async fn validate(&self, plan: &Plan) -> Result<Vec<PlanStep>, ServiceError> {
+        let resolved = self.resolve(plan).await?;
+        Ok(resolved
+            .iter()
+            .map(|(index, op, targets)| PlanStep {
+                index: *index,
+                action: op.action(),
+                target: targets[0].display(),
+            })
+            .collect())
+    }
+
+    /// Validate and apply a plan, rolling back all changes if any operation fails
+    pub async fn apply(&self, plan: &Plan) -> Result<ApplyReport, ServiceError> {
+        let resolved = self.resolve(plan).await?;
+        let version_before = self.artifacts.version().await?;
+
+        let mut created: HashMap<String, String> = HashMap::new();
+        let mut undo: Vec<Undo> = Vec::new();
+        let mut steps = Vec::with_capacity(resolved.len());
+        for (index, op, targets) in &resolved {
+            match self.execute(op, targets, &mut created, &mut undo).await {
+                Ok(id) => steps.push(PlanStep {
+                    index: *index,
+                    action: op.action(),
+                    target: id,
+                }),
+                Err(e) => {
+                    self.roll_back(undo).await.map_err(|re| {
+                        ServiceError::Internal(re.context(format!(
+                            "Operation {} ({}) failed and rollback did not complete: {}",
+                            index,
+                            op.action(),
+                            e
+                        )))
+                    })?;
+                    return Err(ServiceError::Internal(anyhow::anyhow!(
+                        "Operation {} ({}) failed, earlier operations were rolled back: {}",
+                        index,
+                        op.action(),
+                        e
+                    )));
+                },
+            }
+        }
+
+        Ok(ApplyReport {
+            version_before,
+            version_after: self.artifacts.version().await?,
+            steps,
+        })
+    }
+
+    /// Undo the plan's own writes, newest first, leaving other writers'
+    /// changes in place
+    async fn roll_back(&self, undo: Vec<Undo>) -> Result<()> {
+        for step in undo.into_iter().rev() {
+            match step {
+                Undo::Remove(id) => {
+                    self.artifacts.remove(&id).await?;
+                },
+                Undo::PutBack(artifact) => self.artifacts.put_back(&artifact).await?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Resolve IDs and refs and validate every operation, collecting all problems
+    async fn resolve<'a>(
+        &self,
+        plan: &'a Plan,
+    ) -> Result<Vec<(usize, &'a PlanOperation, Vec<Target>)>, ServiceError> {
+        let mut problems = Vec::new();
+        let mut resolved = Vec::new();
+        // Kind of each artifact as of the current point in the plan
+        let mut kinds: HashMap<String, String> = HashMap::new();
+        let mut ref_kinds: HashMap<String, String> = HashMap::new();
+        let mut removed: HashSet<String> = HashSet::new();
+
+        if plan.operations.is_empty() {
+            problems.push("plan has no operations".to_string());
+        }
+
+        for (i, op) in plan.operations.iter().enumerate() {
+            let index = i + 1;
+            let mut fail = |msg: String| problems.push(format!("operation {}: {}", index, msg));
+
+            match op {
+                PlanOperation::Add {
+                    id,
+                    kind,
+                    content,
+                    labels,
+                    reference,
+                    ..
+                } => {
+                    let kind = slugify_kind(kind);
+                    if content.trim().is_empty() {
+                        fail("content cannot be empty".to_string());
+                    }
+                    if let Some(id) = id {
+                        let exists = match self.artifacts.get(id).await {
+                            Ok(existing) => existing.is_some(),
+                            Err(e) => {
+                                fail(e.to_string());
+                                false
+                            },
+                        };
+                        if exists || kinds.contains_key(id) {
+                            fail(format!("artifact '{}' already exists", id));
+                        }
+                        kinds.insert(id.clone(), kind.clone());
+                    }
+                    if let Err(e) = self.labels.validate(&kind, labels) {
+                        fail(e.to_string());
+                    }
+                    let name = reference.clone().unwrap_or_else(|| index.to_string());
+                    if ref_kinds.insert(name.clone(), kind).is_some() {
+                        fail(format!("ref '{}' is defined more than once", name));
+                    }
+                    resolved.push((index, op, vec![Target::Ref(name)]));
+                },
+                PlanOperation::Update {
+                    id, kind, labels, ..
+                } => {
+                    let Some((target, current_kind)) = self
+                        .lookup(id, &kinds, &ref_kinds, &removed, &mut fail)
+                        .await
+                    else {
+                        continue;
+                    };
+                    let new_kind = kind.as_deref().map(slugify_kind).unwrap_or(current_kind);
+                    if let Some(labels) = labels {
+                        if let Err(e) = self.labels.validate(&new_kind, labels) {
+                            fail(e.to_string());
+                        }
+                    }
+                    match &target {
+                        Target::Existing(id) => kinds.insert(id.clone(), new_kind),
+                        Target::Ref(name) => ref_kinds.insert(name.clone(), new_kind),
+                    };
+                    resolved.push((index, op, vec![target]));
+                },
+                PlanOperation::Remove { id } => {
+                    let Some((target, _)) = self
+                        .lookup(id, &kinds, &ref_kinds, &removed, &mut fail)
+                        .await
+                    else {
+                        continue;
+                    };
+                    removed.insert(target.display());
+                    resolved.push((index, op, vec![target]));
+                },
+                PlanOperation::Link { from, to, label } => {
+                    let source = self
+                        .lookup(from, &kinds, &ref_kinds, &removed, &mut fail)
+                        .await;
+                    let dest = self
+                        .lookup(to, &kinds, &ref_kinds, &removed, &mut fail)
+                        .await;
+                    let (Some((source, source_kind)), Some((dest, _))) = (source, dest) else {
+                        continue;
+                    };
+                    let label = label.as_deref().unwrap_or(DEFAULT_LINK_LABEL);
+                    let check = HashMap::from([(label.to_string(), dest.display())]);
+                    if let Err(e) = self.labels.validate(&source_kind, &check) {
+                        fail(e.to_string());
+                    }
+                    resolved.push((index, op, vec![source, dest]));
+                },
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(resolved)
+        } else {
+            Err(ServiceError::Validation(format!(
+                "plan rejected, nothing was changed:\n  {}",
+                problems.join("\n  ")
+            )))
+        }
+    }
+
+    /// Resolve an ID, prefix, or `@ref` to a target and its current kind
+    async fn lookup(
+        &self,
+        id: &str,
+        kinds: &HashMap<String, String>,
+        ref_kinds: &HashMap<String, String>,
+        removed: &HashSet<String>,
+        fail: &mut impl FnMut(String),
+    ) -> Option<(Target, String)> {
+        let (target, kind) = if let Some(name) = id.strip_prefix('@') {
+            match ref_kinds.get(name) {
+                Some(kind) => (Target::Ref(name.to_string()), kind.clone()),
+                None => {
+                    fail(format!("'{}' does not match an earlier add", id));
+                    return None;
+                },
+            }
+        } else if let Some(kind) = kinds.get(id) {
+            // Touched or created earlier in the plan
+            (Target::Existing(id.to_string()), kind.clone())
+        } else {
+            let resolved = match self.artifacts.resolve_id(id).await {
+                Ok(resolved) => resolved,
+                Err(e) => {
+                    fail(e.to_string());
+                    return None;
+                },
+            };
+            let kind = match kinds.get(&resolved) {
+                Some(kind) => kind.clone(),
+                None => match self.artifacts.get(&resolved).await {
+                    Ok(artifact) => artifact.map(|a| a.kind).unwrap_or_default(),
+                    Err(e) => {
+                        fail(e.to_string());
+                        return None;
+                    },
+                },
+            };
+            (Target::Existing(resolved), kind)
+        };
+
+        if removed.contains(&target.display()) {
+            fail(format!("'{}' is removed earlier in the plan", id));
+            return None;
+        }
+        Some((target, kind))
+    }
+
+    /// Run one validated operation, returning the ID it touched and
+    /// recording in `undo` how to reverse it
+    async fn execute(
+        &self,
+        op: &PlanOperation,
+        targets: &[Target],
+        created: &mut HashMap<String, String>,
+        undo: &mut Vec<Undo>,
+    ) -> Result<String> {
+        let id_of = |target: &Target| -> Result<String> {
+            match target {
+                Target::Existing(id) => Ok(id.clone()),
+                Target::Ref(name) => created
+                    .get(name)
+                    .cloned()
+                    .with_context(|| format!("'@{}' was not created", name)),
+            }
+        };
+
+        // Every operation but add writes only its first target; keep it as
+        // it was so a rollback can put it back
+        if !matches!(op, PlanOperation::Add { .. }) {
+            let id = id_of(&targets[0])?;
+            let before = self
+                .artifacts
+                .get(&id)
+                .await?
+                .with_context(|| format!("Artifact '{}' not found", id))?;
+            undo.push(Undo::PutBack(Box::new(before)));
+        }
+
+        match op {
+            PlanOperation::Add {
+                id,
+                kind,
+                content,
+                format,
+                name,
+                labels,
+                context,
+                ..
+            } => {
+                let format = format.unwrap_or(ContentFormat::Markdown);
+                let artifact = match id {
+                    Some(id) => {
+                        let mut artifact = Artifact::new(
+                            kind.clone(),
+                            content.clone(),
+                            format,
+                            name.clone(),
+                            labels.clone(),
+                            String::new(),
+                        );
+                        artifact.id = id.clone();
+                        artifact.context = context.clone();
+                        self.artifacts.import(artifact).await?
+                    },
+                    None => {
+                        self.artifacts
+                            .add(
+                                kind.clone(),
+                                content.clone(),
+                                format,
+                                name.clone(),
+                                labels.clone(),
+                                context.clone(),
+                            )
+                            .await?
+                    },
+                };
+                if let Some(Target::Ref(name)) = targets.first() {
+                    created.insert(name.clone(), artifact.id.clone());
+                }
+                undo.push(Undo::Remove(artifact.id.clone()));
+                Ok(artifact.id)
+            },
+            PlanOperation::Update {
+                content,
+                name,
+                kind,
+                labels,
+                context,
+                ..
+            } => {
+                let id = id_of(&targets[0])?;
+                self.artifacts
+                    .update(
+                        &id,
+                        content.clone(),
+                        name.clone(),
+                        kind.clone(),
+                        labels.clone(),
+                        context.clone(),
+                    )
+                    .await?;
+                Ok(id)
+            },
+            PlanOperation::Remove { .. } => {
+                let id = id_of(&targets[0])?;
+                if !self.artifacts.remove(&id).await? {
+                    anyhow::bail!("Artifact '{}' not found", id);
+                }
+                Ok(id)
+            },
+            PlanOperation::Link { label, .. } => {
+                let from = id_of(&targets[0])?;
+                let to = id_of(&targets[1])?;
+                let label = label.as_deref().unwrap_or(DEFAULT_LINK_LABEL);
+                let artifact = self
+                    .artifacts
+                    .get(&from)
+                    .await?
+                    .with_context(|| format!("Artifact '{}' not found", from))?;
+
+                let mut linked: Vec<&str> = artifact
+                    .metadata
+                    .get(label)
+                    .map(|v| {
+                        v.split(',')
+                            .map(str::trim)
+                            .filter(|s| !s.is_empty())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if !linked.contains(&to.as_str()) {
+                    linked.push(&to);
+                    let metadata = HashMap::from([(label.to_string(), linked.join(","))]);
+                    self.artifacts
+                        .update(&from, None, None, None, Some(metadata), None)
+                        .await?;
+                }
+                Ok(from)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::embedding::EmbeddingProvider;
+    use crate::services::{LabelDefinition, LabelValueType, SearchFilters};
+    use crate::testing::{TestDatabase, TestEmbedding};
+
+    /// Embedding provider that fails for content containing "boom"
+    struct FailingEmbedding;
+
+    #[async_trait::async_trait]
+    impl EmbeddingProvider for FailingEmbedding {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            if text.contains("boom") {
+                anyhow::bail!("embedding provider unavailable");
+            }
+            TestEmbedding.embed(text).await
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            let mut results = Vec::new();
+            for text in texts {
+                results.push(self.embed(text).await?);
+            }
+            Ok(results)
+        }
+
+        fn model_id(&self) -> &str {
+            TestEmbedding.model_id()
+        }
+
+        fn dimensions(&self) -> usize {
+            TestEmbedding.dimensions()
+        }
+    }
+
+    /// Embedding provider standing in for another writer: embedding content
+    /// containing "meanwhile" first commits `other` straight to the store
+    struct InterleavingEmbedding {
+        db: Arc<TestDatabase>,
+        other: Artifact,
+    }
+
+    #[async_trait::async_trait]
+    impl EmbeddingProvider for InterleavingEmbedding {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            if text.contains("meanwhile") {
+                self.db.insert(&self.other).await?;
+            }
+            FailingEmbedding.embed(text).await
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            FailingEmbedding.embed_batch(texts).await
+        }
+
+        fn model_id(&self) -> &str {
+            TestEmbedding.model_id()
+        }
+
+        fn dimensions(&self) -> usize {
+            TestEmbedding.dimensions()
+        }
+    }
+
+    async fn setup(
+        embedding: Arc<dyn EmbeddingProvider>,
+    ) -> (Arc<TestDatabase>, PlanService, Artifact) {
+        let db = Arc::new(TestDatabase::new());
+        let artifacts = Arc::new(ArtifactService::new(db.clone(), embedding));
+        let existing = artifacts
+            .add(
+                "intent".to_string(),
+                "existing".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        (db, PlanService::new(artifacts), existing)
+    }
+
+    #[test]
+    fn parse_reads_yaml_operations() {
+        let plan = Plan::parse(
+            "operations:\n  - op: add\n    ref: a\n    kind: intent\n    content: x\n  - op: link\n    from: \"@a\"\n    to: abc\n",
+        )
+        .unwrap();
+        assert_eq!(plan.operations.len(), 2);
+        assert_eq!(plan.operations[1].action(), "link");
+    }
+
+    #[test]
+    fn parse_rejects_unknown_fields() {
+        assert!(Plan::parse("operations:\n  - op: remove\n    idd: abc\n").is_err());
+        assert!(Plan::parse("operations:\n  - op: rename\n    id: abc\n").is_err());
+    }
+
+    #[tokio::test]
+    async fn apply_runs_operations_in_order() {
+        let (db, service, existing) = setup(Arc::new(TestEmbedding)).await;
+        let plan = Plan::parse(&format!(
+            "operations:
+  - op: add
+    ref: login
+    kind: intent
+    content: Users can log in
+  - op: update
+    id: {prefix}
+    content: updated
+  - op: link
+    from: \"@login\"
+    to: {id}
+",
+            prefix = &existing.id[..6],
+            id = existing.id
+        ))
+        .unwrap();
+
+        let report = service.apply(&plan).await.unwrap();
+        assert_eq!(report.steps.len(), 3);
+        assert!(report.version_after > report.version_before);
+
+        let created = db.get(&report.steps[0].target).await.unwrap().unwrap();
+        assert_eq!(created.metadata.get("related"), Some(&existing.id));
+        let updated = db.get(&existing.id).await.unwrap().unwrap();
+        assert_eq!(updated.content, "updated");
+    }
+
+    #[tokio::test]
+    async fn add_with_id_keeps_id_and_is_addressable() {
+        let (db, service, existing) = setup(Arc::new(TestEmbedding)).await;
+        let plan = Plan::parse(&format!(
+            "operations:
+  - op: add
+    id: fixedid234
+    kind: intent
+    content: pinned
+  - op: link
+    from: fixedid234
+    to: {id}
+",
+            id = existing.id
+        ))
+        .unwrap();
+
+        service.apply(&plan).await.unwrap();
+        let created = db.get("fixedid234").await.unwrap().unwrap();
+        assert_eq!(created.metadata.get("related"), Some(&existing.id));
+
+        let again = service.validate(&plan).await.unwrap_err();
+        assert!(again.to_string().contains("already exists"), "{again}");
+    }
+
+    #[tokio::test]
+    async fn validation_failures_change_nothing() {
+        let (db, service, existing) = setup(Arc::new(TestEmbedding)).await;
+        let plan = Plan::parse(&format!(
+            "operations:
+  - op: add
+    kind: intent
+    content: new
+  - op: remove
+    id: {id}
+  - op: update
+    id: {id}
+    content: too late
+  - op: remove
+    id: missing
+  - op: link
+    from: \"@nope\"
+    to: {id}
+",
+            id = existing.id
+        ))
+        .unwrap();
+
+        let err = service.apply(&plan).await.unwrap_err();
+        let msg = err.to_string();
+        assert!(matches!(err, ServiceError::Validation(_)));
+        assert!(msg.contains("operation 3"), "{msg}");
+        assert!(msg.contains("operation 4"), "{msg}");
+        assert!(msg.contains("operation 5"), "{msg}");
+        assert_eq!(db.list(SearchFilters::default()).await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn validate_checks_labels_against_registry() {
+        let (_db, service, _) = setup(Arc::new(TestEmbedding)).await;
+        let mut labels = LabelsConfig::default();
+        labels.insert(LabelDefinition {
+            value_type: LabelValueType::Enum,
+            values: vec!["high".to_string()],
+            ..LabelDefinition::new("priority", "Priority")
+        });
+        let service = service.with_labels(labels);
+
+        let plan = Plan::parse(
+            "operations:\n  - op: add\n    kind: intent\n    content: x\n    labels: { priority: low }\n",
+        )
+        .unwrap();
+        assert!(service.validate(&plan).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn failure_during_apply_rolls_back() {
+        let (db, service, existing) = setup(Arc::new(FailingEmbedding)).await;
+        let plan = Plan::parse(&format!(
+            "operations:
+  - op: add
+    kind: intent
+    content: fine
+  - op: remove
+    id: {id}
+  - op: add
+    kind: intent
+    content: boom
+",
+            id = existing.id
+        ))
+        .unwrap();
+
+        let err = service.apply(&plan).await.unwrap_err();
+        assert!(err.to_string().contains("rolled back"), "{err}");
+
+        let remaining = db.list(SearchFilters::default()).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, existing.id);
+    }
+
+    #[tokio::test]
+    async fn rollback_keeps_writes_made_by_others_meanwhile() {
+        let db = Arc::new(TestDatabase::new());
+        let other = Artifact::new(
+            "intent".to_string(),
+            "written by another process".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            String::new(),
+        );
+        let embedding = Arc::new(InterleavingEmbedding {
+            db: db.clone(),
+            other: other.clone(),
+        });
+        let artifacts = Arc::new(ArtifactService::new(db.clone(), embedding));
+        let existing = artifacts
+            .add(
+                "intent".to_string(),
+                "existing".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                None,
+            )
+            .await
+            .unwrap();
+        let service = PlanService::new(artifacts);
+        let plan = Plan::parse(&format!(
+            "operations:
+  - op: update
+    id: {id}
+    content: changed
+  - op: add
+    kind: intent
+    content: added meanwhile
+  - op: remove
+    id: {id}
+  - op: add
+    kind: intent
+    content: boom
+",
+            id = existing.id
+        ))
+        .unwrap();
+
+        let err = service.apply(&plan).await.unwrap_err();
+        assert!(err.to_string().contains("rolled back"), "{err}");
+
+        let mut remaining: Vec<String> = db
+            .list(SearchFilters::default())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|a| a.content)
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, ["existing", "written by another process"]);
+        let restored = db.get(&existing.id).await.unwrap().unwrap();
+        assert_eq!(restored.updated_at, existing.updated_at);
+    }
+}
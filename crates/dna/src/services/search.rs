@@ -1,35 +1,433 @@
+use super::access::{self, Access, AccessPolicy};
+use super::glossary::{Glossary, DEFAULT_GLOSSARY_KIND};
+use super::language::LANGUAGE_LABEL;
+use super::snippet::{self, Snippet};
 use super::types::*;
-use crate::db::Database;
+use super::ServiceError;
+use crate::db::{model_slug, Database};
 use crate::embedding::EmbeddingProvider;
 use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 
+/// Candidates fetched from each vector search per requested result when fusing
+const FUSION_CANDIDATES: usize = 3;
+
 /// Service for semantic search operations
+#[derive(Clone)]
 pub struct SearchService {
     db: Arc<dyn Database>,
     embedding: Arc<dyn EmbeddingProvider>,
+    /// Models whose stored content embeddings can be searched instead
+    extra_models: Vec<Arc<dyn EmbeddingProvider>>,
+    /// Slug of the extra model searched, when `embedding` is one of them
+    model: Option<String>,
+    boosts: HashMap<String, f32>,
+    /// Models searched for artifacts in a given language
+    languages: LanguagesConfig,
+    /// Kind whose artifacts supply synonyms for query expansion
+    glossary_kind: String,
+    access: Option<Arc<dyn AccessPolicy>>,
 }
 
 impl SearchService {
     /// Create a new search service
     pub fn new(db: Arc<dyn Database>, embedding: Arc<dyn EmbeddingProvider>) -> Self {
-        Self { db, embedding }
+        Self {
+            db,
+            embedding,
+            extra_models: Vec::new(),
+            model: None,
+            boosts: HashMap::new(),
+            languages: LanguagesConfig::default(),
+            glossary_kind: DEFAULT_GLOSSARY_KIND.to_string(),
+            access: None,
+        }
+    }
+
+    /// Create a search service configured by a project's `config`.
+    ///
+    /// Extra models and access policy depend on the caller, so they are left
+    /// for the `with_*` methods.
+    pub fn from_config(
+        db: Arc<dyn Database>,
+        embedding: Arc<dyn EmbeddingProvider>,
+        config: &ProjectConfig,
+    ) -> Self {
+        Self::new(db, embedding)
+            .with_boosts(config.search.boosts.clone())
+            .with_languages(config.languages.clone())
+            .with_glossary_kind(config.lint.glossary_kind())
+    }
+
+    /// Models besides the configured one whose embeddings the store keeps,
+    /// for [`for_model`](Self::for_model)
+    pub fn with_extra_models(mut self, models: Vec<Arc<dyn EmbeddingProvider>>) -> Self {
+        self.extra_models = models;
+        self
+    }
+
+    /// This service searching the embeddings of `model`: the configured model
+    /// or one of the extra models.
+    ///
+    /// Extra models only embed content, so searches through them can't weigh
+    /// in context.
+    pub fn for_model(&self, model: &str) -> Result<Self, ServiceError> {
+        if model == self.embedding.model_id() {
+            return Ok(self.clone());
+        }
+        let Some(embedding) = self.extra_models.iter().find(|m| m.model_id() == model) else {
+            let mut configured = vec![self.embedding.model_id()];
+            configured.extend(self.extra_models.iter().map(|m| m.model_id()));
+            return Err(ServiceError::Validation(format!(
+                "Model '{}' is not configured (available: {})",
+                model,
+                configured.join(", ")
+            )));
+        };
+        Ok(Self {
+            embedding: Arc::clone(embedding),
+            model: Some(model_slug(model)),
+            ..self.clone()
+        })
+    }
+
+    /// Set default per-kind boosts, usually from the project's search config
+    pub fn with_boosts(mut self, boosts: HashMap<String, f32>) -> Self {
+        self.boosts = boosts;
+        self
+    }
+
+    /// Search the model routed to a language when a search filters on the
+    /// `language` label, e.g. a multilingual model for non-English artifacts
+    pub fn with_languages(mut self, languages: LanguagesConfig) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Expand queries with synonyms from artifacts of `kind` rather than
+    /// `glossary`
+    pub fn with_glossary_kind(mut self, kind: impl Into<String>) -> Self {
+        self.glossary_kind = kind.into();
+        self
+    }
+
+    /// This service searching the model routed to the language `filters`
+    /// ask for, unless a model was chosen with [`for_model`](Self::for_model)
+    fn routed(&self, filters: &SearchFilters) -> Result<Option<Self>, ServiceError> {
+        if self.model.is_some() {
+            return Ok(None);
+        }
+        match filters
+            .metadata
+            .get(LANGUAGE_LABEL)
+            .and_then(|language| self.languages.model_for(language))
+        {
+            Some(model) if model != self.embedding.model_id() => self.for_model(model).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Only find artifacts of kinds `policy` allows to be read
+    pub fn with_access_policy(mut self, policy: Arc<dyn AccessPolicy>) -> Self {
+        self.access = Some(policy);
+        self
+    }
+
+    /// Model whose embeddings the service searches
+    pub fn model_id(&self) -> &str {
+        self.embedding.model_id()
+    }
+
+    /// The service's default per-kind boosts
+    pub fn boosts(&self) -> &HashMap<String, f32> {
+        &self.boosts
+    }
+
+    /// The service's default boosts overridden by `boosts`
+    fn merged_boosts(&self, boosts: &HashMap<String, f32>) -> HashMap<String, f32> {
+        let mut merged = self.boosts.clone();
+        merged.extend(boosts.iter().map(|(k, v)| (k.clone(), *v)));
+        merged
+    }
+
+    /// `query` with the synonyms glossary artifacts give for the terms it
+    /// uses, as in `ICP churn (Ideal Customer Profile)`; see
+    /// [`Glossary::expand`]
+    pub async fn expand_query(&self, query: &str) -> Result<String> {
+        let filters = SearchFilters {
+            kind: Some(self.glossary_kind.clone()),
+            ..Default::default()
+        };
+        let filters = access::restrict(self.access.as_ref(), Access::Read, filters);
+        let glossary = self
+            .db
+            .list(filters)
+            .await
+            .context("Failed to load the glossary")?;
+        Ok(Glossary::new(&glossary).expand(query))
     }
 
     /// Perform semantic search
     pub async fn search(&self, query: &str, filters: SearchFilters) -> Result<Vec<SearchResult>> {
+        let routed = self.routed(&filters)?;
+        routed
+            .as_ref()
+            .unwrap_or(self)
+            .search_unrouted(query, filters)
+            .await
+    }
+
+    /// [`search`](Self::search) through this service's model
+    async fn search_unrouted(
+        &self,
+        query: &str,
+        filters: SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
         // Generate query embedding
         let query_embedding = self
             .embedding
             .embed(query)
             .await
             .context("Failed to generate query embedding")?;
+        let filters = access::restrict(self.access.as_ref(), Access::Read, filters);
 
         // Search in database
-        self.db
-            .search(&query_embedding, filters)
+        match &self.model {
+            Some(model) => self.db.search_model(model, &query_embedding, filters).await,
+            None => self.db.search(&query_embedding, filters).await,
+        }
+        .context("Failed to search database")
+    }
+
+    /// Semantic search ranking by a weighted blend of content and context similarity.
+    ///
+    /// Each artifact's score is the weighted mean of its content and context
+    /// scores, counting a score of zero where it was not among the candidates
+    /// of that search (including artifacts with no context). Content-only
+    /// weights fall back to [`search`](Self::search).
+    pub async fn search_weighted(
+        &self,
+        query: &str,
+        filters: SearchFilters,
+        weights: SearchWeights,
+    ) -> Result<Vec<SearchResult>> {
+        weights.validate()?;
+        let routed = self.routed(&filters)?;
+        routed
+            .as_ref()
+            .unwrap_or(self)
+            .search_weighted_unrouted(query, filters, weights)
+            .await
+    }
+
+    /// [`search_weighted`](Self::search_weighted) through this service's model
+    async fn search_weighted_unrouted(
+        &self,
+        query: &str,
+        filters: SearchFilters,
+        weights: SearchWeights,
+    ) -> Result<Vec<SearchResult>> {
+        if weights.is_content_only() {
+            return self.search_unrouted(query, filters).await;
+        }
+        if let Some(model) = &self.model {
+            return Err(ServiceError::Validation(format!(
+                "Only content embeddings are stored for {}; drop the context weight",
+                model
+            ))
+            .into());
+        }
+
+        let query_embedding = self
+            .embedding
+            .embed(query)
+            .await
+            .context("Failed to generate query embedding")?;
+
+        let limit = filters.limit.unwrap_or(10);
+        let candidates = SearchFilters {
+            limit: Some(limit * FUSION_CANDIDATES),
+            ..access::restrict(self.access.as_ref(), Access::Read, filters)
+        };
+
+        let content = if weights.content > 0.0 {
+            self.db
+                .search(&query_embedding, candidates.clone())
+                .await
+                .context("Failed to search database")?
+        } else {
+            Vec::new()
+        };
+        let context = self
+            .db
+            .search_context(&query_embedding, candidates)
+            .await
+            .context("Failed to search context embeddings")?;
+
+        let total = weights.content + weights.context;
+        let mut fused: HashMap<String, SearchResult> = HashMap::new();
+        for (results, weight) in [(content, weights.content), (context, weights.context)] {
+            for result in results {
+                let score = result.score * weight / total;
+                fused
+                    .entry(result.artifact.id.clone())
+                    .and_modify(|r| r.score += score)
+                    .or_insert(SearchResult {
+                        artifact: result.artifact,
+                        score,
+                    });
+            }
+        }
+
+        Ok(rank(fused.into_values().collect(), limit))
+    }
+
+    /// Weighted search with scores multiplied by a per-kind boost.
+    ///
+    /// `boosts` override the service's defaults for the kinds they name.
+    /// Boosting happens after retrieval, so extra candidates are fetched to
+    /// let boosted kinds overtake results that would otherwise fill the limit.
+    pub async fn search_boosted(
+        &self,
+        query: &str,
+        filters: SearchFilters,
+        weights: SearchWeights,
+        boosts: &HashMap<String, f32>,
+    ) -> Result<Vec<SearchResult>> {
+        let mut merged = self.merged_boosts(boosts);
+        validate_boosts(&merged)?;
+        merged.retain(|_, boost| *boost != 1.0);
+        if merged.is_empty() {
+            return self.search_weighted(query, filters, weights).await;
+        }
+
+        let limit = filters.limit.unwrap_or(10);
+        let candidates = SearchFilters {
+            limit: Some(limit * FUSION_CANDIDATES),
+            ..filters
+        };
+        let mut results = self.search_weighted(query, candidates, weights).await?;
+        for result in &mut results {
+            if let Some(boost) = merged.get(&result.artifact.kind) {
+                result.score *= boost;
+            }
+        }
+        Ok(rank(results, limit))
+    }
+
+    /// Why each result of a search scored as it did.
+    ///
+    /// Returns one explanation per result, in the same order; pass the
+    /// filters, weights and boosts the search ran with. Distances are
+    /// recomputed from the stored embeddings, so both vectors are reported
+    /// even where one was not among the candidates of its search and so
+    /// counted as zero in the fused score.
+    pub async fn explain(
+        &self,
+        query: &str,
+        results: &[SearchResult],
+        filters: &SearchFilters,
+        weights: SearchWeights,
+        boosts: &HashMap<String, f32>,
+    ) -> Result<Vec<SearchExplanation>> {
+        if results.is_empty() {
+            return Ok(Vec::new());
+        }
+        let routed = self.routed(filters)?;
+        let service = routed.as_ref().unwrap_or(self);
+
+        let query_embedding = service
+            .embedding
+            .embed(query)
             .await
-            .context("Failed to search database")
+            .context("Failed to generate query embedding")?;
+        let boosts = self.merged_boosts(boosts);
+
+        Ok(results
+            .iter()
+            .map(|result| {
+                let artifact = &result.artifact;
+                let (content, context) = match &service.model {
+                    Some(model) => (
+                        artifact.model_embeddings.get(model).map(Vec::as_slice),
+                        None,
+                    ),
+                    None => (
+                        artifact.embedding.as_deref(),
+                        artifact.context_embedding.as_deref(),
+                    ),
+                };
+                let content = content.map(|e| VectorMatch::between(&query_embedding, e));
+                let context = context.map(|e| VectorMatch::between(&query_embedding, e));
+                let contribution =
+                    |m: Option<VectorMatch>, weight: f32| m.map_or(0.0, |m| m.similarity * weight);
+                let matched = if contribution(context, weights.context)
+                    > contribution(content, weights.content)
+                {
+                    MatchedVector::Context
+                } else {
+                    MatchedVector::Content
+                };
+
+                SearchExplanation {
+                    matched,
+                    content,
+                    context,
+                    weights,
+                    boost: boosts.get(&artifact.kind).copied().unwrap_or(1.0),
+                    filters: matched_filters(artifact, filters),
+                }
+            })
+            .collect())
+    }
+
+    /// The `count` sentences of each result's content closest to the query.
+    ///
+    /// Returns one list per result, in the same order. All sentences are
+    /// embedded in a single batch; see [`snippet::MAX_SENTENCES`].
+    pub async fn snippets(
+        &self,
+        query: &str,
+        results: &[SearchResult],
+        count: usize,
+    ) -> Result<Vec<Vec<Snippet>>> {
+        let per_result: Vec<Vec<String>> = results
+            .iter()
+            .map(|r| {
+                let mut sentences = snippet::split_sentences(&r.artifact.content);
+                sentences.truncate(snippet::MAX_SENTENCES);
+                sentences
+            })
+            .collect();
+        let texts: Vec<&str> = per_result.iter().flatten().map(String::as_str).collect();
+        if texts.is_empty() {
+            return Ok(vec![Vec::new(); results.len()]);
+        }
+
+        let query_embedding = self
+            .embedding
+            .embed(query)
+            .await
+            .context("Failed to generate query embedding")?;
+        let embeddings = self
+            .embedding
+            .embed_batch(&texts)
+            .await
+            .context("Failed to embed snippet sentences")?;
+
+        let mut embeddings = embeddings.into_iter();
+        Ok(per_result
+            .into_iter()
+            .map(|sentences| {
+                let scores: Vec<f32> = embeddings
+                    .by_ref()
+                    .take(sentences.len())
+                    .map(|e| snippet::cosine_similarity(&query_embedding, &e))
+                    .collect();
+                snippet::select(sentences, &scores, count)
+            })
+            .collect())
     }
 
     /// Check if artifacts have mixed embedding models
@@ -47,6 +445,53 @@ impl SearchService {
     }
 }
 
+/// The filters `artifact` satisfies, keyed as in [`SearchExplanation::filters`]
+fn matched_filters(artifact: &Artifact, filters: &SearchFilters) -> BTreeMap<String, String> {
+    let mut matched = BTreeMap::new();
+    if filters.kind.as_deref() == Some(artifact.kind.as_str()) {
+        matched.insert("kind".to_string(), artifact.kind.clone());
+    }
+    for (prefix, wanted, actual) in [
+        ("metadata", &filters.metadata, &artifact.metadata),
+        ("fields", &filters.fields, &artifact.fields),
+    ] {
+        for (key, value) in wanted {
+            if actual.get(key) == Some(value) {
+                matched.insert(format!("{}.{}", prefix, key), value.clone());
+            }
+        }
+    }
+    if !filters.kind_not.is_empty() && !filters.kind_not.contains(&artifact.kind) {
+        matched.insert("kind_not".to_string(), filters.kind_not.join(","));
+    }
+    for (key, value) in &filters.metadata_not {
+        if artifact.metadata.get(key) != Some(value) {
+            matched.insert(format!("metadata_not.{}", key), value.clone());
+        }
+    }
+    if let Some(after) = filters.after.filter(|after| artifact.updated_at >= *after) {
+        matched.insert("after".to_string(), after.to_rfc3339());
+    }
+    if let Some(before) = filters
+        .before
+        .filter(|before| artifact.updated_at < *before)
+    {
+        matched.insert("before".to_string(), before.to_rfc3339());
+    }
+    matched
+}
+
+/// Sort by descending score, breaking ties by id, and keep the top `limit`
+fn rank(mut results: Vec<SearchResult>, limit: usize) -> Vec<SearchResult> {
+    results.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.artifact.id.cmp(&b.artifact.id))
+    });
+    results.truncate(limit);
+    results
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -91,6 +536,7 @@ mod tests {
     struct TestDatabase {
         artifacts: Mutex<Vec<Artifact>>,
         search_results: Mutex<Vec<SearchResult>>,
+        context_results: Mutex<Vec<SearchResult>>,
     }
 
     impl TestDatabase {
@@ -98,6 +544,7 @@ mod tests {
             Self {
                 artifacts: Mutex::new(vec![]),
                 search_results: Mutex::new(vec![]),
+                context_results: Mutex::new(vec![]),
             }
         }
 
@@ -105,6 +552,7 @@ mod tests {
             Self {
                 artifacts: Mutex::new(artifacts),
                 search_results: Mutex::new(vec![]),
+                context_results: Mutex::new(vec![]),
             }
         }
 
@@ -112,6 +560,15 @@ mod tests {
             Self {
                 artifacts: Mutex::new(vec![]),
                 search_results: Mutex::new(results),
+                context_results: Mutex::new(vec![]),
+            }
+        }
+
+        fn with_fusion_results(content: Vec<SearchResult>, context: Vec<SearchResult>) -> Self {
+            Self {
+                artifacts: Mutex::new(vec![]),
+                search_results: Mutex::new(content),
+                context_results: Mutex::new(context),
             }
         }
     }
@@ -153,6 +610,14 @@ mod tests {
             Ok(self.search_results.lock().unwrap().clone())
         }
 
+        async fn search_context(
+            &self,
+            _query_embedding: &[f32],
+            _filters: SearchFilters,
+        ) -> Result<Vec<SearchResult>> {
+            Ok(self.context_results.lock().unwrap().clone())
+        }
+
         async fn version(&self) -> Result<u64> {
             Ok(1)
         }
@@ -197,6 +662,131 @@ mod tests {
         assert!(results.is_empty());
     }
 
+    #[tokio::test]
+    async fn for_model_searches_an_extra_models_embeddings() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let primary_only = Artifact::new(
+            "intent".to_string(),
+            "primary only".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "primary".to_string(),
+        );
+        let mut both = primary_only.clone();
+        both.id = "both".to_string();
+        both.model_embeddings
+            .insert("nomic_embed_text".to_string(), vec![0.4, 0.5, 0.6]);
+        db.insert(&primary_only).await.unwrap();
+        db.insert(&both).await.unwrap();
+
+        let service = SearchService::new(
+            db,
+            Arc::new(TestEmbedding::new("primary", vec![0.1, 0.2, 0.3])),
+        )
+        .with_extra_models(vec![Arc::new(TestEmbedding::new(
+            "nomic-embed-text",
+            vec![0.4, 0.5, 0.6],
+        ))]);
+        let all = service.search("q", SearchFilters::default()).await.unwrap();
+        assert_eq!(all.len(), 2);
+
+        let extra = service.for_model("nomic-embed-text").unwrap();
+        let results = extra.search("q", SearchFilters::default()).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].artifact.id, "both");
+        let weights = SearchWeights {
+            content: 0.5,
+            context: 0.5,
+        };
+        assert!(extra
+            .search_weighted("q", SearchFilters::default(), weights)
+            .await
+            .is_err());
+        assert!(service.for_model("primary").is_ok());
+        assert!(matches!(
+            service.for_model("missing"),
+            Err(ServiceError::Validation(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn language_filters_search_the_routed_model() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let mut german = Artifact::new(
+            "intent".to_string(),
+            "Bestellungen werden nach der Zahlung versendet".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::from([(LANGUAGE_LABEL.to_string(), "deu".to_string())]),
+            "primary".to_string(),
+        );
+        let mut french = german.clone();
+        french.id = "french".to_string();
+        french
+            .metadata
+            .insert(LANGUAGE_LABEL.to_string(), "fra".to_string());
+        german
+            .model_embeddings
+            .insert("multilingual".to_string(), vec![0.4]);
+        db.insert(&german).await.unwrap();
+        db.insert(&french).await.unwrap();
+
+        let service = SearchService::new(db, Arc::new(TestEmbedding::new("primary", vec![0.1])))
+            .with_extra_models(vec![Arc::new(TestEmbedding::new(
+                "multilingual",
+                vec![0.4],
+            ))])
+            .with_languages(LanguagesConfig {
+                models: BTreeMap::from([("deu".to_string(), "multilingual".to_string())]),
+                ..Default::default()
+            });
+        let in_language = |language: &str| SearchFilters {
+            metadata: HashMap::from([(LANGUAGE_LABEL.to_string(), language.to_string())]),
+            ..Default::default()
+        };
+
+        let results = service.search("Zahlung", in_language("deu")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].score, 0.8, "scored by the routed model");
+
+        let results = service
+            .search("paiement", in_language("fra"))
+            .await
+            .unwrap();
+        assert_eq!(results[0].artifact.id, "french");
+        assert_eq!(results[0].score, 0.85, "scored by the configured model");
+    }
+
+    #[tokio::test]
+    async fn queries_expand_with_synonyms_from_the_glossary_kind() {
+        let db = Arc::new(crate::testing::TestDatabase::new());
+        let term = |kind: &str, content: &str| {
+            Artifact::new(
+                kind.to_string(),
+                content.to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                "model".to_string(),
+            )
+        };
+        db.insert(&term("terms", "ICP: Ideal Customer Profile"))
+            .await
+            .unwrap();
+        db.insert(&term("intent", "NRR: Net Revenue Retention"))
+            .await
+            .unwrap();
+
+        let service = SearchService::new(db, Arc::new(TestEmbedding::new("model", vec![0.1])))
+            .with_glossary_kind("terms");
+        assert_eq!(
+            service.expand_query("ICP and NRR").await.unwrap(),
+            "ICP and NRR (Ideal Customer Profile)"
+        );
+        assert_eq!(service.expand_query("churn").await.unwrap(), "churn");
+    }
+
     #[tokio::test]
     async fn search_returns_results_from_db() {
         let artifact = Artifact::new(
@@ -226,6 +816,253 @@ mod tests {
         assert_eq!(results[0].score, 0.95);
     }
 
+    /// Embeds text by whether it mentions "token", so similarity is predictable
+    struct KeywordEmbedding;
+
+    #[async_trait::async_trait]
+    impl EmbeddingProvider for KeywordEmbedding {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            Ok(vec![if text.contains("token") { 1.0 } else { 0.0 }, 0.1])
+        }
+
+        async fn embed_batch(&self, texts: &[&str]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::new();
+            for text in texts {
+                embeddings.push(self.embed(text).await?);
+            }
+            Ok(embeddings)
+        }
+
+        fn model_id(&self) -> &str {
+            "keyword"
+        }
+
+        fn dimensions(&self) -> usize {
+            2
+        }
+    }
+
+    #[tokio::test]
+    async fn snippets_pick_sentences_closest_to_query() {
+        let mut hit = scored("a", 0.9);
+        hit.artifact.content =
+            "Sessions use cookies. Each token expires after an hour. Logs are kept.".to_string();
+        let empty = SearchResult {
+            artifact: Artifact::new(
+                "intent".to_string(),
+                "---".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                "keyword".to_string(),
+            ),
+            score: 0.5,
+        };
+
+        let service = SearchService::new(Arc::new(TestDatabase::new()), Arc::new(KeywordEmbedding));
+        let snippets = service
+            .snippets("token lifetime", &[hit, empty], 1)
+            .await
+            .unwrap();
+
+        assert_eq!(snippets.len(), 2);
+        assert_eq!(snippets[0].len(), 1);
+        assert_eq!(snippets[0][0].text, "Each token expires after an hour.");
+        assert!(snippets[1].is_empty());
+    }
+
+    fn scored(id: &str, score: f32) -> SearchResult {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            format!("{} content", id),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "model".to_string(),
+        );
+        artifact.id = id.to_string();
+        SearchResult { artifact, score }
+    }
+
+    #[tokio::test]
+    async fn search_weighted_blends_content_and_context_scores() {
+        let db = Arc::new(TestDatabase::with_fusion_results(
+            vec![scored("a", 0.9), scored("b", 0.5)],
+            vec![scored("b", 1.0), scored("c", 0.8)],
+        ));
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1, 0.2, 0.3]));
+        let service = SearchService::new(db, embedding);
+
+        let weights: SearchWeights = "content=0.5,context=0.5".parse().unwrap();
+        let results = service
+            .search_weighted("query", SearchFilters::default(), weights)
+            .await
+            .unwrap();
+
+        let ranked: Vec<(&str, f32)> = results
+            .iter()
+            .map(|r| (r.artifact.id.as_str(), r.score))
+            .collect();
+        assert_eq!(ranked, vec![("b", 0.75), ("a", 0.45), ("c", 0.4)]);
+    }
+
+    #[tokio::test]
+    async fn search_weighted_respects_limit_and_content_only_weights() {
+        let db = Arc::new(TestDatabase::with_fusion_results(
+            vec![scored("a", 0.9)],
+            vec![scored("b", 1.0), scored("c", 0.8)],
+        ));
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1, 0.2, 0.3]));
+        let service = SearchService::new(db, embedding);
+
+        let context_only = SearchWeights {
+            content: 0.0,
+            context: 1.0,
+        };
+        let filters = SearchFilters {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let results = service
+            .search_weighted("query", filters, context_only)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].artifact.id, "b");
+
+        let results = service
+            .search_weighted("query", SearchFilters::default(), SearchWeights::default())
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].artifact.id, "a");
+    }
+
+    #[test]
+    fn weights_parse_and_validate() {
+        let weights: SearchWeights = "content=0.7, context=0.3".parse().unwrap();
+        assert_eq!(weights.content, 0.7);
+        assert_eq!(weights.context, 0.3);
+
+        let context_only: SearchWeights = "context=1".parse().unwrap();
+        assert_eq!(context_only.content, 0.0);
+
+        assert!("content=0,context=0".parse::<SearchWeights>().is_err());
+        assert!("content=-1,context=2".parse::<SearchWeights>().is_err());
+        assert!("title=1".parse::<SearchWeights>().is_err());
+        assert!("content".parse::<SearchWeights>().is_err());
+    }
+
+    #[tokio::test]
+    async fn search_boosted_reranks_by_kind() {
+        let mut invariant = scored("b", 0.6);
+        invariant.artifact.kind = "invariant".to_string();
+        let db = Arc::new(TestDatabase::with_search_results(vec![
+            scored("a", 0.9),
+            invariant,
+        ]));
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![0.1, 0.2, 0.3]));
+        let service = SearchService::new(db, embedding)
+            .with_boosts(HashMap::from([("invariant".to_string(), 2.0)]));
+
+        let filters = SearchFilters {
+            limit: Some(1),
+            ..Default::default()
+        };
+        let results = service
+            .search_boosted(
+                "query",
+                filters.clone(),
+                SearchWeights::default(),
+                &HashMap::new(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].artifact.id, "b");
+        assert_eq!(results[0].score, 1.2);
+
+        let overrides = HashMap::from([("invariant".to_string(), 1.0)]);
+        let results = service
+            .search_boosted("query", filters, SearchWeights::default(), &overrides)
+            .await
+            .unwrap();
+        assert_eq!(results[0].artifact.id, "a");
+    }
+
+    #[test]
+    fn kind_boosts_parse_and_validate() {
+        assert_eq!(
+            parse_kind_boost("invariant=2.0").unwrap(),
+            ("invariant".to_string(), 2.0)
+        );
+        assert!(parse_kind_boost("invariant").is_err());
+        assert!(parse_kind_boost("=2").is_err());
+        assert!(parse_kind_boost("invariant=-1").is_err());
+        assert!(parse_kind_boost("invariant=fast").is_err());
+    }
+
+    #[tokio::test]
+    async fn explain_reports_vectors_boost_and_filters() {
+        let mut result = scored("a", 0.8);
+        result.artifact.kind = "invariant".to_string();
+        result.artifact.embedding = Some(vec![1.0, 2.0]);
+        result.artifact.context_embedding = Some(vec![1.0, 0.0]);
+        result
+            .artifact
+            .metadata
+            .insert("owner".to_string(), "auth".to_string());
+        let mut plain = scored("b", 0.1);
+        plain.artifact.embedding = Some(vec![1.0, 0.0]);
+
+        let db = Arc::new(TestDatabase::new());
+        let embedding = Arc::new(TestEmbedding::new("test-model", vec![1.0, 0.0]));
+        let service = SearchService::new(db, embedding)
+            .with_boosts(HashMap::from([("invariant".to_string(), 2.0)]));
+
+        let filters = SearchFilters {
+            kind: Some("invariant".to_string()),
+            metadata: HashMap::from([("owner".to_string(), "auth".to_string())]),
+            ..Default::default()
+        };
+        let weights: SearchWeights = "content=0.5,context=0.5".parse().unwrap();
+        let explanations = service
+            .explain(
+                "query",
+                &[result, plain],
+                &filters,
+                weights,
+                &HashMap::new(),
+            )
+            .await
+            .unwrap();
+
+        let first = &explanations[0];
+        assert_eq!(first.matched, MatchedVector::Context);
+        assert_eq!(
+            first.content,
+            Some(VectorMatch {
+                distance: 4.0,
+                similarity: 0.2
+            })
+        );
+        assert_eq!(first.context.map(|m| m.distance), Some(0.0));
+        assert_eq!(first.boost, 2.0);
+        assert_eq!(
+            first.filters,
+            BTreeMap::from([
+                ("kind".to_string(), "invariant".to_string()),
+                ("metadata.owner".to_string(), "auth".to_string()),
+            ])
+        );
+
+        let second = &explanations[1];
+        assert_eq!(second.matched, MatchedVector::Content);
+        assert_eq!(second.context, None);
+        assert_eq!(second.boost, 1.0);
+        assert!(second.filters.is_empty());
+    }
+
     #[tokio::test]
     async fn check_embedding_consistency_returns_mismatched_ids() {
         let mut artifact1 = Artifact::new(
@@ -0,0 +1,309 @@
+//! Signed artifacts.
+//!
+//! With a key configured in `[signing]`, every write signs the artifact's
+//! ID, kind, content and context with ed25519 and records the signature and
+//! the signer's identity as labels. Anyone holding the signer's public key
+//! can then tell whether an artifact still reads as it was signed, which
+//! matters once several people and agents write to the same store.
+
+use super::types::{Artifact, SearchResult, SigningConfig};
+use super::ServiceError;
+use anyhow::{Context, Result};
+use ed25519_dalek::{Signature, Signer as _, SigningKey, Verifier as _, VerifyingKey};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Label holding the hex signature of an artifact
+pub const SIGNATURE_LABEL: &str = "signature";
+
+/// Label holding the identity of whoever signed an artifact
+pub const SIGNED_BY_LABEL: &str = "signed_by";
+
+/// Candidates fetched per requested result when only verified ones are kept
+pub const VERIFIED_CANDIDATES: usize = 5;
+
+/// Prefix of every signed message, so a signature can't be replayed elsewhere
+const DOMAIN: &[u8] = b"dna-artifact-v2\n";
+
+/// The bytes a signature covers: ID, kind, content and context. The ID binds
+/// the signature to one artifact, so it can't be copied onto another with
+/// the same text
+fn signed_message(artifact: &Artifact) -> Vec<u8> {
+    let mut message = DOMAIN.to_vec();
+    for part in [
+        artifact.id.as_str(),
+        artifact.kind.as_str(),
+        artifact.content.as_str(),
+        artifact.context.as_deref().unwrap_or_default(),
+    ] {
+        message.extend_from_slice(&(part.len() as u64).to_be_bytes());
+        message.extend_from_slice(part.as_bytes());
+    }
+    message
+}
+
+/// Signs artifacts with one writer's key
+#[derive(Clone)]
+pub struct Signer {
+    identity: String,
+    key: SigningKey,
+}
+
+impl Signer {
+    /// A signer from a hex private key
+    pub fn new(identity: impl Into<String>, key_hex: &str) -> Result<Self, ServiceError> {
+        let identity = identity.into();
+        if identity.trim().is_empty() {
+            return Err(ServiceError::Validation(
+                "Signer identity cannot be empty".to_string(),
+            ));
+        }
+        let seed: [u8; 32] = from_hex(key_hex.trim())
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| {
+                ServiceError::Validation("Signing key must be 64 hex characters".to_string())
+            })?;
+        Ok(Self {
+            identity,
+            key: SigningKey::from_bytes(&seed),
+        })
+    }
+
+    /// The signer `[signing]` configures, reading the key from `key_env` or
+    /// else `key_path` (relative to `project_root`); `None` when neither is set
+    pub fn from_config(config: &SigningConfig, project_root: &Path) -> Result<Option<Self>> {
+        let key = if let Some(var) = &config.key_env {
+            std::env::var(var).with_context(|| format!("{} is not set", var))?
+        } else if let Some(path) = &config.key_path {
+            let path = project_root.join(path);
+            std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read signing key {}", path.display()))?
+        } else {
+            return Ok(None);
+        };
+        let identity = config.signer.clone().ok_or_else(|| {
+            ServiceError::Validation("[signing] needs a signer identity".to_string())
+        })?;
+        Ok(Some(Self::new(identity, &key)?))
+    }
+
+    /// A new random private key, hex
+    pub fn generate_key() -> String {
+        to_hex(&SigningKey::generate(&mut rand_core::OsRng).to_bytes())
+    }
+
+    /// Identity recorded with each signature
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    /// Public key others verify this signer's signatures with, hex
+    pub fn public_key(&self) -> String {
+        to_hex(self.key.verifying_key().as_bytes())
+    }
+
+    /// Sign `artifact` as it is now, replacing any earlier signature
+    pub fn sign(&self, artifact: &mut Artifact) {
+        let signature = self.key.sign(&signed_message(artifact));
+        artifact
+            .metadata
+            .insert(SIGNATURE_LABEL.to_string(), to_hex(&signature.to_bytes()));
+        artifact
+            .metadata
+            .insert(SIGNED_BY_LABEL.to_string(), self.identity.clone());
+    }
+}
+
+/// Whether an artifact's signature holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum Verification {
+    /// Signed by a trusted signer and unchanged since
+    Verified { signer: String },
+    /// No signature
+    Unsigned,
+    /// Signed by someone with no trusted public key
+    UnknownSigner { signer: String },
+    /// The signature does not match the artifact: it changed after signing,
+    /// or the signature was forged
+    Invalid { signer: String },
+}
+
+impl Verification {
+    pub fn is_verified(&self) -> bool {
+        matches!(self, Self::Verified { .. })
+    }
+}
+
+impl std::fmt::Display for Verification {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Verified { signer } => write!(f, "verified, signed by {}", signer),
+            Self::Unsigned => write!(f, "unsigned"),
+            Self::UnknownSigner { signer } => write!(f, "signed by unknown signer {}", signer),
+            Self::Invalid { signer } => {
+                write!(
+                    f,
+                    "invalid signature from {}: changed since signing",
+                    signer
+                )
+            },
+        }
+    }
+}
+
+/// Checks signatures against the public keys of trusted signers
+#[derive(Debug, Clone, Default)]
+pub struct Verifier {
+    trusted: HashMap<String, VerifyingKey>,
+}
+
+impl Verifier {
+    /// Trust the `[signing] trusted` keys, and the configured signer's own
+    pub fn from_config(config: &SigningConfig, signer: Option<&Signer>) -> Result<Self> {
+        let mut verifier = Self::default();
+        for (identity, key) in &config.trusted {
+            verifier = verifier.trust(identity, key)?;
+        }
+        if let Some(signer) = signer {
+            verifier
+                .trusted
+                .insert(signer.identity.clone(), signer.key.verifying_key());
+        }
+        Ok(verifier)
+    }
+
+    /// Also trust signatures by `identity` made with `public_key` (hex)
+    pub fn trust(mut self, identity: &str, public_key: &str) -> Result<Self, ServiceError> {
+        let key = from_hex(public_key.trim())
+            .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+            .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok())
+            .ok_or_else(|| {
+                ServiceError::Validation(format!(
+                    "Public key for signer '{}' must be 64 hex characters",
+                    identity
+                ))
+            })?;
+        self.trusted.insert(identity.to_string(), key);
+        Ok(self)
+    }
+
+    /// Check `artifact`'s signature
+    pub fn verify(&self, artifact: &Artifact) -> Verification {
+        let (Some(signature), Some(signer)) = (
+            artifact.metadata.get(SIGNATURE_LABEL),
+            artifact.metadata.get(SIGNED_BY_LABEL),
+        ) else {
+            return Verification::Unsigned;
+        };
+        let signer = signer.clone();
+        let Some(key) = self.trusted.get(&signer) else {
+            return Verification::UnknownSigner { signer };
+        };
+        let signature = from_hex(signature)
+            .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+            .map(|bytes| Signature::from_bytes(&bytes));
+        match signature {
+            Some(signature) if key.verify(&signed_message(artifact), &signature).is_ok() => {
+                Verification::Verified { signer }
+            },
+            _ => Verification::Invalid { signer },
+        }
+    }
+
+    /// Keep the first `limit` results whose signatures verify
+    pub fn retain_verified(&self, results: &mut Vec<SearchResult>, limit: usize) {
+        results.retain(|result| self.verify(&result.artifact).is_verified());
+        results.truncate(limit);
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+
+    fn artifact(content: &str) -> Artifact {
+        Artifact::new(
+            "invariant".to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "test".to_string(),
+        )
+    }
+
+    #[test]
+    fn signatures_verify_until_the_artifact_changes() {
+        let signer = Signer::new("alice", &Signer::generate_key()).unwrap();
+        let verifier = Verifier::default()
+            .trust("alice", &signer.public_key())
+            .unwrap();
+        let mut signed = artifact("Orders ship after payment");
+        signer.sign(&mut signed);
+
+        assert_eq!(
+            verifier.verify(&signed),
+            Verification::Verified {
+                signer: "alice".to_string()
+            }
+        );
+        assert_eq!(verifier.verify(&artifact("x")), Verification::Unsigned);
+        assert!(matches!(
+            Verifier::default().verify(&signed),
+            Verification::UnknownSigner { .. }
+        ));
+
+        let mut tampered = signed.clone();
+        tampered.content = "Orders ship before payment".to_string();
+        assert!(matches!(
+            verifier.verify(&tampered),
+            Verification::Invalid { .. }
+        ));
+        let mut relabeled = signed.clone();
+        relabeled.kind = "boundary".to_string();
+        assert!(!verifier.verify(&relabeled).is_verified());
+    }
+
+    #[test]
+    fn signatures_copied_to_another_artifact_are_invalid() {
+        let signer = Signer::new("alice", &Signer::generate_key()).unwrap();
+        let verifier = Verifier::default()
+            .trust("alice", &signer.public_key())
+            .unwrap();
+        let mut signed = artifact("Orders ship after payment");
+        signer.sign(&mut signed);
+
+        let mut copy = artifact("Orders ship after payment");
+        assert_ne!(copy.id, signed.id);
+        copy.metadata = signed.metadata.clone();
+        assert!(matches!(
+            verifier.verify(&copy),
+            Verification::Invalid { .. }
+        ));
+    }
+
+    #[test]
+    fn rejects_malformed_keys() {
+        assert!(Signer::new("alice", "abc").is_err());
+        assert!(Signer::new(" ", &Signer::generate_key()).is_err());
+        assert!(Verifier::default().trust("bob", "zz").is_err());
+        assert_eq!(from_hex(&to_hex(&[0, 15, 255])), Some(vec![0, 15, 255]));
+    }
+}
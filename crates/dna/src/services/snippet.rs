@@ -0,0 +1,111 @@
+//! Query-relevant sentences from search hits.
+//!
+//! Content is split into sentences, each sentence is embedded, and the ones
+//! closest to the query are kept, so a hit can be shown to a person (or sent
+//! to an agent) without its full content.
+
+use serde::Serialize;
+
+/// Sentences considered per artifact, bounding the embedding work on long content
+pub const MAX_SENTENCES: usize = 64;
+
+/// A sentence from an artifact and its similarity to the query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snippet {
+    pub text: String,
+    pub score: f32,
+}
+
+/// Split text into sentences.
+///
+/// Lines are split at `.`, `!` or `?` followed by whitespace. Markdown
+/// heading, list and quote markers are dropped, as are empty pieces.
+pub fn split_sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    for line in text.lines() {
+        let line = line.trim_start_matches(['#', '-', '*', '>', ' ', '\t']);
+        let mut start = 0;
+        let mut chars = line.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            let at_break = matches!(c, '.' | '!' | '?')
+                && chars.peek().is_none_or(|(_, next)| next.is_whitespace());
+            if at_break {
+                push_sentence(&mut sentences, &line[start..i + c.len_utf8()]);
+                start = i + c.len_utf8();
+            }
+        }
+        push_sentence(&mut sentences, &line[start..]);
+    }
+    sentences
+}
+
+fn push_sentence(sentences: &mut Vec<String>, piece: &str) {
+    let piece = piece.trim();
+    if piece.chars().any(char::is_alphanumeric) {
+        sentences.push(piece.to_string());
+    }
+}
+
+/// Cosine similarity of two vectors, 0.0 when either is all zeros
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Keep the `count` best-scoring sentences, in the order they appear
+pub fn select(sentences: Vec<String>, scores: &[f32], count: usize) -> Vec<Snippet> {
+    let mut ranked: Vec<usize> = (0..sentences.len().min(scores.len())).collect();
+    ranked.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]).then(a.cmp(&b)));
+    ranked.truncate(count);
+    ranked.sort_unstable();
+
+    let mut sentences: Vec<Option<String>> = sentences.into_iter().map(Some).collect();
+    ranked
+        .into_iter()
+        .filter_map(|i| {
+            sentences[i].take().map(|text| Snippet {
+                text,
+                score: scores[i],
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_sentences_and_strips_markdown_markers() {
+        let text = "# Login\n\nUsers sign in with SSO. Passwords are hashed!\n- Sessions last 1.5 hours\n---";
+        assert_eq!(
+            split_sentences(text),
+            vec![
+                "Login",
+                "Users sign in with SSO.",
+                "Passwords are hashed!",
+                "Sessions last 1.5 hours",
+            ]
+        );
+    }
+
+    #[test]
+    fn selects_best_sentences_in_document_order() {
+        let sentences = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let snippets = select(sentences, &[0.9, 0.1, 0.95], 2);
+        let texts: Vec<_> = snippets.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, vec!["a", "c"]);
+    }
+
+    #[test]
+    fn cosine_similarity_handles_zero_vectors() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 0.0]), 0.0);
+        assert!((cosine_similarity(&[1.0, 1.0], &[2.0, 2.0]) - 1.0).abs() < 1e-6);
+    }
+}
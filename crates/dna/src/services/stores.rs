@@ -0,0 +1,257 @@
+//! Finding the store to act on, and named stores in the user's global
+//! configuration.
+//!
+//! Commands act on the nearest `.dna` store at or above the current
+//! directory unless told otherwise. Registering stores under names lets `--store <name>` target
+//! any project from anywhere, and `dna store use` picks the store commands
+//! fall back to outside a project.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Environment variable naming the global config file, in place of the
+/// platform default
+pub const GLOBAL_CONFIG_ENV: &str = "DNA_GLOBAL_CONFIG";
+
+/// Environment variable naming the store to use, as a project or `.dna`
+/// directory, in place of discovery from the current directory
+pub const DNA_DIR_ENV: &str = "DNA_DIR";
+
+/// Contents of the global config file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GlobalConfig {
+    /// Store commands use outside a project, by name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+    /// Project directories by store name
+    #[serde(default)]
+    pub stores: BTreeMap<String, PathBuf>,
+}
+
+/// Reads and writes the named stores of the global config file
+pub struct StoreRegistry {
+    config_path: PathBuf,
+}
+
+impl StoreRegistry {
+    /// Registry kept in the file at `config_path`
+    pub fn new(config_path: &Path) -> Self {
+        Self {
+            config_path: config_path.to_path_buf(),
+        }
+    }
+
+    /// Registry in `$DNA_GLOBAL_CONFIG`, else `dna/config.toml` in the
+    /// platform config directory (`~/.config` on Linux)
+    pub fn from_env() -> Result<Self> {
+        if let Some(path) = std::env::var_os(GLOBAL_CONFIG_ENV) {
+            return Ok(Self::new(Path::new(&path)));
+        }
+        let dir = dirs::config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not find a config directory for named stores"))?;
+        Ok(Self::new(&dir.join("dna").join("config.toml")))
+    }
+
+    /// Path of the global config file
+    pub fn path(&self) -> &Path {
+        &self.config_path
+    }
+
+    /// Load the global config; a missing file has no stores
+    pub fn load(&self) -> Result<GlobalConfig> {
+        if !self.config_path.exists() {
+            return Ok(GlobalConfig::default());
+        }
+        let content = std::fs::read_to_string(&self.config_path)
+            .with_context(|| format!("Failed to read {}", self.config_path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Invalid global config {}", self.config_path.display()))
+    }
+
+    /// Save the global config
+    pub fn save(&self, config: &GlobalConfig) -> Result<()> {
+        let content =
+            toml::to_string_pretty(config).context("Failed to serialize global config")?;
+        if let Some(parent) = self.config_path.parent() {
+            std::fs::create_dir_all(parent).context("Failed to create config directory")?;
+        }
+        std::fs::write(&self.config_path, content).context("Failed to write global config")?;
+        Ok(())
+    }
+
+    /// Register the project at `path` as `name`, replacing any store of that
+    /// name. Returns the project directory stored.
+    pub fn add(&self, name: &str, path: &Path) -> Result<PathBuf> {
+        if name.is_empty() || name.contains(['/', '\\']) {
+            anyhow::bail!("Invalid store name '{}': names cannot contain '/'", name);
+        }
+        let root = project_root(path);
+        if !is_store(&root) {
+            anyhow::bail!(
+                "No DNA store at {}. Run 'dna init' there first.",
+                root.display()
+            );
+        }
+        let root = root
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve {}", root.display()))?;
+
+        let mut config = self.load()?;
+        config.stores.insert(name.to_string(), root.clone());
+        self.save(&config)?;
+        Ok(root)
+    }
+
+    /// Forget a named store, and stop using it if it was current
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let mut config = self.load()?;
+        let removed = config.stores.remove(name).is_some();
+        if removed {
+            if config.current.as_deref() == Some(name) {
+                config.current = None;
+            }
+            self.save(&config)?;
+        }
+        Ok(removed)
+    }
+
+    /// Make a named store current, or clear the current store with `None`
+    pub fn use_store(&self, name: Option<&str>) -> Result<()> {
+        let mut config = self.load()?;
+        if let Some(name) = name {
+            if !config.stores.contains_key(name) {
+                anyhow::bail!("{}", unknown_store(name, &config));
+            }
+        }
+        config.current = name.map(str::to_string);
+        self.save(&config)
+    }
+
+    /// Project directory of a `--store` value: a registered name, else a path
+    /// to a project or its `.dna` directory
+    pub fn resolve(&self, store: &str) -> Result<PathBuf> {
+        let config = self.load()?;
+        if let Some(root) = config.stores.get(store) {
+            return Ok(root.clone());
+        }
+        let root = project_root(Path::new(store));
+        if is_store(&root) {
+            return Ok(root);
+        }
+        anyhow::bail!("{}", unknown_store(store, &config))
+    }
+
+    /// Project directory of the current store, if one is set
+    pub fn current(&self) -> Result<Option<PathBuf>> {
+        let config = self.load()?;
+        match &config.current {
+            Some(name) => match config.stores.get(name) {
+                Some(root) => Ok(Some(root.clone())),
+                None => anyhow::bail!("{}", unknown_store(name, &config)),
+            },
+            None => Ok(None),
+        }
+    }
+}
+
+/// Whether `root` holds an initialized store
+pub fn is_store(root: &Path) -> bool {
+    root.join(".dna").join("config.toml").exists()
+}
+
+/// The nearest directory holding a store: `start` or one of its parents,
+/// as git finds `.git`
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .find(|dir| is_store(dir))
+        .map(Path::to_path_buf)
+}
+
+/// The project directory, when `path` names its `.dna` directory instead
+pub fn project_root(path: &Path) -> PathBuf {
+    match path.file_name() {
+        Some(name) if name == ".dna" => path.parent().unwrap_or(path).to_path_buf(),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn unknown_store(store: &str, config: &GlobalConfig) -> String {
+    let names: Vec<&str> = config.stores.keys().map(String::as_str).collect();
+    if names.is_empty() {
+        format!(
+            "Unknown store '{}': not a registered name or a directory with a DNA store",
+            store
+        )
+    } else {
+        format!(
+            "Unknown store '{}': not a registered name ({}) or a directory with a DNA store",
+            store,
+            names.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ConfigService;
+    use tempfile::TempDir;
+
+    fn project(dir: &TempDir, name: &str) -> PathBuf {
+        let root = dir.path().join(name);
+        ConfigService::new(&root).init().unwrap();
+        root.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn named_stores_resolve_by_name_or_path() {
+        let dir = TempDir::new().unwrap();
+        let app = project(&dir, "app");
+        let registry = StoreRegistry::new(&dir.path().join("global").join("config.toml"));
+
+        assert_eq!(registry.add("app", &app.join(".dna")).unwrap(), app);
+        assert_eq!(registry.resolve("app").unwrap(), app);
+        assert_eq!(registry.resolve(app.to_str().unwrap()).unwrap(), app);
+
+        let err = registry.resolve("web").unwrap_err().to_string();
+        assert!(err.contains("Unknown store 'web'"), "{}", err);
+        assert!(err.contains("(app)"), "{}", err);
+        assert!(registry.add("web", &dir.path().join("web")).is_err());
+        assert!(registry.add("a/b", &app).is_err());
+    }
+
+    #[test]
+    fn current_store_follows_use_and_remove() {
+        let dir = TempDir::new().unwrap();
+        let app = project(&dir, "app");
+        let registry = StoreRegistry::new(&dir.path().join("config.toml"));
+        assert_eq!(registry.current().unwrap(), None);
+
+        registry.add("app", &app).unwrap();
+        assert!(registry.use_store(Some("web")).is_err());
+        registry.use_store(Some("app")).unwrap();
+        assert_eq!(registry.current().unwrap(), Some(app));
+
+        assert!(registry.remove("app").unwrap());
+        assert!(!registry.remove("app").unwrap());
+        assert_eq!(registry.load().unwrap(), GlobalConfig::default());
+    }
+
+    #[test]
+    fn discovery_finds_the_nearest_store_above() {
+        let dir = TempDir::new().unwrap();
+        let repo = project(&dir, "repo");
+        let nested = project(&dir, "repo/services/api");
+        let deep = repo.join("services").join("web").join("src");
+        std::fs::create_dir_all(&deep).unwrap();
+
+        assert_eq!(discover(&deep), Some(repo.clone()));
+        assert_eq!(discover(&nested.join("src")), Some(nested.clone()));
+        assert_eq!(discover(&nested), Some(nested));
+        assert_eq!(discover(&repo), Some(repo));
+        assert_eq!(discover(&dir.path().join("elsewhere")), None);
+    }
+}
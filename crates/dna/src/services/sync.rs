@@ -0,0 +1,500 @@
+use super::frontmatter::{self, Frontmatter};
+use super::plan::{ApplyReport, Plan, PlanOperation, PlanService};
+use super::types::{slugify_kind, Artifact, ContentFormat, LabelsConfig, SearchFilters};
+use super::{ArtifactService, ServiceError};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// An artifact as described by a frontmatter file
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncDocument {
+    pub path: PathBuf,
+    /// Artifact ID; files without one create a new artifact
+    pub id: Option<String>,
+    pub kind: String,
+    pub format: ContentFormat,
+    /// Only applied when present, so files written without one don't clear it
+    pub name: Option<String>,
+    pub metadata: HashMap<String, String>,
+    /// Only applied when present
+    pub context: Option<String>,
+    pub content: String,
+}
+
+impl SyncDocument {
+    /// Parse a frontmatter file.
+    ///
+    /// When the frontmatter has no `kind`, the first directory below `root`
+    /// is used, matching the layout written by `dna render`.
+    pub fn parse(root: &Path, path: &Path, text: &str) -> Result<Self> {
+        let (frontmatter, body) = frontmatter::split(text)
+            .with_context(|| format!("{}: missing '---' frontmatter", path.display()))?;
+        let fm = Frontmatter::parse(frontmatter)
+            .with_context(|| format!("{}: invalid frontmatter", path.display()))?;
+        let metadata = fm
+            .labels()
+            .map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+
+        let kind = match fm.kind {
+            Some(kind) => kind,
+            None => path
+                .strip_prefix(root)
+                .ok()
+                .and_then(|rel| {
+                    let mut parts = rel.components();
+                    let first = parts.next()?;
+                    // The file itself is not a kind directory
+                    parts.next()?;
+                    Some(first.as_os_str().to_string_lossy().into_owned())
+                })
+                .with_context(|| {
+                    format!(
+                        "{}: no 'kind' in frontmatter and not inside a kind directory",
+                        path.display()
+                    )
+                })?,
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            id: fm.id.filter(|id| !id.is_empty()),
+            kind: slugify_kind(&kind),
+            format: fm.format.unwrap_or(ContentFormat::Markdown),
+            name: fm.name,
+            metadata,
+            context: fm.context,
+            content: body.to_string(),
+        })
+    }
+}
+
+/// Read every non-hidden file under `dir` as a [`SyncDocument`], in path order
+pub fn read_documents(dir: &Path) -> Result<Vec<SyncDocument>> {
+    fn walk(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read directory {}", dir.display()))?
+        {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                walk(&path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    walk(dir, &mut files)?;
+    files.sort();
+
+    files
+        .iter()
+        .map(|path| {
+            let text = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            SyncDocument::parse(dir, path, &text)
+        })
+        .collect()
+}
+
+/// What reconciliation will do to one artifact
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncAction {
+    Create,
+    Update,
+    Delete,
+}
+
+/// A single difference between the directory and the store
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncChange {
+    pub action: SyncAction,
+    /// Artifact ID (filled in for created artifacts once applied)
+    pub id: Option<String>,
+    /// Source file, absent for deletions
+    pub path: Option<PathBuf>,
+    /// Fields that differ, for updates
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// Whether an embedding will be generated
+    pub reembed: bool,
+}
+
+/// Differences between a directory and the store, ready to apply
+#[derive(Debug, Clone, Serialize)]
+pub struct Reconciliation {
+    /// One change per plan operation, in the same order
+    pub changes: Vec<SyncChange>,
+    pub unchanged: usize,
+    #[serde(skip)]
+    plan: Plan,
+}
+
+impl Reconciliation {
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn count(&self, action: SyncAction) -> usize {
+        self.changes.iter().filter(|c| c.action == action).count()
+    }
+}
+
+/// Service that reconciles the store with a directory of frontmatter files.
+///
+/// The directory is the desired state: missing artifacts are created, changed
+/// ones updated (re-embedding only when content or context changed) and, with
+/// `prune`, artifacts without a file are removed. Changes are applied as one
+/// plan, so a failure rolls everything back.
+pub struct SyncService {
+    artifacts: Arc<ArtifactService>,
+    labels: LabelsConfig,
+}
+
+impl SyncService {
+    pub fn new(artifacts: Arc<ArtifactService>) -> Self {
+        Self {
+            artifacts,
+            labels: LabelsConfig::default(),
+        }
+    }
+
+    /// Validate labels in the files against a label registry
+    pub fn with_labels(mut self, labels: LabelsConfig) -> Self {
+        self.labels = labels;
+        self
+    }
+
+    /// Compare documents with the store without changing anything
+    pub async fn reconcile(
+        &self,
+        documents: &[SyncDocument],
+        prune: bool,
+    ) -> Result<Reconciliation> {
+        let mut seen = HashSet::new();
+        for doc in documents {
+            if let Some(id) = &doc.id {
+                if !seen.insert(id.as_str()) {
+                    anyhow::bail!(
+                        "{}: ID '{}' is used by another file",
+                        doc.path.display(),
+                        id
+                    );
+                }
+            }
+        }
+
+        let existing: HashMap<String, Artifact> = self
+            .artifacts
+            .list(SearchFilters::default())
+            .await?
+            .into_iter()
+            .map(|a| (a.id.clone(), a))
+            .collect();
+
+        let mut plan = Plan::default();
+        let mut changes = Vec::new();
+        let mut unchanged = 0;
+
+        for doc in documents {
+            let current = doc.id.as_ref().and_then(|id| existing.get(id));
+            let Some(current) = current else {
+                plan.operations.push(PlanOperation::Add {
+                    id: doc.id.clone(),
+                    kind: doc.kind.clone(),
+                    content: doc.content.clone(),
+                    format: Some(doc.format),
+                    name: doc.name.clone(),
+                    labels: doc.metadata.clone(),
+                    context: doc.context.clone(),
+                    reference: None,
+                });
+                changes.push(SyncChange {
+                    action: SyncAction::Create,
+                    id: doc.id.clone(),
+                    path: Some(doc.path.clone()),
+                    fields: Vec::new(),
+                    reembed: true,
+                });
+                continue;
+            };
+
+            let mut fields = Vec::new();
+            let content = (doc.content != current.content).then(|| doc.content.clone());
+            if content.is_some() {
+                fields.push("content".to_string());
+            }
+            let kind = (doc.kind != current.kind).then(|| doc.kind.clone());
+            if kind.is_some() {
+                fields.push("kind".to_string());
+            }
+            let name = doc
+                .name
+                .clone()
+                .filter(|n| current.name.as_ref() != Some(n));
+            if name.is_some() {
+                fields.push("name".to_string());
+            }
+            let context = doc
+                .context
+                .clone()
+                .filter(|c| current.context.as_ref() != Some(c));
+            if context.is_some() {
+                fields.push("context".to_string());
+            }
+            let labels = (doc.metadata != current.metadata).then(|| {
+                // Empty values remove labels that are no longer in the file
+                let mut labels = doc.metadata.clone();
+                for key in current.metadata.keys() {
+                    labels.entry(key.clone()).or_default();
+                }
+                labels
+            });
+            if labels.is_some() {
+                fields.push("labels".to_string());
+            }
+
+            if fields.is_empty() {
+                unchanged += 1;
+                continue;
+            }
+
+            changes.push(SyncChange {
+                action: SyncAction::Update,
+                id: Some(current.id.clone()),
+                path: Some(doc.path.clone()),
+                reembed: content.is_some() || context.is_some(),
+                fields,
+            });
+            plan.operations.push(PlanOperation::Update {
+                id: current.id.clone(),
+                content,
+                name,
+                kind,
+                labels,
+                context,
+            });
+        }
+
+        if prune {
+            let mut stale: Vec<&String> = existing
+                .keys()
+                .filter(|id| !seen.contains(id.as_str()))
+                .collect();
+            stale.sort();
+            for id in stale {
+                plan.operations
+                    .push(PlanOperation::Remove { id: id.clone() });
+                changes.push(SyncChange {
+                    action: SyncAction::Delete,
+                    id: Some(id.clone()),
+                    path: None,
+                    fields: Vec::new(),
+                    reembed: false,
+                });
+            }
+        }
+
+        Ok(Reconciliation {
+            changes,
+            unchanged,
+            plan,
+        })
+    }
+
+    /// Apply a reconciliation all-or-nothing.
+    ///
+    /// Files that had no ID get the ID of their newly created artifact written
+    /// into their frontmatter, so the next sync updates instead of duplicating.
+    pub async fn apply(
+        &self,
+        reconciliation: &mut Reconciliation,
+    ) -> Result<ApplyReport, ServiceError> {
+        let plans = PlanService::new(Arc::clone(&self.artifacts)).with_labels(self.labels.clone());
+        if reconciliation.is_empty() {
+            let version = self.artifacts.version().await?;
+            return Ok(ApplyReport {
+                version_before: version,
+                version_after: version,
+                steps: Vec::new(),
+            });
+        }
+
+        let report = plans.apply(&reconciliation.plan).await?;
+
+        for (change, step) in reconciliation.changes.iter_mut().zip(&report.steps) {
+            if change.action != SyncAction::Create || change.id.is_some() {
+                continue;
+            }
+            change.id = Some(step.target.clone());
+            if let Some(path) = &change.path {
+                write_id(path, &step.target)?;
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+/// Insert `id:` as the first frontmatter line of a file
+fn write_id(path: &Path, id: &str) -> Result<()> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let rest = text.strip_prefix("---\n").unwrap_or(&text);
+    let updated = format!("---\nid: {}\n{}", id, rest);
+    std::fs::write(path, updated).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+    use crate::testing::{TestDatabase, TestEmbedding};
+    use tempfile::TempDir;
+
+    fn write(dir: &Path, rel: &str, text: &str) -> PathBuf {
+        let path = dir.join(rel);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    async fn service() -> (Arc<TestDatabase>, Arc<ArtifactService>, SyncService) {
+        let db = Arc::new(TestDatabase::new());
+        let artifacts = Arc::new(ArtifactService::new(db.clone(), Arc::new(TestEmbedding)));
+        (db, Arc::clone(&artifacts), SyncService::new(artifacts))
+    }
+
+    #[test]
+    fn parse_reads_rendered_file() {
+        let root = Path::new("/out");
+        let path = root.join("intent/login.md");
+        let text = "---\nid: abc\nkind: intent\nformat: markdown\nmetadata:\n  domain: auth\n  version: 2\ncreated_at: 2024-01-01T00:00:00+00:00\n---\n\nUsers can log in\n";
+        let doc = SyncDocument::parse(root, &path, text).unwrap();
+        assert_eq!(doc.id.as_deref(), Some("abc"));
+        assert_eq!(doc.kind, "intent");
+        assert_eq!(doc.metadata.get("domain").map(String::as_str), Some("auth"));
+        assert_eq!(doc.metadata.get("version").map(String::as_str), Some("2"));
+        assert_eq!(doc.content, "Users can log in\n");
+    }
+
+    #[test]
+    fn parse_drops_rendered_related_section() {
+        let root = Path::new("/out");
+        let path = root.join("intent/login.md");
+        let text = format!(
+            "---\nid: abc\nlinks:\n  - ../contract/api.md\n---\n\nUsers can log in\n\n\n{}\n## Related\n\n- related: [[../contract/api|api]]\n",
+            frontmatter::RELATED_SECTION_MARKER
+        );
+        let doc = SyncDocument::parse(root, &path, &text).unwrap();
+        assert_eq!(doc.content, "Users can log in\n");
+    }
+
+    #[test]
+    fn parse_falls_back_to_directory_kind() {
+        let root = Path::new("/out");
+        let doc =
+            SyncDocument::parse(root, &root.join("contract/api.md"), "---\n---\nbody").unwrap();
+        assert_eq!(doc.kind, "contract");
+        assert_eq!(doc.content, "body");
+
+        assert!(SyncDocument::parse(root, &root.join("top.md"), "---\n---\nbody").is_err());
+        assert!(SyncDocument::parse(root, &root.join("a/b.md"), "no frontmatter").is_err());
+    }
+
+    #[tokio::test]
+    async fn sync_creates_updates_and_prunes() {
+        let (db, artifacts, sync) = service().await;
+        let add = |content: &str| {
+            let artifacts = Arc::clone(&artifacts);
+            let content = content.to_string();
+            async move {
+                artifacts
+                    .add(
+                        "intent".to_string(),
+                        content,
+                        ContentFormat::Markdown,
+                        None,
+                        HashMap::new(),
+                        None,
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
+        let same = add("unchanged").await;
+        let changed = add("old text").await;
+        let stale = add("stale").await;
+
+        let dir = TempDir::new().unwrap();
+        write(
+            dir.path(),
+            "intent/same.md",
+            &format!("---\nid: {}\n---\n\nunchanged", same.id),
+        );
+        write(
+            dir.path(),
+            "intent/changed.md",
+            &format!(
+                "---\nid: {}\nmetadata:\n  domain: auth\n---\n\nnew text",
+                changed.id
+            ),
+        );
+        let new_file = write(
+            dir.path(),
+            "intent/new.md",
+            "---\nkind: intent\n---\n\nbrand new",
+        );
+
+        let docs = read_documents(dir.path()).unwrap();
+        let mut reconciliation = sync.reconcile(&docs, true).await.unwrap();
+        assert_eq!(reconciliation.unchanged, 1);
+        assert_eq!(reconciliation.count(SyncAction::Create), 1);
+        assert_eq!(reconciliation.count(SyncAction::Update), 1);
+        assert_eq!(reconciliation.count(SyncAction::Delete), 1);
+        let update = reconciliation
+            .changes
+            .iter()
+            .find(|c| c.action == SyncAction::Update)
+            .unwrap();
+        assert_eq!(update.fields, vec!["content", "labels"]);
+        assert!(update.reembed);
+
+        sync.apply(&mut reconciliation).await.unwrap();
+
+        assert!(db.get(&stale.id).await.unwrap().is_none());
+        let updated = db.get(&changed.id).await.unwrap().unwrap();
+        assert_eq!(updated.content, "new text");
+        assert_eq!(
+            updated.metadata.get("domain").map(String::as_str),
+            Some("auth")
+        );
+
+        // The new file now carries its ID, so a second sync is a no-op
+        let written = std::fs::read_to_string(&new_file).unwrap();
+        assert!(written.starts_with("---\nid: "));
+        let docs = read_documents(dir.path()).unwrap();
+        let again = sync.reconcile(&docs, true).await.unwrap();
+        assert!(again.is_empty(), "{:?}", again.changes);
+        assert_eq!(again.unchanged, 3);
+    }
+
+    #[tokio::test]
+    async fn reconcile_rejects_duplicate_ids() {
+        let (_db, _artifacts, sync) = service().await;
+        let dir = TempDir::new().unwrap();
+        write(dir.path(), "intent/a.md", "---\nid: same\n---\na");
+        write(dir.path(), "intent/b.md", "---\nid: same\n---\nb");
+        let docs = read_documents(dir.path()).unwrap();
+        assert!(sync.reconcile(&docs, false).await.is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! Checks that structured content parses as its format says.
+//!
+//! YAML, JSON and OpenAPI artifacts feed field extraction, rendering and
+//! ingestion that expect to parse them, so their content is parsed on the
+//! way in and rejected with the location of the first error. An OpenAPI
+//! document must also have top-level `paths` or `components`.
+
+use super::limits::InputRejected;
+use super::types::ContentFormat;
+use serde::Deserialize;
+
+impl ContentFormat {
+    /// Check that `content` parses as this format; Markdown and text always
+    /// do. YAML may hold several documents, each of which must parse.
+    pub fn check(&self, content: &str) -> Result<(), InputRejected> {
+        match self {
+            ContentFormat::Markdown | ContentFormat::Text => Ok(()),
+            ContentFormat::Json => serde_json::from_str::<serde_json::Value>(content)
+                .map(drop)
+                .map_err(|e| InputRejected(format!("Invalid JSON content: {}", e))),
+            ContentFormat::Yaml => {
+                for document in serde_yaml::Deserializer::from_str(content) {
+                    serde_yaml::Value::deserialize(document)
+                        .map_err(|e| InputRejected(format!("Invalid YAML content: {}", e)))?;
+                }
+                Ok(())
+            },
+            ContentFormat::OpenApi => check_openapi(content),
+        }
+    }
+}
+
+/// Parse an OpenAPI document, in YAML or JSON, and check it describes
+/// something
+fn check_openapi(content: &str) -> Result<(), InputRejected> {
+    let spec: serde_yaml::Value = serde_yaml::from_str(content)
+        .map_err(|e| InputRejected(format!("Invalid OpenAPI content: {}", e)))?;
+    let described = spec
+        .as_mapping()
+        .is_some_and(|map| map.contains_key("paths") || map.contains_key("components"));
+    if !described {
+        return Err(InputRejected(
+            "Invalid OpenAPI content: expected top-level paths or components".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_where_structured_content_fails_to_parse() {
+        assert!(ContentFormat::Markdown.check("{ not: json").is_ok());
+        assert!(ContentFormat::Json.check(r#"{"a": [1, 2]}"#).is_ok());
+        assert!(ContentFormat::Yaml.check("a: 1\n---\nb: [2, 3]\n").is_ok());
+
+        let err = ContentFormat::Json
+            .check("{\n  \"a\": 1,\n  \"b\"\n}")
+            .unwrap_err();
+        assert!(err.0.starts_with("Invalid JSON content"), "{}", err);
+        assert!(err.0.contains("line 4"), "{}", err);
+
+        let err = ContentFormat::Yaml
+            .check("a: 1\n---\nb: [2, 3\n")
+            .unwrap_err();
+        assert!(err.0.starts_with("Invalid YAML content"), "{}", err);
+        assert!(err.0.contains("line"), "{}", err);
+    }
+
+    #[test]
+    fn openapi_needs_paths_or_components() {
+        let spec =
+            "openapi: 3.0.0\ninfo:\n  title: Payments\n  version: '1'\npaths:\n  /refunds: {}\n";
+        assert!(ContentFormat::OpenApi.check(spec).is_ok());
+        assert!(ContentFormat::OpenApi
+            .check(r#"{"openapi": "3.1.0", "components": {}}"#)
+            .is_ok());
+
+        let err = ContentFormat::OpenApi
+            .check("openapi: 3.0.0\ninfo:\n  title: Payments\n")
+            .unwrap_err();
+        assert!(err.0.contains("expected top-level paths or components"));
+        assert!(ContentFormat::OpenApi.check("just a sentence").is_err());
+        assert!(ContentFormat::OpenApi
+            .check("paths:\n  /a: [\n")
+            .unwrap_err()
+            .0
+            .starts_with("Invalid OpenAPI content"));
+    }
+}
@@ -1,7 +1,11 @@
+use super::deprecation::DEPRECATED_STATUS;
+use super::pack::STATUS_LABEL;
 use chrono::{DateTime, Utc};
+use regex::Regex;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::LazyLock;
 
 #[cfg(feature = "openapi")]
 use utoipa::ToSchema;
@@ -138,6 +142,19 @@ pub enum ContentFormat {
     Text,
 }
 
+impl ContentFormat {
+    /// File extension for content in this format
+    pub fn file_extension(&self) -> &'static str {
+        match self {
+            ContentFormat::Markdown => "md",
+            ContentFormat::Yaml => "yaml",
+            ContentFormat::Json => "json",
+            ContentFormat::OpenApi => "yaml",
+            ContentFormat::Text => "txt",
+        }
+    }
+}
+
 impl std::fmt::Display for ContentFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -172,6 +189,9 @@ impl std::str::FromStr for ContentFormat {
 pub struct Artifact {
     pub id: String,
     pub kind: String,
+    /// Scope within a shared store, such as a team or service
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
     pub name: Option<String>,
     pub content: String,
     pub format: ContentFormat,
@@ -185,18 +205,176 @@ pub struct Artifact {
     /// Embedding of the context (same dimensions as content embedding)
     #[serde(skip)]
     pub context_embedding: Option<Vec<f32>>,
+    /// Text extracted from the artifact's attachments
+    #[serde(skip)]
+    pub attachment_text: Option<String>,
+    /// Embedding of the attachment text, searched as a second context vector
+    #[serde(skip)]
+    pub attachment_embedding: Option<Vec<f32>>,
+    /// Template the content embedding was composed with; `None` when only
+    /// the content was embedded
+    #[serde(skip)]
+    pub embedding_text: Option<String>,
+    /// Content embeddings from the `[[extra_models]]`, keyed by model slug
+    #[serde(skip)]
+    pub model_embeddings: HashMap<String, Vec<f32>>,
+    /// Top-level fields parsed from structured content, as configured per kind
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub fields: HashMap<String, String>,
+    /// Former IDs that still resolve to this artifact
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// The alias this artifact was looked up by, when fetched through one;
+    /// never stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub redirected_from: Option<String>,
+    /// Person or team accountable for the artifact, as `@handle`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    /// People or teams who review changes to the artifact
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reviewers: Vec<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// Reduced alphabet for nanoid-based IDs (no 0/1/i/l/o to avoid ambiguity)
+const ID_ALPHABET: &[char] = &[
+    '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'j', 'k', 'm',
+    'n', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
+];
+
+/// Random ID of `length` characters from [`ID_ALPHABET`]
+fn random_id(length: usize) -> String {
+    nanoid::nanoid!(length, ID_ALPHABET)
+}
+
+/// Default length of nanoid-based artifact IDs
+pub const DEFAULT_ID_LENGTH: usize = 10;
+
+/// Shortest random part allowed for nanoid-based IDs; about a billion
+/// possible values, where shorter IDs soon start to collide
+pub const MIN_ID_LENGTH: usize = 6;
+
+/// Number of leading kind characters used by [`IdStrategy::KindPrefix`]
+const ID_KIND_PREFIX_LENGTH: usize = 3;
+
+/// Strategy used to generate new artifact IDs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdStrategy {
+    /// Random ID from the reduced alphabet (`k7v3m9xnp2`)
+    #[default]
+    Nanoid,
+    /// Random ID prefixed with the start of the kind slug (`int-k7v3m9xnp2`)
+    KindPrefix,
+    /// Lowercase ULID, lexicographically sortable by creation time
+    Ulid,
+}
+
+impl std::fmt::Display for IdStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IdStrategy::Nanoid => write!(f, "nanoid"),
+            IdStrategy::KindPrefix => write!(f, "kind-prefix"),
+            IdStrategy::Ulid => write!(f, "ulid"),
+        }
+    }
+}
+
+impl std::str::FromStr for IdStrategy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "nanoid" => Ok(IdStrategy::Nanoid),
+            "kind-prefix" | "prefix" => Ok(IdStrategy::KindPrefix),
+            "ulid" => Ok(IdStrategy::Ulid),
+            _ => Err(anyhow::anyhow!("Invalid ID strategy: {}", s)),
+        }
+    }
+}
+
+/// Generates IDs for newly created artifacts
+pub trait IdGenerator: Send + Sync {
+    /// Produce a new ID for an artifact of the given kind
+    fn generate(&self, kind: &str) -> String;
+}
+
+/// Configuration for artifact ID generation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IdConfig {
+    #[serde(default)]
+    pub strategy: IdStrategy,
+    /// Length of the random part for nanoid-based strategies (ignored for
+    /// ULIDs); at least [`MIN_ID_LENGTH`]
+    #[serde(default = "default_id_length")]
+    pub length: usize,
+}
+
+fn default_id_length() -> usize {
+    DEFAULT_ID_LENGTH
+}
+
+impl Default for IdConfig {
+    fn default() -> Self {
+        Self {
+            strategy: IdStrategy::default(),
+            length: DEFAULT_ID_LENGTH,
+        }
+    }
+}
+
+impl IdConfig {
+    /// Prefix used for a kind under [`IdStrategy::KindPrefix`]
+    pub fn kind_prefix(kind: &str) -> String {
+        let prefix: String = kind
+            .chars()
+            .filter(char::is_ascii_alphanumeric)
+            .take(ID_KIND_PREFIX_LENGTH)
+            .collect();
+        if prefix.is_empty() {
+            "art".to_string()
+        } else {
+            prefix.to_lowercase()
+        }
+    }
+
+    /// Check whether an ID could have been produced by this configuration
+    pub fn matches(&self, id: &str, kind: &str) -> bool {
+        let is_random =
+            |s: &str| s.len() == self.length && s.chars().all(|c| ID_ALPHABET.contains(&c));
+        match self.strategy {
+            IdStrategy::Nanoid => is_random(id),
+            IdStrategy::KindPrefix => id
+                .strip_prefix(&format!("{}-", Self::kind_prefix(kind)))
+                .is_some_and(is_random),
+            IdStrategy::Ulid => id.len() == 26 && id.to_uppercase().parse::<ulid::Ulid>().is_ok(),
+        }
+    }
+}
+
+impl IdGenerator for IdConfig {
+    fn generate(&self, kind: &str) -> String {
+        match self.strategy {
+            IdStrategy::Nanoid => random_id(self.length),
+            IdStrategy::KindPrefix => {
+                format!("{}-{}", Self::kind_prefix(kind), random_id(self.length))
+            },
+            IdStrategy::Ulid => ulid::Ulid::new().to_string().to_lowercase(),
+        }
+    }
+}
+
 impl Artifact {
     /// Generate a new 10-character ID using reduced alphabet
     pub fn generate_id() -> String {
-        const ALPHABET: &[char] = &[
-            '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'j',
-            'k', 'm', 'n', 'p', 'q', 'r', 's', 't', 'u', 'v', 'w', 'x', 'y', 'z',
-        ];
-        nanoid::nanoid!(10, ALPHABET)
+        random_id(DEFAULT_ID_LENGTH)
+    }
+
+    /// Generate an ID for an artifact of `kind` using the given generator
+    pub fn generate_id_with(generator: &dyn IdGenerator, kind: &str) -> String {
+        generator.generate(kind)
     }
 
     /// Create a new artifact
@@ -212,6 +390,7 @@ impl Artifact {
         Self {
             id: Self::generate_id(),
             kind,
+            namespace: None,
             name,
             content,
             format,
@@ -220,20 +399,59 @@ impl Artifact {
             embedding_model,
             context: None,
             context_embedding: None,
+            attachment_text: None,
+            attachment_embedding: None,
+            embedding_text: None,
+            model_embeddings: HashMap::new(),
+            fields: HashMap::new(),
+            aliases: Vec::new(),
+            redirected_from: None,
+            owner: None,
+            reviewers: Vec::new(),
             created_at: now,
             updated_at: now,
         }
     }
 
+    /// Parse the named top-level fields out of YAML or JSON content.
+    ///
+    /// Scalars are stored as their plain string form and nested values as
+    /// JSON. Missing or null fields, unparseable content and other formats
+    /// yield nothing.
+    pub fn extract_fields(&self, names: &[String]) -> HashMap<String, String> {
+        let mut fields = HashMap::new();
+        if names.is_empty()
+            || !matches!(
+                self.format,
+                ContentFormat::Yaml | ContentFormat::Json | ContentFormat::OpenApi
+            )
+        {
+            return fields;
+        }
+
+        let Ok(serde_yaml::Value::Mapping(map)) = serde_yaml::from_str(&self.content) else {
+            return fields;
+        };
+
+        for name in names {
+            let value = match map.get(name.as_str()) {
+                None | Some(serde_yaml::Value::Null) => continue,
+                Some(serde_yaml::Value::String(s)) => s.clone(),
+                Some(serde_yaml::Value::Bool(b)) => b.to_string(),
+                Some(serde_yaml::Value::Number(n)) => n.to_string(),
+                Some(other) => match serde_json::to_string(other) {
+                    Ok(json) => json,
+                    Err(_) => continue,
+                },
+            };
+            fields.insert(name.clone(), value);
+        }
+        fields
+    }
+
     /// Get file extension based on format
     pub fn file_extension(&self) -> &str {
-        match self.format {
-            ContentFormat::Markdown => "md",
-            ContentFormat::Yaml => "yaml",
-            ContentFormat::Json => "json",
-            ContentFormat::OpenApi => "yaml",
-            ContentFormat::Text => "txt",
-        }
+        self.format.file_extension()
     }
 }
 
@@ -242,6 +460,8 @@ impl Artifact {
 pub struct ModelInfo {
     pub max_tokens: usize,
     pub dimensions: usize,
+    /// Whether the model was trained on languages other than English
+    pub multilingual: bool,
 }
 
 /// Estimate token count from text (conservative estimate).
@@ -259,31 +479,38 @@ pub fn get_model_info(model: &str) -> ModelInfo {
         "BAAI/bge-small-en-v1.5" => ModelInfo {
             max_tokens: 512,
             dimensions: 384,
+            multilingual: false,
         },
         "BAAI/bge-base-en-v1.5" => ModelInfo {
             max_tokens: 512,
             dimensions: 768,
+            multilingual: false,
         },
         "text-embedding-3-small" => ModelInfo {
             max_tokens: 8191,
             dimensions: 1536,
+            multilingual: true,
         },
         "text-embedding-3-large" => ModelInfo {
             max_tokens: 8191,
             dimensions: 3072,
+            multilingual: true,
         },
         "nomic-embed-text" => ModelInfo {
             max_tokens: 8192,
             dimensions: 768,
+            multilingual: false,
         },
         "voyage-3" => ModelInfo {
             max_tokens: 32000,
             dimensions: 1024,
+            multilingual: true,
         },
         // Conservative default for unknown models
         _ => ModelInfo {
             max_tokens: 512,
             dimensions: 384,
+            multilingual: model.contains("multilingual"),
         },
     }
 }
@@ -292,10 +519,184 @@ pub fn get_model_info(model: &str) -> ModelInfo {
 #[derive(Debug, Clone, Default)]
 pub struct SearchFilters {
     pub kind: Option<String>,
+    /// Only artifacts in this namespace
+    pub namespace: Option<String>,
+    /// Only artifacts owned by this person or team
+    pub owner: Option<String>,
+    /// Only artifacts reviewed by this person or team
+    pub reviewer: Option<String>,
     pub metadata: HashMap<String, String>,
     pub after: Option<DateTime<Utc>>,
     pub before: Option<DateTime<Utc>>,
     pub limit: Option<usize>,
+    /// Also return artifacts that were moved to the archive
+    pub include_archived: bool,
+    /// Exact matches on structured fields parsed from content
+    pub fields: HashMap<String, String>,
+    /// Leave out artifacts of these kinds
+    pub kind_not: Vec<String>,
+    /// Only artifacts of these kinds, when set; used to apply an access policy
+    pub kinds: Option<Vec<String>>,
+    /// Leave out artifacts with any of these labels
+    pub metadata_not: HashMap<String, String>,
+    /// Leave out these artifacts
+    pub exclude_ids: Vec<String>,
+    /// Leave out deprecated artifacts, unless `metadata` asks for them
+    pub exclude_deprecated: bool,
+}
+
+impl SearchFilters {
+    /// Whether `artifact` passes the filters, as the Lance store applies them
+    pub fn matches(&self, artifact: &Artifact) -> bool {
+        self.kind.as_ref().is_none_or(|k| artifact.kind == *k)
+            && self
+                .namespace
+                .as_ref()
+                .is_none_or(|ns| artifact.namespace.as_ref() == Some(ns))
+            && self
+                .owner
+                .as_ref()
+                .is_none_or(|owner| artifact.owner.as_ref() == Some(owner))
+            && self
+                .reviewer
+                .as_ref()
+                .is_none_or(|reviewer| artifact.reviewers.contains(reviewer))
+            && self.after.is_none_or(|dt| artifact.updated_at >= dt)
+            && self.before.is_none_or(|dt| artifact.updated_at < dt)
+            && self
+                .metadata
+                .iter()
+                .all(|(k, v)| artifact.metadata.get(k) == Some(v))
+            && self
+                .fields
+                .iter()
+                .all(|(k, v)| artifact.fields.get(k) == Some(v))
+            && !self.excludes(artifact)
+    }
+
+    /// Whether an exclusion filter rules `artifact` out
+    pub fn excludes(&self, artifact: &Artifact) -> bool {
+        self.kind_not.contains(&artifact.kind)
+            || self
+                .kinds
+                .as_ref()
+                .is_some_and(|kinds| !kinds.contains(&artifact.kind))
+            || self.exclude_ids.contains(&artifact.id)
+            || self
+                .metadata_not
+                .iter()
+                .any(|(k, v)| artifact.metadata.get(k) == Some(v))
+            || (self.excludes_deprecated()
+                && artifact.metadata.get(STATUS_LABEL).map(String::as_str)
+                    == Some(DEPRECATED_STATUS))
+    }
+
+    /// Whether deprecated artifacts are left out: `exclude_deprecated` is
+    /// set and `metadata` doesn't ask for them
+    pub fn excludes_deprecated(&self) -> bool {
+        self.exclude_deprecated
+            && self.metadata.get(STATUS_LABEL).map(String::as_str) != Some(DEPRECATED_STATUS)
+    }
+}
+
+/// How much content and context similarity each contribute to search ranking.
+///
+/// Weights are relative; an unspecified weight is zero. The default ranks by
+/// content alone.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct SearchWeights {
+    /// Weight of similarity to the artifact's content
+    #[serde(default)]
+    pub content: f32,
+    /// Weight of similarity to the artifact's context
+    #[serde(default)]
+    pub context: f32,
+}
+
+impl Default for SearchWeights {
+    fn default() -> Self {
+        Self {
+            content: 1.0,
+            context: 0.0,
+        }
+    }
+}
+
+impl SearchWeights {
+    /// Check weights are non-negative and not all zero
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (name, weight) in [("content", self.content), ("context", self.context)] {
+            if !weight.is_finite() || weight < 0.0 {
+                anyhow::bail!("Search weight for {} must be a non-negative number", name);
+            }
+        }
+        if self.content + self.context == 0.0 {
+            anyhow::bail!("At least one search weight must be greater than zero");
+        }
+        Ok(())
+    }
+
+    /// Whether context similarity plays no part in ranking
+    pub fn is_content_only(&self) -> bool {
+        self.context == 0.0
+    }
+}
+
+impl std::str::FromStr for SearchWeights {
+    type Err = anyhow::Error;
+
+    /// Parse `content=0.7,context=0.3`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut weights = SearchWeights {
+            content: 0.0,
+            context: 0.0,
+        };
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, value) = part
+                .split_once('=')
+                .ok_or_else(|| anyhow::anyhow!("Invalid weight '{}': expected name=value", part))?;
+            let value: f32 = value
+                .trim()
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid weight value in '{}'", part))?;
+            match name.trim() {
+                "content" => weights.content = value,
+                "context" => weights.context = value,
+                other => anyhow::bail!("Unknown search weight '{}': use content or context", other),
+            }
+        }
+        weights.validate()?;
+        Ok(weights)
+    }
+}
+
+/// Check per-kind boost factors are non-negative numbers
+pub fn validate_boosts(boosts: &HashMap<String, f32>) -> anyhow::Result<()> {
+    for (kind, boost) in boosts {
+        if !boost.is_finite() || *boost < 0.0 {
+            anyhow::bail!("Boost for kind '{}' must be a non-negative number", kind);
+        }
+    }
+    Ok(())
+}
+
+/// Parse a `kind=factor` boost such as `invariant=2.0`
+pub fn parse_kind_boost(s: &str) -> anyhow::Result<(String, f32)> {
+    let (kind, value) = s
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Invalid boost '{}': expected kind=factor", s))?;
+    let kind = kind.trim();
+    if kind.is_empty() {
+        anyhow::bail!("Invalid boost '{}': kind is empty", s);
+    }
+    let boost: f32 = value
+        .trim()
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid boost factor in '{}'", s))?;
+    let boosts = HashMap::from([(kind.to_string(), boost)]);
+    validate_boosts(&boosts)?;
+    Ok((kind.to_string(), boost))
 }
 
 /// Specifies which embeddings to regenerate during reindexing.
@@ -309,6 +710,134 @@ pub enum ReindexTarget {
     Both,
 }
 
+impl std::fmt::Display for ReindexTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReindexTarget::Content => write!(f, "content"),
+            ReindexTarget::Context => write!(f, "context"),
+            ReindexTarget::Both => write!(f, "both"),
+        }
+    }
+}
+
+impl std::str::FromStr for ReindexTarget {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "content" => Ok(ReindexTarget::Content),
+            "context" => Ok(ReindexTarget::Context),
+            "both" | "all" => Ok(ReindexTarget::Both),
+            _ => Err(anyhow::anyhow!(
+                "Unknown reindex target '{}': use content, context or both",
+                s
+            )),
+        }
+    }
+}
+
+/// Why an artifact's stored embeddings are unusable with the current model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingIssue {
+    /// Content embedding is absent (or stored as an all-zero placeholder),
+    /// or context text exists without a context embedding.
+    Missing,
+    /// Embeddings were produced by a different model than the one configured.
+    ModelMismatch,
+    /// Embedding length differs from the provider's dimensions.
+    DimensionMismatch,
+    /// Content was embedded with another composition than its kind's
+    /// `embed` setting asks for.
+    CompositionChanged,
+}
+
+impl std::fmt::Display for EmbeddingIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmbeddingIssue::Missing => write!(f, "missing"),
+            EmbeddingIssue::ModelMismatch => write!(f, "model mismatch"),
+            EmbeddingIssue::DimensionMismatch => write!(f, "dimension mismatch"),
+            EmbeddingIssue::CompositionChanged => write!(f, "composition changed"),
+        }
+    }
+}
+
+/// What adding or updating an artifact would do, worked out without
+/// embedding anything or touching the store.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangePreview {
+    /// The artifact as it would be stored, without embeddings
+    pub artifact: Artifact,
+    /// Fields that would be set or changed
+    pub changed_fields: Vec<String>,
+    /// Estimated tokens in the content
+    pub content_tokens: usize,
+    /// Estimated tokens in the context, if there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_tokens: Option<usize>,
+    /// Token limit of the configured model
+    pub max_tokens: usize,
+    /// Calls that would be made to the embedding provider
+    pub embedding_calls: usize,
+}
+
+/// What [`ArtifactService::upsert`](super::ArtifactService::upsert) did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum UpsertAction {
+    Created,
+    Updated,
+    /// The stored artifact already matched, so nothing was written
+    Unchanged,
+}
+
+/// An artifact written, or left as it was, by an upsert
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct Upserted {
+    pub action: UpsertAction,
+    pub artifact: Artifact,
+    /// Fields that changed, for updates
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed_fields: Vec<String>,
+}
+
+/// Changes to one artifact in
+/// [`ArtifactService::update_batch`](super::ArtifactService::update_batch);
+/// fields left unset keep their stored value
+#[derive(Debug, Clone, Default)]
+pub struct ArtifactUpdate {
+    pub id: String,
+    pub content: Option<String>,
+    pub name: Option<String>,
+    pub kind: Option<String>,
+    /// Labels to set; an empty value removes the label
+    pub metadata: Option<HashMap<String, String>>,
+    pub context: Option<String>,
+}
+
+/// Result of scanning artifacts for unusable embeddings.
+#[derive(Debug, Clone, Default)]
+pub struct EmbeddingHealth {
+    /// Number of artifacts examined.
+    pub scanned: usize,
+    /// Artifact IDs paired with the problem found for each.
+    pub issues: Vec<(String, EmbeddingIssue)>,
+}
+
+impl EmbeddingHealth {
+    /// Number of artifacts with the given issue.
+    pub fn count(&self, issue: EmbeddingIssue) -> usize {
+        self.issues.iter().filter(|(_, i)| *i == issue).count()
+    }
+
+    /// True when no artifact needs reindexing.
+    pub fn is_healthy(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
 /// Search result with similarity score
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[cfg_attr(feature = "openapi", derive(ToSchema))]
@@ -317,6 +846,70 @@ pub struct SearchResult {
     pub score: f32,
 }
 
+/// Why a search result scored as it did
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct SearchExplanation {
+    /// Vector that contributed most to the score
+    pub matched: MatchedVector,
+    /// Match against the content embedding, if the artifact has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<VectorMatch>,
+    /// Match against the context embedding, if the artifact has one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<VectorMatch>,
+    /// Weights the content and context similarities were blended with
+    pub weights: SearchWeights,
+    /// Kind boost the score was multiplied by (1.0 when none applies)
+    pub boost: f32,
+    /// Filters the artifact satisfied, keyed as `kind`, `metadata.<key>`,
+    /// `fields.<key>`, `after`, `before`, `kind_not` and `metadata_not.<key>`
+    pub filters: BTreeMap<String, String>,
+}
+
+/// The embedding a search result was found through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum MatchedVector {
+    Content,
+    Context,
+}
+
+impl std::fmt::Display for MatchedVector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchedVector::Content => write!(f, "content"),
+            MatchedVector::Context => write!(f, "context"),
+        }
+    }
+}
+
+/// Distance between a query and one stored embedding
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "openapi", derive(ToSchema))]
+pub struct VectorMatch {
+    /// Squared L2 distance, the metric vector search ranks by
+    pub distance: f32,
+    /// Similarity score derived from the distance, `1 / (1 + distance)`
+    pub similarity: f32,
+}
+
+impl VectorMatch {
+    /// Compare a query embedding with a stored one
+    pub fn between(query: &[f32], stored: &[f32]) -> Self {
+        let distance: f32 = query
+            .iter()
+            .zip(stored)
+            .map(|(a, b)| (a - b) * (a - b))
+            .sum();
+        Self {
+            distance,
+            similarity: 1.0 / (1.0 + distance),
+        }
+    }
+}
+
 /// Configuration for embedding providers
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelConfig {
@@ -326,6 +919,21 @@ pub struct ModelConfig {
     pub api_key: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_url: Option<String>,
+    /// Model cache directory for the local provider (default: the HuggingFace hub cache)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<String>,
+    /// Never download local models; fail if they are not already cached
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offline: Option<bool>,
+    /// Device for the local provider: cpu (default), auto, cuda[:N], metal[:N]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// CPU threads for the local provider (default: one per core)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+    /// Texts per forward pass for the local provider (default: 32)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
 }
 
 impl Default for ModelConfig {
@@ -335,6 +943,11 @@ impl Default for ModelConfig {
             name: "BAAI/bge-small-en-v1.5".to_string(),
             api_key: None,
             base_url: None,
+            cache_dir: None,
+            offline: None,
+            device: None,
+            threads: None,
+            batch_size: None,
         }
     }
 }
@@ -349,112 +962,1163 @@ pub struct StorageConfig {
     /// Enable auto-pruning after mutations (default: false preserves history)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_prune: Option<bool>,
+    /// Connection options for `s3://` URIs
+    #[serde(default, skip_serializing_if = "S3Config::is_empty")]
+    pub s3: S3Config,
+    /// How embedding vectors are stored
+    #[serde(default, skip_serializing_if = "EmbeddingPrecision::is_default")]
+    pub embedding_precision: EmbeddingPrecision,
+}
+
+/// Storage precision for embedding vectors.
+///
+/// Lower precisions shrink the store and speed up scans at a small cost in
+/// search accuracy. Vectors are always returned as `f32`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingPrecision {
+    /// 32-bit floats, as produced by the embedding provider
+    #[default]
+    F32,
+    /// 16-bit floats: half the size of `f32`
+    F16,
+    /// 8-bit integers with a per-vector scale: a quarter of the size of `f32`
+    Int8,
+}
+
+impl EmbeddingPrecision {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl std::fmt::Display for EmbeddingPrecision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EmbeddingPrecision::F32 => write!(f, "f32"),
+            EmbeddingPrecision::F16 => write!(f, "f16"),
+            EmbeddingPrecision::Int8 => write!(f, "int8"),
+        }
+    }
+}
+
+impl std::str::FromStr for EmbeddingPrecision {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "f32" => Ok(EmbeddingPrecision::F32),
+            "f16" => Ok(EmbeddingPrecision::F16),
+            "int8" => Ok(EmbeddingPrecision::Int8),
+            _ => Err(anyhow::anyhow!(
+                "Invalid embedding precision: {} (expected f32, f16 or int8)",
+                s
+            )),
+        }
+    }
+}
+
+/// Whether a storage URI points at S3 rather than a local path
+pub fn is_s3_uri(uri: &str) -> bool {
+    uri.starts_with("s3://") || uri.starts_with("s3+ddb://")
+}
+
+/// S3 connection options passed through to the storage backend
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct S3Config {
+    /// AWS region of the bucket
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Custom endpoint for S3-compatible stores such as MinIO
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// Named AWS credentials profile; the AWS SDK reads it from
+    /// `AWS_PROFILE`, which must be set when this is
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// DynamoDB table that coordinates commits from concurrent writers,
+    /// as `dynamodb://<table>` or a bare table name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commit_store: Option<String>,
+}
+
+impl S3Config {
+    /// Whether no option is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// DynamoDB table name of the commit store, if configured
+    pub fn commit_table(&self) -> Option<&str> {
+        self.commit_store
+            .as_deref()
+            .map(|store| store.strip_prefix("dynamodb://").unwrap_or(store))
+            .filter(|table| !table.is_empty())
+    }
+
+    /// URI to connect with.
+    ///
+    /// With a commit store, `s3://bucket/path` becomes
+    /// `s3+ddb://bucket/path?ddbTableName=<table>`. Any option other than the
+    /// profile requires an S3 URI.
+    pub fn connect_uri(&self, uri: &str) -> anyhow::Result<String> {
+        if !is_s3_uri(uri) {
+            if self.region.is_some() || self.endpoint.is_some() || self.commit_store.is_some() {
+                anyhow::bail!(
+                    "[storage.s3] options require an s3:// storage URI (got '{}')",
+                    uri
+                );
+            }
+            return Ok(uri.to_string());
+        }
+
+        match (self.commit_table(), uri.strip_prefix("s3://")) {
+            (Some(table), Some(rest)) => {
+                let separator = if rest.contains('?') { '&' } else { '?' };
+                Ok(format!(
+                    "s3+ddb://{}{}ddbTableName={}",
+                    rest, separator, table
+                ))
+            },
+            _ => Ok(uri.to_string()),
+        }
+    }
+
+    /// Object store options for the configured region and endpoint
+    pub fn storage_options(&self) -> Vec<(String, String)> {
+        let mut options = Vec::new();
+        if let Some(region) = &self.region {
+            options.push(("aws_region".to_string(), region.clone()));
+        }
+        if let Some(endpoint) = &self.endpoint {
+            options.push(("aws_endpoint".to_string(), endpoint.clone()));
+            if endpoint.starts_with("http://") {
+                options.push(("allow_http".to_string(), "true".to_string()));
+            }
+        }
+        options
+    }
 }
 
 /// Definition of a registered artifact kind
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct KindDefinition {
     pub slug: String,
     pub description: String,
+    /// Top-level content fields stored as queryable columns
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    /// Format used when an add doesn't name one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<ContentFormat>,
+    /// Content scaffold offered on add, with `{{name}}` placeholders to fill in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Guidance for agents on using this kind, added to its MCP tool
+    /// descriptions and to the server instructions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    /// What is embedded for content: `content` (the default), `full` for
+    /// name, labels and content, or a template such as
+    /// `{kind}: {name}\n{content}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embed: Option<String>,
+}
+
+impl KindDefinition {
+    /// Placeholders in this kind's template, in order of first appearance
+    pub fn placeholders(&self) -> Vec<String> {
+        self.template
+            .as_deref()
+            .map(template_placeholders)
+            .unwrap_or_default()
+    }
+}
+
+pub(super) static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([A-Za-z][A-Za-z0-9_.-]*)\s*\}\}").unwrap());
+
+/// Names of the `{{name}}` placeholders in `text`, in order of first appearance
+pub fn template_placeholders(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for cap in PLACEHOLDER.captures_iter(text) {
+        let name = cap[1].to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+/// Type of value a registered label accepts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LabelValueType {
+    /// Any non-empty string
+    #[default]
+    String,
+    /// One of the values listed in the definition
+    Enum,
+    /// `true` or `false`
+    Bool,
+    /// A calendar date in `YYYY-MM-DD` form
+    Date,
+}
+
+impl LabelValueType {
+    fn is_string(&self) -> bool {
+        *self == LabelValueType::String
+    }
+}
+
+impl std::fmt::Display for LabelValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LabelValueType::String => write!(f, "string"),
+            LabelValueType::Enum => write!(f, "enum"),
+            LabelValueType::Bool => write!(f, "bool"),
+            LabelValueType::Date => write!(f, "date"),
+        }
+    }
+}
+
+impl std::str::FromStr for LabelValueType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "string" | "text" => Ok(LabelValueType::String),
+            "enum" => Ok(LabelValueType::Enum),
+            "bool" | "boolean" => Ok(LabelValueType::Bool),
+            "date" => Ok(LabelValueType::Date),
+            _ => Err(anyhow::anyhow!("Invalid label type: {}", s)),
+        }
+    }
+}
+
+/// Error returned when artifact labels don't match the label registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LabelValidationError {
+    /// Keys that are not registered at all.
+    Unregistered { keys: Vec<String> },
+    /// Key is registered, but not for this artifact kind.
+    WrongKind {
+        key: String,
+        kind: String,
+        allowed: Vec<String>,
+    },
+    /// Value does not match the declared type.
+    InvalidValue {
+        key: String,
+        value: String,
+        expected: String,
+    },
+}
+
+impl std::fmt::Display for LabelValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LabelValidationError::Unregistered { keys } => write!(
+                f,
+                "Unregistered label key(s): {}. Register with 'dna label add <key> <description>'.",
+                keys.join(", ")
+            ),
+            LabelValidationError::WrongKind { key, kind, allowed } => write!(
+                f,
+                "Label '{}' does not apply to kind '{}' (allowed kinds: {})",
+                key,
+                kind,
+                allowed.join(", ")
+            ),
+            LabelValidationError::InvalidValue {
+                key,
+                value,
+                expected,
+            } => write!(
+                f,
+                "Invalid value '{}' for label '{}': expected {}",
+                value, key, expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LabelValidationError {}
+
+/// Definition of a registered label key
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabelDefinition {
+    pub key: String,
+    pub description: String,
+    /// Type of value this label accepts
+    #[serde(
+        default,
+        rename = "type",
+        skip_serializing_if = "LabelValueType::is_string"
+    )]
+    pub value_type: LabelValueType,
+    /// Allowed values for enum labels
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<String>,
+    /// Kinds this label applies to (empty means all kinds)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kinds: Vec<String>,
+}
+
+impl LabelDefinition {
+    /// Create an untyped label that applies to every kind
+    pub fn new(key: impl Into<String>, description: impl Into<String>) -> Self {
+        Self {
+            key: key.into(),
+            description: description.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Whether this label may be set on artifacts of the given kind
+    pub fn applies_to(&self, kind: &str) -> bool {
+        self.kinds.is_empty() || self.kinds.iter().any(|k| k == kind)
+    }
+
+    /// Describe the accepted values, e.g. `one of: low, high` or `YYYY-MM-DD`
+    pub fn value_hint(&self) -> String {
+        match self.value_type {
+            LabelValueType::String => "any text".to_string(),
+            LabelValueType::Enum => format!("one of: {}", self.values.join(", ")),
+            LabelValueType::Bool => "true or false".to_string(),
+            LabelValueType::Date => "a date (YYYY-MM-DD)".to_string(),
+        }
+    }
+
+    /// Check a value against the declared type
+    pub fn validate_value(&self, value: &str) -> Result<(), LabelValidationError> {
+        let valid = match self.value_type {
+            LabelValueType::String => true,
+            LabelValueType::Enum => self.values.iter().any(|v| v == value),
+            LabelValueType::Bool => matches!(value, "true" | "false"),
+            LabelValueType::Date => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok(),
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(LabelValidationError::InvalidValue {
+                key: self.key.clone(),
+                value: value.to_string(),
+                expected: self.value_hint(),
+            })
+        }
+    }
+}
+
+/// Validate artifact labels against a set of label definitions.
+///
+/// Passes when no labels are registered. Empty values are skipped, since on
+/// update they mean "remove this label".
+pub fn validate_labels(
+    definitions: &[LabelDefinition],
+    kind: &str,
+    labels: &HashMap<String, String>,
+) -> Result<(), LabelValidationError> {
+    if definitions.is_empty() {
+        return Ok(());
+    }
+
+    let mut entries: Vec<(&String, &String)> =
+        labels.iter().filter(|(_, v)| !v.is_empty()).collect();
+    entries.sort();
+
+    let unregistered: Vec<String> = entries
+        .iter()
+        .filter(|(k, _)| !definitions.iter().any(|d| &d.key == *k))
+        .map(|(k, _)| k.to_string())
+        .collect();
+    if !unregistered.is_empty() {
+        return Err(LabelValidationError::Unregistered { keys: unregistered });
+    }
+
+    for (key, value) in entries {
+        let Some(definition) = definitions.iter().find(|d| &d.key == key) else {
+            continue;
+        };
+        if !definition.applies_to(kind) {
+            return Err(LabelValidationError::WrongKind {
+                key: key.clone(),
+                kind: kind.to_string(),
+                allowed: definition.kinds.clone(),
+            });
+        }
+        definition.validate_value(value)?;
+    }
+
+    Ok(())
+}
+
+/// Configuration for registered label keys
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LabelsConfig {
+    #[serde(default)]
+    pub definitions: Vec<LabelDefinition>,
+}
+
+impl LabelsConfig {
+    /// Check if a label key is registered
+    pub fn has(&self, key: &str) -> bool {
+        self.definitions.iter().any(|d| d.key == key)
+    }
+
+    /// Add a label definition, returning false if already exists
+    pub fn add(&mut self, key: String, description: String) -> bool {
+        self.insert(LabelDefinition::new(key, description))
+    }
+
+    /// Add a fully specified label definition, returning false if already exists
+    pub fn insert(&mut self, definition: LabelDefinition) -> bool {
+        if self.has(&definition.key) {
+            return false;
+        }
+        self.definitions.push(definition);
+        true
+    }
+
+    /// Remove a label definition, returning true if it existed
+    pub fn remove(&mut self, key: &str) -> bool {
+        let len = self.definitions.len();
+        self.definitions.retain(|d| d.key != key);
+        self.definitions.len() < len
+    }
+
+    /// Get a label definition by key
+    pub fn get(&self, key: &str) -> Option<&LabelDefinition> {
+        self.definitions.iter().find(|d| d.key == key)
+    }
+
+    /// List all registered label keys
+    pub fn keys(&self) -> Vec<&str> {
+        self.definitions.iter().map(|d| d.key.as_str()).collect()
+    }
+
+    /// Labels that may be set on artifacts of the given kind
+    pub fn for_kind(&self, kind: &str) -> Vec<&LabelDefinition> {
+        self.definitions
+            .iter()
+            .filter(|d| d.applies_to(kind))
+            .collect()
+    }
+
+    /// Validate artifact labels for the given kind against this registry
+    pub fn validate(
+        &self,
+        kind: &str,
+        labels: &HashMap<String, String>,
+    ) -> Result<(), LabelValidationError> {
+        validate_labels(&self.definitions, kind, labels)
+    }
+}
+
+/// Configuration for registered artifact kinds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KindsConfig {
+    #[serde(default)]
+    pub definitions: Vec<KindDefinition>,
+}
+
+impl KindsConfig {
+    /// Check if a kind is registered
+    pub fn has(&self, slug: &str) -> bool {
+        self.definitions.iter().any(|d| d.slug == slug)
+    }
+
+    /// Add a kind definition, returning false if already exists
+    pub fn add(&mut self, slug: String, description: String) -> bool {
+        self.insert(KindDefinition {
+            slug,
+            description,
+            ..Default::default()
+        })
+    }
+
+    /// Add a full kind definition, returning false if the slug already exists
+    pub fn insert(&mut self, definition: KindDefinition) -> bool {
+        if self.has(&definition.slug) {
+            return false;
+        }
+        self.definitions.push(definition);
+        true
+    }
+
+    /// Remove a kind definition, returning true if it existed
+    pub fn remove(&mut self, slug: &str) -> bool {
+        let len = self.definitions.len();
+        self.definitions.retain(|d| d.slug != slug);
+        self.definitions.len() < len
+    }
+
+    /// Get a kind definition by slug
+    pub fn get(&self, slug: &str) -> Option<&KindDefinition> {
+        self.definitions.iter().find(|d| d.slug == slug)
+    }
+
+    /// List all registered kind slugs
+    pub fn slugs(&self) -> Vec<&str> {
+        self.definitions.iter().map(|d| d.slug.as_str()).collect()
+    }
+
+    /// Structured fields to extract, keyed by kind slug (kinds without fields omitted)
+    pub fn structured_fields(&self) -> HashMap<String, Vec<String>> {
+        self.definitions
+            .iter()
+            .filter(|d| !d.fields.is_empty())
+            .map(|d| (d.slug.clone(), d.fields.clone()))
+            .collect()
+    }
+
+    /// Content templates, keyed by kind slug (kinds without a template omitted)
+    pub fn templates(&self) -> HashMap<String, String> {
+        self.definitions
+            .iter()
+            .filter_map(|d| Some((d.slug.clone(), d.template.clone()?)))
+            .collect()
+    }
+
+    /// Embedding text templates, keyed by kind slug (kinds embedding their
+    /// content alone, or with an invalid setting, omitted)
+    pub fn embed_templates(&self) -> HashMap<String, String> {
+        self.definitions
+            .iter()
+            .filter_map(|d| {
+                let template = super::embed_text::template(d.embed.as_deref()?).ok()??;
+                Some((d.slug.clone(), template))
+            })
+            .collect()
+    }
+
+    /// Format for new artifacts of `slug` when none is given (markdown by default)
+    pub fn default_format(&self, slug: &str) -> ContentFormat {
+        self.get(slug)
+            .and_then(|d| d.format)
+            .unwrap_or(ContentFormat::Markdown)
+    }
+
+    /// How many of `artifacts` each kind has and when one was last updated.
+    ///
+    /// Registered kinds come first, in order, unused ones included; kinds
+    /// only the artifacts use (orphans) follow, by slug.
+    pub fn usage(&self, artifacts: &[Artifact]) -> Vec<KindUsage> {
+        let mut usage: Vec<KindUsage> = self
+            .definitions
+            .iter()
+            .map(|d| KindUsage {
+                slug: d.slug.clone(),
+                registered: true,
+                artifacts: 0,
+                last_used: None,
+            })
+            .collect();
+        let mut orphans: BTreeMap<&str, KindUsage> = BTreeMap::new();
+
+        for artifact in artifacts {
+            let entry = match usage.iter_mut().find(|u| u.slug == artifact.kind) {
+                Some(entry) => entry,
+                None => orphans
+                    .entry(artifact.kind.as_str())
+                    .or_insert_with(|| KindUsage {
+                        slug: artifact.kind.clone(),
+                        registered: false,
+                        artifacts: 0,
+                        last_used: None,
+                    }),
+            };
+            entry.artifacts += 1;
+            if entry.last_used.is_none_or(|at| at < artifact.updated_at) {
+                entry.last_used = Some(artifact.updated_at);
+            }
+        }
+
+        usage.extend(orphans.into_values());
+        usage
+    }
+}
+
+/// Artifacts of one kind, from [`KindsConfig::usage`]
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KindUsage {
+    pub slug: String,
+    /// Whether the kind is in the config; artifacts of unregistered kinds
+    /// are orphans
+    pub registered: bool,
+    pub artifacts: usize,
+    /// When an artifact of the kind was last updated
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// Project configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    pub model: ModelConfig,
+    /// Models whose embeddings are stored alongside `model`'s, to compare
+    /// retrieval or to switch models without a big-bang reindex
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_models: Vec<ModelConfig>,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub kinds: KindsConfig,
+    #[serde(default)]
+    pub labels: LabelsConfig,
+    #[serde(default)]
+    pub ids: IdConfig,
+    #[serde(default, skip_serializing_if = "ScanConfig::is_default")]
+    pub scan: ScanConfig,
+    #[serde(default, skip_serializing_if = "SearchConfig::is_default")]
+    pub search: SearchConfig,
+    #[serde(default, skip_serializing_if = "LimitsConfig::is_default")]
+    pub limits: LimitsConfig,
+    #[serde(default, skip_serializing_if = "BackupConfig::is_default")]
+    pub backup: BackupConfig,
+    #[serde(default, skip_serializing_if = "AttachmentsConfig::is_default")]
+    pub attachments: AttachmentsConfig,
+    #[serde(default, skip_serializing_if = "LanguagesConfig::is_default")]
+    pub languages: LanguagesConfig,
+    #[serde(default, skip_serializing_if = "CompletionConfig::is_default")]
+    pub completion: CompletionConfig,
+    #[serde(default, skip_serializing_if = "DigestConfig::is_default")]
+    pub digest: DigestConfig,
+    #[serde(default, skip_serializing_if = "LintConfig::is_default")]
+    pub lint: LintConfig,
+    #[serde(default, skip_serializing_if = "SigningConfig::is_default")]
+    pub signing: SigningConfig,
+    #[serde(default, skip_serializing_if = "McpConfig::is_default")]
+    pub mcp: McpConfig,
+    #[serde(default, skip_serializing_if = "VariablesConfig::is_default")]
+    pub variables: VariablesConfig,
+    #[serde(default, skip_serializing_if = "OwnersConfig::is_default")]
+    pub owners: OwnersConfig,
+}
+
+impl ProjectConfig {
+    /// Register `template`'s kinds and labels, keeping any already defined.
+    /// Returns how many kinds and labels were added.
+    pub fn apply_template(&mut self, template: &Template) -> (usize, usize) {
+        let kinds = template
+            .kinds
+            .iter()
+            .filter(|kind| {
+                self.kinds
+                    .add(kind.slug.to_string(), kind.description.to_string())
+            })
+            .count();
+        let labels = template
+            .labels
+            .iter()
+            .filter(|label| {
+                self.labels
+                    .add(label.key.to_string(), label.description.to_string())
+            })
+            .count();
+        (kinds, labels)
+    }
+
+    /// Point everything configured for kind `from` at kind `to`.
+    ///
+    /// When `to` is registered, `from`'s definition is dropped and `to`'s
+    /// settings win wherever both kinds have one (a merge); otherwise
+    /// `from`'s definition is renamed. Label scopes, lint rules, search
+    /// boosts and scan overrides follow.
+    pub fn move_kind(&mut self, from: &str, to: &str) {
+        if self.kinds.has(to) {
+            self.kinds.remove(from);
+        } else if let Some(definition) = self.kinds.definitions.iter_mut().find(|d| d.slug == from)
+        {
+            definition.slug = to.to_string();
+        }
+
+        for label in &mut self.labels.definitions {
+            if !label.kinds.iter().any(|k| k == from) {
+                continue;
+            }
+            let mut kinds = Vec::with_capacity(label.kinds.len());
+            for kind in label.kinds.drain(..) {
+                let kind = if kind == from { to.to_string() } else { kind };
+                if !kinds.contains(&kind) {
+                    kinds.push(kind);
+                }
+            }
+            label.kinds = kinds;
+        }
+
+        move_key(&mut self.lint.kinds, from, to);
+        move_key(&mut self.search.boosts, from, to);
+        move_key(&mut self.scan.kinds, from, to);
+    }
+}
+
+/// Move `from`'s entry to `to`, unless `to` already has one
+fn move_key<V>(map: &mut HashMap<String, V>, from: &str, to: &str) {
+    if let Some(value) = map.remove(from) {
+        map.entry(to.to_string()).or_insert(value);
+    }
+}
+
+/// Size and character limits on artifact input, enforced on every write path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LimitsConfig {
+    /// Largest content or context, in bytes
+    #[serde(default = "default_max_content_bytes")]
+    pub max_content_bytes: usize,
+    /// Most metadata (label) entries per artifact
+    #[serde(default = "default_max_metadata_entries")]
+    pub max_metadata_entries: usize,
+    /// Longest metadata key, in bytes
+    #[serde(default = "default_max_metadata_key_length")]
+    pub max_metadata_key_length: usize,
+    /// Longest metadata value, in bytes
+    #[serde(default = "default_max_metadata_value_length")]
+    pub max_metadata_value_length: usize,
+    /// Reject control characters other than newlines and tabs
+    #[serde(default = "default_reject_control_chars")]
+    pub reject_control_chars: bool,
+}
+
+fn default_max_content_bytes() -> usize {
+    256 * 1024
+}
+
+fn default_max_metadata_entries() -> usize {
+    64
+}
+
+fn default_max_metadata_key_length() -> usize {
+    128
+}
+
+fn default_max_metadata_value_length() -> usize {
+    4096
+}
+
+fn default_reject_control_chars() -> bool {
+    true
+}
+
+impl Default for LimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_content_bytes: default_max_content_bytes(),
+            max_metadata_entries: default_max_metadata_entries(),
+            max_metadata_key_length: default_max_metadata_key_length(),
+            max_metadata_value_length: default_max_metadata_value_length(),
+            reject_control_chars: default_reject_control_chars(),
+        }
+    }
+}
+
+impl LimitsConfig {
+    /// Whether every limit is at its default
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Where store snapshots go and how many to keep
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupConfig {
+    /// Local directory or s3://bucket/prefix (default: .dna/backups).
+    /// S3 locations use the `[storage.s3]` region and endpoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// Snapshots to keep; older ones are deleted after each new one (default: all)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep: Option<usize>,
+    /// Hours between snapshots taken by dna-server (default: none)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_hours: Option<u64>,
+}
+
+impl BackupConfig {
+    /// Whether nothing about backups is configured
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Where files attached to artifacts are stored
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttachmentsConfig {
+    /// Local directory or s3://bucket/prefix (default: .dna/attachments).
+    /// S3 locations use the `[storage.s3]` region and endpoint.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+impl AttachmentsConfig {
+    /// Whether nothing about attachments is configured
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Language detection and per-language embedding models
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguagesConfig {
+    /// Label artifacts with the language detected in their content
+    #[serde(default)]
+    pub detect: bool,
+    /// Model whose embeddings are searched for each language, keyed by
+    /// ISO 639-3 code (e.g. `deu = "intfloat/multilingual-e5-small"`). Each
+    /// is the `[model]` or one of the `[[extra_models]]`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, String>,
+    /// Reject content in a language with no entry in `models` when
+    /// `[model]` only knows English
+    #[serde(default)]
+    pub require_multilingual: bool,
+}
+
+impl LanguagesConfig {
+    /// Whether nothing about languages is configured
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Chat model used for digests and answers, through an OpenAI-compatible API
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompletionConfig {
+    /// "openai" or "ollama" (default: none, so no model is called)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// Chat model, e.g. gpt-4o-mini
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// API base URL (default: the provider's)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Environment variable holding the API key (default: OPENAI_API_KEY for openai)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+}
+
+impl CompletionConfig {
+    /// Whether no completion provider is configured
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Signing of artifact content, from `[signing]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigningConfig {
+    /// File holding this writer's ed25519 private key in hex, relative to
+    /// the project root; writes are signed when this or `key_env` is set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+    /// Environment variable holding the private key instead of a file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_env: Option<String>,
+    /// Identity recorded with each signature, e.g. an email address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+    /// Public keys (hex) of signers whose signatures verify, by identity
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub trusted: HashMap<String, String>,
+}
+
+impl SigningConfig {
+    /// Whether nothing is signed or trusted
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// What MCP clients are told about the tools, from `[mcp]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpConfig {
+    /// Guidance sent to clients when they connect, e.g. "always search
+    /// before adding"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
+    /// Descriptions replacing the built-in ones, by tool name, as
+    /// `[mcp.tools]`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tools: HashMap<String, String>,
+}
+
+impl McpConfig {
+    /// Whether clients get the built-in descriptions and no instructions
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Values for `{{name}}` placeholders in content, from `[variables]`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VariablesConfig {
+    /// Files of values (TOML, YAML or JSON) relative to the project root,
+    /// later files winning; nested tables give dotted names like `slo.p99`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// Fail to render or pack content with a placeholder that has no value,
+    /// instead of leaving the placeholder as written
+    #[serde(default)]
+    pub strict: bool,
+    /// Values by name, as `[variables.values]`; these win over the files
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<String, toml::Value>,
+}
+
+impl VariablesConfig {
+    /// Whether no variables are configured
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
-/// Definition of a registered label key
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct LabelDefinition {
-    pub key: String,
-    pub description: String,
+/// People and teams that can own and review artifacts, from `[owners]`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnersConfig {
+    /// Individual people, as `@handle`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub people: Vec<String>,
+    /// Teams, as `@team`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub teams: Vec<String>,
 }
 
-/// Configuration for registered label keys
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-pub struct LabelsConfig {
-    #[serde(default)]
-    pub definitions: Vec<LabelDefinition>,
+/// How `dna digest` summarizes groups of artifacts
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DigestConfig {
+    /// Most points an extractive digest keeps (default: 8)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_points: Option<usize>,
 }
 
-impl LabelsConfig {
-    /// Check if a label key is registered
-    pub fn has(&self, key: &str) -> bool {
-        self.definitions.iter().any(|d| d.key == key)
+impl DigestConfig {
+    /// Whether nothing about digests is configured
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
     }
+}
 
-    /// Add a label definition, returning false if already exists
-    pub fn add(&mut self, key: String, description: String) -> bool {
-        if self.has(&key) {
-            return false;
+/// How serious a lint violation is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl std::fmt::Display for LintSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Info => write!(f, "info"),
+            Self::Warning => write!(f, "warning"),
+            Self::Error => write!(f, "error"),
         }
-        self.definitions.push(LabelDefinition { key, description });
-        true
     }
+}
 
-    /// Remove a label definition, returning true if it existed
-    pub fn remove(&mut self, key: &str) -> bool {
-        let len = self.definitions.len();
-        self.definitions.retain(|d| d.key != key);
-        self.definitions.len() < len
-    }
+/// Content checks `dna lint` applies
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintConfig {
+    /// Rules by kind slug, as `[lint.kinds.<slug>]`; kinds without rules are
+    /// not checked
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub kinds: HashMap<String, LintRules>,
+    /// Terminology checks against the glossary, as `[lint.glossary]`; off
+    /// unless the table is present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub glossary: Option<GlossaryLintConfig>,
+}
 
-    /// Get a label definition by key
-    pub fn get(&self, key: &str) -> Option<&LabelDefinition> {
-        self.definitions.iter().find(|d| d.key == key)
+impl LintConfig {
+    /// Whether no kind has lint rules
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
     }
 
-    /// List all registered label keys
-    pub fn keys(&self) -> Vec<&str> {
-        self.definitions.iter().map(|d| d.key.as_str()).collect()
+    /// Kind holding glossary artifacts, whether or not terminology is checked
+    pub fn glossary_kind(&self) -> &str {
+        self.glossary
+            .as_ref()
+            .and_then(|g| g.kind.as_deref())
+            .unwrap_or(super::glossary::DEFAULT_GLOSSARY_KIND)
     }
 }
 
-/// Configuration for registered artifact kinds
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-pub struct KindsConfig {
+/// Terminology checks against the terms glossary artifacts define
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GlossaryLintConfig {
+    /// Kind holding the glossary (default: glossary)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<String>,
+    /// Kinds to check; empty for every kind but the glossary
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kinds: Vec<String>,
+    /// Acronyms that need no glossary entry, beyond common ones like API
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore: Vec<String>,
+    /// Severity overrides by rule name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub severity: HashMap<String, LintSeverity>,
+}
+
+/// Lint rules for one kind; every check is off unless set
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LintRules {
+    /// Longest content, in characters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// Modal verbs the first sentence must use one of, e.g. `["must", "should"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modal: Vec<String>,
+    /// Vague words content must not use, matched as whole words
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub forbidden_words: Vec<String>,
+    /// Words `--fix` replaces, matched as whole words, e.g. `{ utilize = "use" }`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub replace: HashMap<String, String>,
+    /// Labels every artifact needs, as `key` or `key=default`; `--fix` sets
+    /// the default where one is given
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_labels: Vec<String>,
+    /// Content must include an example: a code block or "e.g.", "example", "Ex:"
     #[serde(default)]
-    pub definitions: Vec<KindDefinition>,
+    pub require_example: bool,
+    /// Flag trailing spaces and runs of blank lines, which `--fix` removes
+    #[serde(default)]
+    pub trim_whitespace: bool,
+    /// Severity overrides by rule name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub severity: HashMap<String, LintSeverity>,
 }
 
-impl KindsConfig {
-    /// Check if a kind is registered
-    pub fn has(&self, slug: &str) -> bool {
-        self.definitions.iter().any(|d| d.slug == slug)
+/// Search ranking defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchConfig {
+    /// Score multipliers by kind slug, applied unless a query overrides them
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub boosts: HashMap<String, f32>,
+    /// Other stores searched alongside this one, as `[[search.federate]]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub federate: Vec<FederatedStoreConfig>,
+}
+
+impl SearchConfig {
+    /// Whether no search defaults are configured
+    pub fn is_default(&self) -> bool {
+        self.boosts.is_empty() && self.federate.is_empty()
     }
+}
 
-    /// Add a kind definition, returning false if already exists
-    pub fn add(&mut self, slug: String, description: String) -> bool {
-        if self.has(&slug) {
-            return false;
+/// A store searched alongside this one: another project on disk, or a
+/// dna-server reached over HTTP. Exactly one of `store` and `url` is set.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FederatedStoreConfig {
+    /// Name results from this store are tagged with
+    pub name: String,
+    /// Project directory, relative to this project, or a store name
+    /// registered with `dna store add`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub store: Option<String>,
+    /// Base URL of a dna-server, such as `https://dna.example.com`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Environment variable holding the dna-server API key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// Score multiplier for this store's results (default: 1)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f32>,
+}
+
+/// What to do when content scanning finds a possible secret or PII
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScanAction {
+    /// Don't scan
+    #[default]
+    Off,
+    /// Store unchanged but log a warning
+    Warn,
+    /// Replace matches with a redaction marker before storing
+    Mask,
+    /// Reject the write
+    Block,
+}
+
+impl std::fmt::Display for ScanAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScanAction::Off => write!(f, "off"),
+            ScanAction::Warn => write!(f, "warn"),
+            ScanAction::Mask => write!(f, "mask"),
+            ScanAction::Block => write!(f, "block"),
         }
-        self.definitions.push(KindDefinition { slug, description });
-        true
     }
+}
 
-    /// Remove a kind definition, returning true if it existed
-    pub fn remove(&mut self, slug: &str) -> bool {
-        let len = self.definitions.len();
-        self.definitions.retain(|d| d.slug != slug);
-        self.definitions.len() < len
-    }
+impl std::str::FromStr for ScanAction {
+    type Err = anyhow::Error;
 
-    /// Get a kind definition by slug
-    pub fn get(&self, slug: &str) -> Option<&KindDefinition> {
-        self.definitions.iter().find(|d| d.slug == slug)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" => Ok(ScanAction::Off),
+            "warn" => Ok(ScanAction::Warn),
+            "mask" => Ok(ScanAction::Mask),
+            "block" => Ok(ScanAction::Block),
+            _ => Err(anyhow::anyhow!("Invalid scan action: {}", s)),
+        }
     }
+}
 
-    /// List all registered kind slugs
-    pub fn slugs(&self) -> Vec<&str> {
-        self.definitions.iter().map(|d| d.slug.as_str()).collect()
-    }
+/// A custom content scanning rule
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanRule {
+    pub name: String,
+    /// Regular expression matched against content and context
+    pub pattern: String,
 }
 
-/// Project configuration
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
-pub struct ProjectConfig {
-    pub model: ModelConfig,
-    #[serde(default)]
-    pub storage: StorageConfig,
-    #[serde(default)]
-    pub kinds: KindsConfig,
+/// Configuration for scanning artifact content on write
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScanConfig {
+    /// Action for kinds without an override
     #[serde(default)]
-    pub labels: LabelsConfig,
+    pub action: ScanAction,
+    /// Per-kind action overrides, keyed by kind slug
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub kinds: HashMap<String, ScanAction>,
+    /// Include the built-in secret and email rules
+    #[serde(default = "default_builtin_rules")]
+    pub builtin_rules: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<ScanRule>,
+    /// Shell command that receives the text on stdin and exits non-zero on a finding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+fn default_builtin_rules() -> bool {
+    true
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        Self {
+            action: ScanAction::default(),
+            kinds: HashMap::new(),
+            builtin_rules: default_builtin_rules(),
+            rules: Vec::new(),
+            command: None,
+        }
+    }
+}
+
+impl ScanConfig {
+    /// Whether this is the default (disabled) configuration
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
 }
 
 /// A kind definition within a template
@@ -769,6 +2433,95 @@ mod tests {
             let id = Artifact::generate_id();
             assert!(id.chars().all(|c| c.is_ascii_alphanumeric()));
         }
+
+        #[test]
+        fn default_config_matches_generate_id() {
+            let config = IdConfig::default();
+            assert!(config.matches(&Artifact::generate_id(), "intent"));
+        }
+
+        #[test]
+        fn kind_prefix_strategy_prepends_kind() {
+            let config = IdConfig {
+                strategy: IdStrategy::KindPrefix,
+                ..Default::default()
+            };
+            let id = Artifact::generate_id_with(&config, "intent");
+            assert!(id.starts_with("int-"), "unexpected id {}", id);
+            assert_eq!(id.len(), 14);
+            assert!(config.matches(&id, "intent"));
+            assert!(!config.matches(&id, "contract"));
+        }
+
+        #[test]
+        fn kind_prefix_skips_hyphens() {
+            assert_eq!(IdConfig::kind_prefix("a-b-c-d"), "abc");
+            assert_eq!(IdConfig::kind_prefix("--"), "art");
+        }
+
+        #[test]
+        fn longer_nanoid_uses_configured_length() {
+            let config = IdConfig {
+                strategy: IdStrategy::Nanoid,
+                length: 16,
+            };
+            let id = config.generate("intent");
+            assert_eq!(id.len(), 16);
+            assert!(config.matches(&id, "intent"));
+            assert!(!IdConfig::default().matches(&id, "intent"));
+        }
+
+        #[test]
+        fn ulid_strategy_is_sortable() {
+            let config = IdConfig {
+                strategy: IdStrategy::Ulid,
+                ..Default::default()
+            };
+            let first = config.generate("intent");
+            std::thread::sleep(std::time::Duration::from_millis(2));
+            let second = config.generate("intent");
+            assert_eq!(first.len(), 26);
+            assert!(first < second);
+            assert_eq!(first, first.to_lowercase());
+            assert!(config.matches(&first, "intent"));
+        }
+
+        #[test]
+        fn strategy_parses_from_str() {
+            assert_eq!(
+                "kind-prefix".parse::<IdStrategy>().unwrap(),
+                IdStrategy::KindPrefix
+            );
+            assert_eq!("ULID".parse::<IdStrategy>().unwrap(), IdStrategy::Ulid);
+            assert!("uuid".parse::<IdStrategy>().is_err());
+        }
+
+        #[test]
+        fn project_config_defaults_to_nanoid() {
+            let config: ProjectConfig = toml::from_str(
+                r#"
+                [model]
+                provider = "local"
+                name = "m"
+                "#,
+            )
+            .unwrap();
+            assert_eq!(config.ids, IdConfig::default());
+
+            let config: ProjectConfig = toml::from_str(
+                r#"
+                [model]
+                provider = "local"
+                name = "m"
+
+                [ids]
+                strategy = "kind-prefix"
+                "#,
+            )
+            .unwrap();
+            assert_eq!(config.ids.strategy, IdStrategy::KindPrefix);
+            assert_eq!(config.ids.length, DEFAULT_ID_LENGTH);
+        }
     }
 
     mod content_format {
@@ -878,6 +2631,62 @@ mod tests {
             assert_eq!(artifact.kind, "custom-kind");
         }
 
+        #[test]
+        fn extract_fields_reads_top_level_yaml_and_json() {
+            let names = vec![
+                "severity".to_string(),
+                "count".to_string(),
+                "tags".to_string(),
+                "missing".to_string(),
+            ];
+            let yaml = Artifact::new(
+                "incident".to_string(),
+                "severity: critical\ncount: 3\ntags: [db, api]\n".to_string(),
+                ContentFormat::Yaml,
+                None,
+                HashMap::new(),
+                "model".to_string(),
+            );
+            let fields = yaml.extract_fields(&names);
+            assert_eq!(fields.get("severity").map(String::as_str), Some("critical"));
+            assert_eq!(fields.get("count").map(String::as_str), Some("3"));
+            assert_eq!(
+                fields.get("tags").map(String::as_str),
+                Some(r#"["db","api"]"#)
+            );
+            assert!(!fields.contains_key("missing"));
+
+            let json = Artifact::new(
+                "incident".to_string(),
+                r#"{"severity": "low"}"#.to_string(),
+                ContentFormat::Json,
+                None,
+                HashMap::new(),
+                "model".to_string(),
+            );
+            assert_eq!(
+                json.extract_fields(&names)
+                    .get("severity")
+                    .map(String::as_str),
+                Some("low")
+            );
+        }
+
+        #[test]
+        fn extract_fields_ignores_unstructured_content() {
+            let artifact = Artifact::new(
+                "incident".to_string(),
+                "severity: critical".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                "model".to_string(),
+            );
+            assert!(artifact
+                .extract_fields(&["severity".to_string()])
+                .is_empty());
+        }
+
         #[test]
         fn file_extension_matches_format() {
             let cases = [
@@ -1022,6 +2831,57 @@ mod tests {
             let config = StorageConfig::default();
             assert!(config.auto_prune.is_none());
         }
+
+        #[test]
+        fn s3_options_parse_from_toml_table() {
+            let config: StorageConfig = toml::from_str(
+                r#"
+                uri = "s3://bucket/dna"
+                [s3]
+                region = "eu-west-1"
+                endpoint = "http://localhost:9000"
+                commit_store = "dynamodb://dna-commits"
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(config.s3.region.as_deref(), Some("eu-west-1"));
+            assert_eq!(config.s3.commit_table(), Some("dna-commits"));
+            assert_eq!(
+                config.s3.storage_options(),
+                vec![
+                    ("aws_region".to_string(), "eu-west-1".to_string()),
+                    (
+                        "aws_endpoint".to_string(),
+                        "http://localhost:9000".to_string()
+                    ),
+                    ("allow_http".to_string(), "true".to_string()),
+                ]
+            );
+        }
+
+        #[test]
+        fn commit_store_rewrites_uri_for_dynamodb() {
+            let s3 = S3Config {
+                commit_store: Some("dna-commits".to_string()),
+                ..Default::default()
+            };
+            assert_eq!(
+                s3.connect_uri("s3://bucket/dna").unwrap(),
+                "s3+ddb://bucket/dna?ddbTableName=dna-commits"
+            );
+            assert!(s3.connect_uri("/tmp/local.lance").is_err());
+            assert_eq!(
+                S3Config::default().connect_uri("/tmp/local.lance").unwrap(),
+                "/tmp/local.lance"
+            );
+        }
+
+        #[test]
+        fn empty_s3_section_is_not_serialized() {
+            let toml = toml::to_string(&StorageConfig::default()).unwrap();
+            assert!(!toml.contains("s3"));
+        }
     }
 
     mod search_filters {
@@ -1152,6 +3012,52 @@ mod tests {
         }
     }
 
+    mod kind_templates {
+        use super::*;
+
+        #[test]
+        fn placeholders_are_listed_once_in_order() {
+            let names = template_placeholders(
+                "Given {{ context }}\nWhen {{action}}\nThen {{outcome}} ({{action}})\n{{}} {{1x}}",
+            );
+            assert_eq!(names, vec!["context", "action", "outcome"]);
+        }
+
+        #[test]
+        fn kind_format_and_template_default_when_unset() {
+            let mut kinds = KindsConfig::default();
+            kinds.add("intent".to_string(), "Intents".to_string());
+            assert!(kinds.insert(KindDefinition {
+                slug: "evaluation".to_string(),
+                description: "Evaluations".to_string(),
+                format: Some(ContentFormat::Yaml),
+                template: Some("given: {{given}}".to_string()),
+                ..Default::default()
+            }));
+
+            assert_eq!(kinds.default_format("evaluation"), ContentFormat::Yaml);
+            assert_eq!(kinds.default_format("intent"), ContentFormat::Markdown);
+            assert_eq!(kinds.default_format("unknown"), ContentFormat::Markdown);
+            assert_eq!(kinds.templates().len(), 1);
+            assert_eq!(
+                kinds.get("evaluation").unwrap().placeholders(),
+                vec!["given"]
+            );
+        }
+
+        #[test]
+        fn unset_format_and_template_are_not_serialized() {
+            let definition = KindDefinition {
+                slug: "intent".to_string(),
+                description: "Intents".to_string(),
+                ..Default::default()
+            };
+            let toml = toml::to_string(&definition).unwrap();
+            assert!(!toml.contains("format"));
+            assert!(!toml.contains("template"));
+        }
+    }
+
     mod model_registry {
         use super::*;
 
@@ -1246,9 +3152,124 @@ mod tests {
         }
     }
 
+    mod kind_usage {
+        use super::*;
+        use chrono::Duration;
+
+        fn artifact(kind: &str, age_days: i64) -> Artifact {
+            let mut artifact = Artifact::new(
+                kind.to_string(),
+                "content".to_string(),
+                ContentFormat::Markdown,
+                None,
+                HashMap::new(),
+                "test".to_string(),
+            );
+            artifact.updated_at = Utc::now() - Duration::days(age_days);
+            artifact
+        }
+
+        #[test]
+        fn counts_registered_kinds_then_orphans() {
+            let mut kinds = KindsConfig::default();
+            kinds.add("intent".to_string(), "Intents".to_string());
+            kinds.add("contract".to_string(), "Contracts".to_string());
+            let artifacts = [
+                artifact("intent", 3),
+                artifact("intent", 1),
+                artifact("zeta", 2),
+                artifact("alpha", 5),
+            ];
+
+            let usage = kinds.usage(&artifacts);
+            let summary: Vec<(&str, bool, usize)> = usage
+                .iter()
+                .map(|u| (u.slug.as_str(), u.registered, u.artifacts))
+                .collect();
+            assert_eq!(
+                summary,
+                [
+                    ("intent", true, 2),
+                    ("contract", true, 0),
+                    ("alpha", false, 1),
+                    ("zeta", false, 1),
+                ]
+            );
+            assert_eq!(usage[0].last_used, Some(artifacts[1].updated_at));
+            assert_eq!(usage[1].last_used, None);
+        }
+    }
+
+    mod move_kind {
+        use super::*;
+
+        fn config() -> ProjectConfig {
+            let mut config = ProjectConfig::default();
+            config.kinds.add("rule".to_string(), "Rules".to_string());
+            config
+                .kinds
+                .add("intent".to_string(), "Intents".to_string());
+            config.labels.definitions.push(LabelDefinition {
+                kinds: vec!["rule".to_string(), "intent".to_string()],
+                ..LabelDefinition::new("owner", "Owner")
+            });
+            config.search.boosts.insert("rule".to_string(), 2.0);
+            config.search.boosts.insert("intent".to_string(), 1.5);
+            config
+                .scan
+                .kinds
+                .insert("rule".to_string(), ScanAction::Mask);
+            config
+        }
+
+        #[test]
+        fn renaming_keeps_the_definition_and_settings() {
+            let mut config = config();
+            config.move_kind("rule", "policy");
+
+            assert_eq!(config.kinds.slugs(), vec!["policy", "intent"]);
+            assert_eq!(config.kinds.get("policy").unwrap().description, "Rules");
+            assert_eq!(config.labels.definitions[0].kinds, vec!["policy", "intent"]);
+            assert_eq!(config.search.boosts.get("policy"), Some(&2.0));
+            assert_eq!(config.scan.kinds.get("policy"), Some(&ScanAction::Mask));
+            assert!(!config.search.boosts.contains_key("rule"));
+        }
+
+        #[test]
+        fn merging_keeps_the_target_settings() {
+            let mut config = config();
+            config.move_kind("rule", "intent");
+
+            assert_eq!(config.kinds.slugs(), vec!["intent"]);
+            assert_eq!(config.labels.definitions[0].kinds, vec!["intent"]);
+            assert_eq!(config.search.boosts.get("intent"), Some(&1.5));
+            assert_eq!(config.scan.kinds.get("intent"), Some(&ScanAction::Mask));
+            assert_eq!(config.search.boosts.len(), 1);
+        }
+    }
+
     mod templates {
         use super::*;
 
+        #[test]
+        fn apply_template_keeps_existing_definitions() {
+            let template = get_template("intent").unwrap();
+            let mut config = ProjectConfig::default();
+            config
+                .kinds
+                .add("intent".to_string(), "Our own intents".to_string());
+
+            let (kinds, labels) = config.apply_template(template);
+            assert_eq!(kinds, template.kinds.len() - 1);
+            assert_eq!(labels, template.labels.len());
+            assert_eq!(
+                config.kinds.get("intent").unwrap().description,
+                "Our own intents"
+            );
+
+            assert_eq!(config.apply_template(template), (0, 0));
+        }
+
         #[test]
         fn get_template_returns_intent() {
             let template = get_template("intent");
@@ -1464,5 +3485,134 @@ mod tests {
             assert!(keys.contains(&"domain"));
             assert!(keys.contains(&"type"));
         }
+
+        fn registry() -> LabelsConfig {
+            let mut config = LabelsConfig::default();
+            config.add("domain".to_string(), "Business domain".to_string());
+            config.insert(LabelDefinition {
+                key: "severity".to_string(),
+                description: "Impact".to_string(),
+                value_type: LabelValueType::Enum,
+                values: vec!["low".to_string(), "high".to_string()],
+                kinds: vec!["incident".to_string()],
+            });
+            config.insert(LabelDefinition {
+                value_type: LabelValueType::Bool,
+                ..LabelDefinition::new("deprecated", "No longer applies")
+            });
+            config.insert(LabelDefinition {
+                value_type: LabelValueType::Date,
+                ..LabelDefinition::new("review", "Next review date")
+            });
+            config
+        }
+
+        fn labels(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        }
+
+        #[test]
+        fn validate_passes_without_definitions() {
+            let config = LabelsConfig::default();
+            assert!(config.validate("intent", &labels(&[("any", "x")])).is_ok());
+        }
+
+        #[test]
+        fn validate_accepts_typed_values() {
+            let config = registry();
+            let input = labels(&[
+                ("domain", "billing"),
+                ("severity", "high"),
+                ("deprecated", "false"),
+                ("review", "2026-01-31"),
+            ]);
+            assert!(config.validate("incident", &input).is_ok());
+        }
+
+        #[test]
+        fn validate_reports_unregistered_keys() {
+            let err = registry()
+                .validate("intent", &labels(&[("zeta", "1"), ("alpha", "2")]))
+                .unwrap_err();
+            assert_eq!(
+                err,
+                LabelValidationError::Unregistered {
+                    keys: vec!["alpha".to_string(), "zeta".to_string()]
+                }
+            );
+            assert!(err.to_string().contains("dna label add"));
+        }
+
+        #[test]
+        fn validate_rejects_label_on_other_kind() {
+            let err = registry()
+                .validate("intent", &labels(&[("severity", "low")]))
+                .unwrap_err();
+            assert!(matches!(err, LabelValidationError::WrongKind { .. }));
+        }
+
+        #[test]
+        fn validate_rejects_bad_values() {
+            let config = registry();
+            for (key, value) in [
+                ("severity", "medium"),
+                ("deprecated", "yes"),
+                ("review", "31/01/2026"),
+            ] {
+                let err = config
+                    .validate("incident", &labels(&[(key, value)]))
+                    .unwrap_err();
+                assert!(
+                    matches!(err, LabelValidationError::InvalidValue { .. }),
+                    "{key}={value}"
+                );
+            }
+        }
+
+        #[test]
+        fn validate_skips_empty_values() {
+            let config = registry();
+            assert!(config
+                .validate("intent", &labels(&[("severity", ""), ("unknown", "")]))
+                .is_ok());
+        }
+
+        #[test]
+        fn for_kind_filters_scoped_labels() {
+            let config = registry();
+            assert_eq!(config.for_kind("intent").len(), 3);
+            assert_eq!(config.for_kind("incident").len(), 4);
+        }
+
+        #[test]
+        fn untyped_definition_serializes_without_extra_fields() {
+            let def = LabelDefinition::new("domain", "Business domain");
+            let toml = toml::to_string(&def).unwrap();
+            assert!(!toml.contains("type"));
+            assert!(!toml.contains("values"));
+
+            let parsed: LabelDefinition = toml::from_str(
+                "key = \"severity\"\ndescription = \"x\"\ntype = \"enum\"\nvalues = [\"low\"]\n",
+            )
+            .unwrap();
+            assert_eq!(parsed.value_type, LabelValueType::Enum);
+            assert_eq!(parsed.values, vec!["low"]);
+        }
+
+        #[test]
+        fn label_type_parses() {
+            assert_eq!(
+                "enum".parse::<LabelValueType>().unwrap(),
+                LabelValueType::Enum
+            );
+            assert_eq!(
+                "boolean".parse::<LabelValueType>().unwrap(),
+                LabelValueType::Bool
+            );
+            assert!("number".parse::<LabelValueType>().is_err());
+        }
     }
 }
@@ -0,0 +1,267 @@
+//! Variables interpolated into artifact content when it is rendered or packed.
+//!
+//! Content can say `{{api_url}}` or `{{slo.p99_ms}}` once and get the value
+//! for the environment at hand, instead of keeping a near-identical artifact
+//! per environment. Values come from `[variables]` in the config and from
+//! variables files; `{{env.NAME}}` reads the environment variable `NAME`.
+//! Stored content keeps its placeholders.
+
+use super::types::{Artifact, VariablesConfig, PLACEHOLDER};
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Prefix of placeholders read from the process environment
+pub const ENV_PREFIX: &str = "env.";
+
+/// Error returned in strict mode when placeholders have no value
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("no value for {}", describe_missing(.0))]
+pub struct UnresolvedVariables(pub Vec<(String, Vec<String>)>);
+
+fn describe_missing(missing: &[(String, Vec<String>)]) -> String {
+    missing
+        .iter()
+        .map(|(id, names)| {
+            let names: Vec<String> = names.iter().map(|n| format!("{{{{{}}}}}", n)).collect();
+            format!("{} in {}", names.join(", "), id)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Values for `{{name}}` placeholders
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    values: HashMap<String, String>,
+    strict: bool,
+}
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The variables configured in `[variables]`, with files read relative
+    /// to `project_root`
+    pub fn from_config(config: &VariablesConfig, project_root: &Path) -> Result<Self> {
+        let mut variables = Self::new().with_strict(config.strict);
+        for file in &config.files {
+            variables.load_file(&project_root.join(file))?;
+        }
+        let values =
+            serde_json::to_value(&config.values).context("Failed to read [variables.values]")?;
+        flatten(None, &values, &mut variables.values)
+            .context("Failed to read [variables.values]")?;
+        Ok(variables)
+    }
+
+    /// Fail on placeholders without a value instead of leaving them as written
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Whether placeholders without a value are an error
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Set `name` to `value`, replacing any earlier value
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Read the values in a TOML, YAML or JSON file, by its extension,
+    /// replacing earlier values of the same names
+    pub fn load_file(&mut self, path: &Path) -> Result<()> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read variables file {}", path.display()))?;
+        let values: serde_json::Value = match path.extension().and_then(|e| e.to_str()) {
+            Some("toml") => toml::from_str(&text)
+                .with_context(|| format!("Failed to parse {}", path.display()))?,
+            Some("yaml" | "yml" | "json") => serde_yaml::from_str(&text)
+                .with_context(|| format!("Failed to parse {}", path.display()))?,
+            _ => anyhow::bail!(
+                "Variables file {} must end in .toml, .yaml, .yml or .json",
+                path.display()
+            ),
+        };
+        flatten(None, &values, &mut self.values)
+            .with_context(|| format!("Failed to read {}", path.display()))
+    }
+
+    /// Value of `name`, from the variables or, for `env.NAME`, the
+    /// environment
+    pub fn get(&self, name: &str) -> Option<String> {
+        if let Some(value) = self.values.get(name) {
+            return Some(value.clone());
+        }
+        std::env::var(name.strip_prefix(ENV_PREFIX)?).ok()
+    }
+
+    /// `text` with each placeholder that has a value replaced by it, and
+    /// the names of those without one, which are left as written
+    pub fn interpolate(&self, text: &str) -> (String, Vec<String>) {
+        let mut missing: Vec<String> = Vec::new();
+        let resolved =
+            PLACEHOLDER.replace_all(text, |cap: &regex::Captures| match self.get(&cap[1]) {
+                Some(value) => value,
+                None => {
+                    if !missing.iter().any(|m| m == &cap[1]) {
+                        missing.push(cap[1].to_string());
+                    }
+                    cap[0].to_string()
+                },
+            });
+        (resolved.into_owned(), missing)
+    }
+
+    /// Interpolate the content of each artifact.
+    ///
+    /// Returns the placeholders left without a value, by artifact ID, and
+    /// logs a warning for each. In strict mode these are an
+    /// [`UnresolvedVariables`] error instead, and no artifact is changed.
+    pub fn apply(
+        &self,
+        artifacts: &mut [Artifact],
+    ) -> Result<Vec<(String, Vec<String>)>, UnresolvedVariables> {
+        let mut resolved = Vec::with_capacity(artifacts.len());
+        let mut missing = Vec::new();
+        for artifact in artifacts.iter() {
+            let (content, names) = self.interpolate(&artifact.content);
+            if !names.is_empty() {
+                missing.push((artifact.id.clone(), names));
+            }
+            resolved.push(content);
+        }
+        if self.strict && !missing.is_empty() {
+            return Err(UnresolvedVariables(missing));
+        }
+        for (id, names) in &missing {
+            tracing::warn!(
+                "No value for {} in {}; left as written",
+                names.join(", "),
+                id
+            );
+        }
+        for (artifact, content) in artifacts.iter_mut().zip(resolved) {
+            artifact.content = content;
+        }
+        Ok(missing)
+    }
+}
+
+/// Add the scalars in `value` to `out`, naming nested ones with dots
+fn flatten(
+    prefix: Option<&str>,
+    value: &serde_json::Value,
+    out: &mut HashMap<String, String>,
+) -> Result<()> {
+    let name = || prefix.unwrap_or_default().to_string();
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                let key = match prefix {
+                    Some(prefix) => format!("{}.{}", prefix, key),
+                    None => key.clone(),
+                };
+                flatten(Some(&key), value, out)?;
+            }
+        },
+        serde_json::Value::String(s) => {
+            out.insert(name(), s.clone());
+        },
+        serde_json::Value::Number(n) => {
+            out.insert(name(), n.to_string());
+        },
+        serde_json::Value::Bool(b) => {
+            out.insert(name(), b.to_string());
+        },
+        serde_json::Value::Null => {
+            out.insert(name(), String::new());
+        },
+        serde_json::Value::Array(_) => {
+            anyhow::bail!(
+                "Variable '{}' is a list; only single values can be used",
+                name()
+            )
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::ContentFormat;
+
+    fn artifact(id: &str, content: &str) -> Artifact {
+        let mut artifact = Artifact::new(
+            "intent".to_string(),
+            content.to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            String::new(),
+        );
+        artifact.id = id.to_string();
+        artifact
+    }
+
+    #[test]
+    fn reads_config_and_files_with_dotted_names() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("prod.toml"),
+            "api_url = \"https://api.example.com\"\n[slo]\np99_ms = 250\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("region.yaml"), "region: eu-west-1\n").unwrap();
+        let config: VariablesConfig = toml::from_str(
+            "files = [\"prod.toml\", \"region.yaml\"]\n\
+             [values]\n\
+             api_url = \"https://api.internal\"\n",
+        )
+        .unwrap();
+
+        let variables = Variables::from_config(&config, dir.path()).unwrap();
+        let (text, missing) = variables
+            .interpolate("{{api_url}} answers in {{ slo.p99_ms }}ms in {{region}} for {{team}}");
+        assert_eq!(
+            text,
+            "https://api.internal answers in 250ms in eu-west-1 for {{team}}"
+        );
+        assert_eq!(missing, vec!["team"]);
+        assert!(!variables.is_strict());
+    }
+
+    #[test]
+    fn strict_mode_fails_without_changing_artifacts() {
+        let mut variables = Variables::new();
+        variables.set("api_url", "https://api.example.com");
+        let mut artifacts = vec![
+            artifact("a1", "Call {{api_url}}"),
+            artifact("a2", "Page {{oncall}} within {{slo}}"),
+        ];
+
+        let err = variables
+            .clone()
+            .with_strict(true)
+            .apply(&mut artifacts)
+            .unwrap_err();
+        assert_eq!(err.to_string(), "no value for {{oncall}}, {{slo}} in a2");
+        assert_eq!(artifacts[0].content, "Call {{api_url}}");
+
+        let missing = variables.apply(&mut artifacts).unwrap();
+        assert_eq!(
+            missing,
+            vec![(
+                "a2".to_string(),
+                vec!["oncall".to_string(), "slo".to_string()]
+            )]
+        );
+        assert_eq!(artifacts[0].content, "Call https://api.example.com");
+        assert_eq!(artifacts[1].content, "Page {{oncall}} within {{slo}}");
+    }
+}
@@ -0,0 +1,336 @@
+//! A single entry point for using DNA as a library.
+//!
+//! [`DnaStore`] loads project configuration, opens and initializes the
+//! store, and creates the embedding provider, then wires up the same services
+//! the CLI and server use. Applications that only need to add, search and
+//! render artifacts don't have to assemble those pieces themselves.
+//!
+//! ```no_run
+//! use dna::services::SearchFilters;
+//! use dna::{DnaStore, NewArtifact};
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let store = DnaStore::open(".").await?;
+//!
+//! store
+//!     .add(NewArtifact::new("intent", "Users sign in with SSO").with_label("team", "auth"))
+//!     .await?;
+//!
+//! let filters = SearchFilters {
+//!     limit: Some(5),
+//!     ..Default::default()
+//! };
+//! for hit in store.search("how do users log in?", filters).await? {
+//!     println!("{:.2} {}", hit.score, hit.artifact.id);
+//! }
+//!
+//! store.render("docs/dna", &[]).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::db::Database;
+use crate::embedding::EmbeddingProvider;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::render::RenderService;
+use crate::services::{
+    slugify_kind, Artifact, ArtifactService, ContentFormat, ProjectConfig, SearchFilters,
+    SearchResult, SearchService, SearchWeights,
+};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Where [`DnaStore::open`] gets its configuration
+#[derive(Debug, Clone)]
+pub enum StoreSource {
+    /// A project directory containing `.dna/config.toml`
+    Project(PathBuf),
+    /// Configuration built in code; relative storage paths resolve against `root`
+    Config {
+        config: Box<ProjectConfig>,
+        root: PathBuf,
+    },
+}
+
+impl From<&str> for StoreSource {
+    fn from(path: &str) -> Self {
+        Self::Project(PathBuf::from(path))
+    }
+}
+
+impl From<&Path> for StoreSource {
+    fn from(path: &Path) -> Self {
+        Self::Project(path.to_path_buf())
+    }
+}
+
+impl From<PathBuf> for StoreSource {
+    fn from(path: PathBuf) -> Self {
+        Self::Project(path)
+    }
+}
+
+impl From<ProjectConfig> for StoreSource {
+    fn from(config: ProjectConfig) -> Self {
+        Self::Config {
+            config: Box::new(config),
+            root: PathBuf::from("."),
+        }
+    }
+}
+
+/// An artifact to add through [`DnaStore::add`]
+#[derive(Debug, Clone)]
+pub struct NewArtifact {
+    pub kind: String,
+    pub content: String,
+    pub format: ContentFormat,
+    pub name: Option<String>,
+    pub labels: HashMap<String, String>,
+    pub context: Option<String>,
+}
+
+impl NewArtifact {
+    /// A markdown artifact with no name, labels or context
+    pub fn new(kind: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            content: content.into(),
+            format: ContentFormat::Markdown,
+            name: None,
+            labels: HashMap::new(),
+            context: None,
+        }
+    }
+
+    pub fn with_format(mut self, format: ContentFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+/// A configured DNA project, ready to add, search and render artifacts
+pub struct DnaStore {
+    config: ProjectConfig,
+    root: PathBuf,
+    artifacts: ArtifactService,
+    search: SearchService,
+}
+
+impl DnaStore {
+    /// Open a project directory or an in-code configuration.
+    ///
+    /// The store is created if it doesn't exist yet and migrated if it was
+    /// written by an older version. Needs the `lance` feature; other builds
+    /// bring their own [`Database`] through [`DnaStore::with_parts`].
+    #[cfg(feature = "lance")]
+    pub async fn open(source: impl Into<StoreSource>) -> Result<Self> {
+        let (config, root) = match source.into() {
+            StoreSource::Project(root) => {
+                let config_service = crate::services::ConfigService::new(&root);
+                if !config_service.exists() {
+                    anyhow::bail!(
+                        "DNA not initialized in {}. Run 'dna init' first.",
+                        root.display()
+                    );
+                }
+                (config_service.load()?, root)
+            },
+            StoreSource::Config { config, root } => (*config, root),
+        };
+
+        let db = config.storage.open(&root).await?;
+        db.init().await?;
+        let embedding = crate::embedding::create_provider(&config.model).await?;
+        let extra_models = crate::embedding::create_extra_providers(&config.extra_models).await;
+        Ok(
+            Self::with_parts(config, root, Arc::new(db), embedding)?
+                .with_extra_models(extra_models),
+        )
+    }
+
+    /// Build a store around an existing database and embedding provider
+    pub fn with_parts(
+        config: ProjectConfig,
+        root: impl Into<PathBuf>,
+        db: Arc<dyn Database>,
+        embedding: Arc<dyn EmbeddingProvider>,
+    ) -> Result<Self> {
+        let root = root.into();
+        let artifacts =
+            ArtifactService::from_config(db.clone(), embedding.clone(), &config, &root)?;
+        let search = SearchService::from_config(db, embedding, &config);
+
+        Ok(Self {
+            config,
+            root,
+            artifacts,
+            search,
+        })
+    }
+
+    /// Also store and search embeddings from these models; see
+    /// [`ArtifactService::with_extra_models`]
+    pub fn with_extra_models(mut self, models: Vec<Arc<dyn EmbeddingProvider>>) -> Self {
+        self.artifacts = self.artifacts.with_extra_models(models.clone());
+        self.search = self.search.with_extra_models(models);
+        self
+    }
+
+    /// Add an artifact, checking its labels against the label registry
+    pub async fn add(&self, new: NewArtifact) -> Result<Artifact> {
+        self.config
+            .labels
+            .validate(&slugify_kind(&new.kind), &new.labels)?;
+        self.artifacts
+            .add(
+                new.kind,
+                new.content,
+                new.format,
+                new.name,
+                new.labels,
+                new.context,
+            )
+            .await
+    }
+
+    /// Get an artifact by ID
+    pub async fn get(&self, id: &str) -> Result<Option<Artifact>> {
+        self.artifacts.get(id).await
+    }
+
+    /// List artifacts matching `filters`
+    pub async fn list(&self, filters: SearchFilters) -> Result<Vec<Artifact>> {
+        self.artifacts.list(filters).await
+    }
+
+    /// Search by meaning, ranked by content similarity and configured kind boosts
+    pub async fn search(&self, query: &str, filters: SearchFilters) -> Result<Vec<SearchResult>> {
+        self.search
+            .search_boosted(query, filters, SearchWeights::default(), &HashMap::new())
+            .await
+    }
+
+    /// Render every active artifact as markdown under `output_dir`, with
+    /// include directives expanded.
+    ///
+    /// Relative paths resolve against the project root. Returns the number
+    /// of artifacts rendered.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn render(&self, output_dir: impl AsRef<Path>, group_by: &[String]) -> Result<usize> {
+        let mut artifacts = self.artifacts.list(SearchFilters::default()).await?;
+        crate::services::expand_includes(&self.artifacts, &mut artifacts).await?;
+        RenderService::new(self.root.join(output_dir))
+            .render_all(&artifacts, group_by)
+            .await?;
+        Ok(artifacts.len())
+    }
+
+    pub fn config(&self) -> &ProjectConfig {
+        &self.config
+    }
+
+    /// The artifact service, for operations the facade doesn't cover
+    pub fn artifacts(&self) -> &ArtifactService {
+        &self.artifacts
+    }
+
+    /// The search service, for weighted search and snippets
+    pub fn search_service(&self) -> &SearchService {
+        &self.search
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::{LabelDefinition, LabelValueType};
+    use crate::testing::{TestDatabase, TestEmbedding};
+
+    fn store(config: ProjectConfig, root: &Path) -> DnaStore {
+        DnaStore::with_parts(
+            config,
+            root,
+            Arc::new(TestDatabase::new()),
+            Arc::new(TestEmbedding),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn adds_searches_and_renders() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(ProjectConfig::default(), dir.path());
+
+        let added = store
+            .add(
+                NewArtifact::new("Intent", "Users sign in with SSO")
+                    .with_name("Login")
+                    .with_label("team", "auth"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(added.kind, "intent");
+        assert_eq!(
+            store.get(&added.id).await.unwrap().unwrap().name,
+            added.name
+        );
+
+        let hits = store
+            .search("sign in", SearchFilters::default())
+            .await
+            .unwrap();
+        assert_eq!(hits[0].artifact.id, added.id);
+
+        assert_eq!(store.render("out", &[]).await.unwrap(), 1);
+        assert!(dir.path().join("out").exists());
+    }
+
+    #[tokio::test]
+    async fn add_rejects_unregistered_label_values() {
+        let mut config = ProjectConfig::default();
+        config.labels.insert(LabelDefinition {
+            value_type: LabelValueType::Enum,
+            values: vec!["low".to_string(), "high".to_string()],
+            ..LabelDefinition::new("severity", "")
+        });
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(config, dir.path());
+
+        let err = store
+            .add(NewArtifact::new("intent", "content").with_label("severity", "urgent"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("severity"), "{err}");
+        assert!(store
+            .list(SearchFilters::default())
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[cfg(feature = "lance")]
+    #[tokio::test]
+    async fn open_requires_an_initialized_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = DnaStore::open(dir.path()).await.err().unwrap();
+        assert!(err.to_string().contains("not initialized"), "{err}");
+    }
+}
@@ -1,13 +1,15 @@
 //! Test utilities for DNA crate
 //!
 //! This module provides reusable test doubles for unit and integration testing.
-//! It includes mock implementations of `EmbeddingProvider` and `Database` traits.
+//! It includes mock implementations of `EmbeddingProvider` and `Database` traits,
+//! assertion helpers in [`assertions`], [proptest] strategies in
+//! [`strategies`] and SQL predicate checks in [`sql`].
 
 use crate::db::{CleanupStats, CompactStats, Database, VersionInfo};
 use crate::embedding::EmbeddingProvider;
 use crate::services::{Artifact, SearchFilters, SearchResult};
 use anyhow::Result;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use std::collections::HashMap;
 use std::sync::Mutex;
 
@@ -45,17 +47,75 @@ impl EmbeddingProvider for TestEmbedding {
 
 /// In-memory database implementation for testing.
 ///
-/// Thread-safe via Mutex, suitable for unit tests.
+/// Thread-safe via Mutex, suitable for unit tests. Every write bumps the
+/// version, and earlier versions can be read back or restored.
 pub struct TestDatabase {
     artifacts: Mutex<HashMap<String, Artifact>>,
+    archived: Mutex<HashMap<String, Artifact>>,
+    /// Active artifacts as of each earlier version (index 0 is version 1)
+    history: Mutex<Vec<HashMap<String, Artifact>>>,
+    /// When each version was committed (index 0 is version 1)
+    committed: Mutex<Vec<DateTime<Utc>>>,
+    /// Versions up to this one were removed by `cleanup_versions`
+    cleaned_up: Mutex<u64>,
 }
 
 impl TestDatabase {
     pub fn new() -> Self {
         Self {
             artifacts: Mutex::new(HashMap::new()),
+            archived: Mutex::new(HashMap::new()),
+            history: Mutex::new(Vec::new()),
+            committed: Mutex::new(vec![Utc::now()]),
+            cleaned_up: Mutex::new(0),
         }
     }
+
+    /// Error for a version `cleanup_versions` removed
+    fn check_kept(&self, version: u64) -> Result<()> {
+        if version <= *self.cleaned_up.lock().unwrap() {
+            anyhow::bail!("Version {} not found", version);
+        }
+        Ok(())
+    }
+
+    /// Record the current state before a write creates a new version
+    fn snapshot(&self) {
+        let current = self.artifacts.lock().unwrap().clone();
+        self.history.lock().unwrap().push(current);
+        self.committed.lock().unwrap().push(Utc::now());
+    }
+
+    fn visible(&self, filters: &SearchFilters) -> Vec<Artifact> {
+        let mut all: Vec<_> = self.artifacts.lock().unwrap().values().cloned().collect();
+        if filters.include_archived {
+            all.extend(self.archived.lock().unwrap().values().cloned());
+        }
+        all.retain(|a| {
+            filters
+                .metadata
+                .iter()
+                .all(|(key, value)| a.metadata.get(key) == Some(value))
+                && filters
+                    .fields
+                    .iter()
+                    .all(|(name, value)| a.fields.get(name) == Some(value))
+                && filters
+                    .namespace
+                    .as_ref()
+                    .is_none_or(|ns| a.namespace.as_ref() == Some(ns))
+                && filters
+                    .owner
+                    .as_ref()
+                    .is_none_or(|owner| a.owner.as_ref() == Some(owner))
+                && filters
+                    .reviewer
+                    .as_ref()
+                    .is_none_or(|reviewer| a.reviewers.contains(reviewer))
+                && !filters.excludes(a)
+        });
+        all
+    }
 }
 
 impl Default for TestDatabase {
@@ -67,6 +127,7 @@ impl Default for TestDatabase {
 #[async_trait::async_trait]
 impl Database for TestDatabase {
     async fn insert(&self, artifact: &Artifact) -> Result<()> {
+        self.snapshot();
         self.artifacts
             .lock()
             .unwrap()
@@ -79,6 +140,7 @@ impl Database for TestDatabase {
     }
 
     async fn update(&self, artifact: &Artifact) -> Result<()> {
+        self.snapshot();
         self.artifacts
             .lock()
             .unwrap()
@@ -86,12 +148,27 @@ impl Database for TestDatabase {
         Ok(())
     }
 
+    async fn rename_kind(&self, from: &str, to: &str) -> Result<usize> {
+        self.snapshot();
+        let mut renamed = 0;
+        for table in [&self.artifacts, &self.archived] {
+            for artifact in table.lock().unwrap().values_mut() {
+                if artifact.kind == from {
+                    artifact.kind = to.to_string();
+                    renamed += 1;
+                }
+            }
+        }
+        Ok(renamed)
+    }
+
     async fn delete(&self, id: &str) -> Result<bool> {
+        self.snapshot();
         Ok(self.artifacts.lock().unwrap().remove(id).is_some())
     }
 
     async fn list(&self, filters: SearchFilters) -> Result<Vec<Artifact>> {
-        let all: Vec<_> = self.artifacts.lock().unwrap().values().cloned().collect();
+        let all = self.visible(&filters);
         Ok(all
             .into_iter()
             .filter(|a| filters.kind.as_ref().is_none_or(|k| a.kind == *k))
@@ -106,7 +183,7 @@ impl Database for TestDatabase {
         _query_embedding: &[f32],
         filters: SearchFilters,
     ) -> Result<Vec<SearchResult>> {
-        let all: Vec<_> = self.artifacts.lock().unwrap().values().cloned().collect();
+        let all = self.visible(&filters);
         Ok(all
             .into_iter()
             .filter(|a| filters.kind.as_ref().is_none_or(|k| a.kind == *k))
@@ -118,20 +195,79 @@ impl Database for TestDatabase {
             .collect())
     }
 
+    async fn search_context(
+        &self,
+        _query_embedding: &[f32],
+        filters: SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
+        let all = self.visible(&filters);
+        Ok(all
+            .into_iter()
+            .filter(|a| a.context.is_some() || a.attachment_text.is_some())
+            .filter(|a| filters.kind.as_ref().is_none_or(|k| a.kind == *k))
+            .take(filters.limit.unwrap_or(usize::MAX))
+            .map(|a| SearchResult {
+                artifact: a,
+                score: 0.75,
+            })
+            .collect())
+    }
+
+    async fn search_model(
+        &self,
+        model: &str,
+        _query_embedding: &[f32],
+        filters: SearchFilters,
+    ) -> Result<Vec<SearchResult>> {
+        let all = self.visible(&filters);
+        Ok(all
+            .into_iter()
+            .filter(|a| a.model_embeddings.contains_key(model))
+            .filter(|a| filters.kind.as_ref().is_none_or(|k| a.kind == *k))
+            .take(filters.limit.unwrap_or(usize::MAX))
+            .map(|a| SearchResult {
+                artifact: a,
+                score: 0.8,
+            })
+            .collect())
+    }
+
     async fn version(&self) -> Result<u64> {
-        Ok(1)
+        Ok(self.history.lock().unwrap().len() as u64 + 1)
+    }
+
+    async fn get_at_version(&self, id: &str, version: u64) -> Result<Option<Artifact>> {
+        self.check_kept(version)?;
+        let history = self.history.lock().unwrap();
+        match history.get((version as usize).wrapping_sub(1)) {
+            Some(state) => Ok(state.get(id).cloned()),
+            None => Ok(self.artifacts.lock().unwrap().get(id).cloned()),
+        }
     }
 
-    async fn get_at_version(&self, id: &str, _version: u64) -> Result<Option<Artifact>> {
-        // TestDatabase doesn't support versioning, just return current state
-        self.get(id).await
+    async fn list_at_version(&self, version: u64) -> Result<Vec<Artifact>> {
+        self.check_kept(version)?;
+        let history = self.history.lock().unwrap();
+        let state = match history.get((version as usize).wrapping_sub(1)) {
+            Some(state) => state.clone(),
+            None => self.artifacts.lock().unwrap().clone(),
+        };
+        Ok(state.into_values().collect())
     }
 
     async fn list_versions(&self, limit: Option<usize>) -> Result<Vec<VersionInfo>> {
-        let versions = vec![VersionInfo {
-            version: 1,
-            timestamp: Utc::now(),
-        }];
+        let committed = self.committed.lock().unwrap();
+        let cleaned_up = *self.cleaned_up.lock().unwrap();
+        let versions: Vec<VersionInfo> = committed
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, timestamp)| VersionInfo {
+                version: i as u64 + 1,
+                timestamp: *timestamp,
+            })
+            .filter(|info| info.version > cleaned_up)
+            .collect();
         Ok(match limit {
             Some(n) => versions.into_iter().take(n).collect(),
             None => versions,
@@ -145,12 +281,287 @@ impl Database for TestDatabase {
         })
     }
 
-    async fn cleanup_versions(&self, _keep_versions: usize) -> Result<CleanupStats> {
+    async fn cleanup_versions(&self, keep_versions: usize) -> Result<CleanupStats> {
+        let current = self.version().await?;
+        let mut cleaned_up = self.cleaned_up.lock().unwrap();
+        let keep_from = current.saturating_sub(keep_versions.max(1) as u64);
+        let versions_removed = keep_from.saturating_sub(*cleaned_up) as usize;
+        *cleaned_up = (*cleaned_up).max(keep_from);
         Ok(CleanupStats {
-            versions_removed: 0,
+            versions_removed,
             bytes_freed: 0,
         })
     }
+
+    async fn archive(&self, id: &str) -> Result<bool> {
+        if !self.artifacts.lock().unwrap().contains_key(id) {
+            return Ok(false);
+        }
+        self.snapshot();
+        let Some(artifact) = self.artifacts.lock().unwrap().remove(id) else {
+            return Ok(false);
+        };
+        self.archived
+            .lock()
+            .unwrap()
+            .insert(artifact.id.clone(), artifact);
+        Ok(true)
+    }
+
+    async fn restore(&self, version: u64) -> Result<()> {
+        let current = self.version().await?;
+        if version == current {
+            return Ok(());
+        }
+        let state = self
+            .history
+            .lock()
+            .unwrap()
+            .get((version as usize).wrapping_sub(1))
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Version {} not found", version))?;
+        self.snapshot();
+        *self.artifacts.lock().unwrap() = state;
+        Ok(())
+    }
+}
+
+/// Assertion helpers for validating artifacts produced in tests.
+pub mod assertions {
+    use crate::services::{Artifact, IdConfig};
+
+    /// Assert that `id` has the shape produced by the default ID strategy.
+    pub fn assert_valid_id(id: &str) {
+        assert_id_matches(id, &IdConfig::default(), "");
+    }
+
+    /// Assert that `id` could have been generated by `config` for an artifact of `kind`.
+    pub fn assert_id_matches(id: &str, config: &IdConfig, kind: &str) {
+        assert!(
+            config.matches(id, kind),
+            "ID '{}' does not match the {} strategy (length {}) for kind '{}'",
+            id,
+            config.strategy,
+            config.length,
+            kind
+        );
+    }
+
+    /// Assert that an artifact's ID was generated by `config` for its kind.
+    pub fn assert_artifact_id(artifact: &Artifact, config: &IdConfig) {
+        assert_id_matches(&artifact.id, config, &artifact.kind);
+    }
+}
+
+/// [proptest] strategies for artifacts and filters with adversarial values.
+///
+/// Text mixes arbitrary unicode with characters that mean something to SQL,
+/// `LIKE` patterns, JSON or YAML, so code that interpolates or parses it is
+/// exercised around quoting and escaping.
+pub mod strategies {
+    use crate::services::{
+        Artifact, ContentFormat, SearchFilters, KIND_SLUG_MAX_LENGTH, KIND_SLUG_MIN_LENGTH,
+        RESERVED_KIND_SLUGS,
+    };
+    use chrono::{TimeZone, Utc};
+    use proptest::collection::{hash_map, vec};
+    use proptest::prelude::*;
+    use std::collections::HashMap;
+
+    /// Characters with a meaning in SQL, `LIKE`, JSON or YAML, plus some
+    /// multi-byte and invisible ones
+    const SPECIAL: &[char] = &[
+        '\'', '"', '\\', '%', '_', ':', '-', '#', '*', '&', '!', '|', '>', '?', '{', '}', '[', ']',
+        ',', ' ', '\n', '\r', '\t', '\0', '\u{7f}', '\u{85}', '\u{a0}', '\u{200b}', '\u{202e}',
+        '\u{feff}', 'é', 'ß', '中', '😀',
+    ];
+
+    /// Text built from [`SPECIAL`] characters, arbitrary unicode or a plain
+    /// identifier
+    pub fn adversarial_text() -> impl Strategy<Value = String> {
+        prop_oneof![
+            vec(prop::sample::select(SPECIAL), 0..12).prop_map(|c| c.into_iter().collect()),
+            any::<String>(),
+            "[a-z][a-z0-9_]{0,15}",
+        ]
+    }
+
+    /// Labels with adversarial keys and values
+    pub fn metadata() -> impl Strategy<Value = HashMap<String, String>> {
+        hash_map(adversarial_text(), adversarial_text(), 0..4)
+    }
+
+    /// Kind slugs that pass `validate_kind_slug`, half of them exactly the
+    /// shortest or longest allowed
+    pub fn kind_slug() -> impl Strategy<Value = String> {
+        prop_oneof![
+            Just(KIND_SLUG_MIN_LENGTH),
+            Just(KIND_SLUG_MAX_LENGTH),
+            KIND_SLUG_MIN_LENGTH..=KIND_SLUG_MAX_LENGTH,
+        ]
+        .prop_flat_map(|len| regex(&format!("[a-z0-9][a-z0-9-]{{{}}}[a-z0-9]", len - 2)))
+        .prop_filter("reserved slug", |slug| {
+            !RESERVED_KIND_SLUGS.contains(&slug.as_str())
+        })
+    }
+
+    /// Kind slugs one character shorter or longer than allowed
+    pub fn out_of_range_kind_slug() -> impl Strategy<Value = String> {
+        prop_oneof![
+            regex(&format!("[a-z]{{{}}}", KIND_SLUG_MIN_LENGTH - 1)),
+            regex(&format!("[a-z]{{{}}}", KIND_SLUG_MAX_LENGTH + 1)),
+        ]
+    }
+
+    fn regex(pattern: &str) -> proptest::string::RegexGeneratorStrategy<String> {
+        proptest::string::string_regex(pattern).expect("valid regex")
+    }
+
+    pub fn content_format() -> impl Strategy<Value = ContentFormat> {
+        prop::sample::select(vec![
+            ContentFormat::Markdown,
+            ContentFormat::Yaml,
+            ContentFormat::Json,
+            ContentFormat::OpenApi,
+            ContentFormat::Text,
+        ])
+    }
+
+    /// Artifacts with a valid kind and adversarial everything else; no
+    /// embeddings are set
+    pub fn artifact() -> impl Strategy<Value = Artifact> {
+        (
+            kind_slug(),
+            any::<String>(),
+            content_format(),
+            proptest::option::of(adversarial_text()),
+            metadata(),
+            proptest::option::of(any::<String>()),
+            proptest::option::of(adversarial_text()),
+        )
+            .prop_map(
+                |(kind, content, format, name, metadata, context, namespace)| {
+                    let mut artifact =
+                        Artifact::new(kind, content, format, name, metadata, "test".to_string());
+                    artifact.context = context;
+                    artifact.namespace = namespace;
+                    artifact
+                },
+            )
+    }
+
+    /// Filters with adversarial values in every field
+    pub fn search_filters() -> impl Strategy<Value = SearchFilters> {
+        let field_name = prop_oneof!["[a-z][a-z0-9_]{0,10}", adversarial_text()];
+        let time = proptest::option::of(
+            (0i64..4_102_444_800_000)
+                .prop_map(|ms| Utc.timestamp_millis_opt(ms).single().expect("in range")),
+        );
+        (
+            (
+                proptest::option::of(adversarial_text()),
+                proptest::option::of(adversarial_text()),
+                metadata(),
+                time.clone(),
+                time,
+            ),
+            (
+                hash_map(field_name, adversarial_text(), 0..3),
+                vec(adversarial_text(), 0..3),
+                proptest::option::of(vec(adversarial_text(), 0..3)),
+                metadata(),
+                vec(adversarial_text(), 0..3),
+            ),
+            (
+                proptest::option::of(adversarial_text()),
+                proptest::option::of(adversarial_text()),
+                any::<bool>(),
+            ),
+        )
+            .prop_map(
+                |(
+                    (kind, namespace, metadata, after, before),
+                    (fields, kind_not, kinds, metadata_not, exclude_ids),
+                    (owner, reviewer, exclude_deprecated),
+                )| SearchFilters {
+                    kind,
+                    namespace,
+                    owner,
+                    reviewer,
+                    metadata,
+                    after,
+                    before,
+                    limit: None,
+                    include_archived: false,
+                    fields,
+                    kind_not,
+                    kinds,
+                    metadata_not,
+                    exclude_ids,
+                    exclude_deprecated,
+                },
+            )
+    }
+}
+
+/// Checks for SQL predicates built by interpolating untrusted values.
+pub mod sql {
+    /// Split a predicate into its text with each string literal replaced by
+    /// `?`, and the literals' values with doubled quotes undone.
+    ///
+    /// Returns `None` if a literal is never closed.
+    pub fn split_literals(predicate: &str) -> Option<(String, Vec<String>)> {
+        let mut skeleton = String::new();
+        let mut values = Vec::new();
+        let mut chars = predicate.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '\'' {
+                skeleton.push(c);
+                continue;
+            }
+            let mut value = String::new();
+            loop {
+                match chars.next()? {
+                    '\'' if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        value.push('\'');
+                    },
+                    '\'' => break,
+                    c => value.push(c),
+                }
+            }
+            skeleton.push('?');
+            values.push(value);
+        }
+        Some((skeleton, values))
+    }
+
+    /// The text a `%text%` `LIKE` pattern matches, with `\` escapes undone.
+    ///
+    /// Returns `None` unless every wildcard inside is escaped.
+    pub fn unescape_contains(pattern: &str) -> Option<String> {
+        let inner = pattern.strip_prefix('%')?.strip_suffix('%')?;
+        let mut text = String::new();
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => text.push(chars.next()?),
+                '%' | '_' => return None,
+                c => text.push(c),
+            }
+        }
+        Some(text)
+    }
+
+    /// Decode the `%,<entry>,%` pattern a label filter matches an entry in
+    /// the middle of the stored labels with, back to its key and value.
+    pub fn unescape_entry(pattern: &str) -> Option<(String, String)> {
+        let entry = unescape_contains(pattern)?;
+        let entry = entry.strip_prefix(',')?.strip_suffix(',')?;
+        let labels: std::collections::HashMap<String, String> =
+            serde_json::from_str(&format!("{{{}}}", entry)).ok()?;
+        labels.into_iter().next()
+    }
 }
 
 #[cfg(test)]
@@ -193,4 +604,83 @@ mod tests {
         assert!(db.delete(&artifact.id).await.unwrap());
         assert!(db.get(&artifact.id).await.unwrap().is_none());
     }
+
+    #[test]
+    fn assertions_accept_ids_for_each_strategy() {
+        use crate::services::{IdConfig, IdGenerator, IdStrategy};
+
+        assertions::assert_valid_id(&Artifact::generate_id());
+        for strategy in [IdStrategy::Nanoid, IdStrategy::KindPrefix, IdStrategy::Ulid] {
+            let config = IdConfig {
+                strategy,
+                ..Default::default()
+            };
+            assertions::assert_id_matches(&config.generate("intent"), &config, "intent");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_database_restore_rolls_back_writes() {
+        let db = TestDatabase::new();
+        let first = Artifact::new(
+            "intent".to_string(),
+            "first".to_string(),
+            ContentFormat::Markdown,
+            None,
+            HashMap::new(),
+            "test".to_string(),
+        );
+        db.insert(&first).await.unwrap();
+        let snapshot = db.version().await.unwrap();
+
+        let second = Artifact {
+            id: "second".to_string(),
+            ..first.clone()
+        };
+        db.insert(&second).await.unwrap();
+        db.delete(&first.id).await.unwrap();
+
+        db.restore(snapshot).await.unwrap();
+        assert!(db.get(&first.id).await.unwrap().is_some());
+        assert!(db.get("second").await.unwrap().is_none());
+        assert!(db
+            .get_at_version("second", snapshot + 1)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[test]
+    #[should_panic(expected = "does not match")]
+    fn assertions_reject_foreign_ids() {
+        assertions::assert_valid_id("INT-0000");
+    }
+
+    #[test]
+    fn split_literals_undoes_doubled_quotes() {
+        assert_eq!(
+            sql::split_literals("kind = 'it''s' AND id IN ('a', '')"),
+            Some((
+                "kind = ? AND id IN (?, ?)".to_string(),
+                vec!["it's".to_string(), "a".to_string(), String::new()]
+            ))
+        );
+        assert_eq!(sql::split_literals("kind = 'open"), None);
+        assert_eq!(
+            sql::unescape_contains(r"%50\%\_off%"),
+            Some("50%_off".to_string())
+        );
+        assert_eq!(sql::unescape_contains("%a_b%"), None);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn kind_slugs_are_valid_up_to_the_length_limits(
+            slug in strategies::kind_slug(),
+            out_of_range in strategies::out_of_range_kind_slug(),
+        ) {
+            proptest::prop_assert!(crate::services::validate_kind_slug(&slug).is_ok());
+            proptest::prop_assert!(crate::services::validate_kind_slug(&out_of_range).is_err());
+        }
+    }
 }